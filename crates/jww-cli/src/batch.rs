@@ -0,0 +1,155 @@
+//! 複数ファイルの一括処理
+//!
+//! 入力のglobパターンを展開し、ファイルごとの処理を複数スレッドで実行する。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::error::{CliError, Result};
+
+/// 展開した入力ファイル
+#[derive(Debug, Clone)]
+pub struct InputFile {
+    pub path: PathBuf,
+    /// パターンの固定部分 (最初のワイルドカードより前のディレクトリ) からの相対パス
+    ///
+    /// 出力ディレクトリにサブディレクトリの構成を再現するために使う。
+    pub relative: PathBuf,
+}
+
+/// パスにglobのワイルドカードが含まれるかどうか
+pub fn is_pattern(path: &str) -> bool {
+    path.contains(['*', '?', '['])
+}
+
+/// 入力の指定 (ファイルパスまたはglobパターン) をファイルの一覧に展開する
+///
+/// パターンに一致するファイルがない場合はエラーにする。
+pub fn expand_inputs(inputs: &[String]) -> Result<Vec<InputFile>> {
+    let mut files = Vec::new();
+    for input in inputs {
+        if !is_pattern(input) {
+            let path = PathBuf::from(input);
+            let relative = path
+                .file_name()
+                .map(PathBuf::from)
+                .unwrap_or_else(|| path.clone());
+            files.push(InputFile { path, relative });
+            continue;
+        }
+        let base = pattern_base(input);
+        let paths = glob::glob(input).map_err(|e| CliError::Pattern {
+            pattern: input.clone(),
+            message: e.to_string(),
+        })?;
+        let before = files.len();
+        for path in paths {
+            let path = path.map_err(|e| CliError::io(e.path().to_path_buf(), e.into()))?;
+            if !path.is_file() {
+                continue;
+            }
+            let relative = path.strip_prefix(&base).unwrap_or(&path).to_path_buf();
+            files.push(InputFile { path, relative });
+        }
+        if files.len() == before {
+            return Err(CliError::Pattern {
+                pattern: input.clone(),
+                message: "no files matched".to_string(),
+            });
+        }
+    }
+    Ok(files)
+}
+
+/// 出力先が重なる入力がないことを確認する
+///
+/// 別のディレクトリにある同じ名前のファイル (`a/x.jww` と `b/x.jww`) を指定すると
+/// `--out-dir` の同じパスに書き出されて一方が失われるため、処理を始める前にエラーにする。
+pub fn check_outputs(
+    files: &[InputFile],
+    output_for: impl Fn(&InputFile) -> PathBuf,
+) -> Result<()> {
+    let mut outputs: HashMap<PathBuf, &Path> = HashMap::new();
+    for file in files {
+        let output = output_for(file);
+        if let Some(previous) = outputs.get(&output) {
+            return Err(CliError::Usage(format!(
+                "{} and {} would both be written to {}",
+                previous.display(),
+                file.path.display(),
+                output.display()
+            )));
+        }
+        outputs.insert(output, &file.path);
+    }
+    Ok(())
+}
+
+/// パターンのうちワイルドカードを含まない先頭のディレクトリ部分
+fn pattern_base(pattern: &str) -> PathBuf {
+    Path::new(pattern)
+        .components()
+        .take_while(|c| !is_pattern(&c.as_os_str().to_string_lossy()))
+        .collect()
+}
+
+/// 既定の並列数 (利用可能なCPU数)
+pub fn default_jobs() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+/// 各ファイルを `jobs` 個のスレッドで処理し、入力と同じ順に結果を返す
+///
+/// `report` は各ファイルの処理が終わるたびに (完了順に) 呼び出される。
+pub fn run_parallel<T, F, R>(
+    files: &[InputFile],
+    jobs: usize,
+    process: F,
+    report: R,
+) -> Vec<Result<T>>
+where
+    T: Send,
+    F: Fn(&InputFile) -> Result<T> + Sync,
+    R: Fn(&InputFile, &Result<T>) + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<T>>>> =
+        Mutex::new((0..files.len()).map(|_| None).collect());
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, files.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(file) = files.get(index) else {
+                    break;
+                };
+                let result = process(file);
+                report(file, &result);
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("every file is processed"))
+        .collect()
+}
+
+/// 一括処理の結果の集計を標準エラー出力に表示し、失敗があればエラーを返す
+pub fn summarize<T>(results: &[Result<T>]) -> Result<()> {
+    let failed = results.iter().filter(|result| result.is_err()).count();
+    eprintln!("{} succeeded, {} failed", results.len() - failed, failed);
+    if failed > 0 {
+        Err(CliError::BatchFailed {
+            failed,
+            total: results.len(),
+        })
+    } else {
+        Ok(())
+    }
+}
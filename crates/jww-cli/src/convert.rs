@@ -0,0 +1,287 @@
+//! `jww convert`: DXF・SVG・JSONへの変換
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use clap::{Args, ValueEnum};
+use jww_dxf::{ConvertOptions, DxfFormat};
+use jww_export::SvgOptions;
+
+use crate::batch::{self, InputFile};
+use crate::error::{CliError, Result};
+use crate::io::{output_path, read_document, read_options, write_output};
+
+/// 変換先の形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Target {
+    Dxf,
+    Svg,
+    Json,
+}
+
+impl Target {
+    fn extension(self) -> &'static str {
+        match self {
+            Target::Dxf => "dxf",
+            Target::Svg => "svg",
+            Target::Json => "json",
+        }
+    }
+
+    /// 出力ファイルの拡張子から変換先を判定する
+    fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        Target::value_variants()
+            .iter()
+            .copied()
+            .find(|target| target.extension() == ext)
+    }
+}
+
+/// DXFの出力形式
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Format {
+    /// Shift-JIS (CP932) のテキストDXF
+    ShiftJis,
+    /// UTF-8のテキストDXF
+    Utf8,
+    /// バイナリDXF
+    Binary,
+}
+
+impl From<Format> for DxfFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::ShiftJis => DxfFormat::ShiftJis,
+            Format::Utf8 => DxfFormat::Utf8,
+            Format::Binary => DxfFormat::Binary,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ConvertArgs {
+    /// 入力するJWWファイルまたはglobパターン (`drawings/**/*.jww` など、`-` で標準入力)
+    #[arg(required = true)]
+    pub inputs: Vec<String>,
+    /// 出力ファイル (入力が1ファイルの場合のみ、`-` で標準出力)
+    ///
+    /// 省略時は入力ファイルの拡張子を変換先の拡張子にしたパス (標準入力の場合は標準出力) に出力する。
+    #[arg(short, long, conflicts_with = "out_dir")]
+    pub output: Option<PathBuf>,
+    /// 出力先ディレクトリ (パターンのワイルドカード以降のサブディレクトリ構成を保つ)
+    #[arg(long, value_name = "DIR")]
+    pub out_dir: Option<PathBuf>,
+    /// 同時に変換するファイル数 (省略時はCPU数)
+    #[arg(short, long, value_name = "N")]
+    pub jobs: Option<usize>,
+    /// 変換先の形式 (省略時は出力ファイルの拡張子から判定し、判定できなければDXF)
+    #[arg(long, value_enum)]
+    pub to: Option<Target>,
+    /// DXFの出力形式
+    ///
+    /// 省略時は `--options` の `encoding` を使用し、それも指定がなければShift-JISとする。
+    #[arg(short, long, value_enum)]
+    pub format: Option<Format>,
+    /// 変換オプションのJSONファイル (DXFは `ConvertOptions`、SVGは `SvgOptions`)
+    #[arg(long, value_name = "FILE")]
+    pub options: Option<PathBuf>,
+    /// JSONをインデントして出力する
+    #[arg(long)]
+    pub pretty: bool,
+    /// 空でないレイヤグループごとにDXFを分けて出力する (`<名前>_<グループ>.dxf`)
+    #[arg(long, conflicts_with = "split_layers")]
+    pub split_groups: bool,
+    /// 空でないレイヤごとにDXFを分けて出力する (`<名前>_<グループ>-<レイヤ>.dxf`)
+    #[arg(long)]
+    pub split_layers: bool,
+}
+
+/// DXFの変換オプションと出力形式を決める
+///
+/// 出力形式は `--format`、オプションファイルの `encoding`、Shift-JISの順に優先する。
+fn read_dxf_options(
+    path: Option<&Path>,
+    format: Option<Format>,
+) -> Result<(ConvertOptions, DxfFormat)> {
+    let Some(path) = path else {
+        let format = format.map_or(DxfFormat::ShiftJis, DxfFormat::from);
+        return Ok((ConvertOptions::default(), format));
+    };
+    let value: serde_json::Value = read_options(Some(path))?;
+    let has_encoding = value.get("encoding").is_some();
+    let options: ConvertOptions =
+        serde_json::from_value(value).map_err(|source| CliError::Options {
+            path: path.to_path_buf(),
+            source,
+        })?;
+    let format = match format {
+        Some(format) => format.into(),
+        None if has_encoding => options.encoding.into(),
+        None => DxfFormat::ShiftJis,
+    };
+    Ok((options, format))
+}
+
+/// 出力ファイルの分割単位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Split {
+    None,
+    Groups,
+    Layers,
+}
+
+/// 変換先と変換オプション
+enum Converter {
+    Dxf(ConvertOptions, DxfFormat, Split),
+    Svg(SvgOptions),
+    Json { pretty: bool },
+}
+
+impl Converter {
+    fn new(target: Target, args: &ConvertArgs) -> Result<Self> {
+        let options = args.options.as_deref();
+        Ok(match target {
+            Target::Dxf => {
+                let split = if args.split_groups {
+                    Split::Groups
+                } else if args.split_layers {
+                    Split::Layers
+                } else {
+                    Split::None
+                };
+                let (options, format) = read_dxf_options(options, args.format)?;
+                Converter::Dxf(options, format, split)
+            }
+            _ if args.split_groups || args.split_layers => {
+                return Err(CliError::Usage(
+                    "--split-groups and --split-layers require DXF output".to_string(),
+                ))
+            }
+            Target::Svg => Converter::Svg(read_options(options)?),
+            Target::Json => Converter::Json {
+                pretty: args.pretty,
+            },
+        })
+    }
+
+    /// 1ファイルを変換する
+    fn convert_file(&self, input: &Path, output: &Path) -> Result<()> {
+        let doc = read_document(input)?;
+        if let Converter::Dxf(options, format, split @ (Split::Groups | Split::Layers)) = self {
+            return write_split(&doc, output, options, *format, *split);
+        }
+        let bytes = match self {
+            Converter::Dxf(options, format, _) => {
+                let dxf_doc = jww_dxf::convert_document_with_options(&doc, options);
+                jww_dxf::to_bytes(&dxf_doc, *format)
+            }
+            Converter::Svg(options) => jww_export::to_svg_with_options(&doc, options).into_bytes(),
+            Converter::Json { pretty } => {
+                let mut json = if *pretty {
+                    serde_json::to_vec_pretty(&doc)?
+                } else {
+                    serde_json::to_vec(&doc)?
+                };
+                json.push(b'\n');
+                json
+            }
+        };
+        write_output(output, &bytes)
+    }
+}
+
+/// レイヤグループ・レイヤごとにDXFを出力する
+///
+/// 出力ファイル名は `output` のファイル名の後に `_<グループ>` または
+/// `_<グループ>-<レイヤ>` (16進) を付けたもの。変換オプションでレイヤが
+/// 指定されている場合は、そのレイヤのみを対象とする。
+fn write_split(
+    doc: &jww_core::Document,
+    output: &Path,
+    options: &ConvertOptions,
+    format: DxfFormat,
+    split: Split,
+) -> Result<()> {
+    if output == Path::new("-") {
+        return Err(CliError::Usage(
+            "split output cannot be written to standard output".to_string(),
+        ));
+    }
+    let index = doc.entities_by_layer();
+    let layers = index
+        .keys()
+        .copied()
+        .filter(|layer| options.filter.layers.is_empty() || options.filter.layers.contains(layer));
+    let mut parts: BTreeMap<String, Vec<(u16, u16)>> = BTreeMap::new();
+    for (group, layer) in layers {
+        let suffix = match split {
+            Split::Layers => format!("{:X}-{:X}", group, layer),
+            _ => format!("{:X}", group),
+        };
+        parts.entry(suffix).or_default().push((group, layer));
+    }
+
+    let stem = output
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = output
+        .extension()
+        .map(|ext| ext.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "dxf".to_string());
+    for (suffix, layers) in parts {
+        let mut options = options.clone();
+        options.filter.layers = layers;
+        let dxf_doc = jww_dxf::convert_document_with_options(doc, &options);
+        let path = output.with_file_name(format!("{}_{}.{}", stem, suffix, extension));
+        write_output(&path, &jww_dxf::to_bytes(&dxf_doc, format))?;
+    }
+    Ok(())
+}
+
+pub fn run(args: &ConvertArgs) -> Result<()> {
+    let target = args
+        .to
+        .or_else(|| args.output.as_deref().and_then(Target::from_path))
+        .unwrap_or(Target::Dxf);
+    let converter = Converter::new(target, args)?;
+    let extension = target.extension();
+
+    let single = args.inputs.len() == 1 && !batch::is_pattern(&args.inputs[0]);
+    if single && args.out_dir.is_none() {
+        let input = Path::new(&args.inputs[0]);
+        let output = output_path(input, args.output.as_deref(), extension);
+        return converter.convert_file(input, &output);
+    }
+    if args.output.is_some() {
+        return Err(CliError::Usage(
+            "--output cannot be used with multiple inputs; use --out-dir".to_string(),
+        ));
+    }
+
+    let files = batch::expand_inputs(&args.inputs)?;
+    let jobs = args.jobs.unwrap_or_else(batch::default_jobs);
+    let output_for = |file: &InputFile| match &args.out_dir {
+        Some(dir) => dir.join(&file.relative).with_extension(extension),
+        None => file.path.with_extension(extension),
+    };
+    batch::check_outputs(&files, output_for)?;
+    let results = batch::run_parallel(
+        &files,
+        jobs,
+        |file| {
+            let output = output_for(file);
+            if let Some(parent) = output.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| CliError::io(parent, e))?;
+            }
+            converter.convert_file(&file.path, &output)?;
+            Ok(output)
+        },
+        |file, result| match result {
+            Ok(output) => eprintln!("ok      {} -> {}", file.path.display(), output.display()),
+            Err(e) => eprintln!("FAILED  {}", e),
+        },
+    );
+    batch::summarize(&results)
+}
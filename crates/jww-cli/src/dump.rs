@@ -0,0 +1,102 @@
+//! `jww dump`: ファイル構造の表示
+
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use clap::Args;
+use jww_core::{Span, SpanKind};
+
+use crate::error::{CliError, Result};
+use crate::io::read_input;
+
+#[derive(Debug, Args)]
+pub struct DumpArgs {
+    /// 入力するJWWファイル (`-` で標準入力)
+    pub input: PathBuf,
+    /// 各範囲のバイト列を16進ダンプで表示する
+    #[arg(long)]
+    pub hex: bool,
+    /// 1つの範囲で16進ダンプを表示する最大バイト数
+    #[arg(long, value_name = "N", default_value_t = 256)]
+    pub hex_limit: usize,
+}
+
+pub fn run(args: &DumpArgs) -> Result<()> {
+    let data = read_input(&args.input)?;
+    let parse_error = |source| CliError::Parse {
+        path: args.input.clone(),
+        source,
+    };
+    let layout = jww_core::layout(&data).map_err(parse_error)?;
+
+    let mut out = String::new();
+    for span in &layout.spans {
+        let _ = writeln!(
+            out,
+            "{:08x}..{:08x} {:>7}  {}",
+            span.range.start,
+            span.range.end,
+            span.range.len(),
+            label(span)
+        );
+        if args.hex {
+            hexdump(
+                &mut out,
+                &data[span.range.clone()],
+                span.range.start,
+                args.hex_limit,
+            );
+        }
+    }
+    print!("{}", out);
+
+    match layout.error {
+        Some(e) => Err(parse_error(e)),
+        None => Ok(()),
+    }
+}
+
+fn label(span: &Span) -> String {
+    match &span.kind {
+        SpanKind::Signature => "signature".to_string(),
+        SpanKind::Version(version) => format!("version {}", version),
+        SpanKind::Memo(memo) => format!("memo {:?}", memo),
+        SpanKind::PaperSize(size) => format!("paper size {}", size),
+        SpanKind::WriteLayerGroup(group) => format!("write layer group {:X}", group),
+        SpanKind::LayerGroup(group) => format!("layer group {:X}", group),
+        SpanKind::Unparsed => "(unparsed)".to_string(),
+        SpanKind::EntityCount(count) => format!("entity count {}", count),
+        SpanKind::ClassDefinition(name) => format!("  class definition {}", name),
+        SpanKind::Entity { index, class } => format!("  entity #{} {}", index, class),
+        SpanKind::NullObject { index } => format!("  entity #{} (null)", index),
+        SpanKind::Trailing => "(after entity list)".to_string(),
+    }
+}
+
+/// 16バイトごとに位置・16進・ASCIIを表示する
+fn hexdump(out: &mut String, bytes: &[u8], offset: usize, limit: usize) {
+    let shown = &bytes[..bytes.len().min(limit)];
+    for (i, chunk) in shown.chunks(16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+        let ascii: String = chunk
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        let _ = writeln!(
+            out,
+            "    {:08x}  {:<47}  {}",
+            offset + i * 16,
+            hex.join(" "),
+            ascii
+        );
+    }
+    if bytes.len() > shown.len() {
+        let _ = writeln!(out, "    ... {} more bytes", bytes.len() - shown.len());
+    }
+}
@@ -0,0 +1,86 @@
+//! CLIのエラー型
+
+use std::io;
+use std::path::PathBuf;
+
+/// コマンド実行時のエラー型
+#[derive(Debug, thiserror::Error)]
+pub enum CliError {
+    /// ファイルの読み書きに失敗した
+    #[error("{}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    /// JWWファイルのパースに失敗した
+    #[error("{}: {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: jww_core::ParseError,
+    },
+
+    /// 画像の出力に失敗した
+    #[error("{}: {source}", path.display())]
+    Render {
+        path: PathBuf,
+        #[source]
+        source: jww_export::PngError,
+    },
+
+    /// JWW以外の形式のファイルが入力された
+    #[error("{}: {format} files are not supported (only JWW)", path.display())]
+    UnsupportedInput { path: PathBuf, format: &'static str },
+
+    /// オプションファイルが不正
+    #[error("{}: invalid options: {source}", path.display())]
+    Options {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    /// 入力のglobパターンが不正、または一致するファイルがない
+    #[error("{pattern}: {message}")]
+    Pattern { pattern: String, message: String },
+
+    /// 引数の組み合わせが不正
+    #[error("{0}")]
+    Usage(String),
+
+    /// 一括処理で失敗したファイルがある
+    #[error("{failed} of {total} files failed")]
+    BatchFailed { failed: usize, total: usize },
+
+    /// 検査で基準以上の問題が見つかった
+    #[error("{failed} of {total} files failed validation")]
+    ValidationFailed { failed: usize, total: usize },
+
+    /// JSONへの変換に失敗した
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl CliError {
+    /// プロセスの終了コード
+    ///
+    /// 検査で問題が見つかった場合は1、ファイルの読み書きやパースなどの失敗は2。
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            CliError::ValidationFailed { .. } => 1,
+            _ => 2,
+        }
+    }
+
+    pub fn io(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        CliError::Io {
+            path: path.into(),
+            source,
+        }
+    }
+}
+
+/// CLIの結果の型エイリアス
+pub type Result<T> = std::result::Result<T, CliError>;
@@ -0,0 +1,133 @@
+//! `jww extract-text`: 文字の抽出
+
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+use jww_core::{Document, Entity};
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::io::{read_document, write_output};
+
+/// 出力形式
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum TextFormat {
+    /// 1行に1件 (座標・レイヤ・文字列をタブ区切り)
+    Text,
+    /// 見出し行付きのCSV
+    Csv,
+    /// JSON配列
+    Json,
+}
+
+#[derive(Debug, Args)]
+pub struct ExtractTextArgs {
+    /// 入力するJWWファイル (`-` で標準入力)
+    pub input: PathBuf,
+    /// 出力先 (省略時は標準出力)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    /// 出力形式
+    #[arg(short, long, value_enum, default_value = "text")]
+    pub format: TextFormat,
+}
+
+/// 抽出した文字1件
+#[derive(Debug, Serialize)]
+struct TextRecord<'a> {
+    /// エンティティの番号 (`Document::entities` の添字)
+    index: usize,
+    x: f64,
+    y: f64,
+    /// 文字角度 (度)
+    angle: f64,
+    /// 文字高さ (用紙上のmm)
+    height: f64,
+    layer_group: u16,
+    layer: u16,
+    layer_name: &'a str,
+    content: &'a str,
+}
+
+pub fn run(args: &ExtractTextArgs) -> Result<()> {
+    let doc = read_document(&args.input)?;
+    let records = collect(&doc);
+    let mut out = match args.format {
+        TextFormat::Json => serde_json::to_string_pretty(&records)?,
+        TextFormat::Csv => to_csv(&records),
+        TextFormat::Text => records
+            .iter()
+            .map(|r| {
+                format!(
+                    "{}\t{}\t{:X}-{:X}\t{}\n",
+                    r.x, r.y, r.layer_group, r.layer, r.content
+                )
+            })
+            .collect(),
+    };
+    if matches!(args.format, TextFormat::Json) {
+        out.push('\n');
+    }
+    let output = args.output.clone().unwrap_or_else(|| PathBuf::from("-"));
+    write_output(&output, out.as_bytes())
+}
+
+/// 図面直下の文字を集める (ブロック内の文字と寸法値は含まない)
+fn collect<'a>(doc: &'a Document) -> Vec<TextRecord<'a>> {
+    doc.entities
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entity)| match entity {
+            Entity::Text(text) => Some((index, text)),
+            _ => None,
+        })
+        .map(|(index, text)| {
+            let base = &text.base;
+            let layer_name = doc
+                .layer_groups
+                .get(base.layer_group as usize)
+                .and_then(|group| group.layers.get(base.layer as usize))
+                .map(|layer| layer.name.as_str())
+                .unwrap_or_default();
+            TextRecord {
+                index,
+                x: text.start.x,
+                y: text.start.y,
+                angle: text.angle.degrees(),
+                height: text.size_y,
+                layer_group: base.layer_group,
+                layer: base.layer,
+                layer_name,
+                content: &text.content,
+            }
+        })
+        .collect()
+}
+
+fn to_csv(records: &[TextRecord]) -> String {
+    let mut out = String::from("index,x,y,angle,height,layer_group,layer,layer_name,content\n");
+    for r in records {
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{}\n",
+            r.index,
+            r.x,
+            r.y,
+            r.angle,
+            r.height,
+            r.layer_group,
+            r.layer,
+            csv_field(r.layer_name),
+            csv_field(r.content)
+        ));
+    }
+    out
+}
+
+/// 区切り文字・引用符・改行を含む値を引用符で囲む
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
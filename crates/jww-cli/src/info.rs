@@ -0,0 +1,47 @@
+//! `jww info`: 図面情報の表示
+
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::error::{CliError, Result};
+use crate::io::read_input;
+
+#[derive(Debug, Args)]
+pub struct InfoArgs {
+    /// 入力するJWWファイル (`-` で標準入力)
+    pub input: PathBuf,
+    /// JSONで出力する
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub fn run(args: &InfoArgs) -> Result<()> {
+    let data = read_input(&args.input)?;
+    let info = jww_core::probe(&data).map_err(|source| CliError::Parse {
+        path: args.input.clone(),
+        source,
+    })?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
+
+    println!("version:       {}", info.version);
+    println!("memo:          {}", info.memo);
+    match info.paper_dimensions() {
+        Some((width, height)) => println!(
+            "paper size:    {} ({} x {} mm)",
+            info.paper_size, width, height
+        ),
+        None => println!("paper size:    {}", info.paper_size),
+    }
+    println!("write layer:   group {:X}", info.write_layer_group);
+    println!("entities:      {}", info.entity_count);
+    println!("layer groups:");
+    for (i, group) in info.layer_groups.iter().enumerate() {
+        println!("  {:X}  1/{:<6} {}", i, group.scale, group.name);
+    }
+    Ok(())
+}
@@ -0,0 +1,116 @@
+//! 入出力のヘルパー
+//!
+//! パスに `-` を指定した場合は標準入力・標準出力を使用する。
+
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::{CliError, Result};
+
+/// ファイル (または標準入力) を読み込む
+pub fn read_input(path: &Path) -> Result<Vec<u8>> {
+    if path == Path::new("-") {
+        let mut data = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut data)
+            .map_err(|e| CliError::io(path, e))?;
+        Ok(data)
+    } else {
+        std::fs::read(path).map_err(|e| CliError::io(path, e))
+    }
+}
+
+/// ファイル (または標準出力) に書き込む
+pub fn write_output(path: &Path, data: &[u8]) -> Result<()> {
+    if path == Path::new("-") {
+        let mut stdout = std::io::stdout().lock();
+        stdout
+            .write_all(data)
+            .and_then(|_| stdout.flush())
+            .map_err(|e| CliError::io(path, e))
+    } else {
+        std::fs::write(path, data).map_err(|e| CliError::io(path, e))
+    }
+}
+
+/// 入力ファイルの形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    /// Jw_cad図面ファイル (.jww)
+    Jww,
+    /// Jw_cad図形ファイル (.jws)
+    Jws,
+    /// DOS版JW_CADの図面ファイル (.jwc)
+    Jwc,
+}
+
+impl InputFormat {
+    fn name(self) -> &'static str {
+        match self {
+            InputFormat::Jww => "JWW",
+            InputFormat::Jws => "JWS",
+            InputFormat::Jwc => "JWC",
+        }
+    }
+}
+
+/// 先頭のシグネチャから入力形式を判定する
+///
+/// JWCにはシグネチャがないため、シグネチャが不明で拡張子が `.jwc` の場合にJWCとみなす。
+pub fn detect_format(path: &Path, data: &[u8]) -> Option<InputFormat> {
+    if data.starts_with(b"JwwData.") {
+        Some(InputFormat::Jww)
+    } else if data.starts_with(b"JwsData.") {
+        Some(InputFormat::Jws)
+    } else if path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("jwc"))
+    {
+        Some(InputFormat::Jwc)
+    } else {
+        None
+    }
+}
+
+/// JWWファイルを読み込んでパースする
+///
+/// 入力形式を判定し、JWW以外の形式の場合はその旨のエラーにする。
+pub fn read_document(path: &Path) -> Result<jww_core::Document<'static>> {
+    let data = read_input(path)?;
+    match detect_format(path, &data) {
+        Some(InputFormat::Jww) | None => {}
+        Some(format) => {
+            return Err(CliError::UnsupportedInput {
+                path: path.to_path_buf(),
+                format: format.name(),
+            })
+        }
+    }
+    jww_core::parse(&data)
+        .map(jww_core::Document::into_owned)
+        .map_err(|source| CliError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+/// JSONファイルからオプションを読み込む (指定がない場合は既定値)
+pub fn read_options<T: serde::de::DeserializeOwned + Default>(path: Option<&Path>) -> Result<T> {
+    let Some(path) = path else {
+        return Ok(T::default());
+    };
+    let data = read_input(path)?;
+    serde_json::from_slice(&data).map_err(|source| CliError::Options {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// 出力先を決める (指定がなければ入力ファイルの拡張子を変えたパス)
+pub fn output_path(input: &Path, output: Option<&Path>, extension: &str) -> PathBuf {
+    match output {
+        Some(output) => output.to_path_buf(),
+        None if input == Path::new("-") => PathBuf::from("-"),
+        None => input.with_extension(extension),
+    }
+}
@@ -0,0 +1,32 @@
+//! `jww json`: ドキュメントのJSON出力
+
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::error::Result;
+use crate::io::{read_document, write_output};
+
+#[derive(Debug, Args)]
+pub struct JsonArgs {
+    /// 入力するJWWファイル (`-` で標準入力)
+    pub input: PathBuf,
+    /// 出力するJSONファイル (省略時は標準出力)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    /// インデントして出力する
+    #[arg(long)]
+    pub pretty: bool,
+}
+
+pub fn run(args: &JsonArgs) -> Result<()> {
+    let doc = read_document(&args.input)?;
+    let mut json = if args.pretty {
+        serde_json::to_vec_pretty(&doc)?
+    } else {
+        serde_json::to_vec(&doc)?
+    };
+    json.push(b'\n');
+    let output = args.output.clone().unwrap_or_else(|| PathBuf::from("-"));
+    write_output(&output, &json)
+}
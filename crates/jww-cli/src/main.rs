@@ -0,0 +1,80 @@
+//! JWWファイルのコマンドラインツール
+//!
+//! ```text
+//! jww convert in.jww -o out.dxf
+//! jww convert "drawings/**/*.jww" --out-dir dxf/ -j8
+//! cat in.jww | jww convert - --to svg > out.svg
+//! jww info in.jww
+//! jww stats in.jww --json
+//! jww json in.jww --pretty
+//! jww validate "incoming/*.jww" --fail-on warning
+//! jww extract-text in.jww --format csv
+//! jww dump in.jww --hex
+//! jww render in.jww -o thumb.png --width 1024
+//! ```
+//!
+//! 終了コードは成功時0、`validate` で問題が見つかった場合1、その他の失敗時2。
+
+mod batch;
+mod convert;
+mod dump;
+mod error;
+mod extract_text;
+mod info;
+mod io;
+mod json;
+mod render;
+mod stats;
+mod validate;
+
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+
+#[derive(Debug, Parser)]
+#[command(name = "jww", version, about = "JWW (Jw_cad) ファイルの変換・情報表示")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// DXF・SVG・JSONに変換する
+    Convert(convert::ConvertArgs),
+    /// 図面情報を表示する (エンティティは読み取らない)
+    Info(info::InfoArgs),
+    /// 種別・レイヤごとのエンティティ数や図面範囲を表示する
+    Stats(stats::StatsArgs),
+    /// ドキュメント全体をJSONで出力する
+    Json(json::JsonArgs),
+    /// 図面を検査し、問題点を出力する
+    Validate(validate::ValidateArgs),
+    /// 文字の内容・座標・レイヤを出力する
+    ExtractText(extract_text::ExtractTextArgs),
+    /// ファイル内のヘッダー・クラス定義・エンティティのバイト範囲を表示する
+    Dump(dump::DumpArgs),
+    /// サムネイル画像 (PNG・SVG) を出力する
+    Render(render::RenderArgs),
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match &cli.command {
+        Command::Convert(args) => convert::run(args),
+        Command::Info(args) => info::run(args),
+        Command::Stats(args) => stats::run(args),
+        Command::Json(args) => json::run(args),
+        Command::Validate(args) => validate::run(args),
+        Command::ExtractText(args) => extract_text::run(args),
+        Command::Dump(args) => dump::run(args),
+        Command::Render(args) => render::run(args),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("jww: {}", e);
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! `jww render`: サムネイル画像 (PNG・SVG) の出力
+
+use std::path::{Path, PathBuf};
+
+use clap::{Args, ValueEnum};
+use jww_export::{PngOptions, RasterSize, SvgOptions, ViewBox};
+
+use crate::batch::{self, InputFile};
+use crate::error::{CliError, Result};
+use crate::io::{output_path, read_document, write_output};
+
+/// 画像の形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImageFormat {
+    Png,
+    Svg,
+}
+
+impl ImageFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Svg => "svg",
+        }
+    }
+
+    /// 出力ファイルの拡張子から形式を判定する
+    fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        ImageFormat::value_variants()
+            .iter()
+            .copied()
+            .find(|format| format.extension() == ext)
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct RenderArgs {
+    /// 入力するJWWファイルまたはglobパターン (`-` で標準入力)
+    #[arg(required = true)]
+    pub inputs: Vec<String>,
+    /// 出力ファイル (入力が1ファイルの場合のみ、`-` で標準出力)
+    #[arg(short, long, conflicts_with = "out_dir")]
+    pub output: Option<PathBuf>,
+    /// 出力先ディレクトリ (パターンのワイルドカード以降のサブディレクトリ構成を保つ)
+    #[arg(long, value_name = "DIR")]
+    pub out_dir: Option<PathBuf>,
+    /// 同時に処理するファイル数 (省略時はCPU数)
+    #[arg(short, long, value_name = "N")]
+    pub jobs: Option<usize>,
+    /// 画像の形式 (省略時は出力ファイルの拡張子から判定し、判定できなければPNG)
+    #[arg(long, value_enum)]
+    pub to: Option<ImageFormat>,
+    /// PNGの幅 (ピクセル)
+    #[arg(long, default_value_t = 1024)]
+    pub width: u32,
+    /// PNGの高さ (ピクセル、省略時は図面の縦横比から決める)
+    #[arg(long)]
+    pub height: Option<u32>,
+    /// 図面の範囲ではなく用紙全体を出力する
+    #[arg(long)]
+    pub paper: bool,
+    /// PNGの背景を透明にする
+    #[arg(long)]
+    pub transparent: bool,
+}
+
+/// 形式と出力オプション
+enum Renderer {
+    Png(PngOptions),
+    Svg(SvgOptions),
+}
+
+impl Renderer {
+    fn new(format: ImageFormat, args: &RenderArgs) -> Self {
+        let view_box = if args.paper {
+            ViewBox::Paper
+        } else {
+            ViewBox::Extents
+        };
+        match format {
+            ImageFormat::Png => {
+                let size = match args.height {
+                    Some(height) => RasterSize::Pixels {
+                        width: args.width,
+                        height,
+                    },
+                    None => RasterSize::Width(args.width),
+                };
+                let defaults = PngOptions::default();
+                Renderer::Png(PngOptions {
+                    size,
+                    view_box,
+                    background: if args.transparent {
+                        None
+                    } else {
+                        defaults.background
+                    },
+                    ..defaults
+                })
+            }
+            ImageFormat::Svg => Renderer::Svg(SvgOptions {
+                view_box,
+                ..SvgOptions::default()
+            }),
+        }
+    }
+
+    /// 1ファイルを画像にする
+    fn render_file(&self, input: &Path, output: &Path) -> Result<()> {
+        let doc = read_document(input)?;
+        let bytes = match self {
+            Renderer::Png(options) => {
+                jww_export::to_png(&doc, options).map_err(|source| CliError::Render {
+                    path: input.to_path_buf(),
+                    source,
+                })?
+            }
+            Renderer::Svg(options) => jww_export::to_svg_with_options(&doc, options).into_bytes(),
+        };
+        write_output(output, &bytes)
+    }
+}
+
+pub fn run(args: &RenderArgs) -> Result<()> {
+    let format = args
+        .to
+        .or_else(|| args.output.as_deref().and_then(ImageFormat::from_path))
+        .unwrap_or(ImageFormat::Png);
+    let renderer = Renderer::new(format, args);
+    let extension = format.extension();
+
+    let single = args.inputs.len() == 1 && !batch::is_pattern(&args.inputs[0]);
+    if single && args.out_dir.is_none() {
+        let input = Path::new(&args.inputs[0]);
+        let output = output_path(input, args.output.as_deref(), extension);
+        return renderer.render_file(input, &output);
+    }
+    if args.output.is_some() {
+        return Err(CliError::Usage(
+            "--output cannot be used with multiple inputs; use --out-dir".to_string(),
+        ));
+    }
+
+    let files = batch::expand_inputs(&args.inputs)?;
+    let jobs = args.jobs.unwrap_or_else(batch::default_jobs);
+    let output_for = |file: &InputFile| match &args.out_dir {
+        Some(dir) => dir.join(&file.relative).with_extension(extension),
+        None => file.path.with_extension(extension),
+    };
+    batch::check_outputs(&files, output_for)?;
+    let results = batch::run_parallel(
+        &files,
+        jobs,
+        |file| {
+            let output = output_for(file);
+            if let Some(parent) = output.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| CliError::io(parent, e))?;
+            }
+            renderer.render_file(&file.path, &output)?;
+            Ok(output)
+        },
+        |file, result| match result {
+            Ok(output) => eprintln!("ok      {} -> {}", file.path.display(), output.display()),
+            Err(e) => eprintln!("FAILED  {}", e),
+        },
+    );
+    batch::summarize(&results)
+}
@@ -0,0 +1,49 @@
+//! `jww stats`: 図面の統計情報の表示
+
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::error::Result;
+use crate::io::read_document;
+
+#[derive(Debug, Args)]
+pub struct StatsArgs {
+    /// 入力するJWWファイル (`-` で標準入力)
+    pub input: PathBuf,
+    /// JSONで出力する
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub fn run(args: &StatsArgs) -> Result<()> {
+    let doc = read_document(&args.input)?;
+    let stats = doc.statistics();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    println!("entities:      {}", stats.entity_count);
+    for (type_name, count) in &stats.by_type {
+        println!("  {:<10}  {}", type_name, count);
+    }
+    println!("texts:         {}", stats.text_count);
+    println!("block defs:    {}", stats.block_def_count);
+    match stats.extents {
+        Some(bb) => println!(
+            "extents:       ({}, {}) - ({}, {})",
+            bb.min_x, bb.min_y, bb.max_x, bb.max_y
+        ),
+        None => println!("extents:       -"),
+    }
+    println!("layers:");
+    for layer in &stats.layers {
+        println!(
+            "  {:X}-{:X}  {:<6} {}",
+            layer.layer_group, layer.layer, layer.entity_count, layer.name
+        );
+    }
+    Ok(())
+}
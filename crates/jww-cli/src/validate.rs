@@ -0,0 +1,120 @@
+//! `jww validate`: 図面の検査
+
+use clap::{Args, ValueEnum};
+use jww_core::{Finding, Severity};
+use serde::Serialize;
+
+use crate::batch;
+use crate::error::{CliError, Result};
+use crate::io::read_input;
+
+/// 失敗とみなす重要度
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum FailOn {
+    Info,
+    Warning,
+    Error,
+}
+
+impl From<FailOn> for Severity {
+    fn from(fail_on: FailOn) -> Self {
+        match fail_on {
+            FailOn::Info => Severity::Info,
+            FailOn::Warning => Severity::Warning,
+            FailOn::Error => Severity::Error,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ValidateArgs {
+    /// 検査するJWWファイルまたはglobパターン (`-` で標準入力)
+    #[arg(required = true)]
+    pub inputs: Vec<String>,
+    /// JSONで出力する
+    #[arg(long)]
+    pub json: bool,
+    /// この重要度以上の検査結果があれば終了コード1で終了する
+    #[arg(long, value_enum, default_value = "error")]
+    pub fail_on: FailOn,
+}
+
+/// 1ファイルの検査結果
+#[derive(Debug, Serialize)]
+struct FileReport {
+    file: String,
+    /// パースに成功したかどうか
+    parsed: bool,
+    findings: Vec<Finding>,
+}
+
+pub fn run(args: &ValidateArgs) -> Result<()> {
+    let files = batch::expand_inputs(&args.inputs)?;
+    let mut reports = Vec::with_capacity(files.len());
+    for file in &files {
+        let data = read_input(&file.path)?;
+        let (parsed, findings) = match jww_core::parse(&data) {
+            Ok(doc) => (true, doc.validate()),
+            Err(e) => (
+                false,
+                vec![Finding {
+                    severity: Severity::Error,
+                    code: e.code(),
+                    message: format!("parse error: {}", e),
+                    entity_index: e.entity_index(),
+                }],
+            ),
+        };
+        reports.push(FileReport {
+            file: file.path.display().to_string(),
+            parsed,
+            findings,
+        });
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    } else {
+        for report in &reports {
+            print_report(report);
+        }
+    }
+
+    let threshold = Severity::from(args.fail_on);
+    let failed = reports
+        .iter()
+        .filter(|report| report.findings.iter().any(|f| f.severity >= threshold))
+        .count();
+    if failed > 0 {
+        Err(CliError::ValidationFailed {
+            failed,
+            total: reports.len(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+fn print_report(report: &FileReport) {
+    if report.findings.is_empty() {
+        println!("{}: ok", report.file);
+        return;
+    }
+    for finding in &report.findings {
+        let severity = match finding.severity {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        match finding.entity_index {
+            Some(index) => println!(
+                "{}: {}[{}] entity #{}: {}",
+                report.file, severity, finding.code, index, finding.message
+            ),
+            None => println!(
+                "{}: {}[{}] {}",
+                report.file, severity, finding.code, finding.message
+            ),
+        }
+    }
+}
@@ -0,0 +1,423 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use jww_core::{Angle, Document, Entity, EntityBase, Line, Point2D};
+
+/// テスト用の作業ディレクトリを作成する
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("jww-cli-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// 直線1本のJWWファイルを書き出す
+fn write_sample(path: &Path) {
+    let doc = Document {
+        version: 600,
+        entities: vec![Entity::Line(Line {
+            base: EntityBase::default(),
+            start: Point2D::new(0.0, 0.0),
+            end: Point2D::new(100.0, 50.0),
+        })],
+        ..Default::default()
+    };
+    std::fs::write(path, jww_core::write(&doc).unwrap()).unwrap();
+}
+
+fn jww() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_jww"))
+}
+
+#[test]
+fn test_convert() {
+    let dir = temp_dir("convert");
+    let input = dir.join("sample.jww");
+    write_sample(&input);
+
+    let status = jww().arg("convert").arg(&input).status().unwrap();
+    assert!(status.success());
+    let dxf = std::fs::read_to_string(dir.join("sample.dxf")).unwrap();
+    assert!(dxf.contains("LINE"));
+    assert!(dxf.contains("ANSI_932"));
+
+    let output = dir.join("out.dxf");
+    let status = jww()
+        .args(["convert", "--format", "utf8", "-o"])
+        .arg(&output)
+        .arg(&input)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert!(!std::fs::read_to_string(&output)
+        .unwrap()
+        .contains("ANSI_932"));
+}
+
+#[test]
+fn test_convert_encoding_from_options() {
+    let dir = temp_dir("convert-encoding");
+    let input = dir.join("sample.jww");
+    write_sample(&input);
+    let options = dir.join("options.json");
+    std::fs::write(&options, r#"{"encoding": "Utf8"}"#).unwrap();
+    let output = dir.join("out.dxf");
+
+    // --format を省略した場合はオプションファイルの文字コードを使う
+    let status = jww()
+        .args(["convert", "--options"])
+        .arg(&options)
+        .arg("-o")
+        .arg(&output)
+        .arg(&input)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert!(!std::fs::read_to_string(&output)
+        .unwrap()
+        .contains("ANSI_932"));
+
+    // --format を指定した場合はそちらを優先する
+    let status = jww()
+        .args(["convert", "--format", "shift-jis", "--options"])
+        .arg(&options)
+        .arg("-o")
+        .arg(&output)
+        .arg(&input)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert!(std::fs::read_to_string(&output)
+        .unwrap()
+        .contains("ANSI_932"));
+}
+
+#[test]
+fn test_info_and_json() {
+    let dir = temp_dir("info");
+    let input = dir.join("sample.jww");
+    write_sample(&input);
+
+    let output = jww().arg("info").arg(&input).output().unwrap();
+    assert!(output.status.success());
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(text.contains("version:       600"));
+    assert!(text.contains("entities:      1"));
+
+    let output = jww().arg("json").arg(&input).output().unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["entities"][0]["type"], "Line");
+}
+
+#[test]
+fn test_stats() {
+    let dir = temp_dir("stats");
+    let input = dir.join("sample.jww");
+    write_sample(&input);
+
+    let output = jww().arg("stats").arg(&input).output().unwrap();
+    assert!(output.status.success());
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(text.contains("entities:      1"));
+
+    let output = jww()
+        .args(["stats", "--json"])
+        .arg(&input)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["entity_count"], 1);
+    assert_eq!(json["by_type"]["LINE"], 1);
+    assert_eq!(json["text_count"], 0);
+    assert_eq!(json["extents"]["max_x"], 100.0);
+    assert_eq!(json["layers"][0]["entity_count"], 1);
+}
+
+#[test]
+fn test_invalid_file() {
+    let dir = temp_dir("invalid");
+    let input = dir.join("broken.jww");
+    std::fs::write(&input, b"not a jww file").unwrap();
+
+    let output = jww().arg("info").arg(&input).output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("invalid JWW signature"));
+}
+
+#[test]
+fn test_batch_convert() {
+    let dir = temp_dir("batch");
+    let drawings = dir.join("drawings");
+    std::fs::create_dir_all(drawings.join("a")).unwrap();
+    std::fs::create_dir_all(drawings.join("b")).unwrap();
+    write_sample(&drawings.join("a").join("x.jww"));
+    write_sample(&drawings.join("b").join("y.jww"));
+    std::fs::write(drawings.join("broken.jww"), b"not a jww file").unwrap();
+
+    let pattern = format!("{}/**/*.jww", drawings.display());
+    let out_dir = dir.join("dxf");
+    let output = jww()
+        .args(["convert", &pattern, "-j2", "--out-dir"])
+        .arg(&out_dir)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("2 succeeded, 1 failed"), "{}", stderr);
+    assert!(stderr.contains("broken.jww"));
+    assert!(out_dir.join("a").join("x.dxf").is_file());
+    assert!(out_dir.join("b").join("y.dxf").is_file());
+}
+
+#[test]
+fn test_batch_same_file_names() {
+    let dir = temp_dir("batch-same-names");
+    std::fs::create_dir_all(dir.join("a")).unwrap();
+    std::fs::create_dir_all(dir.join("b")).unwrap();
+    write_sample(&dir.join("a").join("x.jww"));
+    write_sample(&dir.join("b").join("x.jww"));
+
+    // どちらも出力ディレクトリの x.dxf になるため、書き出す前にエラーにする
+    let out_dir = dir.join("dxf");
+    let output = jww()
+        .arg("convert")
+        .arg(dir.join("a").join("x.jww"))
+        .arg(dir.join("b").join("x.jww"))
+        .arg("--out-dir")
+        .arg(&out_dir)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("would both be written to"), "{}", stderr);
+    assert!(!out_dir.join("x.dxf").exists());
+}
+
+#[test]
+fn test_validate() {
+    let dir = temp_dir("validate");
+    let input = dir.join("sample.jww");
+    write_sample(&input);
+    let broken = dir.join("broken.jww");
+    std::fs::write(&broken, b"not a jww file").unwrap();
+
+    let output = jww().arg("validate").arg(&input).output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8(output.stdout).unwrap().contains(": ok"));
+
+    let output = jww()
+        .args(["validate", "--json"])
+        .arg(&input)
+        .arg(&broken)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json[0]["parsed"], true);
+    assert_eq!(json[1]["parsed"], false);
+    assert_eq!(json[1]["findings"][0]["code"], "invalid_signature");
+
+    let output = jww()
+        .arg("validate")
+        .arg(dir.join("missing.jww"))
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_extract_text() {
+    use jww_core::Text;
+
+    let dir = temp_dir("extract-text");
+    let input = dir.join("text.jww");
+    let doc = Document {
+        version: 600,
+        entities: vec![Entity::Text(Text {
+            base: EntityBase {
+                layer: 2,
+                layer_group: 1,
+                ..Default::default()
+            },
+            start: Point2D::new(10.0, 20.0),
+            end: Point2D::new(30.0, 20.0),
+            text_type: 0,
+            size_x: 2.5,
+            size_y: 2.5,
+            spacing: 0.0,
+            angle: Angle::ZERO,
+            font_name: "ＭＳ ゴシック".into(),
+            content: "居間, LDK".into(),
+        })],
+        ..Default::default()
+    };
+    std::fs::write(&input, jww_core::write(&doc).unwrap()).unwrap();
+
+    let output = jww().arg("extract-text").arg(&input).output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "10\t20\t1-2\t居間, LDK\n"
+    );
+
+    let output = jww()
+        .args(["extract-text", "--format", "csv"])
+        .arg(&input)
+        .output()
+        .unwrap();
+    let csv = String::from_utf8(output.stdout).unwrap();
+    assert!(csv.ends_with(",1,2,1-2,\"居間, LDK\"\n"), "{}", csv);
+
+    let output = jww()
+        .args(["extract-text", "-f", "json"])
+        .arg(&input)
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json[0]["content"], "居間, LDK");
+    assert_eq!(json[0]["layer_name"], "1-2");
+}
+
+#[test]
+fn test_convert_pipe() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let dir = temp_dir("pipe");
+    let input = dir.join("sample.jww");
+    write_sample(&input);
+    let data = std::fs::read(&input).unwrap();
+
+    let mut child = jww()
+        .args(["convert", "-", "--to", "json"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(&data).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["version"], 600);
+
+    // 変換先は出力ファイルの拡張子から判定する
+    let svg = dir.join("out.svg");
+    let status = jww()
+        .args(["convert", "-o"])
+        .arg(&svg)
+        .arg(&input)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert!(std::fs::read_to_string(&svg).unwrap().contains("<svg"));
+
+    let jws = dir.join("part.jws");
+    std::fs::write(&jws, b"JwsData.\x00\x00\x00\x00").unwrap();
+    let output = jww().arg("convert").arg(&jws).output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("JWS files are not supported"));
+}
+
+#[test]
+fn test_dump() {
+    let dir = temp_dir("dump");
+    let input = dir.join("sample.jww");
+    write_sample(&input);
+
+    let output = jww().args(["dump", "--hex"]).arg(&input).output().unwrap();
+    assert!(output.status.success());
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(text.starts_with("00000000..00000008       8  signature\n"));
+    assert!(text.contains("version 600"));
+    assert!(text.contains("class definition CDataSen"));
+    assert!(text.contains("entity #0 CDataSen"));
+    assert!(text.contains("JwwData."));
+
+    let data = std::fs::read(&input).unwrap();
+    let truncated = dir.join("truncated.jww");
+    std::fs::write(&truncated, &data[..data.len() - 4]).unwrap();
+    let output = jww().arg("dump").arg(&truncated).output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("entity count 1"));
+}
+
+#[test]
+fn test_split_groups() {
+    let dir = temp_dir("split");
+    let input = dir.join("plan.jww");
+    let line = |layer_group: u16, layer: u16| {
+        Entity::Line(Line {
+            base: EntityBase {
+                layer,
+                layer_group,
+                ..Default::default()
+            },
+            start: Point2D::new(0.0, 0.0),
+            end: Point2D::new(10.0, 0.0),
+        })
+    };
+    let doc = Document {
+        version: 600,
+        entities: vec![line(0, 0), line(0, 1), line(10, 2)],
+        ..Default::default()
+    };
+    std::fs::write(&input, jww_core::write(&doc).unwrap()).unwrap();
+
+    let status = jww()
+        .args(["convert", "--split-groups"])
+        .arg(&input)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert!(dir.join("plan_0.dxf").is_file());
+    assert!(dir.join("plan_A.dxf").is_file());
+    assert!(!dir.join("plan_1.dxf").exists());
+
+    let status = jww()
+        .args(["convert", "--split-layers"])
+        .arg(&input)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    for name in ["plan_0-0.dxf", "plan_0-1.dxf", "plan_A-2.dxf"] {
+        assert!(dir.join(name).is_file(), "{}", name);
+    }
+}
+
+#[test]
+fn test_render() {
+    let dir = temp_dir("render");
+    let input = dir.join("sample.jww");
+    write_sample(&input);
+
+    let thumb = dir.join("thumb.png");
+    let output = jww()
+        .arg("render")
+        .arg(&input)
+        .arg("-o")
+        .arg(&thumb)
+        .args(["--width", "320"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let png = std::fs::read(&thumb).unwrap();
+    assert!(png.starts_with(b"\x89PNG"));
+    // IHDRの幅
+    assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 320);
+
+    let output = jww()
+        .arg("render")
+        .arg(&input)
+        .args(["--to", "svg", "-o", "-"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout).unwrap().contains("<svg"));
+}
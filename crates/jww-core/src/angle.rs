@@ -0,0 +1,139 @@
+//! 角度の型
+
+use std::ops::{Add, Neg, Sub};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// 角度
+///
+/// JWWファイルでは円弧・点・ブロックの角度はラジアン、文字の角度は度で保存されている。
+/// 値は作成時の単位のまま保持し、[`Angle::radians`]・[`Angle::degrees`] で必要な単位に
+/// 変換して取り出す。作成時と同じ単位で取り出す場合は誤差を生じない。
+///
+/// シリアライズ時はラジアンの数値になる。度の数値にする場合は
+/// `#[serde(with = "jww_core::angle::degrees")]` を指定する。
+#[derive(Debug, Clone, Copy)]
+pub struct Angle {
+    value: f64,
+    unit: Unit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Radians,
+    Degrees,
+}
+
+impl Angle {
+    /// 0
+    pub const ZERO: Angle = Angle::from_radians(0.0);
+
+    /// ラジアンの値から作成する
+    pub const fn from_radians(radians: f64) -> Self {
+        Self {
+            value: radians,
+            unit: Unit::Radians,
+        }
+    }
+
+    /// 度の値から作成する
+    pub const fn from_degrees(degrees: f64) -> Self {
+        Self {
+            value: degrees,
+            unit: Unit::Degrees,
+        }
+    }
+
+    /// ラジアンの値を返す
+    pub fn radians(self) -> f64 {
+        match self.unit {
+            Unit::Radians => self.value,
+            Unit::Degrees => self.value.to_radians(),
+        }
+    }
+
+    /// 度の値を返す
+    pub fn degrees(self) -> f64 {
+        match self.unit {
+            Unit::Radians => self.value.to_degrees(),
+            Unit::Degrees => self.value,
+        }
+    }
+}
+
+impl Default for Angle {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+/// 単位が同じ場合は値を、異なる場合はラジアンに変換して比較する
+impl PartialEq for Angle {
+    fn eq(&self, other: &Self) -> bool {
+        if self.unit == other.unit {
+            self.value == other.value
+        } else {
+            self.radians() == other.radians()
+        }
+    }
+}
+
+impl Add for Angle {
+    type Output = Angle;
+
+    /// 単位が同じ場合はその単位で、異なる場合はラジアンで加算する
+    fn add(self, other: Angle) -> Angle {
+        if self.unit == other.unit {
+            Angle {
+                value: self.value + other.value,
+                unit: self.unit,
+            }
+        } else {
+            Angle::from_radians(self.radians() + other.radians())
+        }
+    }
+}
+
+impl Sub for Angle {
+    type Output = Angle;
+
+    fn sub(self, other: Angle) -> Angle {
+        self + -other
+    }
+}
+
+impl Neg for Angle {
+    type Output = Angle;
+
+    fn neg(self) -> Angle {
+        Angle {
+            value: -self.value,
+            unit: self.unit,
+        }
+    }
+}
+
+impl Serialize for Angle {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.radians())
+    }
+}
+
+impl<'de> Deserialize<'de> for Angle {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        f64::deserialize(deserializer).map(Angle::from_radians)
+    }
+}
+
+/// 度の数値としてシリアライズする (`#[serde(with = "jww_core::angle::degrees")]`)
+pub mod degrees {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(angle: &Angle, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(angle.degrees())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Angle, D::Error> {
+        f64::deserialize(deserializer).map(Angle::from_degrees)
+    }
+}
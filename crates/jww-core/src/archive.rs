@@ -0,0 +1,147 @@
+//! ZIPアーカイブ内の図面の読み込み (`zip` フィーチャー)
+//!
+//! 図面は複数のJWWファイルをまとめたZIPでやり取りされることが多いため、展開せずに
+//! アーカイブ内の `.jww`・`.jwc` ファイルを順にパースする。
+//!
+//! ```no_run
+//! # fn main() -> jww_core::Result<()> {
+//! let file = std::fs::File::open("drawings.zip")?;
+//! for entry in jww_core::archive::ZipDocuments::new(file)? {
+//!     match entry.document {
+//!         Ok(doc) => println!("{}: {} entities", entry.name, doc.entities.len()),
+//!         Err(e) => eprintln!("{}: {}", entry.name, e),
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::{self, Read, Seek};
+
+use zip::ZipArchive;
+
+use crate::error::{ParseError, Result};
+use crate::options::ParseOptions;
+use crate::types::Document;
+
+/// アーカイブ内の1ファイルのパース結果
+#[derive(Debug)]
+pub struct ZipEntry {
+    /// アーカイブ内のパス (Shift-JISのファイル名も変換する)
+    pub name: String,
+    /// パース結果
+    pub document: Result<Document<'static>>,
+}
+
+/// ZIPアーカイブ内の図面を順にパースするイテレータ
+///
+/// 対象は拡張子が `.jww`・`.jwc` のファイル (大文字小文字を区別しない) で、
+/// macOSが追加するメタデータ (`__MACOSX/`、`._*`) は除く。DOS版のJWCファイルは
+/// 読み込みに対応していないため [`ParseError::Other`] になる。
+pub struct ZipDocuments<R> {
+    archive: ZipArchive<R>,
+    /// 対象ファイルのアーカイブ内の番号と名前
+    entries: std::vec::IntoIter<(usize, String)>,
+    options: ParseOptions,
+}
+
+impl<R: Read + Seek> ZipDocuments<R> {
+    /// ZIPアーカイブを開く
+    ///
+    /// # エラー
+    /// ZIPアーカイブとして読み取れない場合は [`ParseError::Io`]
+    pub fn new(reader: R) -> Result<Self> {
+        Self::with_options(reader, &ParseOptions::default())
+    }
+
+    /// オプションを指定してZIPアーカイブを開く
+    ///
+    /// [`ParseOptions::max_file_size`] は展開後のサイズに適用する。
+    pub fn with_options(reader: R, options: &ParseOptions) -> Result<Self> {
+        let mut archive = ZipArchive::new(reader).map_err(io::Error::from)?;
+        let mut entries = Vec::new();
+        for index in 0..archive.len() {
+            let file = archive.by_index_raw(index).map_err(io::Error::from)?;
+            let name = decode_name(file.name_raw());
+            if file.is_file() && is_drawing(&name) {
+                entries.push((index, name));
+            }
+        }
+        Ok(Self {
+            archive,
+            entries: entries.into_iter(),
+            options: options.clone(),
+        })
+    }
+
+    /// 未読の対象ファイルの名前
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries
+            .as_slice()
+            .iter()
+            .map(|(_, name)| name.as_str())
+    }
+
+    /// アーカイブ内のファイルを読み込んでパースする
+    fn read(&mut self, index: usize, name: &str) -> Result<Document<'static>> {
+        let mut file = self.archive.by_index(index).map_err(io::Error::from)?;
+        let limit = self
+            .options
+            .max_file_size
+            .map_or(u64::MAX, |max| max as u64);
+        if file.size() > limit {
+            return Err(ParseError::LimitExceeded(format!(
+                "file of {} bytes exceeds {}",
+                file.size(),
+                limit
+            )));
+        }
+        // ヘッダーのサイズが偽られていても上限を超えて展開しない
+        let mut data = Vec::with_capacity(file.size().min(limit) as usize);
+        (&mut file)
+            .take(limit.saturating_add(1))
+            .read_to_end(&mut data)?;
+        if has_extension(name, "jwc") && !data.starts_with(b"JwwData.") {
+            return Err(ParseError::Other("JWC files are not supported".to_string()));
+        }
+        crate::parse_with_options(&data, &self.options).map(Document::into_owned)
+    }
+}
+
+impl<R: Read + Seek> Iterator for ZipDocuments<R> {
+    type Item = ZipEntry;
+
+    fn next(&mut self) -> Option<ZipEntry> {
+        let (index, name) = self.entries.next()?;
+        let document = self.read(index, &name);
+        Some(ZipEntry { name, document })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl<R: Read + Seek> ExactSizeIterator for ZipDocuments<R> {}
+
+/// ファイル名を変換する (UTF-8でなければWindowsで作成されたShift-JISとみなす)
+fn decode_name(raw: &[u8]) -> String {
+    match std::str::from_utf8(raw) {
+        Ok(name) => name.to_string(),
+        Err(_) => encoding_rs::SHIFT_JIS.decode(raw).0.into_owned(),
+    }
+}
+
+/// 読み込み対象の図面ファイルかどうか
+fn is_drawing(name: &str) -> bool {
+    let file_name = name.rsplit('/').next().unwrap_or(name);
+    !name.starts_with("__MACOSX/")
+        && !file_name.starts_with("._")
+        && (has_extension(name, "jww") || has_extension(name, "jwc"))
+}
+
+fn has_extension(name: &str, extension: &str) -> bool {
+    std::path::Path::new(name)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+}
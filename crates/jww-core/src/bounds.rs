@@ -0,0 +1,284 @@
+//! 図面範囲の計算
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use crate::point::Point2D;
+use crate::types::{Arc, Document, Entity};
+
+/// 軸平行な外接矩形
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl BoundingBox {
+    /// 1点からなる矩形を作成する
+    pub fn from_point(point: Point2D) -> Self {
+        Self {
+            min_x: point.x,
+            min_y: point.y,
+            max_x: point.x,
+            max_y: point.y,
+        }
+    }
+
+    /// 点を含むように矩形を拡張する
+    pub fn include_point(&mut self, point: Point2D) {
+        self.min_x = self.min_x.min(point.x);
+        self.min_y = self.min_y.min(point.y);
+        self.max_x = self.max_x.max(point.x);
+        self.max_y = self.max_y.max(point.y);
+    }
+
+    /// 別の矩形を含むように矩形を拡張する
+    pub fn include(&mut self, other: &BoundingBox) {
+        self.include_point(Point2D::new(other.min_x, other.min_y));
+        self.include_point(Point2D::new(other.max_x, other.max_y));
+    }
+
+    /// 幅を返す
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    /// 高さを返す
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+
+    /// 原点を中心に拡大縮小した矩形を返す (`factor` は正の値)
+    pub fn scaled(&self, factor: f64) -> Self {
+        Self {
+            min_x: self.min_x * factor,
+            min_y: self.min_y * factor,
+            max_x: self.max_x * factor,
+            max_y: self.max_y * factor,
+        }
+    }
+
+    /// 中心点を返す
+    pub fn center(&self) -> Point2D {
+        Point2D::new(
+            (self.min_x + self.max_x) / 2.0,
+            (self.min_y + self.max_y) / 2.0,
+        )
+    }
+}
+
+impl Document<'_> {
+    /// 全エンティティの外接矩形を返す
+    ///
+    /// エンティティが存在しない場合は `None` を返す。
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        entities_bounding_box(self, &self.entities, 0)
+    }
+
+    /// 用紙上 (mm) の外接矩形を返す
+    ///
+    /// 各エンティティの範囲をレイヤグループの縮尺 ([`Document::layer_group_scale`]) で
+    /// 割ってから合成するため、縮尺の異なるレイヤグループを含む図面でも印刷時の範囲になる。
+    /// エンティティが存在しない場合は `None` を返す。
+    pub fn bounding_box_paper(&self) -> Option<BoundingBox> {
+        let mut result: Option<BoundingBox> = None;
+        for entity in &self.entities {
+            let Some(bb) = entities_bounding_box(self, std::slice::from_ref(entity), 0) else {
+                continue;
+            };
+            let bb = bb.scaled(1.0 / self.layer_group_scale(entity.base().layer_group));
+            match result.as_mut() {
+                Some(r) => r.include(&bb),
+                None => result = Some(bb),
+            }
+        }
+        result
+    }
+
+    /// ブロック定義の外接矩形を返す (ブロック定義の座標)
+    ///
+    /// 入れ子のブロック挿入は参照先の範囲を含める。指定した番号のブロック定義が
+    /// 存在しない場合や、エンティティが存在しない場合は `None` を返す。
+    pub fn block_bounding_box(&self, def_number: u32) -> Option<BoundingBox> {
+        let def = self.block_defs.iter().find(|bd| bd.number == def_number)?;
+        entities_bounding_box(self, &def.entities, 1)
+    }
+
+    /// レイヤ (レイヤグループ, レイヤ) ごとの外接矩形を返す
+    ///
+    /// ブロック挿入は挿入したエンティティのレイヤに含める。
+    /// エンティティが存在しないレイヤは含まない。
+    pub fn layer_bounding_boxes(&self) -> BTreeMap<(u16, u16), BoundingBox> {
+        let mut result: BTreeMap<(u16, u16), BoundingBox> = BTreeMap::new();
+        for entity in &self.entities {
+            let Some(bb) = entities_bounding_box(self, std::slice::from_ref(entity), 0) else {
+                continue;
+            };
+            let base = entity.base();
+            result
+                .entry((base.layer_group, base.layer))
+                .and_modify(|r| r.include(&bb))
+                .or_insert(bb);
+        }
+        result
+    }
+}
+
+impl Entity<'_> {
+    /// エンティティ単体の外接矩形を返す
+    ///
+    /// ブロック挿入は参照先の定義を解決できないため挿入基準点のみを返す。
+    /// 参照先を含めた範囲は [`Document::bounding_box`] を使用する。
+    /// 未知のエンティティは座標を持たないため原点を返す ([`Document::bounding_box`] には含めない)。
+    pub fn bounding_box(&self) -> BoundingBox {
+        match self {
+            Entity::Line(e) => {
+                let mut bb = BoundingBox::from_point(e.start);
+                bb.include_point(e.end);
+                bb
+            }
+            Entity::Arc(e) => arc_bounding_box(e),
+            Entity::Point(e) => BoundingBox::from_point(e.position),
+            Entity::Text(e) => {
+                let mut bb = BoundingBox::from_point(e.start);
+                bb.include_point(e.end);
+                bb
+            }
+            Entity::Solid(e) => {
+                let mut bb = BoundingBox::from_point(e.point1);
+                bb.include_point(e.point2);
+                bb.include_point(e.point3);
+                bb.include_point(e.point4);
+                bb
+            }
+            Entity::Block(e) => BoundingBox::from_point(e.reference),
+            Entity::Dimension(e) => {
+                let mut bb = BoundingBox::from_point(e.line.start);
+                bb.include_point(e.line.end);
+                bb.include_point(e.text.start);
+                bb.include_point(e.text.end);
+                for line in &e.extension_lines {
+                    bb.include_point(line.start);
+                    bb.include_point(line.end);
+                }
+                bb
+            }
+            Entity::Unknown(_) => BoundingBox::from_point(Point2D::new(0.0, 0.0)),
+        }
+    }
+}
+
+/// ブロック定義のネストの上限 (循環参照対策)
+const MAX_BLOCK_DEPTH: usize = 16;
+
+/// エンティティ群の外接矩形を計算する
+fn entities_bounding_box(doc: &Document, entities: &[Entity], depth: usize) -> Option<BoundingBox> {
+    let mut result: Option<BoundingBox> = None;
+
+    for entity in entities {
+        let bb = match entity {
+            Entity::Block(block) => {
+                let def = doc.block_defs.iter().find(|bd| bd.number == block.def_number);
+                let inner = match def {
+                    Some(def) if depth < MAX_BLOCK_DEPTH => {
+                        entities_bounding_box(doc, &def.entities, depth + 1)
+                    }
+                    _ => None,
+                };
+                match inner {
+                    Some(inner) => {
+                        // 定義の外接矩形の4隅を挿入変換して包含する
+                        let (sin, cos) = block.rotation.radians().sin_cos();
+                        let mut bb: Option<BoundingBox> = None;
+                        for (x, y) in [
+                            (inner.min_x, inner.min_y),
+                            (inner.max_x, inner.min_y),
+                            (inner.max_x, inner.max_y),
+                            (inner.min_x, inner.max_y),
+                        ] {
+                            let sx = x * block.scale_x;
+                            let sy = y * block.scale_y;
+                            let point = Point2D::new(
+                                block.reference.x + sx * cos - sy * sin,
+                                block.reference.y + sx * sin + sy * cos,
+                            );
+                            match bb.as_mut() {
+                                Some(bb) => bb.include_point(point),
+                                None => bb = Some(BoundingBox::from_point(point)),
+                            }
+                        }
+                        bb.unwrap_or_else(|| entity.bounding_box())
+                    }
+                    None => entity.bounding_box(),
+                }
+            }
+            Entity::Unknown(_) => continue,
+            _ => entity.bounding_box(),
+        };
+
+        match result.as_mut() {
+            Some(r) => r.include(&bb),
+            None => result = Some(bb),
+        }
+    }
+
+    result
+}
+
+/// 円弧の外接矩形を計算する
+///
+/// 楕円 (扁平率が1以外) の場合は楕円全体の外接矩形を返す。
+fn arc_bounding_box(arc: &Arc) -> BoundingBox {
+    let Point2D { x: cx, y: cy } = arc.center;
+    let r = arc.radius;
+
+    if arc.flatness != 1.0 {
+        let a = r;
+        let b = r * arc.flatness;
+        let (sin, cos) = arc.tilt_angle.radians().sin_cos();
+        let half_w = ((a * cos).powi(2) + (b * sin).powi(2)).sqrt();
+        let half_h = ((a * sin).powi(2) + (b * cos).powi(2)).sqrt();
+        return BoundingBox {
+            min_x: cx - half_w,
+            min_y: cy - half_h,
+            max_x: cx + half_w,
+            max_y: cy + half_h,
+        };
+    }
+
+    if arc.is_full_circle || arc.arc_angle.radians().abs() >= 2.0 * PI {
+        return BoundingBox {
+            min_x: cx - r,
+            min_y: cy - r,
+            max_x: cx + r,
+            max_y: cy + r,
+        };
+    }
+
+    // 始点・終点と、範囲内に含まれる象限点を包含する
+    let (start, arc_angle) = (arc.start_angle.radians(), arc.arc_angle.radians());
+    let (start, sweep) = if arc_angle < 0.0 {
+        (start + arc_angle, -arc_angle)
+    } else {
+        (start, arc_angle)
+    };
+    let start = start.rem_euclid(2.0 * PI);
+
+    let at = |angle: f64| Point2D::new(cx + r * angle.cos(), cy + r * angle.sin());
+    let mut bb = BoundingBox::from_point(at(start));
+    let end = start + sweep;
+    bb.include_point(at(end));
+
+    let mut quadrant = 0.0;
+    while quadrant < end {
+        if quadrant > start {
+            bb.include_point(at(quadrant));
+        }
+        quadrant += FRAC_PI_2;
+    }
+
+    bb
+}
@@ -0,0 +1,163 @@
+//! 線色の表示色
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Document, Entity};
+
+/// RGB色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb::new(0, 0, 0);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Windows の COLORREF (0x00BBGGRR) から変換する
+    pub fn from_colorref(color: u32) -> Self {
+        Self::new(
+            (color & 0xFF) as u8,
+            ((color >> 8) & 0xFF) as u8,
+            ((color >> 16) & 0xFF) as u8,
+        )
+    }
+
+    /// Windows の COLORREF (0x00BBGGRR) に変換する
+    pub fn to_colorref(self) -> u32 {
+        self.r as u32 | (self.g as u32) << 8 | (self.b as u32) << 16
+    }
+
+    /// CSSの16進表記 (`#rrggbb`) を返す
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// 線色1-9の表示色
+///
+/// 線色番号の色はJw_cadの設定で変更できるが、JWWファイルには保存されないため、
+/// 出力先に合わせて [`Palette::SCREEN`] か [`Palette::PRINTER`] を選ぶか、
+/// `colors` を書き換えて使用する。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct Palette {
+    /// 線色1-9の色 (添字0が線色1)
+    pub colors: [Rgb; 9],
+}
+
+impl Palette {
+    /// Jw_cadの既定の画面表示色 (白背景)
+    ///
+    /// 線色2 (白) と線色7 (黒) は白背景で見えるように黒で表示する。
+    pub const SCREEN: Palette = Palette {
+        colors: [
+            Rgb::new(0, 192, 192),   // 水色
+            Rgb::BLACK,              // 白
+            Rgb::new(0, 160, 0),     // 緑
+            Rgb::new(192, 160, 0),   // 黄
+            Rgb::new(255, 0, 255),   // ピンク
+            Rgb::new(0, 0, 255),     // 青
+            Rgb::BLACK,              // 黒
+            Rgb::new(255, 0, 0),     // 赤
+            Rgb::new(128, 128, 128), // グレー
+        ],
+    };
+
+    /// Jw_cadの既定のプリンタ出力色 (カラー印刷しない場合と同じくすべて黒)
+    pub const PRINTER: Palette = Palette {
+        colors: [Rgb::BLACK; 9],
+    };
+
+    /// 線色番号の色を返す (対応する色のない線色番号は黒)
+    pub fn rgb(&self, pen_color: u16) -> Rgb {
+        match pen_color {
+            1..=9 => self.colors[pen_color as usize - 1],
+            _ => Rgb::BLACK,
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::SCREEN
+    }
+}
+
+/// 線色番号の標準の表示色 ([`Palette::SCREEN`]) を返す
+pub fn pen_rgb(pen_color: u16) -> Rgb {
+    Palette::SCREEN.rgb(pen_color)
+}
+
+/// SXF対応拡張線色の線色番号から [`Document::sxf_colors`] の添字を求めるためのオフセット
+///
+/// SXF色番号 `n` (1-256) の線色番号は `100 + n`。
+pub const SXF_PEN_COLOR_OFFSET: u16 = 100;
+
+/// SXFの既定義色 (SXF色番号1-16)
+///
+/// ヘッダーにSXF拡張線色の定義がない図面で使う。
+pub const SXF_STANDARD_COLORS: [Rgb; 16] = [
+    Rgb::BLACK,              // black
+    Rgb::new(255, 0, 0),     // red
+    Rgb::new(0, 255, 0),     // green
+    Rgb::new(0, 0, 255),     // blue
+    Rgb::new(255, 255, 0),   // yellow
+    Rgb::new(255, 0, 255),   // magenta
+    Rgb::new(0, 255, 255),   // cyan
+    Rgb::new(255, 255, 255), // white
+    Rgb::new(192, 0, 128),   // deeppink
+    Rgb::new(192, 128, 64),  // brown
+    Rgb::new(255, 128, 0),   // orange
+    Rgb::new(128, 192, 128), // lightgreen
+    Rgb::new(0, 128, 255),   // lightblue
+    Rgb::new(128, 64, 255),  // lavender
+    Rgb::new(192, 192, 192), // lightgray
+    Rgb::new(128, 128, 128), // darkgray
+];
+
+impl Document<'_> {
+    /// このドキュメントでの線色番号の色を返す
+    ///
+    /// 線色1-9は `palette` の色、SXF対応拡張線色 (線色番号101以降) はパレットに関わらず
+    /// ヘッダーの定義 ([`Document::sxf_colors`]) の画面表示色を返す。定義のない図面では
+    /// 既定義色 ([`SXF_STANDARD_COLORS`]) を使い、ユーザー定義色は黒とする。
+    pub fn pen_rgb(&self, pen_color: u16, palette: &Palette) -> Rgb {
+        if pen_color <= SXF_PEN_COLOR_OFFSET {
+            return palette.rgb(pen_color);
+        }
+        let sxf = (pen_color - SXF_PEN_COLOR_OFFSET) as usize;
+        match self.sxf_colors.get(sxf) {
+            Some(color) => *color,
+            None if self.sxf_colors.is_empty() => SXF_STANDARD_COLORS
+                .get(sxf - 1)
+                .copied()
+                .unwrap_or(Rgb::BLACK),
+            None => Rgb::BLACK,
+        }
+    }
+}
+
+impl Entity<'_> {
+    /// エンティティの画面表示色 ([`Palette::SCREEN`]) を返す
+    pub fn resolved_rgb(&self, doc: &Document) -> Rgb {
+        self.resolved_rgb_with(doc, &Palette::SCREEN)
+    }
+
+    /// パレットを指定してエンティティの表示色を返す
+    ///
+    /// 線色10 (任意色) のソリッドは `color` を、それ以外は線色番号の色
+    /// ([`Document::pen_rgb`]) を使用する。
+    pub fn resolved_rgb_with(&self, doc: &Document, palette: &Palette) -> Rgb {
+        match self {
+            Entity::Solid(solid) if solid.base.pen_color == 10 => Rgb::from_colorref(solid.color),
+            entity => doc.pen_rgb(entity.base().pen_color, palette),
+        }
+    }
+}
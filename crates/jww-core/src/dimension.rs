@@ -0,0 +1,113 @@
+//! 図面の寸法設定
+
+use serde::{Deserialize, Serialize};
+
+use crate::point::Point2D;
+use crate::types::{Dimension, Document, Entity};
+
+/// 図面で使われている寸法設定 ([`Document::dimension_settings`])
+///
+/// 値は用紙上のmm。JWWファイルは寸法設定の数値をヘッダーに持たないため、
+/// 寸法エンティティの形状から読み取る。読み取れない項目は `None`。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DimensionSettings {
+    /// 補助線の起点からの離れ (Ver.4.20以降の寸法のみ)
+    pub extension_offset: Option<f64>,
+    /// 補助線の寸法線からのはみ出し (Ver.4.20以降の寸法のみ)
+    pub extension_extension: Option<f64>,
+    /// 寸法値と寸法線の間隔
+    pub text_gap: Option<f64>,
+    /// 寸法値の文字高さ
+    pub text_height: Option<f64>,
+}
+
+impl Document<'_> {
+    /// 寸法エンティティから寸法設定を読み取る
+    ///
+    /// 各項目は最初に読み取れた寸法の値を使う (ブロック定義内の寸法を含む)。
+    /// 寸法線の長さが0の寸法は読み取りに使わない。
+    pub fn dimension_settings(&self) -> DimensionSettings {
+        let mut settings = DimensionSettings::default();
+        let dimensions = self
+            .entities
+            .iter()
+            .chain(self.block_defs.iter().flat_map(|bd| bd.entities.iter()))
+            .filter_map(|e| match e {
+                Entity::Dimension(dim) => Some(dim),
+                _ => None,
+            });
+        for dim in dimensions {
+            let scale = self.layer_group_scale(dim.base.layer_group);
+            let measured = measure(dim);
+            let paper = |value: Option<f64>| value.map(|v| v / scale);
+            settings.extension_offset = settings
+                .extension_offset
+                .or(paper(measured.extension_offset));
+            settings.extension_extension = settings
+                .extension_extension
+                .or(paper(measured.extension_extension));
+            settings.text_gap = settings.text_gap.or(paper(measured.text_gap));
+            if dim.text.size_y > 0.0 {
+                settings.text_height = settings.text_height.or(Some(dim.text.size_y));
+            }
+        }
+        settings
+    }
+}
+
+/// 寸法1つの形状から図面座標での値を読み取る
+fn measure(dim: &Dimension) -> DimensionSettings {
+    let line = &dim.line;
+    let length = line.start.distance(line.end);
+    if length <= 0.0 {
+        return DimensionSettings::default();
+    }
+    let direction = Point2D::new(
+        (line.end.x - line.start.x) / length,
+        (line.end.y - line.start.y) / length,
+    );
+    let foot = |p: Point2D| {
+        let t = (p.x - line.start.x) * direction.x + (p.y - line.start.y) * direction.y;
+        Point2D::new(
+            line.start.x + t * direction.x,
+            line.start.y + t * direction.y,
+        )
+    };
+
+    let text_gap = Some(
+        ((dim.text.start.x - line.start.x) * direction.y
+            - (dim.text.start.y - line.start.y) * direction.x)
+            .abs(),
+    );
+
+    // 補助線の起点は端点の3点目・4点目
+    let extension = dim
+        .extension_lines
+        .first()
+        .zip(dim.end_points.get(2))
+        .and_then(|(ext, origin)| {
+            let origin = origin.position;
+            let foot = foot(origin);
+            let distance = origin.distance(foot);
+            if distance <= 0.0 {
+                return None;
+            }
+            let (near, far) = if origin.distance(ext.start) <= origin.distance(ext.end) {
+                (ext.start, ext.end)
+            } else {
+                (ext.end, ext.start)
+            };
+            // 起点から寸法線へ向かう向きに測った、寸法線から補助線の先端までの長さ
+            let beyond = ((far.x - foot.x) * (foot.x - origin.x)
+                + (far.y - foot.y) * (foot.y - origin.y))
+                / distance;
+            Some((origin.distance(near), beyond))
+        });
+
+    DimensionSettings {
+        extension_offset: extension.map(|(offset, _)| offset),
+        extension_extension: extension.map(|(_, beyond)| beyond),
+        text_gap,
+        text_height: None,
+    }
+}
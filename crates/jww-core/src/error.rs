@@ -19,14 +19,70 @@ pub enum ParseError {
     #[error("unknown entity class: {0}")]
     UnknownEntityClass(String),
 
+    /// Shift-JISとして読み取れない文字列 ([`crate::EncodingPolicy::Strict`] の場合)
+    #[error("invalid Shift-JIS string")]
+    InvalidEncoding,
+
+    /// [`crate::ParseOptions`] の制限を超えた
+    #[error("limit exceeded: {0}")]
+    LimitExceeded(String),
+
     /// IOエラー
     #[error("IO error: {0}")]
     Io(#[from] io::Error),
 
+    /// エンティティの読み取り中のエラー
+    #[error("entity #{index} at byte {offset}: {source}")]
+    Entity {
+        /// エンティティリスト内の番号 (0始まり、Nullオブジェクトを含む)
+        index: usize,
+        /// エンティティの先頭のファイル先頭からのバイト位置
+        offset: usize,
+        /// 元のエラー
+        source: Box<ParseError>,
+    },
+
     /// その他のエラー
     #[error("{0}")]
     Other(String),
 }
 
+impl ParseError {
+    /// エラーの種類を表す識別子を返す
+    ///
+    /// メッセージの翻訳など、表示側でエラーを判別するために使用する。
+    /// `Entity` の場合は元のエラーの識別子を返す。
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseError::InvalidSignature => "invalid_signature",
+            ParseError::UnsupportedVersion(_) => "unsupported_version",
+            ParseError::UnknownClassPid(_) => "unknown_class_pid",
+            ParseError::UnknownEntityClass(_) => "unknown_entity_class",
+            ParseError::InvalidEncoding => "invalid_encoding",
+            ParseError::LimitExceeded(_) => "limit_exceeded",
+            ParseError::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof => "unexpected_eof",
+            ParseError::Io(_) => "io",
+            ParseError::Entity { source, .. } => source.code(),
+            ParseError::Other(_) => "other",
+        }
+    }
+
+    /// エラーが発生したエンティティの先頭のバイト位置を返す
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            ParseError::Entity { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// エラーが発生したエンティティの番号を返す
+    pub fn entity_index(&self) -> Option<usize> {
+        match self {
+            ParseError::Entity { index, .. } => Some(*index),
+            _ => None,
+        }
+    }
+}
+
 /// JWWパース結果の型エイリアス
 pub type Result<T> = std::result::Result<T, ParseError>;
@@ -0,0 +1,90 @@
+//! 曲線の幾何計算
+
+use std::f64::consts::PI;
+
+use crate::angle::Angle;
+use crate::point::Point2D;
+use crate::types::Arc;
+
+/// 1周を近似する際の最小分割数
+const MIN_FULL_SEGMENTS: usize = 8;
+
+/// 近似する際の最大分割数 (極端に小さい許容誤差への対策)
+const MAX_SEGMENTS: usize = 4096;
+
+impl Arc {
+    /// 楕円 (扁平率が1以外) かどうかを返す
+    pub fn is_ellipse(&self) -> bool {
+        self.flatness != 1.0
+    }
+
+    /// 円弧が1周しているかどうかを返す
+    pub fn is_closed(&self) -> bool {
+        self.is_full_circle || self.arc_angle.radians().abs() >= 2.0 * PI
+    }
+
+    /// X軸から測った角度を、扁平前の円上の媒介変数 (ラジアン) に変換する
+    ///
+    /// 媒介変数は傾き方向 (半径側の軸) を基準に測る。
+    pub fn angle_to_param(&self, angle: Angle) -> f64 {
+        let local = (angle - self.tilt_angle).radians();
+        local.sin().atan2(self.flatness * local.cos())
+    }
+
+    /// 媒介変数 `t` に対応する曲線上の点を返す
+    pub fn point_at_param(&self, t: f64) -> Point2D {
+        let (sin, cos) = self.tilt_angle.radians().sin_cos();
+        let lx = self.radius * t.cos();
+        let ly = self.radius * self.flatness * t.sin();
+        Point2D::new(
+            self.center.x + lx * cos - ly * sin,
+            self.center.y + lx * sin + ly * cos,
+        )
+    }
+
+    /// 開始点と終点の媒介変数を返す
+    ///
+    /// 終点は開始点から円弧角の向きに進んだ値となる (負の円弧角では開始点より小さい)。
+    pub fn param_range(&self) -> (f64, f64) {
+        if self.is_closed() {
+            let start = self.angle_to_param(self.start_angle);
+            return (start, start + 2.0 * PI);
+        }
+        let start = self.angle_to_param(self.start_angle);
+        let end = self.angle_to_param(self.start_angle + self.arc_angle);
+        let sweep = if self.arc_angle.radians() >= 0.0 {
+            (end - start).rem_euclid(2.0 * PI)
+        } else {
+            -(start - end).rem_euclid(2.0 * PI)
+        };
+        (start, start + sweep)
+    }
+
+    /// 弦の許容誤差 `tolerance` 以内で曲線を折れ線に近似する
+    ///
+    /// 1周する場合も最後の点は最初の点と一致する (閉じた折れ線)。
+    pub fn tessellate(&self, tolerance: f64) -> Vec<Point2D> {
+        let (start, end) = self.param_range();
+        let sweep = end - start;
+        let segments = segment_count(self.radius.max(self.radius * self.flatness), sweep, tolerance);
+
+        (0..=segments)
+            .map(|i| self.point_at_param(start + sweep * i as f64 / segments as f64))
+            .collect()
+    }
+}
+
+/// 半径 `radius`、角度 `sweep` の円弧を許容誤差 `tolerance` で近似する分割数を返す
+pub fn segment_count(radius: f64, sweep: f64, tolerance: f64) -> usize {
+    let sweep = sweep.abs();
+    let min_segments = ((MIN_FULL_SEGMENTS as f64 * sweep / (2.0 * PI)).ceil() as usize).max(1);
+    if radius <= 0.0 || tolerance <= 0.0 || !tolerance.is_finite() {
+        return min_segments;
+    }
+    if tolerance >= radius {
+        return min_segments;
+    }
+    // 弦の最大距離 (サジッタ) が許容誤差となる中心角
+    let step = 2.0 * (1.0 - tolerance / radius).acos();
+    ((sweep / step).ceil() as usize).clamp(min_segments, MAX_SEGMENTS)
+}
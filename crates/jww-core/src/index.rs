@@ -0,0 +1,83 @@
+//! レイヤごとのエンティティの索引
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use crate::types::{Document, Entity};
+
+/// (レイヤグループ, レイヤ) ごとのエンティティの番号 ([`Document::entities`] の添字)
+pub type LayerIndex = BTreeMap<(u16, u16), Vec<usize>>;
+
+/// ドキュメントから計算した値のキャッシュ
+///
+/// シリアライズせず、複製時は計算済みの値を引き継ぐ。[`Document::entities_mut`] で
+/// エンティティを変更すると破棄される。[`Document::entities`] を直接変更して
+/// エンティティ数が変わった場合は、次に参照したときに計算し直す。
+#[derive(Default)]
+pub struct DocumentCache {
+    /// 計算時のエンティティ数とレイヤごとの索引
+    layers: Mutex<Option<(usize, Arc<LayerIndex>)>>,
+}
+
+impl DocumentCache {
+    fn layers(&self) -> Option<(usize, Arc<LayerIndex>)> {
+        self.layers.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+impl Clone for DocumentCache {
+    fn clone(&self) -> Self {
+        DocumentCache {
+            layers: Mutex::new(self.layers()),
+        }
+    }
+}
+
+impl std::fmt::Debug for DocumentCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DocumentCache")
+            .field("layers", &self.layers().is_some())
+            .finish()
+    }
+}
+
+impl<'a> Document<'a> {
+    /// (レイヤグループ, レイヤ) ごとのエンティティの番号を返す
+    ///
+    /// 計算した結果を [`Document::cache`] に保持し、エンティティ数が変わるまで再利用する。
+    /// エンティティが存在しないレイヤは含まない。各レイヤの番号は昇順。
+    pub fn entities_by_layer(&self) -> Arc<LayerIndex> {
+        let mut layers = self.cache.layers.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((count, index)) = &*layers {
+            if *count == self.entities.len() {
+                return Arc::clone(index);
+            }
+        }
+
+        let mut index = LayerIndex::new();
+        for (i, entity) in self.entities.iter().enumerate() {
+            let base = entity.base();
+            index
+                .entry((base.layer_group, base.layer))
+                .or_default()
+                .push(i);
+        }
+        let index = Arc::new(index);
+        *layers = Some((self.entities.len(), Arc::clone(&index)));
+        index
+    }
+
+    /// 計算済みの値を破棄し、変更用にエンティティを返す
+    ///
+    /// [`Document::entities`] を直接変更してもエンティティ数が変わらない場合
+    /// (レイヤの変更など) は [`Document::invalidate_cache`] を呼ぶ。
+    pub fn entities_mut(&mut self) -> &mut Vec<Entity<'a>> {
+        self.invalidate_cache();
+        &mut self.entities
+    }
+
+    /// 計算済みの値を破棄する
+    pub fn invalidate_cache(&mut self) {
+        self.cache = DocumentCache::default();
+    }
+}
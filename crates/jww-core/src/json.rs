@@ -0,0 +1,143 @@
+//! JSON出力のフィールド名・エンティティ種別の形式 (`json` フィーチャー)
+//!
+//! 既定の出力はRustの型そのまま (`snake_case` のフィールド名、エンティティは
+//! `"type": "Line"` の内部タグ)。JSから使う場合などに [`JsonProfile`] で
+//! `camelCase` のフィールド名や、種別名をキーにした形に変換できる。
+//!
+//! ```
+//! use jww_core::json::{EntityTagging, FieldNaming, JsonProfile};
+//!
+//! let profile = JsonProfile {
+//!     field_naming: FieldNaming::CamelCase,
+//!     entity_tagging: EntityTagging::Compact,
+//! };
+//! let mut value = serde_json::json!({
+//!     "entities": [{ "type": "Point", "pen_color": 1 }],
+//! });
+//! profile.apply(&mut value);
+//! assert_eq!(value["entities"][0]["Point"]["penColor"], 1);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// フィールド名の形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FieldNaming {
+    /// `pen_color` (Rustのフィールド名のまま)
+    #[default]
+    #[serde(rename = "snake_case")]
+    SnakeCase,
+    /// `penColor`
+    #[serde(rename = "camelCase")]
+    CamelCase,
+}
+
+/// エンティティ種別の表し方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntityTagging {
+    /// `{"type": "Line", ...}` (各エンティティに `type` フィールドを持つ)
+    #[default]
+    Verbose,
+    /// `{"Line": {...}}` (種別名を唯一のキーにする)
+    Compact,
+}
+
+/// JSON出力の形式
+///
+/// 既定値は `serde_json::to_value(&doc)` の出力と同じ形。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JsonProfile {
+    /// フィールド名の形式
+    pub field_naming: FieldNaming,
+    /// エンティティ種別の表し方
+    pub entity_tagging: EntityTagging,
+}
+
+impl JsonProfile {
+    /// 既定の形式 (変換が不要) かどうか
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 値をJSONの値に変換し、この形式を適用する
+    pub fn to_value<T: Serialize + ?Sized>(&self, value: &T) -> serde_json::Result<Value> {
+        let mut json = serde_json::to_value(value)?;
+        self.apply(&mut json);
+        Ok(json)
+    }
+
+    /// 既定の形式のJSONの値をこの形式に書き換える
+    ///
+    /// エンティティは [`EntityKind`](crate::EntityKind) の名前の `type` フィールドを
+    /// 持つオブジェクトとして判別する。
+    pub fn apply(&self, value: &mut Value) {
+        if self.is_default() {
+            return;
+        }
+        match value {
+            Value::Array(items) => items.iter_mut().for_each(|item| self.apply(item)),
+            Value::Object(map) => {
+                let fields = std::mem::take(map);
+                let mut renamed: Map<String, Value> = fields
+                    .into_iter()
+                    .map(|(key, mut field)| {
+                        self.apply(&mut field);
+                        (self.field_name(key), field)
+                    })
+                    .collect();
+                if self.entity_tagging == EntityTagging::Compact {
+                    if let Some(kind) = entity_type(&renamed) {
+                        renamed.remove("type");
+                        renamed = Map::from_iter([(kind, Value::Object(renamed))]);
+                    }
+                }
+                *map = renamed;
+            }
+            _ => {}
+        }
+    }
+
+    fn field_name(&self, key: String) -> String {
+        match self.field_naming {
+            FieldNaming::SnakeCase => key,
+            FieldNaming::CamelCase => camel_case(&key),
+        }
+    }
+}
+
+/// エンティティのオブジェクトであれば種別名を返す
+fn entity_type(map: &Map<String, Value>) -> Option<String> {
+    const KINDS: [&str; 7] = [
+        "Line",
+        "Arc",
+        "Point",
+        "Text",
+        "Solid",
+        "Block",
+        "Dimension",
+    ];
+    match map.get("type") {
+        Some(Value::String(kind)) if KINDS.contains(&kind.as_str()) => Some(kind.clone()),
+        _ => None,
+    }
+}
+
+/// `snake_case` を `camelCase` に変換する
+fn camel_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' && !result.is_empty() {
+            upper = true;
+        } else if upper {
+            result.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
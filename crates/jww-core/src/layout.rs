@@ -0,0 +1,184 @@
+//! ファイル内のバイト範囲の対応表
+//!
+//! パーサーが誤読するファイルの調査用に、ヘッダーの各項目・クラス定義・
+//! 各エンティティがファイルのどのバイト範囲にあるかを求める。
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::error::{ParseError, Result};
+use crate::reader::Reader;
+use crate::version::Capabilities;
+use crate::{find_entity_list_offset, parse_entity_with_pid_tracking};
+
+/// バイト範囲の内容
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpanKind {
+    /// シグネチャ `JwwData.`
+    Signature,
+    /// ファイルフォーマットバージョン
+    Version(u32),
+    /// ファイルメモ
+    Memo(String),
+    /// 用紙サイズ
+    PaperSize(u32),
+    /// 書込みレイヤグループ
+    WriteLayerGroup(u32),
+    /// レイヤグループの設定 (グループ番号、16レイヤ分の状態を含む)
+    LayerGroup(usize),
+    /// パーサーが読み飛ばしている領域
+    Unparsed,
+    /// エンティティリストの件数
+    EntityCount(usize),
+    /// MFCのクラス定義 (エンティティの先頭に置かれる)
+    ClassDefinition(String),
+    /// エンティティ (エンティティリスト内の番号、クラス名)
+    Entity { index: usize, class: String },
+    /// Nullオブジェクト
+    NullObject { index: usize },
+    /// エンティティリストより後の領域
+    Trailing,
+}
+
+/// ファイル内のバイト範囲
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    /// ファイル先頭からのバイト位置
+    pub range: Range<usize>,
+    pub kind: SpanKind,
+}
+
+/// バイト範囲の対応表 ([`layout`] の結果)
+#[derive(Debug)]
+pub struct Layout {
+    /// ファイル先頭から順に並べたバイト範囲
+    pub spans: Vec<Span>,
+    /// エンティティの読み取りに失敗した場合のエラー (それ以降の範囲は含まない)
+    pub error: Option<ParseError>,
+}
+
+/// ファイル内のバイト範囲の対応表を作成する
+///
+/// ヘッダーが読み取れない場合はエラーを返す。エンティティの読み取りに失敗した場合は
+/// そこまでの範囲と [`Layout::error`] を返す。
+pub fn layout(data: &[u8]) -> Result<Layout> {
+    if data.len() < 8 || &data[0..8] != b"JwwData." {
+        return Err(ParseError::InvalidSignature);
+    }
+    let mut spans = vec![Span {
+        range: 0..8,
+        kind: SpanKind::Signature,
+    }];
+    let mut reader = Reader::new(&data[8..]);
+    let mut push = |reader: &Reader<&[u8]>, start: usize, kind: SpanKind| {
+        let end = 8 + reader.bytes_read() as usize;
+        spans.push(Span {
+            range: start..end,
+            kind,
+        });
+        end
+    };
+
+    let version = reader.read_dword()?;
+    let caps = Capabilities::of(version)?;
+    let mut pos = push(&reader, 8, SpanKind::Version(version));
+    let memo = reader.read_cstring()?;
+    pos = push(&reader, pos, SpanKind::Memo(memo.into_owned()));
+    let paper_size = reader.read_dword()?;
+    pos = push(&reader, pos, SpanKind::PaperSize(paper_size));
+    let write_layer_group = reader.read_dword()?;
+    pos = push(&reader, pos, SpanKind::WriteLayerGroup(write_layer_group));
+    for group in 0..16 {
+        // 状態・書込みレイヤ・縮尺・保護フラグと、16レイヤの状態・保護フラグ
+        reader.skip(4 + 4 + 8 + 4 + 16 * 8)?;
+        pos = push(&reader, pos, SpanKind::LayerGroup(group));
+    }
+
+    let offset = find_entity_list_offset(data, version, pos, caps)
+        .ok_or_else(|| ParseError::Other("could not find entity list".to_string()))?;
+    if offset > pos {
+        spans.push(Span {
+            range: pos..offset,
+            kind: SpanKind::Unparsed,
+        });
+    }
+
+    let mut reader = Reader::new(&data[offset..]);
+    let count = reader.read_word()? as usize;
+    spans.push(Span {
+        range: offset..offset + 2,
+        kind: SpanKind::EntityCount(count),
+    });
+
+    let mut pid_to_class: HashMap<u32, String> = HashMap::new();
+    let mut next_pid = 1;
+    let mut error = None;
+    for index in 0..count {
+        let start = offset + reader.bytes_read() as usize;
+        let rest = &data[start..];
+        // 新しいクラス定義 (0xFFFF, スキーマ, 名前の長さ, 名前) の範囲
+        let definition = if rest.len() >= 6 && rest[0..2] == [0xFF, 0xFF] {
+            let name_len = u16::from_le_bytes([rest[4], rest[5]]) as usize;
+            let name_end = (6 + name_len).min(rest.len());
+            Some((name_end, String::from_utf8_lossy(&rest[6..name_end]).to_string()))
+        } else {
+            None
+        };
+        let class_id = rest.get(0..2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+
+        match parse_entity_with_pid_tracking(
+            &mut reader,
+            caps,
+            false,
+            &mut pid_to_class,
+            &mut next_pid,
+        ) {
+            Ok(entity) => {
+                let end = offset + reader.bytes_read() as usize;
+                let mut entity_start = start;
+                if let Some((len, name)) = &definition {
+                    entity_start = start + len;
+                    spans.push(Span {
+                        range: start..entity_start,
+                        kind: SpanKind::ClassDefinition(name.clone()),
+                    });
+                }
+                let kind = match entity {
+                    None => SpanKind::NullObject { index },
+                    Some(_) => {
+                        let class = match (&definition, class_id) {
+                            (Some((_, name)), _) => name.clone(),
+                            (None, Some(id)) => pid_to_class.get(&((id & 0x7FFF) as u32)).cloned().unwrap_or_default(),
+                            (None, None) => String::new(),
+                        };
+                        SpanKind::Entity { index, class }
+                    }
+                };
+                spans.push(Span {
+                    range: entity_start..end,
+                    kind,
+                });
+            }
+            Err(e) => {
+                error = Some(ParseError::Entity {
+                    index,
+                    offset: start,
+                    source: Box::new(e),
+                });
+                break;
+            }
+        }
+    }
+
+    if error.is_none() {
+        let end = offset + reader.bytes_read() as usize;
+        if end < data.len() {
+            spans.push(Span {
+                range: end..data.len(),
+                kind: SpanKind::Trailing,
+            });
+        }
+    }
+
+    Ok(Layout { spans, error })
+}
@@ -1,19 +1,57 @@
 //! JWW (Jw_cad) ファイルパーサー
 //!
 //! Jw_cadで使用されるJWWバイナリファイル形式をパースし、
-//! Rustデータ構造に変換するライブラリ。
-
+//! Rustデータ構造に変換するライブラリ。JWW形式での書き出しにも対応する。
+
+pub mod angle;
+#[cfg(feature = "zip")]
+pub mod archive;
+mod bounds;
+mod color;
+mod dimension;
 mod error;
+mod geometry;
+mod index;
+#[cfg(feature = "json")]
+pub mod json;
+mod layout;
+pub mod normalize;
+mod options;
+mod point;
 mod reader;
+mod schema;
+mod search;
+mod stats;
 mod types;
-
+mod validate;
+pub mod version;
+mod writer;
+
+pub use angle::Angle;
+pub use bounds::BoundingBox;
+pub use color::{pen_rgb, Palette, Rgb, SXF_PEN_COLOR_OFFSET, SXF_STANDARD_COLORS};
+pub use dimension::DimensionSettings;
 pub use error::{ParseError, Result};
+pub use geometry::segment_count;
+pub use index::{DocumentCache, LayerIndex};
+pub use layout::{layout, Layout, Span, SpanKind};
+pub use options::{EncodingPolicy, ParseOptions};
+pub use point::Point2D;
 pub use reader::Reader;
+pub use search::TextMatch;
+pub use stats::{LayerStatistics, Statistics};
+pub use validate::{Finding, Severity};
+pub use writer::{write, Writer};
+use std::borrow::Cow;
+use version::Capabilities;
 pub use types::{
-    Document, Entity, EntityBase, Layer, LayerGroup,
-    Line, Arc, Point, Text, Solid, Block, BlockDef,
+    Document, DocumentInfo, Entity, EntityBase, EntityKind, Layer, LayerGroup,
+    Line, Arc, Point, Text, Solid, Block, BlockDef, Dimension, SourceSpan, Unknown,
 };
 
+/// jww-coreのバージョン
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
 /// JWWファイルをパースする
 ///
 /// # 引数
@@ -22,20 +60,255 @@ pub use types::{
 /// # 戻り値
 /// パースされたドキュメント
 ///
+/// ASCIIのみの文字列 (フォント名・文字列内容・ファイルメモ) は複製せずに `data` から
+/// 借用する。`data` より長く保持する場合は [`Document::into_owned`] を使う。
+///
 /// # エラー
 /// - 無効なシグネチャ
 /// - 不正なファイル構造
 /// - IOエラー
-pub fn parse(data: &[u8]) -> Result<Document> {
+pub fn parse(data: &[u8]) -> Result<Document<'_>> {
+    IncrementalParser::new(data)?.finish()
+}
+
+/// オプションを指定してJWWファイルをパースする
+///
+/// `options.lenient` の場合、読み取れないエンティティより前のエンティティで
+/// ドキュメントを返す (読み取りを打ち切った原因はwarnレベルでログに出力する)。
+///
+/// # エラー
+/// [`parse`] のエラーに加えて、制限を超えた場合は [`ParseError::LimitExceeded`]、
+/// `EncodingPolicy::Strict` で読み取れない文字列がある場合は [`ParseError::InvalidEncoding`]
+pub fn parse_with_options<'a>(data: &'a [u8], options: &ParseOptions) -> Result<Document<'a>> {
+    IncrementalParser::with_options(data, options)?.finish()
+}
+
+/// エンティティを少しずつ読み取るパーサー
+///
+/// [`IncrementalParser::step`] で指定した件数ずつエンティティを読み取るため、
+/// 大きなファイルの読み込み中に進捗表示や中断の確認を挟むことができる。
+///
+/// ```no_run
+/// # fn main() -> jww_core::Result<()> {
+/// let data = std::fs::read("example.jww")?;
+/// let mut parser = jww_core::IncrementalParser::new(&data)?;
+/// while !parser.step(1000)? {
+///     let (done, total) = parser.progress();
+///     println!("{}/{}", done, total);
+/// }
+/// let doc = parser.finish()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct IncrementalParser<'a> {
+    header: Header<'a>,
+    reader: Reader<&'a [u8]>,
+    /// エンティティリストのファイル先頭からのバイト位置
+    offset: usize,
+    /// エンティティリストの件数 (Nullオブジェクトを含む)
+    count: usize,
+    /// 読み取り済みの件数
+    index: usize,
+    // MFC CArchive PIDトラッキング
+    pid_to_class: std::collections::HashMap<u32, String>,
+    next_pid: u32,
+    entities: Vec<Entity<'a>>,
+    lenient: bool,
+    record_spans: bool,
+    preserve_unknown_classes: bool,
+}
+
+impl<'a> IncrementalParser<'a> {
+    /// ヘッダーを読み取り、エンティティリストの先頭まで進める
+    pub fn new(data: &'a [u8]) -> Result<Self> {
+        Self::with_options(data, &ParseOptions::default())
+    }
+
+    /// オプションを指定してパーサーを作成する
+    pub fn with_options(data: &'a [u8], options: &ParseOptions) -> Result<Self> {
+        if let Some(max) = options.max_file_size {
+            if data.len() > max {
+                return Err(ParseError::LimitExceeded(format!(
+                    "file of {} bytes exceeds {}",
+                    data.len(),
+                    max
+                )));
+            }
+        }
+        let mut header = read_header(data, options)?;
+        log::debug!("JWW version {}, paper size {}", header.version, header.paper_size);
+
+        // エンティティリスト開始位置を探索
+        let offset = find_entity_list_offset(data, header.version, header.end, header.caps)
+            .ok_or_else(|| ParseError::Other("could not find entity list".to_string()))?;
+        log::debug!("entity list found at byte {}", offset);
+        if header.end < offset {
+            header.sxf_colors = read_sxf_colors(&data[header.end..offset], header.caps);
+        }
+
+        let mut reader = Reader::new(&data[offset..]);
+        reader.set_max_string_length(options.max_string_length);
+        reader.set_strict_encoding(options.encoding == EncodingPolicy::Strict);
+        let count = reader.read_word()? as usize;
+        if let Some(max) = options.max_entities {
+            if count > max {
+                return Err(ParseError::LimitExceeded(format!(
+                    "{} entities exceeds {}",
+                    count, max
+                )));
+            }
+        }
+
+        Ok(Self {
+            header,
+            reader,
+            offset,
+            count,
+            index: 0,
+            pid_to_class: std::collections::HashMap::new(),
+            next_pid: 1,
+            entities: Vec::with_capacity(count),
+            lenient: options.lenient,
+            record_spans: options.record_spans,
+            preserve_unknown_classes: options.preserve_unknown_classes,
+        })
+    }
+
+    /// 最大 `max_entities` 件のエンティティを読み取る
+    ///
+    /// すべてのエンティティを読み終えた場合は `true` を返す。
+    pub fn step(&mut self, max_entities: usize) -> Result<bool> {
+        let end = self.count.min(self.index.saturating_add(max_entities));
+        while self.index < end {
+            let index = self.index;
+            let start = self.offset + self.reader.bytes_read() as usize;
+            match parse_entity_with_pid_tracking(
+                &mut self.reader,
+                self.header.caps,
+                self.preserve_unknown_classes,
+                &mut self.pid_to_class,
+                &mut self.next_pid,
+            ) {
+                Ok(Some(mut entity)) => {
+                    if self.record_spans {
+                        let end = self.offset + self.reader.bytes_read() as usize;
+                        entity.base_mut().source = Some(SourceSpan {
+                            offset: start,
+                            length: end - start,
+                        });
+                    }
+                    let unknown = matches!(entity, Entity::Unknown(_));
+                    self.entities.push(entity);
+                    if unknown {
+                        // 未知のエンティティがファイルの残りを保持しているため、ここで終える
+                        self.index = self.count;
+                        break;
+                    }
+                }
+                Ok(None) => {} // Nullオブジェクトはスキップ
+                Err(e) if self.lenient => {
+                    log::warn!(
+                        "stopped reading entities at #{} (byte {}): {}",
+                        index,
+                        start,
+                        e
+                    );
+                    self.index = self.count;
+                    break;
+                }
+                Err(e) => {
+                    log::debug!("failed to read entity #{} at byte {}: {}", index, start, e);
+                    return Err(ParseError::Entity {
+                        index,
+                        offset: start,
+                        source: Box::new(e),
+                    });
+                }
+            }
+            self.index += 1;
+        }
+        Ok(self.index >= self.count)
+    }
+
+    /// (読み取り済みの件数, エンティティリストの件数) を返す
+    pub fn progress(&self) -> (usize, usize) {
+        (self.index, self.count)
+    }
+
+    /// 残りのエンティティを読み取り、ドキュメントを返す
+    pub fn finish(mut self) -> Result<Document<'a>> {
+        self.step(usize::MAX)?;
+        log::debug!("parsed {} entities", self.entities.len());
+
+        // TODO: ブロック定義のパース
+
+        Ok(Document {
+            version: self.header.version,
+            memo: self.header.memo,
+            paper_size: self.header.paper_size,
+            write_layer_group: self.header.write_layer_group,
+            layer_groups: self.header.layer_groups,
+            entities: self.entities,
+            block_defs: Vec::new(),
+            sxf_colors: self.header.sxf_colors,
+            cache: Default::default(),
+        })
+    }
+}
+
+/// エンティティを読み取らずに図面情報だけを取得する
+///
+/// ヘッダーとレイヤ情報、エンティティリストの件数のみを読むため、
+/// 大きなファイルでも [`parse`] より高速に動作する。
+///
+/// # エラー
+/// - 無効なシグネチャ
+/// - エンティティリストが見つからない
+/// - IOエラー
+pub fn probe(data: &[u8]) -> Result<DocumentInfo> {
+    let header = read_header(data, &ParseOptions::default())?;
+    let entity_list_offset = find_entity_list_offset(data, header.version, header.end, header.caps)
+        .ok_or_else(|| ParseError::Other("could not find entity list".to_string()))?;
+    let entity_count = Reader::new(&data[entity_list_offset..]).read_word()? as usize;
+
+    Ok(DocumentInfo {
+        version: header.version,
+        memo: header.memo.into_owned(),
+        paper_size: header.paper_size,
+        write_layer_group: header.write_layer_group,
+        layer_groups: header.layer_groups,
+        entity_count,
+    })
+}
+
+/// エンティティリストより前のヘッダー情報
+struct Header<'a> {
+    version: u32,
+    caps: Capabilities,
+    memo: Cow<'a, str>,
+    paper_size: u32,
+    write_layer_group: u32,
+    layer_groups: [LayerGroup; 16],
+    /// レイヤグループの設定の直後のファイル先頭からのバイト位置
+    end: usize,
+    /// SXF対応拡張線色の画面表示色 ([`read_sxf_colors`])
+    sxf_colors: Vec<Rgb>,
+}
+
+/// シグネチャを検証し、ヘッダーとレイヤ情報を読み取る
+fn read_header<'a>(data: &'a [u8], options: &ParseOptions) -> Result<Header<'a>> {
     // シグネチャ検証
     if data.len() < 8 || &data[0..8] != b"JwwData." {
         return Err(ParseError::InvalidSignature);
     }
 
     let mut reader = Reader::new(&data[8..]);
+    reader.set_max_string_length(options.max_string_length);
+    reader.set_strict_encoding(options.encoding == EncodingPolicy::Strict);
 
-    // バージョン読み取り
+    // バージョン読み取り (対応していないバージョンはエンティティの読み取りがずれるため先に拒否する)
     let version = reader.read_dword()?;
+    let caps = Capabilities::of(version)?;
 
     // ヘッダー情報読み取り
     let memo = reader.read_cstring()?;
@@ -71,19 +344,7 @@ pub fn parse(data: &[u8]) -> Result<Document> {
         };
     }
 
-    // エンティティリスト開始位置を探索
-    let entity_list_offset = find_entity_list_offset(data, version);
-    let entity_list_offset = match entity_list_offset {
-        Some(offset) => offset,
-        None => return Err(ParseError::Other("could not find entity list".to_string())),
-    };
-
-    // エンティティをパース
-    let entity_data = &data[entity_list_offset..];
-    let mut reader2 = Reader::new(entity_data);
-    let entities = parse_entity_list(&mut reader2, version)?;
-
-    // TODO: ブロック定義のパース
+    let end = 8 + reader.bytes_read() as usize;
 
     // レイヤー名の設定（デフォルト名を使用）
     for g_lay in 0..16 {
@@ -97,19 +358,201 @@ pub fn parse(data: &[u8]) -> Result<Document> {
         }
     }
 
-    Ok(Document {
+    Ok(Header {
         version,
+        caps,
         memo,
         paper_size,
         write_layer_group,
         layer_groups,
-        entities,
-        block_defs: Vec::new(),
+        end,
+        sxf_colors: Vec::new(),
     })
 }
 
+/// SXF対応拡張線色の数 (添字0-256)
+pub(crate) const SXF_COLOR_COUNT: usize = 257;
+
+/// SXF対応拡張線種の数
+pub(crate) const SXF_LINE_TYPE_COUNT: usize = 33;
+
+/// レイヤグループの設定からエンティティリストまでのヘッダーを読み、SXF対応拡張線色の
+/// 画面表示色を返す
+///
+/// `data` はレイヤグループの設定の直後からエンティティリストの直前まで。
+/// 線色の定義より前の項目 (印刷・目盛の設定、レイヤ名、線種のパターンなど) は読み飛ばす。
+/// 定義を持たないバージョンや、読み取った値が色として不正な場合 (項目の位置がずれている場合) は
+/// 空を返す。
+fn read_sxf_colors(data: &[u8], caps: Capabilities) -> Vec<Rgb> {
+    if !caps.sxf_colors {
+        return Vec::new();
+    }
+    match read_sxf_color_table(&mut Reader::new(data)) {
+        Ok(Some(colors)) => colors,
+        Ok(None) => {
+            log::debug!("ignoring SXF color definitions with invalid values");
+            Vec::new()
+        }
+        Err(e) => {
+            log::debug!("could not read SXF color definitions: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// 色 (COLORREF) の上位バイトが0かどうか
+fn is_colorref(color: u32) -> bool {
+    color >> 24 == 0
+}
+
+/// 線幅として妥当な値かどうか
+fn is_pen_width(width: u32) -> bool {
+    width < 0x1_0000
+}
+
+/// Ver.4.20以降のヘッダーをSXF対応拡張線色の定義まで読む
+///
+/// 色 (COLORREF) の上位バイトが0でないか、線幅が大きすぎる場合は `None` を返す。
+fn read_sxf_color_table(reader: &mut Reader<&[u8]>) -> Result<Option<Vec<Rgb>>> {
+    skip_to_pen_colors(reader)?;
+
+    // 線色0-9の画面表示色・線幅
+    for _ in 0..10 {
+        let color = reader.read_dword()?;
+        let width = reader.read_dword()?;
+        if !is_colorref(color) || !is_pen_width(width) {
+            return Ok(None);
+        }
+    }
+    skip_pen_settings(reader)?;
+
+    // SXF対応拡張線色の画面表示色・線幅
+    let mut colors = Vec::with_capacity(SXF_COLOR_COUNT);
+    for _ in 0..SXF_COLOR_COUNT {
+        let color = reader.read_dword()?;
+        let width = reader.read_dword()?;
+        if !is_colorref(color) || !is_pen_width(width) {
+            return Ok(None);
+        }
+        colors.push(Rgb::from_colorref(color));
+    }
+    Ok(Some(colors))
+}
+
+/// レイヤグループの設定の直後から、線色0-9の画面表示色の直前までを読み飛ばす
+fn skip_to_pen_colors(reader: &mut Reader<&[u8]>) -> Result<()> {
+    // ダミー (14)、寸法の設定 (5)、ダミー、線描画の最大幅
+    reader.skip(21 * 4)?;
+    // プリンター出力の原点・倍率、回転・基準点、目盛の設定モード・最小間隔・間隔・基準点
+    reader.skip(3 * 8 + 2 * 4 + 5 * 8)?;
+    // レイヤ名 (16×16)、レイヤグループ名 (16)
+    for _ in 0..16 * 16 + 16 {
+        reader.read_cstring()?;
+    }
+    // 日影・天空図の条件、2.5Dの計算単位
+    reader.skip(2 * 8 + 4 + 8 + 2 * 8 + 4)?;
+    // 保存時の画面倍率・原点、範囲記憶の倍率・基準点
+    reader.skip(6 * 8)?;
+    // マークジャンプの倍率・基準点・レイヤグループ (8)
+    reader.skip(8 * (3 * 8 + 4))?;
+    // ダミー (3)、文字の描画状態
+    reader.skip(3 * (3 * 8 + 4) + 8 + 4)?;
+    // 複線間隔 (10)、両側複線の間隔
+    reader.skip(11 * 8)
+}
+
+/// 線色0-9のプリンター出力色から、SXF対応拡張線色の定義の直前までを読み飛ばす
+fn skip_pen_settings(reader: &mut Reader<&[u8]>) -> Result<()> {
+    // 線色0-9のプリンター出力色・線幅・実点半径
+    reader.skip(10 * (4 + 4 + 8))?;
+    // 線種2-9、ランダム線1-5、倍長線種6-9のパターン
+    reader.skip(8 * 4 * 4 + 5 * 5 * 4 + 4 * 4 * 4)?;
+    // 実点・描画順・印刷の設定 (11)、作図時間、2.5Dの視点の有無
+    reader.skip(13 * 4)?;
+    // 2.5Dの視点 (8)、線の長さ・矩形寸法・円の半径の最終値 (4)
+    reader.skip(12 * 8)?;
+    // ソリッドを任意色で書くフラグと既定の色
+    reader.skip(2 * 4)
+}
+
+/// SXF対応拡張線色のプリンター出力色から、SXF対応拡張線種の定義の最後までを読み飛ばす
+fn skip_sxf_settings(reader: &mut Reader<&[u8]>) -> Result<()> {
+    // SXF対応拡張線色のプリンター出力色・線幅・実点半径
+    reader.skip(SXF_COLOR_COUNT * (4 + 4 + 8))?;
+    // SXF対応拡張線種のパターン
+    reader.skip(SXF_LINE_TYPE_COUNT * 4 * 4)?;
+    // SXF対応拡張線色の名前 (SXF色番号1-256)
+    for _ in 1..SXF_COLOR_COUNT {
+        reader.read_cstring()?;
+    }
+    // SXF対応拡張線種の名前・セグメント数・ピッチ (10)
+    for _ in 0..SXF_LINE_TYPE_COUNT {
+        reader.read_cstring()?;
+        reader.skip(4 + 10 * 8)?;
+    }
+    Ok(())
+}
+
+/// 文字種1-10の設定から、エンティティリストの直前までを読み飛ばす
+fn skip_text_settings(reader: &mut Reader<&[u8]>) -> Result<()> {
+    // 文字種1-10の幅・高さ・間隔・色
+    reader.skip(10 * (3 * 8 + 4))?;
+    // 書込み文字の幅・高さ・間隔・色・文字種
+    reader.skip(3 * 8 + 2 * 4)?;
+    // 文字位置整理の行間・文字数
+    reader.skip(2 * 8)?;
+    // 文字基準点のずれ位置を使うフラグ、横方向 (3)・縦方向 (3) のずれ位置
+    reader.skip(4 + 6 * 8)
+}
+
+/// ヘッダーの項目を順に読み飛ばし、エンティティリストの位置を返す
+///
+/// `header_end` はレイヤグループの設定の直後のファイル先頭からのバイト位置。
+fn skip_header_settings(data: &[u8], header_end: usize, caps: Capabilities) -> Result<usize> {
+    let mut reader = Reader::new(data.get(header_end..).unwrap_or_default());
+    skip_to_pen_colors(&mut reader)?;
+    reader.skip(10 * (4 + 4))?;
+    skip_pen_settings(&mut reader)?;
+    if caps.sxf_colors {
+        reader.skip(SXF_COLOR_COUNT * (4 + 4))?;
+        skip_sxf_settings(&mut reader)?;
+    }
+    skip_text_settings(&mut reader)?;
+    Ok(header_end + reader.bytes_read() as usize)
+}
+
 /// エンティティリストの開始位置を探索する
-fn find_entity_list_offset(data: &[u8], version: u32) -> Option<usize> {
+///
+/// 最初のエンティティのクラス定義を探し、その直前の件数の位置を返す。エンティティがない場合
+/// (クラス定義がないか、最初のクラス定義がブロック定義 `CDataList` の場合) は、
+/// レイヤグループの設定の直後 (`header_end`) からヘッダーの項目を読み飛ばした位置に
+/// 件数0のエンティティリストがあれば、その位置を返す。
+fn find_entity_list_offset(
+    data: &[u8],
+    version: u32,
+    header_end: usize,
+    caps: Capabilities,
+) -> Option<usize> {
+    let block_list = match find_first_class_definition(data, version) {
+        Some((offset, name)) if name != b"CDataList" => return Some(offset),
+        Some((offset, _)) => Some(offset),
+        None => None,
+    };
+
+    let offset = skip_header_settings(data, header_end, caps).ok()?;
+    let mut reader = Reader::new(&data[offset..]);
+    if reader.read_word().ok()? != 0 {
+        return None;
+    }
+    // 直後はブロック定義リストの件数
+    match block_list {
+        Some(block_list) => (block_list == offset + 2).then_some(offset),
+        None => (reader.read_word().ok()? == 0).then_some(offset),
+    }
+}
+
+/// 最初のクラス定義を探し、(直前の件数の位置, クラス名) を返す
+fn find_first_class_definition(data: &[u8], version: u32) -> Option<(usize, &[u8])> {
     let schema_bytes = [version as u8, (version >> 8) as u8];
 
     for i in 100..data.len().saturating_sub(20) {
@@ -119,12 +562,12 @@ fn find_entity_list_offset(data: &[u8], version: u32) -> Option<usize> {
             if data[i + 2] == schema_bytes[0] && data[i + 3] == schema_bytes[1] {
                 // クラス名長さを取得
                 let name_len = (data[i + 4] as u16) | ((data[i + 5] as u16) << 8);
-                if name_len >= 8 && name_len <= 20 && i + 6 + name_len as usize <= data.len() {
+                if (8..=20).contains(&name_len) && i + 6 + name_len as usize <= data.len() {
                     let class_name = &data[i + 6..i + 6 + name_len as usize];
                     if class_name.starts_with(b"CData") {
-                        // 最初のエンティティクラス定義が見つかった
+                        // 最初のクラス定義が見つかった
                         // カウントWORDは直前（2バイト前）
-                        return Some(i.saturating_sub(2));
+                        return Some((i.saturating_sub(2), class_name));
                     }
                 }
             }
@@ -134,34 +577,14 @@ fn find_entity_list_offset(data: &[u8], version: u32) -> Option<usize> {
     None
 }
 
-/// エンティティリストをパースする
-fn parse_entity_list<R: std::io::Read>(reader: &mut Reader<R>, version: u32) -> Result<Vec<Entity>> {
-    let count = reader.read_word()? as u32;
-
-    let mut entities = Vec::with_capacity(count as usize);
-
-    // MFC CArchive PIDトラッキング
-    let mut pid_to_class: std::collections::HashMap<u32, String> = std::collections::HashMap::new();
-    let mut next_pid: u32 = 1;
-
-    for _ in 0..count {
-        match parse_entity_with_pid_tracking(reader, version, &mut pid_to_class, &mut next_pid) {
-            Ok(Some(entity)) => entities.push(entity),
-            Ok(None) => {} // Nullオブジェクトはスキップ
-            Err(e) => return Err(e),
-        }
-    }
-
-    Ok(entities)
-}
-
 /// PIDトラッキング付きでエンティティをパースする
-fn parse_entity_with_pid_tracking<R: std::io::Read>(
-    reader: &mut Reader<R>,
-    version: u32,
+fn parse_entity_with_pid_tracking<'a>(
+    reader: &mut Reader<&'a [u8]>,
+    caps: Capabilities,
+    preserve_unknown_classes: bool,
     pid_to_class: &mut std::collections::HashMap<u32, String>,
     next_pid: &mut u32,
-) -> Result<Option<Entity>> {
+) -> Result<Option<Entity<'a>>> {
     let class_id = reader.read_word()?;
 
     let class_name = match class_id {
@@ -194,33 +617,23 @@ fn parse_entity_with_pid_tracking<R: std::io::Read>(
     // クラス名に応じてエンティティをパース
     let entity = match class_name.as_str() {
         "CDataSen" => {
-            let base = parse_entity_base(reader, version)?;
-            let start_x = reader.read_double()?;
-            let start_y = reader.read_double()?;
-            let end_x = reader.read_double()?;
-            let end_y = reader.read_double()?;
-            Some(Entity::Line(Line {
-                base,
-                start_x,
-                start_y,
-                end_x,
-                end_y,
-            }))
+            let base = parse_entity_base(reader, caps)?;
+            let start = reader.read_point()?;
+            let end = reader.read_point()?;
+            Some(Entity::Line(Line { base, start, end }))
         }
         "CDataEnko" => {
-            let base = parse_entity_base(reader, version)?;
-            let center_x = reader.read_double()?;
-            let center_y = reader.read_double()?;
+            let base = parse_entity_base(reader, caps)?;
+            let center = reader.read_point()?;
             let radius = reader.read_double()?;
-            let start_angle = reader.read_double()?;
-            let arc_angle = reader.read_double()?;
-            let tilt_angle = reader.read_double()?;
+            let start_angle = Angle::from_radians(reader.read_double()?);
+            let arc_angle = Angle::from_radians(reader.read_double()?);
+            let tilt_angle = Angle::from_radians(reader.read_double()?);
             let flatness = reader.read_double()?;
             let full_circle = reader.read_dword()?;
             Some(Entity::Arc(Arc {
                 base,
-                center_x,
-                center_y,
+                center,
                 radius,
                 start_angle,
                 arc_angle,
@@ -230,24 +643,22 @@ fn parse_entity_with_pid_tracking<R: std::io::Read>(
             }))
         }
         "CDataTen" => {
-            let base = parse_entity_base(reader, version)?;
-            let x = reader.read_double()?;
-            let y = reader.read_double()?;
+            let base = parse_entity_base(reader, caps)?;
+            let position = reader.read_point()?;
             let tmp = reader.read_dword()?;
             let is_temporary = tmp != 0;
 
             let mut code = 0;
-            let mut angle = 0.0;
+            let mut angle = Angle::ZERO;
             let mut scale = 1.0;
             if base.pen_style == 100 {
                 code = reader.read_dword()?;
-                angle = reader.read_double()?;
+                angle = Angle::from_radians(reader.read_double()?);
                 scale = reader.read_double()?;
             }
             Some(Entity::Point(Point {
                 base,
-                x,
-                y,
+                position,
                 is_temporary,
                 code,
                 angle,
@@ -255,24 +666,20 @@ fn parse_entity_with_pid_tracking<R: std::io::Read>(
             }))
         }
         "CDataMoji" => {
-            let base = parse_entity_base(reader, version)?;
-            let start_x = reader.read_double()?;
-            let start_y = reader.read_double()?;
-            let end_x = reader.read_double()?;
-            let end_y = reader.read_double()?;
+            let base = parse_entity_base(reader, caps)?;
+            let start = reader.read_point()?;
+            let end = reader.read_point()?;
             let text_type = reader.read_dword()?;
             let size_x = reader.read_double()?;
             let size_y = reader.read_double()?;
             let spacing = reader.read_double()?;
-            let angle = reader.read_double()?;
+            let angle = Angle::from_degrees(reader.read_double()?);
             let font_name = reader.read_cstring()?;
             let content = reader.read_cstring()?;
             Some(Entity::Text(Text {
                 base,
-                start_x,
-                start_y,
-                end_x,
-                end_y,
+                start,
+                end,
                 text_type,
                 size_x,
                 size_y,
@@ -283,15 +690,11 @@ fn parse_entity_with_pid_tracking<R: std::io::Read>(
             }))
         }
         "CDataSolid" => {
-            let base = parse_entity_base(reader, version)?;
-            let point1_x = reader.read_double()?;
-            let point1_y = reader.read_double()?;
-            let point4_x = reader.read_double()?;
-            let point4_y = reader.read_double()?;
-            let point2_x = reader.read_double()?;
-            let point2_y = reader.read_double()?;
-            let point3_x = reader.read_double()?;
-            let point3_y = reader.read_double()?;
+            let base = parse_entity_base(reader, caps)?;
+            let point1 = reader.read_point()?;
+            let point4 = reader.read_point()?;
+            let point2 = reader.read_point()?;
+            let point3 = reader.read_point()?;
 
             let mut color = 0;
             if base.pen_color == 10 {
@@ -299,29 +702,23 @@ fn parse_entity_with_pid_tracking<R: std::io::Read>(
             }
             Some(Entity::Solid(Solid {
                 base,
-                point1_x,
-                point1_y,
-                point2_x,
-                point2_y,
-                point3_x,
-                point3_y,
-                point4_x,
-                point4_y,
+                point1,
+                point2,
+                point3,
+                point4,
                 color,
             }))
         }
         "CDataBlock" => {
-            let base = parse_entity_base(reader, version)?;
-            let ref_x = reader.read_double()?;
-            let ref_y = reader.read_double()?;
+            let base = parse_entity_base(reader, caps)?;
+            let reference = reader.read_point()?;
             let scale_x = reader.read_double()?;
             let scale_y = reader.read_double()?;
-            let rotation = reader.read_double()?;
+            let rotation = Angle::from_radians(reader.read_double()?);
             let def_number = reader.read_dword()?;
             Some(Entity::Block(Block {
                 base,
-                ref_x,
-                ref_y,
+                reference,
                 scale_x,
                 scale_y,
                 rotation,
@@ -329,47 +726,40 @@ fn parse_entity_with_pid_tracking<R: std::io::Read>(
             }))
         }
         "CDataSunpou" => {
-            // 寸法エンティティ - 簡易的に線として扱う
-            let _base = parse_entity_base(reader, version)?;
-            // 線メンバーをパース
-            let _line_base = parse_entity_base(reader, version)?;
-            let _start_x = reader.read_double()?;
-            let _start_y = reader.read_double()?;
-            let _end_x = reader.read_double()?;
-            let _end_y = reader.read_double()?;
-            // 文字メンバーをパース（スキップ）
-            let _text_base = parse_entity_base(reader, version)?;
-            let _text_start_x = reader.read_double()?;
-            let _text_start_y = reader.read_double()?;
-            let _text_end_x = reader.read_double()?;
-            let _text_end_y = reader.read_double()?;
-            let _text_type = reader.read_dword()?;
-            let _text_size_x = reader.read_double()?;
-            let _text_size_y = reader.read_double()?;
-            let _text_spacing = reader.read_double()?;
-            let _text_angle = reader.read_double()?;
-            let _text_font_name = reader.read_cstring()?;
-            let _text_content = reader.read_cstring()?;
+            let base = parse_entity_base(reader, caps)?;
+            let line = parse_line_member(reader, caps)?;
+            let text = parse_text_member(reader, caps)?;
 
             // Ver 4.20+ の追加データ
-            if version >= 420 {
-                let _sxf_mode = reader.read_word()?;
+            let mut sxf_mode = 0;
+            let mut extension_lines = Vec::new();
+            let mut end_points = Vec::new();
+            if caps.sxf_dimensions {
+                sxf_mode = reader.read_word()?;
                 for _ in 0..2 {
-                    let _ = parse_entity_base(reader, version)?;
-                    let _ = reader.read_double()?;
-                    let _ = reader.read_double()?;
-                    let _ = reader.read_double()?;
-                    let _ = reader.read_double()?;
+                    extension_lines.push(parse_line_member(reader, caps)?);
                 }
                 for _ in 0..4 {
-                    let _ = parse_entity_base(reader, version)?;
-                    let _ = reader.read_double()?;
-                    let _ = reader.read_double()?;
-                    let _ = reader.read_dword()?;
+                    end_points.push(parse_point_member(reader, caps)?);
                 }
             }
-            // 寸法はスキップ
-            None
+            Some(Entity::Dimension(Dimension {
+                base,
+                line,
+                text,
+                sxf_mode,
+                extension_lines,
+                end_points,
+            }))
+        }
+        _ if preserve_unknown_classes && class_name.starts_with("CData") => {
+            let base = parse_entity_base(reader, caps)?;
+            let data = Cow::Borrowed(reader.read_rest());
+            Some(Entity::Unknown(Unknown {
+                base,
+                class_name,
+                data,
+            }))
         }
         _ => return Err(ParseError::UnknownEntityClass(class_name)),
     };
@@ -378,13 +768,55 @@ fn parse_entity_with_pid_tracking<R: std::io::Read>(
     Ok(entity)
 }
 
+/// 寸法の線メンバー (CDataSen) をパースする
+fn parse_line_member<R: std::io::Read>(reader: &mut Reader<R>, caps: Capabilities) -> Result<Line> {
+    let base = parse_entity_base(reader, caps)?;
+    Ok(Line {
+        base,
+        start: reader.read_point()?,
+        end: reader.read_point()?,
+    })
+}
+
+/// 寸法の文字メンバー (CDataMoji) をパースする
+fn parse_text_member<'a>(reader: &mut Reader<&'a [u8]>, caps: Capabilities) -> Result<Text<'a>> {
+    let base = parse_entity_base(reader, caps)?;
+    Ok(Text {
+        base,
+        start: reader.read_point()?,
+        end: reader.read_point()?,
+        text_type: reader.read_dword()?,
+        size_x: reader.read_double()?,
+        size_y: reader.read_double()?,
+        spacing: reader.read_double()?,
+        angle: Angle::from_degrees(reader.read_double()?),
+        font_name: reader.read_cstring()?,
+        content: reader.read_cstring()?,
+    })
+}
+
+/// 寸法の点メンバー (CDataTen) をパースする
+///
+/// メンバーとして埋め込まれた点は線種に関わらず追加フィールドを持たない。
+fn parse_point_member<R: std::io::Read>(reader: &mut Reader<R>, caps: Capabilities) -> Result<Point> {
+    let base = parse_entity_base(reader, caps)?;
+    Ok(Point {
+        base,
+        position: reader.read_point()?,
+        is_temporary: reader.read_dword()? != 0,
+        code: 0,
+        angle: Angle::ZERO,
+        scale: 1.0,
+    })
+}
+
 /// エンティティ基本属性をパースする
-fn parse_entity_base<R: std::io::Read>(reader: &mut Reader<R>, version: u32) -> Result<EntityBase> {
+fn parse_entity_base<R: std::io::Read>(reader: &mut Reader<R>, caps: Capabilities) -> Result<EntityBase> {
     let group = reader.read_dword()?;
     let pen_style = reader.read_byte()?;
     let pen_color = reader.read_word()?;
 
-    let pen_width = if version >= 351 {
+    let pen_width = if caps.pen_width {
         reader.read_word()?
     } else {
         0
@@ -402,5 +834,6 @@ fn parse_entity_base<R: std::io::Read>(reader: &mut Reader<R>, version: u32) ->
         layer,
         layer_group,
         flag,
+        source: None,
     })
 }
@@ -0,0 +1,155 @@
+//! 文字列の表記の正規化
+//!
+//! JWWの文字列はShift-JIS (CP932) で保存されるため、全角英数字と半角英数字、
+//! 半角カナと全角カナ、波ダッシュ (`〜` と `～`) などの表記の揺れが混在しやすい。
+//! 検索やフォント名の照合の前にこれらをそろえる。
+
+use std::borrow::Cow;
+
+/// 半角カナ (U+FF61〜U+FF9F) に対応する全角文字
+const HALF_WIDTH_KANA: [char; 63] = [
+    '。', '「', '」', '、', '・', 'ヲ', 'ァ', 'ィ', 'ゥ', 'ェ', 'ォ', 'ャ', 'ュ', 'ョ', 'ッ', 'ー',
+    'ア', 'イ', 'ウ', 'エ', 'オ', 'カ', 'キ', 'ク', 'ケ', 'コ', 'サ', 'シ', 'ス', 'セ', 'ソ', 'タ',
+    'チ', 'ツ', 'テ', 'ト', 'ナ', 'ニ', 'ヌ', 'ネ', 'ノ', 'ハ', 'ヒ', 'フ', 'ヘ', 'ホ', 'マ', 'ミ',
+    'ム', 'メ', 'モ', 'ヤ', 'ユ', 'ヨ', 'ラ', 'リ', 'ル', 'レ', 'ロ', 'ワ', 'ン', '゛', '゜',
+];
+
+/// 全角英数字・記号と全角スペースを半角に、半角カナを全角にそろえる
+///
+/// 半角カナの濁点・半濁点は直前の文字と合成する (`ｶﾞ` → `ガ`)。
+/// 変換する文字がない場合は複製しない。
+pub fn width(s: &str) -> Cow<'_, str> {
+    map(s, width_char)
+}
+
+/// Windows (CP932) とJISで異なる文字をCP932の文字にそろえる
+///
+/// JWWファイルの文字列はCP932の文字 (`～` U+FF5E など) として読み込まれる。
+/// JIS由来の文字 (`〜` U+301C など) はShift-JISで書き出せないため、
+/// 他の形式から取り込んだ文字列もこの形にそろえる。
+pub fn wave_dash(s: &str) -> Cow<'_, str> {
+    map(s, |c, _| wave_dash_char(c).map(|c| (c, 1)))
+}
+
+/// 検索用に表記をそろえる ([`wave_dash`] と [`width`] を順に適用する)
+pub fn text(s: &str) -> Cow<'_, str> {
+    match wave_dash(s) {
+        Cow::Borrowed(s) => width(s),
+        Cow::Owned(s) => Cow::Owned(width(&s).into_owned()),
+    }
+}
+
+/// [`text`] で正規化した文字列と、正規化後の各バイト位置に対応する元の文字列のバイト位置
+///
+/// 対応表は正規化後の文字列の長さ + 1 の要素を持ち、末尾は元の文字列の長さになる。
+pub(crate) fn text_with_offsets(s: &str) -> (String, Vec<usize>) {
+    let mut normalized = String::with_capacity(s.len());
+    let mut offsets = Vec::with_capacity(s.len() + 1);
+    let mut chars = s.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        let c = wave_dash_char(c).unwrap_or(c);
+        let next = chars.peek().map(|&(_, c)| c);
+        let (mapped, consumed) = width_char(c, next).unwrap_or((c, 1));
+        if consumed == 2 {
+            chars.next();
+        }
+        normalized.push(mapped);
+        offsets.resize(normalized.len(), offset);
+    }
+    offsets.push(s.len());
+    (normalized, offsets)
+}
+
+/// フォント名を照合用の表記 (英語名) にそろえる
+///
+/// 全角・半角をそろえたうえで、Windowsの日本語フォントの日本語名を英語名に置き換える
+/// (`ＭＳ ゴシック` → `MS Gothic`)。縦書きを表す先頭の `@` は残す。
+pub fn font_name(name: &str) -> Cow<'_, str> {
+    let (vertical, face) = match name.strip_prefix('@') {
+        Some(face) => ("@", face),
+        None => ("", name),
+    };
+    let face = width(face.trim());
+    let english = match face.as_ref() {
+        "MS ゴシック" => "MS Gothic",
+        "MS Pゴシック" => "MS PGothic",
+        "MS 明朝" => "MS Mincho",
+        "MS P明朝" => "MS PMincho",
+        "メイリオ" => "Meiryo",
+        "游ゴシック" => "Yu Gothic",
+        "游明朝" => "Yu Mincho",
+        _ => {
+            return match face {
+                Cow::Borrowed(face) if face.len() + vertical.len() == name.len() => {
+                    Cow::Borrowed(name)
+                }
+                face => Cow::Owned(format!("{}{}", vertical, face)),
+            }
+        }
+    };
+    Cow::Owned(format!("{}{}", vertical, english))
+}
+
+/// 1文字 (と次の文字) を変換する関数で文字列を変換する
+///
+/// `f` は変換後の文字と消費した文字数 (1か2) を返す。変換しない場合は `None`。
+fn map(s: &str, f: impl Fn(char, Option<char>) -> Option<(char, usize)>) -> Cow<'_, str> {
+    let mut out: Option<String> = None;
+    let mut chars = s.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        let next = chars.peek().map(|&(_, c)| c);
+        match f(c, next) {
+            Some((mapped, consumed)) => {
+                let out = out.get_or_insert_with(|| s[..offset].to_string());
+                out.push(mapped);
+                if consumed == 2 {
+                    chars.next();
+                }
+            }
+            None => {
+                if let Some(out) = &mut out {
+                    out.push(c);
+                }
+            }
+        }
+    }
+    out.map_or(Cow::Borrowed(s), Cow::Owned)
+}
+
+/// [`wave_dash`] の1文字分の変換
+fn wave_dash_char(c: char) -> Option<char> {
+    match c {
+        '\u{301C}' => Some('\u{FF5E}'), // 波ダッシュ → 全角チルダ
+        '\u{2016}' => Some('\u{2225}'), // 双柱 → 平行記号
+        '\u{2212}' => Some('\u{FF0D}'), // マイナス記号 → 全角ハイフンマイナス
+        '\u{00A2}' => Some('\u{FFE0}'), // セント記号
+        '\u{00A3}' => Some('\u{FFE1}'), // ポンド記号
+        '\u{00AC}' => Some('\u{FFE2}'), // 否定記号
+        _ => None,
+    }
+}
+
+/// [`width`] の1文字分の変換
+fn width_char(c: char, next: Option<char>) -> Option<(char, usize)> {
+    match c {
+        '\u{3000}' => Some((' ', 1)),
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).map(|c| (c, 1)),
+        '\u{FF61}'..='\u{FF9F}' => {
+            let kana = HALF_WIDTH_KANA[(c as u32 - 0xFF61) as usize];
+            let voiced = match next {
+                Some('\u{FF9E}') => match c {
+                    'ｳ' => Some('ヴ'),
+                    'ｶ'..='ﾄ' | 'ﾊ'..='ﾎ' => char::from_u32(kana as u32 + 1),
+                    _ => None,
+                },
+                Some('\u{FF9F}') => match c {
+                    'ﾊ'..='ﾎ' => char::from_u32(kana as u32 + 2),
+                    _ => None,
+                },
+                _ => None,
+            };
+            Some(voiced.map_or((kana, 1), |voiced| (voiced, 2)))
+        }
+        _ => None,
+    }
+}
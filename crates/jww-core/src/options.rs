@@ -0,0 +1,44 @@
+//! パースオプション
+
+use serde::{Deserialize, Serialize};
+
+/// Shift-JIS文字列の扱い
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub enum EncodingPolicy {
+    /// 変換できないバイト列を置換文字 (U+FFFD) に置き換える
+    #[default]
+    Replace,
+    /// 変換できないバイト列があればエラーにする
+    Strict,
+}
+
+/// パースオプション
+///
+/// 制限値の `None` は無制限を表す。既定値は [`crate::parse`] と同じ動作。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+#[serde(default)]
+pub struct ParseOptions {
+    /// 読み取れないエンティティがあった場合に、それまでに読み取ったエンティティで
+    /// ドキュメントを返す
+    ///
+    /// エンティティの長さはファイルに記録されていないため、読み取りに失敗した
+    /// 位置より後のエンティティは読み取れない。
+    pub lenient: bool,
+    /// 入力データの最大バイト数
+    pub max_file_size: Option<usize>,
+    /// エンティティリストの最大件数
+    pub max_entities: Option<usize>,
+    /// 文字列1つの最大バイト数
+    pub max_string_length: Option<usize>,
+    /// Shift-JIS文字列の扱い
+    pub encoding: EncodingPolicy,
+    /// 各エンティティのファイル内のバイト範囲を [`crate::EntityBase::source`] に記録する
+    pub record_spans: bool,
+    /// 読み取りに対応していないクラスのエンティティを [`crate::Entity::Unknown`] として残す
+    ///
+    /// 指定しない場合は [`crate::ParseError::UnknownEntityClass`] になる。
+    /// 未知のエンティティより後のエンティティは読み取れない。
+    pub preserve_unknown_classes: bool,
+}
@@ -0,0 +1,42 @@
+//! 座標の型
+
+use serde::{Deserialize, Serialize};
+
+/// 2次元の座標
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct Point2D {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point2D {
+    /// 原点
+    pub const ORIGIN: Point2D = Point2D::new(0.0, 0.0);
+
+    pub const fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    /// 2点間の距離を返す
+    pub fn distance(self, other: Point2D) -> f64 {
+        (other.x - self.x).hypot(other.y - self.y)
+    }
+
+    /// 2点の中点を返す
+    pub fn midpoint(self, other: Point2D) -> Point2D {
+        Point2D::new((self.x + other.x) / 2.0, (self.y + other.y) / 2.0)
+    }
+}
+
+impl From<(f64, f64)> for Point2D {
+    fn from((x, y): (f64, f64)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl From<Point2D> for (f64, f64) {
+    fn from(point: Point2D) -> Self {
+        (point.x, point.y)
+    }
+}
@@ -1,6 +1,8 @@
+use std::borrow::Cow;
 use std::io::Read;
 use byteorder::{LittleEndian, ReadBytesExt};
 use crate::error::{ParseError, Result};
+use crate::point::Point2D;
 
 /// JWWバイナリデータリーダー
 ///
@@ -9,6 +11,8 @@ use crate::error::{ParseError, Result};
 pub struct Reader<R> {
     inner: R,
     bytes_read: u64,
+    max_string_length: Option<usize>,
+    strict_encoding: bool,
 }
 
 impl<R: Read> Reader<R> {
@@ -17,9 +21,21 @@ impl<R: Read> Reader<R> {
         Self {
             inner,
             bytes_read: 0,
+            max_string_length: None,
+            strict_encoding: false,
         }
     }
 
+    /// 文字列1つの最大バイト数を設定する (`None` は無制限)
+    pub fn set_max_string_length(&mut self, max: Option<usize>) {
+        self.max_string_length = max;
+    }
+
+    /// Shift-JISとして読み取れない文字列をエラーにするかどうかを設定する
+    pub fn set_strict_encoding(&mut self, strict: bool) {
+        self.strict_encoding = strict;
+    }
+
     /// シグネチャ "JwwData." を読み取って検証する
     pub fn read_signature(&mut self) -> Result<()> {
         let mut sig = [0u8; 8];
@@ -58,13 +74,30 @@ impl<R: Read> Reader<R> {
         Ok(val)
     }
 
-    /// MFC CString形式で文字列を読み取る
+    /// X・Y座標 (Double 2つ) を読み取る
+    pub fn read_point(&mut self) -> Result<Point2D> {
+        let x = self.read_double()?;
+        let y = self.read_double()?;
+        Ok(Point2D::new(x, y))
+    }
+
+    /// MFC CString形式で文字列を読み取る ([`Reader::read_cstring`] を参照)
+    ///
+    /// スライス以外から読み取る場合に使用する。文字列は常に複製される。
+    pub fn read_cstring_owned(&mut self) -> Result<String> {
+        let length = self.read_cstring_length()?;
+        let mut buf = vec![0u8; length];
+        self.read_exact(&mut buf)?;
+        Ok(self.decode(&buf)?.into_owned())
+    }
+
+    /// CStringの長さプレフィックスを読み取る
     ///
     /// 文字列フォーマット:
     /// - 長さ < 255: 1バイト長さプレフィックス
     /// - 長さ < 65535: 1バイト 0xFF マーカー + 2バイト長さ
     /// - それ以上: 1バイト 0xFF + 2バイト 0xFFFF + 4バイト長さ
-    pub fn read_cstring(&mut self) -> Result<String> {
+    fn read_cstring_length(&mut self) -> Result<usize> {
         let len_byte = self.read_byte()?;
 
         let length = if len_byte < 0xFF {
@@ -78,16 +111,30 @@ impl<R: Read> Reader<R> {
             }
         };
 
-        if length == 0 {
-            return Ok(String::new());
+        if let Some(max) = self.max_string_length {
+            if length as usize > max {
+                return Err(ParseError::LimitExceeded(format!(
+                    "string of {} bytes exceeds {}",
+                    length, max
+                )));
+            }
         }
+        Ok(length as usize)
+    }
 
-        let mut buf = vec![0u8; length as usize];
-        self.read_exact(&mut buf)?;
-
-        // Shift-JISからUTF-8に変換
-        let (utf8_str, ..) = encoding_rs::SHIFT_JIS.decode(&buf);
-        Ok(utf8_str.trim_end_matches('\0').to_string())
+    /// Shift-JISからUTF-8に変換し、末尾のNUL文字を取り除く (ASCIIのみの場合は借用する)
+    fn decode<'b>(&self, bytes: &'b [u8]) -> Result<Cow<'b, str>> {
+        let (decoded, _, had_errors) = encoding_rs::SHIFT_JIS.decode(bytes);
+        if had_errors && self.strict_encoding {
+            return Err(ParseError::InvalidEncoding);
+        }
+        Ok(match decoded {
+            Cow::Borrowed(s) => Cow::Borrowed(s.trim_end_matches('\0')),
+            Cow::Owned(mut s) => {
+                s.truncate(s.trim_end_matches('\0').len());
+                Cow::Owned(s)
+            }
+        })
     }
 
     /// 指定したバイト数だけスキップする
@@ -114,3 +161,26 @@ impl<R: Read> Reader<R> {
         self.inner
     }
 }
+
+impl<'a> Reader<&'a [u8]> {
+    /// MFC CString形式で文字列を読み取る
+    ///
+    /// 文字列がASCIIのみの場合は複製せずに入力データから借用する。
+    pub fn read_cstring(&mut self) -> Result<Cow<'a, str>> {
+        let length = self.read_cstring_length()?;
+        if length > self.inner.len() {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        let (bytes, rest) = self.inner.split_at(length);
+        self.inner = rest;
+        self.bytes_read += length as u64;
+        self.decode(bytes)
+    }
+
+    /// 残りのバイト列をすべて読み取る
+    pub fn read_rest(&mut self) -> &'a [u8] {
+        let rest = std::mem::take(&mut self.inner);
+        self.bytes_read += rest.len() as u64;
+        rest
+    }
+}
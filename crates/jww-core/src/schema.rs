@@ -0,0 +1,66 @@
+//! エンティティの座標のシリアライズ形式
+//!
+//! スキーマ1 (0.x) ではエンティティの座標を `start_x`・`start_y` のように
+//! 平坦なフィールドで表していた。スキーマ2では [`Point2D`] の入れ子
+//! (`"start": {"x": .., "y": ..}`) で出力し、読み込み時はどちらの形式も受け付ける。
+//!
+//! 座標のフィールドには `#[serde(flatten, with = "crate::schema::start")]` のように指定する。
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::point::Point2D;
+
+/// 座標のフィールド名と、スキーマ1でのX・Y座標のフィールド名からモジュールを定義する
+macro_rules! point_fields {
+    ($($name:ident: $x:literal, $y:literal;)*) => {
+        $(
+            pub(crate) mod $name {
+                use super::*;
+
+                #[derive(Serialize)]
+                struct Nested<'a> {
+                    $name: &'a Point2D,
+                }
+
+                #[derive(Deserialize)]
+                struct Either {
+                    $name: Option<Point2D>,
+                    #[serde(rename = $x)]
+                    x: Option<f64>,
+                    #[serde(rename = $y)]
+                    y: Option<f64>,
+                }
+
+                pub(crate) fn serialize<S: Serializer>(
+                    point: &Point2D,
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error> {
+                    Nested { $name: point }.serialize(serializer)
+                }
+
+                pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+                    deserializer: D,
+                ) -> Result<Point2D, D::Error> {
+                    match Either::deserialize(deserializer)? {
+                        Either { $name: Some(point), .. } => Ok(point),
+                        Either { x: Some(x), y: Some(y), .. } => Ok(Point2D::new(x, y)),
+                        _ => Err(D::Error::missing_field(stringify!($name))),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+point_fields! {
+    start: "start_x", "start_y";
+    end: "end_x", "end_y";
+    center: "center_x", "center_y";
+    position: "x", "y";
+    point1: "point1_x", "point1_y";
+    point2: "point2_x", "point2_y";
+    point3: "point3_x", "point3_y";
+    point4: "point4_x", "point4_y";
+    reference: "ref_x", "ref_y";
+}
@@ -0,0 +1,63 @@
+//! 文字列の検索
+
+use std::ops::Range;
+
+use crate::normalize;
+use crate::types::{Document, Entity, Text};
+
+/// 文字列検索の一致結果 (文字エンティティ1件分)
+#[derive(Debug, Clone)]
+pub struct TextMatch<'a> {
+    /// 対象のエンティティの番号 (`Document::entities` の添字)
+    pub entity_index: usize,
+    /// 一致した文字 (寸法の場合は寸法値)
+    pub text: &'a Text<'a>,
+    /// `text.content` 内の一致箇所 (バイト位置)
+    pub ranges: Vec<Range<usize>>,
+}
+
+impl Document<'_> {
+    /// 図面直下の文字と寸法値を検索する
+    ///
+    /// `find` は文字列内の一致箇所 (バイト位置の範囲) を返す関数で、正規表現などの
+    /// 照合方法は呼び出し側で選ぶ。一致箇所のない文字は結果に含まない。
+    /// ブロック内の文字は座標が挿入位置に依存するため対象外とする。
+    pub fn search_text<F>(&self, mut find: F) -> Vec<TextMatch<'_>>
+    where
+        F: FnMut(&str) -> Vec<Range<usize>>,
+    {
+        self.entities
+            .iter()
+            .enumerate()
+            .filter_map(|(entity_index, entity)| {
+                let text = match entity {
+                    Entity::Text(text) => text,
+                    Entity::Dimension(dim) => &dim.text,
+                    _ => return None,
+                };
+                let ranges = find(&text.content);
+                (!ranges.is_empty()).then_some(TextMatch {
+                    entity_index,
+                    text,
+                    ranges,
+                })
+            })
+            .collect()
+    }
+    /// 全角・半角や波ダッシュの違いを無視して文字と寸法値を検索する
+    ///
+    /// `find` には [`normalize::text`] で正規化した文字列を渡す。検索語も同じく正規化して
+    /// おく。一致箇所は正規化前の `text.content` のバイト位置で返す。
+    pub fn search_text_normalized<F>(&self, mut find: F) -> Vec<TextMatch<'_>>
+    where
+        F: FnMut(&str) -> Vec<Range<usize>>,
+    {
+        self.search_text(|content| {
+            let (normalized, offsets) = normalize::text_with_offsets(content);
+            find(&normalized)
+                .into_iter()
+                .map(|range| offsets[range.start]..offsets[range.end])
+                .collect()
+        })
+    }
+}
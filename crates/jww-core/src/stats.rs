@@ -0,0 +1,95 @@
+//! 図面の統計情報
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::bounds::BoundingBox;
+use crate::types::{Document, Entity};
+
+/// 図面の統計情報 ([`Document::statistics`])
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Statistics {
+    /// 図面直下のエンティティ数
+    pub entity_count: usize,
+    /// 種別 ([`Entity::type_name`]) ごとのエンティティ数
+    pub by_type: BTreeMap<String, usize>,
+    /// 文字と寸法値の数 (内容が空のものは含まない)
+    pub text_count: usize,
+    /// ブロック定義の数
+    pub block_def_count: usize,
+    /// 全エンティティの外接矩形 (エンティティがない場合は `None`)
+    pub extents: Option<BoundingBox>,
+    /// レイヤごとの統計 (エンティティがあるレイヤのみ、レイヤグループ・レイヤ順)
+    pub layers: Vec<LayerStatistics>,
+}
+
+/// レイヤごとの統計情報
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayerStatistics {
+    /// レイヤグループ番号 (0-15)
+    pub layer_group: u16,
+    /// レイヤ番号 (0-15)
+    pub layer: u16,
+    /// レイヤ名
+    pub name: String,
+    /// エンティティ数
+    pub entity_count: usize,
+    /// 種別ごとのエンティティ数
+    pub by_type: BTreeMap<String, usize>,
+    /// レイヤ内のエンティティの外接矩形
+    pub extents: Option<BoundingBox>,
+}
+
+impl Document<'_> {
+    /// 種別・レイヤごとのエンティティ数や図面範囲を集計する
+    ///
+    /// 集計対象は図面直下のエンティティで、ブロック定義内のエンティティは数えない。
+    /// 範囲はブロック挿入の参照先を含めて計算する。
+    pub fn statistics(&self) -> Statistics {
+        let mut stats = Statistics {
+            entity_count: self.entities.len(),
+            block_def_count: self.block_defs.len(),
+            extents: self.bounding_box(),
+            ..Default::default()
+        };
+        let mut layers: BTreeMap<(u16, u16), LayerStatistics> = BTreeMap::new();
+        for entity in &self.entities {
+            let type_name = entity.type_name();
+            *stats.by_type.entry(type_name.to_string()).or_default() += 1;
+            let has_text = match entity {
+                Entity::Text(text) => !text.content.is_empty(),
+                Entity::Dimension(dim) => !dim.text.content.is_empty(),
+                _ => false,
+            };
+            if has_text {
+                stats.text_count += 1;
+            }
+
+            let base = entity.base();
+            let layer = layers
+                .entry((base.layer_group, base.layer))
+                .or_insert_with(|| LayerStatistics {
+                    layer_group: base.layer_group,
+                    layer: base.layer,
+                    name: self
+                        .layer_groups
+                        .get(base.layer_group as usize)
+                        .and_then(|group| group.layers.get(base.layer as usize))
+                        .map(|layer| layer.name.clone())
+                        .unwrap_or_default(),
+                    entity_count: 0,
+                    by_type: BTreeMap::new(),
+                    extents: None,
+                });
+            layer.entity_count += 1;
+            *layer.by_type.entry(type_name.to_string()).or_default() += 1;
+        }
+        for (key, bb) in self.layer_bounding_boxes() {
+            if let Some(layer) = layers.get_mut(&key) {
+                layer.extents = Some(bb);
+            }
+        }
+        stats.layers = layers.into_values().collect();
+        stats
+    }
+}
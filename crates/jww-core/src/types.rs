@@ -1,13 +1,22 @@
+use std::borrow::Cow;
+
 use serde::{Deserialize, Serialize};
 
+use crate::angle::Angle;
+use crate::point::Point2D;
+
 /// JWWドキュメント全体を表す構造体
+///
+/// 文字列は可能な限り入力データから借用する ([`crate::parse`] を参照)。
+/// 入力データより長く保持する場合は [`Document::into_owned`] を使う。
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Document {
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct Document<'a> {
     /// JWWファイルフォーマットバージョン (例: 351 for Ver.3.51, 420 for Ver.4.20)
     pub version: u32,
 
     /// ファイルメモ/説明
-    pub memo: String,
+    pub memo: Cow<'a, str>,
 
     /// 用紙サイズ: 0-4でA0-A4、8で2A、9で3Aなど
     pub paper_size: u32,
@@ -19,16 +28,176 @@ pub struct Document {
     pub layer_groups: [LayerGroup; 16],
 
     /// 図面エンティティ（線、円弧、文字など）
-    pub entities: Vec<Entity>,
+    pub entities: Vec<Entity<'a>>,
 
     /// ブロック定義
-    pub block_defs: Vec<BlockDef>,
+    pub block_defs: Vec<BlockDef<'a>>,
+
+    /// SXF対応拡張線色の画面表示色 (Ver.4.20以降のヘッダー)
+    ///
+    /// 添字はSXF色番号 (線色番号 - [`crate::SXF_PEN_COLOR_OFFSET`]) で、添字0は使われない。
+    /// ヘッダーに定義がない場合や読み取れなかった場合は空。
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sxf_colors: Vec<crate::Rgb>,
+
+    /// 計算結果のキャッシュ ([`Document::entities_by_layer`] など)
+    #[serde(skip)]
+    pub cache: crate::index::DocumentCache,
+}
+
+impl Document<'_> {
+    /// 入力データから借用している文字列を複製し、入力データに依存しないドキュメントを返す
+    pub fn into_owned(self) -> Document<'static> {
+        Document {
+            version: self.version,
+            memo: Cow::Owned(self.memo.into_owned()),
+            paper_size: self.paper_size,
+            write_layer_group: self.write_layer_group,
+            layer_groups: self.layer_groups,
+            entities: self.entities.into_iter().map(Entity::into_owned).collect(),
+            block_defs: self.block_defs.into_iter().map(BlockDef::into_owned).collect(),
+            sxf_colors: self.sxf_colors,
+            cache: self.cache,
+        }
+    }
+
+    /// 用紙サイズ (横, 縦) をミリメートルで返す
+    ///
+    /// JWWの用紙は横置きで扱う。不明な用紙コードの場合は `None` を返す。
+    pub fn paper_dimensions(&self) -> Option<(f64, f64)> {
+        paper_dimensions(self.paper_size)
+    }
+
+    /// ドキュメントが使用するメモリのおおよそのバイト数を返す
+    ///
+    /// 構造体自体と、エンティティの配列・文字列が確保している領域の合計。
+    /// 入力データから借用している文字列とアロケータの管理領域は含まない。
+    pub fn memory_size(&self) -> usize {
+        let layer_names: usize = self
+            .layer_groups
+            .iter()
+            .map(|group| group.name.capacity() + group.layers.iter().map(|l| l.name.capacity()).sum::<usize>())
+            .sum();
+        let block_defs: usize = self
+            .block_defs
+            .iter()
+            .map(|def| def.name.capacity() + entities_memory_size(&def.entities))
+            .sum();
+        std::mem::size_of::<Document>()
+            + cow_capacity(&self.memo)
+            + layer_names
+            + entities_memory_size(&self.entities)
+            + self.block_defs.capacity() * std::mem::size_of::<BlockDef>()
+            + self.sxf_colors.capacity() * std::mem::size_of::<crate::Rgb>()
+            + block_defs
+    }
+
+    /// 図面の縮尺分母を返す (書込みレイヤグループの縮尺)
+    pub fn drawing_scale(&self) -> f64 {
+        self.layer_groups
+            .get(self.write_layer_group as usize)
+            .map(|lg| lg.scale)
+            .filter(|s| *s > 0.0)
+            .unwrap_or(1.0)
+    }
+
+    /// レイヤグループの縮尺分母を返す
+    ///
+    /// 縮尺が正の値でない場合は [`Document::drawing_scale`] を返す。
+    pub fn layer_group_scale(&self, layer_group: u16) -> f64 {
+        self.layer_groups
+            .get(layer_group as usize)
+            .map(|lg| lg.scale)
+            .filter(|s| *s > 0.0)
+            .unwrap_or_else(|| self.drawing_scale())
+    }
+}
+
+/// 文字列が確保している領域のバイト数 (借用している場合は0)
+#[allow(clippy::ptr_arg)]
+fn cow_capacity(s: &Cow<str>) -> usize {
+    match s {
+        Cow::Borrowed(_) => 0,
+        Cow::Owned(s) => s.capacity(),
+    }
+}
+
+/// エンティティの配列と、エンティティが持つ文字列の確保領域のバイト数
+fn entities_memory_size(entities: &Vec<Entity>) -> usize {
+    let strings: usize = entities
+        .iter()
+        .map(|entity| match entity {
+            Entity::Text(text) => cow_capacity(&text.font_name) + cow_capacity(&text.content),
+            Entity::Dimension(dim) => {
+                cow_capacity(&dim.text.font_name)
+                    + cow_capacity(&dim.text.content)
+                    + dim.extension_lines.capacity() * std::mem::size_of::<Line>()
+                    + dim.end_points.capacity() * std::mem::size_of::<Point>()
+            }
+            Entity::Unknown(unknown) => {
+                unknown.class_name.capacity()
+                    + match &unknown.data {
+                        Cow::Borrowed(_) => 0,
+                        Cow::Owned(data) => data.capacity(),
+                    }
+            }
+            _ => 0,
+        })
+        .sum();
+    entities.capacity() * std::mem::size_of::<Entity>() + strings
+}
+
+/// エンティティを含まない図面情報 ([`crate::probe`] の結果)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct DocumentInfo {
+    /// JWWファイルフォーマットバージョン
+    pub version: u32,
+
+    /// ファイルメモ/説明
+    pub memo: String,
+
+    /// 用紙サイズ: 0-4でA0-A4、8で2A、9で3Aなど
+    pub paper_size: u32,
+
+    /// 現在の書き込みレイヤグループ (0-15)
+    pub write_layer_group: u32,
+
+    /// 16個のレイヤグループ（各グループに16レイヤ）
+    pub layer_groups: [LayerGroup; 16],
+
+    /// エンティティリストの件数 (Nullオブジェクトを含む)
+    pub entity_count: usize,
+}
+
+impl DocumentInfo {
+    /// 用紙サイズ (横, 縦) をミリメートルで返す (不明な用紙コードの場合は `None`)
+    pub fn paper_dimensions(&self) -> Option<(f64, f64)> {
+        paper_dimensions(self.paper_size)
+    }
+}
+
+/// 用紙コードの寸法 (横, 縦) をミリメートルで返す
+fn paper_dimensions(paper_size: u32) -> Option<(f64, f64)> {
+    match paper_size {
+        0 => Some((1189.0, 841.0)),   // A0
+        1 => Some((841.0, 594.0)),    // A1
+        2 => Some((594.0, 420.0)),    // A2
+        3 => Some((420.0, 297.0)),    // A3
+        4 => Some((297.0, 210.0)),    // A4
+        8 => Some((1682.0, 1189.0)),  // 2A
+        9 => Some((2378.0, 1682.0)),  // 3A
+        10 => Some((3364.0, 2378.0)), // 4A
+        11 => Some((4756.0, 3364.0)), // 5A
+        _ => None,
+    }
 }
 
 /// レイヤグループ (JWW: レイヤグループ)
 ///
 /// JWWは16個のレイヤグループを持ち、各グループに16個のレイヤを持つ
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
 pub struct LayerGroup {
     /// レイヤグループの状態: 0=非表示, 1=表示のみ, 2=編集可能, 3=書込モード
     pub state: u32,
@@ -51,6 +220,7 @@ pub struct LayerGroup {
 
 /// 個別レイヤ
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
 pub struct Layer {
     /// レイヤの状態: 0=非表示, 1=表示のみ, 2=編集可能, 3=書込モード
     pub state: u32,
@@ -64,6 +234,7 @@ pub struct Layer {
 
 /// 全エンティティに共通する属性
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
 pub struct EntityBase {
     /// 曲線属性番号 (線種グループ)
     pub group: u32,
@@ -85,21 +256,105 @@ pub struct EntityBase {
 
     /// 各種属性フラグ
     pub flag: u16,
+
+    /// ファイル内のバイト範囲 ([`crate::ParseOptions::record_spans`] を指定した場合のみ)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceSpan>,
+}
+
+impl Default for EntityBase {
+    /// 線種1 (実線)・線色1、レイヤグループ0のレイヤ0
+    fn default() -> Self {
+        EntityBase {
+            group: 0,
+            pen_style: 1,
+            pen_color: 1,
+            pen_width: 0,
+            layer: 0,
+            layer_group: 0,
+            flag: 0,
+            source: None,
+        }
+    }
+}
+
+/// エンティティのファイル内のバイト範囲
+///
+/// 新しいクラスの最初のエンティティでは、先頭に置かれるクラス定義を含む。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct SourceSpan {
+    /// ファイル先頭からのバイト位置
+    pub offset: usize,
+    /// バイト数
+    pub length: usize,
+}
+
+impl SourceSpan {
+    /// ファイル内の範囲
+    pub fn range(&self) -> std::ops::Range<usize> {
+        self.offset..self.offset + self.length
+    }
 }
 
 /// エンティティ種別
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
 #[serde(tag = "type")]
-pub enum Entity {
+pub enum Entity<'a> {
     Line(Line),
     Arc(Arc),
     Point(Point),
-    Text(Text),
+    Text(Text<'a>),
     Solid(Solid),
     Block(Block),
+    Dimension(Dimension<'a>),
+    Unknown(Unknown<'a>),
 }
 
-impl Entity {
+/// エンティティ種別 (データを持たない識別子)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub enum EntityKind {
+    Line,
+    Arc,
+    Point,
+    Text,
+    Solid,
+    Block,
+    Dimension,
+    Unknown,
+}
+
+impl Entity<'_> {
+    /// 入力データから借用している文字列を複製する
+    pub fn into_owned(self) -> Entity<'static> {
+        match self {
+            Entity::Line(e) => Entity::Line(e),
+            Entity::Arc(e) => Entity::Arc(e),
+            Entity::Point(e) => Entity::Point(e),
+            Entity::Text(e) => Entity::Text(e.into_owned()),
+            Entity::Solid(e) => Entity::Solid(e),
+            Entity::Block(e) => Entity::Block(e),
+            Entity::Dimension(e) => Entity::Dimension(e.into_owned()),
+            Entity::Unknown(e) => Entity::Unknown(e.into_owned()),
+        }
+    }
+
+    /// エンティティ種別を返す
+    pub fn kind(&self) -> EntityKind {
+        match self {
+            Entity::Line(_) => EntityKind::Line,
+            Entity::Arc(_) => EntityKind::Arc,
+            Entity::Point(_) => EntityKind::Point,
+            Entity::Text(_) => EntityKind::Text,
+            Entity::Solid(_) => EntityKind::Solid,
+            Entity::Block(_) => EntityKind::Block,
+            Entity::Dimension(_) => EntityKind::Dimension,
+            Entity::Unknown(_) => EntityKind::Unknown,
+        }
+    }
+
     /// エンティティの基本属性を返す
     pub fn base(&self) -> &EntityBase {
         match self {
@@ -109,6 +364,8 @@ impl Entity {
             Entity::Text(e) => &e.base,
             Entity::Solid(e) => &e.base,
             Entity::Block(e) => &e.base,
+            Entity::Dimension(e) => &e.base,
+            Entity::Unknown(e) => &e.base,
         }
     }
 
@@ -121,6 +378,8 @@ impl Entity {
             Entity::Text(e) => &mut e.base,
             Entity::Solid(e) => &mut e.base,
             Entity::Block(e) => &mut e.base,
+            Entity::Dimension(e) => &mut e.base,
+            Entity::Unknown(e) => &mut e.base,
         }
     }
 
@@ -139,38 +398,49 @@ impl Entity {
             Entity::Text(_) => "TEXT",
             Entity::Solid(_) => "SOLID",
             Entity::Block(_) => "BLOCK",
+            Entity::Dimension(_) => "DIMENSION",
+            Entity::Unknown(_) => "UNKNOWN",
         }
     }
 }
 
 /// 直線エンティティ (JWWクラス: CDataSen)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
 pub struct Line {
     #[serde(flatten)]
     pub base: EntityBase,
-    pub start_x: f64,
-    pub start_y: f64,
-    pub end_x: f64,
-    pub end_y: f64,
+    /// 始点
+    #[serde(flatten, with = "crate::schema::start")]
+    #[cfg_attr(feature = "tsify", tsify(type = "{ start: Point2D }"))]
+    pub start: Point2D,
+    /// 終点
+    #[serde(flatten, with = "crate::schema::end")]
+    #[cfg_attr(feature = "tsify", tsify(type = "{ end: Point2D }"))]
+    pub end: Point2D,
 }
 
 /// 円弧/円エンティティ (JWWクラス: CDataEnko)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
 pub struct Arc {
     #[serde(flatten)]
     pub base: EntityBase,
-    /// 中心X座標
-    pub center_x: f64,
-    /// 中心Y座標
-    pub center_y: f64,
+    /// 中心
+    #[serde(flatten, with = "crate::schema::center")]
+    #[cfg_attr(feature = "tsify", tsify(type = "{ center: Point2D }"))]
+    pub center: Point2D,
     /// 半径（楕円の場合は長軸半径）
     pub radius: f64,
     /// 開始角度 (ラジアン)
-    pub start_angle: f64,
+    #[cfg_attr(feature = "tsify", tsify(type = "number"))]
+    pub start_angle: Angle,
     /// 円弧角度 (ラジアン)
-    pub arc_angle: f64,
+    #[cfg_attr(feature = "tsify", tsify(type = "number"))]
+    pub arc_angle: Angle,
     /// 回転角度 (ラジアン、楕円の場合)
-    pub tilt_angle: f64,
+    #[cfg_attr(feature = "tsify", tsify(type = "number"))]
+    pub tilt_angle: Angle,
     /// 扁平率 (1.0は真円、それ以外は楕円)
     pub flatness: f64,
     /// 完全円かどうか
@@ -179,30 +449,39 @@ pub struct Arc {
 
 /// 点エンティティ (JWWクラス: CDataTen)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
 pub struct Point {
     #[serde(flatten)]
     pub base: EntityBase,
-    pub x: f64,
-    pub y: f64,
+    /// 座標
+    #[serde(flatten, with = "crate::schema::position")]
+    #[cfg_attr(feature = "tsify", tsify(type = "{ position: Point2D }"))]
+    pub position: Point2D,
     /// 仮点かどうか
     pub is_temporary: bool,
     /// 点マーカー種別コード
     pub code: u32,
-    /// 回転角度
-    pub angle: f64,
+    /// 回転角度 (ラジアン)
+    #[cfg_attr(feature = "tsify", tsify(type = "number"))]
+    pub angle: Angle,
     /// スケール
     pub scale: f64,
 }
 
 /// 文字エンティティ (JWWクラス: CDataMoji)
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Text {
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct Text<'a> {
     #[serde(flatten)]
     pub base: EntityBase,
-    pub start_x: f64,
-    pub start_y: f64,
-    pub end_x: f64,
-    pub end_y: f64,
+    /// 始点
+    #[serde(flatten, with = "crate::schema::start")]
+    #[cfg_attr(feature = "tsify", tsify(type = "{ start: Point2D }"))]
+    pub start: Point2D,
+    /// 終点
+    #[serde(flatten, with = "crate::schema::end")]
+    #[cfg_attr(feature = "tsify", tsify(type = "{ end: Point2D }"))]
+    pub end: Point2D,
     /// 文字スタイルフラグ: +10000でイタリック、+20000で太字
     pub text_type: u32,
     pub size_x: f64,
@@ -210,52 +489,158 @@ pub struct Text {
     /// 文字間隔
     pub spacing: f64,
     /// 回転角度 (度)
-    pub angle: f64,
+    #[serde(with = "crate::angle::degrees")]
+    #[cfg_attr(feature = "tsify", tsify(type = "number"))]
+    pub angle: Angle,
     /// フォント名
-    pub font_name: String,
+    pub font_name: Cow<'a, str>,
     /// 文字列内容
-    pub content: String,
+    pub content: Cow<'a, str>,
+}
+
+impl Text<'_> {
+    /// 入力データから借用している文字列を複製する
+    pub fn into_owned(self) -> Text<'static> {
+        Text {
+            font_name: Cow::Owned(self.font_name.into_owned()),
+            content: Cow::Owned(self.content.into_owned()),
+            ..self
+        }
+    }
+
+    /// 文字列の回転角度
+    ///
+    /// `angle` は古い値や0のまま保存されていることがあるため、始点から終点への向きを
+    /// 優先する (縦書きのフォント `@...` では文字の並ぶ向きから90度戻す)。
+    /// 始点と終点が一致する場合は `angle` を返す。
+    pub fn rotation(&self) -> Angle {
+        let (dx, dy) = (self.end.x - self.start.x, self.end.y - self.start.y);
+        if dx == 0.0 && dy == 0.0 {
+            return self.angle;
+        }
+        let mut direction = Angle::from_radians(dy.atan2(dx));
+        if self.font_name.starts_with('@') {
+            direction = direction + Angle::from_degrees(90.0);
+        }
+        // 向きと一致する場合は保存されている値 (度単位の正確な値) を使う
+        let difference = (direction - self.angle)
+            .radians()
+            .rem_euclid(2.0 * std::f64::consts::PI);
+        if difference.min(2.0 * std::f64::consts::PI - difference) < 1e-9 {
+            self.angle
+        } else {
+            direction
+        }
+    }
 }
 
 /// 塗りつぶしエンティティ (JWWクラス: CDataSolid)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
 pub struct Solid {
     #[serde(flatten)]
     pub base: EntityBase,
-    pub point1_x: f64,
-    pub point1_y: f64,
-    pub point2_x: f64,
-    pub point2_y: f64,
-    pub point3_x: f64,
-    pub point3_y: f64,
-    pub point4_x: f64,
-    pub point4_y: f64,
+    /// 第1点
+    #[serde(flatten, with = "crate::schema::point1")]
+    #[cfg_attr(feature = "tsify", tsify(type = "{ point1: Point2D }"))]
+    pub point1: Point2D,
+    /// 第2点
+    #[serde(flatten, with = "crate::schema::point2")]
+    #[cfg_attr(feature = "tsify", tsify(type = "{ point2: Point2D }"))]
+    pub point2: Point2D,
+    /// 第3点
+    #[serde(flatten, with = "crate::schema::point3")]
+    #[cfg_attr(feature = "tsify", tsify(type = "{ point3: Point2D }"))]
+    pub point3: Point2D,
+    /// 第4点
+    #[serde(flatten, with = "crate::schema::point4")]
+    #[cfg_attr(feature = "tsify", tsify(type = "{ point4: Point2D }"))]
+    pub point4: Point2D,
     /// 色 (pen_color == 10の時使用)
     pub color: u32,
 }
 
 /// ブロック挿入エンティティ (JWWクラス: CDataBlock)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
 pub struct Block {
     #[serde(flatten)]
     pub base: EntityBase,
-    /// 挿入基準点X座標
-    pub ref_x: f64,
-    /// 挿入基準点Y座標
-    pub ref_y: f64,
+    /// 挿入基準点
+    #[serde(flatten, with = "crate::schema::reference")]
+    #[cfg_attr(feature = "tsify", tsify(type = "{ reference: Point2D }"))]
+    pub reference: Point2D,
     /// X方向スケール
     pub scale_x: f64,
     /// Y方向スケール
     pub scale_y: f64,
     /// 回転角度 (ラジアン)
-    pub rotation: f64,
+    #[cfg_attr(feature = "tsify", tsify(type = "number"))]
+    pub rotation: Angle,
     /// 参照先ブロック定義番号
     pub def_number: u32,
 }
 
+/// 寸法エンティティ (JWWクラス: CDataSunpou)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct Dimension<'a> {
+    #[serde(flatten)]
+    pub base: EntityBase,
+    /// 寸法線
+    pub line: Line,
+    /// 寸法値
+    pub text: Text<'a>,
+    /// SXF対応モード (Ver.4.20以降)
+    pub sxf_mode: u16,
+    /// 寸法補助線 (Ver.4.20以降、2本)
+    pub extension_lines: Vec<Line>,
+    /// 端点 (Ver.4.20以降、寸法線の両端2点と補助線の起点2点)
+    pub end_points: Vec<Point>,
+}
+
+impl Dimension<'_> {
+    /// 入力データから借用している文字列を複製する
+    pub fn into_owned(self) -> Dimension<'static> {
+        Dimension {
+            text: self.text.into_owned(),
+            ..self
+        }
+    }
+}
+
+/// 読み取りに対応していないクラスのエンティティ
+///
+/// [`crate::ParseOptions::preserve_unknown_classes`] を指定した場合のみ作られる。
+/// エンティティの長さはファイルに記録されていないため、基本属性より後のデータは
+/// ファイルの終わりまでまとめて `data` に保持し、以降のエンティティは読み取らない。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct Unknown<'a> {
+    #[serde(flatten)]
+    pub base: EntityBase,
+    /// MFCのクラス名
+    pub class_name: String,
+    /// 基本属性より後のバイト列 (ファイルの終わりまで)
+    #[cfg_attr(feature = "tsify", tsify(type = "number[]"))]
+    pub data: Cow<'a, [u8]>,
+}
+
+impl Unknown<'_> {
+    /// 入力データから借用しているバイト列を複製する
+    pub fn into_owned(self) -> Unknown<'static> {
+        Unknown {
+            base: self.base,
+            class_name: self.class_name,
+            data: Cow::Owned(self.data.into_owned()),
+        }
+    }
+}
+
 /// ブロック定義 (JWWクラス: CDataList)
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BlockDef {
+#[cfg_attr(feature = "tsify", derive(tsify::Tsify))]
+pub struct BlockDef<'a> {
     #[serde(flatten)]
     pub base: EntityBase,
     /// ブロック定義番号
@@ -265,7 +650,20 @@ pub struct BlockDef {
     /// ブロック名
     pub name: String,
     /// ブロックを構成するエンティティ
-    pub entities: Vec<Entity>,
+    pub entities: Vec<Entity<'a>>,
+}
+
+impl BlockDef<'_> {
+    /// 入力データから借用している文字列を複製する
+    pub fn into_owned(self) -> BlockDef<'static> {
+        BlockDef {
+            base: self.base,
+            number: self.number,
+            is_referenced: self.is_referenced,
+            name: self.name,
+            entities: self.entities.into_iter().map(Entity::into_owned).collect(),
+        }
+    }
 }
 
 impl Default for LayerGroup {
@@ -291,16 +689,18 @@ impl Default for Layer {
     }
 }
 
-impl Default for Document {
+impl Default for Document<'_> {
     fn default() -> Self {
         Self {
             version: 0,
-            memo: String::new(),
+            memo: Cow::Borrowed(""),
             paper_size: 0,
             write_layer_group: 0,
             layer_groups: std::array::from_fn(|_| LayerGroup::default()),
             entities: Vec::new(),
             block_defs: Vec::new(),
+            sxf_colors: Vec::new(),
+            cache: Default::default(),
         }
     }
 }
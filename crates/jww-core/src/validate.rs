@@ -0,0 +1,208 @@
+//! ドキュメントの検査
+
+use serde::Serialize;
+
+use crate::types::{Document, Entity, Line, Text};
+use crate::version::SUPPORTED_VERSIONS;
+
+/// 検査結果の重要度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// 参考情報 (出力には影響しない)
+    Info,
+    /// 不審な値 (出力結果が意図と異なる可能性がある)
+    Warning,
+    /// 不正な値 (変換できない、または破損している可能性が高い)
+    Error,
+}
+
+/// 検査結果の1件
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    /// 検査項目の識別子 (`non_finite_coordinate` など)
+    pub code: &'static str,
+    pub message: String,
+    /// 対象のエンティティの番号 (`Document::entities` の添字)
+    pub entity_index: Option<usize>,
+}
+
+impl Finding {
+    fn new(severity: Severity, code: &'static str, message: String, entity_index: Option<usize>) -> Self {
+        Self {
+            severity,
+            code,
+            message,
+            entity_index,
+        }
+    }
+}
+
+impl Document<'_> {
+    /// ドキュメントの値を検査し、問題点を返す
+    ///
+    /// 破損したファイルや変換に失敗しうる値 (非有限の座標、範囲外のレイヤ番号、
+    /// 存在しないブロック定義の参照など) を検出する。問題がなければ空を返す。
+    pub fn validate(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+
+        if !SUPPORTED_VERSIONS.contains(&self.version) {
+            // JWW形式で書き出せない
+            findings.push(Finding::new(
+                Severity::Error,
+                "unsupported_version",
+                format!("unsupported JWW version: {}", self.version),
+                None,
+            ));
+        }
+        if self.paper_dimensions().is_none() {
+            findings.push(Finding::new(
+                Severity::Warning,
+                "unknown_paper_size",
+                format!("unknown paper size code: {}", self.paper_size),
+                None,
+            ));
+        }
+        if self.write_layer_group > 15 {
+            findings.push(Finding::new(
+                Severity::Warning,
+                "invalid_write_layer_group",
+                format!("write layer group out of range: {}", self.write_layer_group),
+                None,
+            ));
+        }
+        for (i, group) in self.layer_groups.iter().enumerate() {
+            if !(group.scale.is_finite() && group.scale > 0.0) {
+                findings.push(Finding::new(
+                    Severity::Warning,
+                    "invalid_scale",
+                    format!("layer group {:X} has invalid scale: {}", i, group.scale),
+                    None,
+                ));
+            }
+        }
+
+        for (index, entity) in self.entities.iter().enumerate() {
+            self.validate_entity(index, entity, &mut findings);
+        }
+
+        findings
+    }
+
+    fn validate_entity(&self, index: usize, entity: &Entity, findings: &mut Vec<Finding>) {
+        let mut push = |severity, code, message: String| {
+            findings.push(Finding::new(severity, code, message, Some(index)));
+        };
+        let type_name = entity.type_name();
+        let base = entity.base();
+
+        if base.layer_group > 15 || base.layer > 15 {
+            push(
+                Severity::Error,
+                "invalid_layer",
+                format!("{} on layer out of range: {:X}-{:X}", type_name, base.layer_group, base.layer),
+            );
+        }
+        if numbers(entity).iter().any(|v| !v.is_finite()) {
+            push(
+                Severity::Error,
+                "non_finite_coordinate",
+                format!("{} has a non-finite coordinate", type_name),
+            );
+            return;
+        }
+
+        match entity {
+            Entity::Line(line) => {
+                if line.start == line.end {
+                    push(Severity::Info, "zero_length_line", "zero-length line".to_string());
+                }
+            }
+            Entity::Arc(arc) => {
+                if arc.radius <= 0.0 {
+                    push(Severity::Warning, "invalid_radius", format!("arc radius is not positive: {}", arc.radius));
+                }
+                if arc.flatness <= 0.0 {
+                    push(Severity::Warning, "invalid_flatness", format!("arc flatness is not positive: {}", arc.flatness));
+                }
+            }
+            Entity::Text(text) => {
+                if text.content.is_empty() {
+                    push(Severity::Info, "empty_text", "text has no content".to_string());
+                }
+                if text.size_y <= 0.0 {
+                    push(Severity::Warning, "invalid_text_size", format!("text height is not positive: {}", text.size_y));
+                }
+            }
+            Entity::Block(block) => {
+                if !self.block_defs.iter().any(|def| def.number == block.def_number) {
+                    push(
+                        Severity::Warning,
+                        "missing_block_def",
+                        format!("block definition not found: {}", block.def_number),
+                    );
+                }
+                if block.scale_x == 0.0 || block.scale_y == 0.0 {
+                    push(Severity::Warning, "zero_block_scale", "block insert has zero scale".to_string());
+                }
+            }
+            Entity::Unknown(unknown) => {
+                push(
+                    Severity::Warning,
+                    "unknown_entity_class",
+                    format!("unknown entity class: {}", unknown.class_name),
+                );
+            }
+            Entity::Point(_) | Entity::Solid(_) | Entity::Dimension(_) => {}
+        }
+    }
+}
+
+/// エンティティの座標・寸法の数値を列挙する
+fn numbers(entity: &Entity) -> Vec<f64> {
+    match entity {
+        Entity::Line(e) => line_numbers(e).to_vec(),
+        Entity::Arc(e) => vec![
+            e.center.x,
+            e.center.y,
+            e.radius,
+            e.start_angle.radians(),
+            e.arc_angle.radians(),
+            e.tilt_angle.radians(),
+            e.flatness,
+        ],
+        Entity::Point(e) => vec![e.position.x, e.position.y, e.angle.radians(), e.scale],
+        Entity::Text(e) => text_numbers(e).to_vec(),
+        Entity::Solid(e) => vec![
+            e.point1.x, e.point1.y, e.point2.x, e.point2.y, e.point3.x, e.point3.y, e.point4.x, e.point4.y,
+        ],
+        Entity::Block(e) => vec![e.reference.x, e.reference.y, e.scale_x, e.scale_y, e.rotation.radians()],
+        Entity::Dimension(e) => {
+            let mut values = line_numbers(&e.line).to_vec();
+            values.extend(text_numbers(&e.text));
+            for line in &e.extension_lines {
+                values.extend(line_numbers(line));
+            }
+            values
+        }
+        Entity::Unknown(_) => Vec::new(),
+    }
+}
+
+fn line_numbers(line: &Line) -> [f64; 4] {
+    [line.start.x, line.start.y, line.end.x, line.end.y]
+}
+
+fn text_numbers(text: &Text) -> [f64; 8] {
+    [
+        text.start.x,
+        text.start.y,
+        text.end.x,
+        text.end.y,
+        text.size_x,
+        text.size_y,
+        text.spacing,
+        text.angle.degrees(),
+    ]
+}
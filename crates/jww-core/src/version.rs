@@ -0,0 +1,61 @@
+//! JWWファイルフォーマットバージョンと対応状況
+//!
+//! バージョンはJw_cadのバージョンの100倍 (Ver.7.00 → 700) で、ヘッダーに記録される。
+//! バージョンによってエンティティのレイアウトが異なるため、範囲外のバージョンは
+//! エンティティリストの途中で読み取りがずれる前に [`ParseError::UnsupportedVersion`] とする。
+//!
+//! | バージョン | 対応状況 |
+//! |------------|----------|
+//! | 200未満 | 非対応 (`UnsupportedVersion`) |
+//! | 200〜350 | 対応 (エンティティは線幅を持たない) |
+//! | 351〜419 | 対応 (線幅あり、寸法はSXF対応の補助線・端点を持たない) |
+//! | 420〜1000 | 対応 (線幅、寸法の補助線・端点、ヘッダーのSXF拡張線色の定義あり) |
+//! | 1000超 | 非対応 (`UnsupportedVersion`) |
+
+use std::ops::RangeInclusive;
+
+use crate::error::{ParseError, Result};
+
+/// 読み書きに対応するバージョンの範囲
+pub const SUPPORTED_VERSIONS: RangeInclusive<u32> = 200..=1000;
+
+/// エンティティが線幅 ([`crate::EntityBase::pen_width`]) を持つ最初のバージョン (Ver.3.51)
+pub const PEN_WIDTH_VERSION: u32 = 351;
+
+/// 寸法がSXF対応の補助線・端点 ([`crate::Dimension::extension_lines`] など) を持つ
+/// 最初のバージョン (Ver.4.20)
+pub const SXF_DIMENSION_VERSION: u32 = 420;
+
+/// ヘッダーがSXF対応拡張線色の定義 ([`crate::Document::sxf_colors`]) を持つ
+/// 最初のバージョン (Ver.4.20)
+pub const SXF_COLOR_VERSION: u32 = 420;
+
+/// バージョンごとのファイルフォーマットの機能
+///
+/// パーサーとライターはこの値でエンティティのレイアウトを切り替える。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// エンティティが線幅を持つ
+    pub pen_width: bool,
+    /// 寸法がSXF対応の補助線・端点を持つ
+    pub sxf_dimensions: bool,
+    /// ヘッダーがSXF対応拡張線色の定義を持つ
+    pub sxf_colors: bool,
+}
+
+impl Capabilities {
+    /// バージョンの機能を返す
+    ///
+    /// # エラー
+    /// 対応していないバージョンの場合は [`ParseError::UnsupportedVersion`]
+    pub fn of(version: u32) -> Result<Self> {
+        if !SUPPORTED_VERSIONS.contains(&version) {
+            return Err(ParseError::UnsupportedVersion(version));
+        }
+        Ok(Self {
+            pen_width: version >= PEN_WIDTH_VERSION,
+            sxf_dimensions: version >= SXF_DIMENSION_VERSION,
+            sxf_colors: version >= SXF_COLOR_VERSION,
+        })
+    }
+}
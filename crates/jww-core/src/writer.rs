@@ -0,0 +1,494 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use byteorder::{LittleEndian, WriteBytesExt};
+
+use crate::color::{Palette, Rgb, SXF_STANDARD_COLORS};
+use crate::error::{ParseError, Result};
+use crate::point::Point2D;
+use crate::types::{BlockDef, Document, Entity, EntityBase, Line, Point, Text};
+use crate::version::Capabilities;
+use crate::{SXF_COLOR_COUNT, SXF_LINE_TYPE_COUNT};
+
+/// JWWバイナリデータライター
+///
+/// [`Reader`](crate::Reader) と対になり、リトルエンディアン形式で書き込み、
+/// 文字列をShift-JISに変換する。
+pub struct Writer<W> {
+    inner: W,
+    bytes_written: u64,
+}
+
+impl<W: Write> Writer<W> {
+    /// 新しいライターを作成する
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            bytes_written: 0,
+        }
+    }
+
+    /// DWORD (32-bit unsigned int) を書き込む
+    pub fn write_dword(&mut self, val: u32) -> Result<()> {
+        self.inner.write_u32::<LittleEndian>(val)?;
+        self.bytes_written += 4;
+        Ok(())
+    }
+
+    /// WORD (16-bit unsigned int) を書き込む
+    pub fn write_word(&mut self, val: u16) -> Result<()> {
+        self.inner.write_u16::<LittleEndian>(val)?;
+        self.bytes_written += 2;
+        Ok(())
+    }
+
+    /// BYTE (8-bit unsigned int) を書き込む
+    pub fn write_byte(&mut self, val: u8) -> Result<()> {
+        self.inner.write_u8(val)?;
+        self.bytes_written += 1;
+        Ok(())
+    }
+
+    /// Double (64-bit float) を書き込む
+    pub fn write_double(&mut self, val: f64) -> Result<()> {
+        self.inner.write_f64::<LittleEndian>(val)?;
+        self.bytes_written += 8;
+        Ok(())
+    }
+
+    /// X・Y座標 (Double 2つ) を書き込む
+    pub fn write_point(&mut self, point: Point2D) -> Result<()> {
+        self.write_double(point.x)?;
+        self.write_double(point.y)
+    }
+
+    /// MFC CString形式で文字列を書き込む
+    ///
+    /// Shift-JISで表現できない文字は数値文字参照 (`&#NNNN;`) に置き換えられる。
+    pub fn write_cstring(&mut self, s: &str) -> Result<()> {
+        let (bytes, ..) = encoding_rs::SHIFT_JIS.encode(s);
+        let length = bytes.len();
+
+        if length < 0xFF {
+            self.write_byte(length as u8)?;
+        } else if length < 0xFFFF {
+            self.write_byte(0xFF)?;
+            self.write_word(length as u16)?;
+        } else {
+            self.write_byte(0xFF)?;
+            self.write_word(0xFFFF)?;
+            self.write_dword(length as u32)?;
+        }
+
+        self.write_all(&bytes)
+    }
+
+    /// バイト列をそのまま書き込む
+    pub fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        self.inner.write_all(buf)?;
+        self.bytes_written += buf.len() as u64;
+        Ok(())
+    }
+
+    /// 書き込んだ合計バイト数を返す
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// 内部ライターを消費して返す
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// エンティティリストの最大件数 (WORDの件数で表現できる範囲)
+const MAX_ENTITIES: usize = 0xFFFE;
+
+/// 文字種1-10の既定の大きさ (幅・高さ、mm)
+const TEXT_SIZES: [f64; 10] = [2.0, 2.5, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0];
+
+/// ドキュメントをJWWバイナリ形式で書き出す
+///
+/// ヘッダー (バージョン・メモ・用紙・レイヤグループ・レイヤ名・線色の定義などの設定)、
+/// エンティティリストとブロック定義リストを出力する。[`Document`] に保持していない設定
+/// (寸法・印刷・線種のパターンなど) は0 (倍率は1) を書き込む。線色0-9の表示色は
+/// [`Palette::SCREEN`] と [`Palette::PRINTER`]、SXF対応拡張線色は [`Document::sxf_colors`]
+/// (空の場合は既定義色) を書き込む。
+///
+/// # エラー
+/// - バージョンが [`SUPPORTED_VERSIONS`](crate::version::SUPPORTED_VERSIONS) の範囲外の場合
+/// - エンティティ数がリストの上限を超える場合
+pub fn write(doc: &Document) -> Result<Vec<u8>> {
+    let caps = Capabilities::of(doc.version)?;
+    let lists = std::iter::once(doc.entities.len())
+        .chain(std::iter::once(doc.block_defs.len()))
+        .chain(doc.block_defs.iter().map(|def| def.entities.len()));
+    for count in lists {
+        if count > MAX_ENTITIES {
+            return Err(ParseError::Other(format!(
+                "too many entities: {} (max {})",
+                count, MAX_ENTITIES
+            )));
+        }
+    }
+
+    let mut writer = Writer::new(Vec::new());
+    writer.write_all(b"JwwData.")?;
+    writer.write_dword(doc.version)?;
+
+    // ヘッダー情報
+    writer.write_cstring(&doc.memo)?;
+    writer.write_dword(doc.paper_size)?;
+    writer.write_dword(doc.write_layer_group)?;
+
+    // レイヤグループ (16グループ)
+    for group in &doc.layer_groups {
+        writer.write_dword(group.state)?;
+        writer.write_dword(group.write_layer)?;
+        writer.write_double(group.scale)?;
+        writer.write_dword(group.protect)?;
+        for layer in &group.layers {
+            writer.write_dword(layer.state)?;
+            writer.write_dword(layer.protect)?;
+        }
+    }
+
+    // レイヤグループの設定とエンティティリストの間の設定
+    write_settings(&mut writer, doc, caps)?;
+
+    // エンティティリスト
+    let mut classes = ClassTable::new(doc.version);
+    writer.write_word(doc.entities.len() as u16)?;
+    for entity in &doc.entities {
+        write_entity(&mut writer, entity, caps, &mut classes)?;
+    }
+
+    // ブロック定義リスト
+    writer.write_word(doc.block_defs.len() as u16)?;
+    for block_def in &doc.block_defs {
+        write_block_def(&mut writer, block_def, caps, &mut classes)?;
+    }
+
+    Ok(writer.into_inner())
+}
+
+/// レイヤグループの設定の直後から、エンティティリストの直前までの設定を書き込む
+///
+/// 項目の並びはパーサーが読み飛ばす順序と同じ。
+fn write_settings<W: Write>(writer: &mut Writer<W>, doc: &Document, caps: Capabilities) -> Result<()> {
+    let zeros = |writer: &mut Writer<W>, len: usize| writer.write_all(&vec![0; len]);
+
+    // ダミー (14)、寸法の設定 (5)、ダミー、線描画の最大幅
+    zeros(writer, 21 * 4)?;
+    // プリンター出力の原点・倍率、回転・基準点
+    writer.write_point(Point2D::new(0.0, 0.0))?;
+    writer.write_double(1.0)?;
+    zeros(writer, 2 * 4)?;
+    // 目盛の最小間隔・間隔・基準点
+    zeros(writer, 5 * 8)?;
+
+    // レイヤ名 (16×16)、レイヤグループ名 (16)
+    // パーサーが補う既定の名前は空として書き込む
+    for (g, group) in doc.layer_groups.iter().enumerate() {
+        for (l, layer) in group.layers.iter().enumerate() {
+            let default = format!("{:X}-{:X}", g, l);
+            writer.write_cstring(if layer.name == default { "" } else { &layer.name })?;
+        }
+    }
+    for (g, group) in doc.layer_groups.iter().enumerate() {
+        let default = format!("Group{:X}", g);
+        writer.write_cstring(if group.name == default { "" } else { &group.name })?;
+    }
+
+    // 日影・天空図の条件、2.5Dの計算単位
+    zeros(writer, 2 * 8 + 4 + 8 + 2 * 8 + 4)?;
+    // 保存時の画面倍率・原点、範囲記憶の倍率・基準点
+    for _ in 0..2 {
+        writer.write_double(1.0)?;
+        writer.write_point(Point2D::new(0.0, 0.0))?;
+    }
+    // マークジャンプの倍率・基準点・レイヤグループ (8)
+    for _ in 0..8 {
+        writer.write_double(1.0)?;
+        writer.write_point(Point2D::new(0.0, 0.0))?;
+        writer.write_dword(0)?;
+    }
+    // ダミー (3)、文字の描画状態
+    zeros(writer, 3 * (3 * 8 + 4) + 8 + 4)?;
+    // 複線間隔 (10)、両側複線の間隔
+    zeros(writer, 11 * 8)?;
+
+    // 線色0-9の画面表示色・線幅 (線色0は背景色)
+    let screen = std::iter::once(Rgb::new(255, 255, 255)).chain(Palette::SCREEN.colors);
+    for color in screen {
+        writer.write_dword(color.to_colorref())?;
+        writer.write_dword(1)?;
+    }
+    // 線色0-9のプリンター出力色・線幅・実点半径
+    let printer = std::iter::once(Rgb::new(255, 255, 255)).chain(Palette::PRINTER.colors);
+    for color in printer {
+        writer.write_dword(color.to_colorref())?;
+        writer.write_dword(1)?;
+        writer.write_double(0.0)?;
+    }
+    // 線種2-9、ランダム線1-5、倍長線種6-9のパターン
+    zeros(writer, 8 * 4 * 4 + 5 * 5 * 4 + 4 * 4 * 4)?;
+    // 実点・描画順・印刷の設定 (11)、作図時間、2.5Dの視点の有無
+    zeros(writer, 13 * 4)?;
+    // 2.5Dの視点 (8)、線の長さ・矩形寸法・円の半径の最終値 (4)
+    zeros(writer, 12 * 8)?;
+    // ソリッドを任意色で書くフラグと既定の色
+    zeros(writer, 2 * 4)?;
+
+    if caps.sxf_colors {
+        // SXF対応拡張線色の画面表示色・線幅、プリンター出力色・線幅・実点半径
+        let colors: Vec<Rgb> = if doc.sxf_colors.is_empty() {
+            (0..SXF_COLOR_COUNT)
+                .map(|i| match i {
+                    1..=16 => SXF_STANDARD_COLORS[i - 1],
+                    _ => Rgb::BLACK,
+                })
+                .collect()
+        } else {
+            (0..SXF_COLOR_COUNT)
+                .map(|i| doc.sxf_colors.get(i).copied().unwrap_or(Rgb::BLACK))
+                .collect()
+        };
+        for color in &colors {
+            writer.write_dword(color.to_colorref())?;
+            writer.write_dword(1)?;
+        }
+        for color in &colors {
+            writer.write_dword(color.to_colorref())?;
+            writer.write_dword(1)?;
+            writer.write_double(0.0)?;
+        }
+        // SXF対応拡張線種のパターン
+        zeros(writer, SXF_LINE_TYPE_COUNT * 4 * 4)?;
+        // SXF対応拡張線色の名前 (SXF色番号1-256)
+        for _ in 1..SXF_COLOR_COUNT {
+            writer.write_cstring("")?;
+        }
+        // SXF対応拡張線種の名前・セグメント数・ピッチ (10)
+        for _ in 0..SXF_LINE_TYPE_COUNT {
+            writer.write_cstring("")?;
+            zeros(writer, 4 + 10 * 8)?;
+        }
+    }
+
+    // 文字種1-10の幅・高さ・間隔・色
+    for size in TEXT_SIZES {
+        writer.write_double(size)?;
+        writer.write_double(size)?;
+        writer.write_double(0.0)?;
+        writer.write_dword(1)?;
+    }
+    // 書込み文字の幅・高さ・間隔・色・文字種
+    writer.write_double(TEXT_SIZES[0])?;
+    writer.write_double(TEXT_SIZES[0])?;
+    writer.write_double(0.0)?;
+    writer.write_dword(1)?;
+    writer.write_dword(1)?;
+    // 文字位置整理の行間・文字数
+    zeros(writer, 2 * 8)?;
+    // 文字基準点のずれ位置を使うフラグ、横方向 (3)・縦方向 (3) のずれ位置
+    zeros(writer, 4 + 6 * 8)
+}
+
+/// ブロック定義 (CDataList) を書き込む
+fn write_block_def<W: Write>(
+    writer: &mut Writer<W>,
+    block_def: &BlockDef,
+    caps: Capabilities,
+    classes: &mut ClassTable,
+) -> Result<()> {
+    classes.write_tag(writer, "CDataList")?;
+    write_entity_base(writer, &block_def.base, caps)?;
+    writer.write_dword(block_def.number)?;
+    writer.write_dword(block_def.is_referenced as u32)?;
+    // 作成日時 (CTime)
+    writer.write_dword(0)?;
+    writer.write_cstring(&block_def.name)?;
+    writer.write_word(block_def.entities.len() as u16)?;
+    for entity in &block_def.entities {
+        write_entity(writer, entity, caps, classes)?;
+    }
+    Ok(())
+}
+
+/// MFC CArchive のクラスPID管理
+///
+/// パーサーと同じく、新しいクラス定義とオブジェクトごとにPIDを1つ消費する。
+struct ClassTable {
+    pids: HashMap<&'static str, u32>,
+    next_pid: u32,
+    /// クラス定義に書き込むスキーマ (ファイルフォーマットバージョン)
+    schema: u16,
+}
+
+impl ClassTable {
+    fn new(version: u32) -> Self {
+        Self {
+            pids: HashMap::new(),
+            next_pid: 1,
+            schema: version as u16,
+        }
+    }
+
+    /// クラスタグ (新しいクラス定義またはクラス参照) を書き込む
+    fn write_tag<W: Write>(&mut self, writer: &mut Writer<W>, class_name: &'static str) -> Result<()> {
+        match self.pids.get(class_name) {
+            Some(&pid) => writer.write_word(0x8000 | pid as u16)?,
+            None => {
+                writer.write_word(0xFFFF)?;
+                writer.write_word(self.schema)?;
+                writer.write_word(class_name.len() as u16)?;
+                writer.write_all(class_name.as_bytes())?;
+                self.pids.insert(class_name, self.next_pid);
+                self.next_pid += 1;
+            }
+        }
+        // オブジェクト自身のPID
+        self.next_pid += 1;
+        Ok(())
+    }
+}
+
+/// エンティティを書き込む
+fn write_entity<W: Write>(
+    writer: &mut Writer<W>,
+    entity: &Entity,
+    caps: Capabilities,
+    classes: &mut ClassTable,
+) -> Result<()> {
+    match entity {
+        Entity::Line(line) => {
+            classes.write_tag(writer, "CDataSen")?;
+            write_line_member(writer, line, caps)?;
+        }
+        Entity::Arc(arc) => {
+            classes.write_tag(writer, "CDataEnko")?;
+            write_entity_base(writer, &arc.base, caps)?;
+            writer.write_point(arc.center)?;
+            writer.write_double(arc.radius)?;
+            writer.write_double(arc.start_angle.radians())?;
+            writer.write_double(arc.arc_angle.radians())?;
+            writer.write_double(arc.tilt_angle.radians())?;
+            writer.write_double(arc.flatness)?;
+            writer.write_dword(arc.is_full_circle as u32)?;
+        }
+        Entity::Point(point) => {
+            classes.write_tag(writer, "CDataTen")?;
+            write_entity_base(writer, &point.base, caps)?;
+            writer.write_point(point.position)?;
+            writer.write_dword(point.is_temporary as u32)?;
+            if point.base.pen_style == 100 {
+                writer.write_dword(point.code)?;
+                writer.write_double(point.angle.radians())?;
+                writer.write_double(point.scale)?;
+            }
+        }
+        Entity::Text(text) => {
+            classes.write_tag(writer, "CDataMoji")?;
+            write_text_member(writer, text, caps)?;
+        }
+        Entity::Solid(solid) => {
+            classes.write_tag(writer, "CDataSolid")?;
+            write_entity_base(writer, &solid.base, caps)?;
+            writer.write_point(solid.point1)?;
+            writer.write_point(solid.point4)?;
+            writer.write_point(solid.point2)?;
+            writer.write_point(solid.point3)?;
+            if solid.base.pen_color == 10 {
+                writer.write_dword(solid.color)?;
+            }
+        }
+        Entity::Block(block) => {
+            classes.write_tag(writer, "CDataBlock")?;
+            write_entity_base(writer, &block.base, caps)?;
+            writer.write_point(block.reference)?;
+            writer.write_double(block.scale_x)?;
+            writer.write_double(block.scale_y)?;
+            writer.write_double(block.rotation.radians())?;
+            writer.write_dword(block.def_number)?;
+        }
+        Entity::Dimension(dim) => {
+            classes.write_tag(writer, "CDataSunpou")?;
+            write_entity_base(writer, &dim.base, caps)?;
+            write_line_member(writer, &dim.line, caps)?;
+            write_text_member(writer, &dim.text, caps)?;
+            if caps.sxf_dimensions {
+                writer.write_word(dim.sxf_mode)?;
+                // 補助線と端点は固定数のため、不足分は寸法線の両端で補う
+                for i in 0..2 {
+                    let line = dim.extension_lines.get(i).unwrap_or(&dim.line);
+                    write_line_member(writer, line, caps)?;
+                }
+                for i in 0..4 {
+                    match dim.end_points.get(i) {
+                        Some(point) => write_point_member(writer, point, caps)?,
+                        None => {
+                            let point = if i % 2 == 0 {
+                                dim.line.start
+                            } else {
+                                dim.line.end
+                            };
+                            write_entity_base(writer, &dim.line.base, caps)?;
+                            writer.write_point(point)?;
+                            writer.write_dword(0)?;
+                        }
+                    }
+                }
+            }
+        }
+        Entity::Unknown(unknown) => {
+            return Err(ParseError::UnknownEntityClass(unknown.class_name.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// 線メンバー (CDataSen の本体) を書き込む
+fn write_line_member<W: Write>(writer: &mut Writer<W>, line: &Line, caps: Capabilities) -> Result<()> {
+    write_entity_base(writer, &line.base, caps)?;
+    writer.write_point(line.start)?;
+    writer.write_point(line.end)?;
+    Ok(())
+}
+
+/// 文字メンバー (CDataMoji の本体) を書き込む
+fn write_text_member<W: Write>(writer: &mut Writer<W>, text: &Text, caps: Capabilities) -> Result<()> {
+    write_entity_base(writer, &text.base, caps)?;
+    writer.write_point(text.start)?;
+    writer.write_point(text.end)?;
+    writer.write_dword(text.text_type)?;
+    writer.write_double(text.size_x)?;
+    writer.write_double(text.size_y)?;
+    writer.write_double(text.spacing)?;
+    writer.write_double(text.angle.degrees())?;
+    writer.write_cstring(&text.font_name)?;
+    writer.write_cstring(&text.content)?;
+    Ok(())
+}
+
+/// 寸法に埋め込まれた点メンバーを書き込む (追加フィールドなし)
+fn write_point_member<W: Write>(writer: &mut Writer<W>, point: &Point, caps: Capabilities) -> Result<()> {
+    write_entity_base(writer, &point.base, caps)?;
+    writer.write_point(point.position)?;
+    writer.write_dword(point.is_temporary as u32)?;
+    Ok(())
+}
+
+/// エンティティ基本属性を書き込む
+fn write_entity_base<W: Write>(writer: &mut Writer<W>, base: &EntityBase, caps: Capabilities) -> Result<()> {
+    writer.write_dword(base.group)?;
+    writer.write_byte(base.pen_style)?;
+    writer.write_word(base.pen_color)?;
+    if caps.pen_width {
+        writer.write_word(base.pen_width)?;
+    }
+    writer.write_word(base.layer)?;
+    writer.write_word(base.layer_group)?;
+    writer.write_word(base.flag)?;
+    Ok(())
+}
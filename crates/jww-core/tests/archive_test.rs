@@ -0,0 +1,88 @@
+//! ZIPアーカイブの読み込みのテスト (`cargo test -p jww-core --features zip`)
+
+#![cfg(feature = "zip")]
+
+use std::io::{Cursor, Write};
+
+use jww_core::archive::ZipDocuments;
+use jww_core::ParseOptions;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+fn fixture(name: &str) -> Vec<u8> {
+    std::fs::read(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../tests/fixtures")
+            .join(name),
+    )
+    .unwrap()
+}
+
+/// (名前, 内容) のファイルを含むZIPアーカイブを作成する
+fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, data) in files {
+        writer.start_file(*name, options).unwrap();
+        writer.write_all(data).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+#[test]
+fn test_zip_documents() {
+    let geometry = fixture("geometry.jww");
+    let blocks = fixture("blocks.jww");
+    let data = zip(&[
+        ("readme.txt", b"drawings"),
+        ("set/geometry.jww", &geometry),
+        ("set/BLOCKS.JWW", &blocks),
+        ("__MACOSX/set/._geometry.jww", b"metadata"),
+        ("old.jwc", b"\x00\x01"),
+        ("broken.jww", b"JwwData."),
+    ]);
+
+    let documents = ZipDocuments::new(Cursor::new(data)).unwrap();
+    assert_eq!(
+        documents.names().collect::<Vec<_>>(),
+        [
+            "set/geometry.jww",
+            "set/BLOCKS.JWW",
+            "old.jwc",
+            "broken.jww"
+        ]
+    );
+    let entries: Vec<_> = documents.collect();
+    let expected = jww_core::parse(&geometry).unwrap();
+    let doc = entries[0].document.as_ref().unwrap();
+    assert_eq!(doc.entities.len(), expected.entities.len());
+    assert!(entries[1].document.is_ok());
+    assert_eq!(entries[2].document.as_ref().unwrap_err().code(), "other");
+    assert_eq!(
+        entries[3].document.as_ref().unwrap_err().code(),
+        "unexpected_eof"
+    );
+}
+
+#[test]
+fn test_zip_options() {
+    let geometry = fixture("geometry.jww");
+    let data = zip(&[("geometry.jww", &geometry)]);
+    let options = ParseOptions {
+        max_file_size: Some(geometry.len() - 1),
+        ..Default::default()
+    };
+    let entries: Vec<_> = ZipDocuments::with_options(Cursor::new(data), &options)
+        .unwrap()
+        .collect();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(
+        entries[0].document.as_ref().unwrap_err().code(),
+        "limit_exceeded"
+    );
+
+    let err = ZipDocuments::new(Cursor::new(b"not a zip".to_vec()))
+        .err()
+        .unwrap();
+    assert_eq!(err.code(), "io");
+}
@@ -2,22 +2,50 @@
 //!
 //! このテストはGo版パーサーがビルドされている必要があります
 //! 実行方法: cargo test --test compatibility_test -- --ignored
+//!
+//! Go版パーサーのパス、フィクスチャのディレクトリ、比較方法 (許容誤差など) はワークスペース直下の
+//! `jww-compat.toml` (`jww-compat.example.toml` を参照) または環境変数で指定する。
+//!
+//! ```text
+//! JWW_GO_PARSER=../jww-parser/bin/jww-parser cargo test --test compatibility_test -- --ignored
+//! ```
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use jww_dxf::testing::{HarnessConfig, GO_PARSER_ENV};
+
+/// ワークスペース直下のディレクトリ
+fn workspace_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../..")
+}
+
+/// 互換性テストの設定 (`jww-compat.toml` と環境変数)
+fn config() -> HarnessConfig {
+    HarnessConfig::load(&workspace_dir().join("jww-compat.toml"))
+        .unwrap_or_else(|e| panic!("互換性テストの設定が読み込めません: {}", e))
+}
+
 /// Go版パーサーのバイナリパス
 fn go_parser_path() -> PathBuf {
-    PathBuf::from("/Users/fu2hito/src/jww/jww-parser/bin/jww-parser")
+    config().go_parser.unwrap_or_else(|| {
+        panic!(
+            "Go版パーサーのパスが設定されていません。\n\
+             jww-compat.toml の go_parser または環境変数 {} で指定してください",
+            GO_PARSER_ENV
+        )
+    })
 }
 
-/// テストフィクスチャディレクトリ
+/// テストフィクスチャディレクトリ (省略時はワークスペースの `tests/fixtures`)
 fn fixtures_dir() -> PathBuf {
-    PathBuf::from("/Users/fu2hito/src/jww/jww-parser-rs/tests/fixtures")
+    config()
+        .fixtures_dir
+        .unwrap_or_else(|| workspace_dir().join("tests/fixtures"))
 }
 
 /// Go版パーサーを実行してDXF出力を取得
-fn run_go_parser(jww_path: &PathBuf) -> Result<String, String> {
+fn run_go_parser(jww_path: &Path) -> Result<String, String> {
     let temp_dir = std::env::temp_dir();
     let dxf_path = temp_dir.join(format!("output_{}.dxf", std::process::id()));
 
@@ -57,7 +85,7 @@ fn test_go_parser_available() {
     let path = go_parser_path();
     assert!(
         path.exists(),
-        "Go版パーサーが見つかりません: {}\n先にビルドしてください: cd /Users/fu2hito/src/jww/jww-parser && go build -o bin/jww-parser ./cmd/jww-parser",
+        "Go版パーサーが見つかりません: {}\n先にビルドしてください: cd jww-parser && go build -o bin/jww-parser ./cmd/jww-parser",
         path.display()
     );
 
@@ -122,9 +150,7 @@ fn create_minimal_jww_data() -> Vec<u8> {
     data.extend_from_slice(b"CDataXXXX"); // class name
 
     // パディング
-    for _ in 0..100 {
-        data.push(0);
-    }
+    data.extend_from_slice(&[0u8; 100]);
 
     data
 }
@@ -149,8 +175,11 @@ fn test_compatibility_minimal_jww() {
     let go_dxf = run_go_parser(&jww_path).expect("Go版パーサーが失敗");
 
     // DXFを解析して比較
-    let (go_entities, go_layers) = jww_dxf::testing::parse_dxf_entities(&go_dxf);
-    let (rust_entities, rust_layers) = jww_dxf::testing::parse_dxf_entities(&rust_dxf);
+    // ブロックはINSERTを展開して図形単位で比較する
+    let go_drawing = jww_dxf::testing::parse_dxf(&go_dxf);
+    let rust_drawing = jww_dxf::testing::parse_dxf(&rust_dxf);
+    let (go_entities, go_layers) = (go_drawing.resolved_entities(), go_drawing.layers);
+    let (rust_entities, rust_layers) = (rust_drawing.resolved_entities(), rust_drawing.layers);
 
     eprintln!("Go版: {} エンティティ, {} レイヤー", go_entities.len(), go_layers.len());
     eprintln!("Rust版: {} エンティティ, {} レイヤー", rust_entities.len(), rust_layers.len());
@@ -177,6 +206,51 @@ fn test_compatibility_minimal_jww() {
     }
 }
 
+#[test]
+#[ignore]
+fn test_compatibility_fixtures() {
+    let config = config();
+    let dir = fixtures_dir();
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .unwrap_or_else(|e| {
+            panic!("フィクスチャディレクトリが読み込めません: {}: {}", dir.display(), e)
+        })
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("jww")))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "フィクスチャがありません: {}", dir.display());
+
+    let mut failures = Vec::new();
+    for path in &paths {
+        let jww_data = std::fs::read(path).expect("フィクスチャの読み込みに失敗");
+        let rust_dxf = run_rust_parser(&jww_data).expect("Rust版パーサーが失敗");
+        let go_dxf = run_go_parser(path).expect("Go版パーサーが失敗");
+
+        let go_entities = jww_dxf::testing::parse_dxf(&go_dxf).resolved_entities();
+        let rust_entities = jww_dxf::testing::parse_dxf(&rust_dxf).resolved_entities();
+        let differences = jww_dxf::testing::compare_dxf_entities_with(
+            &go_entities,
+            &rust_entities,
+            &config.compare,
+        );
+
+        eprintln!(
+            "{}: {} エンティティ, {} 件の差異",
+            path.display(),
+            rust_entities.len(),
+            differences.len()
+        );
+        for difference in &differences {
+            eprintln!("  {}", difference);
+        }
+        if !differences.is_empty() {
+            failures.push(path.display().to_string());
+        }
+    }
+    assert!(failures.is_empty(), "Go版と出力が一致しません: {:?}", failures);
+}
+
 #[test]
 fn test_rust_parser_only_minimal() {
     // Go版がなくてもRust版単体でテスト
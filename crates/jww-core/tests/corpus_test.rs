@@ -0,0 +1,283 @@
+//! 実ファイルのコーパスの集計
+//!
+//! ディレクトリ内 (サブディレクトリを含む) のJWWファイルをすべてパース・DXF変換し、
+//! 成功率、未対応のクラス、エラーの種類、処理時間を集計する。リリースごとに実行して
+//! 実ファイルへの対応状況を比較する。
+//!
+//! コーパスのディレクトリと集計結果 (JSON) の出力先は `jww-compat.toml` の
+//! `corpus_dir`・`corpus_report`、または環境変数で指定する。
+//!
+//! ```text
+//! JWW_CORPUS_DIR=/data/jww JWW_CORPUS_REPORT=target/corpus-report.json \
+//!     cargo test --release -p jww-core --test corpus_test -- --ignored --nocapture
+//! ```
+
+use std::collections::BTreeMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use jww_core::{ParseError, ParseOptions};
+use jww_dxf::testing::{HarnessConfig, CORPUS_DIR_ENV};
+use serde::Serialize;
+
+/// 集計結果に含める処理時間の長いファイルの数
+const SLOWEST_FILES: usize = 10;
+
+/// 1ファイルの処理結果
+struct FileResult {
+    path: PathBuf,
+    bytes: usize,
+    parse_time: Duration,
+    convert_time: Duration,
+    outcome: Outcome,
+}
+
+enum Outcome {
+    /// パース・変換とも成功 (DXFの検査でエラーがあったかどうか)
+    Converted {
+        entity_types: Vec<&'static str>,
+        invalid_dxf: bool,
+    },
+    /// パースに失敗 (寛容モードで読み取れたエンティティ数)
+    ParseFailed {
+        error: ParseError,
+        recovered: Option<usize>,
+    },
+    /// パニック
+    Panicked(String),
+}
+
+/// 集計結果
+#[derive(Debug, Default, Serialize)]
+struct Report {
+    parser_version: &'static str,
+    files: usize,
+    /// パース・変換に成功したファイルの数
+    converted: usize,
+    /// 構造検査 (`jww_dxf::validate`) でエラーがあったDXFの数
+    invalid_dxf: usize,
+    /// 寛容モード (`ParseOptions::lenient`) なら一部を読み取れたファイルの数
+    recovered: usize,
+    panicked: usize,
+    success_rate: f64,
+    total_bytes: usize,
+    parse_secs: f64,
+    convert_secs: f64,
+    /// パースのスループット (MB/s)
+    parse_mb_per_sec: f64,
+    /// エラーの識別子 (`ParseError::code`) ごとのファイル数
+    error_codes: BTreeMap<&'static str, usize>,
+    /// 未対応のエンティティクラスごとのファイル数
+    unknown_classes: BTreeMap<String, usize>,
+    /// エンティティタイプごとの件数 (パースに成功したファイル)
+    entity_types: BTreeMap<&'static str, usize>,
+    /// 処理時間の長いファイル (パス, ミリ秒)
+    slowest: Vec<(String, f64)>,
+    /// 失敗したファイル (パス, エラー)
+    failures: Vec<(String, String)>,
+}
+
+/// ディレクトリ以下のJWWファイル (パス順)
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = std::fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("ディレクトリが読み込めません: {}: {}", dir.display(), e));
+    for entry in entries {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("jww"))
+        {
+            files.push(path);
+        }
+    }
+}
+
+/// エラーの原因の未対応のクラス名
+fn unknown_class(error: &ParseError) -> Option<&str> {
+    match error {
+        ParseError::UnknownEntityClass(name) => Some(name),
+        ParseError::Entity { source, .. } => unknown_class(source),
+        _ => None,
+    }
+}
+
+/// 1ファイルをパース・変換する
+fn process(path: &Path) -> FileResult {
+    let data = std::fs::read(path)
+        .unwrap_or_else(|e| panic!("ファイルが読み込めません: {}: {}", path.display(), e));
+    let mut result = FileResult {
+        path: path.to_path_buf(),
+        bytes: data.len(),
+        parse_time: Duration::ZERO,
+        convert_time: Duration::ZERO,
+        outcome: Outcome::Panicked(String::new()),
+    };
+
+    let outcome = catch_unwind(AssertUnwindSafe(|| {
+        let start = Instant::now();
+        let parsed = jww_core::parse(&data);
+        result.parse_time = start.elapsed();
+        let doc = match parsed {
+            Ok(doc) => doc,
+            Err(error) => {
+                let lenient = ParseOptions {
+                    lenient: true,
+                    ..ParseOptions::default()
+                };
+                let recovered = jww_core::parse_with_options(&data, &lenient)
+                    .ok()
+                    .map(|doc| doc.entities.len());
+                return Outcome::ParseFailed { error, recovered };
+            }
+        };
+
+        let start = Instant::now();
+        let dxf = jww_dxf::to_string(&jww_dxf::convert_document(&doc));
+        result.convert_time = start.elapsed();
+        Outcome::Converted {
+            entity_types: doc.entities.iter().map(|e| e.type_name()).collect(),
+            invalid_dxf: jww_dxf::validate::has_errors(&jww_dxf::validate::validate(&dxf)),
+        }
+    }));
+    result.outcome = outcome.unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Outcome::Panicked(message)
+    });
+    result
+}
+
+/// 処理結果を集計する
+fn summarize(results: &[FileResult]) -> Report {
+    let mut report = Report {
+        parser_version: env!("CARGO_PKG_VERSION"),
+        files: results.len(),
+        ..Report::default()
+    };
+    let mut parse_time = Duration::ZERO;
+    let mut convert_time = Duration::ZERO;
+    for result in results {
+        let path = result.path.display().to_string();
+        report.total_bytes += result.bytes;
+        parse_time += result.parse_time;
+        convert_time += result.convert_time;
+        match &result.outcome {
+            Outcome::Converted {
+                entity_types,
+                invalid_dxf,
+            } => {
+                report.converted += 1;
+                report.invalid_dxf += *invalid_dxf as usize;
+                for name in entity_types {
+                    *report.entity_types.entry(name).or_default() += 1;
+                }
+            }
+            Outcome::ParseFailed { error, recovered } => {
+                *report.error_codes.entry(error.code()).or_default() += 1;
+                if let Some(name) = unknown_class(error) {
+                    *report.unknown_classes.entry(name.to_string()).or_default() += 1;
+                }
+                report.recovered += recovered.is_some() as usize;
+                report.failures.push((path, error.to_string()));
+            }
+            Outcome::Panicked(message) => {
+                report.panicked += 1;
+                report.failures.push((path, format!("panic: {}", message)));
+            }
+        }
+    }
+
+    report.success_rate = report.converted as f64 / report.files.max(1) as f64;
+    report.parse_secs = parse_time.as_secs_f64();
+    report.convert_secs = convert_time.as_secs_f64();
+    if report.parse_secs > 0.0 {
+        report.parse_mb_per_sec = report.total_bytes as f64 / 1e6 / report.parse_secs;
+    }
+
+    let mut by_time: Vec<&FileResult> = results.iter().collect();
+    by_time.sort_by_key(|r| std::cmp::Reverse(r.parse_time + r.convert_time));
+    report.slowest = by_time
+        .iter()
+        .take(SLOWEST_FILES)
+        .map(|r| {
+            let time = r.parse_time + r.convert_time;
+            (r.path.display().to_string(), time.as_secs_f64() * 1000.0)
+        })
+        .collect();
+    report
+}
+
+fn print_report(report: &Report) {
+    eprintln!("ファイル数:     {}", report.files);
+    eprintln!(
+        "変換成功:       {} ({:.1}%)",
+        report.converted,
+        report.success_rate * 100.0
+    );
+    eprintln!("DXF検査エラー:  {}", report.invalid_dxf);
+    eprintln!("寛容モードで一部読み取り可: {}", report.recovered);
+    eprintln!("パニック:       {}", report.panicked);
+    eprintln!(
+        "処理時間:       パース {:.3}s ({:.1} MB/s), 変換 {:.3}s",
+        report.parse_secs, report.parse_mb_per_sec, report.convert_secs
+    );
+    for (code, count) in &report.error_codes {
+        eprintln!("  エラー {}: {}", code, count);
+    }
+    for (name, count) in &report.unknown_classes {
+        eprintln!("  未対応のクラス {}: {}", name, count);
+    }
+    for (name, count) in &report.entity_types {
+        eprintln!("  {}: {}", name, count);
+    }
+    for (path, ms) in &report.slowest {
+        eprintln!("  {:.1}ms {}", ms, path);
+    }
+    for (path, error) in &report.failures {
+        eprintln!("FAILED  {}: {}", path, error);
+    }
+}
+
+#[test]
+#[ignore]
+fn test_corpus() {
+    let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let config = HarnessConfig::load(&workspace.join("jww-compat.toml"))
+        .unwrap_or_else(|e| panic!("設定が読み込めません: {}", e));
+    let dir = config.corpus_dir.unwrap_or_else(|| {
+        panic!(
+            "コーパスのディレクトリが設定されていません。\n\
+             jww-compat.toml の corpus_dir または環境変数 {} で指定してください",
+            CORPUS_DIR_ENV
+        )
+    });
+
+    let mut files = Vec::new();
+    collect_files(&dir, &mut files);
+    files.sort();
+    assert!(
+        !files.is_empty(),
+        "JWWファイルがありません: {}",
+        dir.display()
+    );
+
+    let results: Vec<FileResult> = files.iter().map(|path| process(path)).collect();
+    let report = summarize(&results);
+    print_report(&report);
+
+    if let Some(path) = &config.corpus_report {
+        let json = serde_json::to_string_pretty(&report).unwrap();
+        std::fs::write(path, json)
+            .unwrap_or_else(|e| panic!("集計結果を書き込めません: {}: {}", path.display(), e));
+        eprintln!("集計結果: {}", path.display());
+    }
+
+    // パースの失敗は集計するだけだが、パニックは不具合として扱う
+    assert_eq!(report.panicked, 0, "パニックしたファイルがあります");
+}
@@ -11,6 +11,36 @@ fn test_invalid_signature() {
     }
 }
 
+#[test]
+fn test_unsupported_version() {
+    use jww_core::version::{Capabilities, SUPPORTED_VERSIONS};
+
+    // レイヤ情報やエンティティを読む前に拒否する
+    for version in [0u32, 199, 1001, 0x4A57_5744] {
+        let mut data = b"JwwData.".to_vec();
+        data.extend_from_slice(&version.to_le_bytes());
+        let results = [
+            jww_core::parse(&data).map(|_| ()),
+            jww_core::probe(&data).map(|_| ()),
+        ];
+        for result in results {
+            match result {
+                Err(e @ jww_core::ParseError::UnsupportedVersion(v)) => {
+                    assert_eq!(v, version);
+                    assert_eq!(e.code(), "unsupported_version");
+                }
+                other => panic!("Expected UnsupportedVersion error: {:?}", other),
+            }
+        }
+    }
+
+    assert!(SUPPORTED_VERSIONS.contains(&700));
+    let caps = |version| Capabilities::of(version).unwrap();
+    assert!(!caps(300).pen_width);
+    assert!(caps(351).pen_width && !caps(351).sxf_dimensions && !caps(351).sxf_colors);
+    assert!(caps(420).sxf_dimensions && caps(420).sxf_colors);
+}
+
 #[test]
 fn test_too_short_data() {
     let short_data = b"short";
@@ -56,9 +86,7 @@ fn test_valid_jww_signature() {
 
     // パディングを追加してファイルサイズを増やす（find_entity_list_offsetが探索するため）
     // 実際のJWWファイルではエンティティリストの後にもデータがある
-    for _ in 0..100 {
-        data.push(0);
-    }
+    data.extend_from_slice(&[0u8; 100]);
 
     let result = jww_core::parse(&data);
     assert!(result.is_ok(), "parse failed: {:?}", result.err());
@@ -98,9 +126,7 @@ fn test_dxf_conversion() {
     data.extend_from_slice(b"CDataXXXX"); // class name
 
     // パディングを追加
-    for _ in 0..100 {
-        data.push(0);
-    }
+    data.extend_from_slice(&[0u8; 100]);
 
     let jww_doc = jww_core::parse(&data).unwrap();
     let dxf_doc = jww_dxf::convert_document(&jww_doc);
@@ -139,9 +165,7 @@ fn test_dxf_to_string() {
     data.extend_from_slice(b"CDataXXXX");
 
     // パディングを追加
-    for _ in 0..100 {
-        data.push(0);
-    }
+    data.extend_from_slice(&[0u8; 100]);
 
     let jww_doc = jww_core::parse(&data).unwrap();
     let dxf_doc = jww_dxf::convert_document(&jww_doc);
@@ -154,3 +178,1004 @@ fn test_dxf_to_string() {
     assert!(dxf_string.contains("ENTITIES"));
     assert!(dxf_string.contains("EOF"));
 }
+
+#[test]
+fn test_entity_error_location() {
+    use jww_core::{Document, Entity, EntityBase, Line};
+
+    let line = |x: f64| {
+        Entity::Line(Line {
+            base: EntityBase::default(),
+            start: jww_core::Point2D::new(x, 0.0),
+            end: jww_core::Point2D::new(x, 10.0),
+        })
+    };
+    let doc = Document {
+        version: 600,
+        entities: vec![line(0.0), line(1.0)],
+        ..Default::default()
+    };
+    let data = jww_core::write(&doc).unwrap();
+    assert_eq!(jww_core::parse(&data).unwrap().entities.len(), 2);
+
+    // 2つ目の直線の終点の途中で切り詰める
+    let err = jww_core::parse(&data[..data.len() - 4]).unwrap_err();
+    assert_eq!(err.code(), "unexpected_eof");
+    assert_eq!(err.entity_index(), Some(1));
+    let offset = err.offset().unwrap();
+    assert_eq!(&data[offset..offset + 2], &[0x01, 0x80]);
+}
+
+#[test]
+fn test_probe() {
+    use jww_core::{Document, Entity, EntityBase, Point};
+
+    let point = Entity::Point(Point {
+        base: EntityBase::default(),
+        position: jww_core::Point2D::new(1.0, 2.0),
+        is_temporary: false,
+        code: 0,
+        angle: jww_core::Angle::ZERO,
+        scale: 1.0,
+    });
+    let mut doc = Document {
+        version: 600,
+        memo: "平面図".into(),
+        paper_size: 3,
+        entities: vec![point.clone(), point.clone(), point],
+        ..Default::default()
+    };
+    doc.layer_groups[2].scale = 100.0;
+    let data = jww_core::write(&doc).unwrap();
+
+    let info = jww_core::probe(&data).unwrap();
+    assert_eq!(info.version, 600);
+    assert_eq!(info.memo, "平面図");
+    assert_eq!(info.paper_dimensions(), Some((420.0, 297.0)));
+    assert_eq!(info.layer_groups[2].scale, 100.0);
+    assert_eq!(info.layer_groups[2].layers[10].name, "2-A");
+    assert_eq!(info.entity_count, 3);
+
+    assert!(matches!(
+        jww_core::probe(b"Invalid signature"),
+        Err(jww_core::ParseError::InvalidSignature)
+    ));
+}
+
+#[test]
+fn test_layer_bounding_boxes() {
+    use jww_core::{Document, Entity, EntityBase, Line};
+
+    let line = |layer: u16, x1: f64, y1: f64, x2: f64, y2: f64| {
+        Entity::Line(Line {
+            base: EntityBase {
+                layer,
+                layer_group: 1,
+                ..Default::default()
+            },
+            start: jww_core::Point2D::new(x1, y1),
+            end: jww_core::Point2D::new(x2, y2),
+        })
+    };
+    let doc = Document {
+        entities: vec![
+            line(0, 0.0, 0.0, 10.0, 5.0),
+            line(2, -5.0, -5.0, 0.0, 0.0),
+            line(0, 20.0, 1.0, 15.0, 2.0),
+        ],
+        ..Default::default()
+    };
+
+    let layers = doc.layer_bounding_boxes();
+    assert_eq!(layers.len(), 2);
+    let bb = layers[&(1, 0)];
+    assert_eq!((bb.min_x, bb.min_y, bb.max_x, bb.max_y), (0.0, 0.0, 20.0, 5.0));
+    let bb = layers[&(1, 2)];
+    assert_eq!((bb.min_x, bb.min_y, bb.max_x, bb.max_y), (-5.0, -5.0, 0.0, 0.0));
+
+    let bb = doc.bounding_box().unwrap();
+    assert_eq!((bb.min_x, bb.min_y, bb.max_x, bb.max_y), (-5.0, -5.0, 20.0, 5.0));
+}
+
+#[test]
+fn test_statistics() {
+    use jww_core::{Angle, Document, Entity, EntityBase, Line, Point2D, Text};
+
+    let base = |layer: u16| EntityBase {
+        layer,
+        layer_group: 1,
+        ..Default::default()
+    };
+    let text = |content: &'static str| {
+        Entity::Text(Text {
+            base: base(3),
+            start: Point2D::new(0.0, 0.0),
+            end: Point2D::new(4.0, 0.0),
+            text_type: 0,
+            size_x: 2.0,
+            size_y: 2.0,
+            spacing: 0.0,
+            angle: Angle::ZERO,
+            font_name: "ＭＳ ゴシック".into(),
+            content: content.into(),
+        })
+    };
+    let mut doc = Document {
+        entities: vec![
+            Entity::Line(Line {
+                base: base(0),
+                start: Point2D::new(-10.0, 0.0),
+                end: Point2D::new(10.0, 5.0),
+            }),
+            text("居間"),
+            text(""),
+        ],
+        ..Default::default()
+    };
+    doc.layer_groups[1].layers[3].name = "文字".into();
+
+    let stats = doc.statistics();
+    assert_eq!(stats.entity_count, 3);
+    assert_eq!(stats.by_type["LINE"], 1);
+    assert_eq!(stats.by_type["TEXT"], 2);
+    assert_eq!(stats.text_count, 1);
+    let bb = stats.extents.unwrap();
+    assert_eq!((bb.min_x, bb.min_y, bb.max_x, bb.max_y), (-10.0, 0.0, 10.0, 5.0));
+
+    assert_eq!(stats.layers.len(), 2);
+    let layer = &stats.layers[1];
+    assert_eq!((layer.layer_group, layer.layer), (1, 3));
+    assert_eq!(layer.name, "文字");
+    assert_eq!(layer.entity_count, 2);
+    assert_eq!(layer.extents.unwrap().max_x, 4.0);
+
+    assert_eq!(Document::default().statistics().extents, None);
+}
+
+#[test]
+fn test_dimension_settings() {
+    use jww_core::{Angle, Dimension, Document, Entity, EntityBase, Line, Point, Point2D, Text};
+
+    let base = EntityBase::default();
+    let line = |x1: f64, y1: f64, x2: f64, y2: f64| Line {
+        base: base.clone(),
+        start: Point2D::new(x1, y1),
+        end: Point2D::new(x2, y2),
+    };
+    let point = |x: f64, y: f64| Point {
+        base: base.clone(),
+        position: Point2D::new(x, y),
+        is_temporary: false,
+        code: 0,
+        angle: Angle::ZERO,
+        scale: 1.0,
+    };
+    // 縮尺1/50で離れ2mm・はみ出し3mm・寸法値の間隔0.5mm
+    let dimension = Dimension {
+        base: base.clone(),
+        line: line(0.0, 500.0, 1000.0, 500.0),
+        text: Text {
+            base: base.clone(),
+            start: Point2D::new(400.0, 525.0),
+            end: Point2D::new(600.0, 525.0),
+            text_type: 0,
+            size_x: 2.5,
+            size_y: 2.5,
+            spacing: 0.0,
+            angle: Angle::ZERO,
+            font_name: "ＭＳ ゴシック".into(),
+            content: "1000".into(),
+        },
+        sxf_mode: 0,
+        extension_lines: vec![line(0.0, 100.0, 0.0, 650.0), line(1000.0, 100.0, 1000.0, 650.0)],
+        end_points: vec![point(0.0, 500.0), point(1000.0, 500.0), point(0.0, 0.0), point(1000.0, 0.0)],
+    };
+    let mut doc = Document {
+        entities: vec![Entity::Dimension(dimension)],
+        ..Default::default()
+    };
+    doc.layer_groups[0].scale = 50.0;
+
+    let settings = doc.dimension_settings();
+    assert_eq!(settings.extension_offset, Some(2.0));
+    assert_eq!(settings.extension_extension, Some(3.0));
+    assert_eq!(settings.text_gap, Some(0.5));
+    assert_eq!(settings.text_height, Some(2.5));
+
+    assert_eq!(Document::default().dimension_settings(), Default::default());
+}
+
+#[test]
+fn test_entities_by_layer() {
+    use jww_core::{Document, Entity, EntityBase, Line};
+
+    let line = |layer_group: u16, layer: u16| {
+        Entity::Line(Line {
+            base: EntityBase {
+                layer,
+                layer_group,
+                ..Default::default()
+            },
+            start: jww_core::Point2D::new(0.0, 0.0),
+            end: jww_core::Point2D::new(1.0, 1.0),
+        })
+    };
+    let mut doc = Document {
+        entities: vec![line(0, 1), line(2, 3), line(0, 1)],
+        ..Default::default()
+    };
+
+    let index = doc.entities_by_layer();
+    assert_eq!(index.len(), 2);
+    assert_eq!(index[&(0, 1)], [0, 2]);
+    assert_eq!(index[&(2, 3)], [1]);
+
+    // 2回目以降は計算済みの結果を返す
+    assert!(std::sync::Arc::ptr_eq(&index, &doc.entities_by_layer()));
+    assert_eq!(doc.clone().entities_by_layer(), doc.entities_by_layer());
+
+    // entities_mut で変更すると索引を作り直す
+    doc.entities_mut().push(line(5, 0));
+    assert_eq!(doc.entities_by_layer()[&(5, 0)], [3]);
+    let mut copy = doc.clone();
+    copy.entities_mut().truncate(1);
+    assert_eq!(copy.entities_by_layer().len(), 1);
+    assert_eq!(doc.entities_by_layer().len(), 3);
+
+    // エンティティ数の変わらない直接の変更は invalidate_cache で破棄する
+    doc.entities[0] = line(7, 7);
+    doc.invalidate_cache();
+    assert_eq!(doc.entities_by_layer()[&(7, 7)], [0]);
+
+    // キャッシュはシリアライズしない
+    let json = serde_json::to_string(&doc).unwrap();
+    assert!(!json.contains("cache"));
+    let parsed: Document = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.entities_by_layer().len(), 4);
+}
+
+#[test]
+fn test_entities_by_layer_after_direct_change() {
+    use jww_core::{Document, Entity, EntityBase, Line};
+
+    let line = |layer: u16| {
+        Entity::Line(Line {
+            base: EntityBase {
+                layer,
+                ..Default::default()
+            },
+            start: jww_core::Point2D::new(0.0, 0.0),
+            end: jww_core::Point2D::new(1.0, 1.0),
+        })
+    };
+    let mut doc = Document {
+        entities: vec![line(0), line(1), line(2)],
+        ..Default::default()
+    };
+    assert_eq!(doc.entities_by_layer().len(), 3);
+
+    // entities を直接変更してエンティティ数が変わった場合は索引を作り直す
+    doc.entities.truncate(1);
+    let index = doc.entities_by_layer();
+    assert_eq!(index.len(), 1);
+    assert_eq!(index[&(0, 0)], [0]);
+    doc.entities.push(line(5));
+    assert_eq!(doc.entities_by_layer()[&(0, 5)], [1]);
+}
+
+#[test]
+fn test_bounding_box_paper() {
+    use jww_core::{Document, Entity, EntityBase, Point};
+
+    let point = |layer_group: u16, x: f64, y: f64| {
+        Entity::Point(Point {
+            base: EntityBase {
+                layer_group,
+                ..Default::default()
+            },
+            position: jww_core::Point2D::new(x, y),
+            is_temporary: false,
+            code: 0,
+            angle: jww_core::Angle::ZERO,
+            scale: 1.0,
+        })
+    };
+    let mut doc = Document {
+        entities: vec![point(0, 1000.0, 500.0), point(1, -4000.0, 2000.0)],
+        ..Default::default()
+    };
+    doc.layer_groups[0].scale = 100.0;
+    doc.layer_groups[1].scale = 200.0;
+
+    let bb = doc.bounding_box_paper().unwrap();
+    assert_eq!((bb.min_x, bb.min_y, bb.max_x, bb.max_y), (-20.0, 5.0, 10.0, 10.0));
+
+    // 縮尺が不正なレイヤグループは書込みレイヤグループの縮尺を使う
+    doc.layer_groups[1].scale = 0.0;
+    let bb = doc.bounding_box_paper().unwrap();
+    assert_eq!((bb.min_x, bb.max_y), (-40.0, 20.0));
+
+    assert!(Document::default().bounding_box_paper().is_none());
+}
+
+#[test]
+fn test_validate() {
+    use jww_core::{Block, Document, Entity, EntityBase, Line, Severity};
+
+    let base = EntityBase::default();
+    let mut doc = Document {
+        version: 600,
+        paper_size: 3,
+        entities: vec![
+            Entity::Line(Line {
+                base: base.clone(),
+                start: jww_core::Point2D::new(0.0, 0.0),
+                end: jww_core::Point2D::new(10.0, 0.0),
+            }),
+            Entity::Line(Line {
+                base: base.clone(),
+                start: jww_core::Point2D::new(f64::NAN, 0.0),
+                end: jww_core::Point2D::new(10.0, 0.0),
+            }),
+            Entity::Block(Block {
+                base,
+                reference: jww_core::Point2D::new(0.0, 0.0),
+                scale_x: 1.0,
+                scale_y: 1.0,
+                rotation: jww_core::Angle::ZERO,
+                def_number: 7,
+            }),
+        ],
+        ..Default::default()
+    };
+    assert!(Document { entities: Vec::new(), ..doc.clone() }.validate().is_empty());
+
+    doc.layer_groups[4].scale = 0.0;
+    let findings = doc.validate();
+    let codes: Vec<_> = findings.iter().map(|f| (f.code, f.entity_index)).collect();
+    assert_eq!(
+        codes,
+        vec![
+            ("invalid_scale", None),
+            ("non_finite_coordinate", Some(1)),
+            ("missing_block_def", Some(2)),
+        ]
+    );
+    assert_eq!(findings[1].severity, Severity::Error);
+}
+
+#[test]
+fn test_incremental_parser() {
+    use jww_core::{Document, Entity, EntityBase, IncrementalParser, Line};
+
+    let line = |x: f64| {
+        Entity::Line(Line {
+            base: EntityBase::default(),
+            start: jww_core::Point2D::new(x, 0.0),
+            end: jww_core::Point2D::new(x, 10.0),
+        })
+    };
+    let doc = Document {
+        version: 600,
+        entities: (0..5).map(|i| line(i as f64)).collect(),
+        ..Default::default()
+    };
+    let data = jww_core::write(&doc).unwrap();
+
+    let mut parser = IncrementalParser::new(&data).unwrap();
+    assert_eq!(parser.progress(), (0, 5));
+    assert!(!parser.step(2).unwrap());
+    assert_eq!(parser.progress(), (2, 5));
+    assert!(!parser.step(2).unwrap());
+    assert!(parser.step(2).unwrap());
+    assert_eq!(parser.progress(), (5, 5));
+    let parsed = parser.finish().unwrap();
+    assert_eq!(parsed.entities.len(), 5);
+    match &parsed.entities[4] {
+        Entity::Line(l) => assert_eq!(l.start.x, 4.0),
+        other => panic!("unexpected entity: {:?}", other),
+    }
+}
+
+#[test]
+fn test_search_text() {
+    use jww_core::{Document, Entity, EntityBase, Text};
+
+    let text = |content: &'static str| {
+        Entity::Text(Text {
+            base: EntityBase::default(),
+            start: jww_core::Point2D::new(0.0, 0.0),
+            end: jww_core::Point2D::new(10.0, 0.0),
+            text_type: 0,
+            size_x: 2.5,
+            size_y: 2.5,
+            spacing: 0.0,
+            angle: jww_core::Angle::ZERO,
+            font_name: "ＭＳ ゴシック".into(),
+            content: content.into(),
+        })
+    };
+    let doc = Document {
+        entities: vec![text("居間 LDK"), text("寝室"), text("LDK 上部吹抜")],
+        ..Default::default()
+    };
+
+    let matches = doc.search_text(|s| s.match_indices("LDK").map(|(i, m)| i..i + m.len()).collect());
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[0].entity_index, 0);
+    assert_eq!(matches[0].ranges, vec![7..10]);
+    assert_eq!(matches[1].entity_index, 2);
+    assert_eq!(matches[1].text.content, "LDK 上部吹抜");
+}
+
+#[test]
+fn test_parse_options() {
+    use jww_core::{Document, EncodingPolicy, Entity, EntityBase, Line, ParseOptions};
+
+    let line = |x: f64| {
+        Entity::Line(Line {
+            base: EntityBase::default(),
+            start: jww_core::Point2D::new(x, 0.0),
+            end: jww_core::Point2D::new(x, 10.0),
+        })
+    };
+    let doc = Document {
+        version: 600,
+        memo: "あ".into(),
+        entities: vec![line(0.0), line(1.0)],
+        ..Default::default()
+    };
+    let data = jww_core::write(&doc).unwrap();
+
+    // 寛容モードでは読み取れたエンティティまでを返す
+    let truncated = &data[..data.len() - 4];
+    let lenient = ParseOptions {
+        lenient: true,
+        ..Default::default()
+    };
+    assert!(jww_core::parse_with_options(truncated, &ParseOptions::default()).is_err());
+    let parsed = jww_core::parse_with_options(truncated, &lenient).unwrap();
+    assert_eq!(parsed.entities.len(), 1);
+
+    let limits = [
+        ParseOptions {
+            max_file_size: Some(data.len() - 1),
+            ..Default::default()
+        },
+        ParseOptions {
+            max_entities: Some(1),
+            ..Default::default()
+        },
+        ParseOptions {
+            max_string_length: Some(1),
+            ..Default::default()
+        },
+    ];
+    for options in &limits {
+        let err = jww_core::parse_with_options(&data, options).unwrap_err();
+        assert_eq!(err.code(), "limit_exceeded");
+    }
+
+    // メモの「あ」(0x82 0xA0) を不正なバイト列にする
+    let mut broken = data.clone();
+    let pos = broken.windows(2).position(|w| w == [0x82, 0xA0]).unwrap();
+    broken[pos + 1] = 0x20;
+    assert!(jww_core::parse(&broken).is_ok());
+    let strict = ParseOptions {
+        encoding: EncodingPolicy::Strict,
+        ..Default::default()
+    };
+    let err = jww_core::parse_with_options(&broken, &strict).unwrap_err();
+    assert_eq!(err.code(), "invalid_encoding");
+}
+
+#[test]
+fn test_preserve_unknown_classes() {
+    use jww_core::{Document, Entity, EntityBase, EntityKind, Line, ParseOptions, Point};
+
+    let line = |x: f64| {
+        Entity::Line(Line {
+            base: EntityBase::default(),
+            start: jww_core::Point2D::new(x, 0.0),
+            end: jww_core::Point2D::new(x, 10.0),
+        })
+    };
+    let point = Entity::Point(Point {
+        base: EntityBase {
+            layer: 3,
+            ..Default::default()
+        },
+        position: jww_core::Point2D::new(5.0, 5.0),
+        is_temporary: false,
+        code: 0,
+        angle: jww_core::Angle::ZERO,
+        scale: 1.0,
+    });
+    let doc = Document {
+        version: 600,
+        entities: vec![line(0.0), point, line(1.0)],
+        ..Default::default()
+    };
+    let mut data = jww_core::write(&doc).unwrap();
+    // 点のクラス名を未知のクラスにする
+    let pos = data.windows(8).position(|w| w == b"CDataTen").unwrap();
+    data[pos..pos + 8].copy_from_slice(b"CDataXyz");
+
+    let err = jww_core::parse(&data).unwrap_err();
+    assert_eq!(err.code(), "unknown_entity_class");
+
+    let options = ParseOptions {
+        preserve_unknown_classes: true,
+        ..Default::default()
+    };
+    let parsed = jww_core::parse_with_options(&data, &options).unwrap();
+    // 未知のエンティティより後は読み取らない
+    assert_eq!(parsed.entities.len(), 2);
+    let Entity::Unknown(unknown) = &parsed.entities[1] else {
+        panic!("expected an unknown entity: {:?}", parsed.entities[1]);
+    };
+    assert_eq!(unknown.class_name, "CDataXyz");
+    assert_eq!(unknown.base.layer, 3);
+    assert_eq!(&unknown.data[..16], [5.0f64.to_le_bytes(), 5.0f64.to_le_bytes()].concat());
+    assert_eq!(parsed.entities[1].kind(), EntityKind::Unknown);
+    assert_eq!(parsed.bounding_box().unwrap().max_x, 0.0);
+    assert!(parsed.validate().iter().any(|f| f.code == "unknown_entity_class"));
+
+    let err = jww_core::write(&parsed).unwrap_err();
+    assert_eq!(err.code(), "unknown_entity_class");
+}
+
+#[test]
+fn test_memory_size() {
+    use jww_core::{Document, Entity, Point};
+
+    let empty = Document::default();
+    let entities = (0..100)
+        .map(|i| {
+            Entity::Point(Point {
+                base: jww_core::EntityBase::default(),
+                position: jww_core::Point2D::new(i as f64, 0.0),
+                is_temporary: false,
+                code: 0,
+                angle: jww_core::Angle::ZERO,
+                scale: 1.0,
+            })
+        })
+        .collect();
+    let doc = Document {
+        entities,
+        ..Default::default()
+    };
+    assert!(doc.memory_size() >= empty.memory_size() + 100 * std::mem::size_of::<Entity>());
+}
+
+#[test]
+fn test_layout() {
+    use jww_core::{Document, Entity, EntityBase, Line, SpanKind};
+
+    let line = |x: f64| {
+        Entity::Line(Line {
+            base: EntityBase::default(),
+            start: jww_core::Point2D::new(x, 0.0),
+            end: jww_core::Point2D::new(x, 10.0),
+        })
+    };
+    let doc = Document {
+        version: 600,
+        memo: "test".into(),
+        entities: vec![line(0.0), line(1.0)],
+        ..Default::default()
+    };
+    let data = jww_core::write(&doc).unwrap();
+
+    let layout = jww_core::layout(&data).unwrap();
+    assert!(layout.error.is_none());
+    assert_eq!(layout.spans[0].kind, SpanKind::Signature);
+    assert_eq!(layout.spans[1].kind, SpanKind::Version(600));
+    assert_eq!(layout.spans[2].kind, SpanKind::Memo("test".to_string()));
+    // 範囲は重ならずに昇順に並ぶ
+    for pair in layout.spans.windows(2) {
+        assert!(pair[0].range.end <= pair[1].range.start);
+    }
+    let kinds: Vec<&SpanKind> = layout.spans.iter().map(|s| &s.kind).collect();
+    assert!(kinds.contains(&&SpanKind::EntityCount(2)));
+    assert!(kinds.contains(&&SpanKind::ClassDefinition("CDataSen".to_string())));
+    // 最後のエンティティの後はブロック定義リスト
+    let [.., last_entity, trailing] = &layout.spans[..] else {
+        unreachable!()
+    };
+    assert_eq!(
+        last_entity.kind,
+        SpanKind::Entity {
+            index: 1,
+            class: "CDataSen".to_string()
+        }
+    );
+    assert_eq!(trailing.kind, SpanKind::Trailing);
+    assert_eq!(trailing.range, last_entity.range.end..data.len());
+
+    let truncated = jww_core::layout(&data[..data.len() - 4]).unwrap();
+    assert_eq!(truncated.error.unwrap().entity_index(), Some(1));
+}
+
+#[test]
+fn test_record_spans() {
+    use jww_core::{ParseOptions, SpanKind};
+
+    let data = std::fs::read(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../tests/fixtures/geometry.jww"),
+    )
+    .unwrap();
+    let options = ParseOptions {
+        record_spans: true,
+        ..Default::default()
+    };
+    let doc = jww_core::parse_with_options(&data, &options).unwrap();
+
+    // 範囲の終端は対応表のエンティティの終端と一致する (先頭はクラス定義を含む)
+    let layout = jww_core::layout(&data).unwrap();
+    let ends: Vec<usize> = layout
+        .spans
+        .iter()
+        .filter(|s| matches!(s.kind, SpanKind::Entity { .. }))
+        .map(|s| s.range.end)
+        .collect();
+    assert_eq!(ends.len(), doc.entities.len());
+    let mut previous = 0;
+    for (entity, end) in doc.entities.iter().zip(ends) {
+        let span = entity.base().source.expect("source span");
+        assert!(span.offset >= previous);
+        assert_eq!(span.range().end, end);
+        assert!(span.length > 0);
+        previous = span.range().end;
+    }
+
+    // 既定では記録せず、JSONにも出力しない
+    let doc = jww_core::parse(&data).unwrap();
+    assert!(doc.entities.iter().all(|e| e.base().source.is_none()));
+    assert!(!serde_json::to_string(&doc).unwrap().contains("\"source\""));
+}
+
+#[test]
+fn test_borrowed_strings() {
+    use jww_core::{Document, Entity, EntityBase, Text};
+    use std::borrow::Cow;
+
+    let text = |content: &'static str| {
+        Entity::Text(Text {
+            base: EntityBase::default(),
+            start: jww_core::Point2D::new(0.0, 0.0),
+            end: jww_core::Point2D::new(10.0, 0.0),
+            text_type: 0,
+            size_x: 2.5,
+            size_y: 2.5,
+            spacing: 0.0,
+            angle: jww_core::Angle::ZERO,
+            font_name: "Arial".into(),
+            content: content.into(),
+        })
+    };
+    let doc = Document {
+        version: 600,
+        memo: "memo".into(),
+        entities: vec![text("A-101"), text("居間")],
+        ..Default::default()
+    };
+    let data = jww_core::write(&doc).unwrap();
+    let parsed = jww_core::parse(&data).unwrap();
+
+    // ASCIIのみの文字列は入力から借用し、それ以外は変換して保持する
+    assert!(matches!(parsed.memo, Cow::Borrowed("memo")));
+    let contents: Vec<&Cow<str>> = parsed
+        .entities
+        .iter()
+        .filter_map(|e| match e {
+            Entity::Text(t) => Some(&t.content),
+            _ => None,
+        })
+        .collect();
+    assert!(matches!(contents[0], Cow::Borrowed("A-101")));
+    assert!(matches!(contents[1], Cow::Owned(s) if s == "居間"));
+
+    let owned: Document<'static> = parsed.into_owned();
+    drop(data);
+    assert_eq!(owned.memo, "memo");
+    assert_eq!(owned.entities.len(), 2);
+}
+
+#[test]
+fn test_resolved_rgb() {
+    use jww_core::{Document, Entity, EntityBase, Palette, Point, Rgb, Solid};
+
+    let base = |pen_color: u16| EntityBase {
+        pen_color,
+        ..Default::default()
+    };
+    let solid = |pen_color: u16| {
+        Entity::Solid(Solid {
+            base: base(pen_color),
+            point1: jww_core::Point2D::new(0.0, 0.0),
+            point2: jww_core::Point2D::new(1.0, 0.0),
+            point3: jww_core::Point2D::new(0.0, 1.0),
+            point4: jww_core::Point2D::new(1.0, 1.0),
+            color: 0x0080_40FF,
+        })
+    };
+    let point = Entity::Point(Point {
+        base: base(8),
+        position: jww_core::Point2D::new(0.0, 0.0),
+        is_temporary: false,
+        code: 0,
+        angle: jww_core::Angle::ZERO,
+        scale: 1.0,
+    });
+    let doc = Document::default();
+
+    assert_eq!(point.resolved_rgb(&doc), Rgb::new(255, 0, 0));
+    // 線色10のソリッドはCOLORREFの任意色
+    assert_eq!(solid(10).resolved_rgb(&doc), Rgb::new(255, 64, 128));
+    assert_eq!(solid(6).resolved_rgb(&doc), Rgb::new(0, 0, 255));
+    assert_eq!(Rgb::new(255, 64, 128).to_hex(), "#ff4080");
+
+    // 印刷用のパレットでも線色10の任意色はそのまま
+    assert_eq!(point.resolved_rgb_with(&doc, &Palette::PRINTER), Rgb::BLACK);
+    assert_eq!(
+        solid(10).resolved_rgb_with(&doc, &Palette::PRINTER),
+        Rgb::new(255, 64, 128)
+    );
+}
+
+#[test]
+fn test_sxf_colors() {
+    use jww_core::{Document, Entity, EntityBase, Line, Palette, Rgb};
+
+    let line = |pen_color: u16| {
+        Entity::Line(Line {
+            base: EntityBase {
+                pen_color,
+                ..Default::default()
+            },
+            start: jww_core::Point2D::new(0.0, 0.0),
+            end: jww_core::Point2D::new(1.0, 1.0),
+        })
+    };
+    let mut sxf_colors: Vec<Rgb> = (0..257).map(Rgb::from_colorref).collect();
+    sxf_colors[17] = Rgb::from_colorref(0x0040_80FF);
+    let doc = Document {
+        version: 700,
+        entities: vec![line(102), line(117)],
+        sxf_colors,
+        ..Default::default()
+    };
+    let data = jww_core::write(&doc).unwrap();
+
+    let parsed = jww_core::parse(&data).unwrap();
+    assert_eq!(parsed.sxf_colors, doc.sxf_colors);
+    assert_eq!(parsed.entities.len(), 2);
+    assert_eq!(parsed.entities[0].resolved_rgb(&parsed), Rgb::from_colorref(2));
+    // ユーザー定義色 (SXF色番号17) はファイルの定義色を使い、パレットに依存しない
+    assert_eq!(parsed.entities[1].resolved_rgb(&parsed), Rgb::new(255, 128, 64));
+    assert_eq!(
+        parsed.entities[1].resolved_rgb_with(&parsed, &Palette::PRINTER),
+        Rgb::new(255, 128, 64)
+    );
+
+    // 色として不正な値を含む定義は読み取らない (最初の出現は画面表示色の定義)
+    let color = [0x0040_80FFu32.to_le_bytes(), 1u32.to_le_bytes()].concat();
+    let pos = data.windows(8).position(|w| w == color.as_slice()).unwrap();
+    let mut invalid = data.clone();
+    invalid[pos..pos + 4].copy_from_slice(&0xFF00_0000u32.to_le_bytes());
+    assert!(jww_core::parse(&invalid).unwrap().sxf_colors.is_empty());
+
+    // 定義のない図面では既定義色を書き込み、ユーザー定義色は黒
+    let doc = Document {
+        sxf_colors: Vec::new(),
+        ..doc
+    };
+    let data = jww_core::write(&doc).unwrap();
+    let parsed = jww_core::parse(&data).unwrap();
+    assert_eq!(parsed.sxf_colors.len(), 257);
+    assert_eq!(parsed.entities[0].resolved_rgb(&parsed), Rgb::new(255, 0, 0));
+    assert_eq!(parsed.entities[1].resolved_rgb(&parsed), Rgb::BLACK);
+
+    // 定義を持たないバージョンでは既定義色を使う
+    let doc = Document { version: 351, ..doc };
+    let data = jww_core::write(&doc).unwrap();
+    let parsed = jww_core::parse(&data).unwrap();
+    assert!(parsed.sxf_colors.is_empty());
+    assert_eq!(parsed.entities[0].resolved_rgb(&parsed), Rgb::new(255, 0, 0));
+    assert_eq!(parsed.entities[1].resolved_rgb(&parsed), Rgb::BLACK);
+}
+
+#[test]
+fn test_normalize() {
+    use jww_core::normalize;
+    use std::borrow::Cow;
+
+    assert_eq!(normalize::width("ＡＢ１２　（寝室）"), "AB12 (寝室)");
+    assert_eq!(normalize::width("ｶﾞｽｺﾝﾛ ﾊﾟｲﾌﾟ ｳﾞ"), "ガスコンロ パイプ ヴ");
+    assert!(matches!(normalize::width("居間 LDK"), Cow::Borrowed(_)));
+    assert_eq!(normalize::wave_dash("1〜3階 −5‖"), "1～3階 －5∥");
+    assert_eq!(normalize::text("１〜３"), "1~3");
+
+    assert_eq!(normalize::font_name("ＭＳ ゴシック"), "MS Gothic");
+    assert_eq!(normalize::font_name("@MS 明朝"), "@MS Mincho");
+    assert_eq!(normalize::font_name("ＨＧ丸ｺﾞｼｯｸM-PRO"), "HG丸ゴシックM-PRO");
+    assert!(matches!(normalize::font_name("MS Gothic"), Cow::Borrowed(_)));
+}
+
+#[test]
+fn test_search_text_normalized() {
+    use jww_core::{normalize, Document, Entity, EntityBase, Text};
+
+    let text = |content: &'static str| {
+        Entity::Text(Text {
+            base: EntityBase::default(),
+            start: jww_core::Point2D::new(0.0, 0.0),
+            end: jww_core::Point2D::new(10.0, 0.0),
+            text_type: 0,
+            size_x: 2.5,
+            size_y: 2.5,
+            spacing: 0.0,
+            angle: jww_core::Angle::ZERO,
+            font_name: "ＭＳ ゴシック".into(),
+            content: content.into(),
+        })
+    };
+    let doc = Document {
+        entities: vec![text("ＬＤＫ 1～3階"), text("ﾀﾞｲﾆﾝｸﾞ"), text("LDK")],
+        ..Default::default()
+    };
+
+    let query = normalize::text("LDK 1〜3");
+    let matches = doc.search_text_normalized(|s| {
+        s.match_indices(query.as_ref())
+            .map(|(i, m)| i..i + m.len())
+            .collect()
+    });
+    assert_eq!(matches.len(), 1);
+    // 一致箇所は正規化前の文字列の位置
+    let range = matches[0].ranges[0].clone();
+    assert_eq!(&matches[0].text.content[range], "ＬＤＫ 1～3");
+
+    let query = normalize::text("ダイニング");
+    let matches = doc.search_text_normalized(|s| {
+        s.match_indices(query.as_ref())
+            .map(|(i, m)| i..i + m.len())
+            .collect()
+    });
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].ranges, vec![0..21]);
+}
+
+#[test]
+fn test_angle() {
+    use jww_core::{Angle, EntityBase, Text};
+    use std::f64::consts::FRAC_PI_2;
+
+    let right = Angle::from_degrees(90.0);
+    assert_eq!(right.degrees(), 90.0);
+    assert!((right.radians() - FRAC_PI_2).abs() < 1e-12);
+    assert_eq!(Angle::from_radians(FRAC_PI_2).radians(), FRAC_PI_2);
+    assert_eq!(right, Angle::from_radians(FRAC_PI_2));
+    assert_eq!((right - right).degrees(), 0.0);
+    assert_eq!(Angle::default(), Angle::ZERO);
+
+    // JSONでは文字の角度は度、それ以外はラジアンの数値になる
+    let text = Text {
+        base: EntityBase::default(),
+        start: jww_core::Point2D::new(0.0, 0.0),
+        end: jww_core::Point2D::new(10.0, 0.0),
+        text_type: 0,
+        size_x: 2.5,
+        size_y: 2.5,
+        spacing: 0.0,
+        angle: Angle::from_degrees(30.0),
+        font_name: "ＭＳ ゴシック".into(),
+        content: "A".into(),
+    };
+    let json = serde_json::to_value(&text).unwrap();
+    assert_eq!(json["angle"], 30.0);
+    let parsed: Text = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed.angle.degrees(), 30.0);
+}
+
+#[test]
+fn test_text_rotation() {
+    use jww_core::{Angle, EntityBase, Point2D, Text};
+
+    let text = |end: Point2D, angle: f64, font_name: &'static str| Text {
+        base: EntityBase::default(),
+        start: Point2D::new(0.0, 0.0),
+        end,
+        text_type: 0,
+        size_x: 2.5,
+        size_y: 2.5,
+        spacing: 0.0,
+        angle: Angle::from_degrees(angle),
+        font_name: font_name.into(),
+        content: "A".into(),
+    };
+
+    // 角度が古い値のままでも始点から終点への向きを使う
+    let rotation = text(Point2D::new(0.0, 10.0), 0.0, "ＭＳ ゴシック").rotation();
+    assert!((rotation.degrees() - 90.0).abs() < 1e-9);
+    // 向きと一致する場合は保存されている値をそのまま使う
+    let stored = text(Point2D::new(10.0, 10.0), 45.0, "ＭＳ ゴシック");
+    assert_eq!(stored.rotation(), stored.angle);
+    // 始点と終点が一致する場合は角度のフィールド
+    assert_eq!(
+        text(Point2D::new(0.0, 0.0), 30.0, "ＭＳ ゴシック")
+            .rotation()
+            .degrees(),
+        30.0
+    );
+    // 縦書きは文字が下へ並ぶ
+    let vertical = text(Point2D::new(0.0, -10.0), 0.0, "@ＭＳ ゴシック");
+    assert_eq!(vertical.rotation(), vertical.angle);
+}
+
+#[test]
+fn test_point2d_schema() {
+    use jww_core::{Block, Entity, EntityBase, Point, Point2D, Solid};
+
+    let base = EntityBase::default();
+    let entities = vec![
+        Entity::Point(Point {
+            base: base.clone(),
+            position: Point2D::new(1.0, 2.0),
+            is_temporary: false,
+            code: 0,
+            angle: jww_core::Angle::ZERO,
+            scale: 1.0,
+        }),
+        Entity::Solid(Solid {
+            base: base.clone(),
+            point1: Point2D::new(0.0, 0.0),
+            point2: Point2D::new(10.0, 0.0),
+            point3: Point2D::new(0.0, 10.0),
+            point4: Point2D::new(10.0, 10.0),
+            color: 0,
+        }),
+        Entity::Block(Block {
+            base,
+            reference: Point2D::new(100.0, 50.0),
+            scale_x: 1.0,
+            scale_y: 1.0,
+            rotation: jww_core::Angle::ZERO,
+            def_number: 1,
+        }),
+    ];
+
+    // スキーマ2: 座標は入れ子のオブジェクト
+    let v2 = serde_json::to_value(&entities).unwrap();
+    assert_eq!(v2[0]["position"], serde_json::json!({"x": 1.0, "y": 2.0}));
+    assert_eq!(v2[2]["reference"]["x"], 100.0);
+
+    // スキーマ1: 座標は平坦なフィールド
+    let mut v1 = v2.clone();
+    for (entity, fields) in v1.as_array_mut().unwrap().iter_mut().zip([
+        &[("position", "x", "y")][..],
+        &[
+            ("point1", "point1_x", "point1_y"),
+            ("point2", "point2_x", "point2_y"),
+            ("point3", "point3_x", "point3_y"),
+            ("point4", "point4_x", "point4_y"),
+        ],
+        &[("reference", "ref_x", "ref_y")],
+    ]) {
+        let entity = entity.as_object_mut().unwrap();
+        for &(name, x, y) in fields {
+            let point = entity.remove(name).unwrap();
+            entity.insert(x.to_string(), point["x"].clone());
+            entity.insert(y.to_string(), point["y"].clone());
+        }
+    }
+    let parsed: Vec<Entity> = serde_json::from_value(v1).unwrap();
+    assert_eq!(serde_json::to_value(&parsed).unwrap(), v2);
+
+    // 座標がない場合はエラー
+    let mut missing = v2.clone();
+    missing[0].as_object_mut().unwrap().remove("position");
+    assert!(serde_json::from_value::<Vec<Entity>>(missing).is_err());
+}
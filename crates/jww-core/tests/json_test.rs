@@ -0,0 +1,60 @@
+//! JSON出力の形式のテスト (`cargo test -p jww-core --features json`)
+
+#![cfg(feature = "json")]
+
+use jww_core::json::{EntityTagging, FieldNaming, JsonProfile};
+
+fn fixture(name: &str) -> Vec<u8> {
+    std::fs::read(
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../tests/fixtures")
+            .join(name),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_json_profile() {
+    let data = fixture("geometry.jww");
+    let doc = jww_core::parse(&data).unwrap();
+
+    // 既定の形式は serde_json の出力そのまま
+    let default = JsonProfile::default().to_value(&doc).unwrap();
+    assert_eq!(default, serde_json::to_value(&doc).unwrap());
+
+    let camel = JsonProfile {
+        field_naming: FieldNaming::CamelCase,
+        ..Default::default()
+    };
+    let value = camel.to_value(&doc).unwrap();
+    assert!(value.get("paperSize").is_some());
+    assert!(value.get("paper_size").is_none());
+    let entity = &value["entities"][0];
+    assert!(entity["type"].is_string());
+    assert!(entity.get("penColor").is_some());
+
+    let compact = JsonProfile {
+        field_naming: FieldNaming::SnakeCase,
+        entity_tagging: EntityTagging::Compact,
+    };
+    let value = compact.to_value(&doc).unwrap();
+    for (entity, original) in value["entities"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .zip(default["entities"].as_array().unwrap())
+    {
+        let kind = original["type"].as_str().unwrap();
+        let fields = entity[kind].as_object().unwrap();
+        assert_eq!(entity.as_object().unwrap().len(), 1);
+        assert!(!fields.contains_key("type"));
+        assert_eq!(fields["pen_color"], original["pen_color"]);
+    }
+
+    // 設定ファイルなどからの読み込み
+    let profile: JsonProfile =
+        serde_json::from_str(r#"{"field_naming": "camelCase", "entity_tagging": "compact"}"#)
+            .unwrap();
+    assert_eq!(profile.field_naming, FieldNaming::CamelCase);
+    assert_eq!(profile.entity_tagging, EntityTagging::Compact);
+}
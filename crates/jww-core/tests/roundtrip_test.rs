@@ -0,0 +1,192 @@
+//! JWW書き出しの往復テスト
+//!
+//! パース → 書き出し → パースの結果が元のドキュメントと一致することを、
+//! `tests/fixtures` のすべてのファイルで確認する。実ファイルのコーパス
+//! (`jww-compat.toml` の `corpus_dir` または `JWW_CORPUS_DIR`) でも実行できる。
+//!
+//! ```text
+//! JWW_CORPUS_DIR=/data/jww cargo test --release -p jww-core --test roundtrip_test -- --ignored
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use jww_core::Document;
+use jww_dxf::testing::{HarnessConfig, CORPUS_DIR_ENV};
+use serde_json::Value;
+
+/// 数値の相対許容誤差
+const EPSILON: f64 = 1e-9;
+
+/// 報告する差異の最大件数
+const MAX_DIFFERENCES: usize = 20;
+
+/// 2つの数値がほぼ等しいかどうか (NaN同士は等しいとみなす)
+fn approx_eq(a: f64, b: f64) -> bool {
+    (a.is_nan() && b.is_nan()) || a == b || (a - b).abs() <= EPSILON * a.abs().max(b.abs()).max(1.0)
+}
+
+/// 2つの値を構造的に比較し、差異のあるパスを集める (数値はほぼ等しければよい)
+fn diff_values(path: &str, expected: &Value, actual: &Value, differences: &mut Vec<String>) {
+    if differences.len() >= MAX_DIFFERENCES {
+        return;
+    }
+    match (expected, actual) {
+        (Value::Number(a), Value::Number(b)) => {
+            let (a, b) = (a.as_f64().unwrap(), b.as_f64().unwrap());
+            if !approx_eq(a, b) {
+                differences.push(format!("{}: {} != {}", path, a, b));
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            if a.len() != b.len() {
+                differences.push(format!("{}: length {} != {}", path, a.len(), b.len()));
+            }
+            for (i, (a, b)) in a.iter().zip(b).enumerate() {
+                diff_values(&format!("{}[{}]", path, i), a, b, differences);
+            }
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            for (key, a_value) in a {
+                match b.get(key) {
+                    Some(b_value) => {
+                        diff_values(&format!("{}.{}", path, key), a_value, b_value, differences)
+                    }
+                    None => differences.push(format!("{}.{}: missing", path, key)),
+                }
+            }
+            for key in b.keys().filter(|key| !a.contains_key(*key)) {
+                differences.push(format!("{}.{}: unexpected", path, key));
+            }
+        }
+        _ if expected != actual => {
+            differences.push(format!("{}: {} != {}", path, expected, actual));
+        }
+        _ => {}
+    }
+}
+
+/// 2つのドキュメントの差異 (一致すれば空)
+fn diff_documents(expected: &Document, actual: &Document) -> Vec<String> {
+    let mut differences = Vec::new();
+    diff_values(
+        "doc",
+        &serde_json::to_value(expected).unwrap(),
+        &serde_json::to_value(actual).unwrap(),
+        &mut differences,
+    );
+    differences
+}
+
+/// パース → 書き出し → パースを行い、問題があれば説明を返す
+///
+/// 元のファイルを読めない場合は `Ok(false)` (対象外) を返す。
+fn roundtrip(data: &[u8]) -> Result<bool, String> {
+    let Ok(doc) = jww_core::parse(data) else {
+        return Ok(false);
+    };
+    let written = jww_core::write(&doc).map_err(|e| format!("write: {}", e))?;
+    let reparsed = jww_core::parse(&written).map_err(|e| format!("reparse: {}", e))?;
+
+    let differences = diff_documents(&doc, &reparsed);
+    if !differences.is_empty() {
+        return Err(differences.join("\n"));
+    }
+    // 書き出し結果は読み直しても変わらない
+    let rewritten = jww_core::write(&reparsed).map_err(|e| format!("rewrite: {}", e))?;
+    if rewritten != written {
+        return Err("rewritten data differs from the first write".to_string());
+    }
+    Ok(true)
+}
+
+/// ディレクトリ以下のJWWファイル (パス順)
+fn jww_files(dir: &Path) -> Vec<PathBuf> {
+    fn walk(dir: &Path, files: &mut Vec<PathBuf>) {
+        let entries = std::fs::read_dir(dir)
+            .unwrap_or_else(|e| panic!("ディレクトリが読み込めません: {}: {}", dir.display(), e));
+        for entry in entries {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                walk(&path, files);
+            } else if path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("jww"))
+            {
+                files.push(path);
+            }
+        }
+    }
+    let mut files = Vec::new();
+    walk(dir, &mut files);
+    files.sort();
+    files
+}
+
+/// ディレクトリ以下のすべてのファイルで往復テストを行う
+fn roundtrip_dir(dir: &Path) {
+    let files = jww_files(dir);
+    assert!(
+        !files.is_empty(),
+        "JWWファイルがありません: {}",
+        dir.display()
+    );
+
+    let mut checked = 0;
+    let mut failures = Vec::new();
+    for path in &files {
+        let data = std::fs::read(path).unwrap();
+        match roundtrip(&data) {
+            Ok(true) => checked += 1,
+            Ok(false) => eprintln!("skipped {} (parse failed)", path.display()),
+            Err(message) => failures.push(format!("{}:\n{}", path.display(), message)),
+        }
+    }
+    eprintln!("{} / {} files round-tripped", checked, files.len());
+    assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+}
+
+#[test]
+fn test_approx_eq() {
+    assert!(approx_eq(1.0, 1.0 + 1e-12));
+    assert!(approx_eq(1e6, 1e6 + 1e-4));
+    assert!(!approx_eq(1.0, 1.001));
+    assert!(approx_eq(f64::NAN, f64::NAN));
+
+    let doc = Document {
+        memo: "memo".into(),
+        ..Default::default()
+    };
+    let mut other = doc.clone();
+    other.layer_groups[2].scale = 50.0;
+    other.memo = "changed".into();
+    let differences = diff_documents(&doc, &other);
+    assert_eq!(differences.len(), 2, "{:?}", differences);
+    for path in ["doc.layer_groups[2].scale:", "doc.memo:"] {
+        assert!(
+            differences.iter().any(|d| d.starts_with(path)),
+            "{:?}",
+            differences
+        );
+    }
+}
+
+#[test]
+fn test_roundtrip_fixtures() {
+    roundtrip_dir(&Path::new(env!("CARGO_MANIFEST_DIR")).join("../../tests/fixtures"));
+}
+
+#[test]
+#[ignore]
+fn test_roundtrip_corpus() {
+    let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let config = HarnessConfig::load(&workspace.join("jww-compat.toml"))
+        .unwrap_or_else(|e| panic!("設定が読み込めません: {}", e));
+    let dir = config.corpus_dir.unwrap_or_else(|| {
+        panic!(
+            "コーパスのディレクトリが設定されていません。\n\
+             jww-compat.toml の corpus_dir または環境変数 {} で指定してください",
+            CORPUS_DIR_ENV
+        )
+    });
+    roundtrip_dir(&dir);
+}
@@ -0,0 +1,246 @@
+//! JWW書き出しのテスト
+
+use jww_core::{
+    Angle, Arc, Block, Dimension, Document, Entity, EntityBase, Line, Point, Point2D, Solid, Text,
+};
+
+fn base(pen_style: u8, pen_color: u16) -> EntityBase {
+    EntityBase {
+        group: 3,
+        pen_style,
+        pen_color,
+        pen_width: 5,
+        layer: 2,
+        layer_group: 1,
+        flag: 0x20,
+        ..Default::default()
+    }
+}
+
+fn sample_document(version: u32) -> Document<'static> {
+    let mut doc = Document {
+        version,
+        memo: "〇〇邸 平面図".into(),
+        paper_size: 3,
+        write_layer_group: 1,
+        ..Default::default()
+    };
+    doc.layer_groups[1].scale = 50.0;
+    doc.layer_groups[1].layers[2].protect = 1;
+    doc.entities = vec![
+        Entity::Line(Line {
+            base: base(1, 2),
+            start: Point2D::new(-10.5, 0.25),
+            end: Point2D::new(100.0, 200.0),
+        }),
+        Entity::Arc(Arc {
+            base: base(2, 3),
+            center: Point2D::new(1.0, 2.0),
+            radius: 30.0,
+            start_angle: Angle::from_radians(0.5),
+            arc_angle: Angle::from_radians(1.5),
+            tilt_angle: Angle::from_radians(0.25),
+            flatness: 0.5,
+            is_full_circle: false,
+        }),
+        Entity::Point(Point {
+            base: base(100, 1),
+            position: Point2D::new(5.0, 6.0),
+            is_temporary: false,
+            code: 3,
+            angle: Angle::from_radians(45.0),
+            scale: 2.0,
+        }),
+        Entity::Point(Point {
+            base: base(1, 1),
+            position: Point2D::new(7.0, 8.0),
+            is_temporary: true,
+            code: 0,
+            angle: Angle::ZERO,
+            scale: 1.0,
+        }),
+        Entity::Text(Text {
+            base: base(1, 4),
+            start: Point2D::new(0.0, 0.0),
+            end: Point2D::new(25.0, 0.0),
+            text_type: 10000,
+            size_x: 5.0,
+            size_y: 5.0,
+            spacing: 0.5,
+            angle: Angle::from_degrees(30.0),
+            font_name: "ＭＳ ゴシック".into(),
+            content: "キッチン".repeat(80).into(),
+        }),
+        Entity::Solid(Solid {
+            base: base(1, 10),
+            point1: Point2D::new(0.0, 0.0),
+            point2: Point2D::new(1.0, 0.0),
+            point3: Point2D::new(1.0, 1.0),
+            point4: Point2D::new(0.0, 1.0),
+            color: 0x00FF8000,
+        }),
+        Entity::Block(Block {
+            base: base(1, 2),
+            reference: Point2D::new(10.0, 20.0),
+            scale_x: -1.0,
+            scale_y: 1.0,
+            rotation: Angle::from_radians(0.5),
+            def_number: 7,
+        }),
+        Entity::Line(Line {
+            base: base(1, 2),
+            start: Point2D::new(1.0, 2.0),
+            end: Point2D::new(3.0, 4.0),
+        }),
+    ];
+    doc
+}
+
+#[test]
+fn test_write_then_parse() {
+    for version in [300, 600] {
+        let doc = sample_document(version);
+        let data = jww_core::write(&doc).expect("write");
+        let parsed = jww_core::parse(&data).expect("parse");
+
+        assert_eq!(parsed.version, version);
+        assert_eq!(parsed.memo, doc.memo);
+        assert_eq!(parsed.paper_size, 3);
+        assert_eq!(parsed.write_layer_group, 1);
+        assert_eq!(parsed.layer_groups[1].scale, 50.0);
+        assert_eq!(parsed.layer_groups[1].layers[2].protect, 1);
+
+        // シリアライズ結果で全フィールドを比較する (Ver.3.51未満は線幅を持たない)
+        let mut expected = doc.entities.clone();
+        if version < 351 {
+            for entity in &mut expected {
+                entity.base_mut().pen_width = 0;
+            }
+        }
+        assert_eq!(parsed.entities.len(), expected.len());
+        for (actual, expected) in parsed.entities.iter().zip(&expected) {
+            assert_eq!(format!("{:?}", actual), format!("{:?}", expected));
+        }
+    }
+}
+
+#[test]
+fn test_write_unsupported_version() {
+    let mut doc = sample_document(600);
+    doc.version = 0;
+    assert!(matches!(
+        jww_core::write(&doc),
+        Err(jww_core::ParseError::UnsupportedVersion(0))
+    ));
+    assert_eq!(doc.validate()[0].code, "unsupported_version");
+}
+
+#[test]
+fn test_write_is_deterministic() {
+    let doc = sample_document(600);
+    let data = jww_core::write(&doc).unwrap();
+    let reparsed = jww_core::parse(&data).unwrap();
+    assert_eq!(jww_core::write(&reparsed).unwrap(), data);
+}
+
+#[test]
+fn test_write_dimension() {
+    let member_line = |x1: f64, y1: f64, x2: f64, y2: f64| Line {
+        base: base(1, 2),
+        start: Point2D::new(x1, y1),
+        end: Point2D::new(x2, y2),
+    };
+    let member_point = |x: f64, y: f64| Point {
+        base: base(1, 2),
+        position: Point2D::new(x, y),
+        is_temporary: false,
+        code: 0,
+        angle: Angle::ZERO,
+        scale: 1.0,
+    };
+    let dimension = Entity::Dimension(Dimension {
+        base: base(1, 2),
+        line: member_line(0.0, 500.0, 1000.0, 500.0),
+        text: Text {
+            base: base(1, 3),
+            start: Point2D::new(450.0, 550.0),
+            end: Point2D::new(550.0, 550.0),
+            text_type: 0,
+            size_x: 3.5,
+            size_y: 3.5,
+            spacing: 0.0,
+            angle: Angle::from_degrees(0.0),
+            font_name: "ＭＳ ゴシック".into(),
+            content: "1,000".into(),
+        },
+        sxf_mode: 1,
+        extension_lines: vec![
+            member_line(0.0, 0.0, 0.0, 500.0),
+            member_line(1000.0, 0.0, 1000.0, 500.0),
+        ],
+        end_points: vec![
+            member_point(0.0, 500.0),
+            member_point(1000.0, 500.0),
+            member_point(0.0, 0.0),
+            member_point(1000.0, 0.0),
+        ],
+    });
+
+    let mut doc = sample_document(600);
+    doc.entities.push(dimension.clone());
+    let data = jww_core::write(&doc).unwrap();
+    let parsed = jww_core::parse(&data).unwrap();
+    assert_eq!(
+        format!("{:?}", parsed.entities.last().unwrap()),
+        format!("{:?}", dimension)
+    );
+}
+
+#[test]
+fn test_write_empty_document() {
+    for version in [300, 600, 700] {
+        let mut doc = Document {
+            version,
+            memo: "空の図面".into(),
+            write_layer_group: 2,
+            ..Default::default()
+        };
+        doc.layer_groups[2].scale = 100.0;
+        doc.layer_groups[2].layers[3].name = "壁".to_string();
+        doc.layer_groups[2].name = "1階".to_string();
+        let data = jww_core::write(&doc).expect("write");
+
+        // エンティティがなくてもエンティティリストを検出できる
+        let parsed = jww_core::parse(&data).expect("parse");
+        assert!(parsed.entities.is_empty());
+        assert_eq!(parsed.memo, doc.memo);
+        assert_eq!(parsed.write_layer_group, 2);
+        assert_eq!(parsed.layer_groups[2].scale, 100.0);
+        assert_eq!(jww_core::probe(&data).unwrap().entity_count, 0);
+        // パーサーはレイヤ名を読まないため、既定の名前で書き出した結果と比べる
+        let mut unnamed = doc.clone();
+        unnamed.layer_groups[2].layers[3].name = "2-3".to_string();
+        unnamed.layer_groups[2].name = "Group2".to_string();
+        assert_eq!(jww_core::write(&parsed).unwrap(), jww_core::write(&unnamed).unwrap());
+
+        // レイヤ名・レイヤグループ名はShift-JISで書き込む
+        let (name, ..) = encoding_rs::SHIFT_JIS.encode("壁");
+        let cstring = [&[name.len() as u8][..], &name].concat();
+        assert!(data.windows(cstring.len()).any(|w| w == cstring.as_slice()));
+
+        // ブロック定義だけを持つ図面でも、エンティティリストは空として読み取る
+        doc.block_defs.push(jww_core::BlockDef {
+            base: EntityBase::default(),
+            number: 1,
+            is_referenced: false,
+            name: "DOOR".to_string(),
+            entities: vec![Entity::Line(Line {
+                base: EntityBase::default(),
+                start: Point2D::new(0.0, 0.0),
+                end: Point2D::new(900.0, 0.0),
+            })],
+        });
+        let data = jww_core::write(&doc).expect("write");
+        assert!(jww_core::parse(&data).expect("parse").entities.is_empty());
+    }
+}
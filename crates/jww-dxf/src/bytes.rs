@@ -0,0 +1,171 @@
+//! DXFのバイト列出力 (Shift-JIS・バイナリDXF)
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Document, DxfVersion};
+use crate::writer::write_document;
+
+/// Shift-JIS (CP932) を表す $DWGCODEPAGE の値
+const CODEPAGE_932: &str = "ANSI_932";
+
+/// バイナリDXFの先頭の識別子
+const BINARY_SENTINEL: &[u8] = b"AutoCAD Binary DXF\r\n\x1a\x00";
+
+/// バイト列出力の形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DxfFormat {
+    /// Shift-JIS (CP932) のテキストDXF ($DWGCODEPAGE = ANSI_932)
+    #[default]
+    ShiftJis,
+    /// UTF-8のテキストDXF ([`crate::to_string`] と同じ内容)
+    Utf8,
+    /// バイナリDXF (文字列はShift-JIS)
+    Binary,
+}
+
+/// テキストDXFの文字コード ([`crate::ConvertOptions::encoding`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DxfEncoding {
+    /// UTF-8 ([`crate::to_string`] と同じ内容)
+    #[default]
+    Utf8,
+    /// Shift-JIS (CP932、$DWGCODEPAGE = ANSI_932)
+    ShiftJis,
+}
+
+impl From<DxfEncoding> for DxfFormat {
+    fn from(encoding: DxfEncoding) -> Self {
+        match encoding {
+            DxfEncoding::Utf8 => DxfFormat::Utf8,
+            DxfEncoding::ShiftJis => DxfFormat::ShiftJis,
+        }
+    }
+}
+
+/// DXFドキュメントをバイト列に変換する
+///
+/// Shift-JISで表せない文字は `\U+XXXX` 形式で出力する。
+/// バイナリDXFでは999コメントは出力しない。
+pub fn to_bytes(doc: &Document, format: DxfFormat) -> Vec<u8> {
+    match format {
+        DxfFormat::Utf8 => write_document(doc, None).into_bytes(),
+        DxfFormat::ShiftJis => {
+            let text = write_document(doc, Some(CODEPAGE_932));
+            let mut out = Vec::with_capacity(text.len());
+            for line in text.lines() {
+                encode_cp932(line, &mut out);
+                out.push(b'\n');
+            }
+            out
+        }
+        DxfFormat::Binary => to_binary(&write_document(doc, Some(CODEPAGE_932)), doc.output_version()),
+    }
+}
+
+/// 文字列をShift-JISで追加する
+fn encode_cp932(text: &str, out: &mut Vec<u8>) {
+    let (bytes, _, had_errors) = encoding_rs::SHIFT_JIS.encode(text);
+    if !had_errors {
+        out.extend_from_slice(&bytes);
+        return;
+    }
+    // 表せない文字を含む場合のみ1文字ずつ変換する
+    let mut buf = [0u8; 4];
+    for c in text.chars() {
+        let (bytes, _, had_errors) = encoding_rs::SHIFT_JIS.encode(c.encode_utf8(&mut buf));
+        if had_errors {
+            out.extend_from_slice(format!("\\U+{:04X}", c as u32).as_bytes());
+        } else {
+            out.extend_from_slice(&bytes);
+        }
+    }
+}
+
+/// 値の型
+pub(crate) enum ValueType {
+    String,
+    Double,
+    Int16,
+    Int32,
+    Int64,
+    Bool,
+    /// 16進文字列で表したバイナリデータ
+    Chunk,
+}
+
+/// グループコードの値の型を返す
+pub(crate) fn value_type(code: u16) -> ValueType {
+    match code {
+        10..=59 | 110..=149 | 210..=239 | 460..=469 | 1010..=1059 => ValueType::Double,
+        60..=79 | 170..=179 | 270..=289 | 370..=389 | 400..=409 | 1060..=1070 => ValueType::Int16,
+        90..=99 | 420..=429 | 440..=459 | 1071 => ValueType::Int32,
+        160..=169 => ValueType::Int64,
+        290..=299 => ValueType::Bool,
+        310..=319 | 1004 => ValueType::Chunk,
+        _ => ValueType::String,
+    }
+}
+
+/// テキストDXFをバイナリDXFに変換する
+///
+/// グループコードはR12では1バイト (255以上は0xFFに続く2バイト)、
+/// R2000では常に2バイトで出力する。
+fn to_binary(text: &str, version: DxfVersion) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    out.extend_from_slice(BINARY_SENTINEL);
+    let mut lines = text.lines();
+    while let (Some(code), Some(value)) = (lines.next(), lines.next()) {
+        let Ok(code) = code.trim().parse::<u16>() else {
+            continue;
+        };
+        if code == 999 {
+            continue;
+        }
+        if version >= DxfVersion::R2000 {
+            out.extend_from_slice(&code.to_le_bytes());
+        } else if code < 255 {
+            out.push(code as u8);
+        } else {
+            out.push(0xFF);
+            out.extend_from_slice(&code.to_le_bytes());
+        }
+        let value = value.trim_end_matches('\r');
+        match value_type(code) {
+            ValueType::String => {
+                encode_cp932(value, &mut out);
+                out.push(0);
+            }
+            ValueType::Double => {
+                let v: f64 = value.trim().parse().unwrap_or(0.0);
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            ValueType::Int16 => {
+                let v: i16 = value.trim().parse().unwrap_or(0);
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            ValueType::Int32 => {
+                let v: i32 = value.trim().parse().unwrap_or(0);
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            ValueType::Int64 => {
+                let v: i64 = value.trim().parse().unwrap_or(0);
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+            ValueType::Bool => {
+                let v: i16 = value.trim().parse().unwrap_or(0);
+                out.push((v != 0) as u8);
+            }
+            ValueType::Chunk => {
+                let bytes: Vec<u8> = value
+                    .as_bytes()
+                    .chunks(2)
+                    .filter_map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+                    .take(255)
+                    .collect();
+                out.push(bytes.len() as u8);
+                out.extend_from_slice(&bytes);
+            }
+        }
+    }
+    out
+}
@@ -1,19 +1,641 @@
 //! JWWからDXFへの変換ロジック
 
+use std::collections::{HashMap, HashSet};
+
+use crate::options::{ConvertOptions, Provenance};
+use crate::report::{ConversionReport, EntityLocation, Loss, SkipReason};
 use crate::types::*;
 use jww_core::{Document as JwwDocument, Entity as JwwEntity};
 
 /// JWWドキュメントをDXFドキュメントに変換する
 pub fn convert_document(jww_doc: &JwwDocument) -> Document {
-    let layers = convert_layers(jww_doc);
-    let entities = convert_entities(jww_doc);
-    let blocks = convert_blocks(jww_doc);
+    convert_document_with_options(jww_doc, &ConvertOptions::default())
+}
+
+/// オプションを指定してJWWドキュメントをDXFドキュメントに変換する
+pub fn convert_document_with_options(jww_doc: &JwwDocument, options: &ConvertOptions) -> Document {
+    convert_document_with_report(jww_doc, options).0
+}
+
+/// JWWドキュメントを変換し、変換レポートとともに返す
+///
+/// レポートには出力されなかったエンティティや近似・既定値で置き換えた変換が記録される。
+pub fn convert_document_with_report(
+    jww_doc: &JwwDocument,
+    options: &ConvertOptions,
+) -> (Document, ConversionReport) {
+    IncrementalConverter::new(jww_doc, options).finish()
+}
+
+/// エンティティを少しずつ変換するコンバーター
+///
+/// [`IncrementalConverter::step`] で指定した件数ずつ図面直下のエンティティを変換するため、
+/// 大きな図面の変換中に進捗表示や中断の確認を挟むことができる。
+/// 結果は [`convert_document_with_report`] と同じ。
+///
+/// ```no_run
+/// # fn main() -> jww_core::Result<()> {
+/// let data = std::fs::read("example.jww")?;
+/// let doc = jww_core::parse(&data)?;
+/// let options = jww_dxf::ConvertOptions::default();
+/// let mut converter = jww_dxf::IncrementalConverter::new(&doc, &options);
+/// while !converter.step(1000) {
+///     let (done, total) = converter.progress();
+///     println!("{}/{}", done, total);
+/// }
+/// let (dxf_doc, report) = converter.finish();
+/// # Ok(())
+/// # }
+/// ```
+pub struct IncrementalConverter<'d> {
+    jww_doc: &'d JwwDocument<'d>,
+    options: std::borrow::Cow<'d, ConvertOptions>,
+    dim_style: DimStyle,
+    report: ConversionReport,
+    /// 変換する順のエンティティ番号
+    ordered: Vec<usize>,
+    /// `ordered` のうち変換済みの件数
+    position: usize,
+    entities: Vec<Entity>,
+    /// 曲線属性番号 -> 出力したエンティティの番号
+    curve_groups: std::collections::BTreeMap<u32, Vec<usize>>,
+    leaders: Vec<crate::leader::LeaderShape>,
+    /// エンティティ番号 -> 引出線の番号
+    leader_of: std::collections::HashMap<usize, usize>,
+    /// 引出線ごとの (引出線, 文字) の出力位置
+    leader_indices: Vec<(Option<usize>, Option<usize>)>,
+    dedup: Option<crate::dedup::Dedup>,
+}
+
+impl<'d> IncrementalConverter<'d> {
+    /// 寸法スタイル・変換順・引出線を準備する
+    pub fn new(jww_doc: &'d JwwDocument<'d>, options: &'d ConvertOptions) -> Self {
+        let options = options.for_version();
+        let dim_style = convert_dim_style(jww_doc, &options, options.scale());
+        let mut report = ConversionReport::default();
+
+        // レイヤの指定やレイヤ順の出力では、レイヤごとの索引から対象のエンティティを取り出す
+        let layers = &options.filter.layers;
+        let mut ordered: Vec<usize> = Vec::with_capacity(jww_doc.entities.len());
+        if options.sort_by_layer || !layers.is_empty() {
+            for (layer, indices) in jww_doc.entities_by_layer().iter() {
+                if layers.is_empty() || layers.contains(layer) {
+                    ordered.extend(indices);
+                    continue;
+                }
+                // 指定外のレイヤのエンティティは変換せずに除外として記録する
+                for &index in indices {
+                    let kind = jww_doc.entities[index].kind();
+                    *report.input_counts.entry(kind).or_default() += 1;
+                    report.skip(EntityLocation { block: None, index }, kind, SkipReason::Filtered);
+                }
+            }
+            if !options.sort_by_layer {
+                ordered.sort_unstable();
+            }
+        } else {
+            ordered.extend(0..jww_doc.entities.len());
+        }
+
+        let leaders = if options.leaders {
+            crate::leader::detect(jww_doc, |e| options.filter.accepts(e))
+        } else {
+            Vec::new()
+        };
+        let leader_of = leaders
+            .iter()
+            .enumerate()
+            .flat_map(|(i, leader)| leader.members.iter().chain([&leader.text]).map(move |&m| (m, i)))
+            .collect();
+        let leader_indices = vec![(None, None); leaders.len()];
+        let dedup = options.dedup_tolerance.map(crate::dedup::Dedup::new);
+
+        Self {
+            jww_doc,
+            options,
+            dim_style,
+            report,
+            ordered,
+            position: 0,
+            entities: Vec::new(),
+            curve_groups: std::collections::BTreeMap::new(),
+            leaders,
+            leader_of,
+            leader_indices,
+            dedup,
+        }
+    }
+
+    /// 最大 `max` 件のエンティティを変換し、すべて変換し終えたかどうかを返す
+    pub fn step(&mut self, max: usize) -> bool {
+        let jww_doc = self.jww_doc;
+        let options = &*self.options;
+        let end = self.ordered.len().min(self.position.saturating_add(max));
+        for &index in &self.ordered[self.position..end] {
+            let jww_entity = &jww_doc.entities[index];
+            let location = EntityLocation { block: None, index };
+            let Some(dxf_entity) =
+                convert_entity(jww_entity, jww_doc, options, &self.dim_style, &mut self.report, location)
+            else {
+                continue;
+            };
+            let dxf_entity = match self.leader_of.get(&index) {
+                Some(&i) if index == self.leaders[i].text => {
+                    self.leader_indices[i].1 = Some(self.entities.len());
+                    dxf_entity
+                }
+                // 引出線の線・矢印は最初の線の位置に引出線として出力する
+                Some(&i) => match (self.leader_indices[i].0, dxf_entity) {
+                    (None, Entity::Line(line)) => {
+                        self.leader_indices[i].0 = Some(self.entities.len());
+                        Entity::Leader(leader_entity(&self.leaders[i], line, &self.dim_style))
+                    }
+                    _ => continue,
+                },
+                None => {
+                    if self.dedup.as_mut().is_some_and(|d| d.is_duplicate(&self.entities, &dxf_entity)) {
+                        self.report.skip(location, jww_entity.kind(), SkipReason::Duplicate);
+                        continue;
+                    }
+                    // 曲線属性番号0はグループに属さない
+                    let group = jww_entity.base().group;
+                    if options.groups && group != 0 {
+                        self.curve_groups.entry(group).or_default().push(self.entities.len());
+                    }
+                    dxf_entity
+                }
+            };
+            self.entities.push(dxf_entity);
+        }
+        self.position = end;
+        self.position >= self.ordered.len()
+    }
+
+    /// (変換済みの件数, 変換する件数) を返す
+    pub fn progress(&self) -> (usize, usize) {
+        (self.position, self.ordered.len())
+    }
+
+    /// 残りのエンティティを変換し、ブロック・ヘッダーなどを加えてDXFドキュメントを返す
+    pub fn finish(mut self) -> (Document, ConversionReport) {
+        self.step(usize::MAX);
+        let groups = self.groups();
+        let Self {
+            jww_doc,
+            options,
+            dim_style,
+            mut report,
+            mut entities,
+            ..
+        } = self;
+        let options = &*options;
+        let factor = options.scale();
+        let mut layers = convert_layers(jww_doc);
+        let (mut blocks, block_offsets) = convert_blocks(jww_doc, options, &dim_style, &mut report);
+        if !block_offsets.is_empty() {
+            let block_entities = blocks.iter_mut().flat_map(|b| b.entities.iter_mut());
+            for entity in entities.iter_mut().chain(block_entities) {
+                rebase_insert(entity, &block_offsets);
+            }
+        }
+        if report.lossy.iter().any(|l| matches!(l.loss, Loss::InvalidLayer { .. }))
+            && !layers.iter().any(|l| l.name == options.fallback_layer())
+        {
+            layers.push(Layer {
+                name: options.fallback_layer().to_string(),
+                color: 7,
+                line_type: "CONTINUOUS".to_string(),
+                frozen: false,
+                locked: false,
+            });
+        }
+        if options.point_marker_blocks {
+            blocks.extend(marker_blocks(jww_doc));
+        }
+
+        // 単位換算
+        if factor != 1.0 {
+            for entity in &mut entities {
+                scale_entity(entity, factor);
+            }
+            for block in &mut blocks {
+                block.base_x *= factor;
+                block.base_y *= factor;
+                for entity in &mut block.entities {
+                    scale_entity(entity, factor);
+                }
+            }
+        }
+
+        let mut header = Header {
+            version: options.version.unwrap_or_default(),
+            insunits: options.units.insunits(),
+            measurement: options.units.measurement(),
+            ltscale: jww_doc.drawing_scale() * factor,
+            limits: convert_limits(jww_doc, factor),
+            ..point_display(jww_doc, factor)
+        };
+
+        let active_view = convert_active_view(jww_doc, factor);
+
+        let layout = if options.paper_space {
+            convert_layout(jww_doc, factor)
+        } else {
+            None
+        };
+
+        let mut comments = Vec::new();
+        if let Some(provenance) = &options.provenance {
+            let timestamp = provenance.timestamp.unwrap_or_else(crate::time::now);
+            comments = provenance_comments(provenance, timestamp);
+            header.created = Some(crate::time::julian_date(
+                provenance.source_modified.unwrap_or(timestamp),
+            ));
+            header.updated = Some(crate::time::julian_date(timestamp));
+            header.project_name = jww_doc
+                .memo
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .map(str::to_string);
+        }
+
+        let mut dim_styles = Vec::new();
+        for entity in entities.iter().chain(blocks.iter().flat_map(|b| b.entities.iter())) {
+            *report.output_counts.entry(entity.type_name().to_string()).or_default() += 1;
+            if matches!(entity, Entity::Dimension(_) | Entity::Leader(_)) && dim_styles.is_empty() {
+                dim_styles.push(dim_style.clone());
+            }
+        }
+
+        log::debug!(
+            "converted {} entities and {} blocks ({} skipped, {} lossy)",
+            entities.len(),
+            blocks.len(),
+            report.skipped.len(),
+            report.lossy.len()
+        );
 
-    Document {
-        layers,
-        entities,
-        blocks,
+        let document = Document {
+            header,
+            active_view,
+            layers,
+            entities,
+            blocks,
+            layout,
+            groups,
+            comments,
+            dim_styles,
+        };
+
+        (document, report)
+    }
+
+    /// 曲線属性と引出線のグループを作成する (`options.groups` が無効な場合は曲線属性のグループはない)
+    fn groups(&mut self) -> Vec<Group> {
+        let curve_groups = std::mem::take(&mut self.curve_groups)
+            .into_iter()
+            .filter(|(_, indices)| indices.len() > 1)
+            .map(|(group, entities)| Group {
+                name: format!("JWW_CURVE_{}", group),
+                description: format!("JWW curve attribute group {}", group),
+                entities,
+            });
+        let leader_groups = self.leaders.iter().zip(&self.leader_indices).filter_map(|(leader, indices)| match *indices {
+            (Some(leader_index), Some(text_index)) => Some(Group {
+                name: format!("JWW_LEADER_{}", leader.group),
+                description: format!("JWW leader with text (curve attribute group {})", leader.group),
+                entities: vec![leader_index, text_index],
+            }),
+            _ => None,
+        });
+        curve_groups.chain(leader_groups).collect()
+    }
+}
+
+/// 変換元情報の999コメントを作成する
+fn provenance_comments(provenance: &Provenance, timestamp: u64) -> Vec<String> {
+    let mut comments = vec![format!(
+        "Converted by {} {}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    )];
+    if let Some(source_name) = &provenance.source_name {
+        comments.push(format!("Source: {}", source_name));
     }
+    comments.push(format!("Converted at: {}", crate::time::format_utc(timestamp)));
+    comments
+}
+
+/// 寸法スタイル名
+const DIM_STYLE_NAME: &str = "JWW";
+
+/// 寸法値の用紙上の既定の文字高さ (mm)
+const DEFAULT_DIM_TEXT_HEIGHT: f64 = 2.5;
+
+/// 矢印の用紙上の大きさ (mm、JWWファイルは矢印の大きさを持たないためJw_cadの初期値)
+const DEFAULT_DIM_ARROW_SIZE: f64 = 3.0;
+
+/// 補助線の起点からの離れの既定値 (mm、Jw_cadの初期値)
+const DEFAULT_DIM_EXTENSION_OFFSET: f64 = 1.0;
+
+/// 補助線のはみ出しの既定値 (mm、Jw_cadの初期値)
+const DEFAULT_DIM_EXTENSION_EXTENSION: f64 = 2.0;
+
+/// 寸法値と寸法線の間隔の既定値 (mm、Jw_cadの初期値)
+const DEFAULT_DIM_TEXT_GAP: f64 = 1.0;
+
+/// 寸法の表示設定から寸法スタイルを作成する
+///
+/// 指定しない項目は図面の寸法から読み取った値 ([`JwwDocument::dimension_settings`]) を使い、
+/// 読み取れない場合は既定値とする。
+fn convert_dim_style(jww_doc: &JwwDocument, options: &ConvertOptions, factor: f64) -> DimStyle {
+    let style = &options.dimension_style;
+    let settings = jww_doc.dimension_settings();
+
+    DimStyle {
+        name: DIM_STYLE_NAME.to_string(),
+        scale: jww_doc.drawing_scale() * factor,
+        arrow_size: style.arrow_size.unwrap_or(DEFAULT_DIM_ARROW_SIZE),
+        extension_offset: style
+            .extension_offset
+            .or(settings.extension_offset)
+            .unwrap_or(DEFAULT_DIM_EXTENSION_OFFSET),
+        extension_extension: style
+            .extension_extension
+            .or(settings.extension_extension)
+            .unwrap_or(DEFAULT_DIM_EXTENSION_EXTENSION),
+        text_gap: style.text_gap.or(settings.text_gap).unwrap_or(DEFAULT_DIM_TEXT_GAP),
+        text_height: style.text_height.or(settings.text_height).unwrap_or(DEFAULT_DIM_TEXT_HEIGHT),
+    }
+}
+
+/// 点マーカーの用紙上の基準サイズ (mm)
+const POINT_MARKER_SIZE: f64 = 2.0;
+
+/// 点マーカーから $PDMODE/$PDSIZE を決定する
+///
+/// DXFの点表示スタイルは図面全体で1つのため、最も多く使われている
+/// マーカー種別を採用する。マーカー付きの点がない場合は既定の点表示とする。
+fn point_display(jww_doc: &JwwDocument, factor: f64) -> Header {
+    let mut counts: std::collections::BTreeMap<u32, (usize, f64)> = std::collections::BTreeMap::new();
+    for entity in &jww_doc.entities {
+        if let JwwEntity::Point(point) = entity {
+            if is_marker(point) {
+                let entry = counts.entry(point.code).or_insert((0, 0.0));
+                entry.0 += 1;
+                entry.1 = entry.1.max(point.scale);
+            }
+        }
+    }
+
+    // 件数が同じ場合はコードの小さい方を優先する
+    let most_used = counts
+        .iter()
+        .max_by(|a, b| a.1 .0.cmp(&b.1 .0).then(b.0.cmp(a.0)));
+
+    match most_used {
+        Some((&code, &(_, scale))) => {
+            Header {
+                pdmode: map_point_marker(code),
+                pdsize: POINT_MARKER_SIZE * positive_or_one(scale) * jww_doc.drawing_scale() * factor,
+                ..Header::default()
+            }
+        }
+        None => Header::default(),
+    }
+}
+
+/// JWW点マーカーコードをDXFの $PDMODE にマッピングする
+///
+/// $PDMODE は図形 (0=点, 2=+, 3=×) に 32 (円) / 64 (四角) を加算して表す。
+fn map_point_marker(code: u32) -> i32 {
+    match code {
+        1 => 32,  // ○
+        2 => 64,  // □
+        3 => 2,   // ＋
+        4 => 3,   // ×
+        5 => 34,  // ○＋
+        6 => 35,  // ○×
+        7 => 66,  // □＋
+        8 => 67,  // □×
+        _ => 33,  // 不明なマーカーは見える形 (○) で表示する
+    }
+}
+
+/// マーカー付きの点 (仮点を除く) かどうか
+fn is_marker(point: &jww_core::Point) -> bool {
+    !point.is_temporary && point.base.pen_style == 100 && point.code != 0
+}
+
+/// 点のスケール (未設定の場合は1)
+fn positive_or_one(scale: f64) -> f64 {
+    if scale > 0.0 {
+        scale
+    } else {
+        1.0
+    }
+}
+
+/// マーカーの形 (外形, 中の線)
+///
+/// 中の線は `true` で＋、`false` で×。1 (○) と不明なマーカーは○とする。
+fn marker_shape(code: u32) -> (Option<MarkerOutline>, Option<bool>) {
+    match code {
+        2 => (Some(MarkerOutline::Square), None),
+        3 => (None, Some(true)),
+        4 => (None, Some(false)),
+        5 => (Some(MarkerOutline::Circle), Some(true)),
+        6 => (Some(MarkerOutline::Circle), Some(false)),
+        7 => (Some(MarkerOutline::Square), Some(true)),
+        8 => (Some(MarkerOutline::Square), Some(false)),
+        _ => (Some(MarkerOutline::Circle), None),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum MarkerOutline {
+    Circle,
+    Square,
+}
+
+/// マーカーのブロックのコード (不明なコードは○のブロックを使う)
+fn marker_block_code(code: u32) -> u32 {
+    if (1..=8).contains(&code) {
+        code
+    } else {
+        1
+    }
+}
+
+/// マーカーのブロック名
+fn marker_block_name(code: u32) -> String {
+    format!("JWW_MARKER_{}", marker_block_code(code))
+}
+
+/// 図面で使われているマーカーのブロックを作成する
+///
+/// ブロックは幅1の大きさで作成し、INSERTの尺度でマーカーの大きさにする。
+/// 色は挿入側に従う (BYBLOCK)。
+fn marker_blocks(jww_doc: &JwwDocument) -> Vec<Block> {
+    let codes: std::collections::BTreeSet<u32> = jww_doc
+        .entities
+        .iter()
+        .chain(jww_doc.block_defs.iter().flat_map(|def| def.entities.iter()))
+        .filter_map(|e| match e {
+            JwwEntity::Point(point) if is_marker(point) => Some(point.code),
+            _ => None,
+        })
+        .map(marker_block_code)
+        .collect();
+
+    codes
+        .into_iter()
+        .map(|code| {
+            let line = |x1: f64, y1: f64, x2: f64, y2: f64| {
+                Entity::Line(Line {
+                    layer: "0".to_string(),
+                    color: 0,
+                    line_type: "CONTINUOUS".to_string(),
+                    line_type_scale: None,
+                    xdata: None,
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                })
+            };
+            let (outline, inner) = marker_shape(code);
+            let mut entities = Vec::new();
+            match outline {
+                Some(MarkerOutline::Circle) => entities.push(Entity::Circle(Circle {
+                    layer: "0".to_string(),
+                    color: 0,
+                    line_type: "CONTINUOUS".to_string(),
+                    line_type_scale: None,
+                    xdata: None,
+                    center_x: 0.0,
+                    center_y: 0.0,
+                    radius: 0.5,
+                })),
+                Some(MarkerOutline::Square) => entities.push(Entity::Polyline(Polyline {
+                    layer: "0".to_string(),
+                    color: 0,
+                    line_type: "CONTINUOUS".to_string(),
+                    line_type_scale: None,
+                    xdata: None,
+                    vertices: [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)]
+                        .into_iter()
+                        .map(|(x, y)| Vertex { x, y })
+                        .collect(),
+                    closed: true,
+                })),
+                None => {}
+            }
+            match inner {
+                Some(true) => {
+                    entities.push(line(-0.5, 0.0, 0.5, 0.0));
+                    entities.push(line(0.0, -0.5, 0.0, 0.5));
+                }
+                Some(false) => {
+                    // ○の中の×は円に内接させる
+                    let h = if outline == Some(MarkerOutline::Circle) {
+                        0.5 * std::f64::consts::FRAC_1_SQRT_2
+                    } else {
+                        0.5
+                    };
+                    entities.push(line(-h, -h, h, h));
+                    entities.push(line(-h, h, h, -h));
+                }
+                None => {}
+            }
+            Block {
+                name: marker_block_name(code),
+                base_x: 0.0,
+                base_y: 0.0,
+                entities,
+            }
+        })
+        .collect()
+}
+
+/// 図面範囲全体が収まる初期表示ビューを作成する
+fn convert_active_view(jww_doc: &JwwDocument, factor: f64) -> Option<ActiveView> {
+    /// 図面範囲の周囲に確保する余白の比率
+    const MARGIN: f64 = 1.05;
+
+    let bb = jww_doc.bounding_box()?;
+    let jww_core::Point2D { x: cx, y: cy } = bb.center();
+    let width = bb.width() * factor;
+    let height = bb.height() * factor;
+
+    let (height, aspect_ratio) = match (width > 0.0, height > 0.0) {
+        (true, true) => (height * MARGIN, width / height),
+        (true, false) => (width * MARGIN, 1.0),
+        (false, true) => (height * MARGIN, 1.0),
+        (false, false) => return None,
+    };
+
+    Some(ActiveView {
+        center_x: cx * factor,
+        center_y: cy * factor,
+        height,
+        aspect_ratio,
+    })
+}
+
+/// 用紙サイズと図面範囲からペーパー空間レイアウトを作成する
+///
+/// JWWの座標原点は用紙中心にあるため、図面範囲を縮尺で割った位置に
+/// ビューポートを配置すると元の用紙上の配置と一致する。
+fn convert_layout(jww_doc: &JwwDocument, factor: f64) -> Option<Layout> {
+    let (paper_width, paper_height) = jww_doc.paper_dimensions()?;
+    let scale = jww_doc.drawing_scale();
+
+    let viewport = match jww_doc.bounding_box() {
+        Some(bb) if bb.width() > 0.0 && bb.height() > 0.0 => {
+            let jww_core::Point2D { x: cx, y: cy } = bb.center();
+            Viewport {
+                center_x: (cx / scale + paper_width / 2.0) * factor,
+                center_y: (cy / scale + paper_height / 2.0) * factor,
+                width: bb.width() / scale * factor,
+                height: bb.height() / scale * factor,
+                view_center_x: cx * factor,
+                view_center_y: cy * factor,
+                view_height: bb.height() * factor,
+            }
+        }
+        // 図面が空の場合は用紙全体を表示する
+        _ => Viewport {
+            center_x: paper_width / 2.0 * factor,
+            center_y: paper_height / 2.0 * factor,
+            width: paper_width * factor,
+            height: paper_height * factor,
+            view_center_x: 0.0,
+            view_center_y: 0.0,
+            view_height: paper_height * scale * factor,
+        },
+    };
+
+    Some(Layout {
+        paper_width: paper_width * factor,
+        paper_height: paper_height * factor,
+        viewport,
+    })
+}
+
+/// 用紙の範囲を縮尺倍した図面範囲を作成する
+///
+/// JWWの図面座標の原点は用紙の中心にある。
+fn convert_limits(jww_doc: &JwwDocument, factor: f64) -> Option<Limits> {
+    let (paper_width, paper_height) = jww_doc.paper_dimensions()?;
+    let scale = jww_doc.drawing_scale() * factor;
+    Some(Limits {
+        min_x: -paper_width / 2.0 * scale,
+        min_y: -paper_height / 2.0 * scale,
+        max_x: paper_width / 2.0 * scale,
+        max_y: paper_height / 2.0 * scale,
+    })
 }
 
 /// JWWレイヤーをDXFレイヤーに変換する
@@ -32,7 +654,7 @@ fn convert_layers(jww_doc: &JwwDocument) -> Vec<Layer> {
 
             layers.push(Layer {
                 name,
-                color: ((g_lay * 16 + lay) % 255 + 1) as i32,
+                color: (g_lay * 16 + lay) % 255 + 1,
                 line_type: "CONTINUOUS".to_string(),
                 frozen: l.state == 0,
                 locked: l.protect != 0,
@@ -43,115 +665,184 @@ fn convert_layers(jww_doc: &JwwDocument) -> Vec<Layer> {
     layers
 }
 
-/// JWWエンティティをDXFエンティティに変換する
-fn convert_entities(jww_doc: &JwwDocument) -> Vec<Entity> {
-    let mut entities = Vec::new();
+/// 長さ・半径・面積を0とみなす値 (mm, mm²)
+const DEGENERATE_TOLERANCE: f64 = 1e-9;
 
-    for jww_entity in &jww_doc.entities {
-        if let Some(dxf_entity) = convert_entity(jww_entity, jww_doc) {
-            entities.push(dxf_entity);
+/// 長さ0の線・半径0の円弧・面積0の塗りつぶしかどうか
+fn is_degenerate(jww_entity: &JwwEntity) -> bool {
+    match jww_entity {
+        JwwEntity::Line(line) => line.start.distance(line.end) <= DEGENERATE_TOLERANCE,
+        JwwEntity::Arc(arc) => arc.radius.abs() <= DEGENERATE_TOLERANCE,
+        JwwEntity::Solid(solid) => {
+            // 4点のどの3点でも三角形にならなければ面積0 (頂点の順序によらない)
+            let points = [solid.point1, solid.point2, solid.point3, solid.point4];
+            let area = |a: usize, b: usize, c: usize| {
+                let (p, q, r) = (points[a], points[b], points[c]);
+                ((q.x - p.x) * (r.y - p.y) - (r.x - p.x) * (q.y - p.y)).abs() / 2.0
+            };
+            [(0, 1, 2), (0, 1, 3), (0, 2, 3), (1, 2, 3)]
+                .into_iter()
+                .all(|(a, b, c)| area(a, b, c) <= DEGENERATE_TOLERANCE)
         }
+        _ => false,
     }
+}
 
-    entities
+/// 引出線の最初の線の属性で引出線を作成する
+fn leader_entity(leader: &crate::leader::LeaderShape, line: Line, dim_style: &DimStyle) -> Leader {
+    Leader {
+        layer: line.layer,
+        color: line.color,
+        line_type: line.line_type,
+        line_type_scale: line.line_type_scale,
+        xdata: line.xdata,
+        style: dim_style.name.clone(),
+        arrowhead: leader.arrowhead,
+        vertices: leader.vertices.iter().map(|p| Vertex { x: p.x, y: p.y }).collect(),
+    }
 }
 
 /// 単一のJWWエンティティをDXFエンティティに変換する
-fn convert_entity(jww_entity: &JwwEntity, jww_doc: &JwwDocument) -> Option<Entity> {
+fn convert_entity(
+    jww_entity: &JwwEntity,
+    jww_doc: &JwwDocument,
+    options: &ConvertOptions,
+    dim_style: &DimStyle,
+    report: &mut ConversionReport,
+    location: EntityLocation,
+) -> Option<Entity> {
+    let kind = jww_entity.kind();
+    *report.input_counts.entry(kind).or_default() += 1;
+
+    if !options.filter.accepts(jww_entity) {
+        report.skip(location, kind, SkipReason::Filtered);
+        return None;
+    }
+    if options.drop_degenerate && is_degenerate(jww_entity) {
+        report.skip(location, kind, SkipReason::Degenerate);
+        return None;
+    }
+    if let JwwEntity::Unknown(unknown) = jww_entity {
+        report.skip(location, kind, SkipReason::UnknownClass(unknown.class_name.clone()));
+        return None;
+    }
+
     let base = jww_entity.base();
-    let layer_name = get_layer_name(jww_doc, base.layer_group, base.layer);
+    let layer_name = match get_layer_name(jww_doc, base.layer_group, base.layer) {
+        Some(name) => name,
+        None => {
+            report.lossy(
+                location,
+                kind,
+                Loss::InvalidLayer {
+                    layer_group: base.layer_group,
+                    layer: base.layer,
+                },
+            );
+            options.fallback_layer().to_string()
+        }
+    };
     let color = map_color(base.pen_color);
+    if !is_known_color(base.pen_color) {
+        report.lossy(location, kind, Loss::UnknownColor(base.pen_color));
+    }
     let line_type = map_line_type(base.pen_style);
+    // 点の線種番号100はマーカー付きの点を表す
+    if base.pen_style > 9 && !(kind == jww_core::EntityKind::Point && base.pen_style == 100) {
+        report.lossy(location, kind, Loss::UnknownLineType(base.pen_style));
+    }
+    let line_type_scale = entity_line_type_scale(jww_doc, base.layer_group, &line_type);
+    let xdata = options.xdata.then(|| jww_attributes_xdata(base));
 
     match jww_entity {
         JwwEntity::Line(line) => Some(Entity::Line(Line {
             layer: layer_name,
             color,
             line_type,
-            x1: line.start_x,
-            y1: line.start_y,
-            x2: line.end_x,
-            y2: line.end_y,
+            line_type_scale,
+            xdata,
+            x1: line.start.x,
+            y1: line.start.y,
+            x2: line.end.x,
+            y2: line.end.y,
         })),
 
         JwwEntity::Arc(arc) => {
-            if arc.is_full_circle && arc.flatness == 1.0 {
-                // 完全円
+            if arc.is_closed() && !arc.is_ellipse() {
+                // 完全円 (円弧角が1周以上の場合を含む)
                 Some(Entity::Circle(Circle {
                     layer: layer_name,
                     color,
                     line_type,
-                    center_x: arc.center_x,
-                    center_y: arc.center_y,
+                    line_type_scale,
+                    xdata,
+                    center_x: arc.center.x,
+                    center_y: arc.center.y,
                     radius: arc.radius,
                 }))
-            } else if arc.flatness != 1.0 {
-                // 楕円または楕円弧
-                let major_radius = arc.radius;
-                let minor_ratio = arc.flatness;
-                let tilt_angle = arc.tilt_angle;
-
-                if minor_ratio > 1.0 {
-                    // 軸を入れ替え
-                    let major_radius = arc.radius * arc.flatness;
-                    let minor_ratio = 1.0 / arc.flatness;
-                    let tilt_angle = arc.tilt_angle + std::f64::consts::PI / 2.0;
-
-                    let major_axis_x = major_radius * tilt_angle.cos();
-                    let major_axis_y = major_radius * tilt_angle.sin();
-
-                    let (start_param, end_param) = if arc.is_full_circle {
-                        (0.0, 2.0 * std::f64::consts::PI)
-                    } else {
-                        (arc.start_angle, arc.start_angle + arc.arc_angle)
-                    };
-
-                    Some(Entity::Ellipse(Ellipse {
-                        layer: layer_name,
-                        color,
-                        line_type,
-                        center_x: arc.center_x,
-                        center_y: arc.center_y,
-                        major_axis_x,
-                        major_axis_y,
-                        minor_ratio,
-                        start_param,
-                        end_param,
-                    }))
+            } else if let Some(tolerance) = options
+                .curve_tolerance
+                .filter(|_| arc.is_ellipse() || arc.tilt_angle.radians() != 0.0)
+            {
+                // 楕円・傾きのある円弧をポリラインで近似
+                let loss = if arc.is_ellipse() {
+                    Loss::EllipseApproximated
                 } else {
-                    let major_axis_x = major_radius * tilt_angle.cos();
-                    let major_axis_y = major_radius * tilt_angle.sin();
-
-                    let (start_param, end_param) = if arc.is_full_circle {
-                        (0.0, 2.0 * std::f64::consts::PI)
-                    } else {
-                        (arc.start_angle, arc.start_angle + arc.arc_angle)
-                    };
-
-                    Some(Entity::Ellipse(Ellipse {
-                        layer: layer_name,
-                        color,
-                        line_type,
-                        center_x: arc.center_x,
-                        center_y: arc.center_y,
-                        major_axis_x,
-                        major_axis_y,
-                        minor_ratio,
-                        start_param,
-                        end_param,
-                    }))
+                    Loss::ArcApproximated
+                };
+                report.lossy(location, kind, loss);
+                let closed = arc.is_closed();
+                let mut points = arc.tessellate(tolerance);
+                if closed {
+                    points.pop();
                 }
+                Some(Entity::Polyline(Polyline {
+                    layer: layer_name,
+                    color,
+                    line_type,
+                    line_type_scale,
+                    xdata,
+                    vertices: points.into_iter().map(|p| Vertex { x: p.x, y: p.y }).collect(),
+                    closed,
+                }))
+            } else if arc.is_ellipse() {
+                // 楕円または楕円弧
+                let params = ellipse_params(arc);
+                Some(Entity::Ellipse(Ellipse {
+                    layer: layer_name,
+                    color,
+                    line_type,
+                    line_type_scale,
+                    xdata,
+                    center_x: arc.center.x,
+                    center_y: arc.center.y,
+                    major_axis_x: params.major_axis_x,
+                    major_axis_y: params.major_axis_y,
+                    minor_ratio: params.minor_ratio,
+                    start_param: params.start_param,
+                    end_param: params.end_param,
+                }))
             } else {
-                // 円弧
-                let start_angle = rad_to_deg(arc.start_angle);
-                let end_angle = rad_to_deg(arc.start_angle + arc.arc_angle);
+                // 円弧: DXFの円弧は反時計回りのため、負の円弧角は始点と終点を入れ替え、
+                // 角度を0以上360未満にそろえる
+                let start = arc.start_angle.radians();
+                let end = (arc.start_angle + arc.arc_angle).radians();
+                let (start, end) = if arc.arc_angle.radians() < 0.0 {
+                    (end, start)
+                } else {
+                    (start, end)
+                };
+                let start_angle = rad_to_deg(start).rem_euclid(360.0);
+                let end_angle = rad_to_deg(end).rem_euclid(360.0);
 
                 Some(Entity::Arc(Arc {
                     layer: layer_name,
                     color,
                     line_type,
-                    center_x: arc.center_x,
-                    center_y: arc.center_y,
+                    line_type_scale,
+                    xdata,
+                    center_x: arc.center.x,
+                    center_y: arc.center.y,
                     radius: arc.radius,
                     start_angle,
                     end_angle,
@@ -160,15 +851,33 @@ fn convert_entity(jww_entity: &JwwEntity, jww_doc: &JwwDocument) -> Option<Entit
         }
 
         JwwEntity::Point(point) => {
-            if point.is_temporary {
-                return None; // 仮点はスキップ
+            if options.point_marker_blocks && is_marker(point) {
+                let size = POINT_MARKER_SIZE
+                    * positive_or_one(point.scale)
+                    * jww_doc.layer_group_scale(base.layer_group);
+                return Some(Entity::Insert(Insert {
+                    layer: layer_name,
+                    color,
+                    line_type,
+                    line_type_scale,
+                    xdata,
+                    block_name: marker_block_name(point.code),
+                    x: point.position.x,
+                    y: point.position.y,
+                    scale_x: size,
+                    scale_y: size,
+                    rotation: point.angle.degrees(),
+                }));
             }
+            // 仮点の除外は EntityFilter で行う
             Some(Entity::Point(Point {
                 layer: layer_name,
                 color,
                 line_type,
-                x: point.x,
-                y: point.y,
+                line_type_scale,
+                xdata,
+                x: point.position.x,
+                y: point.position.y,
             }))
         }
 
@@ -179,11 +888,13 @@ fn convert_entity(jww_entity: &JwwEntity, jww_doc: &JwwDocument) -> Option<Entit
                 layer: layer_name,
                 color,
                 line_type,
-                x: text.start_x,
-                y: text.start_y,
+                line_type_scale,
+                xdata,
+                x: text.start.x,
+                y: text.start.y,
                 height,
-                rotation: text.angle,
-                content: text.content.clone(),
+                rotation: text.rotation().degrees(),
+                content: text.content.to_string(),
                 style: "STANDARD".to_string(),
             }))
         }
@@ -192,46 +903,362 @@ fn convert_entity(jww_entity: &JwwEntity, jww_doc: &JwwDocument) -> Option<Entit
             layer: layer_name,
             color,
             line_type,
-            x1: solid.point1_x,
-            y1: solid.point1_y,
-            x2: solid.point2_x,
-            y2: solid.point2_y,
-            x3: solid.point3_x,
-            y3: solid.point3_y,
-            x4: solid.point4_x,
-            y4: solid.point4_y,
+            line_type_scale,
+            xdata,
+            x1: solid.point1.x,
+            y1: solid.point1.y,
+            x2: solid.point2.x,
+            y2: solid.point2.y,
+            x3: solid.point3.x,
+            y3: solid.point3.y,
+            x4: solid.point4.x,
+            y4: solid.point4.y,
         })),
 
         JwwEntity::Block(block) => {
+            if !jww_doc.block_defs.iter().any(|bd| bd.number == block.def_number) {
+                report.lossy(location, kind, Loss::MissingBlockDefinition(block.def_number));
+            }
             let block_name = get_block_name(jww_doc, block.def_number);
+            let (scale_x, scale_y, rotation) = insert_transform(block);
             Some(Entity::Insert(Insert {
                 layer: layer_name,
                 color,
                 line_type,
+                line_type_scale,
+                xdata,
                 block_name,
-                x: block.ref_x,
-                y: block.ref_y,
-                scale_x: block.scale_x,
-                scale_y: block.scale_y,
-                rotation: rad_to_deg(block.rotation),
+                x: block.reference.x,
+                y: block.reference.y,
+                scale_x,
+                scale_y,
+                rotation,
+            }))
+        }
+
+        JwwEntity::Dimension(dim) => {
+            let points = DimensionPoints::new(dim);
+            let scale = jww_doc.layer_group_scale(base.layer_group);
+            let geometry = dimension_geometry(dim, &points, dim_style, scale);
+            let [ext1, ext2] = points.origins;
+            let [_, def] = points.feet;
+            let shift = dimension_text_shift(dim, &points, dim_style.text_gap * scale);
+            let (text_x, text_y) = dimension_text_center(&dim.text, dim_style.text_height * scale);
+            let (text_x, text_y) = (text_x + shift.0, text_y + shift.1);
+            Some(Entity::Dimension(Dimension {
+                layer: layer_name,
+                color,
+                line_type,
+                line_type_scale,
+                xdata,
+                style: dim_style.name.clone(),
+                def_x: def.0,
+                def_y: def.1,
+                text_x,
+                text_y,
+                ext1_x: ext1.0,
+                ext1_y: ext1.1,
+                ext2_x: ext2.0,
+                ext2_y: ext2.1,
+                angle: points.direction.1.atan2(points.direction.0).to_degrees(),
+                text_override: dimension_text_override(&dim.text.content, &points),
+                geometry,
             }))
         }
+
+        JwwEntity::Unknown(_) => None,
+    }
+}
+
+/// 寸法の定義点 (JWW座標)
+struct DimensionPoints {
+    /// 寸法線の方向 (単位ベクトル)
+    direction: (f64, f64),
+    /// 補助線の起点 (計測点)
+    origins: [(f64, f64); 2],
+    /// 起点から寸法線に下ろした垂線の足
+    feet: [(f64, f64); 2],
+}
+
+impl DimensionPoints {
+    /// 補助線の起点は端点の3点目・4点目とし、端点を持たない場合 (Ver.4.20未満) は寸法線の両端とする
+    fn new(dim: &jww_core::Dimension) -> Self {
+        let line = &dim.line;
+        let (dx, dy) = (line.end.x - line.start.x, line.end.y - line.start.y);
+        let length = dx.hypot(dy);
+        let direction = if length > 0.0 {
+            (dx / length, dy / length)
+        } else {
+            (1.0, 0.0)
+        };
+
+        let origins = match (dim.end_points.get(2), dim.end_points.get(3)) {
+            (Some(p1), Some(p2)) => [p1.position.into(), p2.position.into()],
+            _ => [line.start.into(), line.end.into()],
+        };
+        let feet = origins.map(|(x, y)| {
+            let t = (x - line.start.x) * direction.0 + (y - line.start.y) * direction.1;
+            (line.start.x + t * direction.0, line.start.y + t * direction.1)
+        });
+
+        Self {
+            direction,
+            origins,
+            feet,
+        }
+    }
+}
+
+/// 寸法図形 (寸法線・補助線・矢印・寸法値) を作成する
+///
+/// 大きさは寸法スタイルの用紙上の値にレイヤグループの縮尺を掛けて求める。
+/// 図形はレイヤー "0" に置き、寸法エンティティのレイヤーを引き継がせる。
+fn dimension_geometry(
+    dim: &jww_core::Dimension,
+    points: &DimensionPoints,
+    style: &DimStyle,
+    scale: f64,
+) -> Vec<Entity> {
+    let line = |color: i32, (x1, y1): (f64, f64), (x2, y2): (f64, f64)| {
+        Entity::Line(Line {
+            layer: "0".to_string(),
+            color,
+            line_type: "CONTINUOUS".to_string(),
+            line_type_scale: None,
+            xdata: None,
+            x1,
+            y1,
+            x2,
+            y2,
+        })
+    };
+    let line_color = map_color(dim.line.base.pen_color);
+    let (ux, uy) = points.direction;
+    let shift = dimension_text_shift(dim, points, style.text_gap * scale);
+    let [foot1, foot2] = points.feet;
+    let mut geometry = vec![line(line_color, foot1, foot2)];
+
+    // 補助線: 起点から離れの分だけ空けて、寸法線の先まで延ばす
+    let offset = style.extension_offset * scale;
+    let extension = style.extension_extension * scale;
+    for (i, (origin, foot)) in points.origins.iter().zip(&points.feet).enumerate() {
+        let (nx, ny) = (foot.0 - origin.0, foot.1 - origin.1);
+        let length = nx.hypot(ny);
+        if length <= offset {
+            continue;
+        }
+        let (nx, ny) = (nx / length, ny / length);
+        let color = dim
+            .extension_lines
+            .get(i)
+            .map_or(line_color, |l| map_color(l.base.pen_color));
+        geometry.push(line(
+            color,
+            (origin.0 + nx * offset, origin.1 + ny * offset),
+            (foot.0 + nx * extension, foot.1 + ny * extension),
+        ));
+    }
+
+    // 矢印: 寸法線の両端を先端とする塗りつぶし三角形 (幅は長さの1/3)
+    let size = style.arrow_size * scale;
+    let half_width = size / 6.0;
+    for (tip, sign) in [(foot1, 1.0), (foot2, -1.0)] {
+        let (bx, by) = (tip.0 + sign * ux * size, tip.1 + sign * uy * size);
+        let (x2, y2) = (bx - uy * half_width, by + ux * half_width);
+        let (x3, y3) = (bx + uy * half_width, by - ux * half_width);
+        geometry.push(Entity::Solid(Solid {
+            layer: "0".to_string(),
+            color: line_color,
+            line_type: "CONTINUOUS".to_string(),
+            line_type_scale: None,
+            xdata: None,
+            x1: tip.0,
+            y1: tip.1,
+            x2,
+            y2,
+            x3,
+            y3,
+            x4: x3,
+            y4: y3,
+        }));
+    }
+
+    geometry.push(Entity::Text(Text {
+        layer: "0".to_string(),
+        color: map_color(dim.text.base.pen_color),
+        line_type: "CONTINUOUS".to_string(),
+        line_type_scale: None,
+        xdata: None,
+        x: dim.text.start.x + shift.0,
+        y: dim.text.start.y + shift.1,
+        height: style.text_height * scale,
+        rotation: dim.text.rotation().degrees(),
+        content: dim.text.content.to_string(),
+        style: "STANDARD".to_string(),
+    }));
+
+    geometry
+}
+
+/// 寸法値が計測値と異なる場合 (利用者が書き換えた場合) はその文字列を返す
+///
+/// JWWは寸法値を文字列として保存するため、桁区切りを除いて数値として読み、
+/// 表示されている最小の桁の1単位未満の差であれば計測値 (丸め・切り上げ・切り捨て) とみなす。
+/// 計測値は補助線の起点から寸法線に下ろした垂線の足の間の距離。
+fn dimension_text_override(content: &str, points: &DimensionPoints) -> Option<String> {
+    let [(x1, y1), (x2, y2)] = points.feet;
+    let measured = (x2 - x1).hypot(y2 - y1);
+    let text = content.trim();
+    let number: String = text.chars().filter(|c| *c != ',').collect();
+    let decimals = number.split_once('.').map_or(0, |(_, fraction)| fraction.len());
+    match number.parse::<f64>() {
+        Ok(value) if (value - measured).abs() < 10f64.powi(-(decimals as i32)) => None,
+        // 寸法値を消した場合は、DXFで非表示を表す空白1文字にする
+        _ if text.is_empty() => Some(" ".to_string()),
+        _ => Some(content.to_string()),
+    }
+}
+
+/// 寸法値を寸法線から寸法スタイルの間隔だけ離すための移動量を返す
+///
+/// 寸法値は寸法線に対して置かれている側に移動し、寸法線上にある場合は寸法線の左側に置く。
+fn dimension_text_shift(dim: &jww_core::Dimension, points: &DimensionPoints, gap: f64) -> (f64, f64) {
+    let (ux, uy) = points.direction;
+    let (nx, ny) = (-uy, ux);
+    let distance = (dim.text.start.x - dim.line.start.x) * nx + (dim.text.start.y - dim.line.start.y) * ny;
+    let side = if distance < 0.0 { -1.0 } else { 1.0 };
+    let amount = side * gap - distance;
+    (nx * amount, ny * amount)
+}
+
+/// 寸法値の中心 (文字列の始点・終点の中点から文字高さの半分だけ上) を返す
+fn dimension_text_center(text: &jww_core::Text, height: f64) -> (f64, f64) {
+    let (sin, cos) = text.rotation().radians().sin_cos();
+    let half = height / 2.0;
+    let mid = text.start.midpoint(text.end);
+    (mid.x - sin * half, mid.y + cos * half)
+}
+
+/// ブロック挿入の倍率と回転角度 (度) を返す
+///
+/// 反転したブロックはY方向の倍率が負の場合にX方向の反転と180度回転に置き換え、
+/// AutoCADのミラーと同じくX方向の倍率のみが負となるようにする。
+/// 両方が負の場合は反転のない180度回転となる。
+fn insert_transform(block: &jww_core::Block) -> (f64, f64, f64) {
+    let (mut scale_x, mut scale_y, mut rotation) = (block.scale_x, block.scale_y, block.rotation.radians());
+    if scale_y < 0.0 {
+        scale_x = -scale_x;
+        scale_y = -scale_y;
+        rotation += std::f64::consts::PI;
+    }
+    (scale_x, scale_y, rad_to_deg(rotation).rem_euclid(360.0))
+}
+
+/// DXF楕円の形状パラメータ
+struct EllipseParams {
+    major_axis_x: f64,
+    major_axis_y: f64,
+    minor_ratio: f64,
+    start_param: f64,
+    end_param: f64,
+}
+
+/// JWW楕円弧をDXF楕円のパラメータに変換する
+///
+/// JWWの開始角・円弧角はX軸から測った実際の角度で記録されている。
+/// DXFの開始・終了パラメータは長軸を基準とした、扁平前の円上の角度
+/// (媒介変数) であるため、長軸基準の角度に直してから媒介変数に変換する。
+fn ellipse_params(arc: &jww_core::Arc) -> EllipseParams {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    let start_angle = arc.start_angle.radians();
+    let arc_angle = arc.arc_angle.radians();
+    let tilt_angle = arc.tilt_angle.radians();
+
+    // 扁平率が1より大きい場合は短軸側が長軸になるため軸を入れ替える
+    let (major_radius, minor_ratio, axis_angle) = if arc.flatness > 1.0 {
+        (arc.radius * arc.flatness, 1.0 / arc.flatness, tilt_angle + FRAC_PI_2)
+    } else {
+        (arc.radius, arc.flatness, tilt_angle)
+    };
+
+    let (start_param, end_param) = if arc.is_full_circle || arc_angle.abs() >= 2.0 * PI {
+        (0.0, 2.0 * PI)
+    } else {
+        // DXF楕円は反時計回りのため、負の円弧角は始点と終点を入れ替える
+        let (start, end) = if arc_angle < 0.0 {
+            (start_angle + arc_angle, start_angle)
+        } else {
+            (start_angle, start_angle + arc_angle)
+        };
+        let to_param = |angle: f64| {
+            let local = angle - axis_angle;
+            local.sin().atan2(minor_ratio * local.cos()).rem_euclid(2.0 * PI)
+        };
+        let start_param = to_param(start);
+        let mut sweep = (to_param(end) - start_param).rem_euclid(2.0 * PI);
+        if sweep == 0.0 && arc_angle != 0.0 {
+            sweep = 2.0 * PI;
+        }
+        (start_param, start_param + sweep)
+    };
+
+    EllipseParams {
+        major_axis_x: major_radius * axis_angle.cos(),
+        major_axis_y: major_radius * axis_angle.sin(),
+        minor_ratio,
+        start_param,
+        end_param,
     }
 }
 
 /// JWWブロック定義をDXFブロックに変換する
-fn convert_blocks(jww_doc: &JwwDocument) -> Vec<Block> {
+fn convert_blocks(
+    jww_doc: &JwwDocument,
+    options: &ConvertOptions,
+    dim_style: &DimStyle,
+    report: &mut ConversionReport,
+) -> (Vec<Block>, HashMap<String, (f64, f64)>) {
+    let (order, circular) = block_order(jww_doc);
     let mut blocks = Vec::new();
+    let mut offsets = HashMap::new();
 
-    for bd in &jww_doc.block_defs {
+    for def_index in order {
+        let bd = &jww_doc.block_defs[def_index];
         let mut block_entities = Vec::new();
+        let mut dedup = options.dedup_tolerance.map(crate::dedup::Dedup::new);
 
-        for e in &bd.entities {
-            if let Some(dxf_entity) = convert_entity(e, jww_doc) {
+        for (index, e) in bd.entities.iter().enumerate() {
+            let location = EntityLocation {
+                block: Some(bd.number),
+                index,
+            };
+            if circular.contains(&(def_index, index)) {
+                *report.input_counts.entry(e.kind()).or_default() += 1;
+                report.skip(location, e.kind(), SkipReason::CircularReference);
+                continue;
+            }
+            if let Some(dxf_entity) = convert_entity(e, jww_doc, options, dim_style, report, location) {
+                if dedup.as_mut().is_some_and(|d| d.is_duplicate(&block_entities, &dxf_entity)) {
+                    report.skip(location, e.kind(), SkipReason::Duplicate);
+                    continue;
+                }
                 block_entities.push(dxf_entity);
             }
         }
 
+        // 範囲の左下を基準点 (原点) に移し、挿入点の移動量を記録する
+        if options.rebase_blocks {
+            if let Some(bb) = jww_doc.block_bounding_box(bd.number) {
+                if bb.min_x != 0.0 || bb.min_y != 0.0 {
+                    for entity in &mut block_entities {
+                        translate(entity, -bb.min_x, -bb.min_y);
+                    }
+                    offsets.insert(get_block_name(jww_doc, bd.number), (bb.min_x, bb.min_y));
+                }
+            }
+        }
+
         blocks.push(Block {
             name: bd.name.clone(),
             base_x: 0.0,
@@ -240,19 +1267,167 @@ fn convert_blocks(jww_doc: &JwwDocument) -> Vec<Block> {
         });
     }
 
-    blocks
+    (blocks, offsets)
 }
 
-/// レイヤー名を取得する
-fn get_layer_name(jww_doc: &JwwDocument, layer_group: u16, layer: u16) -> String {
-    if (layer_group as usize) < 16 && (layer as usize) < 16 {
-        let lg = &jww_doc.layer_groups[layer_group as usize];
-        let l = &lg.layers[layer as usize];
-        if !l.name.is_empty() {
-            return l.name.clone();
+/// 構成要素を平行移動したブロックの挿入点を、挿入された図形が動かないように移す
+fn rebase_insert(entity: &mut Entity, offsets: &HashMap<String, (f64, f64)>) {
+    let Entity::Insert(insert) = entity else {
+        return;
+    };
+    let Some(&(dx, dy)) = offsets.get(&insert.block_name) else {
+        return;
+    };
+    let (sin, cos) = insert.rotation.to_radians().sin_cos();
+    let (sx, sy) = (dx * insert.scale_x, dy * insert.scale_y);
+    insert.x += sx * cos - sy * sin;
+    insert.y += sx * sin + sy * cos;
+}
+
+/// ブロック定義の出力順序を決定する
+///
+/// 入れ子のブロック挿入の参照先が参照元より先に定義されるように並べる。
+/// 循環参照となるブロック挿入は (ブロック定義の位置, エンティティの位置) として返す。
+fn block_order(jww_doc: &JwwDocument) -> (Vec<usize>, HashSet<(usize, usize)>) {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        Unvisited,
+        Visiting,
+        Done,
+    }
+
+    fn visit(
+        jww_doc: &JwwDocument,
+        def_index: usize,
+        states: &mut [State],
+        order: &mut Vec<usize>,
+        circular: &mut HashSet<(usize, usize)>,
+    ) {
+        states[def_index] = State::Visiting;
+        for (index, entity) in jww_doc.block_defs[def_index].entities.iter().enumerate() {
+            let JwwEntity::Block(block) = entity else {
+                continue;
+            };
+            let Some(child) = jww_doc
+                .block_defs
+                .iter()
+                .position(|bd| bd.number == block.def_number)
+            else {
+                continue;
+            };
+            match states[child] {
+                State::Unvisited => visit(jww_doc, child, states, order, circular),
+                State::Visiting => {
+                    circular.insert((def_index, index));
+                }
+                State::Done => {}
+            }
+        }
+        states[def_index] = State::Done;
+        order.push(def_index);
+    }
+
+    let mut states = vec![State::Unvisited; jww_doc.block_defs.len()];
+    let mut order = Vec::with_capacity(jww_doc.block_defs.len());
+    let mut circular = HashSet::new();
+    for def_index in 0..jww_doc.block_defs.len() {
+        if states[def_index] == State::Unvisited {
+            visit(jww_doc, def_index, &mut states, &mut order, &mut circular);
         }
     }
-    format!("{:X}-{:X}", layer_group, layer)
+
+    (order, circular)
+}
+
+/// エンティティの座標と寸法を倍率で拡大縮小する
+fn scale_entity(entity: &mut Entity, factor: f64) {
+    match entity {
+        Entity::Line(e) => {
+            e.x1 *= factor;
+            e.y1 *= factor;
+            e.x2 *= factor;
+            e.y2 *= factor;
+        }
+        Entity::Circle(e) => {
+            e.center_x *= factor;
+            e.center_y *= factor;
+            e.radius *= factor;
+        }
+        Entity::Arc(e) => {
+            e.center_x *= factor;
+            e.center_y *= factor;
+            e.radius *= factor;
+        }
+        Entity::Ellipse(e) => {
+            e.center_x *= factor;
+            e.center_y *= factor;
+            e.major_axis_x *= factor;
+            e.major_axis_y *= factor;
+        }
+        Entity::Point(e) => {
+            e.x *= factor;
+            e.y *= factor;
+        }
+        Entity::Text(e) => {
+            e.x *= factor;
+            e.y *= factor;
+            e.height *= factor;
+        }
+        Entity::Solid(e) => {
+            e.x1 *= factor;
+            e.y1 *= factor;
+            e.x2 *= factor;
+            e.y2 *= factor;
+            e.x3 *= factor;
+            e.y3 *= factor;
+            e.x4 *= factor;
+            e.y4 *= factor;
+        }
+        Entity::Polyline(e) => {
+            for v in &mut e.vertices {
+                v.x *= factor;
+                v.y *= factor;
+            }
+        }
+        Entity::Leader(e) => {
+            for v in &mut e.vertices {
+                v.x *= factor;
+                v.y *= factor;
+            }
+        }
+        Entity::Insert(e) => {
+            // ブロック定義側も同じ倍率で拡大縮小するため、挿入スケールは変更しない
+            e.x *= factor;
+            e.y *= factor;
+        }
+        Entity::Dimension(e) => {
+            e.def_x *= factor;
+            e.def_y *= factor;
+            e.text_x *= factor;
+            e.text_y *= factor;
+            e.ext1_x *= factor;
+            e.ext1_y *= factor;
+            e.ext2_x *= factor;
+            e.ext2_y *= factor;
+            for entity in &mut e.geometry {
+                scale_entity(entity, factor);
+            }
+        }
+    }
+}
+
+/// レイヤー名を取得する (範囲外のレイヤ番号の場合はNone)
+fn get_layer_name(jww_doc: &JwwDocument, layer_group: u16, layer: u16) -> Option<String> {
+    let l = jww_doc
+        .layer_groups
+        .get(layer_group as usize)?
+        .layers
+        .get(layer as usize)?;
+    if l.name.is_empty() {
+        Some(format!("{:X}-{:X}", layer_group, layer))
+    } else {
+        Some(l.name.clone())
+    }
 }
 
 /// ブロック名を取得する
@@ -268,6 +1443,52 @@ fn get_block_name(jww_doc: &JwwDocument, def_number: u32) -> String {
     format!("BLOCK_{}", def_number)
 }
 
+/// JWW属性を記録するXDATAのアプリケーション名
+pub const XDATA_APP_NAME: &str = "JWW";
+
+/// エンティティのJWW属性をXDATAに変換する
+///
+/// 値は「名前 (1000), 値 (1071)」の組で記録する。
+/// 1071に収まらない曲線属性番号は `i32::MAX` に丸める。
+fn jww_attributes_xdata(base: &jww_core::EntityBase) -> XData {
+    let attributes = [
+        ("pen_style", i32::from(base.pen_style)),
+        ("pen_color", i32::from(base.pen_color)),
+        ("pen_width", i32::from(base.pen_width)),
+        ("layer_group", i32::from(base.layer_group)),
+        ("layer", i32::from(base.layer)),
+        ("flag", i32::from(base.flag)),
+        ("group", i32::try_from(base.group).unwrap_or(i32::MAX)),
+    ];
+
+    let mut values = Vec::with_capacity(attributes.len() * 2);
+    for (name, value) in attributes {
+        values.push(XDataValue::String(name.to_string()));
+        values.push(XDataValue::Long(value));
+    }
+
+    XData {
+        app_name: XDATA_APP_NAME.to_string(),
+        values,
+    }
+}
+
+/// エンティティ個別の線種尺度 (グループコード48) を求める
+///
+/// $LTSCALE は図面の縮尺から決まるため、縮尺の異なるレイヤグループに属する
+/// 破線系エンティティにのみ、その比率を個別に設定する。
+fn entity_line_type_scale(jww_doc: &JwwDocument, layer_group: u16, line_type: &str) -> Option<f64> {
+    if line_type == "CONTINUOUS" {
+        return None;
+    }
+    let group_scale = jww_doc.layer_groups.get(layer_group as usize)?.scale;
+    let drawing_scale = jww_doc.drawing_scale();
+    if group_scale <= 0.0 || (group_scale - drawing_scale).abs() < 1e-9 {
+        return None;
+    }
+    Some(group_scale / drawing_scale)
+}
+
 /// JWW色コードをDXF ACI値にマッピングする
 fn map_color(jww_color: u16) -> i32 {
     match jww_color {
@@ -281,16 +1502,21 @@ fn map_color(jww_color: u16) -> i32 {
         7 => 7,    // JWW 黒/白 -> DXF white/black
         8 => 1,    // JWW 赤 -> DXF red
         9 => 8,    // JWW グレー -> DXF gray
-        _ => {
-            if jww_color >= 100 {
-                (jww_color - 100 + 10) as i32
-            } else {
-                jww_color as i32
-            }
-        }
+        // SXF色 (10-99) はそのまま、拡張線色 (100-345) はACI 10-255に対応付ける
+        10..=99 => i32::from(jww_color),
+        100..=345 => i32::from(jww_color - 100 + 10),
+        _ => DEFAULT_COLOR,
     }
 }
 
+/// 対応するACIのない線色の出力色 (白/黒)
+const DEFAULT_COLOR: i32 = 7;
+
+/// 線色番号に対応するACIがあるかどうかを返す ([`map_color`] が既定の色にしない範囲)
+fn is_known_color(jww_color: u16) -> bool {
+    jww_color <= 345
+}
+
 /// JWW線種をDXF線種名にマッピングする
 fn map_line_type(pen_style: u8) -> String {
     match pen_style {
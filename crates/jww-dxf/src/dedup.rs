@@ -0,0 +1,138 @@
+//! 重複した図形の除去
+//!
+//! トレースした図面では同じ線が重ねて描かれていることが多く、レーザー加工で
+//! 二重に切断される原因になる。レイヤー・色・線種が同じで、座標の差がすべて
+//! 許容差以内の線・円・円弧・楕円・点・ポリラインを重複とみなす。
+//! 線とポリラインは向きが逆でも重複とする。
+
+use std::collections::HashMap;
+
+use crate::types::Entity;
+
+/// 座標を比較する図形の形
+struct Shape<'a> {
+    /// (種別, レイヤー, 色, 線種, 閉じているかどうか)
+    key: (&'static str, &'a str, i32, &'a str, bool),
+    /// 向きをそろえた座標
+    coords: Vec<f64>,
+}
+
+impl Shape<'_> {
+    fn matches(&self, other: &Shape, tolerance: f64) -> bool {
+        self.key == other.key
+            && self.coords.len() == other.coords.len()
+            && self.coords.iter().zip(&other.coords).all(|(a, b)| (a - b).abs() <= tolerance)
+    }
+}
+
+/// 出力済みの図形を格子で索引し、重複を判定する
+pub(crate) struct Dedup {
+    tolerance: f64,
+    /// 格子の大きさ
+    cell: f64,
+    /// 格子 -> 出力済みエンティティの番号
+    cells: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl Dedup {
+    pub(crate) fn new(tolerance: f64) -> Self {
+        let tolerance = tolerance.max(0.0);
+        Self {
+            tolerance,
+            cell: tolerance.max(1e-9),
+            cells: HashMap::new(),
+        }
+    }
+
+    /// `entity` が `entities` 内の図形と重複するかどうかを返す
+    ///
+    /// 重複しない場合は `entities` の末尾に追加される番号として索引に登録する。
+    pub(crate) fn is_duplicate(&mut self, entities: &[Entity], entity: &Entity) -> bool {
+        let Some(shape) = shape(entity) else {
+            return false;
+        };
+        let (cx, cy) = (self.cell_of(shape.coords[0]), self.cell_of(shape.coords[1]));
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                let candidates = self.cells.get(&(cx + dx, cy + dy)).into_iter().flatten();
+                let duplicate = candidates
+                    .filter_map(|&i| shape_at(entities, i))
+                    .any(|other| shape.matches(&other, self.tolerance));
+                if duplicate {
+                    return true;
+                }
+            }
+        }
+        self.cells.entry((cx, cy)).or_default().push(entities.len());
+        false
+    }
+
+    fn cell_of(&self, value: f64) -> i64 {
+        (value / self.cell).floor() as i64
+    }
+}
+
+fn shape_at(entities: &[Entity], index: usize) -> Option<Shape<'_>> {
+    entities.get(index).and_then(shape)
+}
+
+fn shape(entity: &Entity) -> Option<Shape<'_>> {
+    let mut closed = false;
+    let (layer, color, line_type, coords) = match entity {
+        Entity::Line(e) => {
+            let (a, b) = ordered((e.x1, e.y1), (e.x2, e.y2));
+            (&e.layer, e.color, &e.line_type, vec![a.0, a.1, b.0, b.1])
+        }
+        Entity::Circle(e) => (&e.layer, e.color, &e.line_type, vec![e.center_x, e.center_y, e.radius]),
+        Entity::Arc(e) => {
+            let point = |angle: f64| {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                [e.center_x + e.radius * cos, e.center_y + e.radius * sin]
+            };
+            let mut coords = vec![e.center_x, e.center_y, e.radius];
+            coords.extend(point(e.start_angle));
+            coords.extend(point(e.end_angle));
+            (&e.layer, e.color, &e.line_type, coords)
+        }
+        Entity::Ellipse(e) => {
+            let (nx, ny) = (-e.major_axis_y * e.minor_ratio, e.major_axis_x * e.minor_ratio);
+            let point = |t: f64| {
+                let (sin, cos) = t.sin_cos();
+                [
+                    e.center_x + e.major_axis_x * cos + nx * sin,
+                    e.center_y + e.major_axis_y * cos + ny * sin,
+                ]
+            };
+            let mut coords = vec![e.center_x, e.center_y, e.major_axis_x, e.major_axis_y, nx, ny];
+            coords.extend(point(e.start_param));
+            coords.extend(point(e.end_param));
+            (&e.layer, e.color, &e.line_type, coords)
+        }
+        Entity::Point(e) => (&e.layer, e.color, &e.line_type, vec![e.x, e.y]),
+        Entity::Polyline(e) => {
+            let first = e.vertices.first()?;
+            let last = e.vertices.last()?;
+            let mut vertices: Vec<_> = e.vertices.iter().map(|v| (v.x, v.y)).collect();
+            if ordered((first.x, first.y), (last.x, last.y)).0 != (first.x, first.y) {
+                vertices.reverse();
+            }
+            closed = e.closed;
+            let coords = vertices.into_iter().flat_map(|(x, y)| [x, y]).collect();
+            (&e.layer, e.color, &e.line_type, coords)
+        }
+        _ => return None,
+    };
+    Some(Shape {
+        key: (entity.type_name(), layer, color, line_type, closed),
+        coords,
+    })
+}
+
+/// 2点を座標の小さい順に並べる
+fn ordered(a: (f64, f64), b: (f64, f64)) -> ((f64, f64), (f64, f64)) {
+    if a.0 < b.0 || (a.0 == b.0 && a.1 <= b.1) {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
@@ -0,0 +1,330 @@
+//! dxfクレートを使用した出力 (feature `dxf-rs`)
+//!
+//! DXFドキュメントを [`dxf`] クレートの [`dxf::Drawing`] に変換し、dxfクレートのライターで書き出す。
+//! 独自のライター ([`crate::to_string`]) とは別の実装で同じ図面を出力するため、
+//! 出力結果の相互検証や、より仕様に忠実な出力が必要な場合に使う。
+//!
+//! dxfクレートに対応する表現がないため、次の情報は出力しない。
+//! - レイヤーのフリーズ・ロック
+//! - エンティティのグループ (GROUP)
+//! - ペーパー空間レイアウト
+//! - ファイル先頭のコメント (999)
+//! - R2000のプロジェクト名 ($PROJECTNAME はdxfクレートではR2004以降のみ出力する)
+
+use chrono::{DateTime, Local, Utc};
+use dxf::entities::{
+    Arc, Circle, Ellipse, Entity as DxfEntity, EntityType, Insert, Leader, Line, ModelPoint,
+    Polyline, RotatedDimension, Solid, Text, Vertex,
+};
+use dxf::enums::{AcadVersion, DimensionType, DrawingUnits};
+use dxf::tables::{AppId, DimStyle, Layer, LineType, Style, ViewPort};
+use dxf::{Block, Color, Drawing, DxfResult, Point, Vector, XData, XDataItem};
+
+use crate::Units;
+use crate::types::{Document, DxfVersion, Entity, XDataValue};
+use crate::writer::{text_styles, xdata_app_names, LINE_TYPES};
+
+/// DXFドキュメントを [`dxf::Drawing`] に変換する
+///
+/// バージョンは [`Document::output_version`] で決まる。寸法図形は独自のライターと同じく
+/// ブロック内、モデル空間の順に無名ブロック (`*D1`、`*D2`、…) として定義する。
+pub fn to_drawing(doc: &Document) -> Drawing {
+    let mut builder = Builder {
+        drawing: Drawing::new(),
+        dimension_blocks: 0,
+    };
+    builder.header(doc);
+    builder.tables(doc);
+
+    // 寸法の無名ブロックは変換しながら追加するため、独自のライターと同じく
+    // 寸法図形のブロックが先、通常のブロックが後になる
+    let blocks: Vec<Block> = doc
+        .blocks
+        .iter()
+        .map(|block| Block {
+            name: block.name.clone(),
+            base_point: Point::new(block.base_x, block.base_y, 0.0),
+            entities: block.entities.iter().map(|e| builder.entity(e)).collect(),
+            ..Default::default()
+        })
+        .collect();
+    let entities: Vec<DxfEntity> = doc.entities.iter().map(|e| builder.entity(e)).collect();
+    for block in blocks {
+        builder.drawing.add_block(block);
+    }
+    for entity in entities {
+        builder.drawing.add_entity(entity);
+    }
+
+    builder.drawing
+}
+
+/// DXFドキュメントをdxfクレートで `writer` に書き出す
+pub fn write_drawing<W: std::io::Write + ?Sized>(doc: &Document, writer: &mut W) -> DxfResult<()> {
+    to_drawing(doc).save(writer)
+}
+
+/// 変換中の図面と寸法の無名ブロックの連番
+struct Builder {
+    drawing: Drawing,
+    dimension_blocks: usize,
+}
+
+impl Builder {
+    /// ヘッダー変数を設定する
+    fn header(&mut self, doc: &Document) {
+        let header = &mut self.drawing.header;
+        header.version = match doc.output_version() {
+            DxfVersion::R12 => AcadVersion::R12,
+            DxfVersion::R2000 => AcadVersion::R2000,
+        };
+        header.handles_enabled = true;
+        header.default_drawing_units = match Units::from_insunits(doc.header.insunits) {
+            Some(Units::Inches) => dxf::enums::Units::Inches,
+            Some(Units::Feet) => dxf::enums::Units::Feet,
+            Some(Units::Millimeters) => dxf::enums::Units::Millimeters,
+            Some(Units::Centimeters) => dxf::enums::Units::Centimeters,
+            Some(Units::Meters) => dxf::enums::Units::Meters,
+            Some(Units::Unitless) | None => dxf::enums::Units::Unitless,
+        };
+        header.drawing_units = if doc.header.measurement == 0 {
+            DrawingUnits::English
+        } else {
+            DrawingUnits::Metric
+        };
+        header.line_type_scale = doc.header.ltscale;
+        header.point_display_mode = doc.header.pdmode;
+        header.point_display_size = doc.header.pdsize;
+        if let Some(limits) = &doc.header.limits {
+            header.minimum_drawing_limits = Point::new(limits.min_x, limits.min_y, 0.0);
+            header.maximum_drawing_limits = Point::new(limits.max_x, limits.max_y, 0.0);
+        }
+        // 日時はUTCで記録するため、ローカル時刻とUTCの変数に同じ時刻を設定する
+        if let Some(created) = doc.header.created.and_then(from_julian_date) {
+            header.creation_date = created.with_timezone(&Local);
+            header.creation_date_universal = created;
+        }
+        if let Some(updated) = doc.header.updated.and_then(from_julian_date) {
+            header.update_date = updated.with_timezone(&Local);
+            header.update_date_universal = updated;
+        }
+        if let Some(project_name) = &doc.header.project_name {
+            header.project_name = project_name.clone();
+        }
+        if let Some(style) = doc.dim_styles.first() {
+            header.dimension_style_name = style.name.clone();
+        }
+    }
+
+    /// 線種・レイヤー・文字スタイル・寸法スタイル・初期表示ビュー・アプリケーション名を登録する
+    fn tables(&mut self, doc: &Document) {
+        for (name, description, pattern) in LINE_TYPES {
+            self.drawing.add_line_type(LineType {
+                name: name.to_string(),
+                description: description.to_string(),
+                alignment_code: 65,
+                element_count: pattern.len() as i32,
+                total_pattern_length: pattern.iter().map(|v| v.abs()).sum(),
+                dash_dot_space_lengths: pattern.to_vec(),
+                complex_line_type_element_types: vec![0; pattern.len()],
+                ..Default::default()
+            });
+        }
+
+        for layer in &doc.layers {
+            // 負の色番号は非表示のレイヤーを表す
+            self.drawing.add_layer(Layer {
+                name: layer.name.clone(),
+                color: color(layer.color.abs()),
+                line_type_name: layer.line_type.clone(),
+                is_layer_on: layer.color >= 0,
+                ..Default::default()
+            });
+        }
+
+        for name in text_styles(doc) {
+            if !self.drawing.styles().any(|s| s.name.eq_ignore_ascii_case(name)) {
+                self.drawing.add_style(Style {
+                    name: name.to_string(),
+                    ..Default::default()
+                });
+            }
+        }
+
+        for style in &doc.dim_styles {
+            self.drawing.add_dim_style(DimStyle {
+                name: style.name.clone(),
+                dimensioning_scale_factor: style.scale,
+                dimensioning_arrow_size: style.arrow_size,
+                dimension_extension_line_offset: style.extension_offset,
+                dimension_extension_line_extension: style.extension_extension,
+                dimensioning_text_height: style.text_height,
+                dimension_line_gap: style.text_gap,
+                ..Default::default()
+            });
+        }
+
+        if let Some(view) = &doc.active_view {
+            self.drawing.add_view_port(ViewPort {
+                name: "*ACTIVE".to_string(),
+                view_center: Point::new(view.center_x, view.center_y, 0.0),
+                view_height: view.height,
+                view_port_aspect_ratio: view.aspect_ratio,
+                ..Default::default()
+            });
+        }
+
+        for name in xdata_app_names(doc) {
+            self.drawing.add_app_id(AppId {
+                name: name.to_string(),
+                ..Default::default()
+            });
+        }
+    }
+
+    /// エンティティを変換する
+    ///
+    /// 寸法は寸法図形を無名ブロックとして図面に追加し、そのブロックを参照する。
+    fn entity(&mut self, entity: &Entity) -> DxfEntity {
+        let specific = match entity {
+            Entity::Line(e) => EntityType::Line(Line::new(
+                Point::new(e.x1, e.y1, 0.0),
+                Point::new(e.x2, e.y2, 0.0),
+            )),
+            Entity::Circle(e) => {
+                EntityType::Circle(Circle::new(Point::new(e.center_x, e.center_y, 0.0), e.radius))
+            }
+            Entity::Arc(e) => EntityType::Arc(Arc::new(
+                Point::new(e.center_x, e.center_y, 0.0),
+                e.radius,
+                e.start_angle,
+                e.end_angle,
+            )),
+            Entity::Ellipse(e) => EntityType::Ellipse(Ellipse {
+                center: Point::new(e.center_x, e.center_y, 0.0),
+                major_axis: Vector::new(e.major_axis_x, e.major_axis_y, 0.0),
+                minor_axis_ratio: e.minor_ratio,
+                start_parameter: e.start_param,
+                end_parameter: e.end_param,
+                ..Default::default()
+            }),
+            Entity::Point(e) => EntityType::ModelPoint(ModelPoint::new(Point::new(e.x, e.y, 0.0))),
+            Entity::Text(e) => EntityType::Text(Text {
+                location: Point::new(e.x, e.y, 0.0),
+                text_height: e.height,
+                rotation: e.rotation,
+                value: e.content.clone(),
+                text_style_name: e.style.clone(),
+                ..Default::default()
+            }),
+            Entity::Solid(e) => EntityType::Solid(Solid::new(
+                Point::new(e.x1, e.y1, 0.0),
+                Point::new(e.x2, e.y2, 0.0),
+                Point::new(e.x3, e.y3, 0.0),
+                Point::new(e.x4, e.y4, 0.0),
+            )),
+            Entity::Insert(e) => EntityType::Insert(Insert {
+                name: e.block_name.clone(),
+                location: Point::new(e.x, e.y, 0.0),
+                x_scale_factor: e.scale_x,
+                y_scale_factor: e.scale_y,
+                rotation: e.rotation,
+                ..Default::default()
+            }),
+            Entity::Polyline(e) => {
+                let mut polyline = Polyline {
+                    flags: i32::from(e.closed),
+                    ..Default::default()
+                };
+                for v in &e.vertices {
+                    polyline.add_vertex(&mut self.drawing, Vertex::new(Point::new(v.x, v.y, 0.0)));
+                }
+                EntityType::Polyline(polyline)
+            }
+            Entity::Dimension(e) => {
+                self.dimension_blocks += 1;
+                let block_name = format!("*D{}", self.dimension_blocks);
+                let geometry = e.geometry.iter().map(|g| self.entity(g)).collect();
+                // 無名ブロック (1)
+                self.drawing.add_block(Block {
+                    name: block_name.clone(),
+                    flags: 1,
+                    entities: geometry,
+                    ..Default::default()
+                });
+                let mut dimension = RotatedDimension {
+                    definition_point_2: Point::new(e.ext1_x, e.ext1_y, 0.0),
+                    definition_point_3: Point::new(e.ext2_x, e.ext2_y, 0.0),
+                    rotation_angle: e.angle,
+                    ..Default::default()
+                };
+                let base = &mut dimension.dimension_base;
+                base.block_name = block_name;
+                base.dimension_style_name = e.style.clone();
+                base.definition_point_1 = Point::new(e.def_x, e.def_y, 0.0);
+                base.text_mid_point = Point::new(e.text_x, e.text_y, 0.0);
+                base.dimension_type = DimensionType::RotatedHorizontalOrVertical;
+                base.is_block_reference_referenced_by_this_block_only = true;
+                base.text = e.text_override.clone().unwrap_or_default();
+                EntityType::RotatedDimension(dimension)
+            }
+            Entity::Leader(e) => EntityType::Leader(Leader {
+                dimension_style_name: e.style.clone(),
+                use_arrowheads: e.arrowhead,
+                vertex_count: e.vertices.len() as i32,
+                vertices: e.vertices.iter().map(|v| Point::new(v.x, v.y, 0.0)).collect(),
+                ..Default::default()
+            }),
+        };
+
+        let mut dxf_entity = DxfEntity::new(specific);
+        let (layer, color_number, line_type, line_type_scale) = match entity {
+            Entity::Line(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+            Entity::Circle(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+            Entity::Arc(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+            Entity::Ellipse(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+            Entity::Point(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+            Entity::Text(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+            Entity::Solid(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+            Entity::Insert(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+            Entity::Polyline(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+            Entity::Dimension(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+            Entity::Leader(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+        };
+        let common = &mut dxf_entity.common;
+        common.layer = layer.clone();
+        common.color = color(color_number);
+        common.line_type_name = line_type.clone();
+        common.line_type_scale = line_type_scale.unwrap_or(1.0);
+        if let Some(xdata) = entity.xdata() {
+            common.x_data.push(XData {
+                application_name: xdata.app_name.clone(),
+                items: xdata
+                    .values
+                    .iter()
+                    .map(|value| match value {
+                        XDataValue::String(v) => XDataItem::Str(v.clone()),
+                        XDataValue::Integer(v) => XDataItem::Integer(*v),
+                        XDataValue::Long(v) => XDataItem::Long(*v),
+                        XDataValue::Real(v) => XDataItem::Real(*v),
+                    })
+                    .collect(),
+            });
+        }
+        dxf_entity
+    }
+}
+
+/// 色番号 (0=BYBLOCK, 256=BYLAYER) を変換する
+fn color(number: i32) -> Color {
+    match number {
+        0 => Color::by_block(),
+        1..=255 => Color::from_index(number as u8),
+        _ => Color::by_layer(),
+    }
+}
+
+/// ユリウス日 ($TDCREATE などの形式) をUTC日時に変換する
+fn from_julian_date(julian_date: f64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(crate::time::timestamp(julian_date), 0)
+}
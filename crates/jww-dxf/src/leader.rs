@@ -0,0 +1,174 @@
+//! 引出線の検出
+//!
+//! JWWには引出線のエンティティがなく、線・矢印・文字を同じ曲線属性で
+//! まとめて描かれる。曲線属性ごとに次の条件を満たす組を引出線とみなす。
+//!
+//! - 文字が1つだけで、ほかは線と塗りつぶし (矢印) のみ
+//! - 線が1本の折れ線につながる (先端で交わる短い2本の線は矢印とみなす)
+//! - 折れ線の一方の端が文字の近く (文字高さの2倍以内) にある
+
+use std::collections::BTreeMap;
+
+use jww_core::{Document as JwwDocument, Entity as JwwEntity, Point2D};
+
+/// 端点を同一とみなす距離 (mm)
+const EPSILON: f64 = 1e-6;
+
+/// 検出した引出線
+#[derive(Debug)]
+pub(crate) struct LeaderShape {
+    /// 曲線属性番号
+    pub group: u32,
+    /// 引出線を構成する線・矢印のエンティティ番号
+    pub members: Vec<usize>,
+    /// 文字のエンティティ番号
+    pub text: usize,
+    /// 頂点 (矢印の先端から文字側へ)
+    pub vertices: Vec<Point2D>,
+    /// 先端に矢印があるかどうか
+    pub arrowhead: bool,
+}
+
+/// 図面の引出線を検出する (`accepts` で除外されたエンティティは対象外)
+pub(crate) fn detect(jww_doc: &JwwDocument, accepts: impl Fn(&JwwEntity) -> bool) -> Vec<LeaderShape> {
+    let mut groups: BTreeMap<u32, Vec<usize>> = BTreeMap::new();
+    for (index, entity) in jww_doc.entities.iter().enumerate() {
+        let group = entity.base().group;
+        if group != 0 && accepts(entity) {
+            groups.entry(group).or_default().push(index);
+        }
+    }
+    groups
+        .into_iter()
+        .filter_map(|(group, indices)| detect_group(jww_doc, group, indices))
+        .collect()
+}
+
+fn detect_group(jww_doc: &JwwDocument, group: u32, indices: Vec<usize>) -> Option<LeaderShape> {
+    let mut text = None;
+    let mut lines = Vec::new();
+    let mut solids = Vec::new();
+    for &index in &indices {
+        match &jww_doc.entities[index] {
+            JwwEntity::Text(_) if text.is_none() => text = Some(index),
+            JwwEntity::Line(line) => lines.push((index, line.start, line.end)),
+            JwwEntity::Solid(solid) => solids.push(solid),
+            _ => return None,
+        }
+    }
+    let text_index = text?;
+    let JwwEntity::Text(text) = &jww_doc.entities[text_index] else {
+        unreachable!()
+    };
+
+    let (mut path, arrow_tip) = match chain(&lines) {
+        Some(path) => (path, None),
+        None => {
+            // 先端で交わる短い2本の線 (矢印) を除いて折れ線になるか
+            let (tip, arrow) = arrow_lines(&lines)?;
+            let rest: Vec<_> = lines.iter().filter(|l| !arrow.contains(&l.0)).copied().collect();
+            (chain(&rest)?, Some(tip))
+        }
+    };
+
+    // 文字に近い端を末尾にする
+    let first = *path.first()?;
+    let last = *path.last()?;
+    let reach = text.size_y.max(0.0) * 2.0;
+    let (near_first, near_last) = (
+        segment_distance(first, text.start, text.end),
+        segment_distance(last, text.start, text.end),
+    );
+    if near_first.min(near_last) > reach {
+        return None;
+    }
+    if near_first < near_last {
+        path.reverse();
+    }
+    let tip = path[0];
+    if arrow_tip.is_some_and(|p| p.distance(tip) > EPSILON) {
+        return None;
+    }
+    let solid_tip = solids.iter().any(|s| {
+        [s.point1, s.point2, s.point3, s.point4]
+            .iter()
+            .any(|p| p.distance(tip) <= EPSILON)
+    });
+    if !solids.is_empty() && !solid_tip {
+        return None;
+    }
+
+    Some(LeaderShape {
+        group,
+        members: indices.into_iter().filter(|&i| i != text_index).collect(),
+        text: text_index,
+        vertices: path,
+        arrowhead: arrow_tip.is_some() || solid_tip,
+    })
+}
+
+/// 線がすべて1本の開いた折れ線につながる場合はその頂点を返す
+fn chain(lines: &[(usize, Point2D, Point2D)]) -> Option<Vec<Point2D>> {
+    let (_, start, end) = *lines.first()?;
+    let mut path = vec![start, end];
+    let mut rest: Vec<_> = lines[1..].to_vec();
+    while !rest.is_empty() {
+        let head = path[0];
+        let tail = path[path.len() - 1];
+        let position = rest.iter().position(|&(_, a, b)| {
+            [a, b].iter().any(|p| p.distance(head) <= EPSILON || p.distance(tail) <= EPSILON)
+        })?;
+        let (_, a, b) = rest.swap_remove(position);
+        if a.distance(tail) <= EPSILON {
+            path.push(b);
+        } else if b.distance(tail) <= EPSILON {
+            path.push(a);
+        } else if a.distance(head) <= EPSILON {
+            path.insert(0, b);
+        } else {
+            path.insert(0, a);
+        }
+    }
+    // 閉じた図形・枝分かれは引出線ではない
+    if path[0].distance(path[path.len() - 1]) <= EPSILON || has_branch(lines) {
+        return None;
+    }
+    Some(path)
+}
+
+/// 3本以上の線が集まる端点があるかどうか
+fn has_branch(lines: &[(usize, Point2D, Point2D)]) -> bool {
+    lines.iter().flat_map(|&(_, a, b)| [a, b]).any(|p| {
+        lines
+            .iter()
+            .filter(|&&(_, a, b)| a.distance(p) <= EPSILON || b.distance(p) <= EPSILON)
+            .count()
+            > 2
+    })
+}
+
+/// 3本の線が集まる端点のうち、短い2本を矢印として (先端, 矢印の線の番号) を返す
+fn arrow_lines(lines: &[(usize, Point2D, Point2D)]) -> Option<(Point2D, [usize; 2])> {
+    lines.iter().flat_map(|&(_, a, b)| [a, b]).find_map(|p| {
+        let mut touching: Vec<_> = lines
+            .iter()
+            .filter(|&&(_, a, b)| a.distance(p) <= EPSILON || b.distance(p) <= EPSILON)
+            .collect();
+        if touching.len() != 3 {
+            return None;
+        }
+        touching.sort_by(|x, y| x.1.distance(x.2).total_cmp(&y.1.distance(y.2)));
+        Some((p, [touching[0].0, touching[1].0]))
+    })
+}
+
+/// 点から線分までの距離
+fn segment_distance(p: Point2D, a: Point2D, b: Point2D) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length_sq = dx * dx + dy * dy;
+    if length_sq == 0.0 {
+        return p.distance(a);
+    }
+    let t = (((p.x - a.x) * dx + (p.y - a.y) * dy) / length_sq).clamp(0.0, 1.0);
+    p.distance(Point2D::new(a.x + t * dx, a.y + t * dy))
+}
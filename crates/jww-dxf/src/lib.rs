@@ -1,14 +1,42 @@
 //! DXF変換ライブラリ
 //!
-//! JWWドキュメントをDXF形式に変換する機能を提供する。
+//! JWWドキュメントをDXF形式に変換する機能と、DXFからJWWへの逆変換を提供する。
 
 mod types;
+mod bytes;
+mod options;
+mod report;
+mod time;
 mod converter;
+mod dedup;
+mod leader;
 mod writer;
+mod to_jww;
+mod project;
+pub mod reader;
+pub mod validate;
 
 pub use types::*;
-pub use converter::convert_document;
-pub use writer::to_string;
+pub use options::{
+    ConvertOptions, DimensionStyle, EntityFilter, Provenance, Units, DEFAULT_CURVE_TOLERANCE,
+    DEFAULT_FALLBACK_LAYER, FLAG_AUXILIARY, FLAG_HIDDEN,
+};
+pub use report::{ConversionReport, EntityLocation, Loss, LossyMapping, SkipReason, SkippedEntity};
+pub use converter::{
+    convert_document, convert_document_with_options, convert_document_with_report,
+    IncrementalConverter, XDATA_APP_NAME,
+};
+pub use writer::{to_string, write_to};
+pub use bytes::{to_bytes, DxfEncoding, DxfFormat};
+pub use project::{Project, ProjectError, ProjectFile, PREFIX_SEPARATOR};
+pub use to_jww::{convert_to_jww, dxf_to_jww, DxfToJwwError, JWW_VERSION};
+
+/// jww-dxfのバージョン
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// dxfクレートを使用した出力（dxf-rs feature時のみ使用）
+#[cfg(feature = "dxf-rs")]
+pub mod dxf_rs;
 
 // テスト用ユーティリティ（testing feature時のみ使用）
 #[cfg(feature = "testing")]
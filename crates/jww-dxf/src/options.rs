@@ -0,0 +1,273 @@
+//! 変換オプション
+
+use jww_core::{Entity as JwwEntity, EntityKind};
+use serde::{Deserialize, Serialize};
+
+use crate::bytes::DxfEncoding;
+use crate::types::DxfVersion;
+
+/// 出力図面の単位
+///
+/// JWWの座標は常にミリメートルで記録されている。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Units {
+    /// 単位なし (倍率1で出力)
+    Unitless,
+    /// インチ
+    Inches,
+    /// フィート
+    Feet,
+    /// ミリメートル
+    #[default]
+    Millimeters,
+    /// センチメートル
+    Centimeters,
+    /// メートル
+    Meters,
+}
+
+impl Units {
+    /// $INSUNITS の値を返す
+    pub fn insunits(self) -> i32 {
+        match self {
+            Units::Unitless => 0,
+            Units::Inches => 1,
+            Units::Feet => 2,
+            Units::Millimeters => 4,
+            Units::Centimeters => 5,
+            Units::Meters => 6,
+        }
+    }
+
+    /// $INSUNITS の値から単位を返す (対応していない単位の場合は `None`)
+    pub fn from_insunits(insunits: i32) -> Option<Units> {
+        match insunits {
+            0 => Some(Units::Unitless),
+            1 => Some(Units::Inches),
+            2 => Some(Units::Feet),
+            4 => Some(Units::Millimeters),
+            5 => Some(Units::Centimeters),
+            6 => Some(Units::Meters),
+            _ => None,
+        }
+    }
+
+    /// $MEASUREMENT の値を返す (0=ヤード・ポンド法, 1=メートル法)
+    pub fn measurement(self) -> i32 {
+        match self {
+            Units::Inches | Units::Feet => 0,
+            _ => 1,
+        }
+    }
+
+    /// 1単位あたりのミリメートル数を返す
+    pub fn millimeters(self) -> f64 {
+        match self {
+            Units::Unitless => 1.0,
+            Units::Inches => 25.4,
+            Units::Feet => 304.8,
+            Units::Millimeters => 1.0,
+            Units::Centimeters => 10.0,
+            Units::Meters => 1000.0,
+        }
+    }
+}
+
+/// JWW -> DXF 変換オプション
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConvertOptions {
+    /// 出力単位 ($INSUNITS に反映される)
+    pub units: Units,
+    /// 任意の倍率 (指定時は単位換算の倍率の代わりに使用する)
+    pub scale_factor: Option<f64>,
+    /// 用紙サイズに合わせたペーパー空間レイアウトを出力する
+    ///
+    /// レイアウトはR2000の *Paper_Space ブロックとLAYOUTオブジェクトで表すため、
+    /// 有効にするとR2000形式で出力する。
+    pub paper_space: bool,
+    /// 楕円・楕円弧・傾きのある円弧をポリラインで近似する際の弦の許容誤差 (JWW座標のmm)
+    ///
+    /// ELLIPSEに対応していないビューアやレーザー加工機向け。`None` の場合はELLIPSE・ARCで出力する。
+    pub curve_tolerance: Option<f64>,
+    /// 出力するエンティティの絞り込み
+    pub filter: EntityFilter,
+    /// エンティティをレイヤ順 (レイヤグループ, レイヤ) に並べ替える
+    ///
+    /// 同じレイヤ内では元のJWWの順序を保つ。`false` の場合はJWWの順序のまま出力する。
+    pub sort_by_layer: bool,
+    /// 長さ0の線・半径0の円弧・面積0の塗りつぶしを出力しない
+    ///
+    /// CAMの後処理でエラーになるため。除外したエンティティは変換レポートに記録する。
+    pub drop_degenerate: bool,
+    /// 重複した図形を出力しない場合の座標の許容差 (JWW座標のmm、0で完全に一致する図形のみ)
+    ///
+    /// レーザー加工で重なった線を二重に切断しないため。レイヤー・色・線種が同じ
+    /// 線・円・円弧・楕円・点・ポリラインが対象で、除外したエンティティは変換レポートに記録する。
+    /// `None` の場合は重複を除去しない。
+    pub dedup_tolerance: Option<f64>,
+    /// 元のJWW属性 (線種・線色・線幅・レイヤ・フラグ・曲線属性) をXDATAとして付加する
+    pub xdata: bool,
+    /// 曲線属性 (`EntityBase::group`) を共有するエンティティをGROUPオブジェクトにまとめる
+    pub groups: bool,
+    /// マーカー付きの点を、マーカーの形のブロック (`JWW_MARKER_<コード>`) のINSERTで出力する
+    ///
+    /// $PDMODE を無視するビューアでもマーカーの形を保つ。大きさは用紙上の2mmに
+    /// 点のスケールとレイヤグループの縮尺を掛けた値。
+    pub point_marker_blocks: bool,
+    /// ブロック定義の構成要素を、範囲の左下が基準点 (原点) になるように平行移動する
+    ///
+    /// JWWのブロック定義は作図時の基準点を原点とするため、基準点から離れた図形を持つことがある。
+    /// ブロック挿入の挿入点も同じだけ移すため、挿入された図形の位置は変わらない。
+    pub rebase_blocks: bool,
+    /// 曲線属性でまとめられた線・矢印・文字を引出線 (LEADER) として出力する
+    ///
+    /// 引出線と文字は `JWW_LEADER_<曲線属性番号>` のGROUPにまとめる。
+    pub leaders: bool,
+    /// 変換元情報を999コメントとヘッダー変数 ($TDCREATE/$TDUPDATE/$PROJECTNAME) に記録する
+    pub provenance: Option<Provenance>,
+    /// 寸法の表示設定
+    pub dimension_style: DimensionStyle,
+    /// 範囲外 (16以上) のレイヤ番号・レイヤグループ番号を持つエンティティの出力先レイヤー名
+    ///
+    /// 壊れたファイルで見られる。未指定の場合は [`DEFAULT_FALLBACK_LAYER`]。
+    pub fallback_layer: Option<String>,
+    /// 出力するDXFのバージョン ($ACADVER)
+    ///
+    /// `None` の場合はR12とし、R12にない引出線・グループ・楕円・ペーパー空間を含む場合はR2000にする。
+    /// R12を指定した場合は引出線・グループ・ペーパー空間を出力せず、楕円をポリラインで近似する
+    /// (`curve_tolerance` が未指定の場合は [`DEFAULT_CURVE_TOLERANCE`])。
+    pub version: Option<DxfVersion>,
+    /// テキストDXFの文字コード
+    ///
+    /// 変換結果の [`crate::Document`] には影響せず、文字列を出力する側
+    /// (`to_bytes(&doc, options.encoding.into())` など) が参照する。
+    pub encoding: DxfEncoding,
+}
+
+/// R12で楕円をポリラインで近似する際の既定の弦の許容誤差 (JWW座標のmm)
+pub const DEFAULT_CURVE_TOLERANCE: f64 = 0.1;
+
+/// 範囲外のレイヤ番号を持つエンティティの既定の出力先レイヤー名
+pub const DEFAULT_FALLBACK_LAYER: &str = "JWW_INVALID";
+
+/// 寸法の表示設定 (用紙上のmm)
+///
+/// 未指定 (`None`) の項目は図面の寸法から読み取った値 ([`jww_core::Document::dimension_settings`])
+/// を使用し、読み取れない場合はJw_cadの寸法設定の初期値とする。矢印の大きさはJWWファイルに
+/// 記録されないため、常に初期値を使用する。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DimensionStyle {
+    /// 矢印の大きさ (既定値 3.0)
+    pub arrow_size: Option<f64>,
+    /// 補助線の起点からの離れ (既定値 1.0)
+    pub extension_offset: Option<f64>,
+    /// 補助線の寸法線からのはみ出し (既定値 2.0)
+    pub extension_extension: Option<f64>,
+    /// 寸法値と寸法線の間隔 (既定値 1.0)
+    pub text_gap: Option<f64>,
+    /// 寸法値の文字高さ
+    pub text_height: Option<f64>,
+}
+
+/// 出力ファイルに記録する変換元情報
+///
+/// 変換プログラム名とバージョンは常に記録される。JWWのファイルメモは
+/// 1行目を $PROJECTNAME として記録する。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Provenance {
+    /// 変換元のファイル名
+    pub source_name: Option<String>,
+    /// 変換日時 (UNIX時刻の秒)
+    ///
+    /// `None` の場合は変換時の現在時刻を使用する。システム時刻を取得できない
+    /// 環境 (wasm32-unknown-unknown など) では明示的に指定する。
+    pub timestamp: Option<u64>,
+    /// 変換元ファイルの更新日時 (UNIX時刻の秒)
+    ///
+    /// JWWファイルは作成日時を持たないため、指定時は $TDCREATE に使用する。
+    /// `None` の場合は変換日時を使用する。
+    pub source_modified: Option<u64>,
+}
+
+/// エンティティの絞り込み条件
+///
+/// ブロック定義内のエンティティにも同じ条件を適用する。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EntityFilter {
+    /// 出力する種別 (空の場合は全種別)
+    pub include: Vec<EntityKind>,
+    /// 出力しない種別 (`include` より優先)
+    pub exclude: Vec<EntityKind>,
+    /// 出力するレイヤ (レイヤグループ, レイヤ) の組 (空の場合は全レイヤ)
+    pub layers: Vec<(u16, u16)>,
+    /// 仮点も出力する
+    pub temporary_points: bool,
+    /// いずれかのビットが立っている属性フラグ (`EntityBase::flag`) を持つエンティティを除外する
+    ///
+    /// [`FLAG_HIDDEN`]・[`FLAG_AUXILIARY`] を `|` で組み合わせて指定する。
+    pub exclude_flags: u16,
+}
+
+/// 属性フラグ (`EntityBase::flag`) の補助図形 (印刷しない図形) のビット
+pub const FLAG_AUXILIARY: u16 = 0x0010;
+
+/// 属性フラグ (`EntityBase::flag`) の非表示のビット
+pub const FLAG_HIDDEN: u16 = 0x0020;
+
+impl EntityFilter {
+    /// エンティティが出力対象かどうかを返す
+    pub fn accepts(&self, entity: &JwwEntity) -> bool {
+        let kind = entity.kind();
+        if !self.include.is_empty() && !self.include.contains(&kind) {
+            return false;
+        }
+        if self.exclude.contains(&kind) {
+            return false;
+        }
+        let base = entity.base();
+        if !self.layers.is_empty() && !self.layers.contains(&(base.layer_group, base.layer)) {
+            return false;
+        }
+        if base.flag & self.exclude_flags != 0 {
+            return false;
+        }
+        if let JwwEntity::Point(point) = entity {
+            if point.is_temporary && !self.temporary_points {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl ConvertOptions {
+    /// 座標に掛ける倍率を返す
+    pub fn scale(&self) -> f64 {
+        self.scale_factor.unwrap_or(1.0 / self.units.millimeters())
+    }
+
+    /// 範囲外のレイヤ番号を持つエンティティの出力先レイヤー名を返す
+    pub fn fallback_layer(&self) -> &str {
+        self.fallback_layer.as_deref().unwrap_or(DEFAULT_FALLBACK_LAYER)
+    }
+
+    /// `version` で出力できない項目を無効にしたオプションを返す
+    ///
+    /// R12では引出線・グループ・ペーパー空間を無効にし、楕円をポリラインで近似する。
+    pub(crate) fn for_version(&self) -> std::borrow::Cow<'_, ConvertOptions> {
+        if self.version != Some(DxfVersion::R12) {
+            return std::borrow::Cow::Borrowed(self);
+        }
+        std::borrow::Cow::Owned(ConvertOptions {
+            paper_space: false,
+            groups: false,
+            leaders: false,
+            curve_tolerance: Some(self.curve_tolerance.unwrap_or(DEFAULT_CURVE_TOLERANCE)),
+            ..self.clone()
+        })
+    }
+}
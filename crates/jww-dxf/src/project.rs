@@ -0,0 +1,331 @@
+//! 複数のJWWファイルからなる図面セットの変換
+//!
+//! 図面セットをまとめて1つのDXFに出力するため、ファイルごとにレイヤ名へ
+//! プレフィックスを付け、ブロック定義・寸法スタイルの名前の衝突を解決する。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use jww_core::Document as JwwDocument;
+
+use crate::converter::convert_document_with_options;
+use crate::options::ConvertOptions;
+use crate::types::*;
+
+/// プレフィックスと元の名前の区切り文字
+pub const PREFIX_SEPARATOR: &str = "$";
+
+/// 図面セットの読み込みエラー
+#[derive(Debug, thiserror::Error)]
+pub enum ProjectError {
+    #[error("{}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("{}: {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        source: jww_core::ParseError,
+    },
+}
+
+/// 図面セットの1ファイル
+#[derive(Debug, Clone)]
+pub struct ProjectFile {
+    /// レイヤ名などに付けるプレフィックス (`{prefix}${name}`)
+    pub prefix: String,
+    pub document: JwwDocument<'static>,
+}
+
+/// 複数のJWWファイルからなる図面セット
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let project = jww_dxf::Project::load(["1F.jww", "2F.jww"])?;
+/// std::fs::write("set.dxf", jww_dxf::to_string(&project.to_dxf()))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Project {
+    /// 出力順のファイル
+    pub files: Vec<ProjectFile>,
+}
+
+impl Project {
+    /// 空の図面セットを作成する
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ファイルを読み込んで図面セットを作成する (プレフィックスはファイル名の拡張子を除いた部分)
+    pub fn load<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Result<Self, ProjectError> {
+        let mut project = Self::new();
+        for path in paths {
+            let path = path.as_ref();
+            let data = std::fs::read(path).map_err(|source| ProjectError::Io {
+                path: path.to_path_buf(),
+                source,
+            })?;
+            let document = jww_core::parse(&data)
+                .map(JwwDocument::into_owned)
+                .map_err(|source| ProjectError::Parse {
+                    path: path.to_path_buf(),
+                    source,
+                })?;
+            let prefix = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            project.add(prefix, document);
+        }
+        Ok(project)
+    }
+
+    /// ファイルを追加する
+    ///
+    /// DXFの名前に使えない文字 (`<>/\":;?*|,=` と `` ` ``) は `_` に置き換え、
+    /// 追加済みのファイルと同じプレフィックスには連番を付ける。
+    pub fn add(&mut self, prefix: impl Into<String>, document: JwwDocument<'static>) {
+        let prefix: String = prefix
+            .into()
+            .chars()
+            .map(|c| {
+                if "<>/\\\":;?*|,=`".contains(c) {
+                    '_'
+                } else {
+                    c
+                }
+            })
+            .collect();
+        let used = |name: &str| self.files.iter().any(|f| f.prefix == name);
+        let mut unique = prefix.clone();
+        let mut n = 2;
+        while used(&unique) {
+            unique = format!("{}_{}", prefix, n);
+            n += 1;
+        }
+        self.files.push(ProjectFile {
+            prefix: unique,
+            document,
+        });
+    }
+
+    /// 1つのDXFドキュメントに変換する
+    pub fn to_dxf(&self) -> Document {
+        self.to_dxf_with_options(&ConvertOptions::default())
+    }
+
+    /// オプションを指定して1つのDXFドキュメントに変換する
+    ///
+    /// - レイヤ名・グループ名にはファイルのプレフィックスを付ける。
+    /// - 同じ名前で内容 (レイヤ名を含む) も同じブロック定義・寸法スタイルは1つにまとめ、
+    ///   最初のファイルの定義を使う。内容が異なる場合は後のファイルの名前にプレフィックスを付ける。
+    /// - ヘッダー・レイアウト・コメントは最初のファイルのものを使う。初期表示ビューは
+    ///   ファイルが1つの場合のみ出力する。
+    pub fn to_dxf_with_options(&self, options: &ConvertOptions) -> Document {
+        let mut merger = Merger::default();
+        let mut documents = self
+            .files
+            .iter()
+            .map(|file| (file, convert_document_with_options(&file.document, options)));
+
+        let Some((first, document)) = documents.next() else {
+            return Document {
+                header: Header::default(),
+                active_view: None,
+                layers: Vec::new(),
+                entities: Vec::new(),
+                blocks: Vec::new(),
+                layout: None,
+                groups: Vec::new(),
+                comments: Vec::new(),
+                dim_styles: Vec::new(),
+            };
+        };
+        let mut result = Document {
+            header: Header {
+                project_name: None,
+                ..document.header.clone()
+            },
+            active_view: if self.files.len() == 1 {
+                document.active_view.clone()
+            } else {
+                None
+            },
+            layers: Vec::new(),
+            entities: Vec::new(),
+            blocks: Vec::new(),
+            layout: document.layout.clone(),
+            groups: Vec::new(),
+            comments: document.comments.clone(),
+            dim_styles: Vec::new(),
+        };
+        merger.merge(&mut result, &first.prefix, document);
+        for (file, document) in documents {
+            merger.merge(&mut result, &file.prefix, document);
+        }
+        result
+    }
+}
+
+/// 名前の衝突を解決しながらドキュメントをまとめる
+#[derive(Default)]
+struct Merger {
+    /// 元のブロック名ごとの (出力済みの定義 (レイヤ名の変換前), 出力した名前)
+    blocks: HashMap<String, Vec<(Block, String)>>,
+    /// 元の寸法スタイル名ごとの (出力済みのスタイル, 出力した名前)
+    dim_styles: HashMap<String, Vec<(DimStyle, String)>>,
+}
+
+impl Merger {
+    fn merge(&mut self, result: &mut Document, prefix: &str, document: Document) {
+        let layer = |name: &str| prefixed(prefix, name);
+
+        let mut style_names = HashMap::new();
+        for style in document.dim_styles {
+            let name = self.dim_style_name(result, prefix, &style);
+            if !result.dim_styles.iter().any(|s| s.name == name) {
+                result.dim_styles.push(DimStyle {
+                    name: name.clone(),
+                    ..style.clone()
+                });
+            }
+            style_names.insert(style.name, name);
+        }
+
+        // ブロックは参照先が先に並んでいるため、先頭から名前を決めれば入れ子の参照も解決できる
+        let mut block_names = HashMap::new();
+        for mut block in document.blocks {
+            for entity in &mut block.entities {
+                rename(
+                    entity,
+                    &|name: &str| name.to_string(),
+                    &block_names,
+                    &style_names,
+                );
+            }
+            let original = block.name.clone();
+            let name = self.block_name(result, prefix, &block);
+            if !result.blocks.iter().any(|b| b.name == name) {
+                for entity in &mut block.entities {
+                    rename(entity, &layer, &HashMap::new(), &HashMap::new());
+                }
+                block.name = name.clone();
+                result.blocks.push(block);
+            }
+            block_names.insert(original, name);
+        }
+
+        result
+            .layers
+            .extend(document.layers.into_iter().map(|l| Layer {
+                name: layer(&l.name),
+                ..l
+            }));
+        let offset = result.entities.len();
+        for mut entity in document.entities {
+            rename(&mut entity, &layer, &block_names, &style_names);
+            result.entities.push(entity);
+        }
+        result
+            .groups
+            .extend(document.groups.into_iter().map(|g| Group {
+                name: prefixed(prefix, &g.name),
+                entities: g.entities.iter().map(|i| i + offset).collect(),
+                ..g
+            }));
+    }
+
+    /// ブロック定義の出力名を決める (同じ内容の定義が出力済みならその名前)
+    fn block_name(&mut self, result: &Document, prefix: &str, block: &Block) -> String {
+        let variants = self.blocks.entry(block.name.clone()).or_default();
+        if let Some((_, name)) = variants.iter().find(|(b, _)| b == block) {
+            return name.clone();
+        }
+        let name = unique_name(&block.name, prefix, |name| {
+            result.blocks.iter().any(|b| b.name == name)
+        });
+        variants.push((block.clone(), name.clone()));
+        name
+    }
+
+    /// 寸法スタイルの出力名を決める (同じ設定のスタイルが出力済みならその名前)
+    fn dim_style_name(&mut self, result: &Document, prefix: &str, style: &DimStyle) -> String {
+        let variants = self.dim_styles.entry(style.name.clone()).or_default();
+        if let Some((_, name)) = variants.iter().find(|(s, _)| s == style) {
+            return name.clone();
+        }
+        let name = unique_name(&style.name, prefix, |name| {
+            result.dim_styles.iter().any(|s| s.name == name)
+        });
+        variants.push((style.clone(), name.clone()));
+        name
+    }
+}
+
+/// プレフィックスを付けた名前 (DXFの既定のレイヤ `0` はそのまま)
+fn prefixed(prefix: &str, name: &str) -> String {
+    if name == "0" {
+        return name.to_string();
+    }
+    format!("{}{}{}", prefix, PREFIX_SEPARATOR, name)
+}
+
+/// 使われていなければ元の名前、使われていればプレフィックス (と連番) を付けた名前
+fn unique_name(name: &str, prefix: &str, used: impl Fn(&str) -> bool) -> String {
+    if !used(name) {
+        return name.to_string();
+    }
+    let base = prefixed(prefix, name);
+    let mut candidate = base.clone();
+    let mut n = 2;
+    while used(&candidate) {
+        candidate = format!("{}{}", base, n);
+        n += 1;
+    }
+    candidate
+}
+
+/// エンティティのレイヤ名・ブロック名・寸法スタイル名を置き換える
+fn rename(
+    entity: &mut Entity,
+    layer: &dyn Fn(&str) -> String,
+    blocks: &HashMap<String, String>,
+    dim_styles: &HashMap<String, String>,
+) {
+    let name = match entity {
+        Entity::Line(e) => &mut e.layer,
+        Entity::Circle(e) => &mut e.layer,
+        Entity::Arc(e) => &mut e.layer,
+        Entity::Ellipse(e) => &mut e.layer,
+        Entity::Point(e) => &mut e.layer,
+        Entity::Text(e) => &mut e.layer,
+        Entity::Solid(e) => &mut e.layer,
+        Entity::Insert(e) => {
+            if let Some(name) = blocks.get(&e.block_name) {
+                e.block_name = name.clone();
+            }
+            &mut e.layer
+        }
+        Entity::Polyline(e) => &mut e.layer,
+        Entity::Leader(e) => {
+            if let Some(name) = dim_styles.get(&e.style) {
+                e.style = name.clone();
+            }
+            &mut e.layer
+        }
+        Entity::Dimension(e) => {
+            if let Some(name) = dim_styles.get(&e.style) {
+                e.style = name.clone();
+            }
+            for entity in &mut e.geometry {
+                rename(entity, layer, blocks, dim_styles);
+            }
+            &mut e.layer
+        }
+    };
+    *name = layer(name);
+}
@@ -0,0 +1,788 @@
+//! DXF読み込み
+//!
+//! ASCII形式のDXFを解析し、変換結果と同じ [`Document`] モデルを返す。
+//! HEADER・TABLES (VPORT/LAYER/DIMSTYLE)・BLOCKS・ENTITIES・OBJECTS (GROUP) セクションに対応する。
+
+use std::collections::HashMap;
+
+use crate::types::*;
+
+/// DXF読み込み時のエラー型
+#[derive(Debug, thiserror::Error)]
+pub enum ReadError {
+    /// グループコードが整数でない
+    #[error("line {line}: invalid group code {value:?}")]
+    InvalidGroupCode { line: usize, value: String },
+
+    /// グループコードに対応する値がない
+    #[error("line {line}: missing value for group code {code}")]
+    MissingValue { line: usize, code: i32 },
+
+    /// 値をグループコードの型として解釈できない
+    #[error("line {line}: invalid value {value:?} for group code {code}")]
+    InvalidValue { line: usize, code: i32, value: String },
+}
+
+/// DXF読み込み結果の型エイリアス
+pub type Result<T> = std::result::Result<T, ReadError>;
+
+/// DXF文字列を解析する
+///
+/// 対応していないエンティティとペーパー空間のエンティティ (ビューポートを除く) は読み飛ばす。
+/// 必須レイヤー "0" は `Document::layers` に含めない。
+pub fn parse(input: &str) -> Result<Document> {
+    let (pairs, comments) = tokenize(input)?;
+    let mut reader = Reader { pairs, pos: 0 };
+    let mut state = State::default();
+    let mut doc = Document {
+        header: Header::default(),
+        active_view: None,
+        layers: Vec::new(),
+        entities: Vec::new(),
+        blocks: Vec::new(),
+        layout: None,
+        groups: Vec::new(),
+        comments,
+        dim_styles: Vec::new(),
+    };
+
+    while let Some(pair) = reader.next() {
+        match (pair.code, pair.str()) {
+            (0, "SECTION") => {
+                let name = match reader.peek() {
+                    Some(p) if p.code == 2 => reader.next().map(|p| p.str()).unwrap_or_default(),
+                    _ => "",
+                };
+                match name {
+                    "HEADER" => read_header(&mut reader, &mut doc.header, &mut state)?,
+                    "TABLES" => read_tables(&mut reader, &mut doc)?,
+                    "BLOCKS" => read_blocks(&mut reader, &mut doc, &mut state)?,
+                    "ENTITIES" => {
+                        for (entity, handle) in read_entities(&mut reader, "ENDSEC", &mut state)? {
+                            if let Some(handle) = handle {
+                                state.entity_handles.insert(handle, doc.entities.len());
+                            }
+                            doc.entities.push(entity);
+                        }
+                    }
+                    "OBJECTS" => read_objects(&mut reader, &mut state)?,
+                    _ => skip_section(&mut reader),
+                }
+            }
+            (0, "EOF") => break,
+            _ => {}
+        }
+    }
+
+    doc.layout = state.layout();
+    doc.groups = state.groups();
+    Ok(doc)
+}
+
+/// グループコードと値の組
+#[derive(Debug, Clone, Copy)]
+struct Pair<'a> {
+    code: i32,
+    value: &'a str,
+    line: usize,
+}
+
+impl<'a> Pair<'a> {
+    /// 前後の空白を除いた値を返す
+    fn str(&self) -> &'a str {
+        self.value.trim()
+    }
+
+    fn f64(&self) -> Result<f64> {
+        self.str().parse().map_err(|_| self.invalid())
+    }
+
+    fn i32(&self) -> Result<i32> {
+        self.str().parse().map_err(|_| self.invalid())
+    }
+
+    /// 16進数のハンドル値を返す
+    fn handle(&self) -> Result<u64> {
+        u64::from_str_radix(self.str(), 16).map_err(|_| self.invalid())
+    }
+
+    fn invalid(&self) -> ReadError {
+        ReadError::InvalidValue {
+            line: self.line,
+            code: self.code,
+            value: self.value.to_string(),
+        }
+    }
+}
+
+/// 入力をグループコードと値の組に分割する
+///
+/// 999コメントは組に含めずに別途返す。
+fn tokenize(input: &str) -> Result<(Vec<Pair<'_>>, Vec<String>)> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let mut lines = input.lines().enumerate().map(|(i, l)| (i + 1, l.trim_end_matches('\r')));
+    let mut pairs = Vec::new();
+    let mut comments = Vec::new();
+
+    while let Some((line, code)) = lines.next() {
+        let code_str = code.trim();
+        if code_str.is_empty() {
+            continue;
+        }
+        let code: i32 = code_str.parse().map_err(|_| ReadError::InvalidGroupCode {
+            line,
+            value: code_str.to_string(),
+        })?;
+        let (_, value) = lines.next().ok_or(ReadError::MissingValue { line, code })?;
+        if code == 999 {
+            comments.push(value.to_string());
+        } else {
+            pairs.push(Pair {
+                code,
+                value,
+                line: line + 1,
+            });
+        }
+    }
+
+    Ok((pairs, comments))
+}
+
+/// 組の読み取り位置
+struct Reader<'a> {
+    pairs: Vec<Pair<'a>>,
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn next(&mut self) -> Option<Pair<'a>> {
+        let pair = self.pairs.get(self.pos).copied();
+        self.pos += 1;
+        pair
+    }
+
+    fn peek(&self) -> Option<Pair<'a>> {
+        self.pairs.get(self.pos).copied()
+    }
+
+    /// 次のグループコード0までの組を読み取る
+    fn record(&mut self) -> Vec<Pair<'a>> {
+        let start = self.pos.min(self.pairs.len());
+        while let Some(pair) = self.peek() {
+            if pair.code == 0 {
+                break;
+            }
+            self.pos += 1;
+        }
+        self.pairs[start..self.pos.min(self.pairs.len())].to_vec()
+    }
+
+    /// 次が指定した種別のレコードかどうかを返す
+    fn at(&self, name: &str) -> bool {
+        matches!(self.peek(), Some(p) if p.code == 0 && p.str() == name)
+    }
+}
+
+/// セクションをまたいで参照される情報
+#[derive(Default)]
+struct State {
+    /// ペーパー空間の用紙範囲 ($PLIMMAX)
+    paper_limits: Option<(f64, f64)>,
+    /// ビューポートID 1 (用紙全体) と 2 (モデル空間表示)
+    viewports: HashMap<i32, Viewport>,
+    /// モデル空間エンティティのハンドル -> インデックス
+    entity_handles: HashMap<u64, usize>,
+    /// 辞書エントリのハンドル -> 名前
+    dictionary_names: HashMap<u64, String>,
+    /// GROUPオブジェクト (ハンドル, 説明, エンティティハンドル)
+    groups: Vec<(Option<u64>, String, Vec<u64>)>,
+    /// 無名ブロック (寸法図形) の名前 -> エンティティ
+    anonymous_blocks: HashMap<String, Vec<Entity>>,
+}
+
+impl State {
+    fn layout(&self) -> Option<Layout> {
+        let viewport = self.viewports.get(&2)?.clone();
+        let (paper_width, paper_height) = self
+            .paper_limits
+            .or_else(|| self.viewports.get(&1).map(|vp| (vp.width, vp.height)))?;
+        Some(Layout {
+            paper_width,
+            paper_height,
+            viewport,
+        })
+    }
+
+    fn groups(&self) -> Vec<Group> {
+        self.groups
+            .iter()
+            .map(|(handle, description, members)| Group {
+                name: handle
+                    .and_then(|h| self.dictionary_names.get(&h).cloned())
+                    .or_else(|| handle.map(|h| format!("{:X}", h)))
+                    .unwrap_or_default(),
+                description: description.clone(),
+                entities: members
+                    .iter()
+                    .filter_map(|h| self.entity_handles.get(h).copied())
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+/// セクションの終わりまで読み飛ばす
+fn skip_section(reader: &mut Reader) {
+    while let Some(pair) = reader.next() {
+        if pair.code == 0 && pair.str() == "ENDSEC" {
+            break;
+        }
+    }
+}
+
+/// HEADERセクションを読み取る
+fn read_header(reader: &mut Reader, header: &mut Header, state: &mut State) -> Result<()> {
+    while let Some(pair) = reader.next() {
+        if pair.code == 0 {
+            if pair.str() == "ENDSEC" {
+                break;
+            }
+            continue;
+        }
+        if pair.code != 9 {
+            continue;
+        }
+
+        let mut values = Vec::new();
+        while let Some(value) = reader.peek() {
+            if value.code == 0 || value.code == 9 {
+                break;
+            }
+            values.push(value);
+            reader.pos += 1;
+        }
+        let first = |code: i32| values.iter().find(|v| v.code == code);
+
+        match pair.str() {
+            "$ACADVER" => {
+                if let Some(value) = first(1) {
+                    header.version = DxfVersion::from_acadver(value.str());
+                }
+            }
+            "$INSUNITS" => header.insunits = or_default(first(70), Pair::i32, header.insunits)?,
+            "$MEASUREMENT" => {
+                header.measurement = or_default(first(70), Pair::i32, header.measurement)?
+            }
+            "$LTSCALE" => header.ltscale = or_default(first(40), Pair::f64, header.ltscale)?,
+            "$PDMODE" => header.pdmode = or_default(first(70), Pair::i32, header.pdmode)?,
+            "$PDSIZE" => header.pdsize = or_default(first(40), Pair::f64, header.pdsize)?,
+            "$TDCREATE" => header.created = first(40).map(Pair::f64).transpose()?,
+            "$TDUPDATE" => header.updated = first(40).map(Pair::f64).transpose()?,
+            "$PROJECTNAME" => header.project_name = first(1).map(|v| v.str().to_string()),
+            "$LIMMIN" | "$LIMMAX" => {
+                if let (Some(x), Some(y)) = (first(10), first(20)) {
+                    let (x, y) = (x.f64()?, y.f64()?);
+                    let limits = header.limits.get_or_insert(Limits {
+                        min_x: 0.0,
+                        min_y: 0.0,
+                        max_x: 0.0,
+                        max_y: 0.0,
+                    });
+                    if pair.str() == "$LIMMIN" {
+                        (limits.min_x, limits.min_y) = (x, y);
+                    } else {
+                        (limits.max_x, limits.max_y) = (x, y);
+                    }
+                }
+            }
+            "$PLIMMAX" => {
+                if let (Some(x), Some(y)) = (first(10), first(20)) {
+                    state.paper_limits = Some((x.f64()?, y.f64()?));
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// 値があれば変換し、なければ既定値を返す
+fn or_default<'a, T>(
+    pair: Option<&Pair<'a>>,
+    convert: fn(&Pair<'a>) -> Result<T>,
+    default: T,
+) -> Result<T> {
+    match pair {
+        Some(pair) => convert(pair),
+        None => Ok(default),
+    }
+}
+
+/// TABLESセクションを読み取る
+fn read_tables(reader: &mut Reader, doc: &mut Document) -> Result<()> {
+    while let Some(pair) = reader.next() {
+        if pair.code != 0 {
+            continue;
+        }
+        let record = reader.record();
+        match pair.str() {
+            "ENDSEC" => break,
+            "LAYER" => {
+                let layer = read_layer(&record)?;
+                if layer.name != "0" {
+                    doc.layers.push(layer);
+                }
+            }
+            "DIMSTYLE" => {
+                let fields = Fields(&record);
+                doc.dim_styles.push(DimStyle {
+                    name: fields.str(2).unwrap_or_default().to_string(),
+                    scale: fields.f64_or(40, 1.0)?,
+                    arrow_size: fields.f64_or(41, 0.18)?,
+                    extension_offset: fields.f64_or(42, 0.0625)?,
+                    extension_extension: fields.f64_or(44, 0.18)?,
+                    text_gap: fields.f64_or(147, 0.09)?,
+                    text_height: fields.f64_or(140, 0.18)?,
+                });
+            }
+            "VPORT" => {
+                let fields = Fields(&record);
+                if fields.str(2) == Some("*ACTIVE") {
+                    doc.active_view = Some(ActiveView {
+                        center_x: fields.f64(12)?,
+                        center_y: fields.f64(22)?,
+                        height: fields.f64(40)?,
+                        aspect_ratio: fields.f64(41)?,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// LAYERテーブルのエントリを読み取る
+fn read_layer(record: &[Pair]) -> Result<Layer> {
+    let fields = Fields(record);
+    let flags = fields.i32(70, 0)?;
+    Ok(Layer {
+        name: fields.str(2).unwrap_or_default().to_string(),
+        // 負の色番号は非表示レイヤーを表す
+        color: fields.i32(62, 7)?.abs(),
+        line_type: fields.str(6).unwrap_or("CONTINUOUS").to_string(),
+        frozen: flags & 1 != 0,
+        locked: flags & 4 != 0,
+    })
+}
+
+/// BLOCKSセクションを読み取る
+///
+/// 無名ブロックは `Document::blocks` に含めず、寸法図形として参照できるように `state` に保持する。
+fn read_blocks(reader: &mut Reader, doc: &mut Document, state: &mut State) -> Result<()> {
+    // ブロック内のエンティティはモデル空間のハンドルやビューポートとして扱わない
+    let mut block_state = State::default();
+    while let Some(pair) = reader.next() {
+        if pair.code != 0 {
+            continue;
+        }
+        match pair.str() {
+            "ENDSEC" => break,
+            "BLOCK" => {
+                let record = reader.record();
+                let fields = Fields(&record);
+                let name = fields.str(2).unwrap_or_default().to_string();
+                let base_x = fields.f64(10)?;
+                let base_y = fields.f64(20)?;
+                let entities = read_entities(reader, "ENDBLK", &mut block_state)?
+                    .into_iter()
+                    .map(|(entity, _)| entity)
+                    .collect();
+                if name.starts_with('*') {
+                    // *Model_Space などの特殊ブロックは図形を持たない
+                    let upper = name.to_ascii_uppercase();
+                    if !upper.starts_with("*MODEL_SPACE") && !upper.starts_with("*PAPER_SPACE") {
+                        block_state.anonymous_blocks.insert(name, entities);
+                    }
+                } else {
+                    doc.blocks.push(Block {
+                        name,
+                        base_x,
+                        base_y,
+                        entities,
+                    });
+                }
+            }
+            _ => {
+                reader.record();
+            }
+        }
+    }
+    state.anonymous_blocks = block_state.anonymous_blocks;
+    Ok(())
+}
+
+/// 終端レコード (`ENDSEC` または `ENDBLK`) までのエンティティを読み取る
+///
+/// モデル空間のエンティティとそのハンドルを返す。
+fn read_entities(
+    reader: &mut Reader,
+    terminator: &str,
+    state: &mut State,
+) -> Result<Vec<(Entity, Option<u64>)>> {
+    let mut entities = Vec::new();
+
+    while let Some(pair) = reader.next() {
+        if pair.code != 0 {
+            continue;
+        }
+        let kind = pair.str();
+        let record = reader.record();
+        if kind == terminator {
+            break;
+        }
+
+        let fields = Fields(&record);
+        let common = Common::read(&record)?;
+
+        let entity = match kind {
+            "LINE" => Entity::Line(Line {
+                layer: common.layer,
+                color: common.color,
+                line_type: common.line_type,
+                line_type_scale: common.line_type_scale,
+                xdata: common.xdata,
+                x1: fields.f64(10)?,
+                y1: fields.f64(20)?,
+                x2: fields.f64(11)?,
+                y2: fields.f64(21)?,
+            }),
+            "CIRCLE" => Entity::Circle(Circle {
+                layer: common.layer,
+                color: common.color,
+                line_type: common.line_type,
+                line_type_scale: common.line_type_scale,
+                xdata: common.xdata,
+                center_x: fields.f64(10)?,
+                center_y: fields.f64(20)?,
+                radius: fields.f64(40)?,
+            }),
+            "ARC" => Entity::Arc(Arc {
+                layer: common.layer,
+                color: common.color,
+                line_type: common.line_type,
+                line_type_scale: common.line_type_scale,
+                xdata: common.xdata,
+                center_x: fields.f64(10)?,
+                center_y: fields.f64(20)?,
+                radius: fields.f64(40)?,
+                start_angle: fields.f64(50)?,
+                end_angle: fields.f64(51)?,
+            }),
+            "ELLIPSE" => Entity::Ellipse(Ellipse {
+                layer: common.layer,
+                color: common.color,
+                line_type: common.line_type,
+                line_type_scale: common.line_type_scale,
+                xdata: common.xdata,
+                center_x: fields.f64(10)?,
+                center_y: fields.f64(20)?,
+                major_axis_x: fields.f64(11)?,
+                major_axis_y: fields.f64(21)?,
+                minor_ratio: fields.f64_or(40, 1.0)?,
+                start_param: fields.f64(41)?,
+                end_param: fields.f64_or(42, 2.0 * std::f64::consts::PI)?,
+            }),
+            "POINT" => Entity::Point(Point {
+                layer: common.layer,
+                color: common.color,
+                line_type: common.line_type,
+                line_type_scale: common.line_type_scale,
+                xdata: common.xdata,
+                x: fields.f64(10)?,
+                y: fields.f64(20)?,
+            }),
+            "TEXT" => Entity::Text(Text {
+                layer: common.layer,
+                color: common.color,
+                line_type: common.line_type,
+                line_type_scale: common.line_type_scale,
+                xdata: common.xdata,
+                x: fields.f64(10)?,
+                y: fields.f64(20)?,
+                height: fields.f64(40)?,
+                rotation: fields.f64(50)?,
+                content: fields.raw(1).unwrap_or_default().to_string(),
+                style: fields.str(7).unwrap_or("STANDARD").to_string(),
+            }),
+            "SOLID" => Entity::Solid(Solid {
+                layer: common.layer,
+                color: common.color,
+                line_type: common.line_type,
+                line_type_scale: common.line_type_scale,
+                xdata: common.xdata,
+                x1: fields.f64(10)?,
+                y1: fields.f64(20)?,
+                x2: fields.f64(11)?,
+                y2: fields.f64(21)?,
+                x3: fields.f64(12)?,
+                y3: fields.f64(22)?,
+                // 3点のSOLIDは4点目が3点目と同じ
+                x4: fields.f64_or(13, fields.f64(12)?)?,
+                y4: fields.f64_or(23, fields.f64(22)?)?,
+            }),
+            "INSERT" => Entity::Insert(Insert {
+                layer: common.layer,
+                color: common.color,
+                line_type: common.line_type,
+                line_type_scale: common.line_type_scale,
+                xdata: common.xdata,
+                block_name: fields.str(2).unwrap_or_default().to_string(),
+                x: fields.f64(10)?,
+                y: fields.f64(20)?,
+                scale_x: fields.f64_or(41, 1.0)?,
+                scale_y: fields.f64_or(42, 1.0)?,
+                rotation: fields.f64(50)?,
+            }),
+            "POLYLINE" => {
+                let mut vertices = Vec::new();
+                while reader.at("VERTEX") {
+                    reader.next();
+                    let vertex = reader.record();
+                    let vertex = Fields(&vertex);
+                    vertices.push(Vertex {
+                        x: vertex.f64(10)?,
+                        y: vertex.f64(20)?,
+                    });
+                }
+                if reader.at("SEQEND") {
+                    reader.next();
+                    reader.record();
+                }
+                Entity::Polyline(Polyline {
+                    layer: common.layer,
+                    color: common.color,
+                    line_type: common.line_type,
+                    line_type_scale: common.line_type_scale,
+                    xdata: common.xdata,
+                    vertices,
+                    closed: fields.i32(70, 0)? & 1 != 0,
+                })
+            }
+            "LWPOLYLINE" => {
+                let xs = record.iter().take_while(|p| p.code != 1001).filter(|p| p.code == 10);
+                let ys = record.iter().take_while(|p| p.code != 1001).filter(|p| p.code == 20);
+                let vertices = xs
+                    .zip(ys)
+                    .map(|(x, y)| Ok(Vertex { x: x.f64()?, y: y.f64()? }))
+                    .collect::<Result<Vec<_>>>()?;
+                Entity::Polyline(Polyline {
+                    layer: common.layer,
+                    color: common.color,
+                    line_type: common.line_type,
+                    line_type_scale: common.line_type_scale,
+                    xdata: common.xdata,
+                    vertices,
+                    closed: fields.i32(70, 0)? & 1 != 0,
+                })
+            }
+            "DIMENSION" => Entity::Dimension(Dimension {
+                layer: common.layer,
+                color: common.color,
+                line_type: common.line_type,
+                line_type_scale: common.line_type_scale,
+                xdata: common.xdata,
+                style: fields.str(3).unwrap_or("STANDARD").to_string(),
+                def_x: fields.f64(10)?,
+                def_y: fields.f64(20)?,
+                text_x: fields.f64(11)?,
+                text_y: fields.f64(21)?,
+                ext1_x: fields.f64(13)?,
+                ext1_y: fields.f64(23)?,
+                ext2_x: fields.f64(14)?,
+                ext2_y: fields.f64(24)?,
+                angle: fields.f64(50)?,
+                // 空文字列と "<>" は計測値をそのまま表示する
+                text_override: fields
+                    .str(1)
+                    .filter(|text| !text.is_empty() && *text != "<>")
+                    .map(str::to_string),
+                geometry: fields
+                    .str(2)
+                    .and_then(|name| state.anonymous_blocks.get(name))
+                    .cloned()
+                    .unwrap_or_default(),
+            }),
+            "LEADER" => {
+                let xs = record.iter().take_while(|p| p.code != 1001).filter(|p| p.code == 10);
+                let ys = record.iter().take_while(|p| p.code != 1001).filter(|p| p.code == 20);
+                let vertices = xs
+                    .zip(ys)
+                    .map(|(x, y)| Ok(Vertex { x: x.f64()?, y: y.f64()? }))
+                    .collect::<Result<Vec<_>>>()?;
+                Entity::Leader(Leader {
+                    layer: common.layer,
+                    color: common.color,
+                    line_type: common.line_type,
+                    line_type_scale: common.line_type_scale,
+                    xdata: common.xdata,
+                    style: fields.str(3).unwrap_or("STANDARD").to_string(),
+                    arrowhead: fields.i32(71, 1)? != 0,
+                    vertices,
+                })
+            }
+            "VIEWPORT" => {
+                let id = fields.i32(69, 0)?;
+                state.viewports.insert(
+                    id,
+                    Viewport {
+                        center_x: fields.f64(10)?,
+                        center_y: fields.f64(20)?,
+                        width: fields.f64(40)?,
+                        height: fields.f64(41)?,
+                        view_center_x: fields.f64(12)?,
+                        view_center_y: fields.f64(22)?,
+                        view_height: fields.f64(45)?,
+                    },
+                );
+                continue;
+            }
+            _ => continue,
+        };
+
+        if !common.paper_space {
+            entities.push((entity, common.handle));
+        }
+    }
+
+    Ok(entities)
+}
+
+/// OBJECTSセクションを読み取る
+fn read_objects(reader: &mut Reader, state: &mut State) -> Result<()> {
+    while let Some(pair) = reader.next() {
+        if pair.code != 0 {
+            continue;
+        }
+        let record = reader.record();
+        match pair.str() {
+            "ENDSEC" => break,
+            "DICTIONARY" => {
+                let mut name = None;
+                for pair in &record {
+                    match pair.code {
+                        3 => name = Some(pair.str().to_string()),
+                        350 | 360 => {
+                            if let Some(name) = name.take() {
+                                state.dictionary_names.insert(pair.handle()?, name);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            "GROUP" => {
+                let fields = Fields(&record);
+                let handle = fields.get(5).map(|p| p.handle()).transpose()?;
+                let members = record
+                    .iter()
+                    .filter(|p| p.code == 340)
+                    .map(|p| p.handle())
+                    .collect::<Result<Vec<_>>>()?;
+                let description = fields.str(300).unwrap_or_default().to_string();
+                state.groups.push((handle, description, members));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// レコード内のグループコードの検索 (XDATAより前の最初の値)
+struct Fields<'r, 'a>(&'r [Pair<'a>]);
+
+impl<'a> Fields<'_, 'a> {
+    fn get(&self, code: i32) -> Option<&Pair<'a>> {
+        self.0
+            .iter()
+            .take_while(|p| p.code != 1001)
+            .find(|p| p.code == code)
+    }
+
+    fn str(&self, code: i32) -> Option<&'a str> {
+        self.get(code).map(|p| p.str())
+    }
+
+    /// 前後の空白を除かない値を返す (文字列内容用)
+    fn raw(&self, code: i32) -> Option<&'a str> {
+        self.get(code).map(|p| p.value)
+    }
+
+    fn f64(&self, code: i32) -> Result<f64> {
+        self.f64_or(code, 0.0)
+    }
+
+    fn f64_or(&self, code: i32, default: f64) -> Result<f64> {
+        or_default(self.get(code), Pair::f64, default)
+    }
+
+    fn i32(&self, code: i32, default: i32) -> Result<i32> {
+        or_default(self.get(code), Pair::i32, default)
+    }
+}
+
+/// エンティティ共通の属性
+struct Common {
+    layer: String,
+    color: i32,
+    line_type: String,
+    line_type_scale: Option<f64>,
+    xdata: Option<XData>,
+    handle: Option<u64>,
+    paper_space: bool,
+}
+
+impl Common {
+    fn read(record: &[Pair]) -> Result<Self> {
+        let fields = Fields(record);
+        Ok(Self {
+            layer: fields.str(8).unwrap_or("0").to_string(),
+            color: fields.i32(62, 256)?,
+            line_type: fields.str(6).unwrap_or("BYLAYER").to_string(),
+            line_type_scale: fields.get(48).map(|p| p.f64()).transpose()?,
+            xdata: read_xdata(record)?,
+            handle: fields.get(5).map(|p| p.handle()).transpose()?,
+            paper_space: fields.i32(67, 0)? == 1,
+        })
+    }
+}
+
+/// レコード末尾のXDATAを読み取る
+///
+/// 複数のアプリケーションのXDATAがある場合は最初のものを返す。
+fn read_xdata(record: &[Pair]) -> Result<Option<XData>> {
+    let Some(start) = record.iter().position(|p| p.code == 1001) else {
+        return Ok(None);
+    };
+
+    let mut values = Vec::new();
+    for pair in &record[start + 1..] {
+        match pair.code {
+            1001 => break,
+            1000 => values.push(XDataValue::String(pair.value.to_string())),
+            1070 => values.push(XDataValue::Integer(
+                pair.str().parse().map_err(|_| pair.invalid())?,
+            )),
+            1071 => values.push(XDataValue::Long(
+                pair.str().parse().map_err(|_| pair.invalid())?,
+            )),
+            1040 => values.push(XDataValue::Real(pair.f64()?)),
+            _ => {}
+        }
+    }
+
+    Ok(Some(XData {
+        app_name: record[start].str().to_string(),
+        values,
+    }))
+}
@@ -0,0 +1,101 @@
+//! 変換レポート
+//!
+//! 変換時に出力されなかったエンティティや、情報が欠落した変換を記録する。
+
+use std::collections::BTreeMap;
+
+use jww_core::EntityKind;
+use serde::{Deserialize, Serialize};
+
+/// 変換結果のレポート
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConversionReport {
+    /// 入力JWWエンティティの種別ごとの件数 (ブロック定義内を含む)
+    pub input_counts: BTreeMap<EntityKind, usize>,
+    /// 出力DXFエンティティの種別ごとの件数 (ブロック定義内を含む)
+    pub output_counts: BTreeMap<String, usize>,
+    /// 出力されなかったエンティティ
+    pub skipped: Vec<SkippedEntity>,
+    /// 情報が欠落した変換
+    pub lossy: Vec<LossyMapping>,
+}
+
+impl ConversionReport {
+    /// 手動での確認が必要な変換が含まれているかどうかを返す
+    pub fn needs_review(&self) -> bool {
+        !self.lossy.is_empty()
+    }
+
+    pub(crate) fn skip(&mut self, location: EntityLocation, kind: EntityKind, reason: SkipReason) {
+        self.skipped.push(SkippedEntity {
+            location,
+            kind,
+            reason,
+        });
+    }
+
+    pub(crate) fn lossy(&mut self, location: EntityLocation, kind: EntityKind, loss: Loss) {
+        self.lossy.push(LossyMapping {
+            location,
+            kind,
+            loss,
+        });
+    }
+}
+
+/// JWWドキュメント内のエンティティの位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntityLocation {
+    /// ブロック定義内の場合はブロック定義番号
+    pub block: Option<u32>,
+    /// エンティティリスト内のインデックス
+    pub index: usize,
+}
+
+/// 出力されなかったエンティティ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedEntity {
+    pub location: EntityLocation,
+    pub kind: EntityKind,
+    pub reason: SkipReason,
+}
+
+/// エンティティが出力されなかった理由
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SkipReason {
+    /// `EntityFilter` で除外された
+    Filtered,
+    /// ブロック定義の循環参照となるブロック挿入
+    CircularReference,
+    /// 長さ・半径・面積が0の図形 (`ConvertOptions::drop_degenerate`)
+    Degenerate,
+    /// 先に出力した図形と重複する (`ConvertOptions::dedup_tolerance`)
+    Duplicate,
+    /// 読み取りに対応していないクラス (`ParseOptions::preserve_unknown_classes`)
+    UnknownClass(String),
+}
+
+/// 情報が欠落した変換
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LossyMapping {
+    pub location: EntityLocation,
+    pub kind: EntityKind,
+    pub loss: Loss,
+}
+
+/// 欠落した情報の種類
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Loss {
+    /// 楕円をポリラインで近似した
+    EllipseApproximated,
+    /// 傾きのある円弧をポリラインで近似した
+    ArcApproximated,
+    /// 対応するACIのない線色 (346以上、既定の色 (ACI 7) で出力した)
+    UnknownColor(u16),
+    /// 対応する線種のない線種番号 (実線で出力した)
+    UnknownLineType(u8),
+    /// 参照先のブロック定義が存在しない
+    MissingBlockDefinition(u32),
+    /// 範囲外のレイヤ番号・レイヤグループ番号 (代替のレイヤーに出力した)
+    InvalidLayer { layer_group: u16, layer: u16 },
+}
@@ -4,10 +4,12 @@
 
 use std::collections::HashMap;
 
+use super::harness::ComparePolicy;
+
 /// DXFから抽出したエンティティ情報
 #[derive(Debug, Clone, PartialEq)]
 pub struct DxfEntity {
-    /// エンティティタイプ (LINE, CIRCLE, ARC, ELLIPSE, POINT, TEXT, SOLID, INSERT)
+    /// エンティティタイプ (LINE, CIRCLE, ARC, ELLIPSE, POINT, TEXT, SOLID, INSERT, POLYLINE, LWPOLYLINE)
     pub entity_type: DxfEntityType,
     /// レイヤー名
     pub layer: String,
@@ -17,6 +19,15 @@ pub struct DxfEntity {
     pub line_type: String,
     /// 座標と属性値 (group_code -> value)
     pub properties: HashMap<i32, String>,
+    /// ポリラインの頂点 (POLYLINEはVERTEX、LWPOLYLINEは繰り返しの10/20)
+    pub vertices: Vec<(f64, f64)>,
+}
+
+impl DxfEntity {
+    /// 数値のプロパティを返す
+    pub fn number(&self, group_code: i32) -> Option<f64> {
+        self.properties.get(&group_code)?.parse().ok()
+    }
 }
 
 /// DXFエンティティタイプ
@@ -30,6 +41,8 @@ pub enum DxfEntityType {
     Text,
     Solid,
     Insert,
+    Polyline,
+    LwPolyline,
     Unknown(String),
 }
 
@@ -44,6 +57,8 @@ impl DxfEntityType {
             "TEXT" => DxfEntityType::Text,
             "SOLID" => DxfEntityType::Solid,
             "INSERT" => DxfEntityType::Insert,
+            "POLYLINE" => DxfEntityType::Polyline,
+            "LWPOLYLINE" => DxfEntityType::LwPolyline,
             other => DxfEntityType::Unknown(other.to_string()),
         }
     }
@@ -58,157 +73,369 @@ impl DxfEntityType {
             DxfEntityType::Text => "TEXT",
             DxfEntityType::Solid => "SOLID",
             DxfEntityType::Insert => "INSERT",
+            DxfEntityType::Polyline => "POLYLINE",
+            DxfEntityType::LwPolyline => "LWPOLYLINE",
             DxfEntityType::Unknown(s) => s,
         }
     }
 }
 
-/// DXF文字列を解析してエンティティリストとレイヤーリストを返す
-pub fn parse_dxf_entities(dxf_content: &str) -> (Vec<DxfEntity>, Vec<String>) {
-    let mut entities = Vec::new();
-    let mut layers: Vec<String> = Vec::new();
-    let lines: Vec<&str> = dxf_content.lines().collect();
+/// BLOCKSセクションのブロック定義
+#[derive(Debug, Clone, PartialEq)]
+pub struct DxfBlock {
+    /// ブロック名
+    pub name: String,
+    /// 基点
+    pub base_point: (f64, f64),
+    /// ブロック内のエンティティ
+    pub entities: Vec<DxfEntity>,
+}
 
-    let mut i = 0;
-    let mut in_entities_section = false;
-    let mut in_tables_section = false;
-    let mut in_layer_table = false;
+/// DXFファイル全体の解析結果
+#[derive(Debug, Clone, Default)]
+pub struct DxfDrawing {
+    /// ENTITIESセクションのエンティティ (INSERTは展開しない)
+    pub entities: Vec<DxfEntity>,
+    /// LAYERテーブルのレイヤー名 (出現順)
+    pub layers: Vec<String>,
+    /// ブロック定義 (名前 -> 定義)
+    pub blocks: HashMap<String, DxfBlock>,
+}
 
-    while i < lines.len() {
-        let line = lines[i].trim();
-        if line.is_empty() {
-            i += 1;
-            continue;
+/// INSERTを展開する入れ子の上限 (循環参照対策)
+const MAX_INSERT_DEPTH: usize = 16;
+
+impl DxfDrawing {
+    /// INSERTをブロック内のエンティティに展開したエンティティリストを返す
+    ///
+    /// ブロック内のエンティティは基点からの相対位置を尺度・回転・挿入位置で変換する。
+    /// レイヤー"0"のエンティティはINSERTのレイヤー、色0 (BYBLOCK) はINSERTの色、
+    /// 線種BYBLOCKはINSERTの線種を引き継ぐ。定義のないブロックのINSERTはそのまま残す。
+    pub fn resolved_entities(&self) -> Vec<DxfEntity> {
+        let mut resolved = Vec::new();
+        for entity in &self.entities {
+            self.resolve_into(entity, &Placement::IDENTITY, None, 0, &mut resolved);
         }
+        resolved
+    }
 
-        // セクションの検出
-        if line == "SECTION" {
-            if i + 2 < lines.len() && lines[i + 1].trim() == "2" {
-                let section_name = lines[i + 2].trim();
-                match section_name {
-                    "ENTITIES" => in_entities_section = true,
-                    "TABLES" => in_tables_section = true,
-                    "BLOCKS" => in_entities_section = false,
-                    _ => {}
-                }
+    fn resolve_into(
+        &self,
+        entity: &DxfEntity,
+        placement: &Placement,
+        parent: Option<&DxfEntity>,
+        depth: usize,
+        resolved: &mut Vec<DxfEntity>,
+    ) {
+        let mut entity = placement.apply(entity);
+        if let Some(parent) = parent {
+            if entity.layer == "0" {
+                entity.layer = parent.layer.clone();
+            }
+            if entity.color == 0 {
+                entity.color = parent.color;
+            }
+            if entity.line_type.eq_ignore_ascii_case("BYBLOCK") {
+                entity.line_type = parent.line_type.clone();
             }
-            i += 3;
-            continue;
-        }
-
-        if line == "ENDSEC" {
-            in_entities_section = false;
-            in_tables_section = false;
-            in_layer_table = false;
-            i += 1;
-            continue;
         }
 
-        // LAYERテーブルの検出
-        if in_tables_section && line == "TABLE" {
-            if i + 2 < lines.len() && lines[i + 1].trim() == "2" {
-                let table_name = lines[i + 2].trim();
-                if table_name == "LAYER" {
-                    in_layer_table = true;
+        let block = match entity.properties.get(&2) {
+            Some(name) if entity.entity_type == DxfEntityType::Insert => self.blocks.get(name),
+            _ => None,
+        };
+        match block {
+            Some(block) if depth < MAX_INSERT_DEPTH => {
+                let inner = Placement::for_insert(&entity, block.base_point);
+                for child in &block.entities {
+                    self.resolve_into(child, &inner, Some(&entity), depth + 1, resolved);
                 }
             }
-            i += 3;
-            continue;
+            _ => resolved.push(entity),
         }
+    }
+}
 
-        if line == "ENDTAB" {
-            in_layer_table = false;
-            i += 1;
-            continue;
+/// ブロック座標から図面座標への変換 (基点を引き、尺度・回転をかけ、挿入位置を足す)
+#[derive(Debug, Clone, Copy)]
+struct Placement {
+    base: (f64, f64),
+    scale: (f64, f64),
+    /// 回転角 (度)
+    rotation: f64,
+    insert: (f64, f64),
+}
+
+impl Placement {
+    const IDENTITY: Placement = Placement {
+        base: (0.0, 0.0),
+        scale: (1.0, 1.0),
+        rotation: 0.0,
+        insert: (0.0, 0.0),
+    };
+
+    /// INSERTエンティティの挿入位置・尺度・回転角から変換を作る
+    fn for_insert(insert: &DxfEntity, base: (f64, f64)) -> Self {
+        Placement {
+            base,
+            scale: (
+                insert.number(41).unwrap_or(1.0),
+                insert.number(42).unwrap_or(1.0),
+            ),
+            rotation: insert.number(50).unwrap_or(0.0),
+            insert: (
+                insert.number(10).unwrap_or(0.0),
+                insert.number(20).unwrap_or(0.0),
+            ),
         }
+    }
 
-        // LAYERデータの収集（"  0"の次が"LAYER"の場合のみ）
-        if in_layer_table && line == "0" {
-            if i + 1 < lines.len() && lines[i + 1].trim() == "LAYER" {
-                if let Some(layer_name) = find_next_group_value(&lines, i + 1, "2") {
-                    layers.push(layer_name);
-                }
+    fn is_identity(&self) -> bool {
+        self.base == (0.0, 0.0)
+            && self.scale == (1.0, 1.0)
+            && self.rotation == 0.0
+            && self.insert == (0.0, 0.0)
+    }
+
+    /// ベクトル (相対量) を変換する
+    fn vector(&self, x: f64, y: f64) -> (f64, f64) {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let (x, y) = (x * self.scale.0, y * self.scale.1);
+        (x * cos - y * sin, x * sin + y * cos)
+    }
+
+    /// 点を変換する
+    fn point(&self, x: f64, y: f64) -> (f64, f64) {
+        let (x, y) = self.vector(x - self.base.0, y - self.base.1);
+        (x + self.insert.0, y + self.insert.1)
+    }
+
+    /// エンティティの座標を変換する
+    ///
+    /// 長さ (半径・文字高さ) はX方向の尺度、角度は回転角で変換する。
+    fn apply(&self, entity: &DxfEntity) -> DxfEntity {
+        let mut entity = entity.clone();
+        if self.is_identity() {
+            return entity;
+        }
+        let number = |code: i32| entity.number(code);
+        let mut updates: Vec<(i32, f64)> = Vec::new();
+
+        // ELLIPSEの11/21は中心からの長軸ベクトル
+        let vector_codes: &[i32] = match entity.entity_type {
+            DxfEntityType::Ellipse => &[11],
+            _ => &[],
+        };
+        for x_code in [10, 11, 12, 13] {
+            if let (Some(x), Some(y)) = (number(x_code), number(x_code + 10)) {
+                let (x, y) = if vector_codes.contains(&x_code) {
+                    self.vector(x, y)
+                } else {
+                    self.point(x, y)
+                };
+                updates.push((x_code, x));
+                updates.push((x_code + 10, y));
+            }
+        }
+        // POLYLINEの10/20はダミーの原点
+        if entity.entity_type == DxfEntityType::Polyline {
+            updates.retain(|(code, _)| *code != 10 && *code != 20);
+        }
+        let length_codes: &[i32] = match entity.entity_type {
+            DxfEntityType::Circle | DxfEntityType::Arc | DxfEntityType::Text => &[40],
+            _ => &[],
+        };
+        for &code in length_codes {
+            if let Some(value) = number(code) {
+                updates.push((code, value * self.scale.0.abs()));
+            }
+        }
+        let angle_codes: &[i32] = match entity.entity_type {
+            DxfEntityType::Arc => &[50, 51],
+            DxfEntityType::Text | DxfEntityType::Insert => &[50],
+            _ => &[],
+        };
+        for &code in angle_codes {
+            let value = number(code).unwrap_or(0.0);
+            updates.push((code, (value + self.rotation).rem_euclid(360.0)));
+        }
+        if entity.entity_type == DxfEntityType::Insert {
+            for code in [41, 42] {
+                let value = number(code).unwrap_or(1.0);
+                let scale = if code == 41 { self.scale.0 } else { self.scale.1 };
+                updates.push((code, value * scale));
             }
         }
 
-        // エンティティの解析
-        if in_entities_section {
-            if let Some(entity_type) = parse_entity_at(&lines, i) {
-                entities.push(entity_type);
+        for (code, value) in updates {
+            entity.properties.insert(code, value.to_string());
+        }
+        entity.vertices = entity
+            .vertices
+            .iter()
+            .map(|&(x, y)| self.point(x, y))
+            .collect();
+        entity
+    }
+}
+
+/// DXF文字列をグループコードと値の組に分ける (グループコードが数値でない組は読み飛ばす)
+fn group_pairs(dxf_content: &str) -> Vec<(i32, &str)> {
+    let lines: Vec<&str> = dxf_content.lines().collect();
+    lines
+        .chunks(2)
+        .filter_map(|pair| match pair {
+            [code, value] => Some((code.trim().parse().ok()?, value.trim())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// DXF文字列を解析してエンティティリストとレイヤーリストを返す
+///
+/// INSERTは展開しない。ブロックの展開が必要な場合は [`parse_dxf`] と
+/// [`DxfDrawing::resolved_entities`] を使う。
+pub fn parse_dxf_entities(dxf_content: &str) -> (Vec<DxfEntity>, Vec<String>) {
+    let drawing = parse_dxf(dxf_content);
+    (drawing.entities, drawing.layers)
+}
+
+/// DXF文字列を解析し、エンティティ・レイヤー・ブロック定義を返す
+pub fn parse_dxf(dxf_content: &str) -> DxfDrawing {
+    let pairs = group_pairs(dxf_content);
+    let mut drawing = DxfDrawing::default();
+
+    // 組の列を "0" で始まるレコードに分ける
+    let mut records: Vec<&[(i32, &str)]> = Vec::new();
+    let mut start = 0;
+    for i in 1..=pairs.len() {
+        if i == pairs.len() || pairs[i].0 == 0 {
+            if start < i {
+                records.push(&pairs[start..i]);
             }
+            start = i;
         }
+    }
 
+    let mut section = "";
+    let mut in_layer_table = false;
+    let mut block: Option<DxfBlock> = None;
+    let mut i = 0;
+    while i < records.len() {
+        let record = records[i];
+        let (code, name) = record[0];
         i += 1;
+        if code != 0 {
+            continue;
+        }
+        match name {
+            "SECTION" => section = group_value(record, 2).unwrap_or(""),
+            "ENDSEC" => {
+                section = "";
+                in_layer_table = false;
+            }
+            "TABLE" => in_layer_table = group_value(record, 2) == Some("LAYER"),
+            "ENDTAB" => in_layer_table = false,
+            "LAYER" if section == "TABLES" && in_layer_table => {
+                if let Some(layer_name) = group_value(record, 2) {
+                    drawing.layers.push(layer_name.to_string());
+                }
+            }
+            "BLOCK" if section == "BLOCKS" => {
+                block = Some(DxfBlock {
+                    name: group_value(record, 2).unwrap_or("").to_string(),
+                    base_point: (group_number(record, 10), group_number(record, 20)),
+                    entities: Vec::new(),
+                });
+            }
+            "ENDBLK" if section == "BLOCKS" => {
+                if let Some(block) = block.take() {
+                    drawing.blocks.insert(block.name.clone(), block);
+                }
+            }
+            _ if section == "ENTITIES" || (section == "BLOCKS" && block.is_some()) => {
+                let mut entity = parse_entity(record);
+                if entity.entity_type == DxfEntityType::Polyline {
+                    // 続くVERTEXをSEQENDまで頂点として取り込む
+                    while i < records.len() && records[i][0] == (0, "VERTEX") {
+                        entity
+                            .vertices
+                            .push((group_number(records[i], 10), group_number(records[i], 20)));
+                        i += 1;
+                    }
+                    if i < records.len() && records[i][0] == (0, "SEQEND") {
+                        i += 1;
+                    }
+                }
+                match block.as_mut() {
+                    Some(block) => block.entities.push(entity),
+                    None => drawing.entities.push(entity),
+                }
+            }
+            _ => {}
+        }
     }
 
-    (entities, layers)
+    drawing
 }
 
-/// 指定された位置からエンティティを解析する
-fn parse_entity_at(lines: &[&str], start: usize) -> Option<DxfEntity> {
-    let line = lines[start].trim();
-
-    let entity_type_str = match line {
-        "LINE" | "CIRCLE" | "ARC" | "ELLIPSE" | "POINT" | "TEXT" | "SOLID" | "INSERT" => line,
-        _ => return None,
-    };
+/// レコード内で最初に現れるグループコードの値
+fn group_value<'a>(record: &[(i32, &'a str)], group_code: i32) -> Option<&'a str> {
+    record
+        .iter()
+        .skip(1)
+        .find(|(code, _)| *code == group_code)
+        .map(|(_, value)| *value)
+}
 
-    let entity_type = DxfEntityType::from_str(entity_type_str);
+/// レコード内の数値のグループコードの値 (ない場合は0)
+fn group_number(record: &[(i32, &str)], group_code: i32) -> f64 {
+    group_value(record, group_code)
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0.0)
+}
 
-    // エンティティの終わり（次の0またはENDSEC）まで読む
+/// "0" から始まる1レコードをエンティティとして解析する
+fn parse_entity(record: &[(i32, &str)]) -> DxfEntity {
+    let entity_type = DxfEntityType::from_str(record[0].1);
     let mut layer = String::from("0");
     let mut color = 7; // デフォルト色
     let mut line_type = String::from("CONTINUOUS");
     let mut properties = HashMap::new();
-
-    let mut i = start + 1;
-    while i < lines.len() {
-        let current = lines[i].trim();
-        if current == "0" {
-            break; // 次のエンティティ
-        }
-
-        // グループコードと値のペアを処理
-        if let Ok(group_code) = current.parse::<i32>() {
-            if i + 1 < lines.len() {
-                let value = lines[i + 1].trim();
-
-                // 重要なプロパティを保存
-                match group_code {
-                    8 => layer = value.to_string(),  // レイヤー
-                    62 => {
-                        if let Ok(c) = value.parse::<i32>() {
-                            color = c;
-                        }
-                    }  // 色
-                    6 => line_type = value.to_string(),  // 線種
-                    _ => {
-                        properties.insert(group_code, value.to_string());
-                    }
+    let mut vertices = Vec::new();
+
+    for &(group_code, value) in &record[1..] {
+        match group_code {
+            8 => layer = value.to_string(), // レイヤー
+            62 => {
+                if let Ok(c) = value.parse::<i32>() {
+                    color = c;
+                }
+            } // 色
+            6 => line_type = value.to_string(), // 線種
+            // LWPOLYLINEの頂点 (10の直後に20が続く)
+            10 if entity_type == DxfEntityType::LwPolyline => {
+                vertices.push((value.parse().unwrap_or(0.0), 0.0));
+            }
+            20 if entity_type == DxfEntityType::LwPolyline => {
+                if let Some(vertex) = vertices.last_mut() {
+                    vertex.1 = value.parse().unwrap_or(0.0);
                 }
             }
+            _ => {
+                properties.insert(group_code, value.to_string());
+            }
         }
-
-        i += 2;
     }
 
-    Some(DxfEntity {
+    DxfEntity {
         entity_type,
         layer,
         color,
         line_type,
         properties,
-    })
-}
-
-/// 指定されたグループコードの値を次の行から見つける
-fn find_next_group_value(lines: &[&str], start: usize, group_code: &str) -> Option<String> {
-    for i in start..lines.len().saturating_sub(1) {
-        if lines[i].trim() == group_code {
-            return Some(lines[i + 1].trim().to_string());
-        }
+        vertices,
     }
-    None
 }
 
 /// 2つのエンティティリストを比較する
@@ -216,6 +443,18 @@ pub fn compare_dxf_entities(
     go_entities: &[DxfEntity],
     rust_entities: &[DxfEntity],
     tolerance: f64,
+) -> Vec<EntityDifference> {
+    compare_dxf_entities_with(go_entities, rust_entities, &ComparePolicy::uniform(tolerance))
+}
+
+/// 2つのエンティティリストを比較方法を指定して比較する
+///
+/// グループコード・エンティティタイプごとの許容誤差、角度の周期、比較しない
+/// グループコードを `policy` で指定する。エンティティタイプはGo版のものを使う。
+pub fn compare_dxf_entities_with(
+    go_entities: &[DxfEntity],
+    rust_entities: &[DxfEntity],
+    policy: &ComparePolicy,
 ) -> Vec<EntityDifference> {
     let mut differences = Vec::new();
 
@@ -243,7 +482,7 @@ pub fn compare_dxf_entities(
         }
 
         // レイヤーの比較
-        if go_ent.layer != rust_ent.layer {
+        if policy.compares(8) && go_ent.layer != rust_ent.layer {
             differences.push(EntityDifference::LayerMismatch {
                 index: i,
                 go_layer: go_ent.layer.clone(),
@@ -252,7 +491,7 @@ pub fn compare_dxf_entities(
         }
 
         // 色の比較
-        if go_ent.color != rust_ent.color {
+        if policy.compares(62) && go_ent.color != rust_ent.color {
             differences.push(EntityDifference::ColorMismatch {
                 index: i,
                 go_color: go_ent.color,
@@ -261,7 +500,11 @@ pub fn compare_dxf_entities(
         }
 
         // 座標値の比較
-        compare_coordinates(&go_ent.properties, &rust_ent.properties, i, tolerance, &mut differences);
+        compare_coordinates(go_ent, rust_ent, i, policy, &mut differences);
+
+        // ポリラインの頂点の比較
+        let tolerance = policy.tolerance.for_entity(&go_ent.entity_type);
+        compare_vertices(&go_ent.vertices, &rust_ent.vertices, i, tolerance, &mut differences);
     }
 
     differences
@@ -269,23 +512,23 @@ pub fn compare_dxf_entities(
 
 /// 座標プロパティを比較する
 fn compare_coordinates(
-    go_props: &HashMap<i32, String>,
-    rust_props: &HashMap<i32, String>,
+    go_ent: &DxfEntity,
+    rust_ent: &DxfEntity,
     index: usize,
-    tolerance: f64,
+    policy: &ComparePolicy,
     differences: &mut Vec<EntityDifference>,
 ) {
     // 座標グループコード
     let coord_codes = [10, 11, 12, 13, 20, 21, 22, 23, 40, 41, 42, 50, 51];
 
-    for code in coord_codes {
-        let go_val = go_props.get(&code);
-        let rust_val = rust_props.get(&code);
+    for code in coord_codes.into_iter().filter(|&code| policy.compares(code)) {
+        let go_val = go_ent.properties.get(&code);
+        let rust_val = rust_ent.properties.get(&code);
 
         match (go_val, rust_val) {
             (Some(go_str), Some(rust_str)) => {
                 if let (Ok(go_f), Ok(rust_f)) = (go_str.parse::<f64>(), rust_str.parse::<f64>()) {
-                    if (go_f - rust_f).abs() > tolerance {
+                    if !policy.equal(&go_ent.entity_type, code, go_f, rust_f) {
                         differences.push(EntityDifference::CoordinateMismatch {
                             index,
                             group_code: code,
@@ -306,6 +549,34 @@ fn compare_coordinates(
     }
 }
 
+/// ポリラインの頂点を比較する
+fn compare_vertices(
+    go_vertices: &[(f64, f64)],
+    rust_vertices: &[(f64, f64)],
+    index: usize,
+    tolerance: f64,
+    differences: &mut Vec<EntityDifference>,
+) {
+    if go_vertices.len() != rust_vertices.len() {
+        differences.push(EntityDifference::VertexCountMismatch {
+            index,
+            go: go_vertices.len(),
+            rust: rust_vertices.len(),
+        });
+        return;
+    }
+    for (vertex, (go, rust)) in go_vertices.iter().zip(rust_vertices).enumerate() {
+        if (go.0 - rust.0).abs() > tolerance || (go.1 - rust.1).abs() > tolerance {
+            differences.push(EntityDifference::VertexMismatch {
+                index,
+                vertex,
+                go_value: *go,
+                rust_value: *rust,
+            });
+        }
+    }
+}
+
 /// エンティティの差異
 #[derive(Debug)]
 pub enum EntityDifference {
@@ -335,6 +606,17 @@ pub enum EntityDifference {
         index: usize,
         group_code: i32,
     },
+    VertexCountMismatch {
+        index: usize,
+        go: usize,
+        rust: usize,
+    },
+    VertexMismatch {
+        index: usize,
+        vertex: usize,
+        go_value: (f64, f64),
+        rust_value: (f64, f64),
+    },
 }
 
 impl std::fmt::Display for EntityDifference {
@@ -362,6 +644,16 @@ impl std::fmt::Display for EntityDifference {
             EntityDifference::MissingCoordinate { index, group_code } => {
                 write!(f, "エンティティ[{}] 座標コード{}が不足", index, group_code)
             }
+            EntityDifference::VertexCountMismatch { index, go, rust } => {
+                write!(f, "エンティティ[{}] 頂点数不一致: Go={}, Rust={}", index, go, rust)
+            }
+            EntityDifference::VertexMismatch { index, vertex, go_value, rust_value } => {
+                write!(
+                    f,
+                    "エンティティ[{}] 頂点[{}]不一致: Go={:?}, Rust={:?}",
+                    index, vertex, go_value, rust_value
+                )
+            }
         }
     }
 }
@@ -463,6 +755,120 @@ EOF"#;
         assert!(differences.is_empty());
     }
 
+    #[test]
+    fn test_compare_policy() {
+        let dxf = "0\nSECTION\n2\nENTITIES\n\
+0\nARC\n8\nA\n62\n1\n10\n0.0\n20\n0.0\n40\n5.0\n50\n0.0\n51\n90.0\n\
+0\nENDSEC\n0\nEOF\n";
+        let (go, _) = parse_dxf_entities(dxf);
+        let rust_dxf = dxf
+            .replace("50\n0.0", "50\n360.0")
+            .replace("40\n5.0", "40\n5.01")
+            .replace("62\n1", "62\n3");
+        let (rust, _) = parse_dxf_entities(&rust_dxf);
+
+        let mut policy = ComparePolicy::uniform(1e-6);
+        let differences = compare_dxf_entities_with(&go, &rust, &policy);
+        // 0と360は同じ角度
+        assert_eq!(differences.len(), 2, "{:?}", differences);
+        assert!(matches!(differences[0], EntityDifference::ColorMismatch { .. }));
+        assert!(matches!(
+            differences[1],
+            EntityDifference::CoordinateMismatch { group_code: 40, .. }
+        ));
+
+        policy.tolerance.codes.insert(40, 0.1);
+        policy.ignore_codes.push(62);
+        assert!(compare_dxf_entities_with(&go, &rust, &policy).is_empty());
+
+        policy.angle_codes.clear();
+        let differences = compare_dxf_entities_with(&go, &rust, &policy);
+        assert!(matches!(
+            differences[..],
+            [EntityDifference::CoordinateMismatch { group_code: 50, .. }]
+        ));
+    }
+
+    #[test]
+    fn test_parse_polylines() {
+        let dxf = "0\nSECTION\n2\nENTITIES\n\
+0\nPOLYLINE\n8\nA\n66\n1\n10\n0.0\n20\n0.0\n70\n1\n\
+0\nVERTEX\n8\nA\n10\n1.0\n20\n2.0\n\
+0\nVERTEX\n8\nA\n10\n3.0\n20\n4.0\n\
+0\nSEQEND\n8\nA\n\
+0\nLWPOLYLINE\n8\nB\n90\n2\n10\n5.0\n20\n6.0\n10\n7.0\n20\n8.0\n\
+0\nENDSEC\n0\nEOF\n";
+
+        let (entities, _) = parse_dxf_entities(dxf);
+        assert_eq!(entities.len(), 2);
+        assert_eq!(entities[0].entity_type, DxfEntityType::Polyline);
+        assert_eq!(entities[0].vertices, vec![(1.0, 2.0), (3.0, 4.0)]);
+        assert_eq!(entities[1].entity_type, DxfEntityType::LwPolyline);
+        assert_eq!(entities[1].vertices, vec![(5.0, 6.0), (7.0, 8.0)]);
+
+        let mut moved = entities.clone();
+        moved[1].vertices[1].0 += 1.0;
+        let differences = compare_dxf_entities(&entities, &moved, 0.001);
+        assert_eq!(differences.len(), 1);
+        assert!(matches!(
+            differences[0],
+            EntityDifference::VertexMismatch { index: 1, vertex: 1, .. }
+        ));
+
+        // LWPOLYLINEだけ許容誤差を広げる
+        let mut policy = ComparePolicy::uniform(0.001);
+        policy.tolerance.entities.insert("LWPOLYLINE".to_string(), 2.0);
+        assert!(compare_dxf_entities_with(&entities, &moved, &policy).is_empty());
+    }
+
+    #[test]
+    fn test_resolve_inserts() {
+        use jww_core::{Block, BlockDef, Document, Entity, EntityBase, Line};
+
+        let base = EntityBase::default();
+        let doc = Document {
+            entities: vec![Entity::Block(Block {
+                base: EntityBase { layer: 2, ..base.clone() },
+                reference: jww_core::Point2D::new(100.0, 50.0),
+                scale_x: 2.0,
+                scale_y: 2.0,
+                rotation: jww_core::Angle::from_radians(std::f64::consts::FRAC_PI_2),
+                def_number: 1,
+            })],
+            block_defs: vec![BlockDef {
+                base: base.clone(),
+                number: 1,
+                is_referenced: true,
+                name: "SYMBOL".to_string(),
+                entities: vec![Entity::Line(Line {
+                    base: base.clone(),
+                    start: jww_core::Point2D::new(0.0, 0.0),
+                    end: jww_core::Point2D::new(10.0, 0.0),
+                })],
+            }],
+            ..Default::default()
+        };
+        let dxf = crate::to_string(&crate::convert_document(&doc));
+
+        let drawing = parse_dxf(&dxf);
+        assert_eq!(drawing.entities.len(), 1);
+        assert_eq!(drawing.entities[0].entity_type, DxfEntityType::Insert);
+        let block = &drawing.blocks["SYMBOL"];
+        assert_eq!(block.entities.len(), 1);
+
+        // 長さ10の線を2倍・90度回転して (100, 50) に配置
+        let resolved = drawing.resolved_entities();
+        assert_eq!(resolved.len(), 1);
+        let line = &resolved[0];
+        assert_eq!(line.entity_type, DxfEntityType::Line);
+        assert_eq!(line.layer, "0-0");
+        let point = |x_code| (line.number(x_code).unwrap(), line.number(x_code + 10).unwrap());
+        let (x, y) = point(10);
+        assert!((x - 100.0).abs() < 1e-9 && (y - 50.0).abs() < 1e-9);
+        let (x, y) = point(11);
+        assert!((x - 100.0).abs() < 1e-9 && (y - 70.0).abs() < 1e-9);
+    }
+
     #[test]
     fn test_parse_layers() {
         let dxf = r#"0
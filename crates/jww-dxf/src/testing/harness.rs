@@ -0,0 +1,295 @@
+//! 互換性テストの設定
+//!
+//! Go版パーサーのバイナリ、フィクスチャ・コーパスのディレクトリ、DXFの比較方法を
+//! 設定ファイル (TOML) と環境変数から読み込む。環境変数が設定ファイルより優先される。
+//!
+//! ```toml
+//! go_parser = "../jww-parser/bin/jww-parser"
+//! fixtures_dir = "tests/fixtures"
+//! corpus_dir = "/data/jww-corpus"
+//!
+//! [compare]
+//! angle_codes = [50, 51]
+//! ignore_codes = [41]
+//!
+//! [compare.tolerance]
+//! default = 1e-6
+//!
+//! [compare.tolerance.entities]
+//! ELLIPSE = 1e-4
+//!
+//! [compare.tolerance.codes]
+//! 40 = 1e-3
+//! ```
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use super::dxf_parser::DxfEntityType;
+
+/// 設定ファイルのパスを指定する環境変数
+pub const CONFIG_ENV: &str = "JWW_COMPAT_CONFIG";
+/// Go版パーサーのバイナリのパスを指定する環境変数
+pub const GO_PARSER_ENV: &str = "JWW_GO_PARSER";
+/// フィクスチャのディレクトリを指定する環境変数
+pub const FIXTURES_DIR_ENV: &str = "JWW_FIXTURES_DIR";
+/// 既定の許容誤差を指定する環境変数
+pub const TOLERANCE_ENV: &str = "JWW_COMPAT_TOLERANCE";
+/// 実ファイルのコーパスのディレクトリを指定する環境変数
+pub const CORPUS_DIR_ENV: &str = "JWW_CORPUS_DIR";
+/// コーパスの集計結果 (JSON) の出力先を指定する環境変数
+pub const CORPUS_REPORT_ENV: &str = "JWW_CORPUS_REPORT";
+
+/// 設定の読み込みエラー
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("{}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("{}: {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+
+    #[error("{name}: invalid value {value:?}")]
+    InvalidEnv { name: &'static str, value: String },
+}
+
+/// 互換性テストの設定
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HarnessConfig {
+    /// Go版パーサーのバイナリ
+    pub go_parser: Option<PathBuf>,
+    /// フィクスチャ (`*.jww`) のディレクトリ
+    pub fixtures_dir: Option<PathBuf>,
+    /// 実ファイルのコーパスのディレクトリ (サブディレクトリも含めて読む)
+    pub corpus_dir: Option<PathBuf>,
+    /// コーパスの集計結果 (JSON) の出力先
+    pub corpus_report: Option<PathBuf>,
+    /// DXFのエンティティの比較方法
+    pub compare: ComparePolicy,
+}
+
+impl HarnessConfig {
+    /// 設定ファイルを読み込む (相対パスは設定ファイルのディレクトリを基準にする)
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let mut config: HarnessConfig =
+            toml::from_str(&text).map_err(|source| ConfigError::Parse {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        for path in [
+            &mut config.go_parser,
+            &mut config.fixtures_dir,
+            &mut config.corpus_dir,
+            &mut config.corpus_report,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if path.is_relative() {
+                *path = base.join(&*path);
+            }
+        }
+        Ok(config)
+    }
+
+    /// 設定を読み込む
+    ///
+    /// `JWW_COMPAT_CONFIG` が設定されていればそのファイルを、なければ `default_file` が
+    /// 存在する場合に読み込み、`JWW_GO_PARSER`・`JWW_FIXTURES_DIR`・`JWW_CORPUS_DIR`・
+    /// `JWW_CORPUS_REPORT`・`JWW_COMPAT_TOLERANCE` で上書きする。
+    pub fn load(default_file: &Path) -> Result<Self, ConfigError> {
+        let mut config = match std::env::var_os(CONFIG_ENV) {
+            Some(path) => Self::from_file(Path::new(&path))?,
+            None if default_file.is_file() => Self::from_file(default_file)?,
+            None => Self::default(),
+        };
+        if let Some(path) = std::env::var_os(GO_PARSER_ENV) {
+            config.go_parser = Some(path.into());
+        }
+        if let Some(path) = std::env::var_os(FIXTURES_DIR_ENV) {
+            config.fixtures_dir = Some(path.into());
+        }
+        if let Some(path) = std::env::var_os(CORPUS_DIR_ENV) {
+            config.corpus_dir = Some(path.into());
+        }
+        if let Some(path) = std::env::var_os(CORPUS_REPORT_ENV) {
+            config.corpus_report = Some(path.into());
+        }
+        if let Ok(value) = std::env::var(TOLERANCE_ENV) {
+            config.compare.tolerance.default =
+                value.trim().parse().map_err(|_| ConfigError::InvalidEnv {
+                    name: TOLERANCE_ENV,
+                    value,
+                })?;
+        }
+        Ok(config)
+    }
+}
+
+/// DXFのエンティティの比較方法
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ComparePolicy {
+    /// 数値の許容誤差
+    pub tolerance: Tolerance,
+    /// 角度 (度) として360の差を同じ値とみなすグループコード
+    ///
+    /// ELLIPSEの始点・終点パラメータ (41・42) はこの設定によらず2πの差を同じ値とみなす。
+    pub angle_codes: Vec<i32>,
+    /// 比較しないグループコード
+    pub ignore_codes: Vec<i32>,
+}
+
+impl Default for ComparePolicy {
+    fn default() -> Self {
+        Self::uniform(Tolerance::default().default)
+    }
+}
+
+impl ComparePolicy {
+    /// すべての値で同じ許容誤差を使う比較方法 (角度は50・51)
+    pub fn uniform(tolerance: f64) -> Self {
+        Self {
+            tolerance: Tolerance::uniform(tolerance),
+            angle_codes: vec![50, 51],
+            ignore_codes: Vec::new(),
+        }
+    }
+
+    /// グループコードを比較するかどうか
+    pub fn compares(&self, group_code: i32) -> bool {
+        !self.ignore_codes.contains(&group_code)
+    }
+
+    /// 同じ値とみなす周期 (角度は360、ELLIPSEのパラメータは2π)
+    pub fn period(&self, entity_type: &DxfEntityType, group_code: i32) -> Option<f64> {
+        if *entity_type == DxfEntityType::Ellipse && matches!(group_code, 41 | 42) {
+            Some(std::f64::consts::TAU)
+        } else if self.angle_codes.contains(&group_code) {
+            Some(360.0)
+        } else {
+            None
+        }
+    }
+
+    /// 2つの値が許容誤差の範囲で等しいかどうか
+    pub fn equal(&self, entity_type: &DxfEntityType, group_code: i32, a: f64, b: f64) -> bool {
+        let tolerance = self.tolerance.for_field(entity_type, group_code);
+        let diff = (a - b).abs();
+        match self.period(entity_type, group_code) {
+            Some(period) => {
+                let diff = diff % period;
+                diff.min(period - diff) <= tolerance
+            }
+            None => diff <= tolerance,
+        }
+    }
+}
+
+/// 数値の許容誤差 (エンティティタイプ・グループコードごとに上書きできる)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Tolerance {
+    /// 既定の許容誤差
+    pub default: f64,
+    /// エンティティタイプ (`ELLIPSE` など) ごとの許容誤差
+    pub entities: HashMap<String, f64>,
+    /// グループコードごとの許容誤差 (エンティティタイプの設定より優先する)
+    pub codes: HashMap<i32, f64>,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self::uniform(1e-6)
+    }
+}
+
+impl Tolerance {
+    /// すべてのエンティティで同じ許容誤差
+    pub fn uniform(tolerance: f64) -> Self {
+        Self {
+            default: tolerance,
+            entities: HashMap::new(),
+            codes: HashMap::new(),
+        }
+    }
+
+    /// エンティティタイプの許容誤差
+    pub fn for_entity(&self, entity_type: &DxfEntityType) -> f64 {
+        self.entities
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(entity_type.as_str()))
+            .map_or(self.default, |(_, tolerance)| *tolerance)
+    }
+
+    /// エンティティタイプのグループコードの値の許容誤差
+    pub fn for_field(&self, entity_type: &DxfEntityType, group_code: i32) -> f64 {
+        match self.codes.get(&group_code) {
+            Some(tolerance) => *tolerance,
+            None => self.for_entity(entity_type),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_file() {
+        let dir = std::env::temp_dir().join(format!("jww-compat-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("jww-compat.toml");
+        std::fs::write(
+            &path,
+            "go_parser = \"bin/jww-parser\"\nfixtures_dir = \"/data/jww\"\n\n\
+             [compare]\nignore_codes = [5]\n\n[compare.tolerance]\ndefault = 0.01\n\n\
+             [compare.tolerance.entities]\nellipse = 0.5\n\n[compare.tolerance.codes]\n40 = 2.0\n",
+        )
+        .unwrap();
+
+        let config = HarnessConfig::from_file(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(config.go_parser, Some(dir.join("bin/jww-parser")));
+        assert_eq!(config.fixtures_dir, Some(PathBuf::from("/data/jww")));
+        let tolerance = &config.compare.tolerance;
+        assert_eq!(tolerance.for_entity(&DxfEntityType::Ellipse), 0.5);
+        assert_eq!(tolerance.for_entity(&DxfEntityType::Line), 0.01);
+        assert_eq!(tolerance.for_field(&DxfEntityType::Ellipse, 40), 2.0);
+        assert!(!config.compare.compares(5));
+        // 指定しなかった項目は既定値
+        assert_eq!(config.compare.angle_codes, [50, 51]);
+    }
+
+    #[test]
+    fn test_angle_wrap_around() {
+        let policy = ComparePolicy::uniform(1e-6);
+        assert!(policy.equal(&DxfEntityType::Arc, 50, 0.0, 360.0));
+        assert!(policy.equal(&DxfEntityType::Arc, 51, -90.0, 270.0));
+        assert!(!policy.equal(&DxfEntityType::Arc, 50, 0.0, 180.0));
+        assert!(!policy.equal(&DxfEntityType::Arc, 10, 0.0, 360.0));
+        let tau = std::f64::consts::TAU;
+        assert!(policy.equal(&DxfEntityType::Ellipse, 42, 0.0, tau));
+        assert!(!policy.equal(&DxfEntityType::Circle, 42, 0.0, tau));
+    }
+
+    #[test]
+    fn test_unknown_key() {
+        let err = toml::from_str::<HarnessConfig>("go_parsr = \"x\"\n").unwrap_err();
+        assert!(err.to_string().contains("go_parsr"));
+    }
+}
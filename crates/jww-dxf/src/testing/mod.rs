@@ -1,7 +1,15 @@
 //! テスト用ユーティリティモジュール
 //!
-//! Go版jww-parserとの互換性テストで使用する
+//! Go版jww-parserとの互換性テストと実ファイルのコーパスの集計で使用する
 
 mod dxf_parser;
+mod harness;
 
-pub use dxf_parser::{parse_dxf_entities, DxfEntity, DxfEntityType};
+pub use dxf_parser::{
+    compare_dxf_entities, compare_dxf_entities_with, parse_dxf, parse_dxf_entities, DxfBlock,
+    DxfDrawing, DxfEntity, DxfEntityType, EntityDifference,
+};
+pub use harness::{
+    ComparePolicy, ConfigError, HarnessConfig, Tolerance, CONFIG_ENV, CORPUS_DIR_ENV, CORPUS_REPORT_ENV,
+    FIXTURES_DIR_ENV, GO_PARSER_ENV, TOLERANCE_ENV,
+};
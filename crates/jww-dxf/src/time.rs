@@ -0,0 +1,59 @@
+//! 日時の変換
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 1日の秒数
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// 現在時刻をUNIX時刻の秒で返す
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// UNIX元期 (1970-01-01T00:00:00Z) のユリウス日
+const UNIX_EPOCH_JULIAN_DATE: f64 = 2_440_587.5;
+
+/// UNIX時刻をユリウス日 ($TDCREATE などの形式) に変換する
+pub(crate) fn julian_date(timestamp: u64) -> f64 {
+    UNIX_EPOCH_JULIAN_DATE + timestamp as f64 / SECONDS_PER_DAY as f64
+}
+
+/// ユリウス日をUNIX時刻の秒に変換する (秒未満は四捨五入)
+#[cfg(feature = "dxf-rs")]
+pub(crate) fn timestamp(julian_date: f64) -> i64 {
+    ((julian_date - UNIX_EPOCH_JULIAN_DATE) * SECONDS_PER_DAY as f64).round() as i64
+}
+
+/// UNIX時刻をISO 8601形式のUTC日時 (例: `2024-01-31T12:34:56Z`) に変換する
+pub(crate) fn format_utc(timestamp: u64) -> String {
+    let days = timestamp / SECONDS_PER_DAY;
+    let secs = timestamp % SECONDS_PER_DAY;
+    let (year, month, day) = civil_from_days(days as i64);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// 1970-01-01からの日数を (年, 月, 日) に変換する
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // Howard Hinnant の days_from_civil の逆変換
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
@@ -0,0 +1,690 @@
+//! DXFからJWWへの変換ロジック
+
+use std::collections::HashMap;
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use jww_core::{Angle, Document as JwwDocument, Entity as JwwEntity, EntityBase, Point2D};
+
+use crate::options::Units;
+use crate::reader::{self, ReadError};
+use crate::types::*;
+
+/// 出力するJWWファイルのバージョン
+pub const JWW_VERSION: u32 = 600;
+
+/// DXF -> JWW 変換時のエラー型
+#[derive(Debug, thiserror::Error)]
+pub enum DxfToJwwError {
+    /// DXFの読み込みに失敗した
+    #[error("DXF read error: {0}")]
+    Read(#[from] ReadError),
+
+    /// JWWの書き出しに失敗した
+    #[error("JWW write error: {0}")]
+    Write(#[from] jww_core::ParseError),
+}
+
+/// DXF文字列を読み込み、JWWバイナリに変換する
+pub fn dxf_to_jww(input: &str) -> Result<Vec<u8>, DxfToJwwError> {
+    let doc = reader::parse(input)?;
+    let jww_doc = convert_to_jww(&doc);
+    Ok(jww_core::write(&jww_doc)?)
+}
+
+/// DXFドキュメントをJWWドキュメントに変換する
+///
+/// 座標は $INSUNITS に従ってミリメートルに換算する。JWWファイルにはブロック定義を
+/// 書き出せないため、ブロック挿入は構成要素に分解する。レイヤーは名前が `G-L`
+/// (16進数1桁) 形式であれば対応するレイヤに、それ以外は空いているレイヤに順に割り当てる。
+pub fn convert_to_jww(doc: &Document) -> JwwDocument<'static> {
+    let factor = Units::from_insunits(doc.header.insunits)
+        .map(Units::millimeters)
+        .unwrap_or(1.0);
+
+    let mut jww_doc = JwwDocument {
+        version: JWW_VERSION,
+        memo: doc.header.project_name.clone().unwrap_or_default().into(),
+        paper_size: 3,
+        ..Default::default()
+    };
+
+    if let Some(layout) = &doc.layout {
+        if let Some(code) = paper_size_code(layout.paper_width * factor, layout.paper_height * factor) {
+            jww_doc.paper_size = code;
+        }
+    }
+
+    // $LTSCALE には縮尺分母が出力単位で記録されている
+    let scale = doc.header.ltscale * factor;
+    if scale > 0.0 {
+        for group in &mut jww_doc.layer_groups {
+            group.scale = scale;
+        }
+    }
+
+    let layers = LayerMap::new(doc);
+    for layer in &doc.layers {
+        let (g, l) = layers.slot(&layer.name);
+        let jww_layer = &mut jww_doc.layer_groups[g as usize].layers[l as usize];
+        jww_layer.name = layer.name.clone();
+        if layer.frozen {
+            jww_layer.state = 0;
+        }
+        if layer.locked {
+            jww_layer.protect = 1;
+        }
+    }
+
+    let mut primitives = Vec::new();
+    for entity in &doc.entities {
+        explode(doc, entity, &Affine::IDENTITY, None, 0, &mut primitives);
+    }
+
+    let scale = Affine::scale(factor, factor);
+    for entity in primitives {
+        let entity = transform(&entity, &scale);
+        convert_entity(doc, &layers, &entity, &mut jww_doc.entities);
+    }
+
+    jww_doc
+}
+
+/// 用紙サイズ (mm) に一致するJWW用紙コードを返す
+fn paper_size_code(width: f64, height: f64) -> Option<u32> {
+    const TOLERANCE: f64 = 1.0;
+    [0, 1, 2, 3, 4, 8, 9, 10, 11].into_iter().find(|&code| {
+        let doc = JwwDocument {
+            paper_size: code,
+            ..Default::default()
+        };
+        doc.paper_dimensions().is_some_and(|(w, h)| {
+            ((w - width).abs() < TOLERANCE && (h - height).abs() < TOLERANCE)
+                || ((w - height).abs() < TOLERANCE && (h - width).abs() < TOLERANCE)
+        })
+    })
+}
+
+/// DXFレイヤー名 -> JWWの (レイヤグループ, レイヤ)
+struct LayerMap {
+    slots: HashMap<String, (u16, u16)>,
+}
+
+impl LayerMap {
+    fn new(doc: &Document) -> Self {
+        let mut slots = HashMap::new();
+        let mut used = [[false; 16]; 16];
+
+        // JWW -> DXF 変換の既定レイヤー名 (例: "1-A") はそのまま戻す
+        let names = doc.layers.iter().map(|l| l.name.as_str()).chain(
+            doc.entities
+                .iter()
+                .chain(doc.blocks.iter().flat_map(|b| b.entities.iter()))
+                .map(entity_layer),
+        );
+        let mut others = Vec::new();
+        for name in names {
+            if slots.contains_key(name) || others.iter().any(|o| o == name) {
+                continue;
+            }
+            match parse_layer_name(name) {
+                Some((g, l)) if !used[g as usize][l as usize] => {
+                    used[g as usize][l as usize] = true;
+                    slots.insert(name.to_string(), (g, l));
+                }
+                _ => others.push(name.to_string()),
+            }
+        }
+
+        // 残りは空いているレイヤに順に割り当て、溢れた場合は最後のレイヤにまとめる
+        let mut free = (0..16u16)
+            .flat_map(|g| (0..16u16).map(move |l| (g, l)))
+            .filter(|&(g, l)| !used[g as usize][l as usize]);
+        for name in others {
+            let slot = free.next().unwrap_or((15, 15));
+            slots.insert(name, slot);
+        }
+
+        Self { slots }
+    }
+
+    fn slot(&self, name: &str) -> (u16, u16) {
+        self.slots.get(name).copied().unwrap_or((0, 0))
+    }
+}
+
+/// `G-L` 形式のレイヤー名を解析する
+fn parse_layer_name(name: &str) -> Option<(u16, u16)> {
+    let (g, l) = name.split_once('-')?;
+    if g.len() != 1 || l.len() != 1 {
+        return None;
+    }
+    Some((
+        u16::from_str_radix(g, 16).ok()?,
+        u16::from_str_radix(l, 16).ok()?,
+    ))
+}
+
+/// ブロック定義のネストの上限 (循環参照対策)
+const MAX_BLOCK_DEPTH: usize = 16;
+
+/// ブロック挿入と寸法を構成要素に分解する
+///
+/// ブロック内 (寸法図形内) のレイヤー "0" と色0 (BYBLOCK) は、挿入側 (寸法側) の
+/// レイヤーと色 (`parent`) を引き継ぐ。
+fn explode(
+    doc: &Document,
+    entity: &Entity,
+    affine: &Affine,
+    parent: Option<(&str, i32)>,
+    depth: usize,
+    out: &mut Vec<Entity>,
+) {
+    let mut entity = entity.clone();
+    if let Some((layer, color)) = parent {
+        inherit(&mut entity, layer, color);
+    }
+
+    match &entity {
+        Entity::Insert(insert) => {
+            let Some(block) = doc.blocks.iter().find(|b| b.name == insert.block_name) else {
+                return;
+            };
+            if depth >= MAX_BLOCK_DEPTH {
+                return;
+            }
+            let child = affine.then_insert(insert, block);
+            for e in &block.entities {
+                explode(doc, e, &child, Some((&insert.layer, insert.color)), depth + 1, out);
+            }
+        }
+        Entity::Dimension(dim) => {
+            for e in &dim.geometry {
+                explode(doc, e, affine, Some((&dim.layer, dim.color)), depth, out);
+            }
+        }
+        _ => out.push(transform(&entity, affine)),
+    }
+}
+
+/// 挿入側のレイヤーと色を引き継ぐ
+fn inherit(entity: &mut Entity, parent_layer: &str, parent_color: i32) {
+    let (layer, color) = match entity {
+        Entity::Line(e) => (&mut e.layer, &mut e.color),
+        Entity::Circle(e) => (&mut e.layer, &mut e.color),
+        Entity::Arc(e) => (&mut e.layer, &mut e.color),
+        Entity::Ellipse(e) => (&mut e.layer, &mut e.color),
+        Entity::Point(e) => (&mut e.layer, &mut e.color),
+        Entity::Text(e) => (&mut e.layer, &mut e.color),
+        Entity::Solid(e) => (&mut e.layer, &mut e.color),
+        Entity::Insert(e) => (&mut e.layer, &mut e.color),
+        Entity::Polyline(e) => (&mut e.layer, &mut e.color),
+        Entity::Dimension(e) => (&mut e.layer, &mut e.color),
+        Entity::Leader(e) => (&mut e.layer, &mut e.color),
+    };
+    if layer == "0" {
+        *layer = parent_layer.to_string();
+    }
+    if *color == 0 {
+        *color = parent_color;
+    }
+}
+
+/// 2次元アフィン変換 (x' = a x + b y + tx, y' = c x + d y + ty)
+#[derive(Debug, Clone, Copy)]
+struct Affine {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    tx: f64,
+    ty: f64,
+}
+
+impl Affine {
+    const IDENTITY: Affine = Affine {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        tx: 0.0,
+        ty: 0.0,
+    };
+
+    fn scale(sx: f64, sy: f64) -> Self {
+        Affine {
+            a: sx,
+            d: sy,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// ブロック挿入の変換 (基点を原点に移動 -> 拡大縮小 -> 回転 -> 挿入点へ移動) の後に `self` を適用する変換を返す
+    fn then_insert(&self, insert: &Insert, block: &Block) -> Self {
+        let (sin, cos) = insert.rotation.to_radians().sin_cos();
+        let (sx, sy) = (insert.scale_x, insert.scale_y);
+        let local = Affine {
+            a: cos * sx,
+            b: -sin * sy,
+            c: sin * sx,
+            d: cos * sy,
+            tx: 0.0,
+            ty: 0.0,
+        };
+        let (bx, by) = local.vector(block.base_x, block.base_y);
+        let local = Affine {
+            tx: insert.x - bx,
+            ty: insert.y - by,
+            ..local
+        };
+        self.compose(&local)
+    }
+
+    /// `other` を適用してから `self` を適用する変換を返す
+    fn compose(&self, other: &Affine) -> Self {
+        let (tx, ty) = self.point(other.tx, other.ty);
+        Affine {
+            a: self.a * other.a + self.b * other.c,
+            b: self.a * other.b + self.b * other.d,
+            c: self.c * other.a + self.d * other.c,
+            d: self.c * other.b + self.d * other.d,
+            tx,
+            ty,
+        }
+    }
+
+    fn point(&self, x: f64, y: f64) -> (f64, f64) {
+        let (vx, vy) = self.vector(x, y);
+        (vx + self.tx, vy + self.ty)
+    }
+
+    fn vector(&self, x: f64, y: f64) -> (f64, f64) {
+        (self.a * x + self.b * y, self.c * x + self.d * y)
+    }
+
+    fn determinant(&self) -> f64 {
+        self.a * self.d - self.b * self.c
+    }
+
+    /// 長さの拡大率の目安 (面積比の平方根)
+    fn length_scale(&self) -> f64 {
+        self.determinant().abs().sqrt()
+    }
+}
+
+/// 図形をアフィン変換する
+///
+/// 円・円弧は楕円として変換し、変換後に円であれば円・円弧に戻す。
+fn transform(entity: &Entity, m: &Affine) -> Entity {
+    let mut entity = entity.clone();
+    match &mut entity {
+        Entity::Line(e) => {
+            (e.x1, e.y1) = m.point(e.x1, e.y1);
+            (e.x2, e.y2) = m.point(e.x2, e.y2);
+        }
+        Entity::Point(e) => (e.x, e.y) = m.point(e.x, e.y),
+        Entity::Solid(e) => {
+            (e.x1, e.y1) = m.point(e.x1, e.y1);
+            (e.x2, e.y2) = m.point(e.x2, e.y2);
+            (e.x3, e.y3) = m.point(e.x3, e.y3);
+            (e.x4, e.y4) = m.point(e.x4, e.y4);
+        }
+        Entity::Polyline(e) => {
+            for v in &mut e.vertices {
+                (v.x, v.y) = m.point(v.x, v.y);
+            }
+        }
+        Entity::Leader(e) => {
+            for v in &mut e.vertices {
+                (v.x, v.y) = m.point(v.x, v.y);
+            }
+        }
+        Entity::Text(e) => {
+            let (sin, cos) = e.rotation.to_radians().sin_cos();
+            let (dx, dy) = m.vector(cos, sin);
+            (e.x, e.y) = m.point(e.x, e.y);
+            e.height *= m.length_scale();
+            e.rotation = dy.atan2(dx).to_degrees();
+        }
+        Entity::Insert(e) => (e.x, e.y) = m.point(e.x, e.y),
+        // 寸法は分解済み
+        Entity::Dimension(_) => {}
+        Entity::Circle(e) => {
+            let ellipse = transform_ellipse(&circle_as_ellipse(e), m);
+            return ellipse_or_circle(ellipse);
+        }
+        Entity::Arc(e) => {
+            let ellipse = transform_ellipse(&arc_as_ellipse(e), m);
+            return ellipse_or_circle(ellipse);
+        }
+        Entity::Ellipse(e) => return ellipse_or_circle(transform_ellipse(e, m)),
+    }
+    entity
+}
+
+fn circle_as_ellipse(e: &Circle) -> Ellipse {
+    Ellipse {
+        layer: e.layer.clone(),
+        color: e.color,
+        line_type: e.line_type.clone(),
+        line_type_scale: e.line_type_scale,
+        xdata: e.xdata.clone(),
+        center_x: e.center_x,
+        center_y: e.center_y,
+        major_axis_x: e.radius,
+        major_axis_y: 0.0,
+        minor_ratio: 1.0,
+        start_param: 0.0,
+        end_param: 2.0 * PI,
+    }
+}
+
+fn arc_as_ellipse(e: &Arc) -> Ellipse {
+    let start = e.start_angle.to_radians();
+    let sweep = match (e.end_angle - e.start_angle).rem_euclid(360.0) {
+        // 開始角度と終了角度が同じ場合は1周とみなす
+        0.0 => 360.0,
+        s => s,
+    };
+    let end = start + sweep.to_radians();
+    Ellipse {
+        layer: e.layer.clone(),
+        color: e.color,
+        line_type: e.line_type.clone(),
+        line_type_scale: e.line_type_scale,
+        xdata: e.xdata.clone(),
+        center_x: e.center_x,
+        center_y: e.center_y,
+        major_axis_x: e.radius,
+        major_axis_y: 0.0,
+        minor_ratio: 1.0,
+        start_param: start,
+        end_param: end,
+    }
+}
+
+/// 楕円をアフィン変換する
+///
+/// 変換後の共役半径から主軸を求め、媒介変数を付け替える。
+fn transform_ellipse(e: &Ellipse, m: &Affine) -> Ellipse {
+    let (mx, my) = (e.major_axis_x, e.major_axis_y);
+    let (nx, ny) = (-my * e.minor_ratio, mx * e.minor_ratio);
+    let u = m.vector(mx, my);
+    let v = m.vector(nx, ny);
+
+    // |u cos t + v sin t| が最大となる t0
+    let uv = u.0 * v.0 + u.1 * v.1;
+    let uu = u.0 * u.0 + u.1 * u.1;
+    let vv = v.0 * v.0 + v.1 * v.1;
+    let mut t0 = 0.5 * (2.0 * uv).atan2(uu - vv);
+    let mut major = (u.0 * t0.cos() + v.0 * t0.sin(), u.1 * t0.cos() + v.1 * t0.sin());
+    let mut minor = (-u.0 * t0.sin() + v.0 * t0.cos(), -u.1 * t0.sin() + v.1 * t0.cos());
+    if minor.0.hypot(minor.1) > major.0.hypot(major.1) {
+        (major, minor) = (minor, (-major.0, -major.1));
+        t0 += FRAC_PI_2;
+    }
+
+    let major_len = major.0.hypot(major.1);
+    let minor_len = minor.0.hypot(minor.1);
+    let ratio = if major_len > 0.0 { minor_len / major_len } else { 1.0 };
+    let (mut start, mut end) = (e.start_param - t0, e.end_param - t0);
+
+    // 反転した場合は媒介変数の向きが逆になる
+    if m.determinant() < 0.0 {
+        (start, end) = (-end, -start);
+    }
+
+    let (center_x, center_y) = m.point(e.center_x, e.center_y);
+    Ellipse {
+        center_x,
+        center_y,
+        major_axis_x: major.0,
+        major_axis_y: major.1,
+        minor_ratio: ratio,
+        start_param: start,
+        end_param: end,
+        ..e.clone()
+    }
+}
+
+/// 円とみなせる楕円を円・円弧に戻す
+fn ellipse_or_circle(e: Ellipse) -> Entity {
+    const EPSILON: f64 = 1e-9;
+    if (e.minor_ratio - 1.0).abs() > EPSILON {
+        return Entity::Ellipse(e);
+    }
+
+    let radius = e.major_axis_x.hypot(e.major_axis_y);
+    let rotation = e.major_axis_y.atan2(e.major_axis_x);
+    if e.end_param - e.start_param >= 2.0 * PI - EPSILON {
+        return Entity::Circle(Circle {
+            layer: e.layer,
+            color: e.color,
+            line_type: e.line_type,
+            line_type_scale: e.line_type_scale,
+            xdata: e.xdata,
+            center_x: e.center_x,
+            center_y: e.center_y,
+            radius,
+        });
+    }
+    Entity::Arc(Arc {
+        layer: e.layer,
+        color: e.color,
+        line_type: e.line_type,
+        line_type_scale: e.line_type_scale,
+        xdata: e.xdata,
+        center_x: e.center_x,
+        center_y: e.center_y,
+        radius,
+        start_angle: (e.start_param + rotation).to_degrees().rem_euclid(360.0),
+        end_angle: (e.end_param + rotation).to_degrees().rem_euclid(360.0),
+    })
+}
+
+/// DXFエンティティ (ブロック挿入を除く) をJWWエンティティに変換する
+fn convert_entity(doc: &Document, layers: &LayerMap, entity: &Entity, out: &mut Vec<JwwEntity>) {
+    let base = entity_base(doc, layers, entity);
+
+    match entity {
+        Entity::Line(e) => out.push(JwwEntity::Line(jww_core::Line {
+            base,
+            start: Point2D::new(e.x1, e.y1),
+            end: Point2D::new(e.x2, e.y2),
+        })),
+        Entity::Circle(e) => out.push(JwwEntity::Arc(jww_core::Arc {
+            base,
+            center: Point2D::new(e.center_x, e.center_y),
+            radius: e.radius,
+            start_angle: Angle::ZERO,
+            arc_angle: Angle::from_radians(2.0 * PI),
+            tilt_angle: Angle::ZERO,
+            flatness: 1.0,
+            is_full_circle: true,
+        })),
+        Entity::Arc(e) => {
+            let start = Angle::from_degrees(e.start_angle);
+            let sweep = Angle::from_degrees((e.end_angle - e.start_angle).rem_euclid(360.0));
+            out.push(JwwEntity::Arc(jww_core::Arc {
+                base,
+                center: Point2D::new(e.center_x, e.center_y),
+                radius: e.radius,
+                start_angle: start,
+                arc_angle: sweep,
+                tilt_angle: Angle::ZERO,
+                flatness: 1.0,
+                is_full_circle: false,
+            }));
+        }
+        Entity::Ellipse(e) => {
+            let tilt = e.major_axis_y.atan2(e.major_axis_x);
+            let sweep = e.end_param - e.start_param;
+            let closed = sweep.abs() >= 2.0 * PI - 1e-9;
+            // 媒介変数をX軸からの角度に戻す
+            let angle = |t: f64| tilt + (e.minor_ratio * t.sin()).atan2(t.cos());
+            let start = angle(e.start_param);
+            let end = angle(e.end_param);
+            out.push(JwwEntity::Arc(jww_core::Arc {
+                base,
+                center: Point2D::new(e.center_x, e.center_y),
+                radius: e.major_axis_x.hypot(e.major_axis_y),
+                start_angle: Angle::from_radians(if closed { 0.0 } else { start }),
+                arc_angle: Angle::from_radians(if closed {
+                    2.0 * PI
+                } else {
+                    (end - start).rem_euclid(2.0 * PI)
+                }),
+                tilt_angle: Angle::from_radians(tilt),
+                flatness: e.minor_ratio,
+                is_full_circle: closed,
+            }));
+        }
+        Entity::Point(e) => out.push(JwwEntity::Point(jww_core::Point {
+            base,
+            position: Point2D::new(e.x, e.y),
+            is_temporary: false,
+            code: 0,
+            angle: Angle::ZERO,
+            scale: 1.0,
+        })),
+        Entity::Text(e) => {
+            let width = e.height * e.content.chars().count() as f64;
+            let (sin, cos) = e.rotation.to_radians().sin_cos();
+            out.push(JwwEntity::Text(jww_core::Text {
+                base,
+                start: Point2D::new(e.x, e.y),
+                end: Point2D::new(e.x + width * cos, e.y + width * sin),
+                text_type: 0,
+                size_x: e.height,
+                size_y: e.height,
+                spacing: 0.0,
+                angle: Angle::from_degrees(e.rotation),
+                font_name: "ＭＳ ゴシック".into(),
+                content: e.content.clone().into(),
+            }));
+        }
+        Entity::Solid(e) => out.push(JwwEntity::Solid(jww_core::Solid {
+            base,
+            point1: Point2D::new(e.x1, e.y1),
+            point2: Point2D::new(e.x2, e.y2),
+            point3: Point2D::new(e.x3, e.y3),
+            point4: Point2D::new(e.x4, e.y4),
+            color: 0,
+        })),
+        Entity::Polyline(e) => {
+            let closing = e.closed.then(|| (e.vertices.last(), e.vertices.first()));
+            let segments = e
+                .vertices
+                .windows(2)
+                .map(|w| (&w[0], &w[1]))
+                .chain(closing.and_then(|(a, b)| Some((a?, b?))));
+            for (a, b) in segments {
+                out.push(JwwEntity::Line(jww_core::Line {
+                    base: base.clone(),
+                    start: Point2D::new(a.x, a.y),
+                    end: Point2D::new(b.x, b.y),
+                }));
+            }
+        }
+        // 矢印は出力しない
+        Entity::Leader(e) => {
+            for w in e.vertices.windows(2) {
+                out.push(JwwEntity::Line(jww_core::Line {
+                    base: base.clone(),
+                    start: Point2D::new(w[0].x, w[0].y),
+                    end: Point2D::new(w[1].x, w[1].y),
+                }));
+            }
+        }
+        // ブロック挿入と寸法は分解済み
+        Entity::Insert(_) | Entity::Dimension(_) => {}
+    }
+}
+
+/// エンティティのレイヤー名を返す
+fn entity_layer(entity: &Entity) -> &str {
+    match entity {
+        Entity::Line(e) => &e.layer,
+        Entity::Circle(e) => &e.layer,
+        Entity::Arc(e) => &e.layer,
+        Entity::Ellipse(e) => &e.layer,
+        Entity::Point(e) => &e.layer,
+        Entity::Text(e) => &e.layer,
+        Entity::Solid(e) => &e.layer,
+        Entity::Insert(e) => &e.layer,
+        Entity::Polyline(e) => &e.layer,
+        Entity::Dimension(e) => &e.layer,
+        Entity::Leader(e) => &e.layer,
+    }
+}
+
+/// 共通属性 (レイヤー・線色・線種) をJWWの基本属性に変換する
+fn entity_base(doc: &Document, layers: &LayerMap, entity: &Entity) -> EntityBase {
+    let (layer, color, line_type) = match entity {
+        Entity::Line(e) => (&e.layer, e.color, &e.line_type),
+        Entity::Circle(e) => (&e.layer, e.color, &e.line_type),
+        Entity::Arc(e) => (&e.layer, e.color, &e.line_type),
+        Entity::Ellipse(e) => (&e.layer, e.color, &e.line_type),
+        Entity::Point(e) => (&e.layer, e.color, &e.line_type),
+        Entity::Text(e) => (&e.layer, e.color, &e.line_type),
+        Entity::Solid(e) => (&e.layer, e.color, &e.line_type),
+        Entity::Insert(e) => (&e.layer, e.color, &e.line_type),
+        Entity::Polyline(e) => (&e.layer, e.color, &e.line_type),
+        Entity::Dimension(e) => (&e.layer, e.color, &e.line_type),
+        Entity::Leader(e) => (&e.layer, e.color, &e.line_type),
+    };
+    let dxf_layer = doc.layers.iter().find(|l| &l.name == layer);
+
+    // BYLAYER (256) / BYBLOCK (0) はレイヤーの設定を使用する
+    let color = match color {
+        0 | 256 => dxf_layer.map(|l| l.color).unwrap_or(7),
+        c => c,
+    };
+    let line_type = if line_type.eq_ignore_ascii_case("BYLAYER") || line_type.eq_ignore_ascii_case("BYBLOCK") {
+        dxf_layer.map(|l| l.line_type.as_str()).unwrap_or("CONTINUOUS")
+    } else {
+        line_type.as_str()
+    };
+
+    let (layer_group, layer) = layers.slot(layer);
+    EntityBase {
+        group: 0,
+        pen_style: map_pen_style(line_type),
+        pen_color: map_pen_color(color),
+        pen_width: 0,
+        layer,
+        layer_group,
+        flag: 0,
+        source: None,
+    }
+}
+
+/// ACIをJWW線色にマッピングする (`map_color` の逆変換)
+fn map_pen_color(aci: i32) -> u16 {
+    match aci.abs() {
+        1 => 8,
+        2 => 4,
+        3 => 3,
+        4 => 1,
+        5 => 6,
+        6 => 5,
+        8 => 9,
+        9 => 9,
+        c @ 10..=255 => (c - 10 + 100) as u16,
+        _ => 2,
+    }
+}
+
+/// DXF線種名をJWW線種にマッピングする (`map_line_type` の逆変換)
+fn map_pen_style(line_type: &str) -> u8 {
+    match line_type.to_ascii_uppercase().as_str() {
+        "DASHED" => 2,
+        "DASHDOT" => 3,
+        "CENTER" => 4,
+        "DOT" => 5,
+        "DASHEDX2" => 6,
+        "DASHDOTX2" => 7,
+        "CENTERX2" => 8,
+        "DOTX2" => 9,
+        _ => 1,
+    }
+}
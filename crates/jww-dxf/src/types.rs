@@ -3,18 +3,176 @@
 use serde::{Deserialize, Serialize};
 
 /// DXFドキュメント
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Document {
+    /// ヘッダー変数
+    pub header: Header,
+    /// 初期表示ビュー (*ACTIVE VPORT)
+    pub active_view: Option<ActiveView>,
     /// レイヤーリスト
     pub layers: Vec<Layer>,
     /// エンティティリスト
     pub entities: Vec<Entity>,
     /// ブロックリスト
     pub blocks: Vec<Block>,
+    /// ペーパー空間レイアウト
+    pub layout: Option<Layout>,
+    /// エンティティのグループ (OBJECTSセクションのGROUP)
+    #[serde(default)]
+    pub groups: Vec<Group>,
+    /// ファイル先頭に出力するコメント (グループコード999)
+    #[serde(default)]
+    pub comments: Vec<String>,
+    /// 寸法スタイル (DIMSTYLEテーブル、先頭が現在のスタイル)
+    #[serde(default)]
+    pub dim_styles: Vec<DimStyle>,
+}
+
+/// DXFのバージョン ($ACADVER)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub enum DxfVersion {
+    /// R12 (AC1009)
+    ///
+    /// LEADER・ELLIPSE、OBJECTSセクションとペーパー空間レイアウトは使えない。
+    /// これらを含む図面は [`Document::output_version`] によりR2000で出力される。
+    #[default]
+    R12,
+    /// R2000 (AC1015)
+    ///
+    /// テーブル・ブロック・エンティティ・オブジェクトに所有者ハンドルとサブクラスマーカーを付け、
+    /// BLOCK_RECORDテーブルとLAYOUTオブジェクトを出力する。
+    R2000,
+}
+
+impl DxfVersion {
+    /// $ACADVER の値を返す
+    pub fn acadver(self) -> &'static str {
+        match self {
+            DxfVersion::R12 => "AC1009",
+            DxfVersion::R2000 => "AC1015",
+        }
+    }
+
+    /// $ACADVER の値からバージョンを返す
+    ///
+    /// R13 (AC1012) 以降はR2000、それより前はR12として扱う。
+    pub fn from_acadver(acadver: &str) -> DxfVersion {
+        if acadver.trim() >= "AC1012" {
+            DxfVersion::R2000
+        } else {
+            DxfVersion::R12
+        }
+    }
+}
+
+/// DXFヘッダー変数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Header {
+    /// 出力するバージョン ($ACADVER)
+    ///
+    /// R12を指定しても、R2000が必要な内容を含む場合はR2000で出力する
+    /// ([`Document::output_version`])。
+    #[serde(default)]
+    pub version: DxfVersion,
+    /// 図面単位 ($INSUNITS)
+    pub insunits: i32,
+    /// 計測法 ($MEASUREMENT: 0=ヤード・ポンド法, 1=メートル法)
+    pub measurement: i32,
+    /// 全体線種尺度 ($LTSCALE)
+    pub ltscale: f64,
+    /// 点の表示スタイル ($PDMODE)
+    pub pdmode: i32,
+    /// 点の表示サイズ ($PDSIZE: 0は画面高さの5%、負値は画面に対する割合)
+    pub pdsize: f64,
+    /// 作成日時 ($TDCREATE: ユリウス日)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<f64>,
+    /// 更新日時 ($TDUPDATE: ユリウス日)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated: Option<f64>,
+    /// プロジェクト名 ($PROJECTNAME)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_name: Option<String>,
+    /// 図面範囲 ($LIMMIN/$LIMMAX)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<Limits>,
+}
+
+impl Default for Header {
+    fn default() -> Self {
+        Self {
+            version: DxfVersion::R12,
+            insunits: 4,
+            measurement: 1,
+            ltscale: 1.0,
+            pdmode: 0,
+            pdsize: 0.0,
+            created: None,
+            updated: None,
+            project_name: None,
+            limits: None,
+        }
+    }
+}
+
+/// モデル空間の図面範囲
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Limits {
+    /// 左下X
+    pub min_x: f64,
+    /// 左下Y
+    pub min_y: f64,
+    /// 右上X
+    pub max_x: f64,
+    /// 右上Y
+    pub max_y: f64,
+}
+
+/// 図面を開いた際の初期表示ビュー
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActiveView {
+    /// ビュー中心X
+    pub center_x: f64,
+    /// ビュー中心Y
+    pub center_y: f64,
+    /// ビュー高さ
+    pub height: f64,
+    /// 縦横比 (幅/高さ)
+    pub aspect_ratio: f64,
+}
+
+/// ペーパー空間レイアウト
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Layout {
+    /// 用紙幅
+    pub paper_width: f64,
+    /// 用紙高さ
+    pub paper_height: f64,
+    /// 図面を表示するビューポート
+    pub viewport: Viewport,
+}
+
+/// ペーパー空間ビューポート
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Viewport {
+    /// 用紙上の中心X
+    pub center_x: f64,
+    /// 用紙上の中心Y
+    pub center_y: f64,
+    /// 用紙上の幅
+    pub width: f64,
+    /// 用紙上の高さ
+    pub height: f64,
+    /// モデル空間のビュー中心X
+    pub view_center_x: f64,
+    /// モデル空間のビュー中心Y
+    pub view_center_y: f64,
+    /// モデル空間のビュー高さ
+    pub view_height: f64,
 }
 
 /// DXFレイヤー
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Layer {
     /// レイヤー名
     pub name: String,
@@ -28,8 +186,29 @@ pub struct Layer {
     pub locked: bool,
 }
 
+/// 寸法スタイル
+///
+/// 寸法の大きさは用紙上の値で、`scale` を掛けた値が図面上の大きさとなる。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DimStyle {
+    /// スタイル名
+    pub name: String,
+    /// 全体の尺度 (DIMSCALE)
+    pub scale: f64,
+    /// 矢印の大きさ (DIMASZ)
+    pub arrow_size: f64,
+    /// 補助線の起点からの離れ (DIMEXO)
+    pub extension_offset: f64,
+    /// 補助線の寸法線からのはみ出し (DIMEXE)
+    pub extension_extension: f64,
+    /// 寸法値と寸法線の間隔 (DIMGAP)
+    pub text_gap: f64,
+    /// 寸法値の文字高さ (DIMTXT)
+    pub text_height: f64,
+}
+
 /// DXFエンティティ
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum Entity {
     Line(Line),
@@ -40,10 +219,168 @@ pub enum Entity {
     Text(Text),
     Solid(Solid),
     Insert(Insert),
+    Polyline(Polyline),
+    Dimension(Dimension),
+    Leader(Leader),
+}
+
+impl Entity {
+    /// DXFエンティティタイプ名を返す
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Entity::Line(_) => "LINE",
+            Entity::Circle(_) => "CIRCLE",
+            Entity::Arc(_) => "ARC",
+            Entity::Ellipse(_) => "ELLIPSE",
+            Entity::Point(_) => "POINT",
+            Entity::Text(_) => "TEXT",
+            Entity::Solid(_) => "SOLID",
+            Entity::Insert(_) => "INSERT",
+            Entity::Polyline(_) => "POLYLINE",
+            Entity::Dimension(_) => "DIMENSION",
+            Entity::Leader(_) => "LEADER",
+        }
+    }
+
+    /// 拡張データを返す
+    pub fn xdata(&self) -> Option<&XData> {
+        match self {
+            Entity::Line(e) => e.xdata.as_ref(),
+            Entity::Circle(e) => e.xdata.as_ref(),
+            Entity::Arc(e) => e.xdata.as_ref(),
+            Entity::Ellipse(e) => e.xdata.as_ref(),
+            Entity::Point(e) => e.xdata.as_ref(),
+            Entity::Text(e) => e.xdata.as_ref(),
+            Entity::Solid(e) => e.xdata.as_ref(),
+            Entity::Insert(e) => e.xdata.as_ref(),
+            Entity::Polyline(e) => e.xdata.as_ref(),
+            Entity::Dimension(e) => e.xdata.as_ref(),
+            Entity::Leader(e) => e.xdata.as_ref(),
+        }
+    }
+}
+
+impl Document {
+    /// 出力するバージョンを返す
+    ///
+    /// ヘッダーで指定したバージョンを基本とし、LEADER・ELLIPSE (ブロック・寸法図形内を含む)、
+    /// グループまたはペーパー空間レイアウトを含む場合はR2000とする。
+    /// R12にはLAYOUTオブジェクトがないため、ペーパー空間は *Paper_Space ブロックと
+    /// LAYOUTを持つR2000のファイルとして出力する。
+    pub fn output_version(&self) -> DxfVersion {
+        let r2000 = !self.groups.is_empty()
+            || self.layout.is_some()
+            || requires_r2000(&self.entities)
+            || self.blocks.iter().any(|b| requires_r2000(&b.entities));
+        if r2000 {
+            DxfVersion::R2000
+        } else {
+            self.header.version
+        }
+    }
+
+    /// ブロック定義の基準点を原点に移す
+    ///
+    /// 構成要素を基準点の分だけ平行移動するため、ブロック挿入の挿入点はそのままで
+    /// 挿入された図形の位置も変わらない。基準点を無視するアプリケーションに渡すと
+    /// シンボルがずれる場合に使う。
+    pub fn rebase_blocks(&mut self) {
+        for block in &mut self.blocks {
+            if block.base_x == 0.0 && block.base_y == 0.0 {
+                continue;
+            }
+            for entity in &mut block.entities {
+                translate(entity, -block.base_x, -block.base_y);
+            }
+            block.base_x = 0.0;
+            block.base_y = 0.0;
+        }
+    }
+}
+
+/// R12にないエンティティを含むかどうか
+fn requires_r2000(entities: &[Entity]) -> bool {
+    entities.iter().any(|entity| match entity {
+        Entity::Ellipse(_) | Entity::Leader(_) => true,
+        Entity::Dimension(dim) => requires_r2000(&dim.geometry),
+        _ => false,
+    })
+}
+
+/// エンティティを平行移動する
+pub(crate) fn translate(entity: &mut Entity, dx: f64, dy: f64) {
+    match entity {
+        Entity::Line(e) => {
+            e.x1 += dx;
+            e.y1 += dy;
+            e.x2 += dx;
+            e.y2 += dy;
+        }
+        Entity::Circle(e) => {
+            e.center_x += dx;
+            e.center_y += dy;
+        }
+        Entity::Arc(e) => {
+            e.center_x += dx;
+            e.center_y += dy;
+        }
+        Entity::Ellipse(e) => {
+            e.center_x += dx;
+            e.center_y += dy;
+        }
+        Entity::Point(e) => {
+            e.x += dx;
+            e.y += dy;
+        }
+        Entity::Text(e) => {
+            e.x += dx;
+            e.y += dy;
+        }
+        Entity::Solid(e) => {
+            e.x1 += dx;
+            e.y1 += dy;
+            e.x2 += dx;
+            e.y2 += dy;
+            e.x3 += dx;
+            e.y3 += dy;
+            e.x4 += dx;
+            e.y4 += dy;
+        }
+        Entity::Polyline(e) => {
+            for v in &mut e.vertices {
+                v.x += dx;
+                v.y += dy;
+            }
+        }
+        Entity::Leader(e) => {
+            for v in &mut e.vertices {
+                v.x += dx;
+                v.y += dy;
+            }
+        }
+        Entity::Insert(e) => {
+            // 挿入するブロック定義の側は変更しない
+            e.x += dx;
+            e.y += dy;
+        }
+        Entity::Dimension(e) => {
+            e.def_x += dx;
+            e.def_y += dy;
+            e.text_x += dx;
+            e.text_y += dy;
+            e.ext1_x += dx;
+            e.ext1_y += dy;
+            e.ext2_x += dx;
+            e.ext2_y += dy;
+            for entity in &mut e.geometry {
+                translate(entity, dx, dy);
+            }
+        }
+    }
 }
 
 /// 直線
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Line {
     /// レイヤー名
     pub layer: String,
@@ -51,6 +388,12 @@ pub struct Line {
     pub color: i32,
     /// 線種
     pub line_type: String,
+    /// 線種尺度 (未指定の場合は1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_type_scale: Option<f64>,
+    /// 拡張データ (XDATA)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xdata: Option<XData>,
     /// 始点X
     pub x1: f64,
     /// 始点Y
@@ -62,7 +405,7 @@ pub struct Line {
 }
 
 /// 円
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Circle {
     /// レイヤー名
     pub layer: String,
@@ -70,6 +413,12 @@ pub struct Circle {
     pub color: i32,
     /// 線種
     pub line_type: String,
+    /// 線種尺度 (未指定の場合は1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_type_scale: Option<f64>,
+    /// 拡張データ (XDATA)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xdata: Option<XData>,
     /// 中心X
     pub center_x: f64,
     /// 中心Y
@@ -79,7 +428,7 @@ pub struct Circle {
 }
 
 /// 円弧
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Arc {
     /// レイヤー名
     pub layer: String,
@@ -87,6 +436,12 @@ pub struct Arc {
     pub color: i32,
     /// 線種
     pub line_type: String,
+    /// 線種尺度 (未指定の場合は1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_type_scale: Option<f64>,
+    /// 拡張データ (XDATA)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xdata: Option<XData>,
     /// 中心X
     pub center_x: f64,
     /// 中心Y
@@ -100,7 +455,7 @@ pub struct Arc {
 }
 
 /// 楕円
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Ellipse {
     /// レイヤー名
     pub layer: String,
@@ -108,6 +463,12 @@ pub struct Ellipse {
     pub color: i32,
     /// 線種
     pub line_type: String,
+    /// 線種尺度 (未指定の場合は1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_type_scale: Option<f64>,
+    /// 拡張データ (XDATA)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xdata: Option<XData>,
     /// 中心X
     pub center_x: f64,
     /// 中心Y
@@ -125,7 +486,7 @@ pub struct Ellipse {
 }
 
 /// 点
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Point {
     /// レイヤー名
     pub layer: String,
@@ -133,6 +494,12 @@ pub struct Point {
     pub color: i32,
     /// 線種
     pub line_type: String,
+    /// 線種尺度 (未指定の場合は1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_type_scale: Option<f64>,
+    /// 拡張データ (XDATA)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xdata: Option<XData>,
     /// X座標
     pub x: f64,
     /// Y座標
@@ -140,7 +507,7 @@ pub struct Point {
 }
 
 /// 文字
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Text {
     /// レイヤー名
     pub layer: String,
@@ -148,6 +515,12 @@ pub struct Text {
     pub color: i32,
     /// 線種
     pub line_type: String,
+    /// 線種尺度 (未指定の場合は1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_type_scale: Option<f64>,
+    /// 拡張データ (XDATA)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xdata: Option<XData>,
     /// 挿入点X
     pub x: f64,
     /// 挿入点Y
@@ -163,7 +536,7 @@ pub struct Text {
 }
 
 /// 塗りつぶし
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Solid {
     /// レイヤー名
     pub layer: String,
@@ -171,6 +544,12 @@ pub struct Solid {
     pub color: i32,
     /// 線種
     pub line_type: String,
+    /// 線種尺度 (未指定の場合は1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_type_scale: Option<f64>,
+    /// 拡張データ (XDATA)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xdata: Option<XData>,
     pub x1: f64,
     pub y1: f64,
     pub x2: f64,
@@ -182,7 +561,7 @@ pub struct Solid {
 }
 
 /// ブロック挿入
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Insert {
     /// レイヤー名
     pub layer: String,
@@ -190,6 +569,12 @@ pub struct Insert {
     pub color: i32,
     /// 線種
     pub line_type: String,
+    /// 線種尺度 (未指定の場合は1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_type_scale: Option<f64>,
+    /// 拡張データ (XDATA)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xdata: Option<XData>,
     /// ブロック名
     pub block_name: String,
     /// 挿入点X
@@ -204,8 +589,138 @@ pub struct Insert {
     pub rotation: f64,
 }
 
+/// ポリライン
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Polyline {
+    /// レイヤー名
+    pub layer: String,
+    /// 色番号
+    pub color: i32,
+    /// 線種
+    pub line_type: String,
+    /// 線種尺度 (未指定の場合は1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_type_scale: Option<f64>,
+    /// 拡張データ (XDATA)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xdata: Option<XData>,
+    /// 頂点リスト
+    pub vertices: Vec<Vertex>,
+    /// 閉じているかどうか
+    pub closed: bool,
+}
+
+/// 寸法 (平行寸法)
+///
+/// 寸法図形は出力時に無名ブロック (`*D`) として書き出される。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dimension {
+    /// レイヤー名
+    pub layer: String,
+    /// 色番号
+    pub color: i32,
+    /// 線種
+    pub line_type: String,
+    /// 線種尺度 (未指定の場合は1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_type_scale: Option<f64>,
+    /// 拡張データ (XDATA)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xdata: Option<XData>,
+    /// 寸法スタイル名
+    pub style: String,
+    /// 寸法線上の定義点X (2つ目の補助線側)
+    pub def_x: f64,
+    /// 寸法線上の定義点Y
+    pub def_y: f64,
+    /// 寸法値の中心X
+    pub text_x: f64,
+    /// 寸法値の中心Y
+    pub text_y: f64,
+    /// 1つ目の補助線の起点X
+    pub ext1_x: f64,
+    /// 1つ目の補助線の起点Y
+    pub ext1_y: f64,
+    /// 2つ目の補助線の起点X
+    pub ext2_x: f64,
+    /// 2つ目の補助線の起点Y
+    pub ext2_y: f64,
+    /// 寸法線の角度 (度)
+    pub angle: f64,
+    /// 寸法値の上書き (グループコード1、計測値をそのまま表示する場合はNone)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_override: Option<String>,
+    /// 寸法図形 (寸法線・補助線・矢印・寸法値)
+    pub geometry: Vec<Entity>,
+}
+
+/// 引出線
+///
+/// 注記の文字はTEXTのまま出力し、引出線と同じGROUPにまとめる。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Leader {
+    /// レイヤー名
+    pub layer: String,
+    /// 色番号
+    pub color: i32,
+    /// 線種
+    pub line_type: String,
+    /// 線種尺度 (未指定の場合は1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_type_scale: Option<f64>,
+    /// 拡張データ (XDATA)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xdata: Option<XData>,
+    /// 寸法スタイル名 (矢印の大きさ)
+    pub style: String,
+    /// 先端に矢印を付けるかどうか
+    pub arrowhead: bool,
+    /// 頂点リスト (矢印の先端から順)
+    pub vertices: Vec<Vertex>,
+}
+
+/// ポリラインの頂点
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Vertex {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// 拡張データ (XDATA)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XData {
+    /// 登録アプリケーション名 (APPIDテーブルに登録される)
+    pub app_name: String,
+    /// 値のリスト
+    pub values: Vec<XDataValue>,
+}
+
+/// 拡張データの値
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum XDataValue {
+    /// 文字列 (グループコード1000)
+    String(String),
+    /// 16ビット整数 (グループコード1070)
+    Integer(i16),
+    /// 32ビット整数 (グループコード1071)
+    Long(i32),
+    /// 実数 (グループコード1040)
+    Real(f64),
+}
+
+/// エンティティのグループ
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Group {
+    /// グループ名
+    pub name: String,
+    /// 説明
+    pub description: String,
+    /// 所属するエンティティ (`Document::entities` のインデックス)
+    pub entities: Vec<usize>,
+}
+
 /// DXFブロック定義
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Block {
     /// ブロック名
     pub name: String,
@@ -0,0 +1,634 @@
+//! DXFの構造検査
+//!
+//! テキスト形式のDXFについて、グループコードと値の対応、SECTION/ENDSEC・TABLE/ENDTAB・
+//! BLOCK/ENDBLK の対応、必須テーブル、名前の参照、`EOF` を検査する。
+//! $ACADVER に応じて、R12ではR13以降のエンティティ・セクションを、R13以降では
+//! ハンドル・所有者 (330)・サブクラスマーカー (100) を検査する。
+//! 構文に厳しいソフトウェアへ渡す前の確認に使う。
+//!
+//! ```
+//! let findings = jww_dxf::validate::validate("0\nSECTION\n2\nENTITIES\n0\nENDSEC\n0\nEOF\n");
+//! assert!(!jww_dxf::validate::has_errors(&findings));
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+pub use jww_core::Severity;
+
+use crate::bytes::{value_type, ValueType};
+use crate::types::DxfVersion;
+
+/// 検査結果の1件
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    /// 検査項目の識別子 (`unclosed_section` など)
+    pub code: &'static str,
+    pub message: String,
+    /// 対象のグループコードの行番号 (1始まり)
+    pub line: Option<usize>,
+}
+
+impl Finding {
+    fn new(severity: Severity, code: &'static str, message: String, line: Option<usize>) -> Self {
+        Self {
+            severity,
+            code,
+            message,
+            line,
+        }
+    }
+
+    fn error(code: &'static str, message: String, line: usize) -> Self {
+        Self::new(Severity::Error, code, message, Some(line))
+    }
+}
+
+/// 検査結果にエラーが含まれるかどうか
+pub fn has_errors(findings: &[Finding]) -> bool {
+    findings.iter().any(|f| f.severity == Severity::Error)
+}
+
+/// 既知のセクション名
+const KNOWN_SECTIONS: &[&str] = &[
+    "HEADER",
+    "CLASSES",
+    "TABLES",
+    "BLOCKS",
+    "ENTITIES",
+    "OBJECTS",
+    "THUMBNAILIMAGE",
+    "ACDSDATA",
+];
+
+/// TABLESセクションに必須のテーブル (エンティティのレイヤ・線種の参照先)
+const REQUIRED_TABLES: &[&str] = &["LTYPE", "LAYER"];
+
+/// R13で追加されたエンティティ (R12のファイルには出力できない)
+const R13_ENTITIES: &[&str] = &[
+    "ELLIPSE",
+    "LEADER",
+    "LWPOLYLINE",
+    "MTEXT",
+    "SPLINE",
+    "HATCH",
+    "MLINE",
+    "RAY",
+    "XLINE",
+    "TOLERANCE",
+    "IMAGE",
+    "REGION",
+    "3DSOLID",
+    "BODY",
+    "WIPEOUT",
+    "MULTILEADER",
+];
+
+/// グループコードと値の組
+struct Pair<'a> {
+    code: i32,
+    value: &'a str,
+    /// グループコードの行番号 (1始まり)
+    line: usize,
+}
+
+/// 名前の参照 (参照先のテーブル名・名前・行番号)
+struct Reference {
+    table: &'static str,
+    name: String,
+    line: usize,
+}
+
+/// テキスト形式のDXFを検査し、問題点を返す
+///
+/// 問題がなければ空を返す。バイナリ形式のDXFには対応しない。
+/// Shift_JISのバイト列は `String::from_utf8_lossy` で変換しても構造の検査には影響しない。
+pub fn validate(text: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let Some(pairs) = tokenize(text, &mut findings) else {
+        return findings;
+    };
+    check_structure(&pairs, &mut findings);
+    check_version(&pairs, &mut findings);
+    findings
+}
+
+/// 行をグループコードと値の組に分割し、値の型を検査する
+///
+/// グループコードが読めない場合は以降の対応が崩れるため `None` を返す。
+fn tokenize<'a>(text: &'a str, findings: &mut Vec<Finding>) -> Option<Vec<Pair<'a>>> {
+    let mut pairs = Vec::new();
+    let lines: Vec<&str> = text.lines().collect();
+    let mut lines = lines.iter().enumerate().peekable();
+    while let Some((index, code_line)) = lines.next() {
+        let line = index + 1;
+        let code_line = code_line.trim();
+        if code_line.is_empty() && lines.peek().is_none() {
+            // 末尾の空行
+            break;
+        }
+        let Ok(code) = code_line.parse::<i32>() else {
+            findings.push(Finding::error(
+                "invalid_group_code",
+                format!("invalid group code {:?}", code_line),
+                line,
+            ));
+            return None;
+        };
+        let Some((_, value)) = lines.next() else {
+            findings.push(Finding::error(
+                "missing_value",
+                format!("missing value for group code {}", code),
+                line,
+            ));
+            return None;
+        };
+        let value = value.trim_end_matches('\r');
+        if code == 999 {
+            continue;
+        }
+        match u16::try_from(code) {
+            Ok(code @ 0..=1071) => {
+                if !is_valid_value(code, value) {
+                    findings.push(Finding::error(
+                        "invalid_value",
+                        format!("invalid value {:?} for group code {}", value, code),
+                        line,
+                    ));
+                }
+            }
+            _ if (-5..0).contains(&code) => {}
+            _ => findings.push(Finding::error(
+                "invalid_group_code",
+                format!("group code out of range: {}", code),
+                line,
+            )),
+        }
+        pairs.push(Pair { code, value, line });
+    }
+    Some(pairs)
+}
+
+/// 値がグループコードの型として解釈できるかどうか
+fn is_valid_value(code: u16, value: &str) -> bool {
+    let value = value.trim();
+    match value_type(code) {
+        ValueType::String => true,
+        ValueType::Double => value.parse::<f64>().is_ok_and(f64::is_finite),
+        ValueType::Int16 => value.parse::<i16>().is_ok(),
+        ValueType::Int32 => value.parse::<i32>().is_ok(),
+        ValueType::Int64 => value.parse::<i64>().is_ok(),
+        ValueType::Bool => matches!(value, "0" | "1"),
+        ValueType::Chunk => {
+            value.len().is_multiple_of(2) && value.bytes().all(|b| b.is_ascii_hexdigit())
+        }
+    }
+}
+
+/// セクション・テーブル・ブロックの対応と名前の参照を検査する
+fn check_structure(pairs: &[Pair], findings: &mut Vec<Finding>) {
+    // 開いているセクション・テーブル・ブロック (名前と行番号)
+    let mut section: Option<(&str, usize)> = None;
+    let mut table: Option<(String, usize)> = None;
+    let mut block: Option<usize> = None;
+    // SEQENDで閉じる必要のあるPOLYLINEの行番号
+    let mut polyline: Option<usize> = None;
+    let mut sections: HashSet<&str> = HashSet::new();
+    // テーブル名ごとの定義済みの名前 (大文字)
+    let mut defined: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut blocks: HashSet<String> = HashSet::new();
+    let mut references = Vec::new();
+    // 現在のレコードの種類 (直前の0グループの値)
+    let mut record = "";
+    let mut eof = None;
+
+    let mut i = 0;
+    while i < pairs.len() {
+        let pair = &pairs[i];
+        i += 1;
+        if pair.code != 0 {
+            collect_reference(
+                pair,
+                record,
+                section,
+                table.as_ref(),
+                &mut defined,
+                &mut blocks,
+                &mut references,
+            );
+            continue;
+        }
+        if eof.is_some() {
+            findings.push(Finding::new(
+                Severity::Warning,
+                "data_after_eof",
+                "data after EOF".to_string(),
+                Some(pair.line),
+            ));
+            break;
+        }
+        let value = pair.value.trim();
+        if let Some(line) = polyline {
+            if !matches!(value, "VERTEX" | "SEQEND") {
+                findings.push(Finding::error(
+                    "missing_seqend",
+                    format!("POLYLINE at line {} is not terminated by SEQEND", line),
+                    line,
+                ));
+                polyline = None;
+            }
+        }
+        let previous = std::mem::replace(&mut record, value);
+        match value {
+            "SECTION" => {
+                if let Some((name, line)) = section.take() {
+                    findings.push(unclosed_section(name, line));
+                }
+                let name = match pairs.get(i) {
+                    Some(next) if next.code == 2 => {
+                        i += 1;
+                        next.value.trim()
+                    }
+                    _ => {
+                        findings.push(Finding::error(
+                            "missing_section_name",
+                            "SECTION without a name (group code 2)".to_string(),
+                            pair.line,
+                        ));
+                        ""
+                    }
+                };
+                if !name.is_empty() {
+                    if !sections.insert(name) {
+                        findings.push(Finding::error(
+                            "duplicate_section",
+                            format!("duplicate {} section", name),
+                            pair.line,
+                        ));
+                    }
+                    if !KNOWN_SECTIONS.contains(&name) {
+                        findings.push(Finding::new(
+                            Severity::Warning,
+                            "unknown_section",
+                            format!("unknown section {:?}", name),
+                            Some(pair.line),
+                        ));
+                    }
+                }
+                section = Some((name, pair.line));
+            }
+            "ENDSEC" => {
+                if section.is_none() {
+                    findings.push(Finding::error(
+                        "unexpected_endsec",
+                        "ENDSEC without SECTION".to_string(),
+                        pair.line,
+                    ));
+                }
+                if let Some((name, line)) = table.take() {
+                    findings.push(Finding::error(
+                        "unclosed_table",
+                        format!("{} table is not terminated by ENDTAB", name),
+                        line,
+                    ));
+                }
+                if let Some(line) = block.take() {
+                    findings.push(unclosed_block(line));
+                }
+                section = None;
+            }
+            "TABLE" => {
+                if !matches!(section, Some(("TABLES", _))) {
+                    findings.push(Finding::error(
+                        "table_outside_tables",
+                        "TABLE outside the TABLES section".to_string(),
+                        pair.line,
+                    ));
+                }
+                if let Some((name, line)) = table.take() {
+                    findings.push(Finding::error(
+                        "unclosed_table",
+                        format!("{} table is not terminated by ENDTAB", name),
+                        line,
+                    ));
+                }
+                let name = match pairs.get(i) {
+                    Some(next) if next.code == 2 => {
+                        i += 1;
+                        next.value.trim().to_ascii_uppercase()
+                    }
+                    _ => {
+                        findings.push(Finding::error(
+                            "missing_table_name",
+                            "TABLE without a name (group code 2)".to_string(),
+                            pair.line,
+                        ));
+                        String::new()
+                    }
+                };
+                defined.entry(name.clone()).or_default();
+                table = Some((name, pair.line));
+            }
+            "ENDTAB" if table.take().is_none() => {
+                findings.push(Finding::error(
+                    "unexpected_endtab",
+                    "ENDTAB without TABLE".to_string(),
+                    pair.line,
+                ));
+            }
+            "BLOCK" => {
+                if !matches!(section, Some(("BLOCKS", _))) {
+                    findings.push(Finding::error(
+                        "block_outside_blocks",
+                        "BLOCK outside the BLOCKS section".to_string(),
+                        pair.line,
+                    ));
+                }
+                if let Some(line) = block.replace(pair.line) {
+                    findings.push(unclosed_block(line));
+                }
+            }
+            "ENDBLK" if block.take().is_none() => {
+                findings.push(Finding::error(
+                    "unexpected_endblk",
+                    "ENDBLK without BLOCK".to_string(),
+                    pair.line,
+                ));
+            }
+            "POLYLINE" => polyline = Some(pair.line),
+            // POLYLINEの頂点、またはINSERTの属性の終わり
+            "SEQEND" if polyline.take().is_none() && !matches!(previous, "ATTRIB" | "INSERT") => {
+                findings.push(Finding::error(
+                    "unexpected_seqend",
+                    "SEQEND without POLYLINE or ATTRIB".to_string(),
+                    pair.line,
+                ));
+            }
+            // 対応するTABLE・BLOCK・POLYLINEを閉じた (ガードで処理済み)
+            "ENDTAB" | "ENDBLK" | "SEQEND" => {}
+            "EOF" => {
+                if let Some((name, line)) = section.take() {
+                    findings.push(unclosed_section(name, line));
+                }
+                eof = Some(pair.line);
+            }
+            _ if section.is_none() => {
+                findings.push(Finding::error(
+                    "outside_section",
+                    format!("{} outside any section", value),
+                    pair.line,
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(line) = polyline {
+        findings.push(Finding::error(
+            "missing_seqend",
+            format!("POLYLINE at line {} is not terminated by SEQEND", line),
+            line,
+        ));
+    }
+    if let Some((name, line)) = section {
+        findings.push(unclosed_section(name, line));
+    }
+    if eof.is_none() {
+        findings.push(Finding::new(
+            Severity::Error,
+            "missing_eof",
+            "missing EOF marker".to_string(),
+            None,
+        ));
+    }
+
+    if sections.contains("TABLES") {
+        for name in REQUIRED_TABLES {
+            if !defined.contains_key(*name) {
+                findings.push(Finding::new(
+                    Severity::Error,
+                    "missing_table",
+                    format!("missing {} table", name),
+                    None,
+                ));
+            }
+        }
+    }
+    if !sections.contains("ENTITIES") {
+        findings.push(Finding::new(
+            Severity::Warning,
+            "missing_section",
+            "missing ENTITIES section".to_string(),
+            None,
+        ));
+    }
+
+    for reference in references {
+        let found = match reference.table {
+            "BLOCK" => blocks.contains(&reference.name),
+            table => match defined.get(table) {
+                Some(names) => names.contains(&reference.name),
+                // テーブルがない場合は参照を検査しない
+                None => continue,
+            },
+        };
+        if found {
+            continue;
+        }
+        let finding = match reference.table {
+            // 未定義のレイヤは読み込み時に作成されることが多い
+            "LAYER" => Finding::new(
+                Severity::Warning,
+                "undefined_layer",
+                format!("undefined layer {:?}", reference.name),
+                Some(reference.line),
+            ),
+            "LTYPE" => Finding::error(
+                "undefined_linetype",
+                format!("undefined linetype {:?}", reference.name),
+                reference.line,
+            ),
+            _ => Finding::error(
+                "undefined_block",
+                format!("undefined block {:?}", reference.name),
+                reference.line,
+            ),
+        };
+        findings.push(finding);
+    }
+}
+
+/// 定義された名前と参照されている名前を集める
+fn collect_reference(
+    pair: &Pair,
+    record: &str,
+    section: Option<(&str, usize)>,
+    table: Option<&(String, usize)>,
+    defined: &mut HashMap<String, HashSet<String>>,
+    blocks: &mut HashSet<String>,
+    references: &mut Vec<Reference>,
+) {
+    let name = pair.value.trim().to_ascii_uppercase();
+    let reference = |table: &'static str| Reference {
+        table,
+        name: name.clone(),
+        line: pair.line,
+    };
+    match section.map(|(name, _)| name) {
+        Some("TABLES") => {
+            let Some((table, _)) = table else {
+                return;
+            };
+            // テーブルのエントリ (0グループの値がテーブル名と同じレコード) の名前
+            if pair.code == 2 && record.eq_ignore_ascii_case(table) {
+                if let Some(names) = defined.get_mut(table) {
+                    names.insert(name.clone());
+                }
+            }
+            if pair.code == 6 && table == "LAYER" && record == "LAYER" {
+                references.push(reference("LTYPE"));
+            }
+        }
+        Some("BLOCKS" | "ENTITIES") => match (pair.code, record) {
+            (2, "BLOCK") => {
+                blocks.insert(name.clone());
+            }
+            (2, "INSERT" | "DIMENSION") => references.push(reference("BLOCK")),
+            (8, _) if name != "0" => references.push(reference("LAYER")),
+            (6, _) if !matches!(name.as_str(), "BYLAYER" | "BYBLOCK") => {
+                references.push(reference("LTYPE"))
+            }
+            _ => {}
+        },
+        _ => {}
+    }
+}
+
+/// $ACADVER の値 (ない場合はR12) に応じてレコードの内容を検査する
+///
+/// R12ではR13以降のエンティティとCLASSES・OBJECTSセクションをエラーにする。
+/// R13以降では、テーブル・ブロック・エンティティ・オブジェクトのハンドル (5・105) と
+/// 所有者 (330)、エンティティの `AcDbEntity` マーカーを必須とし、
+/// 未定義のハンドルを指すポインタ (330-369) を警告にする。
+fn check_version(pairs: &[Pair], findings: &mut Vec<Finding>) {
+    let version = pairs
+        .windows(2)
+        .find(|w| w[0].code == 9 && w[0].value.trim() == "$ACADVER" && w[1].code == 1)
+        .map(|w| DxfVersion::from_acadver(w[1].value))
+        .unwrap_or_default();
+
+    let mut section = "";
+    let mut handles: HashSet<String> = HashSet::new();
+    // 参照先のハンドル (大文字) と行番号
+    let mut pointers = Vec::new();
+    let mut start = 0;
+    while start < pairs.len() {
+        let end = pairs[start + 1..]
+            .iter()
+            .position(|p| p.code == 0)
+            .map_or(pairs.len(), |n| start + 1 + n);
+        let record = &pairs[start..end];
+        let head = &record[0];
+        start = end;
+        if head.code != 0 {
+            continue;
+        }
+        let kind = head.value.trim();
+        match kind {
+            "SECTION" => {
+                section = record
+                    .get(1)
+                    .filter(|p| p.code == 2)
+                    .map_or("", |p| p.value.trim());
+                if version == DxfVersion::R12 && matches!(section, "CLASSES" | "OBJECTS") {
+                    findings.push(Finding::error(
+                        "requires_r13",
+                        format!("{} section requires R13 or later ($ACADVER)", section),
+                        head.line,
+                    ));
+                }
+                continue;
+            }
+            "ENDSEC" => {
+                section = "";
+                continue;
+            }
+            "ENDTAB" | "EOF" => continue,
+            _ => {}
+        }
+        let in_entities = matches!(section, "BLOCKS" | "ENTITIES");
+        if version == DxfVersion::R12 {
+            if in_entities && R13_ENTITIES.contains(&kind) {
+                findings.push(Finding::error(
+                    "requires_r13",
+                    format!("{} requires R13 or later ($ACADVER)", kind),
+                    head.line,
+                ));
+            }
+            continue;
+        }
+        if !in_entities && !matches!(section, "TABLES" | "OBJECTS") {
+            continue;
+        }
+
+        match record.iter().find(|p| p.code == 5 || p.code == 105) {
+            Some(handle) => {
+                handles.insert(handle.value.trim().to_ascii_uppercase());
+            }
+            None => findings.push(Finding::error(
+                "missing_handle",
+                format!("{} without a handle (group code 5)", kind),
+                head.line,
+            )),
+        }
+        if !record.iter().any(|p| p.code == 330) {
+            findings.push(Finding::error(
+                "missing_owner",
+                format!("{} without an owner (group code 330)", kind),
+                head.line,
+            ));
+        }
+        if in_entities && !record.iter().any(|p| p.code == 100 && p.value.trim() == "AcDbEntity") {
+            findings.push(Finding::error(
+                "missing_subclass_marker",
+                format!("{} without the AcDbEntity subclass marker (group code 100)", kind),
+                head.line,
+            ));
+        }
+        for pair in record.iter().filter(|p| (330..=369).contains(&p.code)) {
+            let handle = pair.value.trim().to_ascii_uppercase();
+            if handle != "0" {
+                pointers.push((handle, pair.line));
+            }
+        }
+    }
+
+    for (handle, line) in pointers {
+        if !handles.contains(&handle) {
+            findings.push(Finding::new(
+                Severity::Warning,
+                "dangling_handle",
+                format!("reference to undefined handle {}", handle),
+                Some(line),
+            ));
+        }
+    }
+}
+
+fn unclosed_section(name: &str, line: usize) -> Finding {
+    Finding::error(
+        "unclosed_section",
+        format!("{} section is not terminated by ENDSEC", name),
+        line,
+    )
+}
+
+fn unclosed_block(line: usize) -> Finding {
+    Finding::error(
+        "unclosed_block",
+        "BLOCK is not terminated by ENDBLK".to_string(),
+        line,
+    )
+}
@@ -1,19 +1,104 @@
 //! DXF文字列出力
 
-use crate::types::Document;
+use crate::types::{Dimension, Document, DxfVersion, Entity};
+use std::collections::HashMap;
 use std::fmt::Write;
 
+/// 線種定義 (名前, 説明, パターン)
+///
+/// パターンは用紙上のミリメートルで定義し、$LTSCALE で図面の縮尺に合わせる。
+/// 正の値は線分、負の値は空白、0は点を表す。
+pub(crate) const LINE_TYPES: &[(&str, &str, &[f64])] = &[
+    ("CONTINUOUS", "Solid line", &[]),
+    ("DASHED", "Dashed __ __ __", &[3.0, -1.5]),
+    ("DASHDOT", "Dash dot __ . __ .", &[6.0, -1.5, 0.0, -1.5]),
+    ("CENTER", "Center ____ _ ____", &[9.0, -1.5, 3.0, -1.5]),
+    ("DOT", "Dot . . . .", &[0.0, -1.5]),
+    ("DASHEDX2", "Dashed (2x) ____  ____", &[6.0, -3.0]),
+    ("DASHDOTX2", "Dash dot (2x) ____  .  ____", &[12.0, -3.0, 0.0, -3.0]),
+    ("CENTERX2", "Center (2x) ________  __  ________", &[18.0, -3.0, 6.0, -3.0]),
+    ("DOTX2", "Dot (2x) .  .  .  .", &[0.0, -3.0]),
+];
+
+/// R2000で追加する線種 (BYBLOCK・BYLAYERの参照先)
+const R2000_LINE_TYPES: &[(&str, &str, &[f64])] = &[("ByBlock", "", &[]), ("ByLayer", "", &[])];
+
 /// DXFドキュメントを文字列に変換する
+///
+/// エンティティハンドルは出力順に1から連番で割り当てるため、
+/// 同じドキュメントからは常に同じ文字列が得られる。
+/// バージョンは [`Document::output_version`] で決まる。
 pub fn to_string(doc: &Document) -> String {
+    write_document(doc, None)
+}
+
+/// DXFドキュメントを文字列に変換する (`codepage` は $DWGCODEPAGE に出力する値)
+pub(crate) fn write_document(doc: &Document, codepage: Option<&str>) -> String {
     let mut output = String::new();
+    let handseed = write_body(doc, &mut output);
 
-    // ヘッダーセクション
-    writeln!(output, "0").unwrap();
-    writeln!(output, "SECTION").unwrap();
-    writeln!(output, "2").unwrap();
-    writeln!(output, "HEADER").unwrap();
-    writeln!(output, "0").unwrap();
-    writeln!(output, "ENDSEC").unwrap();
+    // ハンドルの割り当てが確定してからヘッダーを出力する
+    let mut result = String::with_capacity(output.len() + 1024);
+    write_preamble(&mut result, doc, handseed, codepage);
+    result.push_str(&output);
+
+    result
+}
+
+/// DXFドキュメントを `output` に先頭から順に書き出す
+///
+/// [`to_string`] と同じ内容を、全体を1つの文字列にせずに出力する。ヘッダーの $HANDSEED を
+/// 求めるため、ヘッダーより後の部分を一度書き出さずに走査してから出力する。
+pub fn write_to(doc: &Document, output: &mut dyn Write) {
+    let handseed = write_body(doc, &mut Discard);
+    write_preamble(output, doc, handseed, None);
+    write_body(doc, output);
+}
+
+/// 書き込んだ内容を捨てる出力先
+struct Discard;
+
+impl Write for Discard {
+    fn write_str(&mut self, _: &str) -> std::fmt::Result {
+        Ok(())
+    }
+}
+
+/// コメントとヘッダーセクションを出力する
+fn write_preamble(output: &mut dyn Write, doc: &Document, handseed: u64, codepage: Option<&str>) {
+    for comment in &doc.comments {
+        // 999コメントは1行ごとに出力する
+        for line in comment.lines() {
+            writeln!(output, "999").unwrap();
+            writeln!(output, "{}", line).unwrap();
+        }
+    }
+    write_header(output, doc, doc.output_version(), handseed, codepage);
+}
+
+/// ヘッダーセクションより後 (CLASSESからEOFまで) を出力し、$HANDSEED の値を返す
+fn write_body(doc: &Document, output: &mut dyn Write) -> u64 {
+    let mut ctx = Context {
+        version: doc.output_version(),
+        handles: Handles::default(),
+        dimension_blocks: 0,
+        owner: 0,
+        paper_space: false,
+        reactors: Vec::new(),
+    };
+    let r2000 = ctx.version == DxfVersion::R2000;
+
+    // OBJECTSセクションのハンドルはBLOCK_RECORDから参照するため先に割り当てる
+    let objects = r2000.then(|| Objects::new(doc, &mut ctx.handles));
+
+    if r2000 {
+        writeln!(output, "0").unwrap();
+        writeln!(output, "SECTION").unwrap();
+        writeln!(output, "2").unwrap();
+        writeln!(output, "CLASSES").unwrap();
+        writeln!(output, "0").unwrap();
+        writeln!(output, "ENDSEC").unwrap();
+    }
 
     // テーブルセクション
     writeln!(output, "0").unwrap();
@@ -21,41 +106,75 @@ pub fn to_string(doc: &Document) -> String {
     writeln!(output, "2").unwrap();
     writeln!(output, "TABLES").unwrap();
 
+    // VPORTテーブル
+    if doc.active_view.is_some() || r2000 {
+        let table = write_table_start(output, &mut ctx, "VPORT", doc.active_view.iter().count());
+        if let Some(view) = &doc.active_view {
+            write_table_entry(output, &mut ctx, "VPORT", table);
+            writeln!(output, "2").unwrap();
+            writeln!(output, "*ACTIVE").unwrap();
+            writeln!(output, "70").unwrap();
+            writeln!(output, "0").unwrap();
+            writeln!(output, "10").unwrap();
+            writeln!(output, "0.0").unwrap();
+            writeln!(output, "20").unwrap();
+            writeln!(output, "0.0").unwrap();
+            writeln!(output, "11").unwrap();
+            writeln!(output, "1.0").unwrap();
+            writeln!(output, "21").unwrap();
+            writeln!(output, "1.0").unwrap();
+            writeln!(output, "12").unwrap();
+            writeln!(output, "{}", view.center_x).unwrap();
+            writeln!(output, "22").unwrap();
+            writeln!(output, "{}", view.center_y).unwrap();
+            writeln!(output, "40").unwrap();
+            writeln!(output, "{}", view.height).unwrap();
+            writeln!(output, "41").unwrap();
+            writeln!(output, "{}", view.aspect_ratio).unwrap();
+        }
+        writeln!(output, "0").unwrap();
+        writeln!(output, "ENDTAB").unwrap();
+    }
+
     // LTYPEテーブル
-    writeln!(output, "0").unwrap();
-    writeln!(output, "TABLE").unwrap();
-    writeln!(output, "2").unwrap();
-    writeln!(output, "LTYPE").unwrap();
-    writeln!(output, "70").unwrap();
-    writeln!(output, "1").unwrap();
-    writeln!(output, "0").unwrap();
-    writeln!(output, "LTYPE").unwrap();
-    writeln!(output, "2").unwrap();
-    writeln!(output, "CONTINUOUS").unwrap();
-    writeln!(output, "70").unwrap();
-    writeln!(output, "0").unwrap();
-    writeln!(output, "3").unwrap();
-    writeln!(output, "Solid line").unwrap();
-    writeln!(output, "72").unwrap();
-    writeln!(output, "65").unwrap();
-    writeln!(output, "73").unwrap();
-    writeln!(output, "0").unwrap();
-    writeln!(output, "40").unwrap();
-    writeln!(output, "0.0").unwrap();
+    let line_types: Vec<_> = if r2000 {
+        R2000_LINE_TYPES.iter().chain(LINE_TYPES).collect()
+    } else {
+        LINE_TYPES.iter().collect()
+    };
+    let table = write_table_start(output, &mut ctx, "LTYPE", line_types.len());
+    for (name, description, pattern) in line_types {
+        write_table_entry(output, &mut ctx, "LTYPE", table);
+        writeln!(output, "2").unwrap();
+        writeln!(output, "{}", name).unwrap();
+        writeln!(output, "70").unwrap();
+        writeln!(output, "0").unwrap();
+        writeln!(output, "3").unwrap();
+        writeln!(output, "{}", description).unwrap();
+        writeln!(output, "72").unwrap();
+        writeln!(output, "65").unwrap();
+        writeln!(output, "73").unwrap();
+        writeln!(output, "{}", pattern.len()).unwrap();
+        writeln!(output, "40").unwrap();
+        writeln!(output, "{}", pattern.iter().fold(0.0, |length, v| length + v.abs())).unwrap();
+        for element in pattern.iter() {
+            writeln!(output, "49").unwrap();
+            writeln!(output, "{}", element).unwrap();
+            if r2000 {
+                // 線分の種類 (0: 文字・図形を含まない)
+                writeln!(output, "74").unwrap();
+                writeln!(output, "0").unwrap();
+            }
+        }
+    }
     writeln!(output, "0").unwrap();
     writeln!(output, "ENDTAB").unwrap();
 
     // LAYERテーブル
-    writeln!(output, "0").unwrap();
-    writeln!(output, "TABLE").unwrap();
-    writeln!(output, "2").unwrap();
-    writeln!(output, "LAYER").unwrap();
-    writeln!(output, "70").unwrap();
-    writeln!(output, "{}", doc.layers.len() + 1).unwrap(); // +1 for required layer 0
+    let table = write_table_start(output, &mut ctx, "LAYER", doc.layers.len() + 1); // +1 for required layer 0
 
     // 必須レイヤー "0" (DXF仕様で必須)
-    writeln!(output, "0").unwrap();
-    writeln!(output, "LAYER").unwrap();
+    write_table_entry(output, &mut ctx, "LAYER", table);
     writeln!(output, "2").unwrap();
     writeln!(output, "0").unwrap();
     writeln!(output, "70").unwrap();
@@ -66,61 +185,186 @@ pub fn to_string(doc: &Document) -> String {
     writeln!(output, "CONTINUOUS").unwrap();
 
     for layer in &doc.layers {
-        writeln!(output, "0").unwrap();
-        writeln!(output, "LAYER").unwrap();
+        write_table_entry(output, &mut ctx, "LAYER", table);
         writeln!(output, "2").unwrap();
         writeln!(output, "{}", layer.name).unwrap();
+        // 1: フリーズ、4: ロック
         writeln!(output, "70").unwrap();
-        writeln!(output, "0").unwrap();
+        writeln!(output, "{}", (layer.frozen as i32) | (layer.locked as i32) << 2).unwrap();
         writeln!(output, "62").unwrap();
         writeln!(output, "{}", layer.color).unwrap();
         writeln!(output, "6").unwrap();
         writeln!(output, "{}", layer.line_type).unwrap();
-        if layer.frozen {
+    }
+
+    writeln!(output, "0").unwrap();
+    writeln!(output, "ENDTAB").unwrap();
+
+    if r2000 {
+        // STYLEテーブル (文字が参照する文字スタイル)
+        let styles = text_styles(doc);
+        let table = write_table_start(output, &mut ctx, "STYLE", styles.len());
+        for style in styles {
+            write_table_entry(output, &mut ctx, "STYLE", table);
+            writeln!(output, "2").unwrap();
+            writeln!(output, "{}", style).unwrap();
             writeln!(output, "70").unwrap();
-            writeln!(output, "1").unwrap();
+            writeln!(output, "0").unwrap();
+            writeln!(output, "40").unwrap();
+            writeln!(output, "0.0").unwrap();
+            writeln!(output, "41").unwrap();
+            writeln!(output, "1.0").unwrap();
+            writeln!(output, "50").unwrap();
+            writeln!(output, "0.0").unwrap();
+            writeln!(output, "71").unwrap();
+            writeln!(output, "0").unwrap();
+            writeln!(output, "42").unwrap();
+            writeln!(output, "2.5").unwrap();
+            writeln!(output, "3").unwrap();
+            writeln!(output, "txt").unwrap();
+            writeln!(output, "4").unwrap();
+            writeln!(output).unwrap();
         }
-        if layer.locked {
+        writeln!(output, "0").unwrap();
+        writeln!(output, "ENDTAB").unwrap();
+
+        // VIEW・UCSテーブル (エントリなし)
+        for name in ["VIEW", "UCS"] {
+            write_table_start(output, &mut ctx, name, 0);
+            writeln!(output, "0").unwrap();
+            writeln!(output, "ENDTAB").unwrap();
+        }
+    }
+
+    // APPIDテーブル (XDATAのアプリケーション名を登録する)
+    let app_names = xdata_app_names(doc);
+    if !app_names.is_empty() || r2000 {
+        let table = write_table_start(output, &mut ctx, "APPID", app_names.len() + 1); // +1 for ACAD
+
+        for app_name in std::iter::once("ACAD").chain(app_names.iter().copied()) {
+            write_table_entry(output, &mut ctx, "APPID", table);
+            writeln!(output, "2").unwrap();
+            writeln!(output, "{}", app_name).unwrap();
             writeln!(output, "70").unwrap();
-            writeln!(output, "4").unwrap();
+            writeln!(output, "0").unwrap();
         }
+
+        writeln!(output, "0").unwrap();
+        writeln!(output, "ENDTAB").unwrap();
     }
 
-    writeln!(output, "0").unwrap();
-    writeln!(output, "ENDTAB").unwrap();
+    // DIMSTYLEテーブル
+    if !doc.dim_styles.is_empty() || r2000 {
+        let table = write_table_start(output, &mut ctx, "DIMSTYLE", doc.dim_styles.len());
+
+        for style in &doc.dim_styles {
+            write_table_entry(output, &mut ctx, "DIMSTYLE", table);
+            writeln!(output, "2").unwrap();
+            writeln!(output, "{}", style.name).unwrap();
+            writeln!(output, "70").unwrap();
+            writeln!(output, "0").unwrap();
+            writeln!(output, "40").unwrap();
+            writeln!(output, "{}", style.scale).unwrap();
+            writeln!(output, "41").unwrap();
+            writeln!(output, "{}", style.arrow_size).unwrap();
+            writeln!(output, "42").unwrap();
+            writeln!(output, "{}", style.extension_offset).unwrap();
+            writeln!(output, "44").unwrap();
+            writeln!(output, "{}", style.extension_extension).unwrap();
+            writeln!(output, "140").unwrap();
+            writeln!(output, "{}", style.text_height).unwrap();
+            writeln!(output, "147").unwrap();
+            writeln!(output, "{}", style.text_gap).unwrap();
+        }
+
+        writeln!(output, "0").unwrap();
+        writeln!(output, "ENDTAB").unwrap();
+    }
+
+    // ブロックセクション
+    // 寸法の無名ブロックは、寸法エンティティの出力順 (ブロック内、モデル空間の順) に番号を付ける
+    let dimensions: Vec<&Dimension> = doc
+        .blocks
+        .iter()
+        .flat_map(|b| b.entities.iter())
+        .chain(doc.entities.iter())
+        .filter_map(|e| match e {
+            Entity::Dimension(dim) => Some(dim),
+            _ => None,
+        })
+        .collect();
+    let dimension_names: Vec<String> = (1..=dimensions.len()).map(|i| format!("*D{}", i)).collect();
+
+    // BLOCK_RECORDテーブル (R2000)
+    let mut records = BlockRecords::default();
+    if let Some(objects) = &objects {
+        let names = ["*Model_Space", "*Paper_Space"]
+            .into_iter()
+            .chain(dimension_names.iter().map(String::as_str))
+            .chain(doc.blocks.iter().map(|b| b.name.as_str()));
+        let count = 2 + dimension_names.len() + doc.blocks.len();
+        let table = write_table_start(output, &mut ctx, "BLOCK_RECORD", count);
+        for (i, name) in names.enumerate() {
+            let handle = write_table_entry(output, &mut ctx, "BLOCK_RECORD", table);
+            writeln!(output, "2").unwrap();
+            writeln!(output, "{}", name).unwrap();
+            match i {
+                0 => {
+                    writeln!(output, "340").unwrap();
+                    writeln!(output, "{:X}", objects.model_layout).unwrap();
+                }
+                1 => {
+                    writeln!(output, "340").unwrap();
+                    writeln!(output, "{:X}", objects.paper_layout).unwrap();
+                }
+                _ => {}
+            }
+            records.handles.push(handle);
+        }
+        writeln!(output, "0").unwrap();
+        writeln!(output, "ENDTAB").unwrap();
+    }
 
     // テーブルセクション終了
     writeln!(output, "0").unwrap();
     writeln!(output, "ENDSEC").unwrap();
 
-    // ブロックセクション
-    if !doc.blocks.is_empty() {
+    if !doc.blocks.is_empty() || !dimensions.is_empty() || r2000 {
         writeln!(output, "0").unwrap();
         writeln!(output, "SECTION").unwrap();
         writeln!(output, "2").unwrap();
         writeln!(output, "BLOCKS").unwrap();
 
-        for block in &doc.blocks {
-            writeln!(output, "0").unwrap();
-            writeln!(output, "BLOCK").unwrap();
-            writeln!(output, "8").unwrap();
-            writeln!(output, "0").unwrap();
-            writeln!(output, "2").unwrap();
-            writeln!(output, "{}", block.name).unwrap();
-            writeln!(output, "70").unwrap();
-            writeln!(output, "0").unwrap();
-            writeln!(output, "10").unwrap();
-            writeln!(output, "{}", block.base_x).unwrap();
-            writeln!(output, "20").unwrap();
-            writeln!(output, "{}", block.base_y).unwrap();
+        if r2000 {
+            // モデル空間・ペーパー空間のブロック (エンティティはENTITIESセクションに出力する)
+            ctx.owner = records.model_space();
+            write_block(output, "*Model_Space", 0, 0.0, 0.0, &[], &mut ctx);
+            ctx.owner = records.paper_space();
+            ctx.paper_space = true;
+            write_block(output, "*Paper_Space", 0, 0.0, 0.0, &[], &mut ctx);
+            ctx.paper_space = false;
+        }
 
-            // ブロック内のエンティティ
-            for entity in &block.entities {
-                write_entity(&mut output, entity);
-            }
+        // 寸法図形は参照元のブロックより先に定義する
+        for (i, (dim, name)) in dimensions.iter().zip(&dimension_names).enumerate() {
+            ctx.owner = records.block(i);
+            // 寸法図形内の寸法は無名ブロックを持たない
+            let dimension_blocks = std::mem::replace(&mut ctx.dimension_blocks, 0);
+            write_block(output, name, 1, 0.0, 0.0, &dim.geometry, &mut ctx);
+            ctx.dimension_blocks = dimension_blocks;
+        }
 
-            writeln!(output, "0").unwrap();
-            writeln!(output, "ENDBLK").unwrap();
+        for (i, block) in doc.blocks.iter().enumerate() {
+            ctx.owner = records.block(dimensions.len() + i);
+            write_block(
+                output,
+                &block.name,
+                0,
+                block.base_x,
+                block.base_y,
+                &block.entities,
+                &mut ctx,
+            );
         }
 
         writeln!(output, "0").unwrap();
@@ -133,34 +377,767 @@ pub fn to_string(doc: &Document) -> String {
     writeln!(output, "2").unwrap();
     writeln!(output, "ENTITIES").unwrap();
 
-    for entity in &doc.entities {
-        write_entity(&mut output, entity);
+    ctx.owner = records.model_space();
+    let mut entity_handles = Vec::with_capacity(doc.entities.len());
+    for (index, entity) in doc.entities.iter().enumerate() {
+        if let Some(objects) = &objects {
+            ctx.reactors = objects.groups_of(index);
+        }
+        entity_handles.push(ctx.handles.seed());
+        write_entity(output, entity, &mut ctx);
+    }
+
+    if let Some(layout) = &doc.layout {
+        ctx.owner = records.paper_space();
+        write_layout(output, layout, &mut ctx);
     }
 
     writeln!(output, "0").unwrap();
     writeln!(output, "ENDSEC").unwrap();
 
+    // オブジェクトセクション
+    if let Some(objects) = &objects {
+        write_objects(output, doc, objects, &records, &entity_handles);
+    }
+
     // ファイル終了
     writeln!(output, "0").unwrap();
     writeln!(output, "EOF").unwrap();
 
-    output
+    ctx.handles.seed()
 }
 
-/// エンティティをDXF形式で出力する
-fn write_entity(output: &mut String, entity: &crate::types::Entity) {
-    use crate::types::Entity;
+/// ヘッダーセクションを出力する
+fn write_header(output: &mut dyn Write, doc: &Document, version: DxfVersion, handseed: u64, codepage: Option<&str>) {
+    writeln!(output, "0").unwrap();
+    writeln!(output, "SECTION").unwrap();
+    writeln!(output, "2").unwrap();
+    writeln!(output, "HEADER").unwrap();
+    writeln!(output, "9").unwrap();
+    writeln!(output, "$ACADVER").unwrap();
+    writeln!(output, "1").unwrap();
+    writeln!(output, "{}", version.acadver()).unwrap();
+    if let Some(codepage) = codepage {
+        writeln!(output, "9").unwrap();
+        writeln!(output, "$DWGCODEPAGE").unwrap();
+        writeln!(output, "3").unwrap();
+        writeln!(output, "{}", codepage).unwrap();
+    }
+    if version == DxfVersion::R12 {
+        writeln!(output, "9").unwrap();
+        writeln!(output, "$HANDLING").unwrap();
+        writeln!(output, "70").unwrap();
+        writeln!(output, "1").unwrap();
+    }
+    writeln!(output, "9").unwrap();
+    writeln!(output, "$HANDSEED").unwrap();
+    writeln!(output, "5").unwrap();
+    writeln!(output, "{:X}", handseed).unwrap();
+    writeln!(output, "9").unwrap();
+    writeln!(output, "$INSUNITS").unwrap();
+    writeln!(output, "70").unwrap();
+    writeln!(output, "{}", doc.header.insunits).unwrap();
+    writeln!(output, "9").unwrap();
+    writeln!(output, "$MEASUREMENT").unwrap();
+    writeln!(output, "70").unwrap();
+    writeln!(output, "{}", doc.header.measurement).unwrap();
+    writeln!(output, "9").unwrap();
+    writeln!(output, "$LTSCALE").unwrap();
+    writeln!(output, "40").unwrap();
+    writeln!(output, "{}", doc.header.ltscale).unwrap();
+    writeln!(output, "9").unwrap();
+    writeln!(output, "$PDMODE").unwrap();
+    writeln!(output, "70").unwrap();
+    writeln!(output, "{}", doc.header.pdmode).unwrap();
+    writeln!(output, "9").unwrap();
+    writeln!(output, "$PDSIZE").unwrap();
+    writeln!(output, "40").unwrap();
+    writeln!(output, "{}", doc.header.pdsize).unwrap();
+    if let Some(limits) = &doc.header.limits {
+        for (name, x, y) in [
+            ("$LIMMIN", limits.min_x, limits.min_y),
+            ("$LIMMAX", limits.max_x, limits.max_y),
+        ] {
+            writeln!(output, "9").unwrap();
+            writeln!(output, "{}", name).unwrap();
+            writeln!(output, "10").unwrap();
+            writeln!(output, "{}", x).unwrap();
+            writeln!(output, "20").unwrap();
+            writeln!(output, "{}", y).unwrap();
+        }
+    }
+    // 日時はUTCで記録するため、ローカル時刻とUTCの変数に同じ値を出力する
+    if let Some(created) = doc.header.created {
+        for name in ["$TDCREATE", "$TDUCREATE"] {
+            writeln!(output, "9").unwrap();
+            writeln!(output, "{}", name).unwrap();
+            writeln!(output, "40").unwrap();
+            writeln!(output, "{}", created).unwrap();
+        }
+    }
+    if let Some(updated) = doc.header.updated {
+        for name in ["$TDUPDATE", "$TDUUPDATE"] {
+            writeln!(output, "9").unwrap();
+            writeln!(output, "{}", name).unwrap();
+            writeln!(output, "40").unwrap();
+            writeln!(output, "{}", updated).unwrap();
+        }
+    }
+    if let Some(project_name) = &doc.header.project_name {
+        writeln!(output, "9").unwrap();
+        writeln!(output, "$PROJECTNAME").unwrap();
+        writeln!(output, "1").unwrap();
+        writeln!(output, "{}", project_name).unwrap();
+    }
+    if let Some(style) = doc.dim_styles.first() {
+        writeln!(output, "9").unwrap();
+        writeln!(output, "$DIMSTYLE").unwrap();
+        writeln!(output, "2").unwrap();
+        writeln!(output, "{}", style.name).unwrap();
+    }
+    if let Some(layout) = &doc.layout {
+        writeln!(output, "9").unwrap();
+        writeln!(output, "$PLIMMIN").unwrap();
+        writeln!(output, "10").unwrap();
+        writeln!(output, "0.0").unwrap();
+        writeln!(output, "20").unwrap();
+        writeln!(output, "0.0").unwrap();
+        writeln!(output, "9").unwrap();
+        writeln!(output, "$PLIMMAX").unwrap();
+        writeln!(output, "10").unwrap();
+        writeln!(output, "{}", layout.paper_width).unwrap();
+        writeln!(output, "20").unwrap();
+        writeln!(output, "{}", layout.paper_height).unwrap();
+    }
+    writeln!(output, "0").unwrap();
+    writeln!(output, "ENDSEC").unwrap();
+}
+
+/// XDATAで使用されているアプリケーション名 (ACADを除く) を返す
+pub(crate) fn xdata_app_names(doc: &Document) -> std::collections::BTreeSet<&str> {
+    doc.entities
+        .iter()
+        .chain(doc.blocks.iter().flat_map(|b| b.entities.iter()))
+        .filter_map(|e| e.xdata())
+        .map(|xdata| xdata.app_name.as_str())
+        .filter(|name| *name != "ACAD")
+        .collect()
+}
+
+/// 文字が参照する文字スタイル名 (先頭は常にSTANDARD) を返す
+pub(crate) fn text_styles(doc: &Document) -> Vec<&str> {
+    fn collect<'a>(entities: &'a [Entity], styles: &mut std::collections::BTreeSet<&'a str>) {
+        for entity in entities {
+            match entity {
+                Entity::Text(text) => {
+                    styles.insert(&text.style);
+                }
+                Entity::Dimension(dim) => collect(&dim.geometry, styles),
+                _ => {}
+            }
+        }
+    }
 
+    let mut styles = std::collections::BTreeSet::new();
+    collect(&doc.entities, &mut styles);
+    for block in &doc.blocks {
+        collect(&block.entities, &mut styles);
+    }
+    std::iter::once("STANDARD")
+        .chain(styles.into_iter().filter(|s| !s.eq_ignore_ascii_case("STANDARD")))
+        .collect()
+}
+
+/// エンティティハンドルの連番
+#[derive(Default)]
+struct Handles {
+    last: u64,
+}
+
+impl Handles {
+    /// 次のハンドルを割り当てる
+    fn next(&mut self) -> u64 {
+        self.last += 1;
+        self.last
+    }
+
+    /// 次に割り当て可能なハンドル ($HANDSEED) を返す
+    fn seed(&self) -> u64 {
+        self.last + 1
+    }
+}
+
+/// 出力中の状態
+struct Context {
+    version: DxfVersion,
+    handles: Handles,
+    /// 寸法の無名ブロック名 (*D1, *D2, ...) の連番
+    dimension_blocks: usize,
+    /// 出力中のエンティティの所有者 (R2000のブロックレコードのハンドル)
+    owner: u64,
+    /// ペーパー空間のエンティティを出力中かどうか
+    paper_space: bool,
+    /// 次に出力するエンティティのリアクタ (所属するGROUPのハンドル)
+    reactors: Vec<u64>,
+}
+
+/// R2000のOBJECTSセクションのハンドル
+struct Objects {
+    /// ルート辞書
+    root: u64,
+    /// グループ辞書 (ACAD_GROUP)
+    groups: u64,
+    /// レイアウト辞書 (ACAD_LAYOUT)
+    layouts: u64,
+    /// モデル空間のLAYOUT
+    model_layout: u64,
+    /// ペーパー空間のLAYOUT
+    paper_layout: u64,
+    /// GROUPオブジェクト (`Document::groups` の順)
+    group_handles: Vec<u64>,
+    /// エンティティ (`Document::entities` の添字) から所属するGROUPのハンドルへの対応
+    entity_groups: HashMap<usize, Vec<u64>>,
+}
+
+impl Objects {
+    fn new(doc: &Document, handles: &mut Handles) -> Self {
+        let root = handles.next();
+        let groups = handles.next();
+        let layouts = handles.next();
+        let model_layout = handles.next();
+        let paper_layout = handles.next();
+        let group_handles: Vec<u64> = doc.groups.iter().map(|_| handles.next()).collect();
+        let mut entity_groups: HashMap<usize, Vec<u64>> = HashMap::new();
+        for (group, handle) in doc.groups.iter().zip(&group_handles) {
+            for &index in &group.entities {
+                let reactors = entity_groups.entry(index).or_default();
+                if reactors.last() != Some(handle) {
+                    reactors.push(*handle);
+                }
+            }
+        }
+        Self {
+            root,
+            groups,
+            layouts,
+            model_layout,
+            paper_layout,
+            group_handles,
+            entity_groups,
+        }
+    }
+
+    /// エンティティ (`Document::entities` の添字) が所属するGROUPのハンドル
+    fn groups_of(&self, index: usize) -> Vec<u64> {
+        self.entity_groups.get(&index).cloned().unwrap_or_default()
+    }
+}
+
+/// BLOCK_RECORDテーブルのハンドル
+///
+/// モデル空間、ペーパー空間、寸法の無名ブロック、ブロック定義の順。R12では空。
+#[derive(Default)]
+struct BlockRecords {
+    handles: Vec<u64>,
+}
+
+impl BlockRecords {
+    fn get(&self, index: usize) -> u64 {
+        self.handles.get(index).copied().unwrap_or(0)
+    }
+
+    fn model_space(&self) -> u64 {
+        self.get(0)
+    }
+
+    fn paper_space(&self) -> u64 {
+        self.get(1)
+    }
+
+    /// 寸法の無名ブロック・ブロック定義 (`index` はBLOCKSセクションでの出力順)
+    fn block(&self, index: usize) -> u64 {
+        self.get(2 + index)
+    }
+}
+
+/// テーブルの開始を出力し、テーブルのハンドル (R12では0) を返す
+fn write_table_start(output: &mut dyn Write, ctx: &mut Context, name: &str, count: usize) -> u64 {
+    writeln!(output, "0").unwrap();
+    writeln!(output, "TABLE").unwrap();
+    writeln!(output, "2").unwrap();
+    writeln!(output, "{}", name).unwrap();
+    let mut handle = 0;
+    if ctx.version == DxfVersion::R2000 {
+        handle = ctx.handles.next();
+        writeln!(output, "5").unwrap();
+        writeln!(output, "{:X}", handle).unwrap();
+        writeln!(output, "330").unwrap();
+        writeln!(output, "0").unwrap();
+        writeln!(output, "100").unwrap();
+        writeln!(output, "AcDbSymbolTable").unwrap();
+    }
+    writeln!(output, "70").unwrap();
+    writeln!(output, "{}", count).unwrap();
+    if ctx.version == DxfVersion::R2000 && name == "DIMSTYLE" {
+        writeln!(output, "100").unwrap();
+        writeln!(output, "AcDbDimStyleTable").unwrap();
+    }
+    handle
+}
+
+/// テーブルのエントリの開始を出力し、エントリのハンドル (R12では0) を返す
+///
+/// R2000ではハンドル・所有者 (`table`)・サブクラスマーカーを出力する。
+fn write_table_entry(output: &mut dyn Write, ctx: &mut Context, kind: &str, table: u64) -> u64 {
+    writeln!(output, "0").unwrap();
+    writeln!(output, "{}", kind).unwrap();
+    if ctx.version == DxfVersion::R12 {
+        return 0;
+    }
+    let handle = ctx.handles.next();
+    // DIMSTYLEのハンドルは105
+    writeln!(output, "{}", if kind == "DIMSTYLE" { 105 } else { 5 }).unwrap();
+    writeln!(output, "{:X}", handle).unwrap();
+    writeln!(output, "330").unwrap();
+    writeln!(output, "{:X}", table).unwrap();
+    writeln!(output, "100").unwrap();
+    writeln!(output, "AcDbSymbolTableRecord").unwrap();
+    let subclass = match kind {
+        "VPORT" => "AcDbViewportTableRecord",
+        "LTYPE" => "AcDbLinetypeTableRecord",
+        "LAYER" => "AcDbLayerTableRecord",
+        "STYLE" => "AcDbTextStyleTableRecord",
+        "APPID" => "AcDbRegAppTableRecord",
+        "DIMSTYLE" => "AcDbDimStyleTableRecord",
+        "BLOCK_RECORD" => "AcDbBlockTableRecord",
+        _ => unreachable!("unsupported table {}", kind),
+    };
+    writeln!(output, "100").unwrap();
+    writeln!(output, "{}", subclass).unwrap();
+    handle
+}
+
+/// OBJECTSセクション (ルート辞書、グループ、レイアウト) を出力する
+fn write_objects(output: &mut dyn Write, doc: &Document, objects: &Objects, records: &BlockRecords, entity_handles: &[u64]) {
+    writeln!(output, "0").unwrap();
+    writeln!(output, "SECTION").unwrap();
+    writeln!(output, "2").unwrap();
+    writeln!(output, "OBJECTS").unwrap();
+
+    // ルート辞書
+    write_dictionary(
+        output,
+        objects.root,
+        0,
+        &[],
+        [("ACAD_GROUP", objects.groups), ("ACAD_LAYOUT", objects.layouts)],
+    );
+
+    // グループ辞書
+    write_dictionary(
+        output,
+        objects.groups,
+        objects.root,
+        &[objects.root],
+        doc.groups
+            .iter()
+            .zip(&objects.group_handles)
+            .map(|(group, handle)| (group.name.as_str(), *handle)),
+    );
+
+    for (group, handle) in doc.groups.iter().zip(&objects.group_handles) {
+        writeln!(output, "0").unwrap();
+        writeln!(output, "GROUP").unwrap();
+        writeln!(output, "5").unwrap();
+        writeln!(output, "{:X}", handle).unwrap();
+        write_reactors(output, &[objects.groups]);
+        writeln!(output, "330").unwrap();
+        writeln!(output, "{:X}", objects.groups).unwrap();
+        writeln!(output, "100").unwrap();
+        writeln!(output, "AcDbGroup").unwrap();
+        writeln!(output, "300").unwrap();
+        writeln!(output, "{}", group.description).unwrap();
+        writeln!(output, "70").unwrap();
+        writeln!(output, "0").unwrap();
+        writeln!(output, "71").unwrap();
+        writeln!(output, "1").unwrap();
+        for index in &group.entities {
+            if let Some(entity_handle) = entity_handles.get(*index) {
+                writeln!(output, "340").unwrap();
+                writeln!(output, "{:X}", entity_handle).unwrap();
+            }
+        }
+    }
+
+    // レイアウト辞書とLAYOUT
+    write_dictionary(
+        output,
+        objects.layouts,
+        objects.root,
+        &[objects.root],
+        [("Model", objects.model_layout), ("Layout1", objects.paper_layout)],
+    );
+    let paper = doc
+        .layout
+        .as_ref()
+        .map(|layout| (layout.paper_width, layout.paper_height))
+        .unwrap_or((420.0, 297.0));
+    let model = (0.0, 0.0);
+    write_layout_object(output, objects.model_layout, objects.layouts, "Model", 0, records.model_space(), model);
+    write_layout_object(output, objects.paper_layout, objects.layouts, "Layout1", 1, records.paper_space(), paper);
+
+    writeln!(output, "0").unwrap();
+    writeln!(output, "ENDSEC").unwrap();
+}
+
+/// DICTIONARYオブジェクトを出力する (`owner` が0の場合はルート辞書)
+fn write_dictionary<'a>(
+    output: &mut dyn Write,
+    handle: u64,
+    owner: u64,
+    reactors: &[u64],
+    entries: impl IntoIterator<Item = (&'a str, u64)>,
+) {
+    writeln!(output, "0").unwrap();
+    writeln!(output, "DICTIONARY").unwrap();
+    writeln!(output, "5").unwrap();
+    writeln!(output, "{:X}", handle).unwrap();
+    write_reactors(output, reactors);
+    writeln!(output, "330").unwrap();
+    writeln!(output, "{:X}", owner).unwrap();
+    writeln!(output, "100").unwrap();
+    writeln!(output, "AcDbDictionary").unwrap();
+    for (name, entry) in entries {
+        writeln!(output, "3").unwrap();
+        writeln!(output, "{}", name).unwrap();
+        writeln!(output, "350").unwrap();
+        writeln!(output, "{:X}", entry).unwrap();
+    }
+}
+
+/// LAYOUTオブジェクトを出力する
+///
+/// 印刷設定は既定値 (プリンタなし、用紙単位mm、尺度1:1) とし、`paper` を用紙の大きさと範囲にする。
+fn write_layout_object(
+    output: &mut dyn Write,
+    handle: u64,
+    owner: u64,
+    name: &str,
+    tab_order: i32,
+    block_record: u64,
+    (paper_width, paper_height): (f64, f64),
+) {
+    writeln!(output, "0").unwrap();
+    writeln!(output, "LAYOUT").unwrap();
+    writeln!(output, "5").unwrap();
+    writeln!(output, "{:X}", handle).unwrap();
+    write_reactors(output, &[owner]);
+    writeln!(output, "330").unwrap();
+    writeln!(output, "{:X}", owner).unwrap();
+    writeln!(output, "100").unwrap();
+    writeln!(output, "AcDbPlotSettings").unwrap();
+    writeln!(output, "1").unwrap();
+    writeln!(output).unwrap();
+    writeln!(output, "2").unwrap();
+    writeln!(output, "none_device").unwrap();
+    writeln!(output, "4").unwrap();
+    writeln!(output).unwrap();
+    writeln!(output, "6").unwrap();
+    writeln!(output).unwrap();
+    // 余白 (左・下・右・上) と用紙の大きさ
+    for (code, value) in [(40, 0.0), (41, 0.0), (42, 0.0), (43, 0.0), (44, paper_width), (45, paper_height)] {
+        writeln!(output, "{}", code).unwrap();
+        writeln!(output, "{}", value).unwrap();
+    }
+    // 印刷原点・印刷範囲と尺度 (1:1)
+    for (code, value) in [(46, 0.0), (47, 0.0), (48, 0.0), (49, 0.0), (140, 0.0), (141, 0.0), (142, 1.0), (143, 1.0)] {
+        writeln!(output, "{}", code).unwrap();
+        writeln!(output, "{}", value).unwrap();
+    }
+    // 印刷フラグ、用紙単位 (1: mm)、回転なし、印刷対象 (5: レイアウト)、尺度 (16: 1:1)
+    for (code, value) in [(70, 688), (72, 1), (73, 0), (74, 5)] {
+        writeln!(output, "{}", code).unwrap();
+        writeln!(output, "{}", value).unwrap();
+    }
+    writeln!(output, "7").unwrap();
+    writeln!(output).unwrap();
+    writeln!(output, "75").unwrap();
+    writeln!(output, "16").unwrap();
+    for (code, value) in [(147, 1.0), (148, 0.0), (149, 0.0)] {
+        writeln!(output, "{}", code).unwrap();
+        writeln!(output, "{}", value).unwrap();
+    }
+    writeln!(output, "100").unwrap();
+    writeln!(output, "AcDbLayout").unwrap();
+    writeln!(output, "1").unwrap();
+    writeln!(output, "{}", name).unwrap();
+    writeln!(output, "70").unwrap();
+    writeln!(output, "1").unwrap();
+    writeln!(output, "71").unwrap();
+    writeln!(output, "{}", tab_order).unwrap();
+    // 図面範囲・挿入基点・範囲・高度・UCS (原点、X軸、Y軸)
+    for (code, value) in [
+        (10, 0.0),
+        (20, 0.0),
+        (11, paper_width),
+        (21, paper_height),
+        (12, 0.0),
+        (22, 0.0),
+        (32, 0.0),
+        (14, 0.0),
+        (24, 0.0),
+        (34, 0.0),
+        (15, paper_width),
+        (25, paper_height),
+        (35, 0.0),
+        (146, 0.0),
+        (13, 0.0),
+        (23, 0.0),
+        (33, 0.0),
+        (16, 1.0),
+        (26, 0.0),
+        (36, 0.0),
+        (17, 0.0),
+        (27, 1.0),
+        (37, 0.0),
+    ] {
+        writeln!(output, "{}", code).unwrap();
+        writeln!(output, "{}", value).unwrap();
+    }
+    writeln!(output, "76").unwrap();
+    writeln!(output, "0").unwrap();
+    writeln!(output, "330").unwrap();
+    writeln!(output, "{:X}", block_record).unwrap();
+}
+
+/// ペーパー空間のビューポートを出力する
+///
+/// ID 1 は用紙全体を表すビューポート、ID 2 がモデル空間を表示するビューポート。
+fn write_layout(output: &mut dyn Write, layout: &crate::types::Layout, ctx: &mut Context) {
+    let paper = crate::types::Viewport {
+        center_x: layout.paper_width / 2.0,
+        center_y: layout.paper_height / 2.0,
+        width: layout.paper_width,
+        height: layout.paper_height,
+        view_center_x: layout.paper_width / 2.0,
+        view_center_y: layout.paper_height / 2.0,
+        view_height: layout.paper_height,
+    };
+    write_viewport(output, 1, 1, &paper, ctx);
+    write_viewport(output, 2, 2, &layout.viewport, ctx);
+}
+
+/// VIEWPORTエンティティを出力する
+///
+/// ペーパー空間はR2000でのみ出力する ([`crate::types::Document::output_version`])。
+fn write_viewport(output: &mut dyn Write, id: i32, status: i32, vp: &crate::types::Viewport, ctx: &mut Context) {
+    writeln!(output, "0").unwrap();
+    writeln!(output, "VIEWPORT").unwrap();
+    writeln!(output, "5").unwrap();
+    writeln!(output, "{:X}", ctx.handles.next()).unwrap();
+    writeln!(output, "330").unwrap();
+    writeln!(output, "{:X}", ctx.owner).unwrap();
+    writeln!(output, "100").unwrap();
+    writeln!(output, "AcDbEntity").unwrap();
+    writeln!(output, "67").unwrap();
+    writeln!(output, "1").unwrap();
+    writeln!(output, "8").unwrap();
+    writeln!(output, "0").unwrap();
+    writeln!(output, "100").unwrap();
+    writeln!(output, "AcDbViewport").unwrap();
+    writeln!(output, "10").unwrap();
+    writeln!(output, "{}", vp.center_x).unwrap();
+    writeln!(output, "20").unwrap();
+    writeln!(output, "{}", vp.center_y).unwrap();
+    writeln!(output, "30").unwrap();
+    writeln!(output, "0.0").unwrap();
+    writeln!(output, "40").unwrap();
+    writeln!(output, "{}", vp.width).unwrap();
+    writeln!(output, "41").unwrap();
+    writeln!(output, "{}", vp.height).unwrap();
+    writeln!(output, "68").unwrap();
+    writeln!(output, "{}", status).unwrap();
+    writeln!(output, "69").unwrap();
+    writeln!(output, "{}", id).unwrap();
+    writeln!(output, "12").unwrap();
+    writeln!(output, "{}", vp.view_center_x).unwrap();
+    writeln!(output, "22").unwrap();
+    writeln!(output, "{}", vp.view_center_y).unwrap();
+    writeln!(output, "45").unwrap();
+    writeln!(output, "{}", vp.view_height).unwrap();
+}
+
+/// ブロック定義を出力する (`flags` は1で無名ブロック)
+///
+/// R2000では `ctx.owner` をブロックレコードとして参照する。
+fn write_block(
+    output: &mut dyn Write,
+    name: &str,
+    flags: i32,
+    base_x: f64,
+    base_y: f64,
+    entities: &[Entity],
+    ctx: &mut Context,
+) {
+    let r2000 = ctx.version == DxfVersion::R2000;
+    writeln!(output, "0").unwrap();
+    writeln!(output, "BLOCK").unwrap();
+    if r2000 {
+        write_owner(output, ctx, "AcDbEntity");
+    }
+    writeln!(output, "8").unwrap();
+    writeln!(output, "0").unwrap();
+    if r2000 {
+        writeln!(output, "100").unwrap();
+        writeln!(output, "AcDbBlockBegin").unwrap();
+    }
+    writeln!(output, "2").unwrap();
+    writeln!(output, "{}", name).unwrap();
+    writeln!(output, "70").unwrap();
+    writeln!(output, "{}", flags).unwrap();
+    writeln!(output, "10").unwrap();
+    writeln!(output, "{}", base_x).unwrap();
+    writeln!(output, "20").unwrap();
+    writeln!(output, "{}", base_y).unwrap();
+    if r2000 {
+        writeln!(output, "30").unwrap();
+        writeln!(output, "0.0").unwrap();
+        writeln!(output, "3").unwrap();
+        writeln!(output, "{}", name).unwrap();
+        writeln!(output, "1").unwrap();
+        writeln!(output).unwrap();
+    }
+
+    // ブロック内のエンティティ
+    for entity in entities {
+        write_entity(output, entity, ctx);
+    }
+
+    writeln!(output, "0").unwrap();
+    writeln!(output, "ENDBLK").unwrap();
+    if r2000 {
+        write_owner(output, ctx, "AcDbEntity");
+        writeln!(output, "8").unwrap();
+        writeln!(output, "0").unwrap();
+        writeln!(output, "100").unwrap();
+        writeln!(output, "AcDbBlockEnd").unwrap();
+    }
+}
+
+/// 永続リアクタ (102 {ACAD_REACTORS) を出力する (`reactors` が空の場合は何も出力しない)
+fn write_reactors(output: &mut dyn Write, reactors: &[u64]) {
+    if reactors.is_empty() {
+        return;
+    }
+    writeln!(output, "102").unwrap();
+    writeln!(output, "{{ACAD_REACTORS").unwrap();
+    for reactor in reactors {
+        writeln!(output, "330").unwrap();
+        writeln!(output, "{:X}", reactor).unwrap();
+    }
+    writeln!(output, "102").unwrap();
+    writeln!(output, "}}").unwrap();
+}
+
+/// R2000のハンドル・リアクタ・所有者・最初のサブクラスマーカーを出力する
+///
+/// `ctx.reactors` は出力後に空にする。
+/// ペーパー空間のエンティティは `AcDbEntity` の後に67を出力する。
+fn write_owner(output: &mut dyn Write, ctx: &mut Context, subclass: &str) -> u64 {
+    let handle = ctx.handles.next();
+    writeln!(output, "5").unwrap();
+    writeln!(output, "{:X}", handle).unwrap();
+    write_reactors(output, &std::mem::take(&mut ctx.reactors));
+    writeln!(output, "330").unwrap();
+    writeln!(output, "{:X}", ctx.owner).unwrap();
+    writeln!(output, "100").unwrap();
+    writeln!(output, "{}", subclass).unwrap();
+    if ctx.paper_space {
+        writeln!(output, "67").unwrap();
+        writeln!(output, "1").unwrap();
+    }
+    handle
+}
+
+/// エンティティの種別・ハンドル・レイヤー・色・線種を出力し、ハンドルを返す
+///
+/// R2000では所有者と `AcDbEntity`・`subclass` のサブクラスマーカーも出力する。
+fn write_entity_start(output: &mut dyn Write, ctx: &mut Context, entity: &Entity, subclass: &str) -> u64 {
+    let (layer, color, line_type, line_type_scale) = match entity {
+        Entity::Line(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+        Entity::Circle(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+        Entity::Arc(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+        Entity::Ellipse(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+        Entity::Point(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+        Entity::Text(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+        Entity::Solid(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+        Entity::Insert(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+        Entity::Polyline(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+        Entity::Dimension(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+        Entity::Leader(e) => (&e.layer, e.color, &e.line_type, e.line_type_scale),
+    };
+    let r2000 = ctx.version == DxfVersion::R2000;
+
+    writeln!(output, "0").unwrap();
+    writeln!(output, "{}", entity.type_name()).unwrap();
+    let handle = if r2000 {
+        write_owner(output, ctx, "AcDbEntity")
+    } else {
+        let handle = ctx.handles.next();
+        writeln!(output, "5").unwrap();
+        writeln!(output, "{:X}", handle).unwrap();
+        handle
+    };
+    writeln!(output, "8").unwrap();
+    writeln!(output, "{}", layer).unwrap();
+    writeln!(output, "62").unwrap();
+    writeln!(output, "{}", color).unwrap();
+    writeln!(output, "6").unwrap();
+    writeln!(output, "{}", line_type).unwrap();
+    if let Some(scale) = line_type_scale {
+        writeln!(output, "48").unwrap();
+        writeln!(output, "{}", scale).unwrap();
+    }
+    if r2000 {
+        writeln!(output, "100").unwrap();
+        writeln!(output, "{}", subclass).unwrap();
+    }
+    handle
+}
+
+/// R2000のサブクラスマーカーを出力する (R12では何も出力しない)
+fn write_subclass(output: &mut dyn Write, ctx: &Context, subclass: &str) {
+    if ctx.version == DxfVersion::R2000 {
+        writeln!(output, "100").unwrap();
+        writeln!(output, "{}", subclass).unwrap();
+    }
+}
+
+/// ポリラインの頂点・終端 (VERTEX・SEQEND) の種別・ハンドル・レイヤーを出力する
+///
+/// R2000ではポリライン (`owner`) を所有者とする。
+fn write_sub_entity_start(output: &mut dyn Write, ctx: &mut Context, kind: &str, owner: u64, layer: &str) {
+    writeln!(output, "0").unwrap();
+    writeln!(output, "{}", kind).unwrap();
+    if ctx.version == DxfVersion::R2000 {
+        let entity_owner = std::mem::replace(&mut ctx.owner, owner);
+        write_owner(output, ctx, "AcDbEntity");
+        ctx.owner = entity_owner;
+    } else {
+        writeln!(output, "5").unwrap();
+        writeln!(output, "{:X}", ctx.handles.next()).unwrap();
+    }
+    writeln!(output, "8").unwrap();
+    writeln!(output, "{}", layer).unwrap();
+}
+
+/// エンティティをDXF形式で出力する
+///
+/// 寸法エンティティは出力するたびに `ctx.dimension_blocks` を進め、対応する無名ブロックを参照する。
+fn write_entity(output: &mut dyn Write, entity: &Entity, ctx: &mut Context) {
     match entity {
         Entity::Line(line) => {
-            writeln!(output, "0").unwrap();
-            writeln!(output, "LINE").unwrap();
-            writeln!(output, "8").unwrap();
-            writeln!(output, "{}", line.layer).unwrap();
-            writeln!(output, "62").unwrap();
-            writeln!(output, "{}", line.color).unwrap();
-            writeln!(output, "6").unwrap();
-            writeln!(output, "{}", line.line_type).unwrap();
+            write_entity_start(output, ctx, entity, "AcDbLine");
             writeln!(output, "10").unwrap();
             writeln!(output, "{}", line.x1).unwrap();
             writeln!(output, "20").unwrap();
@@ -169,55 +1146,38 @@ fn write_entity(output: &mut String, entity: &crate::types::Entity) {
             writeln!(output, "{}", line.x2).unwrap();
             writeln!(output, "21").unwrap();
             writeln!(output, "{}", line.y2).unwrap();
+            write_xdata(output, &line.xdata);
         }
 
         Entity::Circle(circle) => {
-            writeln!(output, "0").unwrap();
-            writeln!(output, "CIRCLE").unwrap();
-            writeln!(output, "8").unwrap();
-            writeln!(output, "{}", circle.layer).unwrap();
-            writeln!(output, "62").unwrap();
-            writeln!(output, "{}", circle.color).unwrap();
-            writeln!(output, "6").unwrap();
-            writeln!(output, "{}", circle.line_type).unwrap();
+            write_entity_start(output, ctx, entity, "AcDbCircle");
             writeln!(output, "10").unwrap();
             writeln!(output, "{}", circle.center_x).unwrap();
             writeln!(output, "20").unwrap();
             writeln!(output, "{}", circle.center_y).unwrap();
             writeln!(output, "40").unwrap();
             writeln!(output, "{}", circle.radius).unwrap();
+            write_xdata(output, &circle.xdata);
         }
 
         Entity::Arc(arc) => {
-            writeln!(output, "0").unwrap();
-            writeln!(output, "ARC").unwrap();
-            writeln!(output, "8").unwrap();
-            writeln!(output, "{}", arc.layer).unwrap();
-            writeln!(output, "62").unwrap();
-            writeln!(output, "{}", arc.color).unwrap();
-            writeln!(output, "6").unwrap();
-            writeln!(output, "{}", arc.line_type).unwrap();
+            write_entity_start(output, ctx, entity, "AcDbCircle");
             writeln!(output, "10").unwrap();
             writeln!(output, "{}", arc.center_x).unwrap();
             writeln!(output, "20").unwrap();
             writeln!(output, "{}", arc.center_y).unwrap();
             writeln!(output, "40").unwrap();
             writeln!(output, "{}", arc.radius).unwrap();
+            write_subclass(output, ctx, "AcDbArc");
             writeln!(output, "50").unwrap();
             writeln!(output, "{}", arc.start_angle).unwrap();
             writeln!(output, "51").unwrap();
             writeln!(output, "{}", arc.end_angle).unwrap();
+            write_xdata(output, &arc.xdata);
         }
 
         Entity::Ellipse(ellipse) => {
-            writeln!(output, "0").unwrap();
-            writeln!(output, "ELLIPSE").unwrap();
-            writeln!(output, "8").unwrap();
-            writeln!(output, "{}", ellipse.layer).unwrap();
-            writeln!(output, "62").unwrap();
-            writeln!(output, "{}", ellipse.color).unwrap();
-            writeln!(output, "6").unwrap();
-            writeln!(output, "{}", ellipse.line_type).unwrap();
+            write_entity_start(output, ctx, entity, "AcDbEllipse");
             writeln!(output, "10").unwrap();
             writeln!(output, "{}", ellipse.center_x).unwrap();
             writeln!(output, "20").unwrap();
@@ -232,32 +1192,20 @@ fn write_entity(output: &mut String, entity: &crate::types::Entity) {
             writeln!(output, "{}", ellipse.start_param).unwrap();
             writeln!(output, "42").unwrap();
             writeln!(output, "{}", ellipse.end_param).unwrap();
+            write_xdata(output, &ellipse.xdata);
         }
 
         Entity::Point(point) => {
-            writeln!(output, "0").unwrap();
-            writeln!(output, "POINT").unwrap();
-            writeln!(output, "8").unwrap();
-            writeln!(output, "{}", point.layer).unwrap();
-            writeln!(output, "62").unwrap();
-            writeln!(output, "{}", point.color).unwrap();
-            writeln!(output, "6").unwrap();
-            writeln!(output, "{}", point.line_type).unwrap();
+            write_entity_start(output, ctx, entity, "AcDbPoint");
             writeln!(output, "10").unwrap();
             writeln!(output, "{}", point.x).unwrap();
             writeln!(output, "20").unwrap();
             writeln!(output, "{}", point.y).unwrap();
+            write_xdata(output, &point.xdata);
         }
 
         Entity::Text(text) => {
-            writeln!(output, "0").unwrap();
-            writeln!(output, "TEXT").unwrap();
-            writeln!(output, "8").unwrap();
-            writeln!(output, "{}", text.layer).unwrap();
-            writeln!(output, "62").unwrap();
-            writeln!(output, "{}", text.color).unwrap();
-            writeln!(output, "6").unwrap();
-            writeln!(output, "{}", text.line_type).unwrap();
+            write_entity_start(output, ctx, entity, "AcDbText");
             writeln!(output, "10").unwrap();
             writeln!(output, "{}", text.x).unwrap();
             writeln!(output, "20").unwrap();
@@ -270,17 +1218,13 @@ fn write_entity(output: &mut String, entity: &crate::types::Entity) {
             writeln!(output, "{}", text.content).unwrap();
             writeln!(output, "7").unwrap();
             writeln!(output, "{}", text.style).unwrap();
+            // R2000では配置 (73) の前に2つ目のサブクラスマーカーが必要
+            write_subclass(output, ctx, "AcDbText");
+            write_xdata(output, &text.xdata);
         }
 
         Entity::Solid(solid) => {
-            writeln!(output, "0").unwrap();
-            writeln!(output, "SOLID").unwrap();
-            writeln!(output, "8").unwrap();
-            writeln!(output, "{}", solid.layer).unwrap();
-            writeln!(output, "62").unwrap();
-            writeln!(output, "{}", solid.color).unwrap();
-            writeln!(output, "6").unwrap();
-            writeln!(output, "{}", solid.line_type).unwrap();
+            write_entity_start(output, ctx, entity, "AcDbTrace");
             writeln!(output, "10").unwrap();
             writeln!(output, "{}", solid.x1).unwrap();
             writeln!(output, "20").unwrap();
@@ -297,17 +1241,11 @@ fn write_entity(output: &mut String, entity: &crate::types::Entity) {
             writeln!(output, "{}", solid.x4).unwrap();
             writeln!(output, "23").unwrap();
             writeln!(output, "{}", solid.y4).unwrap();
+            write_xdata(output, &solid.xdata);
         }
 
         Entity::Insert(insert) => {
-            writeln!(output, "0").unwrap();
-            writeln!(output, "INSERT").unwrap();
-            writeln!(output, "8").unwrap();
-            writeln!(output, "{}", insert.layer).unwrap();
-            writeln!(output, "62").unwrap();
-            writeln!(output, "{}", insert.color).unwrap();
-            writeln!(output, "6").unwrap();
-            writeln!(output, "{}", insert.line_type).unwrap();
+            write_entity_start(output, ctx, entity, "AcDbBlockReference");
             writeln!(output, "2").unwrap();
             writeln!(output, "{}", insert.block_name).unwrap();
             writeln!(output, "10").unwrap();
@@ -320,6 +1258,127 @@ fn write_entity(output: &mut String, entity: &crate::types::Entity) {
             writeln!(output, "{}", insert.scale_y).unwrap();
             writeln!(output, "50").unwrap();
             writeln!(output, "{}", insert.rotation).unwrap();
+            write_xdata(output, &insert.xdata);
+        }
+
+        Entity::Polyline(polyline) => {
+            let handle = write_entity_start(output, ctx, entity, "AcDb2dPolyline");
+            writeln!(output, "66").unwrap();
+            writeln!(output, "1").unwrap();
+            writeln!(output, "10").unwrap();
+            writeln!(output, "0.0").unwrap();
+            writeln!(output, "20").unwrap();
+            writeln!(output, "0.0").unwrap();
+            writeln!(output, "30").unwrap();
+            writeln!(output, "0.0").unwrap();
+            writeln!(output, "70").unwrap();
+            writeln!(output, "{}", if polyline.closed { 1 } else { 0 }).unwrap();
+            write_xdata(output, &polyline.xdata);
+
+            for vertex in &polyline.vertices {
+                write_sub_entity_start(output, ctx, "VERTEX", handle, &polyline.layer);
+                write_subclass(output, ctx, "AcDbVertex");
+                write_subclass(output, ctx, "AcDb2dVertex");
+                writeln!(output, "10").unwrap();
+                writeln!(output, "{}", vertex.x).unwrap();
+                writeln!(output, "20").unwrap();
+                writeln!(output, "{}", vertex.y).unwrap();
+            }
+
+            write_sub_entity_start(output, ctx, "SEQEND", handle, &polyline.layer);
+        }
+
+        Entity::Dimension(dim) => {
+            ctx.dimension_blocks += 1;
+            write_entity_start(output, ctx, entity, "AcDbDimension");
+            writeln!(output, "2").unwrap();
+            writeln!(output, "*D{}", ctx.dimension_blocks).unwrap();
+            writeln!(output, "3").unwrap();
+            writeln!(output, "{}", dim.style).unwrap();
+            writeln!(output, "10").unwrap();
+            writeln!(output, "{}", dim.def_x).unwrap();
+            writeln!(output, "20").unwrap();
+            writeln!(output, "{}", dim.def_y).unwrap();
+            writeln!(output, "11").unwrap();
+            writeln!(output, "{}", dim.text_x).unwrap();
+            writeln!(output, "21").unwrap();
+            writeln!(output, "{}", dim.text_y).unwrap();
+            // 平行寸法 (0) + 寸法専用ブロック (32)
+            writeln!(output, "70").unwrap();
+            writeln!(output, "32").unwrap();
+            if let Some(text) = &dim.text_override {
+                writeln!(output, "1").unwrap();
+                writeln!(output, "{}", text).unwrap();
+            }
+            write_subclass(output, ctx, "AcDbAlignedDimension");
+            writeln!(output, "13").unwrap();
+            writeln!(output, "{}", dim.ext1_x).unwrap();
+            writeln!(output, "23").unwrap();
+            writeln!(output, "{}", dim.ext1_y).unwrap();
+            writeln!(output, "14").unwrap();
+            writeln!(output, "{}", dim.ext2_x).unwrap();
+            writeln!(output, "24").unwrap();
+            writeln!(output, "{}", dim.ext2_y).unwrap();
+            writeln!(output, "50").unwrap();
+            writeln!(output, "{}", dim.angle).unwrap();
+            write_subclass(output, ctx, "AcDbRotatedDimension");
+            write_xdata(output, &dim.xdata);
+        }
+
+        Entity::Leader(leader) => {
+            write_entity_start(output, ctx, entity, "AcDbLeader");
+            writeln!(output, "3").unwrap();
+            writeln!(output, "{}", leader.style).unwrap();
+            writeln!(output, "71").unwrap();
+            writeln!(output, "{}", u8::from(leader.arrowhead)).unwrap();
+            // 直線 (0)、注記なし (3: 文字はGROUPで関連付ける)
+            writeln!(output, "72").unwrap();
+            writeln!(output, "0").unwrap();
+            writeln!(output, "73").unwrap();
+            writeln!(output, "3").unwrap();
+            writeln!(output, "76").unwrap();
+            writeln!(output, "{}", leader.vertices.len()).unwrap();
+            for vertex in &leader.vertices {
+                writeln!(output, "10").unwrap();
+                writeln!(output, "{}", vertex.x).unwrap();
+                writeln!(output, "20").unwrap();
+                writeln!(output, "{}", vertex.y).unwrap();
+                writeln!(output, "30").unwrap();
+                writeln!(output, "0.0").unwrap();
+            }
+            write_xdata(output, &leader.xdata);
+        }
+    }
+}
+
+/// 拡張データ (XDATA) を出力する
+fn write_xdata(output: &mut dyn Write, xdata: &Option<crate::types::XData>) {
+    use crate::types::XDataValue;
+
+    let Some(xdata) = xdata else {
+        return;
+    };
+
+    writeln!(output, "1001").unwrap();
+    writeln!(output, "{}", xdata.app_name).unwrap();
+    for value in &xdata.values {
+        match value {
+            XDataValue::String(v) => {
+                writeln!(output, "1000").unwrap();
+                writeln!(output, "{}", v).unwrap();
+            }
+            XDataValue::Integer(v) => {
+                writeln!(output, "1070").unwrap();
+                writeln!(output, "{}", v).unwrap();
+            }
+            XDataValue::Long(v) => {
+                writeln!(output, "1071").unwrap();
+                writeln!(output, "{}", v).unwrap();
+            }
+            XDataValue::Real(v) => {
+                writeln!(output, "1040").unwrap();
+                writeln!(output, "{}", v).unwrap();
+            }
         }
     }
 }
@@ -0,0 +1,1469 @@
+//! JWW -> DXF 変換のテスト
+
+use jww_core::{Angle, Document as JwwDocument, Entity as JwwEntity, EntityBase, Line, Point2D, Text};
+use jww_dxf::{ConvertOptions, Entity, Units};
+
+fn base() -> EntityBase {
+    EntityBase {
+        pen_color: 2,
+        ..Default::default()
+    }
+}
+
+fn line(start_x: f64, start_y: f64, end_x: f64, end_y: f64) -> JwwEntity<'static> {
+    JwwEntity::Line(Line {
+        base: base(),
+        start: Point2D::new(start_x, start_y),
+        end: Point2D::new(end_x, end_y),
+    })
+}
+
+fn text(x: f64, y: f64, size: f64, content: &str) -> JwwEntity<'_> {
+    JwwEntity::Text(Text {
+        base: base(),
+        start: Point2D::new(x, y),
+        end: Point2D::new(x + size * content.chars().count() as f64, y),
+        text_type: 0,
+        size_x: size,
+        size_y: size,
+        spacing: 0.0,
+        angle: Angle::ZERO,
+        font_name: "ＭＳ ゴシック".into(),
+        content: content.into(),
+    })
+}
+
+fn block_ref(x: f64, y: f64, scale_x: f64, scale_y: f64, rotation: f64, def_number: u32) -> JwwEntity<'static> {
+    JwwEntity::Block(jww_core::Block {
+        base: base(),
+        reference: Point2D::new(x, y),
+        scale_x,
+        scale_y,
+        rotation: Angle::from_radians(rotation),
+        def_number,
+    })
+}
+
+fn block_def(number: u32, name: &str, entities: Vec<JwwEntity<'static>>) -> jww_core::BlockDef<'static> {
+    jww_core::BlockDef {
+        base: base(),
+        number,
+        is_referenced: true,
+        name: name.to_string(),
+        entities,
+    }
+}
+
+fn document(entities: Vec<JwwEntity>) -> JwwDocument {
+    JwwDocument {
+        version: 600,
+        entities,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_default_units_are_millimeters() {
+    let doc = document(vec![line(0.0, 0.0, 254.0, 0.0)]);
+    let dxf = jww_dxf::convert_document(&doc);
+
+    assert_eq!(dxf.header.insunits, 4);
+    assert_eq!(dxf.header.measurement, 1);
+    match &dxf.entities[0] {
+        Entity::Line(l) => assert_eq!(l.x2, 254.0),
+        other => panic!("unexpected entity: {:?}", other),
+    }
+
+    let output = jww_dxf::to_string(&dxf);
+    assert!(output.contains("$INSUNITS\n70\n4\n"));
+}
+
+#[test]
+fn test_convert_to_inches() {
+    let doc = document(vec![line(0.0, 0.0, 254.0, 25.4), text(25.4, 50.8, 5.08, "A")]);
+    let options = ConvertOptions {
+        units: Units::Inches,
+        ..Default::default()
+    };
+    let dxf = jww_dxf::convert_document_with_options(&doc, &options);
+
+    assert_eq!(dxf.header.insunits, 1);
+    assert_eq!(dxf.header.measurement, 0);
+    match &dxf.entities[0] {
+        Entity::Line(l) => {
+            assert!((l.x2 - 10.0).abs() < 1e-9);
+            assert!((l.y2 - 1.0).abs() < 1e-9);
+        }
+        other => panic!("unexpected entity: {:?}", other),
+    }
+    match &dxf.entities[1] {
+        Entity::Text(t) => {
+            assert!((t.x - 1.0).abs() < 1e-9);
+            assert!((t.y - 2.0).abs() < 1e-9);
+            assert!((t.height - 0.2).abs() < 1e-9);
+        }
+        other => panic!("unexpected entity: {:?}", other),
+    }
+}
+
+#[test]
+fn test_arbitrary_scale_factor() {
+    let doc = document(vec![line(1.0, 2.0, 3.0, 4.0)]);
+    let options = ConvertOptions {
+        units: Units::Unitless,
+        scale_factor: Some(10.0),
+        ..Default::default()
+    };
+    let dxf = jww_dxf::convert_document_with_options(&doc, &options);
+
+    assert_eq!(dxf.header.insunits, 0);
+    match &dxf.entities[0] {
+        Entity::Line(l) => assert_eq!((l.x1, l.y1, l.x2, l.y2), (10.0, 20.0, 30.0, 40.0)),
+        other => panic!("unexpected entity: {:?}", other),
+    }
+}
+
+#[test]
+fn test_paper_space_layout() {
+    let mut doc = document(vec![line(-1000.0, -500.0, 3000.0, 1500.0)]);
+    doc.paper_size = 3; // A3
+    doc.layer_groups[0].scale = 100.0;
+
+    let dxf = jww_dxf::convert_document(&doc);
+    assert!(dxf.layout.is_none());
+
+    // 図面範囲は用紙の中心を原点として縮尺倍する
+    let limits = dxf.header.limits.expect("limits");
+    assert_eq!((limits.min_x, limits.min_y), (-21000.0, -14850.0));
+    assert_eq!((limits.max_x, limits.max_y), (21000.0, 14850.0));
+    assert!(jww_dxf::to_string(&dxf).contains("$LIMMAX\n10\n21000\n20\n14850\n"));
+
+    let options = ConvertOptions {
+        paper_space: true,
+        ..Default::default()
+    };
+    let dxf = jww_dxf::convert_document_with_options(&doc, &options);
+    let layout = dxf.layout.as_ref().expect("layout");
+    assert_eq!((layout.paper_width, layout.paper_height), (420.0, 297.0));
+
+    // 図面範囲の中心 (1000, 500) は用紙中心から (10, 5) mm ずれた位置
+    let vp = &layout.viewport;
+    assert!((vp.center_x - 220.0).abs() < 1e-9);
+    assert!((vp.center_y - 153.5).abs() < 1e-9);
+    assert!((vp.width - 40.0).abs() < 1e-9);
+    assert!((vp.height - 20.0).abs() < 1e-9);
+    assert_eq!((vp.view_center_x, vp.view_center_y), (1000.0, 500.0));
+    assert_eq!(vp.view_height, 2000.0);
+
+    let output = jww_dxf::to_string(&dxf);
+    assert!(output.contains("$PLIMMAX\n10\n420\n20\n297\n"));
+    assert_eq!(output.matches("\nVIEWPORT\n").count(), 2);
+
+    // ペーパー空間はR2000の *Paper_Space ブロックとLAYOUTで表す
+    assert!(output.contains("$ACADVER\n1\nAC1015\n"));
+    assert!(output.contains("0\nBLOCK_RECORD\n"));
+    assert!(output.contains("2\n*Paper_Space\n"));
+    assert!(output.contains("0\nLAYOUT\n"));
+    assert!(output.contains("1\nLayout1\n"));
+    assert!(output.contains("44\n420\n45\n297\n"));
+    assert!(output.contains("100\nAcDbEntity\n67\n1\n8\n0\n100\nAcDbViewport\n"));
+    let findings = jww_dxf::validate::validate(&output);
+    assert!(findings.is_empty(), "{:?}", findings);
+}
+
+#[test]
+fn test_active_view_frames_extents() {
+    let dxf = jww_dxf::convert_document(&document(Vec::new()));
+    assert!(dxf.active_view.is_none());
+    assert!(!jww_dxf::to_string(&dxf).contains("*ACTIVE"));
+
+    let doc = document(vec![line(0.0, 0.0, 400.0, 200.0)]);
+    let dxf = jww_dxf::convert_document(&doc);
+    let view = dxf.active_view.as_ref().expect("active view");
+    assert_eq!((view.center_x, view.center_y), (200.0, 100.0));
+    assert!(view.height >= 200.0);
+    assert!(view.height * view.aspect_ratio >= 400.0);
+
+    let output = jww_dxf::to_string(&dxf);
+    assert!(output.contains("VPORT\n2\n*ACTIVE\n"));
+    assert!(output.contains("\n12\n200\n22\n100\n"));
+}
+
+#[test]
+fn test_ltscale_from_drawing_scale() {
+    let mut doc = document(vec![line(0.0, 0.0, 100.0, 0.0), line(0.0, 0.0, 100.0, 0.0)]);
+    doc.layer_groups[0].scale = 100.0;
+    doc.layer_groups[1].scale = 50.0;
+    for entity in &mut doc.entities {
+        entity.base_mut().pen_style = 2;
+    }
+    doc.entities[1].base_mut().layer_group = 1;
+
+    let dxf = jww_dxf::convert_document(&doc);
+    assert_eq!(dxf.header.ltscale, 100.0);
+    match (&dxf.entities[0], &dxf.entities[1]) {
+        (Entity::Line(a), Entity::Line(b)) => {
+            assert_eq!(a.line_type, "DASHED");
+            assert_eq!(a.line_type_scale, None);
+            assert_eq!(b.line_type_scale, Some(0.5));
+        }
+        other => panic!("unexpected entities: {:?}", other),
+    }
+
+    let output = jww_dxf::to_string(&dxf);
+    assert!(output.contains("$LTSCALE\n40\n100\n"));
+    assert!(output.contains("LTYPE\n2\nDASHED\n"));
+    // パターンの長さと要素は他の実数と同じ形式で出力する
+    assert!(output.contains("Solid line\n72\n65\n73\n0\n40\n0\n"));
+    assert!(output.contains("73\n2\n40\n4.5\n49\n3\n49\n-1.5\n"));
+    assert!(output.contains("DASHED\n48\n0.5\n"));
+}
+
+fn marker_point(x: f64, y: f64, code: u32) -> JwwEntity<'static> {
+    JwwEntity::Point(jww_core::Point {
+        base: EntityBase {
+            pen_style: 100,
+            ..base()
+        },
+        position: Point2D::new(x, y),
+        is_temporary: false,
+        code,
+        angle: Angle::ZERO,
+        scale: 1.0,
+    })
+}
+
+#[test]
+fn test_point_marker_display() {
+    let dxf = jww_dxf::convert_document(&document(vec![line(0.0, 0.0, 1.0, 1.0)]));
+    assert_eq!(dxf.header.pdmode, 0);
+
+    let mut doc = document(vec![
+        marker_point(0.0, 0.0, 1),
+        marker_point(10.0, 0.0, 3),
+        marker_point(20.0, 0.0, 3),
+    ]);
+    doc.layer_groups[0].scale = 50.0;
+    let dxf = jww_dxf::convert_document(&doc);
+    assert_eq!(dxf.entities.len(), 3);
+    assert_eq!(dxf.header.pdmode, 2);
+    assert_eq!(dxf.header.pdsize, 100.0);
+
+    let output = jww_dxf::to_string(&dxf);
+    assert!(output.contains("$PDMODE\n70\n2\n"));
+    assert!(output.contains("$PDSIZE\n40\n100\n"));
+}
+
+#[test]
+fn test_point_marker_blocks() {
+    let mut doc = document(vec![
+        marker_point(0.0, 0.0, 6),
+        marker_point(10.0, 0.0, 3),
+        marker_point(20.0, 0.0, 42),
+    ]);
+    doc.layer_groups[0].scale = 50.0;
+    if let JwwEntity::Point(p) = &mut doc.entities[1] {
+        p.scale = 2.0;
+        p.angle = Angle::from_degrees(30.0);
+    }
+    let options = ConvertOptions {
+        point_marker_blocks: true,
+        ..Default::default()
+    };
+    let dxf = jww_dxf::convert_document_with_options(&doc, &options);
+
+    let inserts: Vec<_> = dxf
+        .entities
+        .iter()
+        .map(|e| match e {
+            Entity::Insert(i) => (i.block_name.as_str(), i.x, i.scale_x, i.rotation),
+            other => panic!("expected INSERT, got {:?}", other),
+        })
+        .collect();
+    // 不明なマーカーは○のブロックを使う
+    assert_eq!(
+        inserts,
+        vec![
+            ("JWW_MARKER_6", 0.0, 100.0, 0.0),
+            ("JWW_MARKER_3", 10.0, 200.0, 30.0),
+            ("JWW_MARKER_1", 20.0, 100.0, 0.0),
+        ]
+    );
+
+    let names: Vec<_> = dxf.blocks.iter().map(|b| b.name.as_str()).collect();
+    assert_eq!(names, vec!["JWW_MARKER_1", "JWW_MARKER_3", "JWW_MARKER_6"]);
+    let kinds = |name: &str| -> Vec<&str> {
+        let block = dxf.blocks.iter().find(|b| b.name == name).unwrap();
+        block.entities.iter().map(Entity::type_name).collect()
+    };
+    assert_eq!(kinds("JWW_MARKER_1"), vec!["CIRCLE"]);
+    assert_eq!(kinds("JWW_MARKER_3"), vec!["LINE", "LINE"]);
+    assert_eq!(kinds("JWW_MARKER_6"), vec!["CIRCLE", "LINE", "LINE"]);
+
+    let output = jww_dxf::to_string(&dxf);
+    assert!(output.contains("0\nBLOCK\n8\n0\n2\nJWW_MARKER_6\n"));
+    assert!(output.contains("0\nINSERT\n"));
+}
+
+fn ellipse_arc(flatness: f64, tilt_angle: f64, start_angle: f64, arc_angle: f64) -> JwwEntity<'static> {
+    JwwEntity::Arc(jww_core::Arc {
+        base: base(),
+        center: Point2D::new(0.0, 0.0),
+        radius: 10.0,
+        start_angle: Angle::from_radians(start_angle),
+        arc_angle: Angle::from_radians(arc_angle),
+        tilt_angle: Angle::from_radians(tilt_angle),
+        flatness,
+        is_full_circle: false,
+    })
+}
+
+#[test]
+fn test_negative_arc_sweep() {
+    use std::f64::consts::PI;
+
+    let doc = document(vec![
+        ellipse_arc(1.0, 0.0, PI / 2.0, -PI / 2.0),
+        ellipse_arc(1.0, 0.0, PI / 6.0, -PI / 3.0),
+        ellipse_arc(1.0, 0.0, -PI / 4.0, PI / 2.0),
+        ellipse_arc(1.0, 0.0, 0.0, -2.0 * PI),
+    ]);
+    let dxf = jww_dxf::convert_document(&doc);
+
+    // DXFの円弧は反時計回り: 始点と終点を入れ替え、角度は0以上360未満
+    let arcs: Vec<_> = dxf.entities[..3]
+        .iter()
+        .map(|e| match e {
+            Entity::Arc(a) => ((a.start_angle * 1e6).round() / 1e6, (a.end_angle * 1e6).round() / 1e6),
+            other => panic!("expected ARC, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(arcs, vec![(0.0, 90.0), (330.0, 30.0), (315.0, 45.0)]);
+    assert!(matches!(dxf.entities[3], Entity::Circle(_)));
+}
+
+/// DXF楕円上の媒介変数 t の点を返す
+fn ellipse_point(e: &jww_dxf::Ellipse, t: f64) -> (f64, f64) {
+    let (mx, my) = (e.major_axis_x, e.major_axis_y);
+    let (nx, ny) = (-my * e.minor_ratio, mx * e.minor_ratio);
+    (
+        e.center_x + mx * t.cos() + nx * t.sin(),
+        e.center_y + my * t.cos() + ny * t.sin(),
+    )
+}
+
+fn assert_direction(point: (f64, f64), angle: f64) {
+    use std::f64::consts::PI;
+    let actual = point.1.atan2(point.0);
+    let diff = ((actual - angle + PI).rem_euclid(2.0 * PI) - PI).abs();
+    assert!(diff < 1e-9, "{} != {}", actual, angle);
+}
+
+#[test]
+fn test_ellipse_arc_parameters() {
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    let cases = [
+        // (扁平率, 傾き, 開始角, 円弧角)
+        (0.5, 0.0, FRAC_PI_4, FRAC_PI_2),
+        (0.5, 0.3, 0.2, 2.0),
+        (2.0, 0.0, FRAC_PI_4, PI),
+        (0.5, 0.0, FRAC_PI_2, -FRAC_PI_4),
+    ];
+
+    for (flatness, tilt, start, sweep) in cases {
+        let dxf = jww_dxf::convert_document(&document(vec![ellipse_arc(flatness, tilt, start, sweep)]));
+        let e = match &dxf.entities[0] {
+            Entity::Ellipse(e) => e.clone(),
+            other => panic!("unexpected entity: {:?}", other),
+        };
+        assert!(e.minor_ratio <= 1.0);
+        assert!(e.end_param > e.start_param);
+
+        // 始点・終点がJWWの角度方向に一致すること
+        let (from, to) = if sweep < 0.0 { (start + sweep, start) } else { (start, start + sweep) };
+        assert_direction(ellipse_point(&e, e.start_param), from);
+        assert_direction(ellipse_point(&e, e.end_param), to);
+    }
+}
+
+#[test]
+fn test_ellipse_tessellation() {
+    use std::f64::consts::PI;
+
+    let doc = document(vec![ellipse_arc(0.5, 0.3, 0.2, 2.0)]);
+    let ellipse = match &jww_dxf::convert_document(&doc).entities[0] {
+        Entity::Ellipse(e) => e.clone(),
+        other => panic!("unexpected entity: {:?}", other),
+    };
+
+    let options = ConvertOptions {
+        curve_tolerance: Some(0.01),
+        ..Default::default()
+    };
+    let dxf = jww_dxf::convert_document_with_options(&doc, &options);
+    let polyline = match &dxf.entities[0] {
+        Entity::Polyline(p) => p.clone(),
+        other => panic!("unexpected entity: {:?}", other),
+    };
+    assert!(!polyline.closed);
+    assert!(polyline.vertices.len() > 10);
+
+    // 端点はELLIPSE出力と一致する
+    let first = polyline.vertices.first().unwrap();
+    let last = polyline.vertices.last().unwrap();
+    let start = ellipse_point(&ellipse, ellipse.start_param);
+    let end = ellipse_point(&ellipse, ellipse.end_param);
+    assert!((first.x - start.0).abs() < 1e-9 && (first.y - start.1).abs() < 1e-9);
+    assert!((last.x - end.0).abs() < 1e-9 && (last.y - end.1).abs() < 1e-9);
+
+    // 全周の楕円は閉じたポリラインになる
+    let mut full = ellipse_arc(0.5, 0.0, 0.0, 2.0 * PI);
+    if let JwwEntity::Arc(arc) = &mut full {
+        arc.is_full_circle = true;
+    }
+    let dxf = jww_dxf::convert_document_with_options(&document(vec![full]), &options);
+    match &dxf.entities[0] {
+        Entity::Polyline(p) => {
+            assert!(p.closed);
+            assert_ne!(p.vertices.first(), p.vertices.last());
+        }
+        other => panic!("unexpected entity: {:?}", other),
+    }
+
+    let output = jww_dxf::to_string(&dxf);
+    assert!(output.contains("\nPOLYLINE\n"));
+    assert!(output.contains("\nSEQEND\n"));
+
+    // 傾きのある円弧も近似し、傾きのない円弧はARCのまま出力する
+    let doc = document(vec![ellipse_arc(1.0, 0.5, 0.2, 1.0), ellipse_arc(1.0, 0.0, 0.2, 1.0)]);
+    let (dxf, report) = jww_dxf::convert_document_with_report(&doc, &options);
+    let Entity::Polyline(polyline) = &dxf.entities[0] else {
+        panic!("unexpected entity: {:?}", dxf.entities[0]);
+    };
+    assert!(matches!(dxf.entities[1], Entity::Arc(_)));
+    assert_eq!(report.lossy.len(), 1);
+    assert_eq!(report.lossy[0].loss, jww_dxf::Loss::ArcApproximated);
+    // 開始角はX軸から測るため、端点は傾きによらない
+    let first = polyline.vertices.first().unwrap();
+    let last = polyline.vertices.last().unwrap();
+    assert_direction((first.x, first.y), 0.2);
+    assert_direction((last.x, last.y), 1.2);
+}
+
+#[test]
+fn test_output_version() {
+    use jww_dxf::{DxfEncoding, DxfFormat, DxfVersion};
+
+    let mut doc = document(vec![
+        ellipse_arc(0.5, 0.3, 0.2, 2.0),
+        grouped(3, line(0.0, 0.0, 100.0, 100.0)),
+        grouped(3, line(0.0, 0.0, 3.0, 1.0)),
+        grouped(3, line(0.0, 0.0, 1.0, 3.0)),
+        grouped(3, text(105.0, 101.0, 5.0, "W=900")),
+    ]);
+    doc.paper_size = 3;
+    let options = ConvertOptions {
+        paper_space: true,
+        groups: true,
+        leaders: true,
+        ..Default::default()
+    };
+    let dxf = jww_dxf::convert_document_with_options(&doc, &options);
+    assert_eq!(dxf.output_version(), DxfVersion::R2000);
+
+    // R12を指定するとR12にない項目を出力しない
+    let options = ConvertOptions {
+        version: Some(DxfVersion::R12),
+        ..options
+    };
+    let dxf = jww_dxf::convert_document_with_options(&doc, &options);
+    assert_eq!(dxf.output_version(), DxfVersion::R12);
+    assert!(dxf.layout.is_none() && dxf.groups.is_empty());
+    assert!(matches!(dxf.entities[0], Entity::Polyline(_)));
+    assert!(dxf.entities.iter().all(|e| !matches!(e, Entity::Leader(_))));
+    let output = jww_dxf::to_string(&dxf);
+    assert!(output.contains("$ACADVER\n1\nAC1009\n"));
+    assert_eq!(jww_dxf::validate::validate(&output), Vec::new());
+
+    // R2000を指定するとR12で表せる図面もR2000で出力する
+    let options = ConvertOptions {
+        version: Some(DxfVersion::R2000),
+        encoding: DxfEncoding::ShiftJis,
+        ..Default::default()
+    };
+    let dxf = jww_dxf::convert_document_with_options(&document(vec![line(0.0, 0.0, 1.0, 0.0)]), &options);
+    let output = jww_dxf::to_string(&dxf);
+    assert!(output.contains("$ACADVER\n1\nAC1015\n"));
+    assert_eq!(jww_dxf::validate::validate(&output), Vec::new());
+    let sjis = jww_dxf::to_bytes(&dxf, options.encoding.into());
+    assert_eq!(sjis, jww_dxf::to_bytes(&dxf, DxfFormat::ShiftJis));
+}
+
+#[test]
+fn test_entity_filter() {
+    use jww_core::EntityKind;
+
+    let mut temporary = marker_point(5.0, 5.0, 0);
+    if let JwwEntity::Point(p) = &mut temporary {
+        p.is_temporary = true;
+    }
+    let mut flagged = line(0.0, 0.0, 0.0, 10.0);
+    flagged.base_mut().flag = jww_dxf::FLAG_HIDDEN;
+    let doc = document(vec![
+        line(0.0, 0.0, 10.0, 0.0),
+        text(0.0, 0.0, 3.0, "A"),
+        temporary,
+        flagged,
+    ]);
+
+    // 既定では仮点のみ除外する
+    let dxf = jww_dxf::convert_document(&doc);
+    assert_eq!(dxf.entities.len(), 3);
+
+    let mut options = ConvertOptions::default();
+    options.filter.exclude = vec![EntityKind::Text];
+    options.filter.exclude_flags = jww_dxf::FLAG_HIDDEN | jww_dxf::FLAG_AUXILIARY;
+    let dxf = jww_dxf::convert_document_with_options(&doc, &options);
+    assert_eq!(dxf.entities.len(), 1);
+    assert!(matches!(dxf.entities[0], Entity::Line(_)));
+
+    let mut options = ConvertOptions::default();
+    options.filter.include = vec![EntityKind::Point];
+    options.filter.temporary_points = true;
+    let dxf = jww_dxf::convert_document_with_options(&doc, &options);
+    assert_eq!(dxf.entities.len(), 1);
+    assert!(matches!(dxf.entities[0], Entity::Point(_)));
+
+    let mut other_layer = line(0.0, 0.0, 5.0, 5.0);
+    other_layer.base_mut().layer = 3;
+    let doc = document(vec![line(0.0, 0.0, 10.0, 0.0), other_layer]);
+    let mut options = ConvertOptions::default();
+    options.filter.layers = vec![(0, 3)];
+    let (dxf, report) = jww_dxf::convert_document_with_report(&doc, &options);
+    assert_eq!(dxf.entities.len(), 1);
+    match &dxf.entities[0] {
+        Entity::Line(l) => assert_eq!(l.x2, 5.0),
+        other => panic!("unexpected entity: {:?}", other),
+    }
+    // 指定外のレイヤのエンティティも入力数と除外に記録する
+    assert_eq!(report.input_counts[&EntityKind::Line], 2);
+    assert_eq!(report.skipped.len(), 1);
+    assert_eq!(report.skipped[0].location.index, 0);
+}
+
+#[test]
+fn test_deterministic_order_and_handles() {
+    let mut entities = Vec::new();
+    for i in 0..6 {
+        let mut e = line(i as f64, 0.0, i as f64, 10.0);
+        e.base_mut().layer = (5 - i % 3) as u16;
+        entities.push(e);
+    }
+    let doc = document(entities);
+
+    // JWWの順序を保つ
+    let dxf = jww_dxf::convert_document(&doc);
+    let xs: Vec<f64> = dxf
+        .entities
+        .iter()
+        .map(|e| match e {
+            Entity::Line(l) => l.x1,
+            other => panic!("unexpected entity: {:?}", other),
+        })
+        .collect();
+    assert_eq!(xs, vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+
+    // 繰り返し変換しても同一の出力になる
+    let first = jww_dxf::to_string(&dxf);
+    let second = jww_dxf::to_string(&jww_dxf::convert_document(&doc));
+    assert_eq!(first, second);
+    assert!(first.contains("$HANDSEED\n5\n7\n"));
+    assert!(first.contains("LINE\n5\n1\n"));
+    assert!(first.contains("LINE\n5\n6\n"));
+
+    // レイヤ順に並べ替える (同一レイヤ内は元の順序)
+    let options = ConvertOptions {
+        sort_by_layer: true,
+        ..Default::default()
+    };
+    let sorted = jww_dxf::convert_document_with_options(&doc, &options);
+    let order: Vec<(String, f64)> = sorted
+        .entities
+        .iter()
+        .map(|e| match e {
+            Entity::Line(l) => (l.layer.clone(), l.x1),
+            other => panic!("unexpected entity: {:?}", other),
+        })
+        .collect();
+    assert_eq!(
+        order,
+        vec![
+            ("0-3".to_string(), 2.0),
+            ("0-3".to_string(), 5.0),
+            ("0-4".to_string(), 1.0),
+            ("0-4".to_string(), 4.0),
+            ("0-5".to_string(), 0.0),
+            ("0-5".to_string(), 3.0),
+        ]
+    );
+}
+
+#[test]
+fn test_xdata_preserves_jww_attributes() {
+    use jww_dxf::XDataValue;
+
+    let mut e = line(0.0, 0.0, 10.0, 0.0);
+    {
+        let base = e.base_mut();
+        base.pen_style = 3;
+        base.pen_color = 8;
+        base.pen_width = 5;
+        base.layer_group = 2;
+        base.layer = 7;
+        base.flag = 0x10;
+        base.group = 42;
+    }
+    let doc = document(vec![e]);
+
+    let dxf = jww_dxf::convert_document(&doc);
+    match &dxf.entities[0] {
+        Entity::Line(l) => assert!(l.xdata.is_none()),
+        other => panic!("unexpected entity: {:?}", other),
+    }
+
+    let options = ConvertOptions {
+        xdata: true,
+        ..Default::default()
+    };
+    let dxf = jww_dxf::convert_document_with_options(&doc, &options);
+    let xdata = match &dxf.entities[0] {
+        Entity::Line(l) => l.xdata.clone().expect("xdata"),
+        other => panic!("unexpected entity: {:?}", other),
+    };
+    assert_eq!(xdata.app_name, jww_dxf::XDATA_APP_NAME);
+    let pairs: Vec<(String, i32)> = xdata
+        .values
+        .chunks(2)
+        .map(|pair| match pair {
+            [XDataValue::String(name), XDataValue::Long(value)] => (name.clone(), *value),
+            other => panic!("unexpected xdata: {:?}", other),
+        })
+        .collect();
+    assert!(pairs.contains(&("pen_color".to_string(), 8)));
+    assert!(pairs.contains(&("layer_group".to_string(), 2)));
+    assert!(pairs.contains(&("group".to_string(), 42)));
+
+    let output = jww_dxf::to_string(&dxf);
+    assert!(output.contains("1001\nJWW\n1000\npen_style\n1071\n3\n"));
+
+    // 1071に収まらない曲線属性番号は負の値にせず丸める
+    let mut e = line(0.0, 0.0, 10.0, 0.0);
+    e.base_mut().group = u32::MAX;
+    let dxf = jww_dxf::convert_document_with_options(&document(vec![e]), &options);
+    let output = jww_dxf::to_string(&dxf);
+    assert!(output.contains("1000\ngroup\n1071\n2147483647\n"));
+}
+
+#[test]
+fn test_curve_groups() {
+    let mut entities = vec![
+        line(0.0, 0.0, 10.0, 0.0),
+        line(10.0, 0.0, 10.0, 10.0),
+        line(20.0, 0.0, 30.0, 0.0),
+        line(40.0, 0.0, 50.0, 0.0),
+    ];
+    entities[0].base_mut().group = 3;
+    entities[1].base_mut().group = 3;
+    entities[2].base_mut().group = 4; // 1本だけのグループは出力しない
+    let doc = document(entities);
+
+    assert!(jww_dxf::convert_document(&doc).groups.is_empty());
+
+    let options = ConvertOptions {
+        groups: true,
+        ..Default::default()
+    };
+    let dxf = jww_dxf::convert_document_with_options(&doc, &options);
+    assert_eq!(dxf.groups.len(), 1);
+    assert_eq!(dxf.groups[0].entities, vec![0, 1]);
+
+    let output = jww_dxf::to_string(&dxf);
+    assert!(output.contains("\nOBJECTS\n"));
+    assert!(output.contains("ACAD_GROUP\n350\n"));
+    assert!(output.contains("AcDbGroup\n"));
+    // グループはR2000の所有者・サブクラスマーカー付きで出力し、エンティティのハンドルを参照する
+    assert!(output.contains("$ACADVER\n1\nAC1015\n"));
+    let findings = jww_dxf::validate::validate(&output);
+    assert!(findings.is_empty(), "{:?}", findings);
+    let parsed = jww_dxf::reader::parse(&output).unwrap();
+    assert_eq!(parsed.groups[0].entities, vec![0, 1]);
+    // グループに属するエンティティとGROUPはリアクタで所有者を参照する
+    for record in ["0\nLINE\n", "0\nGROUP\n"] {
+        let start = &output[output.find(record).unwrap()..];
+        let head = &start[..start.find("\n100\n").unwrap()];
+        assert!(head.contains("\n102\n{ACAD_REACTORS\n330\n"), "{}", head);
+    }
+    let last_line = &output[output.rfind("0\nLINE\n").unwrap()..];
+    assert!(!last_line[..last_line.find("\n100\n").unwrap()].contains("ACAD_REACTORS"));
+}
+
+#[test]
+fn test_conversion_report() {
+    use jww_core::EntityKind;
+    use jww_dxf::{Loss, SkipReason};
+
+    let mut unknown_color = line(0.0, 0.0, 1.0, 0.0);
+    unknown_color.base_mut().pen_color = 400;
+    let missing_block = block_ref(0.0, 0.0, 1.0, 1.0, 0.0, 9);
+    let doc = document(vec![
+        unknown_color,
+        text(0.0, 0.0, 3.0, "A"),
+        ellipse_arc(0.5, 0.0, 0.0, 1.0),
+        missing_block,
+    ]);
+
+    let mut options = ConvertOptions {
+        curve_tolerance: Some(0.1),
+        ..Default::default()
+    };
+    options.filter.exclude = vec![EntityKind::Text];
+    let (dxf, report) = jww_dxf::convert_document_with_report(&doc, &options);
+
+    assert_eq!(dxf.entities.len(), 3);
+    assert_eq!(report.input_counts[&EntityKind::Line], 1);
+    assert_eq!(report.input_counts[&EntityKind::Text], 1);
+    assert_eq!(report.output_counts["POLYLINE"], 1);
+    assert!(!report.output_counts.contains_key("TEXT"));
+
+    assert_eq!(report.skipped.len(), 1);
+    assert_eq!(report.skipped[0].location.index, 1);
+    assert_eq!(report.skipped[0].reason, SkipReason::Filtered);
+
+    assert!(report.needs_review());
+    let losses: Vec<(usize, Loss)> = report
+        .lossy
+        .iter()
+        .map(|l| (l.location.index, l.loss.clone()))
+        .collect();
+    assert_eq!(
+        losses,
+        vec![
+            (0, Loss::UnknownColor(400)),
+            (2, Loss::EllipseApproximated),
+            (3, Loss::MissingBlockDefinition(9)),
+        ]
+    );
+}
+
+#[test]
+fn test_color_mapping() {
+    let colors = [2, 10, 50, 100, 345, 400];
+    let doc = document(
+        colors
+            .iter()
+            .map(|&color| {
+                let mut entity = line(0.0, 0.0, 1.0, 0.0);
+                entity.base_mut().pen_color = color;
+                entity
+            })
+            .collect(),
+    );
+    let (dxf, report) = jww_dxf::convert_document_with_report(&doc, &ConvertOptions::default());
+
+    let aci: Vec<i32> = dxf
+        .entities
+        .iter()
+        .map(|e| match e {
+            Entity::Line(l) => l.color,
+            other => panic!("unexpected entity: {:?}", other),
+        })
+        .collect();
+    assert_eq!(aci, vec![7, 10, 50, 10, 255, 7]);
+    assert_eq!(report.lossy.len(), 1);
+    assert_eq!(report.lossy[0].location.index, 5);
+}
+
+#[test]
+fn test_text_rotation_follows_vector() {
+    // 角度のフィールドが古い値 (0度) のまま、文字列は始点から終点へ60度の向きに並ぶ
+    let mut stale = text(10.0, 20.0, 5.0, "居間");
+    if let JwwEntity::Text(t) = &mut stale {
+        t.end = Point2D::new(15.0, 20.0 + 10.0 * 3f64.sqrt() / 2.0);
+    }
+    let dxf = jww_dxf::convert_document(&document(vec![stale]));
+
+    match &dxf.entities[0] {
+        Entity::Text(t) => assert!((t.rotation - 60.0).abs() < 1e-9, "{}", t.rotation),
+        other => panic!("unexpected entity: {:?}", other),
+    }
+}
+
+#[test]
+fn test_invalid_layer_fallback() {
+    use jww_dxf::Loss;
+
+    let mut invalid = line(0.0, 0.0, 10.0, 0.0);
+    if let JwwEntity::Line(l) = &mut invalid {
+        l.base.layer_group = 16;
+        l.base.layer = 3;
+    }
+    let doc = document(vec![line(0.0, 0.0, 10.0, 10.0), invalid]);
+    let (dxf, report) = jww_dxf::convert_document_with_report(&doc, &ConvertOptions::default());
+
+    let layers: Vec<_> = dxf
+        .entities
+        .iter()
+        .map(|e| match e {
+            Entity::Line(l) => l.layer.as_str(),
+            other => panic!("unexpected entity: {:?}", other),
+        })
+        .collect();
+    assert_eq!(layers, vec!["0-0", jww_dxf::DEFAULT_FALLBACK_LAYER]);
+    assert!(dxf.layers.iter().any(|l| l.name == "JWW_INVALID"));
+    assert_eq!(report.lossy.len(), 1);
+    assert_eq!(report.lossy[0].location.index, 1);
+    assert_eq!(
+        report.lossy[0].loss,
+        Loss::InvalidLayer {
+            layer_group: 16,
+            layer: 3
+        }
+    );
+
+    let options = ConvertOptions {
+        fallback_layer: Some("BROKEN".to_string()),
+        ..Default::default()
+    };
+    let dxf = jww_dxf::convert_document_with_options(&doc, &options);
+    assert!(matches!(&dxf.entities[1], Entity::Line(l) if l.layer == "BROKEN"));
+    assert!(dxf.layers.iter().any(|l| l.name == "BROKEN"));
+    assert!(!dxf.layers.iter().any(|l| l.name == "JWW_INVALID"));
+
+    // 範囲外のレイヤがなければ代替のレイヤーは出力しない
+    let dxf = jww_dxf::convert_document(&document(vec![line(0.0, 0.0, 1.0, 1.0)]));
+    assert!(!dxf.layers.iter().any(|l| l.name == "JWW_INVALID"));
+}
+
+#[test]
+fn test_drop_degenerate() {
+    use jww_dxf::SkipReason;
+
+    let solid = |points: [(f64, f64); 4]| {
+        let [p1, p2, p3, p4] = points.map(|(x, y)| Point2D::new(x, y));
+        JwwEntity::Solid(jww_core::Solid {
+            base: base(),
+            point1: p1,
+            point2: p2,
+            point3: p3,
+            point4: p4,
+            color: 0,
+        })
+    };
+    let mut zero_radius = ellipse_arc(1.0, 0.0, 0.0, 1.0);
+    if let JwwEntity::Arc(arc) = &mut zero_radius {
+        arc.radius = 0.0;
+    }
+    let doc = document(vec![
+        line(0.0, 0.0, 10.0, 0.0),
+        line(5.0, 5.0, 5.0, 5.0),
+        zero_radius,
+        ellipse_arc(1.0, 0.0, 0.0, 1.0),
+        solid([(0.0, 0.0), (1.0, 1.0), (2.0, 2.0), (3.0, 3.0)]),
+        solid([(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)]),
+    ]);
+
+    // 既定ではそのまま出力する
+    assert_eq!(jww_dxf::convert_document(&doc).entities.len(), 6);
+
+    let options = ConvertOptions {
+        drop_degenerate: true,
+        ..Default::default()
+    };
+    let (dxf, report) = jww_dxf::convert_document_with_report(&doc, &options);
+    let kinds: Vec<_> = dxf.entities.iter().map(Entity::type_name).collect();
+    assert_eq!(kinds, vec!["LINE", "ARC", "SOLID"]);
+    let skipped: Vec<_> = report
+        .skipped
+        .iter()
+        .map(|s| (s.location.index, s.reason.clone()))
+        .collect();
+    assert_eq!(
+        skipped,
+        vec![
+            (1, SkipReason::Degenerate),
+            (2, SkipReason::Degenerate),
+            (4, SkipReason::Degenerate),
+        ]
+    );
+}
+
+#[test]
+fn test_dedup() {
+    use jww_dxf::SkipReason;
+
+    let mut other_color = line(0.0, 0.0, 100.0, 0.0);
+    if let JwwEntity::Line(l) = &mut other_color {
+        l.base.pen_color = 5;
+    }
+    let doc = document(vec![
+        line(0.0, 0.0, 100.0, 0.0),
+        // 逆向きの同じ線
+        line(100.0, 0.0, 0.0, 0.0),
+        // 許容差以内のずれ
+        line(0.0, 0.005, 100.0, 0.0),
+        other_color,
+        ellipse_arc(1.0, 0.0, 0.0, 1.0),
+        ellipse_arc(1.0, 0.0, 0.0, 1.0),
+        ellipse_arc(1.0, 0.0, 0.0, 2.0),
+    ]);
+
+    // 既定では重複を除去しない
+    assert_eq!(jww_dxf::convert_document(&doc).entities.len(), 7);
+
+    let skipped = |tolerance: f64| {
+        let options = ConvertOptions {
+            dedup_tolerance: Some(tolerance),
+            ..Default::default()
+        };
+        let (dxf, report) = jww_dxf::convert_document_with_report(&doc, &options);
+        assert!(report.skipped.iter().all(|s| s.reason == SkipReason::Duplicate));
+        assert_eq!(dxf.entities.len() + report.skipped.len(), 7);
+        report.skipped.iter().map(|s| s.location.index).collect::<Vec<_>>()
+    };
+    assert_eq!(skipped(0.0), vec![1, 5]);
+    assert_eq!(skipped(0.01), vec![1, 2, 5]);
+}
+
+#[test]
+fn test_nested_blocks() {
+    use jww_dxf::SkipReason;
+
+    let mut doc = document(vec![block_ref(1000.0, 500.0, 1.0, 1.0, 0.0, 2)]);
+    doc.block_defs = vec![
+        block_def(
+            2,
+            "WINDOW",
+            vec![
+                line(0.0, 0.0, 900.0, 0.0),
+                block_ref(450.0, 0.0, 0.5, 0.5, std::f64::consts::FRAC_PI_2, 1),
+            ],
+        ),
+        block_def(1, "PANE", vec![line(0.0, 0.0, 100.0, 0.0)]),
+        block_def(3, "LOOP_A", vec![block_ref(0.0, 0.0, 1.0, 1.0, 0.0, 4)]),
+        block_def(4, "LOOP_B", vec![block_ref(0.0, 0.0, 1.0, 1.0, 0.0, 3)]),
+    ];
+
+    let options = ConvertOptions {
+        units: Units::Meters,
+        ..Default::default()
+    };
+    let (dxf, report) = jww_dxf::convert_document_with_report(&doc, &options);
+
+    // 参照先のブロックが先に定義される
+    let names: Vec<&str> = dxf.blocks.iter().map(|b| b.name.as_str()).collect();
+    assert_eq!(names, vec!["PANE", "WINDOW", "LOOP_B", "LOOP_A"]);
+
+    // 入れ子の挿入はブロック基点からの相対変換を保つ
+    match &dxf.blocks[1].entities[1] {
+        Entity::Insert(insert) => {
+            assert_eq!(insert.block_name, "PANE");
+            assert!((insert.x - 0.45).abs() < 1e-9);
+            assert!(insert.y.abs() < 1e-9);
+            assert!((insert.scale_x - 0.5).abs() < 1e-9);
+            assert!((insert.rotation - 90.0).abs() < 1e-9);
+        }
+        other => panic!("expected INSERT, got {:?}", other),
+    }
+
+    // 循環参照は1箇所で断ち切る
+    assert_eq!(dxf.blocks[2].entities.len(), 0);
+    assert_eq!(dxf.blocks[3].entities.len(), 1);
+    assert_eq!(report.skipped.len(), 1);
+    assert_eq!(report.skipped[0].location.block, Some(4));
+    assert_eq!(report.skipped[0].reason, SkipReason::CircularReference);
+
+    let output = jww_dxf::to_string(&dxf);
+    let pane = output.find("\nPANE\n70\n").unwrap();
+    let window = output.find("\nWINDOW\n70\n").unwrap();
+    assert!(pane < window);
+}
+
+/// ブロック座標系の点を挿入変換する (倍率を適用してから回転する)
+fn transform_point(x: f64, y: f64, scale_x: f64, scale_y: f64, rotation: f64) -> (f64, f64) {
+    let (sin, cos) = rotation.sin_cos();
+    let (sx, sy) = (x * scale_x, y * scale_y);
+    (sx * cos - sy * sin, sx * sin + sy * cos)
+}
+
+#[test]
+fn test_mirrored_blocks() {
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    // 片開き扉 (左右反転・上下反転・回転との組み合わせ)
+    let fixtures = [
+        (-1.0, 1.0, 0.0),
+        (1.0, -1.0, 0.0),
+        (-1.0, -1.0, 0.0),
+        (1.0, -1.0, FRAC_PI_2),
+        (-2.0, 0.5, PI / 6.0),
+        (0.5, -2.0, -PI / 3.0),
+    ];
+
+    for (scale_x, scale_y, rotation) in fixtures {
+        let doc = document(vec![block_ref(0.0, 0.0, scale_x, scale_y, rotation, 1)]);
+        let dxf = jww_dxf::convert_document(&doc);
+        let insert = match &dxf.entities[0] {
+            Entity::Insert(insert) => insert,
+            other => panic!("expected INSERT, got {:?}", other),
+        };
+
+        assert!(insert.scale_y > 0.0);
+        assert!((0.0..360.0).contains(&insert.rotation));
+        assert_eq!(insert.scale_x < 0.0, scale_x * scale_y < 0.0);
+
+        // 扉の蝶番側と開き側の点が同じ位置に配置される
+        for (x, y) in [(0.0, 0.0), (900.0, 0.0), (0.0, 900.0), (300.0, 700.0)] {
+            let expected = transform_point(x, y, scale_x, scale_y, rotation);
+            let actual = transform_point(x, y, insert.scale_x, insert.scale_y, insert.rotation.to_radians());
+            assert!((expected.0 - actual.0).abs() < 1e-6, "{:?}", (scale_x, scale_y, rotation));
+            assert!((expected.1 - actual.1).abs() < 1e-6, "{:?}", (scale_x, scale_y, rotation));
+        }
+    }
+}
+
+#[test]
+fn test_rebase_blocks() {
+    let mut doc = document(vec![block_ref(3000.0, 0.0, -1.0, 1.0, 0.0, 2)]);
+    doc.block_defs = vec![
+        block_def(
+            2,
+            "WINDOW",
+            vec![
+                line(1000.0, 200.0, 1900.0, 200.0),
+                block_ref(1450.0, 200.0, 0.5, 0.5, std::f64::consts::FRAC_PI_2, 1),
+            ],
+        ),
+        block_def(1, "PANE", vec![line(100.0, 50.0, 200.0, 50.0)]),
+    ];
+
+    let line_points = |entity: &Entity| match entity {
+        Entity::Line(l) => (l.x1, l.y1, l.x2, l.y2),
+        other => panic!("expected LINE, got {:?}", other),
+    };
+    let insert_point = |entity: &Entity| match entity {
+        Entity::Insert(insert) => (insert.x, insert.y),
+        other => panic!("expected INSERT, got {:?}", other),
+    };
+
+    // 既定ではJWWの座標のまま
+    let dxf = jww_dxf::convert_document(&doc);
+    assert_eq!(line_points(&dxf.blocks[0].entities[0]), (100.0, 50.0, 200.0, 50.0));
+    assert_eq!(insert_point(&dxf.entities[0]), (3000.0, 0.0));
+
+    let options = ConvertOptions {
+        rebase_blocks: true,
+        ..Default::default()
+    };
+    let dxf = jww_dxf::convert_document_with_options(&doc, &options);
+    assert!(dxf.blocks.iter().all(|b| b.base_x == 0.0 && b.base_y == 0.0));
+
+    // 構成要素は範囲の左下が原点になるように移る
+    assert_eq!(line_points(&dxf.blocks[0].entities[0]), (0.0, 0.0, 100.0, 0.0));
+    assert_eq!(line_points(&dxf.blocks[1].entities[0]), (0.0, 0.0, 900.0, 0.0));
+
+    // 挿入点は倍率・回転を掛けた移動量だけ移り、図形の位置は変わらない
+    let (x, y) = insert_point(&dxf.blocks[1].entities[1]);
+    assert!((x - 425.0).abs() < 1e-9 && (y - 50.0).abs() < 1e-9, "{:?}", (x, y));
+    let (x, y) = insert_point(&dxf.entities[0]);
+    assert!((x - 2000.0).abs() < 1e-9 && (y - 200.0).abs() < 1e-9, "{:?}", (x, y));
+}
+
+#[test]
+fn test_provenance_comments() {
+    let doc = document(vec![line(0.0, 0.0, 1.0, 0.0)]);
+
+    let dxf = jww_dxf::convert_document(&doc);
+    assert!(!jww_dxf::to_string(&dxf).contains("999\n"));
+
+    let options = ConvertOptions {
+        provenance: Some(jww_dxf::Provenance {
+            source_name: Some("plan.jww".to_string()),
+            timestamp: Some(1_700_000_000),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let dxf = jww_dxf::convert_document_with_options(&doc, &options);
+    let output = jww_dxf::to_string(&dxf);
+
+    let expected = format!(
+        "999\nConverted by jww-dxf {}\n999\nSource: plan.jww\n999\nConverted at: 2023-11-14T22:13:20Z\n0\nSECTION\n2\nHEADER\n",
+        env!("CARGO_PKG_VERSION")
+    );
+    assert!(output.starts_with(&expected), "{}", &output[..200]);
+}
+
+#[test]
+fn test_provenance_header_variables() {
+    let mut doc = document(vec![line(0.0, 0.0, 1.0, 0.0)]);
+    doc.memo = "\n  〇〇邸 新築工事  \n平面図".into();
+
+    let dxf = jww_dxf::convert_document(&doc);
+    assert!(dxf.header.created.is_none());
+    assert!(dxf.header.project_name.is_none());
+
+    let options = ConvertOptions {
+        provenance: Some(jww_dxf::Provenance {
+            timestamp: Some(1_700_000_000),
+            source_modified: Some(1_699_913_600),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let dxf = jww_dxf::convert_document_with_options(&doc, &options);
+
+    // 2023-11-14T22:13:20Z と その1日前
+    let updated = dxf.header.updated.unwrap();
+    let created = dxf.header.created.unwrap();
+    assert!((updated - 2_460_263.425_925_926).abs() < 1e-6);
+    assert!((updated - created - 1.0).abs() < 1e-9);
+    assert_eq!(dxf.header.project_name.as_deref(), Some("〇〇邸 新築工事"));
+
+    let output = jww_dxf::to_string(&dxf);
+    assert!(output.contains("9\n$TDCREATE\n40\n"));
+    assert!(output.contains("9\n$TDUPDATE\n40\n"));
+    assert!(output.contains("9\n$PROJECTNAME\n1\n〇〇邸 新築工事\n"));
+}
+
+#[test]
+fn test_appid_table_for_xdata() {
+    let doc = document(vec![line(0.0, 0.0, 10.0, 0.0)]);
+
+    let output = jww_dxf::to_string(&jww_dxf::convert_document(&doc));
+    assert!(!output.contains("APPID"));
+
+    let options = ConvertOptions {
+        xdata: true,
+        ..Default::default()
+    };
+    let output = jww_dxf::to_string(&jww_dxf::convert_document_with_options(&doc, &options));
+    let table = format!(
+        "0\nTABLE\n2\nAPPID\n70\n2\n0\nAPPID\n2\nACAD\n70\n0\n0\nAPPID\n2\n{}\n70\n0\n0\nENDTAB\n",
+        jww_dxf::XDATA_APP_NAME
+    );
+    let table_pos = output.find(&table).expect("APPID table");
+    let xdata_pos = output.find(&format!("1001\n{}\n", jww_dxf::XDATA_APP_NAME)).unwrap();
+    assert!(table_pos < xdata_pos);
+}
+
+fn dimension(start: (f64, f64), end: (f64, f64), offset: f64, content: &str) -> JwwEntity<'_> {
+    let point = |x: f64, y: f64| jww_core::Point {
+        base: base(),
+        position: Point2D::new(x, y),
+        is_temporary: false,
+        code: 0,
+        angle: Angle::ZERO,
+        scale: 1.0,
+    };
+    let dim_line = |x1: f64, y1: f64, x2: f64, y2: f64| Line {
+        base: base(),
+        start: Point2D::new(x1, y1),
+        end: Point2D::new(x2, y2),
+    };
+    // 縮尺1/100でのJw_cadの初期設定 (離れ1mm・はみ出し2mm・寸法値の間隔1mm)
+    let (x1, y1, x2, y2) = (start.0, start.1 + offset, end.0, end.1 + offset);
+    let JwwEntity::Text(text) = text((x1 + x2) / 2.0, y1 + 100.0, 3.5, content) else {
+        unreachable!()
+    };
+    JwwEntity::Dimension(jww_core::Dimension {
+        base: base(),
+        line: dim_line(x1, y1, x2, y2),
+        text,
+        sxf_mode: 0,
+        extension_lines: vec![
+            dim_line(start.0, start.1 + 100.0, x1, y1 + 200.0),
+            dim_line(end.0, end.1 + 100.0, x2, y2 + 200.0),
+        ],
+        end_points: vec![point(x1, y1), point(x2, y2), point(start.0, start.1), point(end.0, end.1)],
+    })
+}
+
+#[test]
+fn test_dimension_style() {
+    let mut doc = document(vec![dimension((0.0, 0.0), (1000.0, 0.0), 500.0, "1000")]);
+    doc.layer_groups[0].scale = 100.0;
+    let options = ConvertOptions {
+        dimension_style: jww_dxf::DimensionStyle {
+            arrow_size: Some(2.0),
+            extension_offset: Some(3.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let dxf = jww_dxf::convert_document_with_options(&doc, &options);
+
+    // 未指定の項目は図面の寸法から読み取る
+    let style = &dxf.dim_styles[0];
+    assert_eq!(style.scale, 100.0);
+    assert_eq!(style.arrow_size, 2.0);
+    assert_eq!(style.extension_offset, 3.0);
+    assert_eq!(style.extension_extension, 2.0);
+    assert_eq!(style.text_gap, 1.0);
+    assert_eq!(style.text_height, 3.5);
+
+    let Entity::Dimension(dim) = &dxf.entities[0] else {
+        panic!("expected DIMENSION, got {:?}", dxf.entities[0]);
+    };
+    assert_eq!(dim.style, style.name);
+    assert_eq!((dim.ext1_x, dim.ext1_y), (0.0, 0.0));
+    assert_eq!((dim.ext2_x, dim.ext2_y), (1000.0, 0.0));
+    assert_eq!((dim.def_x, dim.def_y), (1000.0, 500.0));
+    assert_eq!(dim.angle, 0.0);
+
+    // 寸法図形は用紙上の大きさに縮尺を掛けて作成する
+    let lines: Vec<_> = dim
+        .geometry
+        .iter()
+        .filter_map(|e| match e {
+            Entity::Line(l) => Some((l.x1, l.y1, l.x2, l.y2)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            (0.0, 500.0, 1000.0, 500.0),
+            (0.0, 300.0, 0.0, 700.0),
+            (1000.0, 300.0, 1000.0, 700.0),
+        ]
+    );
+    let arrow_bases: Vec<_> = dim
+        .geometry
+        .iter()
+        .filter_map(|e| match e {
+            Entity::Solid(s) => Some(((s.x1, s.y1), (s.x2 + s.x3) / 2.0)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(arrow_bases, vec![((0.0, 500.0), 200.0), ((1000.0, 500.0), 800.0)]);
+    let text_height = dim.geometry.iter().find_map(|e| match e {
+        Entity::Text(t) => Some(t.height),
+        _ => None,
+    });
+    assert_eq!(text_height, Some(350.0));
+
+    let output = jww_dxf::to_string(&dxf);
+    assert!(output.contains("9\n$DIMSTYLE\n2\nJWW\n"));
+    assert!(output.contains("0\nDIMSTYLE\n2\nJWW\n70\n0\n40\n100\n41\n2\n42\n3\n44\n2\n140\n3.5\n147\n1\n"));
+    assert!(output.contains("0\nBLOCK\n8\n0\n2\n*D1\n70\n1\n"));
+    assert!(output.contains("0\nDIMENSION\n"));
+
+    // 寸法がない場合はDIMSTYLEテーブルを出力しない
+    let dxf = jww_dxf::convert_document(&document(vec![line(0.0, 0.0, 10.0, 0.0)]));
+    assert!(dxf.dim_styles.is_empty());
+    assert!(!jww_dxf::to_string(&dxf).contains("DIMSTYLE"));
+}
+
+#[test]
+fn test_dimension_text_gap() {
+    let mut doc = document(vec![dimension((0.0, 0.0), (1000.0, 0.0), 500.0, "1000")]);
+    doc.layer_groups[0].scale = 100.0;
+    let options = ConvertOptions {
+        dimension_style: jww_dxf::DimensionStyle {
+            text_gap: Some(2.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let dxf = jww_dxf::convert_document_with_options(&doc, &options);
+    assert_eq!(dxf.dim_styles[0].text_gap, 2.0);
+
+    // 寸法図形の寸法値も寸法スタイルの間隔だけ寸法線から離す
+    let Entity::Dimension(dim) = &dxf.entities[0] else {
+        panic!("expected DIMENSION, got {:?}", dxf.entities[0]);
+    };
+    let text_y = dim.geometry.iter().find_map(|e| match e {
+        Entity::Text(t) => Some(t.y),
+        _ => None,
+    });
+    assert_eq!(text_y, Some(700.0));
+    assert_eq!(dim.text_y, 700.0 + 350.0 / 2.0);
+}
+
+#[test]
+fn test_dimension_text_override() {
+    let doc = document(vec![
+        dimension((0.0, 0.0), (1000.0, 0.0), 500.0, "1,000"),
+        dimension((0.0, 0.0), (1234.56, 0.0), 500.0, "1234.6"),
+        dimension((0.0, 0.0), (1000.0, 0.0), 500.0, "1200"),
+        dimension((0.0, 0.0), (1000.0, 0.0), 500.0, "W=1000"),
+        dimension((0.0, 0.0), (1000.0, 0.0), 500.0, ""),
+    ]);
+    let dxf = jww_dxf::convert_document(&doc);
+    let overrides: Vec<_> = dxf
+        .entities
+        .iter()
+        .map(|e| match e {
+            Entity::Dimension(dim) => dim.text_override.as_deref(),
+            _ => panic!("expected DIMENSION, got {:?}", e),
+        })
+        .collect();
+    // 計測値 (桁区切り・丸めを含む) は上書きしない
+    assert_eq!(overrides, vec![None, None, Some("1200"), Some("W=1000"), Some(" ")]);
+
+    let output = jww_dxf::to_string(&dxf);
+    assert!(output.contains("70\n32\n1\n1200\n13\n"));
+    assert!(output.contains("70\n32\n13\n"));
+    let Entity::Dimension(dim) = &jww_dxf::reader::parse(&output).unwrap().entities[2] else {
+        panic!("expected DIMENSION");
+    };
+    assert_eq!(dim.text_override.as_deref(), Some("1200"));
+}
+
+/// 曲線属性を設定する
+fn grouped(group: u32, mut entity: JwwEntity) -> JwwEntity {
+    match &mut entity {
+        JwwEntity::Line(e) => e.base.group = group,
+        JwwEntity::Text(e) => e.base.group = group,
+        JwwEntity::Solid(e) => e.base.group = group,
+        _ => unreachable!(),
+    }
+    entity
+}
+
+#[test]
+fn test_leaders() {
+    let doc = document(vec![
+        line(-50.0, 0.0, 50.0, 0.0),
+        // 矢印 (先端で交わる短い2本の線) 付きの折れ線と文字
+        grouped(3, line(0.0, 0.0, 100.0, 100.0)),
+        grouped(3, line(0.0, 0.0, 3.0, 1.0)),
+        grouped(3, line(0.0, 0.0, 1.0, 3.0)),
+        grouped(3, line(100.0, 100.0, 130.0, 100.0)),
+        grouped(3, text(105.0, 101.0, 5.0, "W=900")),
+        // 文字から離れた線は引出線とみなさない
+        grouped(4, line(0.0, -100.0, 100.0, -100.0)),
+        grouped(4, text(500.0, 500.0, 5.0, "A")),
+    ]);
+    let options = ConvertOptions {
+        leaders: true,
+        ..Default::default()
+    };
+    let dxf = jww_dxf::convert_document_with_options(&doc, &options);
+
+    let kinds: Vec<_> = dxf.entities.iter().map(Entity::type_name).collect();
+    assert_eq!(kinds, vec!["LINE", "LEADER", "TEXT", "LINE", "TEXT"]);
+    let Entity::Leader(leader) = &dxf.entities[1] else {
+        unreachable!()
+    };
+    assert!(leader.arrowhead);
+    assert_eq!(leader.style, dxf.dim_styles[0].name);
+    let vertices: Vec<_> = leader.vertices.iter().map(|v| (v.x, v.y)).collect();
+    assert_eq!(vertices, vec![(0.0, 0.0), (100.0, 100.0), (130.0, 100.0)]);
+    assert_eq!(dxf.groups.len(), 1);
+    assert_eq!(dxf.groups[0].name, "JWW_LEADER_3");
+    assert_eq!(dxf.groups[0].entities, vec![1, 2]);
+
+    let output = jww_dxf::to_string(&dxf);
+    assert!(output.contains("0\nLEADER\n"));
+    assert!(output.contains("3\nJWW\n71\n1\n72\n0\n73\n3\n76\n3\n10\n0\n20\n0\n30\n0.0\n10\n100\n"));
+    let parsed = jww_dxf::reader::parse(&output).unwrap();
+    assert_eq!(parsed.entities[1], dxf.entities[1]);
+
+    // LEADERはR13以降のエンティティのため、R2000の形式で出力する
+    assert!(output.contains("$ACADVER\n1\nAC1015\n"));
+    assert!(output.contains("0\nLEADER\n5\n"));
+    assert!(output.contains("100\nAcDbEntity\n8\n0-0\n62\n"));
+    assert!(output.contains("100\nAcDbLeader\n3\nJWW\n"));
+    assert!(output.contains("0\nBLOCK_RECORD\n"));
+    let findings = jww_dxf::validate::validate(&output);
+    assert!(findings.is_empty(), "{:?}", findings);
+    // バイナリDXFのグループコードは2バイト
+    let binary = jww_dxf::to_bytes(&dxf, jww_dxf::DxfFormat::Binary);
+    assert!(binary.ends_with(b"ENDSEC\x00\x00\x00EOF\x00"));
+
+    // 既定では線と文字のまま出力する
+    let dxf = jww_dxf::convert_document(&doc);
+    assert!(dxf.entities.iter().all(|e| !matches!(e, Entity::Leader(_))));
+}
+
+#[test]
+fn test_to_bytes() {
+    use jww_dxf::DxfFormat;
+
+    let doc = document(vec![line(0.0, 0.0, 10.0, 0.0), text(0.0, 0.0, 3.0, "平面図①🏠")]);
+    let dxf = jww_dxf::convert_document(&doc);
+
+    let utf8 = jww_dxf::to_bytes(&dxf, DxfFormat::Utf8);
+    assert_eq!(utf8, jww_dxf::to_string(&dxf).into_bytes());
+
+    // Shift-JISで表せない文字は \U+XXXX で出力する
+    let sjis = jww_dxf::to_bytes(&dxf, DxfFormat::ShiftJis);
+    let (decoded, _, had_errors) = encoding_rs::SHIFT_JIS.decode(&sjis);
+    assert!(!had_errors);
+    assert!(decoded.contains("$DWGCODEPAGE\n3\nANSI_932\n"));
+    assert!(decoded.contains("平面図①\\U+1F3E0\n"));
+
+    let binary = jww_dxf::to_bytes(&dxf, DxfFormat::Binary);
+    assert!(binary.starts_with(b"AutoCAD Binary DXF\r\n\x1a\x00"));
+    assert!(binary.ends_with(b"\x00EOF\x00"));
+    // LINEの終点X (グループコード11) は8バイトの倍精度浮動小数点数
+    let start = binary
+        .windows(5)
+        .position(|w| w == b"LINE\x00")
+        .unwrap();
+    let code11 = start + binary[start..].iter().position(|&b| b == 11).unwrap();
+    let value = f64::from_le_bytes(binary[code11 + 1..code11 + 9].try_into().unwrap());
+    assert_eq!(value, 10.0);
+}
+
+#[test]
+fn test_incremental_converter() {
+    let doc = document(vec![
+        line(0.0, 0.0, 10.0, 0.0),
+        text(0.0, 0.0, 3.0, "A"),
+        dimension((0.0, 0.0), (1000.0, 0.0), 500.0, "1000"),
+        line(0.0, 0.0, 0.0, 10.0),
+    ]);
+    let options = ConvertOptions {
+        sort_by_layer: true,
+        ..Default::default()
+    };
+
+    let mut converter = jww_dxf::IncrementalConverter::new(&doc, &options);
+    assert_eq!(converter.progress(), (0, 4));
+    let mut steps = 0;
+    while !converter.step(3) {
+        steps += 1;
+    }
+    assert_eq!(steps, 1);
+    assert_eq!(converter.progress(), (4, 4));
+    let (dxf, report) = converter.finish();
+
+    // 一度に変換した場合と同じ結果になる
+    let (expected, expected_report) = jww_dxf::convert_document_with_report(&doc, &options);
+    assert_eq!(jww_dxf::to_string(&dxf), jww_dxf::to_string(&expected));
+    assert_eq!(report.output_counts, expected_report.output_counts);
+}
+
+#[test]
+fn test_sort_by_layer_after_direct_change() {
+    let mut doc = document(vec![
+        line(0.0, 0.0, 10.0, 0.0),
+        line(0.0, 0.0, 0.0, 10.0),
+        line(0.0, 0.0, 10.0, 10.0),
+    ]);
+    let options = ConvertOptions {
+        sort_by_layer: true,
+        ..Default::default()
+    };
+    assert_eq!(jww_dxf::convert_document_with_options(&doc, &options).entities.len(), 3);
+
+    // 索引を作った後に entities を直接変更しても、変更後のエンティティで変換する
+    doc.entities.truncate(1);
+    let mut converter = jww_dxf::IncrementalConverter::new(&doc, &options);
+    while !converter.step(10) {}
+    let (dxf, _) = converter.finish();
+    assert_eq!(dxf.entities.len(), 1);
+}
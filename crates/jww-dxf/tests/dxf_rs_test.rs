@@ -0,0 +1,392 @@
+//! dxfクレートによる出力のテスト (独自ライターとの相互検証)
+
+#![cfg(feature = "dxf-rs")]
+
+use dxf::entities::EntityType;
+use jww_dxf::*;
+
+fn line(x1: f64, y1: f64, x2: f64, y2: f64) -> Entity {
+    Entity::Line(Line {
+        layer: "0-1".to_string(),
+        color: 1,
+        line_type: "CONTINUOUS".to_string(),
+        line_type_scale: None,
+        xdata: None,
+        x1,
+        y1,
+        x2,
+        y2,
+    })
+}
+
+/// 全種類のエンティティを含むドキュメント
+fn sample_document() -> Document {
+    let entities = vec![
+        line(0.0, 0.0, 100.5, 20.25),
+        Entity::Circle(Circle {
+            layer: "0-1".to_string(),
+            color: 256,
+            line_type: "DASHED".to_string(),
+            line_type_scale: Some(0.5),
+            xdata: Some(XData {
+                app_name: XDATA_APP_NAME.to_string(),
+                values: vec![
+                    XDataValue::String("pen_color".to_string()),
+                    XDataValue::Long(2),
+                    XDataValue::Integer(-3),
+                    XDataValue::Real(0.25),
+                ],
+            }),
+            center_x: 10.0,
+            center_y: -5.0,
+            radius: 3.0,
+        }),
+        Entity::Arc(Arc {
+            layer: "0-1".to_string(),
+            color: 3,
+            line_type: "CONTINUOUS".to_string(),
+            line_type_scale: None,
+            xdata: None,
+            center_x: 1.0,
+            center_y: 2.0,
+            radius: 4.0,
+            start_angle: 30.0,
+            end_angle: 120.0,
+        }),
+        Entity::Ellipse(Ellipse {
+            layer: "壁".to_string(),
+            color: 4,
+            line_type: "CONTINUOUS".to_string(),
+            line_type_scale: None,
+            xdata: None,
+            center_x: 0.0,
+            center_y: 0.0,
+            major_axis_x: 10.0,
+            major_axis_y: 0.0,
+            minor_ratio: 0.5,
+            start_param: 0.0,
+            end_param: std::f64::consts::PI,
+        }),
+        Entity::Point(Point {
+            layer: "0-1".to_string(),
+            color: 5,
+            line_type: "CONTINUOUS".to_string(),
+            line_type_scale: None,
+            xdata: None,
+            x: 7.0,
+            y: 8.0,
+        }),
+        Entity::Text(Text {
+            layer: "壁".to_string(),
+            color: 6,
+            line_type: "CONTINUOUS".to_string(),
+            line_type_scale: None,
+            xdata: None,
+            x: 0.0,
+            y: 10.0,
+            height: 3.5,
+            rotation: 90.0,
+            content: "PLAN 1/100".to_string(),
+            style: "JWW".to_string(),
+        }),
+        Entity::Solid(Solid {
+            layer: "0-1".to_string(),
+            color: 7,
+            line_type: "CONTINUOUS".to_string(),
+            line_type_scale: None,
+            xdata: None,
+            x1: 0.0,
+            y1: 0.0,
+            x2: 1.0,
+            y2: 0.0,
+            x3: 0.0,
+            y3: 1.0,
+            x4: 1.0,
+            y4: 1.0,
+        }),
+        Entity::Insert(Insert {
+            layer: "0-1".to_string(),
+            color: 0,
+            line_type: "CONTINUOUS".to_string(),
+            line_type_scale: None,
+            xdata: None,
+            block_name: "DOOR".to_string(),
+            x: 50.0,
+            y: 60.0,
+            scale_x: -1.0,
+            scale_y: 1.0,
+            rotation: 180.0,
+        }),
+        Entity::Polyline(Polyline {
+            layer: "0-1".to_string(),
+            color: 9,
+            line_type: "CONTINUOUS".to_string(),
+            line_type_scale: None,
+            xdata: None,
+            vertices: vec![
+                Vertex { x: 0.0, y: 0.0 },
+                Vertex { x: 1.0, y: 0.5 },
+                Vertex { x: 2.0, y: 0.0 },
+            ],
+            closed: true,
+        }),
+        Entity::Dimension(Dimension {
+            layer: "0-1".to_string(),
+            color: 2,
+            line_type: "CONTINUOUS".to_string(),
+            line_type_scale: None,
+            xdata: None,
+            style: "JWW".to_string(),
+            def_x: 1000.0,
+            def_y: 500.0,
+            text_x: 500.0,
+            text_y: 675.0,
+            ext1_x: 0.0,
+            ext1_y: 0.0,
+            ext2_x: 1000.0,
+            ext2_y: 0.0,
+            angle: 0.0,
+            text_override: None,
+            geometry: vec![line(0.0, 500.0, 1000.0, 500.0)],
+        }),
+        Entity::Leader(Leader {
+            layer: "0-1".to_string(),
+            color: 2,
+            line_type: "CONTINUOUS".to_string(),
+            line_type_scale: None,
+            xdata: None,
+            style: "JWW".to_string(),
+            arrowhead: true,
+            vertices: vec![Vertex { x: 0.0, y: 0.0 }, Vertex { x: 10.0, y: 10.0 }],
+        }),
+    ];
+
+    Document {
+        header: Header {
+            version: DxfVersion::R2000,
+            insunits: 4,
+            measurement: 1,
+            ltscale: 50.0,
+            pdmode: 34,
+            pdsize: 100.0,
+            created: Some(2_460_262.5),
+            updated: Some(2_460_263.5),
+            project_name: Some("HOUSE".to_string()),
+            limits: Some(Limits {
+                min_x: -10.5,
+                min_y: -7.425,
+                max_x: 10.5,
+                max_y: 7.425,
+            }),
+        },
+        active_view: None,
+        layers: vec![
+            Layer {
+                name: "0-1".to_string(),
+                color: 7,
+                line_type: "CONTINUOUS".to_string(),
+                frozen: false,
+                locked: false,
+            },
+            Layer {
+                name: "壁".to_string(),
+                color: -1,
+                line_type: "DASHED".to_string(),
+                frozen: false,
+                locked: false,
+            },
+        ],
+        entities,
+        blocks: vec![Block {
+            name: "DOOR".to_string(),
+            base_x: 0.0,
+            base_y: 0.0,
+            entities: vec![line(0.0, 0.0, 900.0, 0.0)],
+        }],
+        layout: None,
+        groups: Vec::new(),
+        comments: Vec::new(),
+        dim_styles: vec![DimStyle {
+            name: "JWW".to_string(),
+            scale: 100.0,
+            arrow_size: 3.0,
+            extension_offset: 1.0,
+            extension_extension: 2.0,
+            text_gap: 1.0,
+            text_height: 3.5,
+        }],
+    }
+}
+
+/// 比較用にエンティティの種別・共通属性・座標を文字列にする
+fn describe(entity: &dxf::entities::Entity) -> String {
+    let p = |p: &dxf::Point| format!("({:.6},{:.6})", p.x, p.y);
+    let geometry = match &entity.specific {
+        EntityType::Line(e) => format!("LINE {} {}", p(&e.p1), p(&e.p2)),
+        EntityType::Circle(e) => format!("CIRCLE {} {:.6}", p(&e.center), e.radius),
+        EntityType::Arc(e) => format!(
+            "ARC {} {:.6} {:.6} {:.6}",
+            p(&e.center),
+            e.radius,
+            e.start_angle,
+            e.end_angle
+        ),
+        EntityType::Ellipse(e) => format!(
+            "ELLIPSE {} ({:.6},{:.6}) {:.6} {:.6} {:.6}",
+            p(&e.center),
+            e.major_axis.x,
+            e.major_axis.y,
+            e.minor_axis_ratio,
+            e.start_parameter,
+            e.end_parameter
+        ),
+        EntityType::ModelPoint(e) => format!("POINT {}", p(&e.location)),
+        EntityType::Text(e) => format!(
+            "TEXT {} {:.6} {:.6} {} {}",
+            p(&e.location),
+            e.text_height,
+            e.rotation,
+            e.value,
+            e.text_style_name
+        ),
+        EntityType::Solid(e) => format!(
+            "SOLID {} {} {} {}",
+            p(&e.first_corner),
+            p(&e.second_corner),
+            p(&e.third_corner),
+            p(&e.fourth_corner)
+        ),
+        EntityType::Insert(e) => format!(
+            "INSERT {} {} {:.6} {:.6} {:.6}",
+            e.name,
+            p(&e.location),
+            e.x_scale_factor,
+            e.y_scale_factor,
+            e.rotation
+        ),
+        EntityType::Polyline(e) => format!(
+            "POLYLINE closed={} {}",
+            e.is_closed(),
+            e.vertices().map(|v| p(&v.location)).collect::<Vec<_>>().join(" ")
+        ),
+        EntityType::RotatedDimension(e) => format!(
+            "DIMENSION {} {} {} {} {} {:.6} {}",
+            e.dimension_base.dimension_style_name,
+            p(&e.dimension_base.definition_point_1),
+            p(&e.dimension_base.text_mid_point),
+            p(&e.definition_point_2),
+            p(&e.definition_point_3),
+            e.rotation_angle,
+            e.dimension_base.block_name
+        ),
+        EntityType::Leader(e) => format!(
+            "LEADER {} {} {}",
+            e.dimension_style_name,
+            e.use_arrowheads,
+            e.vertices.iter().map(p).collect::<Vec<_>>().join(" ")
+        ),
+        other => panic!("unexpected entity {:?}", other),
+    };
+    let xdata: Vec<String> = entity
+        .common
+        .x_data
+        .iter()
+        .map(|x| format!("{}{:?}", x.application_name, x.items))
+        .collect();
+    format!(
+        "{} layer={} color={:?} ltype={} ltscale={:.6} xdata={:?}",
+        geometry,
+        entity.common.layer,
+        entity.common.color,
+        entity.common.line_type_name,
+        entity.common.line_type_scale,
+        xdata
+    )
+}
+
+/// 図面のモデル空間・ブロック・レイヤーを比較用の文字列にする
+fn summary(drawing: &dxf::Drawing) -> Vec<String> {
+    let mut lines: Vec<String> = drawing.entities().map(describe).collect();
+    // R2000のモデル空間・ペーパー空間のブロックは空のため比較しない
+    for block in drawing.blocks().filter(|b| !matches!(b.name.as_str(), "*Model_Space" | "*Paper_Space")) {
+        lines.push(format!("BLOCK {} flags={}", block.name, block.flags));
+        lines.extend(block.entities.iter().map(describe));
+    }
+    for layer in drawing.layers() {
+        lines.push(format!(
+            "LAYER {} color={:?} on={} ltype={}",
+            layer.name, layer.color, layer.is_layer_on, layer.line_type_name
+        ));
+    }
+    lines
+}
+
+fn load(bytes: &[u8]) -> dxf::Drawing {
+    dxf::Drawing::load(&mut std::io::Cursor::new(bytes)).expect("load")
+}
+
+/// 独自ライターの出力 (UTF-8) を読み取る
+fn load_utf8(output: &str) -> dxf::Drawing {
+    dxf::Drawing::load_with_encoding(&mut output.as_bytes(), encoding_rs::UTF_8).expect("load")
+}
+
+#[test]
+fn test_matches_writer_output() {
+    let doc = sample_document();
+
+    // 独自ライターの出力をdxfクレートで読み取った内容と、直接変換した内容が一致する
+    let written = load_utf8(&to_string(&doc));
+    let converted = dxf_rs::to_drawing(&doc);
+    assert_eq!(summary(&written), summary(&converted));
+    assert_eq!(written.header.version, dxf::enums::AcadVersion::R2000);
+    assert_eq!(converted.header.version, dxf::enums::AcadVersion::R2000);
+
+    // dxfクレートで書き出した内容も読み戻せる
+    let mut output = Vec::new();
+    dxf_rs::write_drawing(&doc, &mut output).expect("write");
+    let saved = load(&output);
+    assert_eq!(summary(&saved), summary(&converted));
+
+    // dxfクレートの出力は独自のリーダーでも読み取れる
+    let parsed = reader::parse(std::str::from_utf8(&output).unwrap()).expect("parse");
+    assert_eq!(parsed.entities.len(), doc.entities.len());
+    assert_eq!(
+        parsed.entities.iter().map(Entity::type_name).collect::<Vec<_>>(),
+        doc.entities.iter().map(Entity::type_name).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_header_variables() {
+    let doc = sample_document();
+    let mut output = Vec::new();
+    dxf_rs::write_drawing(&doc, &mut output).expect("write");
+    let header = load(&output).header;
+
+    assert_eq!(header.default_drawing_units, dxf::enums::Units::Millimeters);
+    assert_eq!(header.drawing_units, dxf::enums::DrawingUnits::Metric);
+    assert_eq!(header.line_type_scale, 50.0);
+    assert_eq!(header.point_display_mode, 34);
+    assert_eq!(header.point_display_size, 100.0);
+    assert_eq!(header.minimum_drawing_limits, dxf::Point::new(-10.5, -7.425, 0.0));
+    assert_eq!(header.maximum_drawing_limits, dxf::Point::new(10.5, 7.425, 0.0));
+    assert_eq!(header.dimension_style_name, "JWW");
+    // 日時はユリウス日のまま書き出すため、秒未満の誤差を許容する
+    assert!((header.creation_date_universal.timestamp() - 1_699_920_000).abs() <= 1);
+    assert!((header.update_date_universal.timestamp() - 1_700_006_400).abs() <= 1);
+}
+
+#[test]
+fn test_r12_output() {
+    // R2000のエンティティを含まなければR12で出力する
+    // (dxfクレートは寸法の種類をサブクラスマーカーで判定するため、R12の寸法は読み取れない)
+    let mut doc = sample_document();
+    doc.header.version = DxfVersion::R12;
+    doc.entities
+        .retain(|e| !matches!(e, Entity::Ellipse(_) | Entity::Leader(_) | Entity::Dimension(_)));
+
+    let written = load_utf8(&to_string(&doc));
+    let converted = dxf_rs::to_drawing(&doc);
+    assert_eq!(converted.header.version, dxf::enums::AcadVersion::R12);
+    assert_eq!(summary(&written), summary(&converted));
+}
@@ -0,0 +1,131 @@
+//! 図面セットの変換のテスト
+
+use jww_core::{Angle, Document as JwwDocument, Entity as JwwEntity, EntityBase, Line, Point2D};
+use jww_dxf::{Entity, Project};
+
+fn base(layer: u16) -> EntityBase {
+    EntityBase {
+        pen_color: 2,
+        layer,
+        ..Default::default()
+    }
+}
+
+fn line(layer: u16, length: f64) -> JwwEntity<'static> {
+    JwwEntity::Line(Line {
+        base: base(layer),
+        start: Point2D::new(0.0, 0.0),
+        end: Point2D::new(length, 0.0),
+    })
+}
+
+fn block_ref(def_number: u32) -> JwwEntity<'static> {
+    JwwEntity::Block(jww_core::Block {
+        base: base(0),
+        reference: Point2D::new(0.0, 0.0),
+        scale_x: 1.0,
+        scale_y: 1.0,
+        rotation: Angle::ZERO,
+        def_number,
+    })
+}
+
+fn block_def(number: u32, name: &str, entities: Vec<JwwEntity<'static>>) -> jww_core::BlockDef<'static> {
+    jww_core::BlockDef {
+        base: base(0),
+        number,
+        is_referenced: true,
+        name: name.to_string(),
+        entities,
+    }
+}
+
+/// `DOOR` ブロック (線の長さ `door`) と、それを含む `UNIT` ブロックを持つ図面
+fn document(door: f64) -> JwwDocument<'static> {
+    let mut doc = JwwDocument {
+        version: 600,
+        entities: vec![line(1, 10.0), block_ref(2)],
+        block_defs: vec![
+            block_def(1, "DOOR", vec![line(0, door)]),
+            block_def(2, "UNIT", vec![block_ref(1)]),
+        ],
+        ..Default::default()
+    };
+    doc.layer_groups[0].layers[1].name = "壁".to_string();
+    doc
+}
+
+fn inserts(entities: &[Entity]) -> Vec<&str> {
+    entities
+        .iter()
+        .filter_map(|e| match e {
+            Entity::Insert(insert) => Some(insert.block_name.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_project_layer_prefixes() {
+    let mut project = Project::new();
+    project.add("1F", document(1.0));
+    project.add("2F", document(1.0));
+    let dxf = project.to_dxf();
+
+    assert_eq!(dxf.entities.len(), 4);
+    assert_eq!(
+        dxf.layers.len(),
+        2 * jww_dxf::convert_document(&document(1.0)).layers.len()
+    );
+    let Entity::Line(first) = &dxf.entities[0] else {
+        panic!("{:?}", dxf.entities[0]);
+    };
+    let Entity::Line(second) = &dxf.entities[2] else {
+        panic!("{:?}", dxf.entities[2]);
+    };
+    assert_eq!(first.layer, "1F$壁");
+    assert_eq!(second.layer, "2F$壁");
+    assert!(dxf.layers.iter().any(|l| l.name == "2F$壁"));
+    assert!(dxf.active_view.is_none());
+
+    // 同じ内容のブロック定義は共有する
+    assert_eq!(dxf.blocks.len(), 2);
+    assert_eq!(inserts(&dxf.entities), ["UNIT", "UNIT"]);
+}
+
+#[test]
+fn test_project_block_conflicts() {
+    let mut project = Project::new();
+    project.add("1F", document(1.0));
+    project.add("2F", document(2.0));
+    let dxf = project.to_dxf();
+
+    // 内容の異なるDOORと、それを参照するUNITは後のファイルの名前を変える
+    let names: Vec<&str> = dxf.blocks.iter().map(|b| b.name.as_str()).collect();
+    assert_eq!(names, ["DOOR", "UNIT", "2F$DOOR", "2F$UNIT"]);
+    assert_eq!(inserts(&dxf.entities), ["UNIT", "2F$UNIT"]);
+    assert_eq!(inserts(&dxf.blocks[3].entities), ["2F$DOOR"]);
+
+    let text = jww_dxf::to_string(&dxf);
+    let findings = jww_dxf::validate::validate(&text);
+    assert!(!jww_dxf::validate::has_errors(&findings), "{:?}", findings);
+}
+
+#[test]
+fn test_project_prefixes() {
+    let mut project = Project::new();
+    project.add("a/b", document(1.0));
+    project.add("a_b", document(1.0));
+    let prefixes: Vec<&str> = project.files.iter().map(|f| f.prefix.as_str()).collect();
+    assert_eq!(prefixes, ["a_b", "a_b_2"]);
+
+    let err = Project::load(["no-such-file.jww"]).unwrap_err();
+    assert!(matches!(err, jww_dxf::ProjectError::Io { .. }));
+
+    let single =
+        Project::load([std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../tests/fixtures/geometry.jww")])
+        .unwrap();
+    assert_eq!(single.files[0].prefix, "geometry");
+    assert!(single.to_dxf().active_view.is_some());
+}
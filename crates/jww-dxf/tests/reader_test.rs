@@ -0,0 +1,303 @@
+//! DXF読み込みのテスト
+
+use jww_dxf::reader::{self, ReadError};
+use jww_dxf::*;
+
+fn line(x1: f64, y1: f64, x2: f64, y2: f64) -> Entity {
+    Entity::Line(Line {
+        layer: "0-1".to_string(),
+        color: 1,
+        line_type: "CONTINUOUS".to_string(),
+        line_type_scale: None,
+        xdata: None,
+        x1,
+        y1,
+        x2,
+        y2,
+    })
+}
+
+/// 全種類のエンティティとセクションを含むドキュメント
+fn sample_document() -> Document {
+    let xdata = XData {
+        app_name: XDATA_APP_NAME.to_string(),
+        values: vec![
+            XDataValue::String("pen_color".to_string()),
+            XDataValue::Long(2),
+            XDataValue::Integer(-3),
+            XDataValue::Real(0.25),
+        ],
+    };
+
+    let entities = vec![
+        line(0.0, 0.0, 100.5, 20.25),
+        Entity::Circle(Circle {
+            layer: "0-1".to_string(),
+            color: 2,
+            line_type: "DASHED".to_string(),
+            line_type_scale: Some(0.5),
+            xdata: Some(xdata.clone()),
+            center_x: 10.0,
+            center_y: -5.0,
+            radius: 3.0,
+        }),
+        Entity::Arc(Arc {
+            layer: "0-1".to_string(),
+            color: 3,
+            line_type: "CONTINUOUS".to_string(),
+            line_type_scale: None,
+            xdata: None,
+            center_x: 1.0,
+            center_y: 2.0,
+            radius: 4.0,
+            start_angle: 30.0,
+            end_angle: 120.0,
+        }),
+        Entity::Ellipse(Ellipse {
+            layer: "壁".to_string(),
+            color: 4,
+            line_type: "CONTINUOUS".to_string(),
+            line_type_scale: None,
+            xdata: None,
+            center_x: 0.0,
+            center_y: 0.0,
+            major_axis_x: 10.0,
+            major_axis_y: 0.0,
+            minor_ratio: 0.5,
+            start_param: 0.0,
+            end_param: std::f64::consts::PI,
+        }),
+        Entity::Point(Point {
+            layer: "0-1".to_string(),
+            color: 5,
+            line_type: "CONTINUOUS".to_string(),
+            line_type_scale: None,
+            xdata: None,
+            x: 7.0,
+            y: 8.0,
+        }),
+        Entity::Text(Text {
+            layer: "壁".to_string(),
+            color: 6,
+            line_type: "CONTINUOUS".to_string(),
+            line_type_scale: None,
+            xdata: None,
+            x: 0.0,
+            y: 10.0,
+            height: 3.5,
+            rotation: 90.0,
+            content: " 平面図 1/100".to_string(),
+            style: "STANDARD".to_string(),
+        }),
+        Entity::Solid(Solid {
+            layer: "0-1".to_string(),
+            color: 7,
+            line_type: "CONTINUOUS".to_string(),
+            line_type_scale: None,
+            xdata: None,
+            x1: 0.0,
+            y1: 0.0,
+            x2: 1.0,
+            y2: 0.0,
+            x3: 0.0,
+            y3: 1.0,
+            x4: 1.0,
+            y4: 1.0,
+        }),
+        Entity::Insert(Insert {
+            layer: "0-1".to_string(),
+            color: 8,
+            line_type: "CONTINUOUS".to_string(),
+            line_type_scale: None,
+            xdata: Some(xdata),
+            block_name: "DOOR".to_string(),
+            x: 50.0,
+            y: 60.0,
+            scale_x: -1.0,
+            scale_y: 1.0,
+            rotation: 180.0,
+        }),
+        Entity::Polyline(Polyline {
+            layer: "0-1".to_string(),
+            color: 9,
+            line_type: "CONTINUOUS".to_string(),
+            line_type_scale: None,
+            xdata: None,
+            vertices: vec![
+                Vertex { x: 0.0, y: 0.0 },
+                Vertex { x: 1.0, y: 0.5 },
+                Vertex { x: 2.0, y: 0.0 },
+            ],
+            closed: true,
+        }),
+        Entity::Dimension(Dimension {
+            layer: "0-1".to_string(),
+            color: 2,
+            line_type: "CONTINUOUS".to_string(),
+            line_type_scale: None,
+            xdata: None,
+            style: "JWW".to_string(),
+            def_x: 1000.0,
+            def_y: 500.0,
+            text_x: 500.0,
+            text_y: 675.0,
+            ext1_x: 0.0,
+            ext1_y: 0.0,
+            ext2_x: 1000.0,
+            ext2_y: 0.0,
+            angle: 0.0,
+            text_override: Some("約1m".to_string()),
+            geometry: vec![line(0.0, 500.0, 1000.0, 500.0)],
+        }),
+    ];
+
+    Document {
+        header: Header {
+            version: DxfVersion::R2000,
+            insunits: 6,
+            measurement: 1,
+            ltscale: 50.0,
+            pdmode: 34,
+            pdsize: 100.0,
+            created: Some(2_460_262.5),
+            updated: Some(2_460_263.425_925_926),
+            project_name: Some("〇〇邸".to_string()),
+            limits: Some(Limits {
+                min_x: -10.5,
+                min_y: -7.425,
+                max_x: 10.5,
+                max_y: 7.425,
+            }),
+        },
+        active_view: Some(ActiveView {
+            center_x: 1.5,
+            center_y: -2.5,
+            height: 420.0,
+            aspect_ratio: 1.414,
+        }),
+        layers: vec![
+            Layer {
+                name: "0-1".to_string(),
+                color: 7,
+                line_type: "CONTINUOUS".to_string(),
+                frozen: false,
+                locked: true,
+            },
+            Layer {
+                name: "壁".to_string(),
+                color: 1,
+                line_type: "DASHED".to_string(),
+                frozen: true,
+                locked: false,
+            },
+        ],
+        entities,
+        blocks: vec![Block {
+            name: "DOOR".to_string(),
+            base_x: 0.0,
+            base_y: 0.0,
+            entities: vec![line(0.0, 0.0, 900.0, 0.0)],
+        }],
+        layout: Some(Layout {
+            paper_width: 420.0,
+            paper_height: 297.0,
+            viewport: Viewport {
+                center_x: 210.0,
+                center_y: 148.5,
+                width: 400.0,
+                height: 280.0,
+                view_center_x: 0.0,
+                view_center_y: 0.0,
+                view_height: 28000.0,
+            },
+        }),
+        groups: vec![Group {
+            name: "JWW_CURVE_1".to_string(),
+            description: "JWW curve 1".to_string(),
+            entities: vec![0, 2],
+        }],
+        comments: vec!["Converted by jww-dxf".to_string()],
+        dim_styles: vec![DimStyle {
+            name: "JWW".to_string(),
+            scale: 100.0,
+            arrow_size: 3.0,
+            extension_offset: 1.0,
+            extension_extension: 2.0,
+            text_gap: 1.0,
+            text_height: 3.5,
+        }],
+    }
+}
+
+#[test]
+fn test_round_trip() {
+    let doc = sample_document();
+    let output = to_string(&doc);
+    // フリーズ・ロックはレイヤーごとに1つの70にまとめる
+    assert!(output.contains("2\n0-1\n70\n4\n62\n"));
+    assert!(output.contains("2\n壁\n70\n1\n62\n"));
+
+    // 順に書き出しても同じ内容になる
+    let mut streamed = String::new();
+    jww_dxf::write_to(&doc, &mut streamed);
+    assert_eq!(streamed, output);
+
+    let parsed = reader::parse(&output).expect("parse");
+    assert_eq!(parsed.entities.len(), doc.entities.len());
+    assert_eq!(parsed.layers.len(), 2);
+    assert_eq!(parsed.groups[0].entities, vec![0, 2]);
+    // 寸法図形の無名ブロックはブロック一覧に含めない
+    assert_eq!(parsed.blocks.len(), 1);
+
+    // 書き出した内容をすべて読み取れていれば、再出力は元の出力と一致する
+    assert_eq!(to_string(&parsed), output);
+}
+
+#[test]
+fn test_parse_other_writer_output() {
+    // 他のCADが出力する形式 (CRLF、字下げされたグループコード、LWPOLYLINE、既定値の省略)
+    let input = [
+        "  0", "SECTION", "  2", "HEADER", "  9", "$ACADVER", "  1", "AC1015", "  9", "$INSUNITS",
+        " 70", "     5", "  0", "ENDSEC", "  0", "SECTION", "  2", "ENTITIES", "  0", "LWPOLYLINE",
+        "  8", "A-WALL", " 90", "        3", " 70", "     1", " 10", "0.0", " 20", "0.0", " 10",
+        "10.0", " 20", "0.0", " 10", "10.0", " 20", "5.0", "  0", "CIRCLE", " 67", "     1", " 10",
+        "0.0", " 20", "0.0", " 40", "1.0", "  0", "MTEXT", "  1", "skipped", "  0", "INSERT", "  2",
+        "WINDOW", " 10", "1.0", " 20", "2.0", "  0", "ENDSEC", "  0", "EOF",
+    ]
+    .join("\r\n");
+
+    let doc = reader::parse(&input).expect("parse");
+    assert_eq!(doc.header.insunits, 5);
+    assert_eq!(doc.entities.len(), 2);
+
+    match &doc.entities[0] {
+        Entity::Polyline(p) => {
+            assert_eq!(p.layer, "A-WALL");
+            assert_eq!(p.color, 256);
+            assert!(p.closed);
+            assert_eq!(p.vertices.len(), 3);
+            assert_eq!(p.vertices[2], Vertex { x: 10.0, y: 5.0 });
+        }
+        other => panic!("expected POLYLINE, got {:?}", other),
+    }
+    match &doc.entities[1] {
+        Entity::Insert(insert) => {
+            assert_eq!(insert.block_name, "WINDOW");
+            assert_eq!(insert.layer, "0");
+            assert_eq!((insert.scale_x, insert.scale_y), (1.0, 1.0));
+        }
+        other => panic!("expected INSERT, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_errors() {
+    let err = reader::parse("0\nSECTION\nabc\nHEADER\n").unwrap_err();
+    assert!(matches!(err, ReadError::InvalidGroupCode { line: 3, .. }), "{}", err);
+
+    let err = reader::parse("0\nSECTION\n2").unwrap_err();
+    assert!(matches!(err, ReadError::MissingValue { line: 3, code: 2 }), "{}", err);
+
+    let err = reader::parse("0\nSECTION\n2\nENTITIES\n0\nLINE\n10\nx\n0\nENDSEC\n").unwrap_err();
+    assert!(matches!(err, ReadError::InvalidValue { line: 8, code: 10, .. }), "{}", err);
+}
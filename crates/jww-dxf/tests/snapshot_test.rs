@@ -0,0 +1,101 @@
+//! フィクスチャのスナップショットテスト
+//!
+//! `tests/fixtures/*.jww` をパースしたJSONと変換したDXFを `tests/snapshots/` と比較する。
+//! パーサーや変換の変更で出力が変わる場合は、以下で更新して差分をレビューする。
+//!
+//! ```text
+//! UPDATE_SNAPSHOTS=1 cargo test -p jww-dxf --test snapshot_test
+//! ```
+
+use std::path::{Path, PathBuf};
+
+/// ワークスペース直下の `tests` ディレクトリ
+fn tests_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../tests")
+}
+
+/// フィクスチャのパス (名前順)
+fn fixtures() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(tests_dir().join("fixtures"))
+        .expect("tests/fixtures が読み込めません")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("jww"))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// 出力をスナップショットと比較する
+///
+/// 一致しない場合は最初の相違行を返す。`UPDATE_SNAPSHOTS` が設定されている場合は
+/// スナップショットを書き換える。
+fn check_snapshot(name: &str, actual: &str) -> Result<(), String> {
+    let path = tests_dir().join("snapshots").join(name);
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        return Ok(());
+    }
+    let expected = std::fs::read_to_string(&path)
+        .map_err(|_| format!("{}: スナップショットがありません", name))?;
+    if expected == actual {
+        return Ok(());
+    }
+    let expected_lines: Vec<&str> = expected.lines().collect();
+    let actual_lines: Vec<&str> = actual.lines().collect();
+    let line = expected_lines
+        .iter()
+        .zip(&actual_lines)
+        .position(|(e, a)| e != a)
+        .unwrap_or(expected_lines.len().min(actual_lines.len()));
+    Err(format!(
+        "{}:{}: expected {:?}, actual {:?}",
+        name,
+        line + 1,
+        expected_lines.get(line).unwrap_or(&"<EOF>"),
+        actual_lines.get(line).unwrap_or(&"<EOF>"),
+    ))
+}
+
+#[test]
+fn test_fixture_snapshots() {
+    let fixtures = fixtures();
+    assert!(
+        !fixtures.is_empty(),
+        "tests/fixtures にJWWファイルがありません"
+    );
+
+    let mut failures = Vec::new();
+    for path in &fixtures {
+        let stem = path.file_stem().unwrap().to_string_lossy();
+        let data = std::fs::read(path).unwrap();
+        let doc = match jww_core::parse(&data) {
+            Ok(doc) => doc,
+            Err(e) => {
+                failures.push(format!("{}: パースに失敗: {}", path.display(), e));
+                continue;
+            }
+        };
+
+        let mut json = serde_json::to_string_pretty(&doc).unwrap();
+        json.push('\n');
+        let dxf = jww_dxf::to_string(&jww_dxf::convert_document(&doc));
+        for result in [
+            check_snapshot(&format!("{}.json", stem), &json),
+            check_snapshot(&format!("{}.dxf", stem), &dxf),
+        ] {
+            if let Err(e) = result {
+                failures.push(e);
+            }
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "スナップショットが一致しません (意図した変更なら UPDATE_SNAPSHOTS=1 で更新):\n{}",
+        failures.join("\n")
+    );
+}
@@ -0,0 +1,316 @@
+//! DXF -> JWW 変換のテスト
+
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use jww_core::{Angle, Document as JwwDocument, Entity as JwwEntity, EntityBase, Point2D};
+use jww_dxf::*;
+
+fn base(layer_group: u16, layer: u16) -> EntityBase {
+    EntityBase {
+        pen_style: 2,
+        pen_color: 8,
+        layer,
+        layer_group,
+        ..Default::default()
+    }
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-6, "{} != {}", actual, expected);
+}
+
+/// 曲線上の点 (JWWの角度表現) を返す
+fn arc_point(arc: &jww_core::Arc, angle: f64) -> Point2D {
+    arc.point_at_param(arc.angle_to_param(Angle::from_radians(angle)))
+}
+
+#[test]
+fn test_jww_dxf_jww_round_trip() {
+    let mut doc = JwwDocument {
+        version: 600,
+        memo: "〇〇邸".into(),
+        paper_size: 2,
+        ..Default::default()
+    };
+    doc.layer_groups[0].scale = 100.0;
+    doc.entities = vec![
+        JwwEntity::Line(jww_core::Line {
+            base: base(1, 0xA),
+            start: Point2D::new(-100.0, 50.0),
+            end: Point2D::new(200.0, 75.5),
+        }),
+        JwwEntity::Arc(jww_core::Arc {
+            base: base(0, 1),
+            center: Point2D::new(10.0, 20.0),
+            radius: 30.0,
+            start_angle: Angle::from_radians(FRAC_PI_2),
+            arc_angle: Angle::from_radians(PI),
+            tilt_angle: Angle::ZERO,
+            flatness: 1.0,
+            is_full_circle: false,
+        }),
+        JwwEntity::Arc(jww_core::Arc {
+            base: base(0, 1),
+            center: Point2D::new(0.0, 0.0),
+            radius: 40.0,
+            start_angle: Angle::from_radians(0.3),
+            arc_angle: Angle::from_radians(2.0),
+            tilt_angle: Angle::from_radians(0.5),
+            flatness: 0.5,
+            is_full_circle: false,
+        }),
+        JwwEntity::Text(jww_core::Text {
+            base: base(0, 0),
+            start: Point2D::new(5.0, 6.0),
+            end: Point2D::new(15.0, 6.0),
+            text_type: 0,
+            size_x: 5.0,
+            size_y: 5.0,
+            spacing: 0.0,
+            angle: Angle::from_degrees(30.0),
+            font_name: "ＭＳ ゴシック".into(),
+            content: "居間".into(),
+        }),
+    ];
+
+    let options = ConvertOptions {
+        units: Units::Meters,
+        paper_space: true,
+        provenance: Some(Provenance {
+            timestamp: Some(0),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let dxf = to_string(&convert_document_with_options(&doc, &options));
+    let data = dxf_to_jww(&dxf).expect("dxf_to_jww");
+    let parsed = jww_core::parse(&data).expect("parse");
+
+    assert_eq!(parsed.version, JWW_VERSION);
+    assert_eq!(parsed.memo, "〇〇邸");
+    assert_eq!(parsed.paper_size, 2);
+    assert_close(parsed.layer_groups[0].scale, 100.0);
+    assert_eq!(parsed.entities.len(), 4);
+
+    match &parsed.entities[0] {
+        JwwEntity::Line(line) => {
+            assert_eq!((line.base.layer_group, line.base.layer), (1, 0xA));
+            assert_eq!(line.base.pen_style, 2);
+            assert_eq!(line.base.pen_color, 8);
+            assert_close(line.start.x, -100.0);
+            assert_close(line.end.y, 75.5);
+        }
+        other => panic!("expected line, got {:?}", other),
+    }
+    match &parsed.entities[1] {
+        JwwEntity::Arc(arc) => {
+            assert_close(arc.radius, 30.0);
+            assert_close(arc.start_angle.radians(), FRAC_PI_2);
+            assert_close(arc.arc_angle.radians(), PI);
+        }
+        other => panic!("expected arc, got {:?}", other),
+    }
+    match (&doc.entities[2], &parsed.entities[2]) {
+        (JwwEntity::Arc(expected), JwwEntity::Arc(actual)) => {
+            // 同じ楕円弧を描く (表現は異なってもよい)
+            for angle in [0.3, 1.3, 2.3] {
+                let expected = arc_point(expected, angle);
+                let actual = arc_point(actual, angle);
+                assert_close(actual.x, expected.x);
+                assert_close(actual.y, expected.y);
+            }
+            assert_close(actual.arc_angle.radians(), 2.0);
+        }
+        other => panic!("expected ellipse arcs, got {:?}", other),
+    }
+    match &parsed.entities[3] {
+        JwwEntity::Text(text) => {
+            assert_eq!(text.content, "居間");
+            assert_close(text.size_y, 5.0);
+            // 角度のフィールド (30度) ではなく始点から終点への向きで回転する
+            assert_close(text.angle.degrees(), 0.0);
+        }
+        other => panic!("expected text, got {:?}", other),
+    }
+}
+
+fn dxf_document(entities: Vec<Entity>, blocks: Vec<Block>) -> Document {
+    Document {
+        header: Header::default(),
+        active_view: None,
+        layers: vec![Layer {
+            name: "A-DOOR".to_string(),
+            color: 1,
+            line_type: "DASHED".to_string(),
+            frozen: false,
+            locked: false,
+        }],
+        entities,
+        blocks,
+        layout: None,
+        groups: Vec::new(),
+        comments: Vec::new(),
+        dim_styles: Vec::new(),
+    }
+}
+
+#[test]
+fn test_inserts_are_exploded() {
+    let circle = Entity::Circle(Circle {
+        layer: "0".to_string(),
+        color: 0,
+        line_type: "BYLAYER".to_string(),
+        line_type_scale: None,
+        xdata: None,
+        center_x: 10.0,
+        center_y: 0.0,
+        radius: 5.0,
+    });
+    let inner = Block {
+        name: "KNOB".to_string(),
+        base_x: 0.0,
+        base_y: 0.0,
+        entities: vec![circle],
+    };
+    let outer = Block {
+        name: "DOOR".to_string(),
+        base_x: 100.0,
+        base_y: 0.0,
+        entities: vec![Entity::Insert(Insert {
+            layer: "0".to_string(),
+            color: 0,
+            line_type: "BYLAYER".to_string(),
+            line_type_scale: None,
+            xdata: None,
+            block_name: "KNOB".to_string(),
+            x: 100.0,
+            y: 0.0,
+            scale_x: 1.0,
+            scale_y: 1.0,
+            rotation: 0.0,
+        })],
+    };
+    let insert = Entity::Insert(Insert {
+        layer: "A-DOOR".to_string(),
+        color: 256,
+        line_type: "BYLAYER".to_string(),
+        line_type_scale: None,
+        xdata: None,
+        block_name: "DOOR".to_string(),
+        x: 1000.0,
+        y: 500.0,
+        scale_x: -2.0,
+        scale_y: 1.0,
+        rotation: 90.0,
+    });
+    let doc = dxf_document(vec![insert], vec![inner, outer]);
+
+    let jww_doc = convert_to_jww(&doc);
+    assert!(jww_doc.block_defs.is_empty());
+    assert_eq!(jww_doc.entities.len(), 1);
+
+    // 挿入側のレイヤーを引き継ぎ、空いているレイヤに割り当てられる
+    assert_eq!(jww_doc.layer_groups[0].layers[0].name, "A-DOOR");
+
+    match &jww_doc.entities[0] {
+        JwwEntity::Arc(arc) => {
+            assert_eq!(arc.base.pen_color, 8);
+            assert_eq!(arc.base.pen_style, 2);
+            // 円 (10, 0) -> KNOBの挿入で (110, 0) -> 基点 (100, 0) からの相対 (10, 0)
+            // -> 倍率 (-2, 1) で (-20, 0) -> 90度回転で (0, -20) -> 挿入点 (1000, 500) で (1000, 480)
+            assert_close(arc.center.x, 1000.0);
+            assert_close(arc.center.y, 480.0);
+            // 非一様な倍率により長径10・短径5の楕円になる
+            assert_close(arc.radius, 10.0);
+            assert_close(arc.flatness, 0.5);
+            assert!(arc.is_full_circle);
+            assert_close(arc.tilt_angle.radians().sin().abs(), 1.0);
+        }
+        other => panic!("expected ellipse, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_rebase_blocks() {
+    let circle = Entity::Circle(Circle {
+        layer: "0".to_string(),
+        color: 0,
+        line_type: "BYLAYER".to_string(),
+        line_type_scale: None,
+        xdata: None,
+        center_x: 110.0,
+        center_y: 50.0,
+        radius: 5.0,
+    });
+    let block = Block {
+        name: "DOOR".to_string(),
+        base_x: 100.0,
+        base_y: 50.0,
+        entities: vec![circle],
+    };
+    let insert = Entity::Insert(Insert {
+        layer: "0".to_string(),
+        color: 256,
+        line_type: "BYLAYER".to_string(),
+        line_type_scale: None,
+        xdata: None,
+        block_name: "DOOR".to_string(),
+        x: 1000.0,
+        y: 500.0,
+        scale_x: 1.0,
+        scale_y: 1.0,
+        rotation: 90.0,
+    });
+    let doc = dxf_document(vec![insert], vec![block]);
+
+    // 基準点を原点に移しても挿入点と挿入された図形の位置は変わらない
+    let mut rebased = doc.clone();
+    rebased.rebase_blocks();
+    assert_eq!((rebased.blocks[0].base_x, rebased.blocks[0].base_y), (0.0, 0.0));
+    assert_eq!(rebased.entities, doc.entities);
+    match &rebased.blocks[0].entities[0] {
+        Entity::Circle(circle) => assert_eq!((circle.center_x, circle.center_y), (10.0, 0.0)),
+        other => panic!("expected circle, got {:?}", other),
+    }
+    for doc in [&doc, &rebased] {
+        match &convert_to_jww(doc).entities[0] {
+            JwwEntity::Arc(arc) => {
+                assert_close(arc.center.x, 1000.0);
+                assert_close(arc.center.y, 510.0);
+            }
+            other => panic!("expected circle, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_polyline_becomes_lines() {
+    let polyline = Entity::Polyline(Polyline {
+        layer: "3-F".to_string(),
+        color: 3,
+        line_type: "CONTINUOUS".to_string(),
+        line_type_scale: None,
+        xdata: None,
+        vertices: vec![
+            Vertex { x: 0.0, y: 0.0 },
+            Vertex { x: 10.0, y: 0.0 },
+            Vertex { x: 10.0, y: 10.0 },
+        ],
+        closed: true,
+    });
+    let mut doc = dxf_document(vec![polyline], Vec::new());
+    doc.header.insunits = 5;
+
+    let jww_doc = convert_to_jww(&doc);
+    assert_eq!(jww_doc.entities.len(), 3);
+    match &jww_doc.entities[2] {
+        JwwEntity::Line(line) => {
+            assert_eq!((line.base.layer_group, line.base.layer), (3, 0xF));
+            assert_eq!(line.base.pen_color, 3);
+            assert_close(line.start.x, 100.0);
+            assert_close(line.start.y, 100.0);
+            assert_close(line.end.x, 0.0);
+        }
+        other => panic!("expected line, got {:?}", other),
+    }
+}
@@ -0,0 +1,185 @@
+//! DXF構造検査のテスト
+
+use std::path::Path;
+
+use jww_dxf::validate::{has_errors, validate, Finding, Severity};
+
+/// 検査項目の識別子の一覧
+fn codes(findings: &[Finding]) -> Vec<&'static str> {
+    findings.iter().map(|f| f.code).collect()
+}
+
+/// ブロック定義を読み込めないため参照先のないブロック挿入 (`blocks.jww`) 以外の検査結果
+///
+/// jww-coreはブロック定義 (CDataList) の読み込みに未対応で、挿入だけが出力される。
+fn unexpected(findings: &[Finding]) -> Vec<&Finding> {
+    findings
+        .iter()
+        .filter(|f| f.code != "undefined_block")
+        .collect()
+}
+
+/// 最小限の正しいDXF (LTYPE・LAYERテーブルとENTITIESセクション)
+const MINIMAL: &str = "0\nSECTION\n2\nTABLES\n\
+0\nTABLE\n2\nLTYPE\n70\n1\n0\nLTYPE\n2\nCONTINUOUS\n70\n0\n0\nENDTAB\n\
+0\nTABLE\n2\nLAYER\n70\n1\n0\nLAYER\n2\nWALL\n70\n0\n62\n7\n6\nCONTINUOUS\n0\nENDTAB\n\
+0\nENDSEC\n\
+0\nSECTION\n2\nENTITIES\n\
+0\nLINE\n8\nWALL\n6\nCONTINUOUS\n10\n0.0\n20\n0.0\n11\n1.0\n21\n1.0\n\
+0\nENDSEC\n0\nEOF\n";
+
+#[test]
+fn test_minimal_is_valid() {
+    assert_eq!(validate(MINIMAL), Vec::new());
+}
+
+#[test]
+fn test_snapshots_are_valid() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../tests/snapshots");
+    let mut checked = 0;
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|ext| ext == "dxf") {
+            let text = std::fs::read_to_string(&path).unwrap();
+            let findings = validate(&text);
+            assert_eq!(
+                unexpected(&findings),
+                Vec::<&Finding>::new(),
+                "{}",
+                path.display()
+            );
+            checked += 1;
+        }
+    }
+    assert!(checked > 0);
+}
+
+#[test]
+fn test_group_code_errors() {
+    let findings = validate("0\nSECTION\n2\nENTITIES\nabc\nLINE\n");
+    assert_eq!(codes(&findings), ["invalid_group_code"]);
+    assert_eq!(findings[0].line, Some(5));
+
+    let findings = validate("0\nSECTION\n2\nENTITIES\n0\n");
+    assert_eq!(codes(&findings), ["missing_value"]);
+    assert_eq!(findings[0].line, Some(5));
+
+    let text = MINIMAL.replace("10\n0.0\n", "10\nabc\n");
+    assert_eq!(codes(&validate(&text)), ["invalid_value"]);
+}
+
+#[test]
+fn test_section_balance() {
+    let text = MINIMAL.replace("0\nENDSEC\n0\nEOF\n", "0\nEOF\n");
+    assert_eq!(codes(&validate(&text)), ["unclosed_section"]);
+
+    let text = MINIMAL.replace("0\nEOF\n", "0\nENDSEC\n0\nEOF\n");
+    assert_eq!(codes(&validate(&text)), ["unexpected_endsec"]);
+
+    let text = MINIMAL.replace("0\nEOF\n", "");
+    assert_eq!(codes(&validate(&text)), ["missing_eof"]);
+
+    let text = format!("{MINIMAL}0\nLINE\n");
+    let findings = validate(&text);
+    assert_eq!(codes(&findings), ["data_after_eof"]);
+    assert_eq!(findings[0].severity, Severity::Warning);
+    assert!(!has_errors(&findings));
+}
+
+#[test]
+fn test_table_balance() {
+    let text = MINIMAL.replacen("0\nENDTAB\n", "", 1);
+    assert_eq!(codes(&validate(&text)), ["unclosed_table"]);
+
+    let text = MINIMAL.replace("0\nENDSEC\n0\nSECTION", "0\nENDTAB\n0\nENDSEC\n0\nSECTION");
+    assert_eq!(codes(&validate(&text)), ["unexpected_endtab"]);
+}
+
+#[test]
+fn test_required_tables_and_references() {
+    // LTYPEテーブルがない場合は線種の参照を検査しない
+    let start = MINIMAL.find("0\nTABLE\n2\nLTYPE").unwrap();
+    let end = MINIMAL.find("0\nTABLE\n2\nLAYER").unwrap();
+    let text = format!("{}{}", &MINIMAL[..start], &MINIMAL[end..]);
+    assert_eq!(codes(&validate(&text)), ["missing_table"]);
+
+    let text = MINIMAL.replace("8\nWALL\n6\nCONTINUOUS", "8\nDOOR\n6\nDASHED");
+    let findings = validate(&text);
+    assert_eq!(codes(&findings), ["undefined_layer", "undefined_linetype"]);
+    assert_eq!(findings[0].severity, Severity::Warning);
+    assert_eq!(findings[1].severity, Severity::Error);
+
+    let text = MINIMAL.replace("0\nLINE\n", "0\nINSERT\n2\nMISSING\n0\nLINE\n");
+    assert_eq!(codes(&validate(&text)), ["undefined_block"]);
+}
+
+#[test]
+fn test_blocks_and_polylines() {
+    let blocks = "0\nSECTION\n2\nBLOCKS\n0\nBLOCK\n8\n0\n2\nDOOR\n70\n0\n10\n0.0\n20\n0.0\n\
+0\nLINE\n8\n0\n10\n0.0\n20\n0.0\n11\n1.0\n21\n0.0\n0\nENDBLK\n8\n0\n0\nENDSEC\n";
+    let insert = "0\nINSERT\n8\nWALL\n2\nDOOR\n10\n0.0\n20\n0.0\n\
+0\nPOLYLINE\n8\nWALL\n66\n1\n0\nVERTEX\n8\nWALL\n10\n0.0\n20\n0.0\n0\nSEQEND\n8\nWALL\n";
+    let valid = MINIMAL.replacen(
+        "0\nSECTION\n2\nENTITIES\n",
+        &format!("{blocks}0\nSECTION\n2\nENTITIES\n{insert}"),
+        1,
+    );
+    assert_eq!(validate(&valid), Vec::new());
+
+    let text = valid.replace("0\nENDBLK\n8\n0\n", "");
+    assert_eq!(codes(&validate(&text)), ["unclosed_block"]);
+
+    let text = valid.replace("0\nSEQEND\n8\nWALL\n", "");
+    assert_eq!(codes(&validate(&text)), ["missing_seqend"]);
+
+    let text = MINIMAL.replace("0\nLINE\n", "0\nBLOCK\n2\nX\n0\nENDBLK\n0\nLINE\n");
+    assert_eq!(codes(&validate(&text)), ["block_outside_blocks"]);
+}
+
+#[test]
+fn test_version_checks() {
+    // $ACADVERがない場合はR12として扱い、R13以降のエンティティをエラーにする
+    let text = MINIMAL.replace("0\nLINE\n", "0\nELLIPSE\n8\nWALL\n0\nLINE\n");
+    assert_eq!(codes(&validate(&text)), ["requires_r13"]);
+
+    // R2000ではハンドル・所有者・サブクラスマーカーを必須とする
+    let header = "0\nSECTION\n2\nHEADER\n9\n$ACADVER\n1\nAC1015\n0\nENDSEC\n";
+    let entities = "0\nSECTION\n2\nENTITIES\n\
+0\nLINE\n5\n1\n330\n0\n100\nAcDbEntity\n8\n0\n100\nAcDbLine\n\
+0\nENDSEC\n0\nEOF\n";
+    assert_eq!(validate(&format!("{header}{entities}")), Vec::new());
+
+    let text = format!("{header}{}", entities.replace("5\n1\n330\n0\n", ""));
+    assert_eq!(codes(&validate(&text)), ["missing_handle", "missing_owner"]);
+
+    let text = format!("{header}{}", entities.replace("100\nAcDbEntity\n", ""));
+    assert_eq!(codes(&validate(&text)), ["missing_subclass_marker"]);
+
+    let text = format!("{header}{}", entities.replace("330\n0\n", "330\n1F\n"));
+    let findings = validate(&text);
+    assert_eq!(codes(&findings), ["dangling_handle"]);
+    assert!(!has_errors(&findings));
+}
+
+#[test]
+fn test_converted_output_is_valid() {
+    let bytes = std::fs::read(
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../../tests/fixtures/blocks.jww"),
+    )
+    .unwrap();
+    let doc = jww_core::parse(&bytes).unwrap();
+    let dxf = jww_dxf::to_string(&jww_dxf::convert_document(&doc));
+    let findings = validate(&dxf);
+    assert_eq!(unexpected(&findings), Vec::<&Finding>::new());
+    assert!(findings.iter().all(|f| f.message.contains("BLOCK_")));
+
+    // Shift_JISの出力も文字列以外の部分は同じように検査できる
+    let sjis = jww_dxf::to_bytes(
+        &jww_dxf::convert_document(&doc),
+        jww_dxf::DxfFormat::ShiftJis,
+    );
+    assert_eq!(
+        codes(&validate(&String::from_utf8_lossy(&sjis))),
+        codes(&findings)
+    );
+}
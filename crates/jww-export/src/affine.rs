@@ -0,0 +1,101 @@
+//! 座標のアフィン変換
+
+use jww_core::{Block, Point2D};
+use serde::{Deserialize, Serialize};
+
+/// 座標のアフィン変換
+///
+/// `x' = a·x + b·y + c`、`y' = d·x + e·y + f` で図面座標 (mm) を変換する。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Affine {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+impl Affine {
+    /// 恒等変換
+    pub const IDENTITY: Affine = Affine {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 0.0,
+        e: 1.0,
+        f: 0.0,
+    };
+
+    /// 拡大縮小・回転 (ラジアン)・平行移動をこの順に適用する変換を作成する
+    ///
+    /// 図面の原点を `(tx, ty)` に置き、1mmを `scale` 単位とする配置に使う。
+    pub fn from_scale_rotate_translate(scale: f64, rotation: f64, tx: f64, ty: f64) -> Self {
+        let (sin, cos) = rotation.sin_cos();
+        Affine {
+            a: scale * cos,
+            b: -scale * sin,
+            c: tx,
+            d: scale * sin,
+            e: scale * cos,
+            f: ty,
+        }
+    }
+
+    /// ブロック挿入 (拡大縮小・回転・基準点への移動) の変換を作成する
+    pub fn from_block(block: &Block) -> Self {
+        let (sin, cos) = block.rotation.radians().sin_cos();
+        Affine {
+            a: block.scale_x * cos,
+            b: -block.scale_y * sin,
+            c: block.reference.x,
+            d: block.scale_x * sin,
+            e: block.scale_y * cos,
+            f: block.reference.y,
+        }
+    }
+
+    /// X軸方向の回転角 (ラジアン) を返す
+    pub fn rotation(&self) -> f64 {
+        self.d.atan2(self.a)
+    }
+
+    /// 面積比の平方根 (長さの平均的な倍率) を返す
+    pub fn scale_factor(&self) -> f64 {
+        (self.a * self.e - self.b * self.d).abs().sqrt()
+    }
+
+    /// 円を円に移す (等方的な拡大縮小と回転のみで、反転を含まない) かどうかを返す
+    pub fn is_conformal(&self) -> bool {
+        let eps = 1e-9 * (self.a.abs() + self.b.abs() + self.d.abs() + self.e.abs());
+        (self.a - self.e).abs() <= eps
+            && (self.b + self.d).abs() <= eps
+            && self.a * self.e - self.b * self.d > 0.0
+    }
+
+    /// 座標を変換する
+    pub fn apply(&self, point: Point2D) -> Point2D {
+        Point2D::new(
+            self.a * point.x + self.b * point.y + self.c,
+            self.d * point.x + self.e * point.y + self.f,
+        )
+    }
+
+    /// この変換の後に `next` を適用する変換を返す
+    pub fn then(&self, next: &Affine) -> Affine {
+        Affine {
+            a: next.a * self.a + next.b * self.d,
+            b: next.a * self.b + next.b * self.e,
+            c: next.a * self.c + next.b * self.f + next.c,
+            d: next.d * self.a + next.e * self.d,
+            e: next.d * self.b + next.e * self.e,
+            f: next.d * self.c + next.e * self.f + next.f,
+        }
+    }
+}
+
+impl Default for Affine {
+    fn default() -> Self {
+        Affine::IDENTITY
+    }
+}
@@ -0,0 +1,217 @@
+//! CSV/TSV出力
+
+use std::f64::consts::TAU;
+
+use jww_core::{Arc, Document, Entity};
+use serde::{Deserialize, Serialize};
+
+use crate::format::num;
+
+/// 列の区切り文字
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Delimiter {
+    /// カンマ (CSV)
+    #[default]
+    Comma,
+    /// タブ (TSV)
+    Tab,
+}
+
+impl Delimiter {
+    pub(crate) fn as_char(self) -> char {
+        match self {
+            Delimiter::Comma => ',',
+            Delimiter::Tab => '\t',
+        }
+    }
+}
+
+/// CSV/TSV出力オプション
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsvOptions {
+    /// 列の区切り文字
+    pub delimiter: Delimiter,
+    /// 先頭に見出し行を出力する
+    pub header: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: Delimiter::Comma,
+            header: true,
+        }
+    }
+}
+
+/// 出力する列
+const COLUMNS: [&str; 17] = [
+    "type",
+    "layer",
+    "layer_group",
+    "layer_index",
+    "pen_color",
+    "pen_style",
+    "pen_width",
+    "x1",
+    "y1",
+    "x2",
+    "y2",
+    "cx",
+    "cy",
+    "radius",
+    "angle",
+    "length",
+    "text",
+];
+
+/// JWWドキュメントのエンティティを1行ずつCSV/TSVに出力する
+///
+/// 座標はJWWのmm、角度は度で出力する。エンティティごとの列の内容は次のとおり。
+/// 該当しない列は空欄とする。
+///
+/// - `LINE`: 始点 (x1, y1)・終点 (x2, y2)・長さ
+/// - `ARC`/`CIRCLE`: 始点・終点・中心 (cx, cy)・半径・円弧角 (angle)・弧長
+/// - `POINT`: 座標 (x1, y1)
+/// - `TEXT`: 始点・終点・文字角度・文字列
+/// - `SOLID`: 第1点 (x1, y1)・第3点 (x2, y2)
+/// - `BLOCK`: 基準点 (x1, y1)・回転角・ブロック名 (text)
+/// - `DIMENSION`: 寸法線の始点・終点・長さ・寸法値 (text)
+///
+/// ブロック挿入は分解せず1行として出力する。
+pub fn to_csv(doc: &Document, options: &CsvOptions) -> String {
+    let delimiter = options.delimiter.as_char();
+    let mut out = String::new();
+    if options.header {
+        write_row(&mut out, COLUMNS.iter().map(|c| c.to_string()), delimiter);
+    }
+    for entity in &doc.entities {
+        write_row(&mut out, row(doc, entity), delimiter);
+    }
+    out
+}
+
+/// エンティティの行を作成する
+fn row(doc: &Document, entity: &Entity) -> Vec<String> {
+    let base = entity.base();
+    let mut cells = vec![String::new(); COLUMNS.len()];
+    cells[0] = entity.type_name().to_string();
+    cells[1] = doc
+        .layer_groups
+        .get(base.layer_group as usize)
+        .and_then(|lg| lg.layers.get(base.layer as usize))
+        .map(|l| l.name.clone())
+        .unwrap_or_default();
+    cells[2] = base.layer_group.to_string();
+    cells[3] = base.layer.to_string();
+    cells[4] = base.pen_color.to_string();
+    cells[5] = base.pen_style.to_string();
+    cells[6] = base.pen_width.to_string();
+
+    let mut set = |column: usize, value: f64| cells[column] = num(value);
+    match entity {
+        Entity::Line(line) => {
+            set(7, line.start.x);
+            set(8, line.start.y);
+            set(9, line.end.x);
+            set(10, line.end.y);
+            set(15, line.start.distance(line.end));
+        }
+        Entity::Arc(arc) => {
+            let (start, end) = arc.param_range();
+            let start = arc.point_at_param(start);
+            let end = arc.point_at_param(end);
+            set(7, start.x);
+            set(8, start.y);
+            set(9, end.x);
+            set(10, end.y);
+            set(11, arc.center.x);
+            set(12, arc.center.y);
+            set(13, arc.radius);
+            let sweep = if arc.is_closed() {
+                TAU
+            } else {
+                arc.arc_angle.radians()
+            };
+            set(14, sweep.to_degrees());
+            set(15, arc_length(arc));
+        }
+        Entity::Point(point) => {
+            set(7, point.position.x);
+            set(8, point.position.y);
+        }
+        Entity::Text(text) => {
+            set(7, text.start.x);
+            set(8, text.start.y);
+            set(9, text.end.x);
+            set(10, text.end.y);
+            set(14, text.angle.degrees());
+            cells[16] = text.content.to_string();
+        }
+        Entity::Solid(solid) => {
+            set(7, solid.point1.x);
+            set(8, solid.point1.y);
+            set(9, solid.point3.x);
+            set(10, solid.point3.y);
+        }
+        Entity::Block(block) => {
+            set(7, block.reference.x);
+            set(8, block.reference.y);
+            set(14, block.rotation.degrees());
+            cells[16] = doc
+                .block_defs
+                .iter()
+                .find(|bd| bd.number == block.def_number)
+                .map(|bd| bd.name.clone())
+                .unwrap_or_default();
+        }
+        Entity::Dimension(dim) => {
+            let line = &dim.line;
+            set(7, line.start.x);
+            set(8, line.start.y);
+            set(9, line.end.x);
+            set(10, line.end.y);
+            set(15, line.start.distance(line.end));
+            cells[16] = dim.text.content.to_string();
+        }
+        Entity::Unknown(unknown) => cells[16] = unknown.class_name.clone(),
+    }
+    cells
+}
+
+/// 円弧の長さを返す (楕円弧は折れ線近似による)
+fn arc_length(arc: &Arc) -> f64 {
+    if arc.is_ellipse() {
+        arc.tessellate(arc.radius * 1e-4)
+            .windows(2)
+            .map(|w| w[0].distance(w[1]))
+            .sum()
+    } else if arc.is_closed() {
+        TAU * arc.radius
+    } else {
+        let (start, end) = arc.param_range();
+        (end - start).abs() * arc.radius
+    }
+}
+
+/// 1行を書き込む (区切り文字・引用符・改行を含む値は引用符で囲む)
+pub(crate) fn write_row(
+    out: &mut String,
+    cells: impl IntoIterator<Item = String>,
+    delimiter: char,
+) {
+    for (i, cell) in cells.into_iter().enumerate() {
+        if i > 0 {
+            out.push(delimiter);
+        }
+        if cell.contains([delimiter, '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&cell.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(&cell);
+        }
+    }
+    out.push_str("\r\n");
+}
@@ -0,0 +1,440 @@
+//! EMF (拡張メタファイル) 出力
+//!
+//! Word/Excel などに貼り付けられるベクター画像として出力する。
+//! 論理座標の1単位を用紙上の0.01mmとし、Y軸を下向きに反転する。
+
+use std::collections::HashMap;
+
+use jww_core::{Document, EntityBase};
+use serde::{Deserialize, Serialize};
+
+use crate::affine::Affine;
+use crate::flatten::{flatten, Shape};
+use crate::style::{dash_pattern, is_vertical_font, PenStyle, Rgb};
+use crate::view::group_scale;
+
+/// 用紙上の1mmあたりの論理単位数
+const UNITS_PER_MM: f64 = 100.0;
+
+// レコード種別 ([MS-EMF] 2.1.1)
+const EMR_HEADER: u32 = 1;
+const EMR_POLYGON: u32 = 3;
+const EMR_POLYLINE: u32 = 4;
+const EMR_EOF: u32 = 14;
+const EMR_SETMAPMODE: u32 = 17;
+const EMR_SETBKMODE: u32 = 18;
+const EMR_SETTEXTALIGN: u32 = 22;
+const EMR_SETTEXTCOLOR: u32 = 24;
+const EMR_SELECTOBJECT: u32 = 37;
+const EMR_CREATEBRUSHINDIRECT: u32 = 39;
+const EMR_DELETEOBJECT: u32 = 40;
+const EMR_EXTCREATEFONTINDIRECTW: u32 = 82;
+const EMR_EXTTEXTOUTW: u32 = 84;
+const EMR_EXTCREATEPEN: u32 = 95;
+
+const MM_TEXT: u32 = 1;
+const TRANSPARENT: u32 = 1;
+const TA_BOTTOM: u32 = 8;
+const GM_ADVANCED: u32 = 2;
+const NULL_BRUSH: u32 = 0x8000_0005;
+const NULL_PEN: u32 = 0x8000_0008;
+const SYSTEM_FONT: u32 = 0x8000_000D;
+const PS_GEOMETRIC: u32 = 0x0001_0000;
+const PS_USERSTYLE: u32 = 7;
+const PS_ENDCAP_FLAT: u32 = 0x0200;
+const PS_JOIN_ROUND: u32 = 0;
+const BS_SOLID: u32 = 0;
+const SHIFTJIS_CHARSET: u8 = 128;
+
+/// EMF出力オプション
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmfOptions {
+    /// 出力の縮尺 (出力1mmに対する図面上の長さ、`None` の場合は図面の縮尺で用紙上の寸法とする)
+    pub plot_scale: Option<f64>,
+    /// 線色ごとの線幅と色 ([`SvgOptions::style`](crate::SvgOptions::style) と同じ)
+    #[serde(flatten)]
+    pub style: PenStyle,
+    /// 曲線を折れ線に近似する際の弦の許容誤差 (出力上のmm)
+    pub curve_tolerance: f64,
+}
+
+impl Default for EmfOptions {
+    fn default() -> Self {
+        Self {
+            plot_scale: None,
+            style: PenStyle::default(),
+            curve_tolerance: 0.02,
+        }
+    }
+}
+
+/// JWWドキュメントをEMFに変換する
+///
+/// 線・円弧・寸法線は線幅と線種を反映した折れ線、ソリッドは塗りつぶし多角形、
+/// 文字はShift-JISのフォントとして出力する。点と非表示レイヤのエンティティは出力しない。
+pub fn to_emf(doc: &Document, options: &EmfOptions) -> Vec<u8> {
+    let plot_scale = options
+        .plot_scale
+        .filter(|s| *s > 0.0)
+        .unwrap_or_else(|| doc.drawing_scale());
+    let (min_x, max_y, width, height) = doc
+        .bounding_box()
+        .map(|bb| (bb.min_x, bb.max_y, bb.width(), bb.height()))
+        .unwrap_or_default();
+    // 図面座標から論理単位への変換 (Y軸は下向き)
+    let k = UNITS_PER_MM / plot_scale;
+    let transform = Affine {
+        a: k,
+        b: 0.0,
+        c: -min_x * k,
+        d: 0.0,
+        e: -k,
+        f: max_y * k,
+    };
+    let size = ((width * k).ceil() as i32, (height * k).ceil() as i32);
+
+    let mut emf = EmfWriter {
+        records: Vec::new(),
+        record_count: 0,
+        next_handle: 1,
+        handle_count: 1,
+        pens: HashMap::new(),
+        brushes: HashMap::new(),
+        fonts: HashMap::new(),
+    };
+    emf.record(EMR_SETMAPMODE, |r| push_u32(r, MM_TEXT));
+    emf.record(EMR_SETBKMODE, |r| push_u32(r, TRANSPARENT));
+    emf.record(EMR_SETTEXTALIGN, |r| push_u32(r, TA_BOTTOM));
+
+    flatten(
+        doc,
+        &transform,
+        options.curve_tolerance * plot_scale * k,
+        &options.style.palette,
+        |item| {
+            let base = item.base;
+            // 図面上の長さを論理単位に換算する倍率
+            let scale = group_scale(doc, base) * k;
+            match item.shape {
+                Shape::Polyline { points, .. } => {
+                    if points.len() < 2 {
+                        return;
+                    }
+                    let width = options.style.paper_stroke_width(base) * scale;
+                    emf.select_pen(item.color, base, width, scale);
+                    emf.poly(EMR_POLYLINE, &points);
+                }
+                Shape::Fill { points } => {
+                    emf.select_brush(item.color);
+                    emf.record(EMR_SELECTOBJECT, |r| push_u32(r, NULL_PEN));
+                    emf.poly(EMR_POLYGON, &points);
+                }
+                Shape::Text {
+                    x,
+                    y,
+                    height,
+                    angle,
+                    text,
+                } => {
+                    if text.content.is_empty() {
+                        return;
+                    }
+                    let ratio = if text.size_y != 0.0 {
+                        height / text.size_y
+                    } else {
+                        1.0
+                    };
+                    let length =
+                        (text.end.x - text.start.x).hypot(text.end.y - text.start.y) * ratio * k;
+                    emf.text(
+                        item.color,
+                        (x, y),
+                        height * group_scale(doc, base) * k,
+                        length,
+                        angle,
+                        &text.font_name,
+                        &text.content,
+                    );
+                }
+                Shape::Point { .. } => {}
+            }
+        },
+    );
+
+    emf.finish(size)
+}
+
+struct EmfWriter {
+    /// ヘッダーを除くレコード
+    records: Vec<u8>,
+    record_count: u32,
+    next_handle: u32,
+    /// ハンドル表の大きさ (使用したハンドルの最大値 + 1)
+    handle_count: u32,
+    /// (色, 線幅, 線種) ごとのペンのハンドル
+    pens: HashMap<(Rgb, i32, u8), u32>,
+    brushes: HashMap<Rgb, u32>,
+    /// (文字高さ, 角度, フォント名) ごとのフォントのハンドル
+    fonts: HashMap<(i32, i32, String), u32>,
+}
+
+impl EmfWriter {
+    /// レコードを追加する (種別とサイズは自動で付加する)
+    fn record(&mut self, record_type: u32, body: impl FnOnce(&mut Vec<u8>)) {
+        let start = self.records.len();
+        push_u32(&mut self.records, record_type);
+        push_u32(&mut self.records, 0);
+        body(&mut self.records);
+        while !(self.records.len() - start).is_multiple_of(4) {
+            self.records.push(0);
+        }
+        let size = (self.records.len() - start) as u32;
+        self.records[start + 4..start + 8].copy_from_slice(&size.to_le_bytes());
+        self.record_count += 1;
+    }
+
+    /// 新しいオブジェクトのハンドルを割り当てる
+    ///
+    /// ハンドル数はヘッダーのWORDで表すため、上限に達した場合は作成済みの
+    /// オブジェクトを削除してハンドルを1から使い直す。
+    fn handle(&mut self) -> u32 {
+        if self.next_handle >= u32::from(u16::MAX) {
+            self.delete_objects();
+        }
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        self.handle_count = self.handle_count.max(self.next_handle);
+        handle
+    }
+
+    /// 既定のオブジェクトを選択し直して、作成済みのペン・ブラシ・フォントを削除する
+    fn delete_objects(&mut self) {
+        for stock in [NULL_PEN, NULL_BRUSH, SYSTEM_FONT] {
+            self.record(EMR_SELECTOBJECT, |r| push_u32(r, stock));
+        }
+        let handles: Vec<u32> = self
+            .pens
+            .drain()
+            .map(|(_, h)| h)
+            .chain(self.brushes.drain().map(|(_, h)| h))
+            .chain(self.fonts.drain().map(|(_, h)| h))
+            .collect();
+        for handle in handles {
+            self.record(EMR_DELETEOBJECT, |r| push_u32(r, handle));
+        }
+        self.next_handle = 1;
+    }
+
+    /// 線色・線幅・線種に対応するペンを選択する (初回のみ作成する)
+    fn select_pen(&mut self, rgb: Rgb, base: &EntityBase, width: f64, scale: f64) {
+        let width = width.round().max(1.0) as i32;
+        let key = (rgb, width, base.pen_style);
+        let handle = match self.pens.get(&key) {
+            Some(&handle) => handle,
+            None => {
+                let handle = self.handle();
+                let pattern = dash_pattern(base.pen_style);
+                let style = if pattern.is_empty() { 0 } else { PS_USERSTYLE };
+                self.record(EMR_EXTCREATEPEN, |r| {
+                    push_u32(r, handle);
+                    // ビットマップなし (offBmi, cbBmi, offBits, cbBits)
+                    for _ in 0..4 {
+                        push_u32(r, 0);
+                    }
+                    push_u32(r, PS_GEOMETRIC | style | PS_ENDCAP_FLAT | PS_JOIN_ROUND);
+                    push_u32(r, width as u32);
+                    push_u32(r, BS_SOLID);
+                    push_u32(r, colorref(rgb));
+                    push_u32(r, 0);
+                    push_u32(r, pattern.len() as u32);
+                    for v in pattern {
+                        // 長さ0の線分は線幅分の点とする
+                        push_u32(r, ((v * scale).round() as u32).max(width as u32));
+                    }
+                });
+                self.pens.insert(key, handle);
+                handle
+            }
+        };
+        self.record(EMR_SELECTOBJECT, |r| push_u32(r, handle));
+    }
+
+    /// 塗りつぶし色のブラシを選択する (初回のみ作成する)
+    fn select_brush(&mut self, rgb: Rgb) {
+        let handle = match self.brushes.get(&rgb) {
+            Some(&handle) => handle,
+            None => {
+                let handle = self.handle();
+                self.record(EMR_CREATEBRUSHINDIRECT, |r| {
+                    push_u32(r, handle);
+                    push_u32(r, BS_SOLID);
+                    push_u32(r, colorref(rgb));
+                    push_u32(r, 0);
+                });
+                self.brushes.insert(rgb, handle);
+                handle
+            }
+        };
+        self.record(EMR_SELECTOBJECT, |r| push_u32(r, handle));
+    }
+
+    /// 折れ線または多角形のレコードを追加する
+    fn poly(&mut self, record_type: u32, points: &[(f64, f64)]) {
+        let points: Vec<(i32, i32)> = points
+            .iter()
+            .map(|&(x, y)| (x.round() as i32, y.round() as i32))
+            .collect();
+        self.record(record_type, |r| {
+            push_rect(r, bounds(&points));
+            push_u32(r, points.len() as u32);
+            for &(x, y) in &points {
+                push_i32(r, x);
+                push_i32(r, y);
+            }
+        });
+    }
+
+    /// 文字高さ・角度・フォント名に対応するフォントを選択する (初回のみ作成する)
+    fn select_font(&mut self, height: i32, escapement: i32, font_name: &str) {
+        let key = (height, escapement, font_name.to_string());
+        let handle = match self.fonts.get(&key) {
+            Some(&handle) => handle,
+            None => {
+                let handle = self.handle();
+                self.record(EMR_EXTCREATEFONTINDIRECTW, |r| {
+                    push_u32(r, handle);
+                    push_i32(r, -height);
+                    push_i32(r, 0);
+                    push_i32(r, escapement);
+                    push_i32(r, escapement);
+                    push_i32(r, 400);
+                    r.extend_from_slice(&[0, 0, 0, SHIFTJIS_CHARSET, 0, 0, 0, 0]);
+                    let mut face: Vec<u16> = font_name.encode_utf16().take(31).collect();
+                    face.resize(32, 0);
+                    for c in face {
+                        r.extend_from_slice(&c.to_le_bytes());
+                    }
+                });
+                self.fonts.insert(key, handle);
+                handle
+            }
+        };
+        self.record(EMR_SELECTOBJECT, |r| push_u32(r, handle));
+    }
+
+    /// 文字を出力する
+    #[allow(clippy::too_many_arguments)]
+    fn text(
+        &mut self,
+        color: Rgb,
+        (x, y): (f64, f64),
+        height: f64,
+        length: f64,
+        angle: f64,
+        font_name: &str,
+        content: &str,
+    ) {
+        // 縦書きフォント (`@`) は下向きに文字を並べる
+        let escapement = if is_vertical_font(font_name) {
+            angle - 90.0
+        } else {
+            angle
+        };
+        let escapement = (escapement * 10.0).round() as i32;
+        self.select_font((height.round() as i32).max(1), escapement, font_name);
+        self.record(EMR_SETTEXTCOLOR, |r| push_u32(r, colorref(color)));
+
+        let chars: Vec<u16> = content.encode_utf16().collect();
+        let advance = (length / chars.len() as f64).round().max(0.0) as u32;
+        // EMR_EXTTEXTOUTW の固定部分 (種別・サイズを含む) は76バイト
+        let string_offset = 76u32;
+        let string_size = (chars.len() as u32 * 2).div_ceil(4) * 4;
+        let point = (x.round() as i32, y.round() as i32);
+        self.record(EMR_EXTTEXTOUTW, |r| {
+            push_rect(r, (0, 0, -1, -1));
+            push_u32(r, GM_ADVANCED);
+            r.extend_from_slice(&0f32.to_le_bytes());
+            r.extend_from_slice(&0f32.to_le_bytes());
+            push_i32(r, point.0);
+            push_i32(r, point.1);
+            push_u32(r, chars.len() as u32);
+            push_u32(r, string_offset);
+            push_u32(r, 0);
+            push_rect(r, (0, 0, -1, -1));
+            push_u32(r, string_offset + string_size);
+            for c in &chars {
+                r.extend_from_slice(&c.to_le_bytes());
+            }
+            if !chars.len().is_multiple_of(2) {
+                r.extend_from_slice(&[0, 0]);
+            }
+            for _ in &chars {
+                push_u32(r, advance);
+            }
+        });
+    }
+
+    /// ヘッダーとEOFを付加してEMFを完成させる
+    fn finish(mut self, (width, height): (i32, i32)) -> Vec<u8> {
+        self.record(EMR_EOF, |r| {
+            push_u32(r, 0);
+            push_u32(r, 16);
+            push_u32(r, 20);
+        });
+
+        const HEADER_SIZE: u32 = 88;
+        let mut out = Vec::with_capacity(HEADER_SIZE as usize + self.records.len());
+        push_u32(&mut out, EMR_HEADER);
+        push_u32(&mut out, HEADER_SIZE);
+        // 描画範囲 (論理単位 = 0.01mm) と図面枠 (0.01mm)
+        push_rect(&mut out, (0, 0, width, height));
+        push_rect(&mut out, (0, 0, width, height));
+        push_u32(&mut out, 0x464D_4520); // " EMF"
+        push_u32(&mut out, 0x0001_0000);
+        push_u32(&mut out, HEADER_SIZE + self.records.len() as u32);
+        push_u32(&mut out, self.record_count + 1);
+        let handles =
+            u16::try_from(self.handle_count).expect("handle table is reset before it overflows");
+        out.extend_from_slice(&handles.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes());
+        push_u32(&mut out, 0); // nDescription
+        push_u32(&mut out, 0); // offDescription
+        push_u32(&mut out, 0); // nPalEntries
+                               // 参照デバイス: 1ピクセル = 0.01mm
+        push_i32(&mut out, 100_000);
+        push_i32(&mut out, 100_000);
+        push_i32(&mut out, 1000);
+        push_i32(&mut out, 1000);
+        out.extend_from_slice(&self.records);
+        out
+    }
+}
+
+/// Windows の COLORREF (0x00BBGGRR) を返す
+fn colorref(rgb: Rgb) -> u32 {
+    rgb.r as u32 | (rgb.g as u32) << 8 | (rgb.b as u32) << 16
+}
+
+/// 点列を囲む矩形 (両端を含む) を返す
+fn bounds(points: &[(i32, i32)]) -> (i32, i32, i32, i32) {
+    points.iter().fold(
+        (i32::MAX, i32::MAX, i32::MIN, i32::MIN),
+        |(l, t, r, b), &(x, y)| (l.min(x), t.min(y), r.max(x), b.max(y)),
+    )
+}
+
+fn push_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn push_i32(buf: &mut Vec<u8>, value: i32) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn push_rect(buf: &mut Vec<u8>, (left, top, right, bottom): (i32, i32, i32, i32)) {
+    push_i32(buf, left);
+    push_i32(buf, top);
+    push_i32(buf, right);
+    push_i32(buf, bottom);
+}
@@ -0,0 +1,217 @@
+//! 描画用プリミティブへの展開
+//!
+//! ブロック挿入を構成要素に分解し、曲線を折れ線に近似して、出力形式に依存しない
+//! 図形の列に変換する。
+
+use jww_core::{BlockDef, Document, Entity, EntityBase, Line, Palette, Point2D, Rgb, Text};
+
+use crate::affine::Affine;
+
+/// ブロックの入れ子の上限 (循環参照対策)
+const MAX_BLOCK_DEPTH: usize = 16;
+
+/// 展開された図形
+pub(crate) enum Shape<'a> {
+    /// 折れ線 (`closed` の場合は始点と終点が一致する)
+    Polyline {
+        points: Vec<(f64, f64)>,
+        closed: bool,
+    },
+    /// 塗りつぶし多角形 (外周順)
+    Fill { points: Vec<(f64, f64)> },
+    /// 点
+    Point { x: f64, y: f64 },
+    /// 文字 (位置・高さ・角度 (度) は変換後の値)
+    Text {
+        x: f64,
+        y: f64,
+        height: f64,
+        angle: f64,
+        text: &'a Text<'a>,
+    },
+}
+
+/// 展開された図形と元のエンティティの属性
+pub(crate) struct Item<'a> {
+    /// 元のエンティティの種類名
+    pub type_name: &'static str,
+    pub base: &'a EntityBase,
+    /// 表示色
+    pub color: Rgb,
+    /// ブロック内の場合のブロック定義 (最も内側)
+    pub block: Option<&'a BlockDef<'a>>,
+    pub shape: Shape<'a>,
+    /// 真円の円弧の場合、折れ線近似前の円弧 (変換で円が保たれない場合は `None`)
+    pub circular: Option<CircularArc>,
+}
+
+/// 変換後の真円の円弧 (始点は折れ線の最初の点)
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CircularArc {
+    pub center_x: f64,
+    pub center_y: f64,
+    /// 円弧角 (ラジアン、正は反時計回り)
+    pub sweep: f64,
+}
+
+/// ドキュメントを図形に展開する
+///
+/// 非表示レイヤのエンティティと仮点は除く。曲線は弦の許容誤差 `tolerance` で近似し、
+/// 線色番号の色は `palette` から求める。
+pub(crate) fn flatten<'a>(
+    doc: &'a Document<'a>,
+    transform: &Affine,
+    tolerance: f64,
+    palette: &Palette,
+    mut visit: impl FnMut(Item<'a>),
+) {
+    for entity in &doc.entities {
+        if crate::view::is_visible(doc, entity.base()) {
+            flatten_entity(
+                doc, entity, transform, tolerance, palette, None, 0, &mut visit,
+            );
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn flatten_entity<'a>(
+    doc: &'a Document<'a>,
+    entity: &'a Entity<'a>,
+    affine: &Affine,
+    tolerance: f64,
+    palette: &Palette,
+    block: Option<&'a BlockDef<'a>>,
+    depth: usize,
+    visit: &mut impl FnMut(Item<'a>),
+) {
+    let type_name = entity.type_name();
+    let base = entity.base();
+    let mut emit = |base: &'a EntityBase, shape: Shape<'a>| {
+        visit(Item {
+            type_name,
+            base,
+            color: doc.pen_rgb(base.pen_color, palette),
+            block,
+            shape,
+            circular: None,
+        })
+    };
+    let map = |points: &[Point2D]| -> Vec<(f64, f64)> {
+        points.iter().map(|&p| affine.apply(p).into()).collect()
+    };
+    let line = |line: &Line| Shape::Polyline {
+        points: map(&[line.start, line.end]),
+        closed: false,
+    };
+    let text = |text: &'a Text| {
+        let Point2D { x, y } = affine.apply(text.start);
+        Shape::Text {
+            x,
+            y,
+            height: text.size_y * affine.scale_factor(),
+            angle: text.angle.degrees() + affine.rotation().to_degrees(),
+            text,
+        }
+    };
+
+    match entity {
+        Entity::Line(l) => emit(base, line(l)),
+        Entity::Arc(arc) => {
+            // 拡大縮小後の曲線でも許容誤差を保つ
+            let scale = affine.scale_factor();
+            let tolerance = if scale > 0.0 {
+                tolerance / scale
+            } else {
+                tolerance
+            };
+            let mut points = map(&arc.tessellate(tolerance));
+            let closed = arc.is_closed();
+            if closed {
+                // 始点と終点を厳密に一致させる
+                if let Some(&first) = points.first() {
+                    let last = points.len() - 1;
+                    points[last] = first;
+                }
+            }
+            let circular = (!arc.is_ellipse() && affine.is_conformal()).then(|| {
+                let (start, end) = arc.param_range();
+                let center = affine.apply(arc.center);
+                CircularArc {
+                    center_x: center.x,
+                    center_y: center.y,
+                    sweep: end - start,
+                }
+            });
+            visit(Item {
+                type_name,
+                base,
+                color: entity.resolved_rgb_with(doc, palette),
+                block,
+                shape: Shape::Polyline { points, closed },
+                circular,
+            });
+        }
+        Entity::Point(point) => {
+            if !point.is_temporary {
+                let Point2D { x, y } = affine.apply(point.position);
+                emit(base, Shape::Point { x, y });
+            }
+        }
+        Entity::Text(t) => emit(base, text(t)),
+        Entity::Solid(solid) => {
+            // DXFのSOLIDと同じく 1-2-4-3 の順に外周をたどる
+            let points = map(&[solid.point1, solid.point2, solid.point4, solid.point3]);
+            visit(Item {
+                type_name,
+                base,
+                color: entity.resolved_rgb_with(doc, palette),
+                block,
+                shape: Shape::Fill { points },
+                circular: None,
+            });
+        }
+        Entity::Block(insert) => {
+            if depth >= MAX_BLOCK_DEPTH {
+                return;
+            }
+            let Some(def) = doc
+                .block_defs
+                .iter()
+                .find(|bd| bd.number == insert.def_number)
+            else {
+                return;
+            };
+            let child = Affine::from_block(insert).then(affine);
+            for entity in &def.entities {
+                flatten_entity(
+                    doc,
+                    entity,
+                    &child,
+                    tolerance,
+                    palette,
+                    Some(def),
+                    depth + 1,
+                    visit,
+                );
+            }
+        }
+        Entity::Dimension(dim) => {
+            emit(&dim.line.base, line(&dim.line));
+            for l in &dim.extension_lines {
+                emit(&l.base, line(l));
+            }
+            emit(&dim.text.base, text(&dim.text));
+        }
+        Entity::Unknown(_) => {}
+    }
+}
+
+/// ブロック名を返す (名前がない場合は `BLOCK_番号`)
+pub(crate) fn block_name(def: &BlockDef) -> String {
+    if def.name.is_empty() {
+        format!("BLOCK_{}", def.number)
+    } else {
+        def.name.clone()
+    }
+}
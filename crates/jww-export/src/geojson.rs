@@ -0,0 +1,140 @@
+//! GeoJSON出力
+
+use jww_core::Document;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::affine::Affine;
+use crate::flatten::{block_name, flatten, Item, Shape};
+use crate::style::PenStyle;
+use crate::view::layer_name;
+
+/// GeoJSON出力オプション
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GeoJsonOptions {
+    /// 図面座標に適用するアフィン変換 (`None` の場合は図面座標のまま)
+    pub transform: Option<Affine>,
+    /// 円弧を折れ線に近似する際の弦の許容誤差 (JWW座標のmm)
+    pub curve_tolerance: f64,
+    /// `color` プロパティに使用する線色の色 (`palette` のみ使用し、線幅は使用しない)
+    #[serde(flatten)]
+    pub style: PenStyle,
+}
+
+impl Default for GeoJsonOptions {
+    fn default() -> Self {
+        Self {
+            transform: None,
+            curve_tolerance: 1.0,
+            style: PenStyle::default(),
+        }
+    }
+}
+
+/// JWWドキュメントをGeoJSON (FeatureCollection) 文字列に変換する
+///
+/// 線・寸法線・開いた円弧は `LineString`、円・楕円・ソリッドは `Polygon`、
+/// 点・文字・寸法値は `Point` とし、レイヤ・線色などをプロパティに持たせる。
+/// ブロック挿入は構成要素に分解する。非表示レイヤのエンティティと仮点は出力しない。
+pub fn to_geojson(doc: &Document, options: &GeoJsonOptions) -> String {
+    to_geojson_with_transform(doc, options, |x, y| (x, y))
+}
+
+/// 座標変換関数を指定してJWWドキュメントをGeoJSON文字列に変換する
+///
+/// `transform` は [`GeoJsonOptions::transform`] を適用した後の座標に適用する。
+/// 任意の座標参照系への投影などに使用する。
+pub fn to_geojson_with_transform<F>(
+    doc: &Document,
+    options: &GeoJsonOptions,
+    transform: F,
+) -> String
+where
+    F: Fn(f64, f64) -> (f64, f64),
+{
+    let coord = |(x, y): (f64, f64)| -> [f64; 2] {
+        let (x, y) = transform(x, y);
+        [x, y]
+    };
+    let coords =
+        |points: Vec<(f64, f64)>| -> Vec<[f64; 2]> { points.into_iter().map(coord).collect() };
+
+    let mut features = Vec::new();
+    let affine = options.transform.unwrap_or_default();
+    flatten(
+        doc,
+        &affine,
+        options.curve_tolerance,
+        &options.style.palette,
+        |item| {
+            let block = item.block.map(block_name);
+            let mut props = properties(doc, &item, block.as_deref());
+            let geometry = match item.shape {
+                Shape::Polyline {
+                    points,
+                    closed: true,
+                } => {
+                    json!({ "type": "Polygon", "coordinates": [coords(points)] })
+                }
+                Shape::Polyline {
+                    points,
+                    closed: false,
+                } => {
+                    json!({ "type": "LineString", "coordinates": coords(points) })
+                }
+                Shape::Fill { mut points, .. } => {
+                    if let Some(&first) = points.first() {
+                        points.push(first);
+                    }
+                    json!({ "type": "Polygon", "coordinates": [coords(points)] })
+                }
+                Shape::Point { x, y } => json!({ "type": "Point", "coordinates": coord((x, y)) }),
+                Shape::Text {
+                    x,
+                    y,
+                    height,
+                    angle,
+                    text,
+                } => {
+                    props.insert("text".to_string(), json!(text.content));
+                    props.insert("height".to_string(), json!(height));
+                    props.insert("angle".to_string(), json!(angle));
+                    json!({ "type": "Point", "coordinates": coord((x, y)) })
+                }
+            };
+            features.push(json!({
+                "type": "Feature",
+                "geometry": geometry,
+                "properties": props,
+            }));
+        },
+    );
+
+    json!({
+        "type": "FeatureCollection",
+        "features": features,
+    })
+    .to_string()
+}
+
+/// エンティティ共通のプロパティを作成する
+fn properties(doc: &Document, item: &Item, block: Option<&str>) -> Map<String, Value> {
+    let base = item.base;
+    let mut props = Map::new();
+    props.insert("type".to_string(), json!(item.type_name));
+    props.insert(
+        "layer".to_string(),
+        json!(layer_name(doc, base.layer_group, base.layer)),
+    );
+    props.insert("layer_group".to_string(), json!(base.layer_group));
+    props.insert("layer_index".to_string(), json!(base.layer));
+    props.insert("pen_color".to_string(), json!(base.pen_color));
+    props.insert("pen_style".to_string(), json!(base.pen_style));
+    props.insert("pen_width".to_string(), json!(base.pen_width));
+    props.insert("color".to_string(), json!(item.color.to_hex()));
+    if let Some(block) = block {
+        props.insert("block".to_string(), json!(block));
+    }
+    props
+}
@@ -0,0 +1,167 @@
+//! GPU向け頂点・インデックスバッファ出力
+//!
+//! WebGL/wgpu などにそのまま転送できる `f32` の頂点配列と `u32` のインデックス配列を、
+//! 表示色と線幅ごとにまとめて出力する。
+
+use std::collections::HashMap;
+
+use jww_core::{Document, Point2D};
+use serde::{Deserialize, Serialize};
+
+use crate::affine::Affine;
+use crate::flatten::{flatten, Shape};
+use crate::style::{PenStyle, Rgb};
+use crate::view::group_scale;
+
+/// GPUバッファの出力オプション
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GpuOptions {
+    /// 円弧を折れ線に近似する際の弦の許容誤差 (JWW座標のmm)
+    pub curve_tolerance: f64,
+    /// 線色ごとの線幅と色 ([`SvgOptions::style`](crate::SvgOptions::style) と同じ)
+    #[serde(flatten)]
+    pub style: PenStyle,
+}
+
+impl Default for GpuOptions {
+    fn default() -> Self {
+        Self {
+            curve_tolerance: 0.5,
+            style: PenStyle::default(),
+        }
+    }
+}
+
+/// GPU向けバッファ
+///
+/// 頂点座標は `origin` からの相対座標 (JWW座標のmm) とし、`f32` の精度落ちを抑える。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GpuBuffers {
+    /// 頂点座標の原点 (図面範囲の中心)
+    pub origin: [f64; 2],
+    /// 線 (表示色・線幅ごと)
+    pub lines: Vec<LineBatch>,
+    /// 塗りつぶし (表示色ごと)
+    pub fills: Vec<FillBatch>,
+}
+
+/// 同じ表示色・線幅の線分の集まり
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LineBatch {
+    /// 表示色 (RGBA、0.0-1.0)
+    pub color: [f32; 4],
+    /// 線幅 (JWW座標のmm)
+    pub width: f32,
+    /// 頂点座標 `[x0, y0, x1, y1, ...]`
+    pub vertices: Vec<f32>,
+    /// 線分リストのインデックス (2つで1本の線分)
+    pub indices: Vec<u32>,
+}
+
+/// 同じ表示色の三角形の集まり
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FillBatch {
+    /// 表示色 (RGBA、0.0-1.0)
+    pub color: [f32; 4],
+    /// 頂点座標 `[x0, y0, x1, y1, ...]`
+    pub vertices: Vec<f32>,
+    /// 三角形リストのインデックス (3つで1つの三角形)
+    pub indices: Vec<u32>,
+}
+
+/// JWWドキュメントをGPU向けバッファに変換する
+///
+/// 線・円弧・寸法線は線分リスト、ソリッドは三角形リストとする。ブロックは展開する。
+/// 破線は展開せず実線として出力し、点と文字は出力しない。
+/// 非表示レイヤのエンティティは出力しない。
+pub fn to_gpu_buffers(doc: &Document, options: &GpuOptions) -> GpuBuffers {
+    let Point2D { x: ox, y: oy } = doc
+        .bounding_box()
+        .map(|bb| bb.center())
+        .unwrap_or(Point2D::ORIGIN);
+    let mut out = GpuBuffers {
+        origin: [ox, oy],
+        ..Default::default()
+    };
+    let mut line_batches: HashMap<(Rgb, u32), usize> = HashMap::new();
+    let mut fill_batches: HashMap<Rgb, usize> = HashMap::new();
+
+    flatten(
+        doc,
+        &Affine::IDENTITY,
+        options.curve_tolerance,
+        &options.style.palette,
+        |item| {
+            let base = item.base;
+            match item.shape {
+                Shape::Polyline { points, .. } => {
+                    if points.len() < 2 {
+                        return;
+                    }
+                    let rgb = item.color;
+                    let width =
+                        (options.style.paper_stroke_width(base) * group_scale(doc, base)) as f32;
+                    let index = *line_batches
+                        .entry((rgb, width.to_bits()))
+                        .or_insert_with(|| {
+                            out.lines.push(LineBatch {
+                                color: rgba(rgb),
+                                width,
+                                vertices: Vec::new(),
+                                indices: Vec::new(),
+                            });
+                            out.lines.len() - 1
+                        });
+                    let batch = &mut out.lines[index];
+                    let first = push_vertices(&mut batch.vertices, &points, ox, oy);
+                    for i in 0..points.len() as u32 - 1 {
+                        batch.indices.extend([first + i, first + i + 1]);
+                    }
+                }
+                Shape::Fill { points } => {
+                    if points.len() < 3 {
+                        return;
+                    }
+                    let rgb = item.color;
+                    let index = *fill_batches.entry(rgb).or_insert_with(|| {
+                        out.fills.push(FillBatch {
+                            color: rgba(rgb),
+                            vertices: Vec::new(),
+                            indices: Vec::new(),
+                        });
+                        out.fills.len() - 1
+                    });
+                    let batch = &mut out.fills[index];
+                    let first = push_vertices(&mut batch.vertices, &points, ox, oy);
+                    // 外周順の凸多角形を扇形に分割する
+                    for i in 1..points.len() as u32 - 1 {
+                        batch.indices.extend([first, first + i, first + i + 1]);
+                    }
+                }
+                Shape::Point { .. } | Shape::Text { .. } => {}
+            }
+        },
+    );
+
+    out
+}
+
+/// 原点からの相対座標として頂点を追加し、最初の頂点番号を返す
+fn push_vertices(vertices: &mut Vec<f32>, points: &[(f64, f64)], ox: f64, oy: f64) -> u32 {
+    let first = (vertices.len() / 2) as u32;
+    for &(x, y) in points {
+        vertices.push((x - ox) as f32);
+        vertices.push((y - oy) as f32);
+    }
+    first
+}
+
+fn rgba(rgb: Rgb) -> [f32; 4] {
+    [
+        rgb.r as f32 / 255.0,
+        rgb.g as f32 / 255.0,
+        rgb.b as f32 / 255.0,
+        1.0,
+    ]
+}
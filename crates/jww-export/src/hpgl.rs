@@ -0,0 +1,119 @@
+//! HP-GL/2出力 (ペンプロッタ・カッティングプロッタ向け)
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use jww_core::{Document, Palette};
+use serde::{Deserialize, Serialize};
+
+use crate::affine::Affine;
+use crate::flatten::{flatten, Shape};
+
+/// 1mmあたりのプロッタ単位数 (1単位 = 0.025mm)
+const UNITS_PER_MM: f64 = 40.0;
+
+/// HP-GL/2出力オプション
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HpglOptions {
+    /// 出力の縮尺 (出力1mmに対する図面上の長さ、`None` の場合は図面の縮尺で用紙上の寸法とする)
+    pub plot_scale: Option<f64>,
+    /// 線色1-9に対応するペン番号
+    pub pens: [u8; 9],
+    /// 真円の円弧を `AA` コマンドで出力する (`false` の場合は折れ線で近似する)
+    pub use_arcs: bool,
+    /// 曲線を折れ線に近似する際の弦の許容誤差 (出力上のmm)
+    pub curve_tolerance: f64,
+}
+
+impl Default for HpglOptions {
+    fn default() -> Self {
+        Self {
+            plot_scale: None,
+            pens: [1, 2, 3, 4, 5, 6, 7, 8, 8],
+            use_arcs: true,
+            curve_tolerance: 0.05,
+        }
+    }
+}
+
+/// JWWドキュメントをHP-GL/2コマンド列に変換する
+///
+/// 図面範囲の左下を原点とし、ペンごとにまとめて `PU`/`PD`/`AA` で出力する。
+/// ソリッドは外形線として出力する。点と文字は出力しない。非表示レイヤのエンティティは出力しない。
+pub fn to_hpgl(doc: &Document, options: &HpglOptions) -> String {
+    let plot_scale = options
+        .plot_scale
+        .filter(|s| *s > 0.0)
+        .unwrap_or_else(|| doc.drawing_scale());
+    let (min_x, min_y) = doc
+        .bounding_box()
+        .map(|bb| (bb.min_x, bb.min_y))
+        .unwrap_or((0.0, 0.0));
+    // 図面座標からプロッタ単位への変換
+    let k = UNITS_PER_MM / plot_scale;
+    let transform = Affine {
+        a: k,
+        b: 0.0,
+        c: -min_x * k,
+        d: 0.0,
+        e: k,
+        f: -min_y * k,
+    };
+    let tolerance = options.curve_tolerance * plot_scale;
+
+    // ペン番号ごとのコマンド列
+    let mut pens: BTreeMap<u8, String> = BTreeMap::new();
+    flatten(doc, &transform, tolerance, &Palette::SCREEN, |item| {
+        let pen = match item.base.pen_color {
+            1..=9 => options.pens[item.base.pen_color as usize - 1],
+            _ => options.pens[0],
+        };
+        let out = pens.entry(pen).or_default();
+        let points = match item.shape {
+            Shape::Polyline { points, .. } => points,
+            Shape::Fill { mut points, .. } => {
+                if let Some(&first) = points.first() {
+                    points.push(first);
+                }
+                points
+            }
+            Shape::Point { .. } | Shape::Text { .. } => return,
+        };
+        let Some(&(x, y)) = points.first() else {
+            return;
+        };
+        write!(out, "PU{},{};", unit(x), unit(y)).unwrap();
+        match item.circular.filter(|_| options.use_arcs) {
+            Some(arc) => {
+                write!(
+                    out,
+                    "PD;AA{},{},{};",
+                    unit(arc.center_x),
+                    unit(arc.center_y),
+                    crate::format::num(arc.sweep.to_degrees())
+                )
+                .unwrap();
+            }
+            None => {
+                let coords: Vec<String> = points[1..]
+                    .iter()
+                    .map(|&(x, y)| format!("{},{}", unit(x), unit(y)))
+                    .collect();
+                write!(out, "PD{};", coords.join(",")).unwrap();
+            }
+        }
+    });
+
+    let mut out = String::from("IN;");
+    for (pen, commands) in &pens {
+        write!(out, "SP{};{}", pen, commands).unwrap();
+    }
+    out.push_str("PU;SP0;");
+    out
+}
+
+/// プロッタ単位の整数座標を返す
+fn unit(value: f64) -> i64 {
+    value.round() as i64
+}
@@ -0,0 +1,44 @@
+//! JWW図面の出力ライブラリ
+//!
+//! JWWドキュメントをSVG・PNG・GeoJSON・CSVなどの表示・共有向けの形式で出力する。
+
+mod affine;
+mod csv;
+mod emf;
+mod flatten;
+mod format;
+mod geojson;
+mod gpu;
+mod hpgl;
+mod pdf;
+#[cfg(feature = "png")]
+mod png;
+mod report;
+mod style;
+mod svg;
+mod takeoff;
+mod view;
+mod viewer;
+
+pub use affine::Affine;
+pub use csv::{to_csv, CsvOptions, Delimiter};
+pub use emf::{to_emf, EmfOptions};
+pub use geojson::{to_geojson, to_geojson_with_transform, GeoJsonOptions};
+pub use gpu::{to_gpu_buffers, FillBatch, GpuBuffers, GpuOptions, LineBatch};
+pub use hpgl::{to_hpgl, HpglOptions};
+pub use pdf::{to_pdf, PdfOptions};
+#[cfg(feature = "png")]
+pub use png::{to_png, PngError, PngOptions, RasterSize};
+pub use report::{to_report, ReportFormat, ReportOptions};
+pub use style::{dash_pattern, font_family, is_vertical_font, pen_rgb, Palette, PenStyle, Rgb};
+pub use svg::{to_svg, to_svg_with_options, SvgOptions};
+pub use takeoff::{
+    quantity_takeoff, to_takeoff_csv, LengthUnit, TakeoffGroup, TakeoffOptions, TakeoffRow,
+};
+#[cfg(feature = "xlsx")]
+pub use takeoff::{to_takeoff_xlsx, TakeoffError};
+pub use view::ViewBox;
+pub use viewer::{
+    to_viewer_document, to_viewer_json, ViewerDocument, ViewerFill, ViewerLayer, ViewerOptions,
+    ViewerPoint, ViewerPolyline, ViewerText,
+};
@@ -0,0 +1,342 @@
+//! PDF出力
+//!
+//! 1ページのベクターPDFとして出力する。ページの大きさは用紙上の寸法とし、
+//! 文字はAdobe-Japan1の非埋め込みフォント (小塚明朝・小塚ゴシック) で表示する。
+
+use std::fmt::Write;
+
+use jww_core::{normalize, Document};
+use serde::{Deserialize, Serialize};
+
+use crate::affine::Affine;
+use crate::flatten::{flatten, Shape};
+use crate::format::num;
+use crate::style::{dash_pattern, is_vertical_font, PenStyle, Rgb};
+use crate::view::{group_scale, view_rect, ViewBox};
+
+/// 用紙上の1mmあたりのポイント数
+const PT_PER_MM: f64 = 72.0 / 25.4;
+
+/// PDF出力オプション
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PdfOptions {
+    /// ページの範囲の決め方 (既定は用紙の範囲)
+    pub view_box: ViewBox,
+    /// 線色ごとの線幅と色 ([`SvgOptions::style`](crate::SvgOptions::style) と同じ)
+    #[serde(flatten)]
+    pub style: PenStyle,
+    /// 曲線を折れ線に近似する際の弦の許容誤差 (出力上のmm)
+    pub curve_tolerance: f64,
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        Self {
+            view_box: ViewBox::Paper,
+            style: PenStyle::default(),
+            curve_tolerance: 0.02,
+        }
+    }
+}
+
+/// JWWドキュメントをPDFに変換する
+///
+/// 線・円弧・寸法線は線幅と線種を反映した折れ線、ソリッドは塗りつぶし多角形として出力する。
+/// 縦書きフォント (`@`) の文字は縦書きのCMapで出力する。点と非表示レイヤのエンティティは出力しない。
+pub fn to_pdf(doc: &Document, options: &PdfOptions) -> Vec<u8> {
+    let scale = doc.drawing_scale();
+    let (min_x, min_y, width, height) = view_rect(doc, options.view_box, false, 0.0);
+    // 図面座標からポイントへの変換 (PDFもY軸は上向き)
+    let k = PT_PER_MM / scale;
+    let transform = Affine {
+        a: k,
+        b: 0.0,
+        c: -min_x * k,
+        d: 0.0,
+        e: k,
+        f: -min_y * k,
+    };
+
+    let mut page = PageWriter::default();
+    flatten(
+        doc,
+        &transform,
+        options.curve_tolerance * PT_PER_MM,
+        &options.style.palette,
+        |item| {
+            let base = item.base;
+            match item.shape {
+                Shape::Polyline { points, .. } => {
+                    if points.len() < 2 {
+                        return;
+                    }
+                    let width = options.style.paper_stroke_width(base) * PT_PER_MM;
+                    page.stroke(item.color, width, base.pen_style);
+                    page.path(&points);
+                    page.content.push_str("S\n");
+                }
+                Shape::Fill { points } => {
+                    if points.len() < 3 {
+                        return;
+                    }
+                    writeln!(page.content, "{} rg", color(item.color)).unwrap();
+                    page.path(&points);
+                    page.content.push_str("h f\n");
+                }
+                Shape::Text {
+                    x,
+                    y,
+                    height,
+                    angle,
+                    text,
+                } => {
+                    if text.content.is_empty() || text.size_y <= 0.0 {
+                        return;
+                    }
+                    // `height` は図面上の長さを変換したもの、文字の大きさは用紙上のmm
+                    let ratio = height / text.size_y;
+                    let font = Font {
+                        mincho: is_mincho(&text.font_name),
+                        vertical: is_vertical_font(&text.font_name),
+                    };
+                    let length =
+                        (text.end.x - text.start.x).hypot(text.end.y - text.start.y) * ratio;
+                    let size = height * group_scale(doc, base);
+                    let char_width = text.size_x * ratio * group_scale(doc, base);
+                    page.text(
+                        item.color,
+                        (x, y),
+                        size,
+                        char_width,
+                        length,
+                        angle,
+                        font,
+                        &text.content,
+                    );
+                }
+                Shape::Point { .. } => {}
+            }
+        },
+    );
+
+    page.finish(width * k, height * k)
+}
+
+/// 使用するフォントの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Font {
+    mincho: bool,
+    vertical: bool,
+}
+
+impl Font {
+    fn base_font(self) -> &'static str {
+        if self.mincho {
+            "KozMinPr6N-Regular"
+        } else {
+            "KozGoPr6N-Medium"
+        }
+    }
+
+    fn encoding(self) -> &'static str {
+        if self.vertical {
+            "UniJIS-UCS2-V"
+        } else {
+            "UniJIS-UCS2-H"
+        }
+    }
+}
+
+/// 明朝体のフォントかどうか (それ以外はゴシック体で表示する)
+fn is_mincho(font_name: &str) -> bool {
+    let name = normalize::font_name(font_name.trim_start_matches('@'));
+    name.contains("明朝") || name.contains("Mincho")
+}
+
+/// 半角で表示される文字かどうか
+fn is_half_width(c: char) -> bool {
+    c.is_ascii() || ('\u{FF61}'..='\u{FF9F}').contains(&c)
+}
+
+#[derive(Default)]
+struct PageWriter {
+    /// ページの内容ストリーム
+    content: String,
+    /// 使用したフォント (添字+1がリソース名 `/F1` などの番号)
+    fonts: Vec<Font>,
+    /// 現在の線の状態 (色, 線幅, 線種)
+    stroke: Option<(Rgb, f64, u8)>,
+}
+
+impl PageWriter {
+    /// 線の色・線幅・線種を設定する (変化がない場合は出力しない)
+    fn stroke(&mut self, rgb: Rgb, width: f64, pen_style: u8) {
+        if self.stroke == Some((rgb, width, pen_style)) {
+            return;
+        }
+        self.stroke = Some((rgb, width, pen_style));
+        let dashes: Vec<String> = dash_pattern(pen_style)
+            .iter()
+            // 長さ0の線分は線幅分の点とする
+            .map(|v| num((v * PT_PER_MM).max(width)))
+            .collect();
+        writeln!(
+            self.content,
+            "{} RG {} w [{}] 0 d",
+            color(rgb),
+            num(width),
+            dashes.join(" ")
+        )
+        .unwrap();
+    }
+
+    fn path(&mut self, points: &[(f64, f64)]) {
+        for (i, &(x, y)) in points.iter().enumerate() {
+            let op = if i == 0 { "m" } else { "l" };
+            writeln!(self.content, "{} {} {}", num(x), num(y), op).unwrap();
+        }
+    }
+
+    fn font(&mut self, font: Font) -> usize {
+        match self.fonts.iter().position(|&f| f == font) {
+            Some(index) => index + 1,
+            None => {
+                self.fonts.push(font);
+                self.fonts.len()
+            }
+        }
+    }
+
+    /// 文字列を出力する
+    ///
+    /// 文字列の長さが `length` になるように文字間隔を調整する。
+    #[allow(clippy::too_many_arguments)]
+    fn text(
+        &mut self,
+        rgb: Rgb,
+        (mut x, mut y): (f64, f64),
+        size: f64,
+        char_width: f64,
+        length: f64,
+        angle: f64,
+        font: Font,
+        content: &str,
+    ) {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let chars: Vec<char> = content.chars().collect();
+        let natural = if font.vertical {
+            // 縦書きの字形の原点は上端の中央
+            x += cos * char_width / 2.0;
+            y += sin * char_width / 2.0;
+            chars.len() as f64 * size
+        } else {
+            chars
+                .iter()
+                .map(|&c| if is_half_width(c) { size / 2.0 } else { size })
+                .sum()
+        };
+        let spacing = if length > 0.0 && chars.len() > 1 {
+            (length - natural) / (chars.len() - 1) as f64
+        } else {
+            0.0
+        };
+        let font = self.font(font);
+        let mut hex = String::new();
+        for unit in content.encode_utf16() {
+            write!(hex, "{:04X}", unit).unwrap();
+        }
+        writeln!(
+            self.content,
+            "BT {} rg /F{} {} Tf {} Tc {} {} {} {} {} {} Tm <{}> Tj ET",
+            color(rgb),
+            font,
+            num(size),
+            num(spacing),
+            num(cos),
+            num(sin),
+            num(-sin),
+            num(cos),
+            num(x),
+            num(y),
+            hex
+        )
+        .unwrap();
+    }
+
+    /// ページの内容とフォントからPDFを組み立てる
+    fn finish(self, width: f64, height: f64) -> Vec<u8> {
+        // 1: カタログ、2: ページツリー、3: ページ、4: 内容、以降フォントごとに3つ
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        ];
+        let fonts: Vec<String> = (0..self.fonts.len())
+            .map(|i| format!("/F{} {} 0 R", i + 1, 5 + i * 3))
+            .collect();
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << {} >> >> /Contents 4 0 R >>",
+            num(width),
+            num(height),
+            fonts.join(" ")
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            self.content.len(),
+            self.content
+        ));
+        for (i, font) in self.fonts.iter().enumerate() {
+            let id = 5 + i * 3;
+            let name = font.base_font();
+            objects.push(format!(
+                "<< /Type /Font /Subtype /Type0 /BaseFont /{}-{} /Encoding /{} /DescendantFonts [{} 0 R] >>",
+                name,
+                font.encoding(),
+                font.encoding(),
+                id + 1
+            ));
+            // 半角の英数字・カタカナ (CID 1-95, 327-389) は半分の幅
+            objects.push(format!(
+                "<< /Type /Font /Subtype /CIDFontType0 /BaseFont /{} /CIDSystemInfo << /Registry (Adobe) /Ordering (Japan1) /Supplement 6 >> /FontDescriptor {} 0 R /DW 1000 /W [1 95 500 327 389 500] >>",
+                name,
+                id + 2
+            ));
+            objects.push(format!(
+                "<< /Type /FontDescriptor /FontName /{} /Flags {} /FontBBox [-437 -340 1147 1317] /ItalicAngle 0 /Ascent 880 /Descent -120 /CapHeight 742 /StemV 80 >>",
+                name,
+                if font.mincho { 6 } else { 4 }
+            ));
+        }
+
+        let mut out = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+        }
+        let xref = out.len();
+        let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            writeln!(trailer, "{:010} 00000 n ", offset).unwrap();
+        }
+        write!(
+            trailer,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .unwrap();
+        out.extend_from_slice(trailer.as_bytes());
+        out
+    }
+}
+
+/// PDFの色成分 (0-1) を返す
+fn color(rgb: Rgb) -> String {
+    format!(
+        "{} {} {}",
+        num(rgb.r as f64 / 255.0),
+        num(rgb.g as f64 / 255.0),
+        num(rgb.b as f64 / 255.0)
+    )
+}
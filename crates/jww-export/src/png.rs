@@ -0,0 +1,275 @@
+//! PNG出力 (tiny-skia によるラスタライズ)
+
+use jww_core::{Arc, Document, Entity, EntityBase, Line};
+use serde::{Deserialize, Serialize};
+use tiny_skia::{
+    Color, FillRule, LineCap, Paint, PathBuilder, Pixmap, Stroke, StrokeDash, Transform,
+};
+
+use crate::style::{dash_pattern, PenStyle, Rgb};
+use crate::view::{group_scale, is_visible, view_rect, ViewBox};
+
+/// ブロックの入れ子の上限 (循環参照対策)
+const MAX_BLOCK_DEPTH: usize = 16;
+
+/// PNG出力時のエラー型
+#[derive(Debug, thiserror::Error)]
+pub enum PngError {
+    /// 画像サイズが0または大きすぎる
+    #[error("invalid image size: {0}x{1}")]
+    InvalidSize(u32, u32),
+
+    /// PNGのエンコードに失敗した
+    #[error("PNG encode error: {0}")]
+    Encode(String),
+}
+
+/// 出力画像の大きさ
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RasterSize {
+    /// 用紙上の寸法に対する解像度 (dpi)
+    Dpi(f64),
+    /// 画像のピクセル数 (図面は縦横比を保って中央に配置する)
+    Pixels { width: u32, height: u32 },
+    /// 画像の幅のピクセル数 (高さは図面の縦横比から決める)
+    Width(u32),
+}
+
+/// PNG出力オプション
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PngOptions {
+    /// 出力画像の大きさ
+    pub size: RasterSize,
+    /// 出力範囲の決め方
+    pub view_box: ViewBox,
+    /// 背景色 (`None` の場合は透明)
+    pub background: Option<Rgb>,
+    /// 線色ごとの線幅と色 ([`SvgOptions::style`](crate::SvgOptions::style) と同じ)
+    #[serde(flatten)]
+    pub style: PenStyle,
+}
+
+impl Default for PngOptions {
+    fn default() -> Self {
+        Self {
+            size: RasterSize::Dpi(96.0),
+            view_box: ViewBox::Extents,
+            background: Some(Rgb::new(255, 255, 255)),
+            style: PenStyle::default(),
+        }
+    }
+}
+
+/// JWWドキュメントをPNG画像に変換する
+///
+/// 線・円弧・点・ソリッド・ブロック・寸法線を描画する。フォントを持たないため文字は描画しない。
+///
+/// # エラー
+/// - 画像サイズが0、または確保できない大きさの場合
+/// - PNGのエンコードに失敗した場合
+pub fn to_png(doc: &Document, options: &PngOptions) -> Result<Vec<u8>, PngError> {
+    let scale = doc.drawing_scale();
+    let margin = options
+        .style
+        .pen_widths
+        .iter()
+        .fold(0.0_f64, |a, b| a.max(*b))
+        * scale;
+    let (min_x, min_y, width, height) = view_rect(doc, options.view_box, false, margin);
+
+    // 図面上の長さ1あたりのピクセル数
+    let (pixel_width, pixel_height, k) = match options.size {
+        RasterSize::Dpi(dpi) => {
+            let k = dpi / 25.4 / scale;
+            ((width * k).ceil() as u32, (height * k).ceil() as u32, k)
+        }
+        RasterSize::Pixels {
+            width: w,
+            height: h,
+        } => {
+            let k = (w as f64 / width).min(h as f64 / height);
+            (w, h, k)
+        }
+        RasterSize::Width(w) => {
+            let k = w as f64 / width;
+            (w, (height * k).ceil() as u32, k)
+        }
+    };
+    if !k.is_finite() || k <= 0.0 {
+        return Err(PngError::InvalidSize(pixel_width, pixel_height));
+    }
+    let mut pixmap = Pixmap::new(pixel_width, pixel_height)
+        .ok_or(PngError::InvalidSize(pixel_width, pixel_height))?;
+    if let Some(bg) = options.background {
+        pixmap.fill(Color::from_rgba8(bg.r, bg.g, bg.b, 255));
+    }
+
+    // 図面を中央に配置し、Y軸を反転する
+    let offset_x = (pixel_width as f64 - width * k) / 2.0;
+    let offset_y = (pixel_height as f64 - height * k) / 2.0;
+    let transform = Transform::from_row(
+        k as f32,
+        0.0,
+        0.0,
+        -k as f32,
+        (offset_x - min_x * k) as f32,
+        (offset_y + (min_y + height) * k) as f32,
+    );
+
+    let mut renderer = Renderer {
+        doc,
+        options,
+        pixmap: &mut pixmap,
+        tolerance: 0.25 / k,
+    };
+    for entity in &doc.entities {
+        if is_visible(doc, entity.base()) {
+            renderer.entity(entity, transform, 0);
+        }
+    }
+
+    pixmap
+        .encode_png()
+        .map_err(|e| PngError::Encode(e.to_string()))
+}
+
+struct Renderer<'a> {
+    doc: &'a Document<'a>,
+    options: &'a PngOptions,
+    pixmap: &'a mut Pixmap,
+    /// 円弧を折れ線に近似する許容誤差 (図面上の長さ)
+    tolerance: f64,
+}
+
+impl Renderer<'_> {
+    fn entity(&mut self, entity: &Entity, transform: Transform, depth: usize) {
+        match entity {
+            Entity::Line(line) => self.line(line, transform),
+            Entity::Arc(arc) => self.arc(arc, transform),
+            Entity::Point(point) => {
+                if point.is_temporary {
+                    return;
+                }
+                let radius = self.stroke_width(&point.base);
+                if let Some(path) = PathBuilder::from_circle(
+                    point.position.x as f32,
+                    point.position.y as f32,
+                    radius as f32,
+                ) {
+                    let paint =
+                        paint(entity.resolved_rgb_with(self.doc, &self.options.style.palette));
+                    self.pixmap
+                        .fill_path(&path, &paint, FillRule::Winding, transform, None);
+                }
+            }
+            Entity::Text(_) => {}
+            Entity::Solid(solid) => {
+                let color = entity.resolved_rgb_with(self.doc, &self.options.style.palette);
+                // DXFのSOLIDと同じく 1-2-4-3 の順に外周をたどる
+                let mut pb = PathBuilder::new();
+                pb.move_to(solid.point1.x as f32, solid.point1.y as f32);
+                pb.line_to(solid.point2.x as f32, solid.point2.y as f32);
+                pb.line_to(solid.point4.x as f32, solid.point4.y as f32);
+                pb.line_to(solid.point3.x as f32, solid.point3.y as f32);
+                pb.close();
+                if let Some(path) = pb.finish() {
+                    self.pixmap
+                        .fill_path(&path, &paint(color), FillRule::Winding, transform, None);
+                }
+            }
+            Entity::Block(block) => {
+                if depth >= MAX_BLOCK_DEPTH {
+                    return;
+                }
+                let Some(def) = self
+                    .doc
+                    .block_defs
+                    .iter()
+                    .find(|bd| bd.number == block.def_number)
+                else {
+                    return;
+                };
+                let child = transform
+                    .pre_translate(block.reference.x as f32, block.reference.y as f32)
+                    .pre_rotate(block.rotation.degrees() as f32)
+                    .pre_scale(block.scale_x as f32, block.scale_y as f32);
+                for entity in &def.entities {
+                    self.entity(entity, child, depth + 1);
+                }
+            }
+            Entity::Dimension(dim) => {
+                self.line(&dim.line, transform);
+                for line in &dim.extension_lines {
+                    self.line(line, transform);
+                }
+            }
+            Entity::Unknown(_) => {}
+        }
+    }
+
+    fn line(&mut self, line: &Line, transform: Transform) {
+        let mut pb = PathBuilder::new();
+        pb.move_to(line.start.x as f32, line.start.y as f32);
+        pb.line_to(line.end.x as f32, line.end.y as f32);
+        self.stroke(pb, &line.base, transform);
+    }
+
+    fn arc(&mut self, arc: &Arc, transform: Transform) {
+        let points = arc.tessellate(self.tolerance);
+        let mut pb = PathBuilder::new();
+        for (i, point) in points.iter().enumerate() {
+            if i == 0 {
+                pb.move_to(point.x as f32, point.y as f32);
+            } else {
+                pb.line_to(point.x as f32, point.y as f32);
+            }
+        }
+        if arc.is_closed() {
+            pb.close();
+        }
+        self.stroke(pb, &arc.base, transform);
+    }
+
+    /// 図面上の線幅を返す
+    fn stroke_width(&self, base: &EntityBase) -> f64 {
+        self.options.style.paper_stroke_width(base) * group_scale(self.doc, base)
+    }
+
+    /// 線色・線幅・線種に従って線を描画する
+    fn stroke(&mut self, pb: PathBuilder, base: &EntityBase, transform: Transform) {
+        let Some(path) = pb.finish() else {
+            return;
+        };
+        let scale = group_scale(self.doc, base);
+        let pattern = dash_pattern(base.pen_style);
+        let stroke = Stroke {
+            width: self.stroke_width(base) as f32,
+            // 長さ0の線分を点として表示する
+            line_cap: if pattern.contains(&0.0) {
+                LineCap::Round
+            } else {
+                LineCap::Butt
+            },
+            dash: StrokeDash::new(pattern.iter().map(|v| (v * scale) as f32).collect(), 0.0),
+            ..Default::default()
+        };
+        self.pixmap.stroke_path(
+            &path,
+            &paint(
+                self.doc
+                    .pen_rgb(base.pen_color, &self.options.style.palette),
+            ),
+            &stroke,
+            transform,
+            None,
+        );
+    }
+}
+
+fn paint(color: Rgb) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(color.r, color.g, color.b, 255);
+    paint.anti_alias = true;
+    paint
+}
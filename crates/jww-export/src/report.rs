@@ -0,0 +1,254 @@
+//! 図面レポート出力 (HTML/Markdown)
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use jww_core::{Document, Entity};
+use serde::{Deserialize, Serialize};
+
+use crate::format::{escape, num};
+use crate::svg::to_svg;
+use crate::view::layer_name;
+
+/// レポートの形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ReportFormat {
+    /// HTML (単独で表示できる文書)
+    #[default]
+    Html,
+    /// Markdown
+    Markdown,
+}
+
+/// レポート出力オプション
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportOptions {
+    /// レポートの形式
+    pub format: ReportFormat,
+    /// 見出し (`None` の場合は「図面レポート」)
+    pub title: Option<String>,
+    /// SVGのサムネイルを埋め込む
+    pub thumbnail: bool,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            format: ReportFormat::Html,
+            title: None,
+            thumbnail: true,
+        }
+    }
+}
+
+/// レポートの1つの表
+struct Table {
+    heading: &'static str,
+    columns: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+}
+
+/// JWWドキュメントの概要レポートを作成する
+///
+/// 図面情報 (バージョン・用紙・縮尺・範囲・メモ)、レイヤごとのエンティティ数、
+/// 文字の一覧と、SVGのサムネイル (データURIの画像) を出力する。
+pub fn to_report(doc: &Document, options: &ReportOptions) -> String {
+    let title = options.title.as_deref().unwrap_or("図面レポート");
+    let tables = [metadata_table(doc), layer_table(doc), text_table(doc)];
+    let thumbnail = options.thumbnail.then(|| svg_data_uri(&to_svg(doc)));
+    match options.format {
+        ReportFormat::Html => html(title, &tables, thumbnail.as_deref()),
+        ReportFormat::Markdown => markdown(title, &tables, thumbnail.as_deref()),
+    }
+}
+
+fn metadata_table(doc: &Document) -> Table {
+    let paper = doc
+        .paper_dimensions()
+        .map(|(w, h)| format!("{} ({}×{}mm)", paper_name(doc.paper_size), num(w), num(h)))
+        .unwrap_or_else(|| format!("不明 ({})", doc.paper_size));
+    let extents = doc
+        .bounding_box()
+        .map(|bb| {
+            format!(
+                "({}, {}) - ({}, {})",
+                num(bb.min_x),
+                num(bb.min_y),
+                num(bb.max_x),
+                num(bb.max_y)
+            )
+        })
+        .unwrap_or_else(|| "-".to_string());
+    let rows = [
+        ("バージョン", doc.version.to_string()),
+        ("用紙", paper),
+        ("縮尺", format!("1/{}", num(doc.drawing_scale()))),
+        ("エンティティ数", doc.entities.len().to_string()),
+        ("ブロック定義数", doc.block_defs.len().to_string()),
+        ("図面範囲", extents),
+        ("メモ", doc.memo.to_string()),
+    ];
+    Table {
+        heading: "図面情報",
+        columns: &["項目", "値"],
+        rows: rows
+            .into_iter()
+            .map(|(k, v)| vec![k.to_string(), v])
+            .collect(),
+    }
+}
+
+fn layer_table(doc: &Document) -> Table {
+    let mut counts: BTreeMap<(u16, u16), usize> = BTreeMap::new();
+    for entity in &doc.entities {
+        let base = entity.base();
+        *counts.entry((base.layer_group, base.layer)).or_default() += 1;
+    }
+    let rows = counts
+        .into_iter()
+        .map(|((group, layer), count)| {
+            vec![
+                format!("{:X}", group),
+                format!("{:X}", layer),
+                layer_name(doc, group, layer),
+                count.to_string(),
+            ]
+        })
+        .collect();
+    Table {
+        heading: "レイヤ",
+        columns: &["グループ", "レイヤ", "名前", "エンティティ数"],
+        rows,
+    }
+}
+
+fn text_table(doc: &Document) -> Table {
+    let rows = doc
+        .entities
+        .iter()
+        .filter_map(|entity| match entity {
+            Entity::Text(text) => Some(vec![
+                text.content.to_string(),
+                layer_name(doc, text.base.layer_group, text.base.layer),
+                format!("({}, {})", num(text.start.x), num(text.start.y)),
+                num(text.size_y),
+            ]),
+            _ => None,
+        })
+        .collect();
+    Table {
+        heading: "文字",
+        columns: &["文字列", "レイヤ", "位置", "高さ"],
+        rows,
+    }
+}
+
+/// 用紙サイズコードの名前を返す
+fn paper_name(paper_size: u32) -> &'static str {
+    match paper_size {
+        0 => "A0",
+        1 => "A1",
+        2 => "A2",
+        3 => "A3",
+        4 => "A4",
+        8 => "2A",
+        9 => "3A",
+        10 => "4A",
+        11 => "5A",
+        _ => "-",
+    }
+}
+
+fn html(title: &str, tables: &[Table], thumbnail: Option<&str>) -> String {
+    let mut out = String::new();
+    writeln!(out, "<!DOCTYPE html>").unwrap();
+    writeln!(out, r#"<html lang="ja">"#).unwrap();
+    writeln!(
+        out,
+        r#"<head><meta charset="utf-8"><title>{}</title></head>"#,
+        escape(title)
+    )
+    .unwrap();
+    writeln!(out, "<body>").unwrap();
+    writeln!(out, "<h1>{}</h1>", escape(title)).unwrap();
+    if let Some(uri) = thumbnail {
+        writeln!(
+            out,
+            r#"<p><img src="{}" alt="サムネイル" style="max-width: 100%; border: 1px solid #ccc"></p>"#,
+            uri
+        )
+        .unwrap();
+    }
+    for table in tables {
+        writeln!(out, "<h2>{}</h2>", table.heading).unwrap();
+        writeln!(out, "<table>").unwrap();
+        let header: String = table
+            .columns
+            .iter()
+            .map(|c| format!("<th>{}</th>", c))
+            .collect();
+        writeln!(out, "<tr>{}</tr>", header).unwrap();
+        for row in &table.rows {
+            let cells: String = row
+                .iter()
+                .map(|c| format!("<td>{}</td>", escape(c)))
+                .collect();
+            writeln!(out, "<tr>{}</tr>", cells).unwrap();
+        }
+        writeln!(out, "</table>").unwrap();
+    }
+    writeln!(out, "</body>").unwrap();
+    writeln!(out, "</html>").unwrap();
+    out
+}
+
+fn markdown(title: &str, tables: &[Table], thumbnail: Option<&str>) -> String {
+    let mut out = String::new();
+    writeln!(out, "# {}", title).unwrap();
+    if let Some(uri) = thumbnail {
+        writeln!(out, "\n![サムネイル]({})", uri).unwrap();
+    }
+    for table in tables {
+        writeln!(out, "\n## {}\n", table.heading).unwrap();
+        writeln!(out, "| {} |", table.columns.join(" | ")).unwrap();
+        writeln!(out, "|{}", "---|".repeat(table.columns.len())).unwrap();
+        for row in &table.rows {
+            let cells: Vec<String> = row.iter().map(|c| markdown_cell(c)).collect();
+            writeln!(out, "| {} |", cells.join(" | ")).unwrap();
+        }
+    }
+    out
+}
+
+/// Markdownの表のセルに入れられるよう `|`・改行・HTMLの特殊文字をエスケープする
+fn markdown_cell(s: &str) -> String {
+    escape(s)
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace(['\r', '\n'], "<br>")
+}
+
+/// SVGをパーセントエンコードしたデータURIに変換する
+fn svg_data_uri(svg: &str) -> String {
+    let mut uri = String::from("data:image/svg+xml;charset=utf-8,");
+    for &b in svg.as_bytes() {
+        match b {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'~'
+            | b'='
+            | b':'
+            | b'/'
+            | b','
+            | b';' => uri.push(b as char),
+            _ => write!(uri, "%{:02X}", b).unwrap(),
+        }
+    }
+    uri
+}
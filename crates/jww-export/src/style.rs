@@ -0,0 +1,89 @@
+//! 線色・線種の表示スタイル
+
+use jww_core::EntityBase;
+pub use jww_core::{pen_rgb, Palette, Rgb};
+use serde::{Deserialize, Serialize};
+
+/// 線色ごとの線幅と色 (各出力オプションに `#[serde(flatten)]` で含める)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PenStyle {
+    /// 線色1-9の線幅 (用紙上のmm)
+    ///
+    /// 線幅 (`EntityBase::pen_width`) が0のエンティティに使用する。JWWファイルの
+    /// 線幅設定は読み込んでいないため、Jw_cadの設定に合わせる場合は明示的に指定する。
+    pub pen_widths: [f64; 9],
+    /// 線幅の最小値 (用紙上のmm、細すぎる線が表示されなくなるのを防ぐ)
+    pub min_stroke_width: f64,
+    /// 線色1-9の色
+    ///
+    /// 既定値はJw_cadの画面表示色。印刷と同じ色にする場合は [`Palette::PRINTER`] を指定する。
+    pub palette: Palette,
+}
+
+impl Default for PenStyle {
+    fn default() -> Self {
+        Self {
+            pen_widths: [0.13, 0.18, 0.25, 0.35, 0.5, 0.7, 0.18, 0.25, 0.13],
+            min_stroke_width: 0.05,
+            palette: Palette::SCREEN,
+        }
+    }
+}
+
+impl PenStyle {
+    /// 用紙上の線幅 (mm) を返す
+    ///
+    /// 線幅が指定されている場合は1/100mm単位として扱い、それ以外は線色ごとの線幅 `pen_widths` を使用する。
+    pub(crate) fn paper_stroke_width(&self, base: &EntityBase) -> f64 {
+        let width = if base.pen_width > 0 {
+            base.pen_width as f64 / 100.0
+        } else {
+            match base.pen_color {
+                1..=9 => self.pen_widths[base.pen_color as usize - 1],
+                _ => self.pen_widths[0],
+            }
+        };
+        width.max(self.min_stroke_width)
+    }
+}
+
+/// 線種番号の破線パターンを返す (用紙上のmm)
+///
+/// 線分と空白の長さを交互に並べる。0は点を表す。実線の場合は空のスライスを返す。
+pub fn dash_pattern(pen_style: u8) -> &'static [f64] {
+    match pen_style {
+        2 => &[1.0, 1.0],                     // 点線1
+        3 => &[2.0, 1.0],                     // 点線2
+        4 => &[4.0, 1.0],                     // 点線3
+        5 => &[4.0, 1.0, 0.0, 1.0],           // 一点鎖1
+        6 => &[8.0, 1.0, 0.0, 1.0],           // 一点鎖2
+        7 => &[4.0, 1.0, 0.0, 1.0, 0.0, 1.0], // 二点鎖1
+        8 => &[8.0, 1.0, 0.0, 1.0, 0.0, 1.0], // 二点鎖2
+        9 => &[0.0, 1.0],                     // 補助線種
+        _ => &[],
+    }
+}
+
+/// JWWのフォント名をCSSのfont-familyに変換する
+///
+/// 縦書きを表す先頭の `@` は取り除き、フォント名は [`jww_core::normalize::font_name`] で
+/// 英語名にそろえる。ゴシック体・明朝体は環境に応じた代替フォントを後ろに並べ、
+/// それ以外のフォントは総称ファミリーのみ補う。
+pub fn font_family(font_name: &str) -> String {
+    let name = jww_core::normalize::font_name(font_name.trim_start_matches('@'));
+    let fallback = if name.contains("明朝") || name.contains("Mincho") {
+        "'Hiragino Mincho ProN', 'Yu Mincho', 'Noto Serif JP', serif"
+    } else {
+        "'Hiragino Kaku Gothic ProN', 'Yu Gothic', 'Noto Sans JP', sans-serif"
+    };
+    if name.is_empty() {
+        return fallback.to_string();
+    }
+    format!("'{}', {}", name.replace('\'', ""), fallback)
+}
+
+/// フォント名が縦書き (`@` で始まる) かどうかを返す
+pub fn is_vertical_font(font_name: &str) -> bool {
+    font_name.starts_with('@')
+}
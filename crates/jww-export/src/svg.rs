@@ -0,0 +1,438 @@
+//! SVG出力
+
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::fmt::Write;
+
+use jww_core::{normalize, Arc, Document, Entity, EntityBase, Line, Text};
+use serde::{Deserialize, Serialize};
+
+use crate::format::{escape, num};
+use crate::style::{dash_pattern, font_family, is_vertical_font, PenStyle};
+use crate::view::{group_scale, is_visible, paper_rect, view_rect, ViewBox};
+
+/// SVG出力オプション
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SvgOptions {
+    /// 線色ごとの線幅と色
+    #[serde(flatten)]
+    pub style: PenStyle,
+    /// JWWのフォント名 (`@` を除く) からCSSのfont-familyへの対応
+    ///
+    /// フォント名は全角・半角や日本語名・英語名の違いを区別せずに照合する
+    /// (`ＭＳ ゴシック` と `MS Gothic` は同じ)。指定のないフォントは [`font_family`] で変換する。
+    pub font_families: HashMap<String, String>,
+    /// viewBoxの決め方
+    pub view_box: ViewBox,
+    /// 用紙枠を描画する
+    pub draw_border: bool,
+    /// Y軸を反転する (JWWは上向き、SVGは下向き)
+    ///
+    /// `false` の場合はJWWの座標をそのまま出力するため、表示側で反転が必要になる。
+    pub flip_y: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            style: PenStyle::default(),
+            font_families: HashMap::new(),
+            view_box: ViewBox::Extents,
+            draw_border: false,
+            flip_y: true,
+        }
+    }
+}
+
+/// JWWドキュメントをSVG文字列に変換する
+pub fn to_svg(doc: &Document) -> String {
+    to_svg_with_options(doc, &SvgOptions::default())
+}
+
+/// オプションを指定してJWWドキュメントをSVG文字列に変換する
+///
+/// 座標はJWWのmmのまま出力し、既定ではY軸を反転する。
+/// `width`/`height` は用紙上の寸法 (mm) とする。
+/// 非表示レイヤ・非表示レイヤグループのエンティティと仮点は出力しない。
+/// ブロック定義は `<defs>` に出力し、ブロック挿入は `<use>` で参照する。
+pub fn to_svg_with_options(doc: &Document, options: &SvgOptions) -> String {
+    let mut writer = SvgWriter {
+        doc,
+        options,
+        out: String::new(),
+    };
+    writer.document();
+    writer.out
+}
+
+/// ブロック定義の要素ID
+fn block_id(def_number: u32) -> String {
+    format!("jww-block-{}", def_number)
+}
+
+struct SvgWriter<'a> {
+    doc: &'a Document<'a>,
+    options: &'a SvgOptions,
+    out: String,
+}
+
+impl SvgWriter<'_> {
+    fn document(&mut self) {
+        let scale = self.doc.drawing_scale();
+        let paper = paper_rect(self.doc);
+        // 線幅の分だけ余白を確保する
+        let margin = self
+            .options
+            .style
+            .pen_widths
+            .iter()
+            .fold(0.0_f64, |a, b| a.max(*b))
+            * scale;
+        let (min_x, min_y, width, height) = view_rect(
+            self.doc,
+            self.options.view_box,
+            self.options.draw_border,
+            margin,
+        );
+        let (min_x, min_y) = self.point(
+            min_x,
+            if self.options.flip_y {
+                min_y + height
+            } else {
+                min_y
+            },
+        );
+
+        writeln!(self.out, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+        writeln!(
+            self.out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}mm" height="{}mm">"#,
+            num(min_x),
+            num(min_y),
+            num(width),
+            num(height),
+            num(width / scale),
+            num(height / scale)
+        )
+        .unwrap();
+
+        let doc = self.doc;
+        if !doc.block_defs.is_empty() {
+            writeln!(self.out, "<defs>").unwrap();
+            for def in &doc.block_defs {
+                writeln!(self.out, r#"<g id="{}">"#, block_id(def.number)).unwrap();
+                for entity in &def.entities {
+                    self.entity(entity);
+                }
+                writeln!(self.out, "</g>").unwrap();
+            }
+            writeln!(self.out, "</defs>").unwrap();
+        }
+
+        writeln!(self.out, r#"<g fill="none">"#).unwrap();
+        if self.options.draw_border {
+            if let Some((x, y, w, h)) = paper {
+                let (x, y) = self.point(x, if self.options.flip_y { y + h } else { y });
+                writeln!(
+                    self.out,
+                    r##"<rect x="{}" y="{}" width="{}" height="{}" stroke="#808080" stroke-width="{}"/>"##,
+                    num(x),
+                    num(y),
+                    num(w),
+                    num(h),
+                    num(self.options.style.pen_widths[0] * scale)
+                )
+                .unwrap();
+            }
+        }
+        for entity in &doc.entities {
+            if is_visible(self.doc, entity.base()) {
+                self.entity(entity);
+            }
+        }
+        writeln!(self.out, "</g>").unwrap();
+        writeln!(self.out, "</svg>").unwrap();
+    }
+
+    /// 出力座標を返す (Y軸の反転を反映)
+    fn point(&self, x: f64, y: f64) -> (f64, f64) {
+        if self.options.flip_y {
+            (x, -y)
+        } else {
+            (x, y)
+        }
+    }
+
+    /// 出力する回転角 (度) を返す (Y軸を反転すると回転の向きが逆になる)
+    fn rotation(&self, degrees: f64) -> f64 {
+        if self.options.flip_y {
+            -degrees
+        } else {
+            degrees
+        }
+    }
+
+    /// レイヤグループの縮尺を返す
+    fn scale(&self, base: &EntityBase) -> f64 {
+        group_scale(self.doc, base)
+    }
+
+    /// 用紙上の線幅 (mm) を返す
+    fn paper_stroke_width(&self, base: &EntityBase) -> f64 {
+        self.options.style.paper_stroke_width(base)
+    }
+
+    /// 線の描画属性 (色・線幅・破線) を返す
+    fn stroke(&self, base: &EntityBase) -> String {
+        let scale = self.scale(base);
+        let mut attrs = format!(
+            r#"stroke="{}" stroke-width="{}""#,
+            self.doc
+                .pen_rgb(base.pen_color, &self.options.style.palette)
+                .to_hex(),
+            num(self.paper_stroke_width(base) * scale)
+        );
+        let pattern = dash_pattern(base.pen_style);
+        if !pattern.is_empty() {
+            let dashes: Vec<String> = pattern.iter().map(|v| num(v * scale)).collect();
+            write!(attrs, r#" stroke-dasharray="{}""#, dashes.join(",")).unwrap();
+            // 長さ0の線分を点として表示する
+            if pattern.contains(&0.0) {
+                attrs.push_str(r#" stroke-linecap="round""#);
+            }
+        }
+        attrs
+    }
+
+    fn entity(&mut self, entity: &Entity) {
+        match entity {
+            Entity::Line(line) => self.line(line),
+            Entity::Arc(arc) => self.arc(arc),
+            Entity::Point(point) => {
+                if point.is_temporary {
+                    return;
+                }
+                let (x, y) = self.point(point.position.x, point.position.y);
+                let radius = self.paper_stroke_width(&point.base) * self.scale(&point.base);
+                writeln!(
+                    self.out,
+                    r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+                    num(x),
+                    num(y),
+                    num(radius),
+                    entity
+                        .resolved_rgb_with(self.doc, &self.options.style.palette)
+                        .to_hex()
+                )
+                .unwrap();
+            }
+            Entity::Text(text) => self.text(text),
+            Entity::Solid(solid) => {
+                let color = entity.resolved_rgb_with(self.doc, &self.options.style.palette);
+                // DXFのSOLIDと同じく 1-2-4-3 の順に外周をたどる
+                let points: Vec<String> = [
+                    (solid.point1.x, solid.point1.y),
+                    (solid.point2.x, solid.point2.y),
+                    (solid.point4.x, solid.point4.y),
+                    (solid.point3.x, solid.point3.y),
+                ]
+                .iter()
+                .map(|&(x, y)| {
+                    let (x, y) = self.point(x, y);
+                    format!("{},{}", num(x), num(y))
+                })
+                .collect();
+                writeln!(
+                    self.out,
+                    r#"<polygon points="{}" fill="{}"/>"#,
+                    points.join(" "),
+                    color.to_hex()
+                )
+                .unwrap();
+            }
+            Entity::Block(block) => {
+                if !self
+                    .doc
+                    .block_defs
+                    .iter()
+                    .any(|bd| bd.number == block.def_number)
+                {
+                    return;
+                }
+                let (x, y) = self.point(block.reference.x, block.reference.y);
+                writeln!(
+                    self.out,
+                    r##"<use href="#{}" transform="translate({} {}) rotate({}) scale({} {})"/>"##,
+                    block_id(block.def_number),
+                    num(x),
+                    num(y),
+                    num(self.rotation(block.rotation.degrees())),
+                    num(block.scale_x),
+                    num(block.scale_y)
+                )
+                .unwrap();
+            }
+            Entity::Dimension(dim) => {
+                self.line(&dim.line);
+                for line in &dim.extension_lines {
+                    self.line(line);
+                }
+                self.text(&dim.text);
+            }
+            Entity::Unknown(_) => {}
+        }
+    }
+
+    fn line(&mut self, line: &Line) {
+        let (x1, y1) = self.point(line.start.x, line.start.y);
+        let (x2, y2) = self.point(line.end.x, line.end.y);
+        writeln!(
+            self.out,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" {}/>"#,
+            num(x1),
+            num(y1),
+            num(x2),
+            num(y2),
+            self.stroke(&line.base)
+        )
+        .unwrap();
+    }
+
+    fn arc(&mut self, arc: &Arc) {
+        let stroke = self.stroke(&arc.base);
+        let (cx, cy) = self.point(arc.center.x, arc.center.y);
+        let rx = arc.radius;
+        let ry = (arc.radius * arc.flatness).abs();
+        let rotation = self.rotation(arc.tilt_angle.degrees());
+
+        if arc.is_closed() {
+            if arc.is_ellipse() {
+                writeln!(
+                    self.out,
+                    r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}" transform="rotate({} {} {})" {}/>"#,
+                    num(cx),
+                    num(cy),
+                    num(rx),
+                    num(ry),
+                    num(rotation),
+                    num(cx),
+                    num(cy),
+                    stroke
+                )
+                .unwrap();
+            } else {
+                writeln!(
+                    self.out,
+                    r#"<circle cx="{}" cy="{}" r="{}" {}/>"#,
+                    num(cx),
+                    num(cy),
+                    num(rx),
+                    stroke
+                )
+                .unwrap();
+            }
+            return;
+        }
+
+        let (start, end) = arc.param_range();
+        let start_point = arc.point_at_param(start);
+        let end_point = arc.point_at_param(end);
+        let (sx, sy) = self.point(start_point.x, start_point.y);
+        let (ex, ey) = self.point(end_point.x, end_point.y);
+        let sweep = end - start;
+        let large_arc = (sweep.abs() > PI) as u8;
+        // Y軸を反転すると反時計回りの円弧は sweep-flag=0 となる
+        let counter_clockwise = (sweep > 0.0) == (arc.flatness >= 0.0);
+        let sweep_flag = (counter_clockwise != self.options.flip_y) as u8;
+        writeln!(
+            self.out,
+            r#"<path d="M{},{} A{},{} {} {} {} {},{}" {}/>"#,
+            num(sx),
+            num(sy),
+            num(rx),
+            num(ry),
+            num(rotation),
+            large_arc,
+            sweep_flag,
+            num(ex),
+            num(ey),
+            stroke
+        )
+        .unwrap();
+    }
+
+    /// 文字を出力する
+    ///
+    /// 始点を文字枠の左下、始点から終点までを文字列の長さとして扱う。
+    /// 縦書きフォント (`@` で始まる) は始点を列の左上として上から下へ並べる。
+    fn text(&mut self, text: &Text) {
+        let (x, y) = self.point(text.start.x, text.start.y);
+        let scale = self.scale(&text.base);
+        let name = text.font_name.trim_start_matches('@');
+        let families = &self.options.font_families;
+        let family = match families.get(name).or_else(|| {
+            let canonical = normalize::font_name(name);
+            families
+                .iter()
+                .find(|(key, _)| normalize::font_name(key) == canonical)
+                .map(|(_, family)| family)
+        }) {
+            Some(family) => family.clone(),
+            None => font_family(&text.font_name),
+        };
+        let vertical = is_vertical_font(&text.font_name);
+
+        let mut attrs = if vertical {
+            format!(
+                r#"x="{}" y="{}" style="writing-mode: vertical-rl" dominant-baseline="central""#,
+                num(x + text.size_x * scale / 2.0),
+                num(y)
+            )
+        } else {
+            format!(
+                r#"x="{}" y="{}" dominant-baseline="text-after-edge""#,
+                num(x),
+                num(y)
+            )
+        };
+        write!(
+            attrs,
+            r#" font-size="{}" font-family="{}" fill="{}" stroke="none""#,
+            num(text.size_y * scale),
+            escape(&family),
+            self.doc
+                .pen_rgb(text.base.pen_color, &self.options.style.palette)
+                .to_hex()
+        )
+        .unwrap();
+        // 文字種: 斜体 +10000、太字 +20000
+        let style_flags = text.text_type / 10000;
+        if style_flags & 1 != 0 {
+            attrs.push_str(r#" font-style="italic""#);
+        }
+        if style_flags & 2 != 0 {
+            attrs.push_str(r#" font-weight="bold""#);
+        }
+        let length = (text.end.x - text.start.x).hypot(text.end.y - text.start.y);
+        if length > 0.0 && !text.content.is_empty() {
+            write!(
+                attrs,
+                r#" textLength="{}" lengthAdjust="spacingAndGlyphs""#,
+                num(length)
+            )
+            .unwrap();
+        }
+        if text.angle.degrees() != 0.0 {
+            write!(
+                attrs,
+                r#" transform="rotate({} {} {})""#,
+                num(self.rotation(text.angle.degrees())),
+                num(x),
+                num(y)
+            )
+            .unwrap();
+        }
+        writeln!(self.out, "<text {}>{}</text>", attrs, escape(&text.content)).unwrap();
+    }
+}
@@ -0,0 +1,334 @@
+//! 数量拾い出し (レイヤ・線色ごとの線長・弧長・面積の集計)
+
+use std::collections::BTreeMap;
+
+use jww_core::{Document, Palette};
+use serde::{Deserialize, Serialize};
+
+use crate::affine::Affine;
+use crate::csv::{write_row, Delimiter};
+use crate::flatten::{flatten, Shape};
+use crate::format::num;
+use crate::view::layer_name;
+
+/// 集計の単位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TakeoffGroup {
+    /// レイヤごと
+    #[default]
+    Layer,
+    /// 線色ごと
+    PenColor,
+    /// レイヤと線色の組み合わせごと
+    LayerAndPenColor,
+}
+
+/// 長さの単位 (面積は対応する平方単位)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LengthUnit {
+    /// ミリメートル (JWWの座標単位)
+    #[default]
+    Millimeter,
+    /// メートル
+    Meter,
+}
+
+impl LengthUnit {
+    fn per_mm(self) -> f64 {
+        match self {
+            LengthUnit::Millimeter => 1.0,
+            LengthUnit::Meter => 0.001,
+        }
+    }
+
+    fn suffix(self) -> &'static str {
+        match self {
+            LengthUnit::Millimeter => "mm",
+            LengthUnit::Meter => "m",
+        }
+    }
+}
+
+/// 数量拾い出しオプション
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TakeoffOptions {
+    /// 集計の単位
+    pub group_by: TakeoffGroup,
+    /// 長さ・面積の単位
+    pub unit: LengthUnit,
+    /// 末尾に合計行を出力する
+    pub total: bool,
+    /// CSVの列の区切り文字
+    pub delimiter: Delimiter,
+    /// 楕円弧を折れ線で近似する際の弦の許容誤差 (mm)
+    pub curve_tolerance: f64,
+}
+
+impl Default for TakeoffOptions {
+    fn default() -> Self {
+        Self {
+            group_by: TakeoffGroup::Layer,
+            unit: LengthUnit::Millimeter,
+            total: true,
+            delimiter: Delimiter::Comma,
+            curve_tolerance: 0.01,
+        }
+    }
+}
+
+/// 集計結果の1行
+///
+/// 集計の単位に含まれない項目は `None` とする。長さ・面積はオプションの単位で表す。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TakeoffRow {
+    pub layer_group: Option<u16>,
+    pub layer: Option<u16>,
+    pub layer_name: Option<String>,
+    pub pen_color: Option<u16>,
+    /// 直線の本数
+    pub line_count: usize,
+    /// 直線の合計長さ
+    pub line_length: f64,
+    /// 円弧・円・楕円の数
+    pub arc_count: usize,
+    /// 円弧・円・楕円の合計弧長
+    pub arc_length: f64,
+    /// ソリッドの数
+    pub solid_count: usize,
+    /// ソリッドの合計面積
+    pub solid_area: f64,
+}
+
+impl TakeoffRow {
+    fn add(&mut self, other: &TakeoffRow) {
+        self.line_count += other.line_count;
+        self.line_length += other.line_length;
+        self.arc_count += other.arc_count;
+        self.arc_length += other.arc_length;
+        self.solid_count += other.solid_count;
+        self.solid_area += other.solid_area;
+    }
+}
+
+/// XLSX出力時のエラー型
+#[cfg(feature = "xlsx")]
+#[derive(Debug, thiserror::Error)]
+pub enum TakeoffError {
+    /// ブックの書き込みに失敗した
+    #[error("XLSX write error: {0}")]
+    Xlsx(String),
+}
+
+#[cfg(feature = "xlsx")]
+impl From<rust_xlsxwriter::XlsxError> for TakeoffError {
+    fn from(err: rust_xlsxwriter::XlsxError) -> Self {
+        TakeoffError::Xlsx(err.to_string())
+    }
+}
+
+/// 直線の長さ・円弧の弧長・ソリッドの面積を集計する
+///
+/// ブロック挿入は構成要素に分解して集計し、非表示レイヤ・寸法・文字・点は除く。
+/// 行はレイヤ (グループ・番号順)、線色の順に並べる。合計行は含まない。
+pub fn quantity_takeoff(doc: &Document, options: &TakeoffOptions) -> Vec<TakeoffRow> {
+    let mut rows: BTreeMap<RowKey, TakeoffRow> = BTreeMap::new();
+    let unit = options.unit.per_mm();
+    let tolerance = options.curve_tolerance.max(1e-6);
+    flatten(
+        doc,
+        &Affine::IDENTITY,
+        tolerance,
+        &Palette::SCREEN,
+        |item| {
+            if item.type_name == "DIMENSION" {
+                return;
+            }
+            let base = item.base;
+            let layer = match options.group_by {
+                TakeoffGroup::PenColor => None,
+                _ => Some((base.layer_group, base.layer)),
+            };
+            let pen_color = match options.group_by {
+                TakeoffGroup::Layer => None,
+                _ => Some(base.pen_color),
+            };
+            let row = rows
+                .entry((layer, pen_color))
+                .or_insert_with(|| TakeoffRow {
+                    layer_group: layer.map(|(group, _)| group),
+                    layer: layer.map(|(_, layer)| layer),
+                    layer_name: layer.map(|(group, layer)| layer_name(doc, group, layer)),
+                    pen_color,
+                    ..Default::default()
+                });
+            match item.shape {
+                Shape::Polyline { points, .. } => {
+                    if item.type_name == "LINE" {
+                        row.line_count += 1;
+                        row.line_length += polyline_length(&points) * unit;
+                    } else {
+                        // 真円は近似前の円弧から正確な弧長を求める
+                        let length = match (item.circular, points.first()) {
+                            (Some(arc), Some(&(x, y))) => {
+                                (x - arc.center_x).hypot(y - arc.center_y) * arc.sweep.abs()
+                            }
+                            _ => polyline_length(&points),
+                        };
+                        row.arc_count += 1;
+                        row.arc_length += length * unit;
+                    }
+                }
+                Shape::Fill { points, .. } => {
+                    row.solid_count += 1;
+                    row.solid_area += polygon_area(&points) * unit * unit;
+                }
+                Shape::Point { .. } | Shape::Text { .. } => {}
+            }
+        },
+    );
+    rows.into_values().collect()
+}
+
+/// 数量拾い出しの結果をCSV/TSVで出力する
+///
+/// 1行目は見出し行とし、集計の単位に応じたキー列 (レイヤ・線色) の後に
+/// 本数・長さ・面積の列を並べる。
+pub fn to_takeoff_csv(doc: &Document, options: &TakeoffOptions) -> String {
+    let delimiter = options.delimiter.as_char();
+    let mut out = String::new();
+    write_row(&mut out, columns(options), delimiter);
+    for row in table(doc, options) {
+        let cells = row.into_iter().map(|cell| match cell {
+            Cell::Text(text) => text,
+            Cell::Integer(value) => value.to_string(),
+            Cell::Number(value) => num(value),
+        });
+        write_row(&mut out, cells, delimiter);
+    }
+    out
+}
+
+/// 数量拾い出しの結果をExcelブック (XLSX) で出力する
+///
+/// 列の構成は [`to_takeoff_csv`] と同じ。見出し行は太字で固定し、
+/// 長さ・面積は小数点以下3桁で表示する。
+#[cfg(feature = "xlsx")]
+pub fn to_takeoff_xlsx(doc: &Document, options: &TakeoffOptions) -> Result<Vec<u8>, TakeoffError> {
+    use rust_xlsxwriter::{Format, Workbook};
+
+    let header = Format::new().set_bold();
+    let decimal = Format::new().set_num_format("0.000");
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    sheet.set_name("Takeoff")?;
+    for (col, title) in columns(options).into_iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, title, &header)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    for (row, cells) in table(doc, options).into_iter().enumerate() {
+        let row = row as u32 + 1;
+        for (col, cell) in cells.into_iter().enumerate() {
+            let col = col as u16;
+            match cell {
+                Cell::Text(text) => sheet.write_string(row, col, text)?,
+                Cell::Integer(value) => sheet.write_number(row, col, value as f64)?,
+                Cell::Number(value) => sheet.write_number_with_format(row, col, value, &decimal)?,
+            };
+        }
+    }
+    Ok(workbook.save_to_buffer()?)
+}
+
+/// 集計のキー ((レイヤグループ, レイヤ), 線色)
+type RowKey = (Option<(u16, u16)>, Option<u16>);
+
+/// 表のセル
+enum Cell {
+    Text(String),
+    Integer(u64),
+    Number(f64),
+}
+
+/// 見出し行
+fn columns(options: &TakeoffOptions) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    if options.group_by != TakeoffGroup::PenColor {
+        columns.extend(["layer_group", "layer", "layer_name"].map(String::from));
+    }
+    if options.group_by != TakeoffGroup::Layer {
+        columns.push("pen_color".to_string());
+    }
+    let unit = options.unit.suffix();
+    columns.extend([
+        "line_count".to_string(),
+        format!("line_length_{}", unit),
+        "arc_count".to_string(),
+        format!("arc_length_{}", unit),
+        "solid_count".to_string(),
+        format!("solid_area_{}2", unit),
+    ]);
+    columns
+}
+
+/// 見出し行を除く表の内容 (合計行を含む)
+fn table(doc: &Document, options: &TakeoffOptions) -> Vec<Vec<Cell>> {
+    let rows = quantity_takeoff(doc, options);
+    let mut total = TakeoffRow::default();
+    let mut table = Vec::with_capacity(rows.len() + 1);
+    for row in &rows {
+        total.add(row);
+        let mut cells = Vec::new();
+        if options.group_by != TakeoffGroup::PenColor {
+            cells.push(Cell::Integer(row.layer_group.unwrap_or(0).into()));
+            cells.push(Cell::Integer(row.layer.unwrap_or(0).into()));
+            cells.push(Cell::Text(row.layer_name.clone().unwrap_or_default()));
+        }
+        if options.group_by != TakeoffGroup::Layer {
+            cells.push(Cell::Integer(row.pen_color.unwrap_or(0).into()));
+        }
+        cells.extend(quantities(row));
+        table.push(cells);
+    }
+    if options.total {
+        // キー列は先頭に "total" と書き、残りは空欄とする
+        let keys = columns(options).len() - 6;
+        let mut cells: Vec<Cell> = (0..keys).map(|_| Cell::Text(String::new())).collect();
+        cells[0] = Cell::Text("total".to_string());
+        cells.extend(quantities(&total));
+        table.push(cells);
+    }
+    table
+}
+
+fn quantities(row: &TakeoffRow) -> [Cell; 6] {
+    [
+        Cell::Integer(row.line_count as u64),
+        Cell::Number(row.line_length),
+        Cell::Integer(row.arc_count as u64),
+        Cell::Number(row.arc_length),
+        Cell::Integer(row.solid_count as u64),
+        Cell::Number(row.solid_area),
+    ]
+}
+
+/// 折れ線の長さ
+fn polyline_length(points: &[(f64, f64)]) -> f64 {
+    points
+        .windows(2)
+        .map(|w| (w[1].0 - w[0].0).hypot(w[1].1 - w[0].1))
+        .sum()
+}
+
+/// 多角形の面積 (靴ひも公式、向きによらず正の値)
+fn polygon_area(points: &[(f64, f64)]) -> f64 {
+    let n = points.len();
+    let twice: f64 = (0..n)
+        .map(|i| {
+            let (x1, y1) = points[i];
+            let (x2, y2) = points[(i + 1) % n];
+            x1 * y2 - x2 * y1
+        })
+        .sum();
+    twice.abs() / 2.0
+}
@@ -0,0 +1,84 @@
+//! 出力範囲と表示状態
+
+use jww_core::{Document, EntityBase, Point2D};
+use serde::{Deserialize, Serialize};
+
+/// 出力範囲の決め方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ViewBox {
+    /// 図面の範囲 (線幅分の余白を含む)
+    #[default]
+    Extents,
+    /// 用紙の範囲 (用紙サイズが不明な場合は図面の範囲)
+    Paper,
+}
+
+/// 図面座標の矩形 (最小X, 最小Y, 幅, 高さ)
+pub(crate) type Rect = (f64, f64, f64, f64);
+
+/// 用紙の範囲を返す (原点が用紙中心)
+pub(crate) fn paper_rect(doc: &Document) -> Option<Rect> {
+    let scale = doc.drawing_scale();
+    let (w, h) = doc.paper_dimensions()?;
+    Some((-w * scale / 2.0, -h * scale / 2.0, w * scale, h * scale))
+}
+
+/// 出力範囲を返す
+///
+/// `include_paper` が真の場合、図面の範囲に用紙枠を含める。
+/// `margin` は図面の範囲に加える余白 (図面上の長さ)。
+pub(crate) fn view_rect(
+    doc: &Document,
+    view_box: ViewBox,
+    include_paper: bool,
+    margin: f64,
+) -> Rect {
+    let scale = doc.drawing_scale();
+    let paper = paper_rect(doc);
+    let extents = doc
+        .bounding_box()
+        .filter(|bb| bb.width() > 0.0 || bb.height() > 0.0);
+    match (view_box, paper, extents) {
+        (ViewBox::Paper, Some(paper), _) | (_, Some(paper), None) => paper,
+        (_, _, Some(mut bb)) => {
+            if include_paper {
+                if let Some((x, y, w, h)) = paper {
+                    bb.include_point(Point2D::new(x, y));
+                    bb.include_point(Point2D::new(x + w, y + h));
+                }
+            }
+            (
+                bb.min_x - margin,
+                bb.min_y - margin,
+                bb.width() + 2.0 * margin,
+                bb.height() + 2.0 * margin,
+            )
+        }
+        // 用紙サイズが不明で図面も空の場合はA3とする
+        _ => (-210.0 * scale, -148.5 * scale, 420.0 * scale, 297.0 * scale),
+    }
+}
+
+/// レイヤとレイヤグループが表示状態かどうかを返す
+pub(crate) fn is_visible(doc: &Document, base: &EntityBase) -> bool {
+    let Some(group) = doc.layer_groups.get(base.layer_group as usize) else {
+        return true;
+    };
+    let layer_state = group.layers.get(base.layer as usize).map_or(1, |l| l.state);
+    group.state != 0 && layer_state != 0
+}
+
+/// レイヤグループの縮尺 (用紙上の1mmに対する図面上の長さ) を返す
+pub(crate) fn group_scale(doc: &Document, base: &EntityBase) -> f64 {
+    doc.layer_group_scale(base.layer_group)
+}
+
+/// レイヤ名を返す (名前がない場合は `グループ-レイヤ` の16進表記)
+pub(crate) fn layer_name(doc: &Document, layer_group: u16, layer: u16) -> String {
+    doc.layer_groups
+        .get(layer_group as usize)
+        .and_then(|lg| lg.layers.get(layer as usize))
+        .filter(|l| !l.name.is_empty())
+        .map(|l| l.name.clone())
+        .unwrap_or_else(|| format!("{:X}-{:X}", layer_group, layer))
+}
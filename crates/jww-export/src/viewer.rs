@@ -0,0 +1,231 @@
+//! Webビューア向けJSON出力
+//!
+//! 線色・線幅・線種を解決し、曲線とブロックを展開した描画用プリミティブを出力する。
+//! ビューア側でJWWの仕様 (線色番号・縮尺・ブロック定義) を扱う必要がない。
+
+use std::collections::HashMap;
+
+use jww_core::Document;
+use serde::{Deserialize, Serialize};
+
+use crate::affine::Affine;
+use crate::flatten::{flatten, Shape};
+use crate::format::round;
+use crate::style::{dash_pattern, font_family, is_vertical_font, PenStyle};
+use crate::view::{group_scale, layer_name};
+
+/// Webビューア向けJSONの出力オプション
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewerOptions {
+    /// 円弧を折れ線に近似する際の弦の許容誤差 (JWW座標のmm)
+    pub curve_tolerance: f64,
+    /// 線色ごとの線幅と色 ([`SvgOptions::style`](crate::SvgOptions::style) と同じ)
+    #[serde(flatten)]
+    pub style: PenStyle,
+}
+
+impl Default for ViewerOptions {
+    fn default() -> Self {
+        Self {
+            curve_tolerance: 0.5,
+            style: PenStyle::default(),
+        }
+    }
+}
+
+/// Webビューア向けの描画用ドキュメント
+///
+/// 座標・線幅・文字高さはJWW座標 (mm、Y軸上向き) で、小数点以下3桁に丸める。
+/// 座標列は `[x0, y0, x1, y1, ...]` の平坦な配列とする。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ViewerDocument {
+    /// 図形の範囲 `[最小X, 最小Y, 最大X, 最大Y]` (図形がない場合は0)
+    pub bounds: [f64; 4],
+    /// 図形が参照するレイヤ
+    pub layers: Vec<ViewerLayer>,
+    pub polylines: Vec<ViewerPolyline>,
+    pub fills: Vec<ViewerFill>,
+    pub points: Vec<ViewerPoint>,
+    pub texts: Vec<ViewerText>,
+}
+
+/// レイヤ
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ViewerLayer {
+    /// レイヤ名 (名前がない場合は `グループ-レイヤ` の16進表記)
+    pub name: String,
+    pub group: u16,
+    pub layer: u16,
+}
+
+/// 折れ線
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ViewerPolyline {
+    /// [`ViewerDocument::layers`] のインデックス
+    pub layer: usize,
+    /// 表示色 (`#rrggbb`)
+    pub color: String,
+    /// 線幅
+    pub width: f64,
+    /// 破線パターン (線分と空白の長さ、実線の場合は省略)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dash: Vec<f64>,
+    /// 閉じた折れ線 (最後の点は最初の点と一致する)
+    pub closed: bool,
+    pub points: Vec<f64>,
+}
+
+/// 塗りつぶし多角形
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ViewerFill {
+    pub layer: usize,
+    pub color: String,
+    pub points: Vec<f64>,
+}
+
+/// 点
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ViewerPoint {
+    pub layer: usize,
+    pub color: String,
+    pub x: f64,
+    pub y: f64,
+    /// 表示半径
+    pub radius: f64,
+}
+
+/// 文字
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ViewerText {
+    pub layer: usize,
+    pub color: String,
+    /// 文字枠の左下 (縦書きの場合は左上)
+    pub x: f64,
+    pub y: f64,
+    /// 文字の高さ
+    pub height: f64,
+    /// 文字列の長さ (始点から終点まで)
+    pub length: f64,
+    /// 回転角 (度、反時計回り)
+    pub angle: f64,
+    /// CSSのfont-family
+    pub font: String,
+    /// 縦書き
+    pub vertical: bool,
+    pub text: String,
+}
+
+/// JWWドキュメントをWebビューア向けの描画用ドキュメントに変換する
+///
+/// 非表示レイヤのエンティティと仮点は出力しない。
+pub fn to_viewer_document(doc: &Document, options: &ViewerOptions) -> ViewerDocument {
+    let mut out = ViewerDocument::default();
+    let mut layer_indices: HashMap<(u16, u16), usize> = HashMap::new();
+    let mut bounds: Option<[f64; 4]> = None;
+    let mut include = |x: f64, y: f64| {
+        let b = bounds.get_or_insert([x, y, x, y]);
+        b[0] = b[0].min(x);
+        b[1] = b[1].min(y);
+        b[2] = b[2].max(x);
+        b[3] = b[3].max(y);
+    };
+
+    flatten(
+        doc,
+        &Affine::IDENTITY,
+        options.curve_tolerance,
+        &options.style.palette,
+        |item| {
+            let base = item.base;
+            let layer = *layer_indices
+                .entry((base.layer_group, base.layer))
+                .or_insert_with(|| {
+                    out.layers.push(ViewerLayer {
+                        name: layer_name(doc, base.layer_group, base.layer),
+                        group: base.layer_group,
+                        layer: base.layer,
+                    });
+                    out.layers.len() - 1
+                });
+            let scale = group_scale(doc, base);
+            let width = options.style.paper_stroke_width(base) * scale;
+            let color = item.color.to_hex();
+            let mut flat = |points: &[(f64, f64)]| -> Vec<f64> {
+                points
+                    .iter()
+                    .flat_map(|&(x, y)| {
+                        include(x, y);
+                        [round(x), round(y)]
+                    })
+                    .collect()
+            };
+
+            match item.shape {
+                Shape::Polyline { points, closed } => out.polylines.push(ViewerPolyline {
+                    layer,
+                    color,
+                    width: round(width),
+                    dash: dash_pattern(base.pen_style)
+                        .iter()
+                        .map(|v| round(v * scale))
+                        .collect(),
+                    closed,
+                    points: flat(&points),
+                }),
+                Shape::Fill { points } => out.fills.push(ViewerFill {
+                    layer,
+                    color,
+                    points: flat(&points),
+                }),
+                Shape::Point { x, y } => {
+                    flat(&[(x, y)]);
+                    out.points.push(ViewerPoint {
+                        layer,
+                        color,
+                        x: round(x),
+                        y: round(y),
+                        radius: round(width),
+                    })
+                }
+                Shape::Text {
+                    x,
+                    y,
+                    height,
+                    angle,
+                    text,
+                } => {
+                    flat(&[(x, y)]);
+                    let ratio = if text.size_y != 0.0 {
+                        height / text.size_y
+                    } else {
+                        1.0
+                    };
+                    let length =
+                        (text.end.x - text.start.x).hypot(text.end.y - text.start.y) * ratio;
+                    out.texts.push(ViewerText {
+                        layer,
+                        color,
+                        x: round(x),
+                        y: round(y),
+                        height: round(height * scale),
+                        length: round(length),
+                        angle: round(angle),
+                        font: font_family(&text.font_name),
+                        vertical: is_vertical_font(&text.font_name),
+                        text: text.content.to_string(),
+                    })
+                }
+            }
+        },
+    );
+
+    out.bounds = bounds.map(|b| b.map(round)).unwrap_or_default();
+    out
+}
+
+/// JWWドキュメントをWebビューア向けのJSON文字列に変換する
+pub fn to_viewer_json(doc: &Document, options: &ViewerOptions) -> String {
+    serde_json::to_string(&to_viewer_document(doc, options))
+        .expect("viewer document is serializable")
+}
@@ -0,0 +1,80 @@
+//! CSV/TSV出力のテスト
+
+use jww_core::{Angle, Arc, Document, Entity, EntityBase, Line, Point2D, Text};
+use jww_export::{to_csv, CsvOptions, Delimiter};
+
+fn base() -> EntityBase {
+    EntityBase {
+        pen_color: 2,
+        layer: 3,
+        ..Default::default()
+    }
+}
+
+fn document() -> Document<'static> {
+    let mut doc = Document {
+        entities: vec![
+            Entity::Line(Line {
+                base: base(),
+                start: Point2D::new(0.0, 0.0),
+                end: Point2D::new(3.0, 4.0),
+            }),
+            Entity::Arc(Arc {
+                base: base(),
+                center: Point2D::new(10.0, 0.0),
+                radius: 2.0,
+                start_angle: Angle::ZERO,
+                arc_angle: Angle::ZERO,
+                tilt_angle: Angle::ZERO,
+                flatness: 1.0,
+                is_full_circle: true,
+            }),
+            Entity::Text(Text {
+                base: base(),
+                start: Point2D::new(1.0, 2.0),
+                end: Point2D::new(7.0, 2.0),
+                text_type: 0,
+                size_x: 3.0,
+                size_y: 3.0,
+                spacing: 0.0,
+                angle: Angle::ZERO,
+                font_name: "ＭＳ ゴシック".into(),
+                content: "壁, \"A\"".into(),
+            }),
+        ],
+        ..Default::default()
+    };
+    doc.layer_groups[0].layers[3].name = "躯体".to_string();
+    doc
+}
+
+#[test]
+fn test_csv_rows() {
+    let csv = to_csv(&document(), &CsvOptions::default());
+    let lines: Vec<&str> = csv.split("\r\n").collect();
+
+    assert_eq!(
+        lines[0],
+        "type,layer,layer_group,layer_index,pen_color,pen_style,pen_width,x1,y1,x2,y2,cx,cy,radius,angle,length,text"
+    );
+    assert_eq!(lines[1], "LINE,躯体,0,3,2,1,0,0,0,3,4,,,,,5,");
+    assert_eq!(
+        lines[2],
+        "CIRCLE,躯体,0,3,2,1,0,12,0,12,0,10,0,2,360,12.566,"
+    );
+    assert!(lines[3].ends_with(",\"壁, \"\"A\"\"\""));
+    assert_eq!(lines.len(), 5);
+}
+
+#[test]
+fn test_tsv_without_header() {
+    let options = CsvOptions {
+        delimiter: Delimiter::Tab,
+        header: false,
+    };
+    let tsv = to_csv(&document(), &options);
+
+    assert!(tsv.starts_with("LINE\t躯体\t"));
+    assert!(tsv.contains("\t5\t\r\n"));
+    assert!(tsv.ends_with("\t\"壁, \"\"A\"\"\"\r\n"));
+}
@@ -0,0 +1,171 @@
+//! EMF出力のテスト
+
+use jww_core::{Angle, Document, Entity, EntityBase, Line, Point2D, Text};
+use jww_export::{to_emf, EmfOptions};
+
+fn base(pen_style: u8) -> EntityBase {
+    EntityBase {
+        pen_style,
+        pen_color: 8,
+        ..Default::default()
+    }
+}
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn i32_at(data: &[u8], offset: usize) -> i32 {
+    i32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+/// レコードを (種別, 開始位置) の一覧に分解する
+fn records(data: &[u8]) -> Vec<(u32, usize)> {
+    let mut records = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let size = u32_at(data, offset + 4) as usize;
+        assert!(size >= 8 && size.is_multiple_of(4));
+        records.push((u32_at(data, offset), offset));
+        offset += size;
+    }
+    assert_eq!(offset, data.len());
+    records
+}
+
+fn document() -> Document<'static> {
+    let mut doc = Document {
+        entities: vec![
+            Entity::Line(Line {
+                base: base(1),
+                start: Point2D::new(0.0, 0.0),
+                end: Point2D::new(100.0, 50.0),
+            }),
+            Entity::Line(Line {
+                base: base(2),
+                start: Point2D::new(0.0, 50.0),
+                end: Point2D::new(100.0, 50.0),
+            }),
+            Entity::Text(Text {
+                base: base(1),
+                start: Point2D::new(10.0, 10.0),
+                end: Point2D::new(16.0, 10.0),
+                text_type: 0,
+                size_x: 3.0,
+                size_y: 3.0,
+                spacing: 0.0,
+                angle: Angle::ZERO,
+                font_name: "ＭＳ ゴシック".into(),
+                content: "寸法".into(),
+            }),
+        ],
+        ..Default::default()
+    };
+    doc.layer_groups[0].scale = 2.0;
+    doc
+}
+
+#[test]
+fn test_emf_header() {
+    let emf = to_emf(&document(), &EmfOptions::default());
+    let records = records(&emf);
+
+    assert_eq!(records[0].0, 1);
+    assert_eq!(u32_at(&emf, 40), 0x464D_4520);
+    assert_eq!(u32_at(&emf, 48) as usize, emf.len());
+    assert_eq!(u32_at(&emf, 52) as usize, records.len());
+    // 縮尺1/2: 図面の100mm×50mmは用紙上の50mm×25mm (0.01mm単位)
+    assert_eq!((i32_at(&emf, 32), i32_at(&emf, 36)), (5000, 2500));
+    assert_eq!(records.last().unwrap().0, 14);
+}
+
+#[test]
+fn test_emf_polyline_and_pens() {
+    let emf = to_emf(&document(), &EmfOptions::default());
+    let records = records(&emf);
+
+    // Y軸は下向き
+    let polylines: Vec<usize> = records.iter().filter(|r| r.0 == 4).map(|r| r.1).collect();
+    assert_eq!(polylines.len(), 2);
+    let first = polylines[0];
+    assert_eq!(u32_at(&emf, first + 24), 2);
+    assert_eq!(
+        (0..4)
+            .map(|i| i32_at(&emf, first + 28 + i * 4))
+            .collect::<Vec<_>>(),
+        vec![0, 2500, 5000, 0]
+    );
+
+    // 実線と点線のペン
+    let pens: Vec<usize> = records.iter().filter(|r| r.0 == 95).map(|r| r.1).collect();
+    assert_eq!(pens.len(), 2);
+    assert_eq!(u32_at(&emf, pens[1] + 28) & 0xF, 7);
+    assert_eq!(u32_at(&emf, pens[1] + 48), 2);
+
+    // 文字列はUTF-16で格納する
+    let (_, text) = records.iter().find(|r| r.0 == 84).copied().unwrap();
+    assert_eq!(u32_at(&emf, text + 44), 2);
+    let offset = text + u32_at(&emf, text + 48) as usize;
+    let chars: Vec<u16> = (0..2)
+        .map(|i| u16::from_le_bytes([emf[offset + i * 2], emf[offset + i * 2 + 1]]))
+        .collect();
+    assert_eq!(String::from_utf16(&chars).unwrap(), "寸法");
+}
+
+fn text(size: f64, x: f64) -> Entity<'static> {
+    Entity::Text(Text {
+        base: base(1),
+        start: Point2D::new(x, 0.0),
+        end: Point2D::new(x + 6.0, 0.0),
+        text_type: 0,
+        size_x: size,
+        size_y: size,
+        spacing: 0.0,
+        angle: Angle::ZERO,
+        font_name: "ＭＳ ゴシック".into(),
+        content: "A".into(),
+    })
+}
+
+#[test]
+fn test_emf_font_reuse() {
+    let doc = Document {
+        entities: vec![text(3.0, 0.0), text(3.0, 10.0), text(5.0, 20.0)],
+        ..Default::default()
+    };
+    let emf = to_emf(&doc, &EmfOptions::default());
+    let records = records(&emf);
+
+    // 同じ文字高さ・角度・フォントのフォントは使い回す
+    let fonts = records.iter().filter(|r| r.0 == 82).count();
+    assert_eq!(fonts, 2);
+    assert_eq!(records.iter().filter(|r| r.0 == 84).count(), 3);
+    let objects = records
+        .iter()
+        .filter(|r| matches!(r.0, 39 | 82 | 95))
+        .count();
+    assert_eq!(u16::from_le_bytes([emf[56], emf[57]]) as usize, objects + 1);
+}
+
+#[test]
+fn test_emf_handle_table_limit() {
+    // 文字高さの異なる文字がハンドル数の上限を超える
+    let doc = Document {
+        entities: (0..70_000)
+            .map(|i| text(1.0 + i as f64 * 0.01, 0.0))
+            .collect(),
+        ..Default::default()
+    };
+    let emf = to_emf(&doc, &EmfOptions::default());
+    let records = records(&emf);
+
+    assert!(records.iter().any(|r| r.0 == 40));
+    assert_eq!(records.iter().filter(|r| r.0 == 84).count(), 70_000);
+    // 選択するハンドルはヘッダーのハンドル数に収まる
+    let handles = u32::from(u16::from_le_bytes([emf[56], emf[57]]));
+    assert!(records
+        .iter()
+        .filter(|r| r.0 == 37)
+        .map(|r| u32_at(&emf, r.1 + 8))
+        .all(|h| h & 0x8000_0000 != 0 || h < handles));
+}
@@ -0,0 +1,126 @@
+//! GeoJSON出力のテスト
+
+use std::f64::consts::FRAC_PI_2;
+
+use jww_core::{Angle, Arc, Block, BlockDef, Document, Entity, EntityBase, Line, Point2D};
+use jww_export::{to_geojson, to_geojson_with_transform, Affine, GeoJsonOptions};
+use serde_json::Value;
+
+fn base() -> EntityBase {
+    EntityBase {
+        pen_color: 8,
+        layer: 2,
+        layer_group: 1,
+        ..Default::default()
+    }
+}
+
+fn line(start_x: f64, start_y: f64, end_x: f64, end_y: f64) -> Entity<'static> {
+    Entity::Line(Line {
+        base: base(),
+        start: Point2D::new(start_x, start_y),
+        end: Point2D::new(end_x, end_y),
+    })
+}
+
+fn parse(doc: &Document, options: &GeoJsonOptions) -> Value {
+    serde_json::from_str(&to_geojson(doc, options)).unwrap()
+}
+
+#[test]
+fn test_line_feature() {
+    let doc = Document {
+        entities: vec![line(0.0, 0.0, 10.0, 5.0)],
+        ..Default::default()
+    };
+    let json = parse(&doc, &GeoJsonOptions::default());
+
+    assert_eq!(json["type"], "FeatureCollection");
+    let feature = &json["features"][0];
+    assert_eq!(feature["geometry"]["type"], "LineString");
+    assert_eq!(
+        feature["geometry"]["coordinates"],
+        serde_json::json!([[0.0, 0.0], [10.0, 5.0]])
+    );
+    assert_eq!(feature["properties"]["type"], "LINE");
+    assert_eq!(feature["properties"]["layer"], "1-2");
+    assert_eq!(feature["properties"]["color"], "#ff0000");
+}
+
+#[test]
+fn test_circle_is_polygon() {
+    let doc = Document {
+        entities: vec![Entity::Arc(Arc {
+            base: base(),
+            center: Point2D::new(0.0, 0.0),
+            radius: 10.0,
+            start_angle: Angle::ZERO,
+            arc_angle: Angle::ZERO,
+            tilt_angle: Angle::ZERO,
+            flatness: 1.0,
+            is_full_circle: true,
+        })],
+        ..Default::default()
+    };
+    let json = parse(&doc, &GeoJsonOptions::default());
+    let geometry = &json["features"][0]["geometry"];
+
+    assert_eq!(geometry["type"], "Polygon");
+    let ring = geometry["coordinates"][0].as_array().unwrap();
+    assert!(ring.len() > 4);
+    assert_eq!(ring.first(), ring.last());
+}
+
+#[test]
+fn test_affine_and_callback_transform() {
+    let doc = Document {
+        entities: vec![line(0.0, 0.0, 1000.0, 0.0)],
+        ..Default::default()
+    };
+    // 1mm = 0.001m、90度回転して (100, 200) に配置
+    let options = GeoJsonOptions {
+        transform: Some(Affine::from_scale_rotate_translate(
+            0.001, FRAC_PI_2, 100.0, 200.0,
+        )),
+        ..Default::default()
+    };
+    let json: Value = serde_json::from_str(&to_geojson_with_transform(&doc, &options, |x, y| {
+        (x.round(), y.round() + 1.0)
+    }))
+    .unwrap();
+
+    assert_eq!(
+        json["features"][0]["geometry"]["coordinates"],
+        serde_json::json!([[100.0, 201.0], [100.0, 202.0]])
+    );
+}
+
+#[test]
+fn test_block_is_exploded() {
+    let doc = Document {
+        entities: vec![Entity::Block(Block {
+            base: base(),
+            reference: Point2D::new(10.0, 0.0),
+            scale_x: 2.0,
+            scale_y: 2.0,
+            rotation: Angle::ZERO,
+            def_number: 1,
+        })],
+        block_defs: vec![BlockDef {
+            base: base(),
+            number: 1,
+            is_referenced: true,
+            name: "部品".to_string(),
+            entities: vec![line(0.0, 0.0, 1.0, 0.0)],
+        }],
+        ..Default::default()
+    };
+    let json = parse(&doc, &GeoJsonOptions::default());
+    let feature = &json["features"][0];
+
+    assert_eq!(
+        feature["geometry"]["coordinates"],
+        serde_json::json!([[10.0, 0.0], [12.0, 0.0]])
+    );
+    assert_eq!(feature["properties"]["block"], "部品");
+}
@@ -0,0 +1,84 @@
+//! GPUバッファ出力のテスト
+
+use jww_core::{Angle, Arc, Document, Entity, EntityBase, Line, Point2D, Solid};
+use jww_export::{to_gpu_buffers, GpuOptions};
+
+fn base(pen_color: u16, pen_width: u16) -> EntityBase {
+    EntityBase {
+        pen_color,
+        pen_width,
+        ..Default::default()
+    }
+}
+
+fn line(base: EntityBase, start_x: f64, start_y: f64, end_x: f64, end_y: f64) -> Entity<'static> {
+    Entity::Line(Line {
+        base,
+        start: Point2D::new(start_x, start_y),
+        end: Point2D::new(end_x, end_y),
+    })
+}
+
+#[test]
+fn test_lines_grouped_by_color_and_width() {
+    let doc = Document {
+        entities: vec![
+            line(base(8, 0), 0.0, 0.0, 10.0, 0.0),
+            line(base(6, 0), 0.0, 10.0, 10.0, 10.0),
+            line(base(8, 0), 0.0, 20.0, 10.0, 20.0),
+            line(base(8, 50), 0.0, 30.0, 10.0, 30.0),
+        ],
+        ..Default::default()
+    };
+    let buffers = to_gpu_buffers(&doc, &GpuOptions::default());
+
+    assert_eq!(buffers.origin, [5.0, 15.0]);
+    assert_eq!(buffers.lines.len(), 3);
+    let red = &buffers.lines[0];
+    assert_eq!(red.color, [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(red.width, 0.25);
+    assert_eq!(
+        red.vertices,
+        vec![-5.0, -15.0, 5.0, -15.0, -5.0, 5.0, 5.0, 5.0]
+    );
+    assert_eq!(red.indices, vec![0, 1, 2, 3]);
+    assert_eq!(buffers.lines[2].width, 0.5);
+}
+
+#[test]
+fn test_arc_and_solid_buffers() {
+    let doc = Document {
+        entities: vec![
+            Entity::Arc(Arc {
+                base: base(2, 0),
+                center: Point2D::new(0.0, 0.0),
+                radius: 10.0,
+                start_angle: Angle::ZERO,
+                arc_angle: Angle::from_radians(std::f64::consts::PI),
+                tilt_angle: Angle::ZERO,
+                flatness: 1.0,
+                is_full_circle: false,
+            }),
+            Entity::Solid(Solid {
+                base: base(2, 0),
+                point1: Point2D::new(0.0, 0.0),
+                point2: Point2D::new(1.0, 0.0),
+                point3: Point2D::new(0.0, 1.0),
+                point4: Point2D::new(1.0, 1.0),
+                color: 0,
+            }),
+        ],
+        ..Default::default()
+    };
+    let buffers = to_gpu_buffers(&doc, &GpuOptions::default());
+
+    let arc = &buffers.lines[0];
+    let vertex_count = arc.vertices.len() / 2;
+    assert!(vertex_count > 2);
+    assert_eq!(arc.indices.len(), (vertex_count - 1) * 2);
+    assert!(arc.indices.iter().all(|&i| (i as usize) < vertex_count));
+
+    let fill = &buffers.fills[0];
+    assert_eq!(fill.vertices.len(), 8);
+    assert_eq!(fill.indices, vec![0, 1, 2, 0, 2, 3]);
+}
@@ -0,0 +1,62 @@
+//! HP-GL/2出力のテスト
+
+use jww_core::{Angle, Arc, Document, Entity, EntityBase, Line, Point2D};
+use jww_export::{to_hpgl, HpglOptions};
+
+fn base(pen_color: u16) -> EntityBase {
+    EntityBase {
+        pen_color,
+        ..Default::default()
+    }
+}
+
+fn document() -> Document<'static> {
+    let mut doc = Document {
+        entities: vec![
+            Entity::Line(Line {
+                base: base(3),
+                start: Point2D::new(0.0, 0.0),
+                end: Point2D::new(100.0, 0.0),
+            }),
+            Entity::Arc(Arc {
+                base: base(1),
+                center: Point2D::new(50.0, 0.0),
+                radius: 50.0,
+                start_angle: Angle::ZERO,
+                arc_angle: Angle::from_radians(std::f64::consts::PI),
+                tilt_angle: Angle::ZERO,
+                flatness: 1.0,
+                is_full_circle: false,
+            }),
+        ],
+        ..Default::default()
+    };
+    doc.layer_groups[0].scale = 2.0;
+    doc
+}
+
+#[test]
+fn test_hpgl_commands() {
+    let hpgl = to_hpgl(&document(), &HpglOptions::default());
+
+    // 縮尺1/2: 図面の100mmは出力の50mm (2000単位)
+    assert_eq!(
+        hpgl,
+        "IN;SP1;PU2000,0;PD;AA1000,0,180;SP3;PU0,0;PD2000,0;PU;SP0;"
+    );
+}
+
+#[test]
+fn test_hpgl_pen_map_and_polyline_arcs() {
+    let options = HpglOptions {
+        plot_scale: Some(1.0),
+        pens: [5, 2, 6, 4, 5, 6, 7, 8, 8],
+        use_arcs: false,
+        curve_tolerance: 0.5,
+    };
+    let hpgl = to_hpgl(&document(), &options);
+
+    assert!(hpgl.starts_with("IN;SP5;PU4000,0;PD"));
+    assert!(!hpgl.contains("AA"));
+    assert!(hpgl.contains("SP6;PU0,0;PD4000,0;"));
+}
@@ -0,0 +1,99 @@
+//! PDF出力のテスト
+
+use jww_core::{Angle, Document, Entity, EntityBase, Line, Point2D, Text};
+use jww_export::{to_pdf, PdfOptions, ViewBox};
+
+fn base(pen_style: u8) -> EntityBase {
+    EntityBase {
+        pen_style,
+        pen_color: 8,
+        ..Default::default()
+    }
+}
+
+fn document() -> Document<'static> {
+    let mut doc = Document {
+        paper_size: 3,
+        entities: vec![
+            Entity::Line(Line {
+                base: base(1),
+                start: Point2D::new(0.0, 0.0),
+                end: Point2D::new(254.0, 0.0),
+            }),
+            Entity::Line(Line {
+                base: base(2),
+                start: Point2D::new(0.0, 50.8),
+                end: Point2D::new(254.0, 50.8),
+            }),
+            Entity::Text(Text {
+                base: base(1),
+                start: Point2D::new(10.0, 10.0),
+                end: Point2D::new(16.0, 10.0),
+                text_type: 0,
+                size_x: 3.0,
+                size_y: 3.0,
+                spacing: 0.0,
+                angle: Angle::ZERO,
+                font_name: "@ＭＳ 明朝".into(),
+                content: "寸法".into(),
+            }),
+        ],
+        ..Default::default()
+    };
+    doc.layer_groups[0].scale = 2.0;
+    doc
+}
+
+#[test]
+fn test_pdf_structure() {
+    let pdf = to_pdf(&document(), &PdfOptions::default());
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.starts_with("%PDF-1.4\n"));
+    assert!(text.ends_with("%%EOF\n"));
+
+    // 相互参照表の位置が各オブジェクトの先頭を指す (2行目はバイナリのコメント)
+    let startxref: usize = text
+        .rsplit("startxref\n")
+        .next()
+        .unwrap()
+        .lines()
+        .next()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let xref = std::str::from_utf8(&pdf[startxref..]).unwrap();
+    assert!(xref.starts_with("xref\n"));
+    let offsets: Vec<usize> = xref
+        .lines()
+        .skip(3)
+        .take_while(|line| line.ends_with(" n "))
+        .map(|line| line[..10].parse().unwrap())
+        .collect();
+    assert_eq!(offsets.len(), 7);
+    for (i, offset) in offsets.iter().enumerate() {
+        assert!(pdf[*offset..].starts_with(format!("{} 0 obj\n", i + 1).as_bytes()));
+    }
+
+    // A3の用紙 (420mm×297mm) のページ
+    assert!(text.contains("/MediaBox [0 0 1190.551 841.89]"));
+}
+
+#[test]
+fn test_pdf_content() {
+    let options = PdfOptions {
+        view_box: ViewBox::Extents,
+        ..Default::default()
+    };
+    let pdf = to_pdf(&document(), &options);
+    let text = String::from_utf8_lossy(&pdf);
+
+    // 縮尺1/2: 図面の254mmは用紙上の127mm = 360pt
+    assert!(text.contains("/MediaBox [0 0 360 72]"));
+    assert!(text.contains("0 0 m\n360 0 l\nS\n"));
+    // 点線は線種のパターンを設定する
+    assert!(text.contains("[2.835 2.835] 0 d"));
+
+    // 縦書きの明朝体は縦書きのCMap、文字列はUCS-2の16進表記
+    assert!(text.contains("/BaseFont /KozMinPr6N-Regular-UniJIS-UCS2-V"));
+    assert!(text.contains("<5BF86CD5> Tj"));
+}
@@ -0,0 +1,111 @@
+//! PNG出力のテスト
+
+use jww_core::{Document, Entity, EntityBase, Line, Point2D};
+use jww_export::{to_png, PngError, PngOptions, RasterSize};
+use tiny_skia::Pixmap;
+
+fn line(start_x: f64, start_y: f64, end_x: f64, end_y: f64) -> Entity<'static> {
+    Entity::Line(Line {
+        base: EntityBase {
+            pen_color: 8,
+            pen_width: 100,
+            ..Default::default()
+        },
+        start: Point2D::new(start_x, start_y),
+        end: Point2D::new(end_x, end_y),
+    })
+}
+
+fn document(entities: Vec<Entity>) -> Document {
+    Document {
+        entities,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_png_size_from_dpi() {
+    // 範囲 100mm + 余白 0.7mm×2 を 25.4dpi (1px/mm) で出力
+    let doc = document(vec![
+        line(0.0, 0.0, 100.0, 0.0),
+        line(0.0, 50.0, 100.0, 50.0),
+    ]);
+    let png = to_png(
+        &doc,
+        &PngOptions {
+            size: RasterSize::Dpi(25.4),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let pixmap = Pixmap::decode_png(&png).unwrap();
+
+    assert_eq!((pixmap.width(), pixmap.height()), (102, 52));
+}
+
+#[test]
+fn test_png_size_from_width() {
+    // 範囲 101.4mm × 51.4mm を幅204pxに合わせ、高さは縦横比から決める
+    let doc = document(vec![
+        line(0.0, 0.0, 100.0, 0.0),
+        line(0.0, 50.0, 100.0, 50.0),
+    ]);
+    let png = to_png(
+        &doc,
+        &PngOptions {
+            size: RasterSize::Width(204),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let pixmap = Pixmap::decode_png(&png).unwrap();
+
+    assert_eq!((pixmap.width(), pixmap.height()), (204, 104));
+}
+
+#[test]
+fn test_png_draws_lines() {
+    let doc = document(vec![
+        line(-50.0, 0.0, 50.0, 0.0),
+        line(-50.0, 50.0, 50.0, 50.0),
+    ]);
+    let options = PngOptions {
+        size: RasterSize::Pixels {
+            width: 200,
+            height: 200,
+        },
+        ..Default::default()
+    };
+    let pixmap = Pixmap::decode_png(&to_png(&doc, &options).unwrap()).unwrap();
+    assert_eq!((pixmap.width(), pixmap.height()), (200, 200));
+
+    // 中央付近の線は赤、それ以外は背景の白
+    let pixel_at = |x: u32, y: u32| pixmap.pixel(x, y).unwrap();
+    let red = (0..200)
+        .map(|y| pixel_at(100, y))
+        .filter(|p| p.red() == 255 && p.green() == 0)
+        .count();
+    assert!(red > 0);
+    let corner = pixel_at(0, 0);
+    assert_eq!(
+        (corner.red(), corner.green(), corner.blue()),
+        (255, 255, 255)
+    );
+}
+
+#[test]
+fn test_png_invalid_size() {
+    let doc = document(vec![line(0.0, 0.0, 100.0, 0.0)]);
+    let options = PngOptions {
+        size: RasterSize::Pixels {
+            width: 0,
+            height: 100,
+        },
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        to_png(&doc, &options),
+        Err(PngError::InvalidSize(..))
+    ));
+}
@@ -0,0 +1,76 @@
+//! 図面レポート出力のテスト
+
+use jww_core::{Angle, Document, Entity, EntityBase, Line, Point2D, Text};
+use jww_export::{to_report, ReportFormat, ReportOptions};
+
+fn base(layer: u16) -> EntityBase {
+    EntityBase {
+        pen_color: 2,
+        layer,
+        ..Default::default()
+    }
+}
+
+fn document() -> Document<'static> {
+    let line = |y: f64| {
+        Entity::Line(Line {
+            base: base(1),
+            start: Point2D::new(0.0, y),
+            end: Point2D::new(100.0, y),
+        })
+    };
+    let mut doc = Document {
+        version: 600,
+        paper_size: 3,
+        memo: "テスト".into(),
+        entities: vec![
+            line(0.0),
+            line(10.0),
+            Entity::Text(Text {
+                base: base(0xA),
+                start: Point2D::new(5.0, 5.0),
+                end: Point2D::new(20.0, 5.0),
+                text_type: 0,
+                size_x: 3.0,
+                size_y: 3.0,
+                spacing: 0.0,
+                angle: Angle::ZERO,
+                font_name: "ＭＳ ゴシック".into(),
+                content: "A|B <C>".into(),
+            }),
+        ],
+        ..Default::default()
+    };
+    doc.layer_groups[0].layers[1].name = "壁".to_string();
+    doc
+}
+
+#[test]
+fn test_html_report() {
+    let html = to_report(&document(), &ReportOptions::default());
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<h1>図面レポート</h1>"));
+    assert!(html.contains("<tr><td>用紙</td><td>A3 (420×297mm)</td></tr>"));
+    assert!(html.contains("<tr><td>0</td><td>1</td><td>壁</td><td>2</td></tr>"));
+    assert!(html.contains("<tr><td>0</td><td>A</td><td>0-A</td><td>1</td></tr>"));
+    assert!(html.contains("<td>A|B &lt;C&gt;</td>"));
+    assert!(html.contains(r#"<img src="data:image/svg+xml;charset=utf-8,%3C%3Fxml"#));
+}
+
+#[test]
+fn test_markdown_report() {
+    let options = ReportOptions {
+        format: ReportFormat::Markdown,
+        title: Some("平面図".to_string()),
+        thumbnail: false,
+    };
+    let markdown = to_report(&document(), &options);
+
+    assert!(markdown.starts_with("# 平面図\n"));
+    assert!(markdown.contains(
+        "| グループ | レイヤ | 名前 | エンティティ数 |\n|---|---|---|---|\n| 0 | 1 | 壁 | 2 |\n"
+    ));
+    assert!(markdown.contains("| A\\|B &lt;C&gt; | 0-A | (5, 5) | 3 |"));
+    assert!(!markdown.contains("data:image"));
+}
@@ -0,0 +1,231 @@
+//! SVG出力のテスト
+
+use jww_core::{Angle, Document, Entity, EntityBase, Line, Point2D, Text};
+use jww_export::{
+    dash_pattern, font_family, to_svg, to_svg_with_options, Palette, PenStyle, Rgb, SvgOptions,
+    ViewBox,
+};
+
+fn base(pen_style: u8, pen_color: u16, pen_width: u16) -> EntityBase {
+    EntityBase {
+        pen_style,
+        pen_color,
+        pen_width,
+        ..Default::default()
+    }
+}
+
+fn line(base: EntityBase) -> Entity<'static> {
+    Entity::Line(Line {
+        base,
+        start: Point2D::new(0.0, 0.0),
+        end: Point2D::new(100.0, 50.0),
+    })
+}
+
+fn text<'a>(font_name: &'a str, content: &'a str, angle: f64) -> Entity<'a> {
+    Entity::Text(Text {
+        base: base(1, 2, 0),
+        start: Point2D::new(10.0, 20.0),
+        end: Point2D::new(10.0 + 3.0 * content.chars().count() as f64, 20.0),
+        text_type: 0,
+        size_x: 3.0,
+        size_y: 3.0,
+        spacing: 0.0,
+        angle: Angle::from_degrees(angle),
+        font_name: font_name.into(),
+        content: content.into(),
+    })
+}
+
+fn document(entities: Vec<Entity>) -> Document {
+    Document {
+        entities,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_line_flips_y_axis() {
+    let svg = to_svg(&document(vec![line(base(1, 2, 0))]));
+
+    assert!(svg.starts_with("<?xml"));
+    assert!(svg.contains(r#"<line x1="0" y1="0" x2="100" y2="-50""#));
+    assert!(!svg.contains("stroke-dasharray"));
+}
+
+#[test]
+fn test_stroke_width_from_pen() {
+    let svg = to_svg(&document(vec![line(base(1, 5, 0)), line(base(1, 2, 35))]));
+
+    // 線色5の既定線幅 / 線幅35 (1/100mm)
+    assert!(svg.contains(r##"stroke="#ff00ff" stroke-width="0.5""##));
+    assert!(svg.contains(r#"stroke-width="0.35""#));
+
+    let options = SvgOptions {
+        style: PenStyle {
+            pen_widths: [1.0; 9],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let svg = to_svg_with_options(&document(vec![line(base(1, 5, 0))]), &options);
+    assert!(svg.contains(r#"stroke-width="1""#));
+}
+
+#[test]
+fn test_palette() {
+    let doc = document(vec![line(base(1, 5, 0)), text("", "A", 0.0)]);
+    let options = SvgOptions {
+        style: PenStyle {
+            palette: Palette::PRINTER,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let svg = to_svg_with_options(&doc, &options);
+    assert!(svg.contains(r##"stroke="#000000""##));
+    assert!(!svg.contains("#ff00ff"));
+
+    let mut palette = Palette::SCREEN;
+    palette.colors[4] = Rgb::new(0x12, 0x34, 0x56);
+    let options = SvgOptions {
+        style: PenStyle {
+            palette,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let svg = to_svg_with_options(&doc, &options);
+    assert!(svg.contains(r##"stroke="#123456""##));
+    // 文字は線色2
+    assert!(svg.contains(r##"fill="#000000""##));
+}
+
+#[test]
+fn test_pen_style_json() {
+    // 線幅・色はJSONでは各オプションの直下に置く
+    let options: SvgOptions =
+        serde_json::from_str(r#"{"pen_widths": [1, 1, 1, 1, 1, 1, 1, 1, 1], "flip_y": false}"#)
+            .unwrap();
+    assert_eq!(options.style.pen_widths, [1.0; 9]);
+    assert_eq!(
+        options.style.min_stroke_width,
+        PenStyle::default().min_stroke_width
+    );
+    assert!(!options.flip_y);
+
+    let value = serde_json::to_value(jww_export::PdfOptions::default()).unwrap();
+    assert!(value.get("palette").is_some());
+    assert!(value.get("min_stroke_width").is_some());
+    assert!(value.get("style").is_none());
+}
+
+#[test]
+fn test_stroke_dasharray_scaled_by_layer_group() {
+    let mut doc = document(vec![line(base(2, 2, 0)), line(base(5, 2, 0))]);
+    doc.layer_groups[0].scale = 50.0;
+    let svg = to_svg(&doc);
+
+    assert!(svg.contains(r#"stroke-dasharray="50,50""#));
+    assert!(svg.contains(r#"stroke-dasharray="200,50,0,50" stroke-linecap="round""#));
+    // 線色2の既定線幅 0.18mm × 50
+    assert!(svg.contains(r#"stroke-width="9""#));
+}
+
+#[test]
+fn test_hidden_layer_is_skipped() {
+    let mut doc = document(vec![line(base(1, 2, 0))]);
+    doc.layer_groups[0].layers[0].state = 0;
+
+    assert!(!to_svg(&doc).contains("<line"));
+}
+
+#[test]
+fn test_dash_pattern() {
+    assert!(dash_pattern(1).is_empty());
+    assert_eq!(dash_pattern(9), &[0.0, 1.0]);
+}
+
+#[test]
+fn test_text_font_and_rotation() {
+    let svg = to_svg(&document(vec![text("ＭＳ 明朝", "A<B", 30.0)]));
+
+    assert!(
+        svg.contains(r#"<text x="10" y="-20" dominant-baseline="text-after-edge" font-size="3""#)
+    );
+    assert!(svg.contains("font-family=\"'MS Mincho', "));
+    assert!(svg.contains(r#"textLength="9" lengthAdjust="spacingAndGlyphs""#));
+    assert!(svg.contains(r#"transform="rotate(-30 10 -20)">A&lt;B</text>"#));
+    assert!(!svg.contains("writing-mode"));
+}
+
+#[test]
+fn test_vertical_text() {
+    let svg = to_svg(&document(vec![text("@ＭＳ ゴシック", "縦書き", 0.0)]));
+
+    assert!(svg.contains(r#"<text x="11.5" y="-20" style="writing-mode: vertical-rl""#));
+    assert!(svg.contains("font-family=\"'MS Gothic', "));
+    assert!(!svg.contains("@"));
+}
+
+#[test]
+fn test_font_family_override() {
+    let mut options = SvgOptions::default();
+    options
+        .font_families
+        .insert("ＭＳ ゴシック".to_string(), "monospace".to_string());
+    let svg = to_svg_with_options(&document(vec![text("@ＭＳ ゴシック", "A", 0.0)]), &options);
+
+    assert!(svg.contains(r#"font-family="monospace""#));
+    assert!(font_family("独自フォント").starts_with("'独自フォント', "));
+
+    // 全角・半角や英語名で指定したフォントも照合する
+    let mut options = SvgOptions::default();
+    options
+        .font_families
+        .insert("MS Mincho".to_string(), "serif".to_string());
+    let svg = to_svg_with_options(&document(vec![text("ＭＳ 明朝", "A", 0.0)]), &options);
+    assert!(svg.contains(r#"font-family="serif""#));
+    assert!(font_family("MS 明朝").starts_with("'MS Mincho', "));
+}
+
+#[test]
+fn test_view_box_from_extents() {
+    let svg = to_svg(&document(vec![line(base(1, 2, 0))]));
+
+    // 範囲 (0, 0)-(100, 50) に線幅0.7mmの余白
+    assert!(svg.contains(r#"viewBox="-0.7 -50.7 101.4 51.4" width="101.4mm" height="51.4mm""#));
+}
+
+#[test]
+fn test_view_box_from_paper_with_border() {
+    let mut doc = document(vec![line(base(1, 2, 0))]);
+    doc.paper_size = 4; // A4
+    doc.layer_groups[0].scale = 2.0;
+    let options = SvgOptions {
+        view_box: ViewBox::Paper,
+        draw_border: true,
+        ..Default::default()
+    };
+    let svg = to_svg_with_options(&doc, &options);
+
+    assert!(svg.contains(r#"viewBox="-297 -210 594 420" width="297mm" height="210mm""#));
+    assert!(svg.contains(r#"<rect x="-297" y="-210" width="594" height="420""#));
+}
+
+#[test]
+fn test_without_y_flip() {
+    let options = SvgOptions {
+        flip_y: false,
+        ..Default::default()
+    };
+    let svg = to_svg_with_options(
+        &document(vec![line(base(1, 2, 0)), text("", "A", 30.0)]),
+        &options,
+    );
+
+    assert!(svg.contains(r#"<line x1="0" y1="0" x2="100" y2="50""#));
+    assert!(svg.contains(r#"viewBox="-0.7 -0.7 101.4 51.4""#));
+    assert!(svg.contains(r#"transform="rotate(30 10 20)""#));
+}
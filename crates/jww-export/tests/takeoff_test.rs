@@ -0,0 +1,93 @@
+//! 数量拾い出しのテスト
+
+use jww_core::{Angle, Arc, Document, Entity, EntityBase, Line, Point2D, Solid};
+use jww_export::{quantity_takeoff, to_takeoff_csv, LengthUnit, TakeoffGroup, TakeoffOptions};
+
+fn base(layer: u16, pen_color: u16) -> EntityBase {
+    EntityBase {
+        pen_color,
+        layer,
+        ..Default::default()
+    }
+}
+
+fn document() -> Document<'static> {
+    let mut doc = Document {
+        entities: vec![
+            Entity::Line(Line {
+                base: base(1, 2),
+                start: Point2D::new(0.0, 0.0),
+                end: Point2D::new(3000.0, 4000.0),
+            }),
+            Entity::Line(Line {
+                base: base(1, 3),
+                start: Point2D::new(0.0, 0.0),
+                end: Point2D::new(1000.0, 0.0),
+            }),
+            Entity::Arc(Arc {
+                base: base(2, 2),
+                center: Point2D::new(0.0, 0.0),
+                radius: 1000.0,
+                start_angle: Angle::ZERO,
+                arc_angle: Angle::ZERO,
+                tilt_angle: Angle::ZERO,
+                flatness: 1.0,
+                is_full_circle: true,
+            }),
+            Entity::Solid(Solid {
+                base: base(2, 2),
+                point1: Point2D::new(0.0, 0.0),
+                point2: Point2D::new(2000.0, 0.0),
+                point3: Point2D::new(0.0, 1000.0),
+                point4: Point2D::new(2000.0, 1000.0),
+                color: 0,
+            }),
+        ],
+        ..Default::default()
+    };
+    doc.layer_groups[0].layers[1].name = "壁".to_string();
+    doc
+}
+
+#[test]
+fn test_takeoff_by_layer() {
+    let rows = quantity_takeoff(&document(), &TakeoffOptions::default());
+    assert_eq!(rows.len(), 2);
+
+    assert_eq!(rows[0].layer, Some(1));
+    assert_eq!(rows[0].layer_name.as_deref(), Some("壁"));
+    assert_eq!(rows[0].pen_color, None);
+    assert_eq!(rows[0].line_count, 2);
+    assert!((rows[0].line_length - 6000.0).abs() < 1e-9);
+
+    assert_eq!(rows[1].arc_count, 1);
+    assert!((rows[1].arc_length - 2000.0 * std::f64::consts::PI).abs() < 1e-6);
+    assert_eq!(rows[1].solid_count, 1);
+    assert!((rows[1].solid_area - 2_000_000.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_takeoff_csv_by_color_in_meters() {
+    let options = TakeoffOptions {
+        group_by: TakeoffGroup::PenColor,
+        unit: LengthUnit::Meter,
+        ..Default::default()
+    };
+    let csv = to_takeoff_csv(&document(), &options);
+    let lines: Vec<&str> = csv.split("\r\n").collect();
+    assert_eq!(
+        lines[0],
+        "pen_color,line_count,line_length_m,arc_count,arc_length_m,solid_count,solid_area_m2"
+    );
+    assert_eq!(lines[1], "2,1,5,1,6.283,1,2");
+    assert_eq!(lines[2], "3,1,1,0,0,0,0");
+    assert_eq!(lines[3], "total,2,6,1,6.283,1,2");
+}
+
+#[cfg(feature = "xlsx")]
+#[test]
+fn test_takeoff_xlsx() {
+    let xlsx = jww_export::to_takeoff_xlsx(&document(), &TakeoffOptions::default()).unwrap();
+    // XLSXはZIPアーカイブ
+    assert!(xlsx.starts_with(b"PK\x03\x04"));
+}
@@ -0,0 +1,110 @@
+//! Webビューア向けJSON出力のテスト
+
+use jww_core::{Angle, Arc, Block, BlockDef, Document, Entity, EntityBase, Line, Point2D, Solid};
+use jww_export::{to_viewer_document, to_viewer_json, ViewerOptions};
+
+fn base(pen_style: u8, pen_color: u16) -> EntityBase {
+    EntityBase {
+        pen_style,
+        pen_color,
+        layer: 1,
+        ..Default::default()
+    }
+}
+
+fn line(start_x: f64, start_y: f64, end_x: f64, end_y: f64) -> Entity<'static> {
+    Entity::Line(Line {
+        base: base(2, 6),
+        start: Point2D::new(start_x, start_y),
+        end: Point2D::new(end_x, end_y),
+    })
+}
+
+#[test]
+fn test_polyline_resolves_style() {
+    let mut doc = Document {
+        entities: vec![line(0.0, 0.0, 10.0, 5.0)],
+        ..Default::default()
+    };
+    doc.layer_groups[0].scale = 10.0;
+    let viewer = to_viewer_document(&doc, &ViewerOptions::default());
+
+    assert_eq!(viewer.bounds, [0.0, 0.0, 10.0, 5.0]);
+    assert_eq!(viewer.layers.len(), 1);
+    assert_eq!(viewer.layers[0].name, "0-1");
+    let polyline = &viewer.polylines[0];
+    assert_eq!(polyline.color, "#0000ff");
+    // 線色6の既定線幅 0.7mm × 縮尺10
+    assert_eq!(polyline.width, 7.0);
+    assert_eq!(polyline.dash, vec![10.0, 10.0]);
+    assert_eq!(polyline.points, vec![0.0, 0.0, 10.0, 5.0]);
+}
+
+#[test]
+fn test_arc_is_tessellated_and_block_resolved() {
+    let doc = Document {
+        entities: vec![
+            Entity::Arc(Arc {
+                base: base(1, 2),
+                center: Point2D::new(0.0, 0.0),
+                radius: 10.0,
+                start_angle: Angle::ZERO,
+                arc_angle: Angle::ZERO,
+                tilt_angle: Angle::ZERO,
+                flatness: 1.0,
+                is_full_circle: true,
+            }),
+            Entity::Block(Block {
+                base: base(1, 2),
+                reference: Point2D::new(100.0, 0.0),
+                scale_x: 1.0,
+                scale_y: 1.0,
+                rotation: Angle::from_radians(std::f64::consts::FRAC_PI_2),
+                def_number: 1,
+            }),
+        ],
+        block_defs: vec![BlockDef {
+            base: base(1, 2),
+            number: 1,
+            is_referenced: true,
+            name: String::new(),
+            entities: vec![line(0.0, 0.0, 10.0, 0.0)],
+        }],
+        ..Default::default()
+    };
+    let viewer = to_viewer_document(&doc, &ViewerOptions::default());
+
+    let circle = &viewer.polylines[0];
+    assert!(circle.closed);
+    assert!(circle.points.len() > 8);
+    assert!(circle.dash.is_empty());
+    assert_eq!(viewer.polylines[1].points, vec![100.0, 0.0, 100.0, 10.0]);
+    // 範囲は折れ線近似後の頂点から求める
+    assert_eq!(
+        (viewer.bounds[0], viewer.bounds[2], viewer.bounds[3]),
+        (-10.0, 100.0, 10.0)
+    );
+}
+
+#[test]
+fn test_fill_color_and_json() {
+    let mut solid_base = base(1, 10);
+    solid_base.layer = 2;
+    let doc = Document {
+        entities: vec![Entity::Solid(Solid {
+            base: solid_base,
+            point1: Point2D::new(0.0, 0.0),
+            point2: Point2D::new(1.0, 0.0),
+            point3: Point2D::new(0.0, 1.0),
+            point4: Point2D::new(1.0, 1.0),
+            color: 0x00FF8000,
+        })],
+        ..Default::default()
+    };
+    let json = to_viewer_json(&doc, &ViewerOptions::default());
+
+    assert!(json.contains(
+        r##""fills":[{"layer":0,"color":"#0080ff","points":[0.0,0.0,1.0,0.0,1.0,1.0,0.0,1.0]}]"##
+    ));
+    assert!(!json.contains("dash"));
+}
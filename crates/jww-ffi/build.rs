@@ -0,0 +1,23 @@
+//! C言語向けヘッダーを `OUT_DIR/jww.h` に生成する
+//!
+//! リポジトリの `include/jww.h` は生成結果をコミットしたもので、
+//! `UPDATE_SNAPSHOTS=1 cargo test -p jww-ffi --test ffi_test` で更新する。
+
+use std::path::PathBuf;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let crate_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("invalid cbindgen.toml");
+    match cbindgen::generate_with_config(&crate_dir, config) {
+        Ok(bindings) => {
+            bindings.write_to_file(out_dir.join("jww.h"));
+        }
+        // ヘッダーを生成できなくてもライブラリのビルドは続ける
+        Err(e) => println!("cargo:warning=failed to generate C header: {}", e),
+    }
+}
@@ -0,0 +1,87 @@
+/* JWW parser C API (generated by cbindgen, do not edit) */
+
+#ifndef JWW_H
+#define JWW_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// 成功
+#define JWW_OK 0
+
+// 必須の引数がNULL
+#define JWW_ERR_NULL_POINTER 1
+
+// JWWファイルのパースに失敗した
+#define JWW_ERR_PARSE 2
+
+// オプションのJSONが不正
+#define JWW_ERR_INVALID_OPTIONS 3
+
+// 結果の変換に失敗した
+#define JWW_ERR_OUTPUT 4
+
+// ライブラリ内部でパニックが発生した (`panic = "unwind"` でビルドした場合のみ)
+#define JWW_ERR_PANIC 5
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// JWWファイルをパースし、ドキュメントのJSON (UTF-8、NUL終端) を返す
+//
+// 成功時は `*out_json` に文字列を書き込む。不要になったら `jww_free_string` で解放する。
+//
+// # Safety
+// - `data` は `len` バイト読み取り可能であること
+// - `out_json` は書き込み可能な `char *` を指すこと
+int32_t jww_parse_to_json(const uint8_t *data,
+                          size_t len,
+                          char **out_json);
+
+// JWWファイルをDXFのバイト列に変換する
+//
+// `options_json` は `ConvertOptions` の項目と出力形式 `format` (`ShiftJis`・`Utf8`・`Binary`)
+// を持つJSON (NULLの場合は既定値)。成功時は `*out_data`・`*out_len` にバイト列を書き込む。
+// 不要になったら `jww_free_bytes` で解放する。
+//
+// # Safety
+// - `data` は `len` バイト読み取り可能であること
+// - `options_json` はNULLまたはNUL終端のUTF-8文字列であること
+// - `out_data`・`out_len` は書き込み可能な領域を指すこと
+int32_t jww_convert_to_dxf(const uint8_t *data,
+                           size_t len,
+                           const char *options_json,
+                           uint8_t **out_data,
+                           size_t *out_len);
+
+// 直前に失敗した呼び出しのエラーメッセージ (UTF-8、NUL終端) を返す
+//
+// エラーがない場合はNULLを返す。ポインタは同じスレッドで次にAPIを呼び出すまで有効で、
+// 解放してはならない。
+const char *jww_last_error_message(void);
+
+// `jww_parse_to_json` が返した文字列を解放する (NULLの場合は何もしない)
+//
+// # Safety
+// `s` はこのライブラリが返した未解放の文字列であること
+void jww_free_string(char *s);
+
+// `jww_convert_to_dxf` が返したバイト列を解放する (NULLの場合は何もしない)
+//
+// # Safety
+// `data`・`len` はこのライブラリが返した未解放のバイト列とその長さであること
+void jww_free_bytes(uint8_t *data,
+                    size_t len);
+
+// ライブラリのバージョン (NUL終端、解放不要)
+const char *jww_version(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* JWW_H */
@@ -0,0 +1,198 @@
+//! C ABI bindings for jww-parser
+//!
+//! C#・C++・Delphiなどから利用するためのC関数を提供する。ヘッダーファイルは
+//! cbindgenで生成した `include/jww.h`。
+//!
+//! 内部のパニックは `JWW_ERR_PANIC` として返すが、これは `panic = "unwind"` で
+//! ビルドした場合に限る。`--release` (`panic = "abort"`) ではプロセスが終了するため、
+//! 配布用には `--profile release-unwind` でビルドする。
+//!
+//! - 関数は状態コード (`JWW_OK` など) を返し、結果は出力引数に書き込む
+//! - 失敗時のメッセージは `jww_last_error_message` で取得する (呼び出したスレッドごと)
+//! - ライブラリが確保したメモリは `jww_free_string`・`jww_free_bytes` で解放する
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, UnwindSafe};
+use std::ptr;
+
+/// 成功
+pub const JWW_OK: i32 = 0;
+/// 必須の引数がNULL
+pub const JWW_ERR_NULL_POINTER: i32 = 1;
+/// JWWファイルのパースに失敗した
+pub const JWW_ERR_PARSE: i32 = 2;
+/// オプションのJSONが不正
+pub const JWW_ERR_INVALID_OPTIONS: i32 = 3;
+/// 結果の変換に失敗した
+pub const JWW_ERR_OUTPUT: i32 = 4;
+/// ライブラリ内部でパニックが発生した (`panic = "unwind"` でビルドした場合のみ)
+pub const JWW_ERR_PANIC: i32 = 5;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// 直前のエラーを記録し、状態コードを返す
+fn fail(status: i32, message: impl Into<String>) -> i32 {
+    let message = CString::new(message.into().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|cell| *cell.borrow_mut() = Some(message));
+    status
+}
+
+/// パニックを状態コードに変換して実行する (`panic = "abort"` の場合は変換されない)
+fn guard(f: impl FnOnce() -> i32 + UnwindSafe) -> i32 {
+    LAST_ERROR.with(|cell| *cell.borrow_mut() = None);
+    catch_unwind(f).unwrap_or_else(|_| fail(JWW_ERR_PANIC, "internal error (panic)"))
+}
+
+/// 入力のバイト列を借用する
+///
+/// # Safety
+/// `data` は `len` バイト読み取り可能であること (`len` が0の場合はNULLでもよい)
+unsafe fn input<'a>(data: *const u8, len: usize) -> Option<&'a [u8]> {
+    if len == 0 {
+        Some(&[])
+    } else if data.is_null() {
+        None
+    } else {
+        Some(std::slice::from_raw_parts(data, len))
+    }
+}
+
+/// `jww_convert_to_dxf` のオプション (変換オプションに出力形式を加えたもの)
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+struct DxfOptions {
+    #[serde(flatten)]
+    convert: jww_dxf::ConvertOptions,
+    /// 出力形式 (`ShiftJis`・`Utf8`・`Binary`、既定値は `ShiftJis`)
+    format: jww_dxf::DxfFormat,
+}
+
+/// JWWファイルをパースし、ドキュメントのJSON (UTF-8、NUL終端) を返す
+///
+/// 成功時は `*out_json` に文字列を書き込む。不要になったら `jww_free_string` で解放する。
+///
+/// # Safety
+/// - `data` は `len` バイト読み取り可能であること
+/// - `out_json` は書き込み可能な `char *` を指すこと
+#[no_mangle]
+pub unsafe extern "C" fn jww_parse_to_json(
+    data: *const u8,
+    len: usize,
+    out_json: *mut *mut c_char,
+) -> i32 {
+    guard(|| {
+        if out_json.is_null() {
+            return fail(JWW_ERR_NULL_POINTER, "out_json is null");
+        }
+        *out_json = ptr::null_mut();
+        let Some(data) = input(data, len) else {
+            return fail(JWW_ERR_NULL_POINTER, "data is null");
+        };
+        let doc = match jww_core::parse(data) {
+            Ok(doc) => doc,
+            Err(e) => return fail(JWW_ERR_PARSE, format!("parse error: {}", e)),
+        };
+        let json = match serde_json::to_string(&doc) {
+            Ok(json) => json,
+            Err(e) => return fail(JWW_ERR_OUTPUT, format!("JSON error: {}", e)),
+        };
+        // JSONの文字列中のNULは \u0000 にエスケープされるため失敗しない
+        *out_json = CString::new(json).unwrap_or_default().into_raw();
+        JWW_OK
+    })
+}
+
+/// JWWファイルをDXFのバイト列に変換する
+///
+/// `options_json` は `ConvertOptions` の項目と出力形式 `format` (`ShiftJis`・`Utf8`・`Binary`)
+/// を持つJSON (NULLの場合は既定値)。成功時は `*out_data`・`*out_len` にバイト列を書き込む。
+/// 不要になったら `jww_free_bytes` で解放する。
+///
+/// # Safety
+/// - `data` は `len` バイト読み取り可能であること
+/// - `options_json` はNULLまたはNUL終端のUTF-8文字列であること
+/// - `out_data`・`out_len` は書き込み可能な領域を指すこと
+#[no_mangle]
+pub unsafe extern "C" fn jww_convert_to_dxf(
+    data: *const u8,
+    len: usize,
+    options_json: *const c_char,
+    out_data: *mut *mut u8,
+    out_len: *mut usize,
+) -> i32 {
+    guard(|| {
+        if out_data.is_null() || out_len.is_null() {
+            return fail(JWW_ERR_NULL_POINTER, "out_data or out_len is null");
+        }
+        *out_data = ptr::null_mut();
+        *out_len = 0;
+        let Some(data) = input(data, len) else {
+            return fail(JWW_ERR_NULL_POINTER, "data is null");
+        };
+        let options: DxfOptions = if options_json.is_null() {
+            DxfOptions::default()
+        } else {
+            let parsed = CStr::from_ptr(options_json)
+                .to_str()
+                .map_err(|e| e.to_string())
+                .and_then(|json| serde_json::from_str(json).map_err(|e| e.to_string()));
+            match parsed {
+                Ok(options) => options,
+                Err(e) => return fail(JWW_ERR_INVALID_OPTIONS, format!("invalid options: {}", e)),
+            }
+        };
+        let doc = match jww_core::parse(data) {
+            Ok(doc) => doc,
+            Err(e) => return fail(JWW_ERR_PARSE, format!("parse error: {}", e)),
+        };
+        let dxf_doc = jww_dxf::convert_document_with_options(&doc, &options.convert);
+        let bytes = jww_dxf::to_bytes(&dxf_doc, options.format).into_boxed_slice();
+        *out_len = bytes.len();
+        *out_data = Box::into_raw(bytes) as *mut u8;
+        JWW_OK
+    })
+}
+
+/// 直前に失敗した呼び出しのエラーメッセージ (UTF-8、NUL終端) を返す
+///
+/// エラーがない場合はNULLを返す。ポインタは同じスレッドで次にAPIを呼び出すまで有効で、
+/// 解放してはならない。
+#[no_mangle]
+pub extern "C" fn jww_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|cell| {
+        cell.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// `jww_parse_to_json` が返した文字列を解放する (NULLの場合は何もしない)
+///
+/// # Safety
+/// `s` はこのライブラリが返した未解放の文字列であること
+#[no_mangle]
+pub unsafe extern "C" fn jww_free_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// `jww_convert_to_dxf` が返したバイト列を解放する (NULLの場合は何もしない)
+///
+/// # Safety
+/// `data`・`len` はこのライブラリが返した未解放のバイト列とその長さであること
+#[no_mangle]
+pub unsafe extern "C" fn jww_free_bytes(data: *mut u8, len: usize) {
+    if !data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(data, len)));
+    }
+}
+
+/// ライブラリのバージョン (NUL終端、解放不要)
+#[no_mangle]
+pub extern "C" fn jww_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
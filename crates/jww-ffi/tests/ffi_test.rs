@@ -0,0 +1,105 @@
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+use jww_core::{Document, Entity, EntityBase, Line, Point2D};
+use jww_ffi::*;
+
+fn sample() -> Vec<u8> {
+    let doc = Document {
+        version: 600,
+        entities: vec![Entity::Line(Line {
+            base: EntityBase::default(),
+            start: Point2D::new(0.0, 0.0),
+            end: Point2D::new(100.0, 50.0),
+        })],
+        ..Default::default()
+    };
+    jww_core::write(&doc).unwrap()
+}
+
+#[test]
+fn test_parse_to_json() {
+    let data = sample();
+    let mut json = ptr::null_mut();
+    let status = unsafe { jww_parse_to_json(data.as_ptr(), data.len(), &mut json) };
+    assert_eq!(status, JWW_OK);
+    assert!(jww_last_error_message().is_null());
+    let text = unsafe { CStr::from_ptr(json) }
+        .to_str()
+        .unwrap()
+        .to_string();
+    unsafe { jww_free_string(json) };
+    let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(value["version"], 600);
+
+    let broken = b"not a jww file";
+    let status = unsafe { jww_parse_to_json(broken.as_ptr(), broken.len(), &mut json) };
+    assert_eq!(status, JWW_ERR_PARSE);
+    assert!(json.is_null());
+    let message = unsafe { CStr::from_ptr(jww_last_error_message()) };
+    assert!(message.to_str().unwrap().contains("invalid JWW signature"));
+
+    let status = unsafe { jww_parse_to_json(ptr::null(), 10, &mut json) };
+    assert_eq!(status, JWW_ERR_NULL_POINTER);
+}
+
+#[test]
+fn test_convert_to_dxf() {
+    let data = sample();
+    let mut out = ptr::null_mut();
+    let mut len = 0;
+    let options = CString::new(r#"{"format": "Utf8"}"#).unwrap();
+    let status = unsafe {
+        jww_convert_to_dxf(
+            data.as_ptr(),
+            data.len(),
+            options.as_ptr(),
+            &mut out,
+            &mut len,
+        )
+    };
+    assert_eq!(status, JWW_OK);
+    let dxf = unsafe { std::slice::from_raw_parts(out, len) }.to_vec();
+    unsafe { jww_free_bytes(out, len) };
+    let dxf = String::from_utf8(dxf).unwrap();
+    assert!(dxf.contains("LINE"));
+
+    let options = CString::new(r#"{"format": "Unknown"}"#).unwrap();
+    let status = unsafe {
+        jww_convert_to_dxf(
+            data.as_ptr(),
+            data.len(),
+            options.as_ptr(),
+            &mut out,
+            &mut len,
+        )
+    };
+    assert_eq!(status, JWW_ERR_INVALID_OPTIONS);
+    assert!(out.is_null());
+    assert_eq!(len, 0);
+}
+
+#[test]
+fn test_version() {
+    let version = unsafe { CStr::from_ptr(jww_version()) };
+    assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+}
+
+/// コミットされた `include/jww.h` がビルド時の生成結果と一致することを確認する
+///
+/// `UPDATE_SNAPSHOTS` が設定されている場合は `include/jww.h` を書き換える。
+#[test]
+fn test_header_up_to_date() {
+    let generated = std::fs::read_to_string(concat!(env!("OUT_DIR"), "/jww.h"))
+        .expect("C header was not generated by build.rs");
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("include/jww.h");
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, &generated).unwrap();
+        return;
+    }
+    let committed = std::fs::read_to_string(&path).unwrap();
+    assert!(
+        committed == generated,
+        "include/jww.h is out of date; run `UPDATE_SNAPSHOTS=1 cargo test -p jww-ffi --test ffi_test`"
+    );
+}
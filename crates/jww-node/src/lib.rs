@@ -0,0 +1,195 @@
+//! Node.js native addon for jww-parser
+//!
+//! サーバーサイドのNode.js向けにnapi-rsでネイティブモジュールを提供する。
+//! WASM版と違い、`Buffer` を直接読み書きし、一括変換を複数スレッドで処理する。
+//!
+//! ```js
+//! const jww = require('jww-parser-node');
+//!
+//! const dxf = jww.convertToDxf(fs.readFileSync('example.jww'), { format: 'Utf8' });
+//! const results = await jww.convertBatch(files.map((f) => fs.readFileSync(f)));
+//! ```
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+/// DXF変換のオプション (変換オプションに出力形式を加えたもの)
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+struct DxfOptions {
+    #[serde(flatten)]
+    convert: jww_dxf::ConvertOptions,
+    /// 出力形式 (`ShiftJis`・`Utf8`・`Binary`、既定値は `ShiftJis`)
+    format: jww_dxf::DxfFormat,
+}
+
+impl DxfOptions {
+    /// JSのオブジェクトから変換する (省略時は既定値)
+    fn from_js(options: Option<serde_json::Value>) -> Result<Self> {
+        match options {
+            None | Some(serde_json::Value::Null) => Ok(Self::default()),
+            Some(value) => serde_json::from_value(value)
+                .map_err(|e| Error::new(Status::InvalidArg, format!("invalid options: {e}"))),
+        }
+    }
+}
+
+/// 1ファイルの変換結果 (DXFのバイト列またはエラーメッセージ)
+type Converted = std::result::Result<Vec<u8>, String>;
+
+/// JWWファイルをパースし、DXFのバイト列に変換する
+fn convert(data: &[u8], options: &DxfOptions) -> Converted {
+    let doc = jww_core::parse(data).map_err(|e| e.to_string())?;
+    let dxf_doc = jww_dxf::convert_document_with_options(&doc, &options.convert);
+    Ok(jww_dxf::to_bytes(&dxf_doc, options.format))
+}
+
+/// JWWファイルをパースし、ドキュメントのJSON文字列を返す
+///
+/// オブジェクトを1つずつ作るより `JSON.parse` の方が速いため、文字列で返す。
+#[napi]
+pub fn parse_to_json(data: Buffer) -> Result<String> {
+    let doc = jww_core::parse(&data).map_err(|e| Error::from_reason(e.to_string()))?;
+    serde_json::to_string(&doc).map_err(|e| Error::from_reason(e.to_string()))
+}
+
+/// JWWファイルをDXFに変換する
+///
+/// `options` は `ConvertOptions` のフィールドと `format` (`ShiftJis`・`Utf8`・`Binary`) を持つオブジェクト。
+#[napi]
+pub fn convert_to_dxf(data: Buffer, options: Option<serde_json::Value>) -> Result<Buffer> {
+    let options = DxfOptions::from_js(options)?;
+    convert(&data, &options)
+        .map(Buffer::from)
+        .map_err(Error::from_reason)
+}
+
+/// libuvのスレッドプールで実行するDXF変換
+pub struct ConvertTask {
+    data: Vec<u8>,
+    options: DxfOptions,
+}
+
+impl Task for ConvertTask {
+    type Output = Vec<u8>;
+    type JsValue = Buffer;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        convert(&self.data, &self.options).map_err(Error::from_reason)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output.into())
+    }
+}
+
+/// `convertToDxf` の非同期版 (変換中にイベントループを止めない)
+#[napi(ts_return_type = "Promise<Buffer>")]
+pub fn convert_to_dxf_async(
+    data: Buffer,
+    options: Option<serde_json::Value>,
+) -> Result<AsyncTask<ConvertTask>> {
+    Ok(AsyncTask::new(ConvertTask {
+        data: data.to_vec(),
+        options: DxfOptions::from_js(options)?,
+    }))
+}
+
+/// 一括変換の各ファイルの結果
+#[napi(object)]
+pub struct BatchResult {
+    /// 変換に成功したかどうか
+    pub ok: bool,
+    /// DXFのバイト列 (成功時)
+    pub data: Option<Buffer>,
+    /// エラーメッセージ (失敗時)
+    pub error: Option<String>,
+}
+
+/// 複数ファイルを複数スレッドで変換するタスク
+pub struct BatchTask {
+    files: Vec<Vec<u8>>,
+    options: DxfOptions,
+    threads: usize,
+}
+
+impl Task for BatchTask {
+    type Output = Vec<Converted>;
+    type JsValue = Vec<BatchResult>;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        let files = &self.files;
+        let options = &self.options;
+        let next = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<Converted>>> =
+            Mutex::new((0..files.len()).map(|_| None).collect());
+        std::thread::scope(|scope| {
+            for _ in 0..self.threads.clamp(1, files.len().max(1)) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(data) = files.get(index) else {
+                        break;
+                    };
+                    let result = convert(data, options);
+                    results.lock().unwrap()[index] = Some(result);
+                });
+            }
+        });
+        Ok(results
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|result| result.expect("every file is processed"))
+            .collect())
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output
+            .into_iter()
+            .map(|result| match result {
+                Ok(data) => BatchResult {
+                    ok: true,
+                    data: Some(data.into()),
+                    error: None,
+                },
+                Err(error) => BatchResult {
+                    ok: false,
+                    data: None,
+                    error: Some(error),
+                },
+            })
+            .collect())
+    }
+}
+
+/// 複数のJWWファイルをDXFに一括変換する
+///
+/// 結果は入力と同じ順の配列で、失敗したファイルがあっても他のファイルの変換は続ける。
+/// `threads` を省略した場合は利用可能なCPU数のスレッドを使う。
+#[napi(ts_return_type = "Promise<Array<BatchResult>>")]
+pub fn convert_batch(
+    files: Vec<Buffer>,
+    options: Option<serde_json::Value>,
+    threads: Option<u32>,
+) -> Result<AsyncTask<BatchTask>> {
+    let threads = match threads {
+        Some(n) => n as usize,
+        None => std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1),
+    };
+    Ok(AsyncTask::new(BatchTask {
+        files: files.iter().map(|file| file.to_vec()).collect(),
+        options: DxfOptions::from_js(options)?,
+        threads,
+    }))
+}
+
+/// ライブラリのバージョン
+#[napi]
+pub fn version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
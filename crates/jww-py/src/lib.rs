@@ -0,0 +1,234 @@
+//! Python bindings for jww-parser
+//!
+//! pyo3で `jww` モジュールを提供する。maturinでビルドする。
+//!
+//! ```python
+//! import jww
+//! import pandas as pd
+//!
+//! doc = jww.read("example.jww")
+//! df = pd.DataFrame(doc.entities)   # 1行1エンティティ (typeと共通属性・座標の列)
+//! doc.save_dxf("example.dxf", format="Utf8")
+//! ```
+
+use std::path::PathBuf;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pyo3::create_exception!(jww, JwwError, PyValueError, "JWWファイルのパースに失敗した");
+
+/// Rustの値をJSON経由でPythonのdict/list等に変換する
+fn to_py<'py>(py: Python<'py>, value: &impl Serialize) -> PyResult<Bound<'py, PyAny>> {
+    let json = serde_json::to_string(value).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    py.import("json")?.call_method1("loads", (json,))
+}
+
+/// キーワード引数をオプション構造体に変換する (省略時は既定値)
+fn options_from_py<T: DeserializeOwned + Default>(
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<T> {
+    let Some(options) = options else {
+        return Ok(T::default());
+    };
+    let json: String = options
+        .py()
+        .import("json")?
+        .call_method1("dumps", (options,))?
+        .extract()?;
+    serde_json::from_str(&json).map_err(|e| PyValueError::new_err(format!("invalid options: {e}")))
+}
+
+/// `save_dxf` のオプション (変換オプションに出力形式を加えたもの)
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+struct DxfOptions {
+    #[serde(flatten)]
+    convert: jww_dxf::ConvertOptions,
+    /// 出力形式 (`ShiftJis`・`Utf8`・`Binary`、既定値は `ShiftJis`)
+    format: jww_dxf::DxfFormat,
+}
+
+/// パース済みのJWWドキュメント
+#[pyclass(module = "jww", frozen)]
+pub struct Document {
+    doc: jww_core::Document<'static>,
+}
+
+#[pymethods]
+impl Document {
+    /// JWWファイルフォーマットバージョン
+    #[getter]
+    fn version(&self) -> u32 {
+        self.doc.version
+    }
+
+    /// ファイルメモ
+    #[getter]
+    fn memo(&self) -> &str {
+        &self.doc.memo
+    }
+
+    /// 用紙サイズコード
+    #[getter]
+    fn paper_size(&self) -> u32 {
+        self.doc.paper_size
+    }
+
+    /// 書込みレイヤグループ番号
+    #[getter]
+    fn write_layer_group(&self) -> u32 {
+        self.doc.write_layer_group
+    }
+
+    /// 図面の縮尺分母 (書込みレイヤグループの縮尺)
+    #[getter]
+    fn scale(&self) -> f64 {
+        self.doc.drawing_scale()
+    }
+
+    /// 図面直下のエンティティ (`type` キーを持つdictのリスト、呼び出しごとに作成)
+    #[getter]
+    fn entities<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.doc.entities)
+    }
+
+    /// ブロック定義 (dictのリスト)
+    #[getter]
+    fn block_defs<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.doc.block_defs)
+    }
+
+    /// 全レイヤ (16グループ×16レイヤ、グループ順)
+    #[getter]
+    fn layers(&self) -> Vec<Layer> {
+        self.doc
+            .layer_groups
+            .iter()
+            .enumerate()
+            .flat_map(|(group, lg)| {
+                lg.layers
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, layer)| Layer {
+                        group,
+                        index,
+                        name: layer.name.clone(),
+                        group_name: lg.name.clone(),
+                        state: layer.state,
+                        protect: layer.protect,
+                    })
+            })
+            .collect()
+    }
+
+    /// ドキュメント全体をdictで返す (`to_json` と同じ構造)
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_py(py, &self.doc)
+    }
+
+    /// ドキュメント全体のJSON文字列
+    #[pyo3(signature = (pretty = false))]
+    fn to_json(&self, pretty: bool) -> PyResult<String> {
+        let json = if pretty {
+            serde_json::to_string_pretty(&self.doc)
+        } else {
+            serde_json::to_string(&self.doc)
+        };
+        json.map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// DXF文字列に変換する (キーワード引数は `ConvertOptions` のフィールド)
+    #[pyo3(signature = (**options))]
+    fn to_dxf(&self, options: Option<&Bound<'_, PyDict>>) -> PyResult<String> {
+        let options: jww_dxf::ConvertOptions = options_from_py(options)?;
+        let dxf_doc = jww_dxf::convert_document_with_options(&self.doc, &options);
+        Ok(jww_dxf::to_string(&dxf_doc))
+    }
+
+    /// DXFファイルに書き出す (`format` で出力形式、その他は `to_dxf` と同じ)
+    #[pyo3(signature = (path, **options))]
+    fn save_dxf(&self, path: PathBuf, options: Option<&Bound<'_, PyDict>>) -> PyResult<()> {
+        let options: DxfOptions = options_from_py(options)?;
+        let dxf_doc = jww_dxf::convert_document_with_options(&self.doc, &options.convert);
+        std::fs::write(path, jww_dxf::to_bytes(&dxf_doc, options.format))?;
+        Ok(())
+    }
+
+    fn __len__(&self) -> usize {
+        self.doc.entities.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "<jww.Document version={} entities={}>",
+            self.doc.version,
+            self.doc.entities.len()
+        )
+    }
+}
+
+/// レイヤ
+#[pyclass(module = "jww", frozen, get_all)]
+pub struct Layer {
+    /// レイヤグループ番号 (0-15)
+    group: usize,
+    /// グループ内のレイヤ番号 (0-15)
+    index: usize,
+    /// レイヤ名
+    name: String,
+    /// レイヤグループ名
+    group_name: String,
+    /// 状態: 0=非表示, 1=表示のみ, 2=編集可能, 3=書込モード
+    state: u32,
+    /// 保護フラグ
+    protect: u32,
+}
+
+#[pymethods]
+impl Layer {
+    fn __repr__(&self) -> String {
+        format!(
+            "<jww.Layer {:X}-{:X} {:?}>",
+            self.group, self.index, self.name
+        )
+    }
+}
+
+/// JWWファイルのバイト列をパースする
+#[pyfunction]
+fn parse(data: &[u8]) -> PyResult<Document> {
+    let doc = jww_core::parse(data)
+        .map(jww_core::Document::into_owned)
+        .map_err(|e| JwwError::new_err(e.to_string()))?;
+    Ok(Document { doc })
+}
+
+/// JWWファイルを読み込んでパースする
+#[pyfunction]
+fn read(path: PathBuf) -> PyResult<Document> {
+    parse(&std::fs::read(path)?)
+}
+
+/// JWWファイルのバイト列をDXF文字列に変換する
+#[pyfunction]
+#[pyo3(signature = (data, **options))]
+fn to_dxf(data: &[u8], options: Option<&Bound<'_, PyDict>>) -> PyResult<String> {
+    parse(data)?.to_dxf(options)
+}
+
+/// Pythonモジュール `jww`
+#[pymodule(name = "jww")]
+pub fn jww_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add("JwwError", m.py().get_type::<JwwError>())?;
+    m.add_class::<Document>()?;
+    m.add_class::<Layer>()?;
+    m.add_function(wrap_pyfunction!(parse, m)?)?;
+    m.add_function(wrap_pyfunction!(read, m)?)?;
+    m.add_function(wrap_pyfunction!(to_dxf, m)?)?;
+    Ok(())
+}
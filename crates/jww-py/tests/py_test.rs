@@ -0,0 +1,85 @@
+use std::sync::Once;
+
+use jww_core::{Document, Entity, EntityBase, Line, Point2D};
+use jww_py::jww_py;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+fn sample() -> Vec<u8> {
+    let doc = Document {
+        version: 600,
+        entities: vec![Entity::Line(Line {
+            base: EntityBase {
+                pen_color: 2,
+                layer: 3,
+                layer_group: 1,
+                ..Default::default()
+            },
+            start: Point2D::new(0.0, 0.0),
+            end: Point2D::new(100.0, 50.0),
+        })],
+        ..Default::default()
+    };
+    jww_core::write(&doc).unwrap()
+}
+
+/// `jww` モジュールと `data` (サンプルのバイト列) を変数に持たせてPythonコードを実行する
+fn run(code: &std::ffi::CStr) {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        pyo3::append_to_inittab!(jww_py);
+        Python::initialize();
+    });
+    Python::attach(|py| {
+        let locals = PyDict::new(py);
+        locals.set_item("jww", py.import("jww").unwrap()).unwrap();
+        locals
+            .set_item("data", PyBytes::new(py, &sample()))
+            .unwrap();
+        if let Err(e) = py.run(code, None, Some(&locals)) {
+            e.display(py);
+            panic!("python code failed");
+        }
+    });
+}
+
+#[test]
+fn test_parse() {
+    run(c"
+doc = jww.parse(data)
+assert doc.version == 600
+assert len(doc) == 1
+entity = doc.entities[0]
+assert entity['type'] == 'Line'
+assert entity['pen_color'] == 2 and entity['layer_group'] == 1
+assert entity['end'] == {'x': 100.0, 'y': 50.0}
+assert doc.to_dict()['entities'][0] == entity
+assert len(doc.layers) == 256
+assert (doc.layers[19].group, doc.layers[19].index) == (1, 3)
+");
+}
+
+#[test]
+fn test_to_dxf() {
+    run(c"
+dxf = jww.to_dxf(data)
+assert 'LINE' in dxf
+assert jww.parse(data).to_dxf(sort_by_layer=True) == dxf
+try:
+    jww.parse(data).to_dxf(units='Parsecs')
+    raise AssertionError('invalid options accepted')
+except ValueError as e:
+    assert 'invalid options' in str(e)
+");
+}
+
+#[test]
+fn test_parse_error() {
+    run(c"
+try:
+    jww.parse(b'not a jww file')
+    raise AssertionError('invalid data accepted')
+except jww.JwwError as e:
+    assert 'invalid JWW signature' in str(e)
+");
+}
@@ -0,0 +1,74 @@
+//! エラー型
+
+use std::time::Duration;
+
+use axum::extract::rejection::BytesRejection;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use thiserror::Error;
+
+/// リクエスト処理のエラー
+///
+/// レスポンスは `{ "code": ..., "message": ... }` 形式のJSONで返す。
+#[derive(Debug, Error)]
+pub enum ServerError {
+    /// JWWファイルのパースに失敗した
+    #[error(transparent)]
+    Parse(#[from] jww_core::ParseError),
+
+    /// クエリパラメータが不正
+    #[error("invalid query: {0}")]
+    InvalidQuery(String),
+
+    /// リクエストボディを読み込めなかった (サイズ超過を含む)
+    #[error(transparent)]
+    Body(#[from] BytesRejection),
+
+    /// 変換が制限時間内に終わらなかった
+    #[error("conversion timed out after {0:?}")]
+    Timeout(Duration),
+
+    /// 内部エラー
+    #[error("internal error: {0}")]
+    Internal(String),
+}
+
+impl ServerError {
+    /// HTTPステータスコード
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ServerError::Parse(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ServerError::InvalidQuery(_) => StatusCode::BAD_REQUEST,
+            ServerError::Body(rejection) => rejection.status(),
+            ServerError::Timeout(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ServerError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// 機械処理向けのエラーコード
+    pub fn code(&self) -> &'static str {
+        match self {
+            ServerError::Parse(e) => e.code(),
+            ServerError::InvalidQuery(_) => "invalid_query",
+            ServerError::Body(rejection) if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+                "payload_too_large"
+            }
+            ServerError::Body(_) => "invalid_body",
+            ServerError::Timeout(_) => "timeout",
+            ServerError::Internal(_) => "internal",
+        }
+    }
+}
+
+impl IntoResponse for ServerError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({
+            "code": self.code(),
+            "message": self.to_string(),
+        });
+        (self.status(), Json(body)).into_response()
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ServerError>;
@@ -0,0 +1,242 @@
+//! JWW conversion HTTP service
+//!
+//! JWWファイルをDXF・SVG・JSONに変換するHTTPサービス。
+//!
+//! - `POST /convert?to=dxf|svg|json`: リクエストボディのJWWファイルを変換する
+//!   (`format=shift_jis|utf8|binary` でDXFの形式、`pretty=true` でJSONを整形)
+//! - `POST /info`: 図面情報 (エンティティを除くヘッダー) をJSONで返す
+//! - `GET /health`: 死活監視用
+//!
+//! 変換中のパニックは500として返すが、これは `panic = "unwind"` でビルドした場合に限る。
+//! `--release` (`panic = "abort"`) ではサーバーが終了するため、`--profile release-unwind` でビルドする。
+
+mod error;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use axum::extract::rejection::{BytesRejection, QueryRejection};
+use axum::extract::{DefaultBodyLimit, Query, State};
+use axum::http::header::CONTENT_TYPE;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use tokio::sync::Semaphore;
+
+pub use error::{Result, ServerError};
+
+/// サーバーの設定
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// リクエストボディの最大バイト数
+    pub max_body_size: usize,
+    /// 1リクエストの変換にかけられる最大時間 (同時実行数の空き待ちを含む)
+    pub timeout: Duration,
+    /// 同時に実行する変換の最大数
+    pub max_concurrent: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_body_size: 50 * 1024 * 1024,
+            timeout: Duration::from_secs(30),
+            max_concurrent: std::thread::available_parallelism().map_or(4, |n| n.get()),
+        }
+    }
+}
+
+/// ハンドラーで共有する状態
+struct AppState {
+    config: Config,
+    /// 変換の同時実行数の制限
+    conversions: Arc<Semaphore>,
+}
+
+/// 制御を返すまでに読み取り・変換するエンティティ数
+const ENTITIES_PER_STEP: usize = 2000;
+
+/// 変換の中断要求 (制限時間を過ぎると立つ)
+#[derive(Clone)]
+struct Cancel {
+    aborted: Arc<AtomicBool>,
+    timeout: Duration,
+}
+
+impl Cancel {
+    /// 中断が要求されている場合はタイムアウトのエラーを返す
+    fn check(&self) -> Result<()> {
+        if self.aborted.load(Ordering::Relaxed) {
+            Err(ServerError::Timeout(self.timeout))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// 変換先の形式
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Target {
+    #[default]
+    Dxf,
+    Svg,
+    Json,
+}
+
+/// DXFの出力形式
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Format {
+    #[default]
+    ShiftJis,
+    Utf8,
+    Binary,
+}
+
+impl From<Format> for jww_dxf::DxfFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::ShiftJis => jww_dxf::DxfFormat::ShiftJis,
+            Format::Utf8 => jww_dxf::DxfFormat::Utf8,
+            Format::Binary => jww_dxf::DxfFormat::Binary,
+        }
+    }
+}
+
+/// `/convert` のクエリパラメータ
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ConvertQuery {
+    to: Target,
+    format: Format,
+    pretty: bool,
+}
+
+/// ルーターを作成する
+pub fn router(config: Config) -> Router {
+    let max_body_size = config.max_body_size;
+    let state = AppState {
+        conversions: Arc::new(Semaphore::new(config.max_concurrent.max(1))),
+        config,
+    };
+    Router::new()
+        .route("/health", get(health))
+        .route("/info", post(info))
+        .route("/convert", post(convert))
+        .layer(DefaultBodyLimit::max(max_body_size))
+        .with_state(Arc::new(state))
+}
+
+/// CPU負荷の高い処理をブロッキング用スレッドで、同時実行数と制限時間付きで実行する
+///
+/// 制限時間を過ぎた場合は中断を要求してエラーを返す。処理は `Cancel::check` で中断を確認し、
+/// 終了するまで同時実行数の枠を使い続ける。
+async fn run_blocking<T, F>(state: &AppState, f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&Cancel) -> Result<T> + Send + 'static,
+{
+    let timeout = state.config.timeout;
+    let cancel = Cancel {
+        aborted: Arc::new(AtomicBool::new(false)),
+        timeout,
+    };
+    let task = {
+        let cancel = cancel.clone();
+        let conversions = state.conversions.clone();
+        async move {
+            let permit = conversions
+                .acquire_owned()
+                .await
+                .map_err(|e| ServerError::Internal(e.to_string()))?;
+            tokio::task::spawn_blocking(move || {
+                let _permit = permit;
+                f(&cancel)
+            })
+            .await
+            .map_err(|e| {
+                if e.is_panic() {
+                    ServerError::Internal("conversion panicked".to_string())
+                } else {
+                    ServerError::Internal(e.to_string())
+                }
+            })?
+        }
+    };
+    match tokio::time::timeout(timeout, task).await {
+        Ok(result) => result,
+        Err(_) => {
+            cancel.aborted.store(true, Ordering::Relaxed);
+            Err(ServerError::Timeout(timeout))
+        }
+    }
+}
+
+/// 中断を確認しながらJWWファイルを読み取る
+fn parse<'a>(data: &'a [u8], cancel: &Cancel) -> Result<jww_core::Document<'a>> {
+    let mut parser = jww_core::IncrementalParser::new(data)?;
+    while !parser.step(ENTITIES_PER_STEP)? {
+        cancel.check()?;
+    }
+    Ok(parser.finish()?)
+}
+
+/// 中断を確認しながらDXFに変換する
+fn convert_dxf(doc: &jww_core::Document, cancel: &Cancel) -> Result<jww_dxf::Document> {
+    let options = jww_dxf::ConvertOptions::default();
+    let mut converter = jww_dxf::IncrementalConverter::new(doc, &options);
+    while !converter.step(ENTITIES_PER_STEP) {
+        cancel.check()?;
+    }
+    Ok(converter.finish().0)
+}
+
+async fn health() -> &'static str {
+    "ok"
+}
+
+async fn info(
+    State(state): State<Arc<AppState>>,
+    body: std::result::Result<Bytes, BytesRejection>,
+) -> Result<Json<jww_core::DocumentInfo>> {
+    let body = body?;
+    let info = run_blocking(&state, move |_| Ok(jww_core::probe(&body)?)).await?;
+    Ok(Json(info))
+}
+
+async fn convert(
+    State(state): State<Arc<AppState>>,
+    query: std::result::Result<Query<ConvertQuery>, QueryRejection>,
+    body: std::result::Result<Bytes, BytesRejection>,
+) -> Result<Response> {
+    let Query(query) = query.map_err(|e| ServerError::InvalidQuery(e.body_text()))?;
+    let body = body?;
+    let (content_type, output) = run_blocking(&state, move |cancel| {
+        let doc = parse(&body, cancel)?;
+        Ok(match query.to {
+            Target::Dxf => {
+                let dxf_doc = convert_dxf(&doc, cancel)?;
+                (
+                    "application/dxf",
+                    jww_dxf::to_bytes(&dxf_doc, query.format.into()),
+                )
+            }
+            Target::Svg => ("image/svg+xml", jww_export::to_svg(&doc).into_bytes()),
+            Target::Json => {
+                let json = if query.pretty {
+                    serde_json::to_vec_pretty(&doc)
+                } else {
+                    serde_json::to_vec(&doc)
+                };
+                let json = json.map_err(|e| ServerError::Internal(e.to_string()))?;
+                ("application/json", json)
+            }
+        })
+    })
+    .await?;
+    Ok(([(CONTENT_TYPE, content_type)], output).into_response())
+}
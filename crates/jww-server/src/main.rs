@@ -0,0 +1,41 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use clap::Parser;
+
+/// JWWファイル変換HTTPサービス
+#[derive(Debug, Parser)]
+#[command(name = "jww-server", version)]
+struct Args {
+    /// 待ち受けるアドレス
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    bind: SocketAddr,
+    /// リクエストボディの最大バイト数
+    #[arg(long, default_value_t = jww_server::Config::default().max_body_size)]
+    max_body_size: usize,
+    /// 1リクエストの変換にかけられる最大秒数
+    #[arg(long, default_value_t = 30)]
+    timeout: u64,
+    /// 同時に実行する変換の最大数
+    #[arg(long, default_value_t = jww_server::Config::default().max_concurrent)]
+    max_concurrent: usize,
+}
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+    let app = jww_server::router(jww_server::Config {
+        max_body_size: args.max_body_size,
+        timeout: Duration::from_secs(args.timeout),
+        max_concurrent: args.max_concurrent,
+    });
+    #[cfg(panic = "abort")]
+    eprintln!("warning: built with panic = \"abort\"; a panic during conversion stops the server (build with --profile release-unwind)");
+    let listener = tokio::net::TcpListener::bind(args.bind).await?;
+    eprintln!("listening on http://{}", listener.local_addr()?);
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await
+}
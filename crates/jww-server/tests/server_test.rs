@@ -0,0 +1,135 @@
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use jww_core::{Document, Entity, EntityBase, Line, Point2D};
+use jww_server::{router, Config};
+use tower::ServiceExt;
+
+fn sample() -> Vec<u8> {
+    lines(1)
+}
+
+/// 線を `count` 本含むJWWファイル
+fn lines(count: usize) -> Vec<u8> {
+    let line = Entity::Line(Line {
+        base: EntityBase::default(),
+        start: Point2D::new(0.0, 0.0),
+        end: Point2D::new(100.0, 50.0),
+    });
+    let doc = Document {
+        version: 600,
+        entities: vec![line; count],
+        ..Default::default()
+    };
+    jww_core::write(&doc).unwrap()
+}
+
+/// リクエストを送り、ステータス・Content-Type・ボディを返す
+async fn send(
+    config: Config,
+    method: &str,
+    uri: &str,
+    body: Vec<u8>,
+) -> (StatusCode, String, Vec<u8>) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .body(Body::from(body))
+        .unwrap();
+    let response = router(config).oneshot(request).await.unwrap();
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .map(|v| v.to_str().unwrap().to_string())
+        .unwrap_or_default();
+    let body = response
+        .into_body()
+        .collect()
+        .await
+        .unwrap()
+        .to_bytes()
+        .to_vec();
+    (status, content_type, body)
+}
+
+#[tokio::test]
+async fn test_convert() {
+    let (status, content_type, body) = send(Config::default(), "POST", "/convert", sample()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/dxf");
+    assert!(String::from_utf8_lossy(&body).contains("LINE"));
+
+    let (status, content_type, body) =
+        send(Config::default(), "POST", "/convert?to=svg", sample()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "image/svg+xml");
+    assert!(String::from_utf8(body).unwrap().contains("<svg"));
+
+    let (status, content_type, body) = send(
+        Config::default(),
+        "POST",
+        "/convert?to=json&pretty=true",
+        sample(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/json");
+    let doc: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(doc["entities"][0]["type"], "Line");
+}
+
+#[tokio::test]
+async fn test_info() {
+    let (status, _, body) = send(Config::default(), "POST", "/info", sample()).await;
+    assert_eq!(status, StatusCode::OK);
+    let info: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(info["version"], 600);
+    assert_eq!(info["entity_count"], 1);
+
+    let (status, _, body) = send(Config::default(), "GET", "/health", Vec::new()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, b"ok");
+}
+
+#[tokio::test]
+async fn test_errors() {
+    let (status, _, body) = send(
+        Config::default(),
+        "POST",
+        "/convert",
+        b"not a jww file".to_vec(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error["code"], "invalid_signature");
+
+    let (status, _, body) = send(Config::default(), "POST", "/convert?to=pdf", sample()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error["code"], "invalid_query");
+
+    let config = Config {
+        max_body_size: 100,
+        ..Config::default()
+    };
+    let (status, _, body) = send(config, "POST", "/convert", sample()).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error["code"], "payload_too_large");
+}
+
+#[tokio::test]
+async fn test_timeout() {
+    // 制限時間を過ぎた変換は打ち切ってタイムアウトを返す
+    let config = Config {
+        timeout: std::time::Duration::ZERO,
+        max_concurrent: 1,
+        ..Config::default()
+    };
+    let (status, _, body) = send(config, "POST", "/convert", lines(60_000)).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error["code"], "timeout");
+}
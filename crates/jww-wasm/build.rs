@@ -0,0 +1,72 @@
+//! ビルド情報 (コミットハッシュ・ビルド日時) を環境変数として埋め込む
+//!
+//! - `JWW_COMMIT_HASH`: 環境変数で指定された値、なければ `git rev-parse HEAD` の結果
+//! - `JWW_BUILD_TIMESTAMP`: `SOURCE_DATE_EPOCH` (再現可能ビルド用)、なければ現在時刻
+
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=JWW_COMMIT_HASH");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    println!("cargo:rerun-if-changed=../../.git/HEAD");
+    println!("cargo:rerun-if-changed=../../.git/refs");
+    println!("cargo:rerun-if-changed=src");
+
+    let commit = std::env::var("JWW_COMMIT_HASH")
+        .ok()
+        .filter(|hash| !hash.is_empty())
+        .or_else(git_commit_hash)
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=JWW_COMMIT_HASH={}", commit);
+
+    let timestamp = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.trim().parse().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+        });
+    println!(
+        "cargo:rustc-env=JWW_BUILD_TIMESTAMP={}",
+        format_utc(timestamp)
+    );
+}
+
+/// 作業ツリーのコミットハッシュ (gitがない場合は `None`)
+fn git_commit_hash() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let hash = String::from_utf8(output.stdout).ok()?;
+    Some(hash.trim().to_string()).filter(|hash| !hash.is_empty())
+}
+
+/// UNIX時刻をISO 8601形式のUTC日時 (例: `2024-01-31T12:34:56Z`) に変換する
+fn format_utc(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let secs = timestamp % 86_400;
+    // Howard Hinnant の days_from_civil の逆変換
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
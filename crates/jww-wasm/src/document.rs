@@ -0,0 +1,116 @@
+//! パース済みドキュメントを保持するクラス
+
+use std::cell::Cell;
+
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "dxf")]
+use crate::{dxf_string, options_from_js};
+use crate::{to_js, Bounds};
+
+/// パース済みのJWWドキュメント
+///
+/// ドキュメントをWASM側に保持したまま問い合わせ・変換を行うため、
+/// 同じファイルに対して何度も再パースやJSON全体の受け渡しをせずに済む。
+/// 不要になったら `free()` でメモリを解放する。
+#[wasm_bindgen]
+pub struct JwwDocument {
+    doc: jww_core::Document<'static>,
+    /// 作成時の `Document::memory_size`
+    memory_size: usize,
+}
+
+thread_local! {
+    /// 解放されていない `JwwDocument` の (件数, 合計バイト数)
+    static RETAINED: Cell<(usize, usize)> = const { Cell::new((0, 0)) };
+}
+
+/// 解放されていない `JwwDocument` の (件数, 合計バイト数) を返す
+pub(crate) fn retained() -> (usize, usize) {
+    RETAINED.with(Cell::get)
+}
+
+impl Drop for JwwDocument {
+    fn drop(&mut self) {
+        RETAINED.with(|cell| {
+            let (count, bytes) = cell.get();
+            cell.set((count - 1, bytes - self.memory_size));
+        });
+    }
+}
+
+#[wasm_bindgen]
+impl JwwDocument {
+    /// JWWファイルをパースする (失敗時は `{ code, message, offset, entity_index }` を投げる)
+    ///
+    /// `parse_options` はパースオプション (`JwwParseOptions`、省略可)。
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        data: &[u8],
+        #[wasm_bindgen(unchecked_param_type = "JwwParseOptions | null | undefined")] parse_options: JsValue,
+    ) -> Result<JwwDocument, JsValue> {
+        let doc = crate::parse(data, parse_options)
+            .map(jww_core::Document::into_owned)
+            .map_err(JsValue::from)?;
+        let memory_size = doc.memory_size();
+        RETAINED.with(|cell| {
+            let (count, bytes) = cell.get();
+            cell.set((count + 1, bytes + memory_size));
+        });
+        Ok(JwwDocument { doc, memory_size })
+    }
+
+    /// 保持しているドキュメントのおおよそのバイト数
+    #[wasm_bindgen(getter, js_name = memorySize)]
+    pub fn memory_size(&self) -> usize {
+        self.memory_size
+    }
+
+    /// JWWファイルフォーマットバージョン
+    #[wasm_bindgen(getter)]
+    pub fn version(&self) -> u32 {
+        self.doc.version
+    }
+
+    /// 図面直下のエンティティ数
+    #[wasm_bindgen(js_name = entityCount)]
+    pub fn entity_count(&self) -> usize {
+        self.doc.entities.len()
+    }
+
+    /// レイヤ名を返す (範囲外の場合はundefined)
+    #[wasm_bindgen(js_name = layerName)]
+    pub fn layer_name(&self, group: usize, layer: usize) -> Option<String> {
+        self.doc
+            .layer_groups
+            .get(group)
+            .and_then(|g| g.layers.get(layer))
+            .map(|l| l.name.clone())
+    }
+
+    /// 図面全体とレイヤごとの外接矩形 (`jww_get_bounds` と同じ形)
+    pub fn bounds(&self) -> Result<JsValue, JsValue> {
+        Ok(to_js(&Bounds::new(&self.doc))?)
+    }
+
+    /// DXF文字列に変換する
+    ///
+    /// `options.encoding` に `ShiftJis` を指定した場合は `invalid_options` のエラーを投げる
+    /// (Shift-JISのDXFは `jww_to_dxf_bytes` で出力する)。
+    ///
+    /// # 引数
+    /// * `options` - 変換オプション (`ConvertOptions` と同じ構造のオブジェクト、省略可)
+    #[cfg(feature = "dxf")]
+    #[wasm_bindgen(js_name = toDxf)]
+    pub fn to_dxf(&self, options: JsValue) -> Result<String, JsValue> {
+        let options: jww_dxf::ConvertOptions = options_from_js(options)?;
+        Ok(dxf_string(&self.doc, &options)?)
+    }
+
+    /// ドキュメント全体のJSON表現 (`jww_parse` の `data` と同じ形)
+    #[cfg(feature = "json")]
+    #[wasm_bindgen(js_name = toJson, unchecked_return_type = "Document")]
+    pub fn to_json(&self) -> Result<JsValue, JsValue> {
+        Ok(to_js(&self.doc)?)
+    }
+}
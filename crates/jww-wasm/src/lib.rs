@@ -1,125 +1,1148 @@
 //! WebAssembly bindings for jww-parser
 //!
 //! JWWファイルをパースし、DXF形式に変換するWASMモジュール
+//!
+//! 出力形式ごとにfeatureで切り替えられる (既定ではすべて有効)。
+//! - `dxf`: DXF変換 (`jww_to_dxf_string`・`jww_to_dxf_bytes` など)
+//! - `svg`: SVG出力 (`jww_to_svg`)
+//! - `pdf`: PDF出力 (`jww_to_pdf`)
+//! - `json`: ドキュメント全体のJSON出力 (`jww_parse` など)
 
+#[cfg(feature = "json")]
+use jww_core::json::{EntityTagging, FieldNaming, JsonProfile};
+use serde::Serialize;
 use wasm_bindgen::prelude::*;
 
-// パース結果を返すヘルパー型
+mod document;
+#[cfg(any(feature = "json", feature = "dxf"))]
+mod tasks;
+
+pub use document::JwwDocument;
+#[cfg(any(feature = "json", feature = "dxf"))]
+pub use tasks::CancelHandle;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_PARSE_OPTIONS: &'static str = r#"
+/** JWWファイルを読み込む各APIに渡すパースオプション (省略した項目は既定値) */
+export type JwwParseOptions = Partial<ParseOptions>;
+"#;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_PARSE_RESULT: &'static str = r#"
+/** `data` の型を指定した {@link ParseResult} */
+export type TypedParseResult<T> = Omit<ParseResult, "data" | "takeData" | "unwrap"> & {
+    readonly data: T | null;
+    takeData(): T | null;
+    unwrap(): T;
+};
+"#;
+
+/// パース・変換の結果
+///
+/// 成功時はデータ、失敗時はエラー情報のどちらか一方のみを保持する。
+/// 大きな結果は `takeData()` または `unwrap()` で取り出すと、WASM側の参照が
+/// 残らずに所有権がJSへ移る。
 #[wasm_bindgen]
 pub struct ParseResult {
-    ok: bool,
-    data: JsValue,
-    error: String,
+    result: Result<JsValue, ErrorInfo>,
 }
 
 #[wasm_bindgen]
 impl ParseResult {
     #[wasm_bindgen(getter)]
     pub fn ok(&self) -> bool {
-        self.ok
+        self.result.is_ok()
     }
 
+    /// 成功時のデータ (失敗時、または `takeData()` で取り出し済みの場合はnull)
     #[wasm_bindgen(getter)]
     pub fn data(&self) -> JsValue {
-        self.data.clone()
+        match &self.result {
+            Ok(data) => data.clone(),
+            Err(_) => JsValue::NULL,
+        }
     }
 
+    /// エラー情報 (`{ code, message, offset, entity_index }`、成功時はnull)
     #[wasm_bindgen(getter)]
-    pub fn error(&self) -> String {
-        self.error.clone()
+    pub fn error(&self) -> JsValue {
+        match &self.result {
+            Ok(_) => JsValue::NULL,
+            Err(error) => serde_wasm_bindgen::to_value(error).unwrap_or(JsValue::NULL),
+        }
+    }
+
+    /// 成功時のデータを取り出す
+    ///
+    /// 取り出した後は `data` がnullになり、WASM側はデータへの参照を持たない。
+    #[wasm_bindgen(js_name = takeData)]
+    pub fn take_data(&mut self) -> JsValue {
+        match &mut self.result {
+            Ok(data) => std::mem::replace(data, JsValue::NULL),
+            Err(_) => JsValue::NULL,
+        }
+    }
+
+    /// 成功時のデータを返し、失敗時はエラー情報を投げる
+    ///
+    /// 呼び出し後はこのオブジェクトが解放されるため、`free()` は不要。
+    pub fn unwrap(self) -> Result<JsValue, JsValue> {
+        self.result.map_err(JsValue::from)
+    }
+}
+
+impl ParseResult {
+    fn success(data: JsValue) -> Self {
+        ParseResult { result: Ok(data) }
+    }
+
+    fn failure(error: ErrorInfo) -> Self {
+        ParseResult { result: Err(error) }
+    }
+
+    /// 値をJSの値に変換して成功結果とする
+    fn from_serialize<T: Serialize>(value: &T) -> Self {
+        match to_js(value) {
+            Ok(json) => ParseResult::success(json),
+            Err(e) => ParseResult::failure(e),
+        }
+    }
+}
+
+/// 値をJSの値に変換する ([`jww_set_output_options`] の設定を使用する)
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, ErrorInfo> {
+    let options = OUTPUT_OPTIONS.with(|options| options.get());
+    let marshal_error = |e: &dyn std::fmt::Display| {
+        ErrorInfo::new("json_marshal", format!("JSON marshal error: {}", e))
+    };
+    #[cfg(feature = "json")]
+    {
+        let profile = options.profile();
+        if !profile.is_default() {
+            // 形式の変換は serde_json の値を経由するため、構造体と同じくオブジェクトで出力する
+            let json = profile.to_value(value).map_err(|e| marshal_error(&e))?;
+            return json
+                .serialize(&options.serializer().serialize_maps_as_objects(true))
+                .map_err(|e| marshal_error(&e));
+        }
+    }
+    value
+        .serialize(&options.serializer())
+        .map_err(|e| marshal_error(&e))
+}
+
+/// JSの値への変換方法
+///
+/// 既定値はserde-wasm-bindgenの既定の動作 (連想配列は `Map`、欠損値は `undefined`)。
+#[derive(Debug, Clone, Copy, Default, serde::Deserialize)]
+#[serde(default)]
+struct OutputOptions {
+    /// `JSON.parse` と同じ形 (連想配列はプレーンなオブジェクト、欠損値は `null`) で出力する
+    json_compatible: bool,
+    /// 連想配列を `Map` ではなくプレーンなオブジェクトで出力する
+    maps_as_objects: bool,
+    /// `Option::None` などの欠損値を `undefined` ではなく `null` で出力する
+    missing_as_null: bool,
+    /// 64ビット整数を `BigInt` で出力する
+    large_numbers_as_bigints: bool,
+    /// フィールド名の形式 (`"snake_case"`・`"camelCase"`、feature `json`)
+    #[cfg(feature = "json")]
+    field_naming: FieldNaming,
+    /// エンティティ種別の表し方 (`"verbose"`: `{ type: "Line", ... }`、`"compact"`: `{ Line: {...} }`)
+    #[cfg(feature = "json")]
+    entity_tagging: EntityTagging,
+}
+
+impl OutputOptions {
+    #[cfg(feature = "json")]
+    fn profile(self) -> JsonProfile {
+        JsonProfile {
+            field_naming: self.field_naming,
+            entity_tagging: self.entity_tagging,
+        }
+    }
+
+    fn serializer(self) -> serde_wasm_bindgen::Serializer {
+        let serializer = if self.json_compatible {
+            serde_wasm_bindgen::Serializer::json_compatible()
+        } else {
+            serde_wasm_bindgen::Serializer::new()
+                .serialize_maps_as_objects(self.maps_as_objects)
+                .serialize_missing_as_null(self.missing_as_null)
+        };
+        serializer.serialize_large_number_types_as_bigints(self.large_numbers_as_bigints)
+    }
+}
+
+thread_local! {
+    static OUTPUT_OPTIONS: std::cell::Cell<OutputOptions> = std::cell::Cell::new(OutputOptions::default());
+}
+
+/// JSON出力 (`ParseResult.data`) の形を設定する
+///
+/// # 引数
+/// * `options` - `{ json_compatible, maps_as_objects, missing_as_null, large_numbers_as_bigints }`
+///   (いずれも省略時はfalse、undefined/nullの場合は既定値に戻す) と
+///   `{ field_naming, entity_tagging }` (省略時は `"snake_case"`・`"verbose"`、feature `json` のみ)
+#[wasm_bindgen]
+pub fn jww_set_output_options(options: JsValue) -> Result<(), JsValue> {
+    let options: OutputOptions =
+        options_from_js(options).map_err(|e| JsValue::from_str(&e.message))?;
+    OUTPUT_OPTIONS.with(|cell| cell.set(options));
+    Ok(())
+}
+
+/// JSから渡されたパースオプション (undefined/nullの場合は既定値) でJWWファイルをパースする
+fn parse(data: &[u8], options: JsValue) -> Result<jww_core::Document<'_>, ErrorInfo> {
+    let options: jww_core::ParseOptions = options_from_js(options)?;
+    Ok(jww_core::parse_with_options(data, &options)?)
+}
+
+/// パースオプションの `max_file_size` を確認してから図面情報を読む
+///
+/// エンティティを読まないため、`max_file_size` 以外の項目は使わない。
+fn probe(data: &[u8], options: JsValue) -> Result<jww_core::DocumentInfo, ErrorInfo> {
+    let options: jww_core::ParseOptions = options_from_js(options)?;
+    if let Some(max) = options.max_file_size {
+        if data.len() > max {
+            return Err(jww_core::ParseError::LimitExceeded(format!(
+                "file of {} bytes exceeds {}",
+                data.len(),
+                max
+            ))
+            .into());
+        }
+    }
+    Ok(jww_core::probe(data)?)
+}
+
+/// JSに返すエラー情報
+///
+/// `code` は表示側でメッセージを翻訳するための識別子。パースエラーの場合は
+/// `jww_core::ParseError::code` の値、それ以外は `invalid_options`・`json_marshal` など。
+#[derive(Debug, Clone, Serialize)]
+struct ErrorInfo {
+    code: &'static str,
+    message: String,
+    /// エラーが発生したエンティティの先頭のバイト位置
+    offset: Option<usize>,
+    /// エラーが発生したエンティティの番号
+    entity_index: Option<usize>,
+}
+
+impl ErrorInfo {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        ErrorInfo {
+            code,
+            message: message.into(),
+            offset: None,
+            entity_index: None,
+        }
+    }
+}
+
+impl From<ErrorInfo> for JsValue {
+    /// 例外として投げるためのオブジェクトに変換する
+    fn from(error: ErrorInfo) -> Self {
+        serde_wasm_bindgen::to_value(&error).unwrap_or_else(|_| JsValue::from_str(&error.message))
+    }
+}
+
+impl From<jww_core::ParseError> for ErrorInfo {
+    fn from(e: jww_core::ParseError) -> Self {
+        ErrorInfo {
+            code: e.code(),
+            message: format!("parse error: {}", e),
+            offset: e.offset(),
+            entity_index: e.entity_index(),
+        }
     }
 }
 
 /// JWWファイルをパースし、JSON表現を返す
 ///
+/// アップロードされたファイルごとに、寛容さと厳密さを選んでパースできる。
+///
 /// # 引数
 /// * `data` - JWWファイルのバイナリデータ (Uint8Array)
+/// * `parse_options` - `{ lenient, max_file_size, max_entities, max_string_length, encoding,
+///   record_spans, preserve_unknown_classes }` (`ParseOptions` と同じ構造のオブジェクト、
+///   `encoding` は `Replace` または `Strict`、省略可)
 ///
 /// # 戻り値
-/// ParseResult - 成功時はdataフィールドにJSON、失敗時はerrorフィールドにエラーメッセージ
-#[wasm_bindgen]
-pub fn jww_parse(data: &[u8]) -> ParseResult {
-    match jww_core::parse(data) {
-        Ok(doc) => {
-            match serde_wasm_bindgen::to_value(&doc) {
-                Ok(json) => ParseResult {
-                    ok: true,
-                    data: json,
-                    error: String::new(),
-                },
-                Err(e) => ParseResult {
-                    ok: false,
-                    data: JsValue::NULL,
-                    error: format!("JSON marshal error: {}", e),
-                },
-            }
+/// ParseResult - 成功時はdataフィールドにJSON、失敗時はerrorフィールドにエラー情報
+/// (制限を超えた場合は `limit_exceeded`、不正な文字列の場合は `invalid_encoding`)
+#[cfg(feature = "json")]
+#[wasm_bindgen(unchecked_return_type = "TypedParseResult<Document>")]
+pub fn jww_parse(
+    data: &[u8],
+    #[wasm_bindgen(unchecked_param_type = "JwwParseOptions | null | undefined")] parse_options: JsValue,
+) -> ParseResult {
+    match parse(data, parse_options) {
+        Ok(doc) => ParseResult::from_serialize(&doc),
+        Err(e) => ParseResult::failure(e),
+    }
+}
+
+/// オプションを指定してJWWファイルをパースし、JSON表現を返す
+///
+/// `jww_parse` と同じ (`jww_parse` がパースオプションを受け取る前からのAPI)。
+#[cfg(feature = "json")]
+#[wasm_bindgen(unchecked_return_type = "TypedParseResult<Document>")]
+pub fn jww_parse_with_options(
+    data: &[u8],
+    #[wasm_bindgen(unchecked_param_type = "JwwParseOptions | null | undefined")] options: JsValue,
+) -> ParseResult {
+    jww_parse(data, options)
+}
+
+/// 分割して受け取ったJWWファイルをパースするクラス
+///
+/// `fetch` のストリームやFile APIから読み込んだチャンクを `push` で順に渡し、
+/// `finish` でパースする。チャンクはWASM側のバッファに直接追加されるため、
+/// JS側で大きなUint8Arrayに連結する必要がない。
+#[cfg(feature = "json")]
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct JwwParser {
+    buffer: Vec<u8>,
+}
+
+#[cfg(feature = "json")]
+#[wasm_bindgen]
+impl JwwParser {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> JwwParser {
+        JwwParser::default()
+    }
+
+    /// チャンクを追加する
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// これまでに追加したバイト数
+    #[wasm_bindgen(getter, js_name = byteLength)]
+    pub fn byte_length(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// 追加したデータをパースし、JSON表現を返す
+    ///
+    /// 呼び出し後はバッファを空にするため、同じインスタンスで別のファイルを読み込める。
+    /// `parse_options` はパースオプション (`JwwParseOptions`、省略可)。
+    #[wasm_bindgen(unchecked_return_type = "TypedParseResult<Document>")]
+    pub fn finish(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "JwwParseOptions | null | undefined")] parse_options: JsValue,
+    ) -> ParseResult {
+        let data = std::mem::take(&mut self.buffer);
+        jww_parse(&data, parse_options)
+    }
+}
+
+/// エンティティを読み取らずに図面情報を返す
+///
+/// バージョン・メモ・用紙サイズ・レイヤ情報 (名前を含む)・エンティティ件数のみを読むため、
+/// 大きなファイルでもファイル選択時の表示に使える。
+///
+/// # 引数
+/// * `data` - JWWファイルのバイナリデータ (Uint8Array)
+/// * `parse_options` - パースオプション (`JwwParseOptions`、省略可)
+///
+/// # 戻り値
+/// ParseResult - 成功時はdataフィールドに `DocumentInfo` のJSON、失敗時はerrorフィールドにエラー情報
+#[wasm_bindgen(unchecked_return_type = "TypedParseResult<DocumentInfo>")]
+pub fn jww_get_info(
+    data: &[u8],
+    #[wasm_bindgen(unchecked_param_type = "JwwParseOptions | null | undefined")] parse_options: JsValue,
+) -> ParseResult {
+    match probe(data, parse_options) {
+        Ok(info) => ParseResult::from_serialize(&info),
+        Err(e) => ParseResult::failure(e),
+    }
+}
+
+/// レイヤ一覧の1要素
+#[derive(Debug, Serialize)]
+struct LayerEntry {
+    /// レイヤグループ番号 (0-15)
+    group: u32,
+    /// レイヤ番号 (0-15)
+    layer: u32,
+    group_name: String,
+    name: String,
+    /// レイヤグループとレイヤがともに表示状態
+    visible: bool,
+    /// レイヤグループまたはレイヤが保護されている
+    protected: bool,
+    /// レイヤグループの縮尺分母
+    scale: f64,
+}
+
+/// 16×16のレイヤ表を返す
+///
+/// エンティティは読み取らない。レイヤグループ順・レイヤ順に並べた256要素の配列
+/// `[{ group, layer, group_name, name, visible, protected, scale }, ...]` を返す。
+///
+/// # 引数
+/// * `data` - JWWファイルのバイナリデータ (Uint8Array)
+/// * `parse_options` - パースオプション (`JwwParseOptions`、省略可)
+///
+/// # 戻り値
+/// ParseResult - 成功時はdataフィールドにレイヤの配列、失敗時はerrorフィールドにエラー情報
+#[wasm_bindgen]
+pub fn jww_get_layers(
+    data: &[u8],
+    #[wasm_bindgen(unchecked_param_type = "JwwParseOptions | null | undefined")] parse_options: JsValue,
+) -> ParseResult {
+    let info = match probe(data, parse_options) {
+        Ok(info) => info,
+        Err(e) => return ParseResult::failure(e),
+    };
+    let mut layers = Vec::with_capacity(256);
+    for (group, layer_group) in (0u32..).zip(&info.layer_groups) {
+        for (index, layer) in (0u32..).zip(&layer_group.layers) {
+            layers.push(LayerEntry {
+                group,
+                layer: index,
+                group_name: layer_group.name.clone(),
+                name: layer.name.clone(),
+                visible: layer_group.state != 0 && layer.state != 0,
+                protected: layer_group.protect != 0 || layer.protect != 0,
+                scale: layer_group.scale,
+            });
         }
-        Err(e) => ParseResult {
-            ok: false,
-            data: JsValue::NULL,
-            error: format!("parse error: {}", e),
-        },
     }
+    ParseResult::from_serialize(&layers)
+}
+
+/// 図面範囲
+#[derive(Debug, Serialize)]
+struct Bounds {
+    /// 全エンティティの外接矩形 (エンティティがない場合はnull)
+    bounds: Option<jww_core::BoundingBox>,
+    /// レイヤごとの外接矩形 (エンティティのあるレイヤのみ)
+    layers: Vec<LayerBounds>,
+}
+
+impl Bounds {
+    fn new(doc: &jww_core::Document) -> Self {
+        let layers = doc
+            .layer_bounding_boxes()
+            .into_iter()
+            .map(|((group, layer), bounds)| LayerBounds {
+                group,
+                layer,
+                bounds,
+            })
+            .collect();
+        Bounds {
+            bounds: doc.bounding_box(),
+            layers,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct LayerBounds {
+    group: u16,
+    layer: u16,
+    bounds: jww_core::BoundingBox,
+}
+
+/// 図面全体とレイヤごとの外接矩形を返す
+///
+/// ビューアが図形を受け取る前に表示範囲を決めるために使う。座標はJWWのmm。
+///
+/// # 引数
+/// * `data` - JWWファイルのバイナリデータ (Uint8Array)
+/// * `parse_options` - パースオプション (`JwwParseOptions`、省略可)
+///
+/// # 戻り値
+/// ParseResult - 成功時はdataフィールドに `{ bounds, layers: [{ group, layer, bounds }] }`、
+/// 失敗時はerrorフィールドにエラー情報
+#[wasm_bindgen]
+pub fn jww_get_bounds(
+    data: &[u8],
+    #[wasm_bindgen(unchecked_param_type = "JwwParseOptions | null | undefined")] parse_options: JsValue,
+) -> ParseResult {
+    let doc = match parse(data, parse_options) {
+        Ok(doc) => doc,
+        Err(e) => return ParseResult::failure(e),
+    };
+    ParseResult::from_serialize(&Bounds::new(&doc))
+}
+
+/// 文字列の抽出結果の1要素
+#[derive(Debug, Serialize)]
+struct TextEntry {
+    /// エンティティの番号 (`Document.entities` の添字)
+    index: usize,
+    content: String,
+    /// 始点 (mm)
+    x: f64,
+    y: f64,
+    /// 文字角度 (度)
+    angle: f64,
+    /// 文字高さ (用紙上のmm)
+    height: f64,
+    layer_group: u16,
+    layer: u16,
+    layer_name: String,
+}
+
+impl TextEntry {
+    fn new(doc: &jww_core::Document, index: usize, text: &jww_core::Text) -> Self {
+        let base = &text.base;
+        let layer_name = doc
+            .layer_groups
+            .get(base.layer_group as usize)
+            .and_then(|group| group.layers.get(base.layer as usize))
+            .map(|layer| layer.name.clone())
+            .unwrap_or_default();
+        TextEntry {
+            index,
+            content: text.content.to_string(),
+            x: text.start.x,
+            y: text.start.y,
+            angle: text.angle.degrees(),
+            height: text.size_y,
+            layer_group: base.layer_group,
+            layer: base.layer,
+            layer_name,
+        }
+    }
+}
+
+/// 文字エンティティの文字列と位置・レイヤを返す
+///
+/// 図形を渡さずにJS側で注記の検索・索引付けを行うために使う。
+/// 図面直下の文字のみを対象とし、ブロック内の文字と寸法値は含まない。
+///
+/// # 引数
+/// * `data` - JWWファイルのバイナリデータ (Uint8Array)
+/// * `parse_options` - パースオプション (`JwwParseOptions`、省略可)
+///
+/// # 戻り値
+/// ParseResult - 成功時はdataフィールドに
+/// `[{ index, content, x, y, angle, height, layer_group, layer, layer_name }]`、
+/// 失敗時はerrorフィールドにエラー情報
+#[wasm_bindgen]
+pub fn jww_extract_text(
+    data: &[u8],
+    #[wasm_bindgen(unchecked_param_type = "JwwParseOptions | null | undefined")] parse_options: JsValue,
+) -> ParseResult {
+    let doc = match parse(data, parse_options) {
+        Ok(doc) => doc,
+        Err(e) => return ParseResult::failure(e),
+    };
+    let texts: Vec<TextEntry> = doc
+        .entities
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entity)| match entity {
+            jww_core::Entity::Text(text) => Some(TextEntry::new(&doc, index, text)),
+            _ => None,
+        })
+        .collect();
+    ParseResult::from_serialize(&texts)
+}
+
+/// `jww_search_text` のオプション
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+struct SearchOptions {
+    /// 検索文字列を正規表現として扱う
+    regex: bool,
+    /// 英字の大文字・小文字を区別しない
+    ignore_case: bool,
+    /// 全角・半角や波ダッシュの違いを区別しない
+    normalize: bool,
+}
+
+/// 文字検索の一致結果の1要素
+#[derive(Debug, Serialize)]
+struct SearchMatch {
+    #[serde(flatten)]
+    text: TextEntry,
+    /// 一致箇所 (`content` 内のUTF-16の位置、`[start, end]` の配列)
+    ranges: Vec<[usize; 2]>,
+}
+
+/// 文字と寸法値を検索し、一致した文字の位置を返す
+///
+/// 図面内の文字検索と、検索結果の位置への移動に使う。
+/// 図面直下の文字と寸法値を対象とし、ブロック内の文字は含まない。
+/// 一致箇所はJSの文字列の添字 (UTF-16) で返すため、そのまま `slice` に使える。
+///
+/// # 引数
+/// * `data` - JWWファイルのバイナリデータ (Uint8Array)
+/// * `query` - 検索文字列 (正規表現の場合はRustの `regex` の構文)
+/// * `options` - `{ regex, ignore_case, normalize }` (いずれも省略時はfalse)
+/// * `parse_options` - パースオプション (`JwwParseOptions`、省略可)
+///
+/// # 戻り値
+/// ParseResult - 成功時はdataフィールドに `jww_extract_text` の各項目と `ranges` の配列、
+/// 失敗時はerrorフィールドにエラー情報 (正規表現が不正な場合は `invalid_pattern`)
+#[wasm_bindgen]
+pub fn jww_search_text(
+    data: &[u8],
+    query: &str,
+    options: JsValue,
+    #[wasm_bindgen(unchecked_param_type = "JwwParseOptions | null | undefined")] parse_options: JsValue,
+) -> ParseResult {
+    let options: SearchOptions = match options_from_js(options) {
+        Ok(options) => options,
+        Err(e) => return ParseResult::failure(e),
+    };
+    let query = if options.normalize {
+        jww_core::normalize::text(query)
+    } else {
+        query.into()
+    };
+    let pattern = if options.regex {
+        query.to_string()
+    } else {
+        regex_lite::escape(&query)
+    };
+    let regex = match regex_lite::RegexBuilder::new(&pattern)
+        .case_insensitive(options.ignore_case)
+        .build()
+    {
+        Ok(regex) => regex,
+        Err(e) => {
+            return ParseResult::failure(ErrorInfo::new(
+                "invalid_pattern",
+                format!("invalid pattern: {}", e),
+            ))
+        }
+    };
+    let doc = match parse(data, parse_options) {
+        Ok(doc) => doc,
+        Err(e) => return ParseResult::failure(e),
+    };
+    let find = |content: &str| {
+        regex
+            .find_iter(content)
+            .filter(|m| !m.is_empty())
+            .map(|m| m.range())
+            .collect()
+    };
+    let matches = if options.normalize {
+        doc.search_text_normalized(find)
+    } else {
+        doc.search_text(find)
+    };
+    let matches: Vec<SearchMatch> = matches
+        .into_iter()
+        .map(|m| {
+            let content = &m.text.content;
+            let utf16 = |byte: usize| content[..byte].encode_utf16().count();
+            SearchMatch {
+                ranges: m
+                    .ranges
+                    .iter()
+                    .map(|range| [utf16(range.start), utf16(range.end)])
+                    .collect(),
+                text: TextEntry::new(&doc, m.entity_index, m.text),
+            }
+        })
+        .collect();
+    ParseResult::from_serialize(&matches)
+}
+
+/// ファイルを検査し、問題点の一覧を返す
+///
+/// パースに失敗した場合は重要度 `error` の1件を返し、成功した場合は
+/// `Document::validate()` の結果を返す。問題がなければ空の配列になる。
+///
+/// # 引数
+/// * `data` - JWWファイルのバイナリデータ (Uint8Array)
+/// * `parse_options` - パースオプション (`JwwParseOptions`、省略可)
+///
+/// # 戻り値
+/// ParseResult - dataフィールドに `[{ severity, code, message, entity_index }]`
+/// (`severity` は `info`・`warning`・`error` のいずれか)
+#[wasm_bindgen]
+pub fn jww_validate(
+    data: &[u8],
+    #[wasm_bindgen(unchecked_param_type = "JwwParseOptions | null | undefined")] parse_options: JsValue,
+) -> ParseResult {
+    let parse_options: jww_core::ParseOptions = match options_from_js(parse_options) {
+        Ok(options) => options,
+        Err(e) => return ParseResult::failure(e),
+    };
+    let findings = match jww_core::parse_with_options(data, &parse_options) {
+        Ok(doc) => doc.validate(),
+        Err(e) => vec![jww_core::Finding {
+            severity: jww_core::Severity::Error,
+            code: e.code(),
+            message: format!("parse error: {}", e),
+            entity_index: e.entity_index(),
+        }],
+    };
+    ParseResult::from_serialize(&findings)
 }
 
 /// JWWファイルをパースし、DXF JSONを返す
 ///
+/// `options.version` は出力するDXFのバージョン (`header.version`) に反映する。
+/// `options.encoding` はJSONには影響しない。
+///
 /// # 引数
 /// * `data` - JWWファイルのバイナリデータ (Uint8Array)
+/// * `options` - 変換オプション (`ConvertOptions` と同じ構造のオブジェクト、省略可)
+/// * `parse_options` - パースオプション (`JwwParseOptions`、省略可)
 ///
 /// # 戻り値
-/// ParseResult - 成功時はdataフィールドにDXF JSON、失敗時はerrorフィールドにエラーメッセージ
+/// ParseResult - 成功時はdataフィールドにDXF JSON、失敗時はerrorフィールドにエラー情報
+#[cfg(all(feature = "dxf", feature = "json"))]
 #[wasm_bindgen]
-pub fn jww_to_dxf(data: &[u8]) -> ParseResult {
-    match jww_core::parse(data) {
+pub fn jww_to_dxf(
+    data: &[u8],
+    options: JsValue,
+    #[wasm_bindgen(unchecked_param_type = "JwwParseOptions | null | undefined")] parse_options: JsValue,
+) -> ParseResult {
+    let options: jww_dxf::ConvertOptions = match options_from_js(options) {
+        Ok(options) => options,
+        Err(e) => return ParseResult::failure(e),
+    };
+    match parse(data, parse_options) {
         Ok(jww_doc) => {
-            let dxf_doc = jww_dxf::convert_document(&jww_doc);
-            match serde_wasm_bindgen::to_value(&dxf_doc) {
-                Ok(json) => ParseResult {
-                    ok: true,
-                    data: json,
-                    error: String::new(),
-                },
-                Err(e) => ParseResult {
-                    ok: false,
-                    data: JsValue::NULL,
-                    error: format!("JSON marshal error: {}", e),
-                },
-            }
+            let dxf_doc = jww_dxf::convert_document_with_options(&jww_doc, &options);
+            ParseResult::from_serialize(&dxf_doc)
         }
-        Err(e) => ParseResult {
-            ok: false,
-            data: JsValue::NULL,
-            error: format!("parse error: {}", e),
-        },
+        Err(e) => ParseResult::failure(e),
     }
 }
 
 /// JWWファイルをパースし、DXF文字列を返す
 ///
+/// JS文字列にはShift-JISのテキストを入れられないため、`options.encoding` に `ShiftJis` を
+/// 指定した場合は `invalid_options` のエラーを返す。Shift-JIS (CP932) のDXFは
+/// `jww_to_dxf_bytes` でバイト列として受け取る。
+///
 /// # 引数
 /// * `data` - JWWファイルのバイナリデータ (Uint8Array)
+/// * `options` - 変換オプション (`ConvertOptions` と同じ構造のオブジェクト、省略可)
+/// * `parse_options` - パースオプション (`JwwParseOptions`、省略可)
 ///
 /// # 戻り値
-/// ParseResult - 成功時はdataフィールドにDXF文字列、失敗時はerrorフィールドにエラーメッセージ
+/// ParseResult - 成功時はdataフィールドにDXF文字列、失敗時はerrorフィールドにエラー情報
+#[cfg(feature = "dxf")]
 #[wasm_bindgen]
-pub fn jww_to_dxf_string(data: &[u8]) -> ParseResult {
-    match jww_core::parse(data) {
+pub fn jww_to_dxf_string(
+    data: &[u8],
+    options: JsValue,
+    #[wasm_bindgen(unchecked_param_type = "JwwParseOptions | null | undefined")] parse_options: JsValue,
+) -> ParseResult {
+    let options: jww_dxf::ConvertOptions = match options_from_js(options) {
+        Ok(options) => options,
+        Err(e) => return ParseResult::failure(e),
+    };
+    if let Err(e) = check_string_encoding(&options) {
+        return ParseResult::failure(e);
+    }
+    match parse(data, parse_options) {
         Ok(jww_doc) => {
-            let dxf_doc = jww_dxf::convert_document(&jww_doc);
-            let dxf_string = jww_dxf::to_string(&dxf_doc);
-            ParseResult {
-                ok: true,
-                data: JsValue::from_str(&dxf_string),
-                error: String::new(),
-            }
+            let dxf_doc = jww_dxf::convert_document_with_options(&jww_doc, &options);
+            ParseResult::success(JsValue::from_str(&jww_dxf::to_string(&dxf_doc)))
+        }
+        Err(e) => ParseResult::failure(e),
+    }
+}
+
+/// DXFを文字列で返せる文字コードかどうかを確認する
+///
+/// JS文字列にはShift-JISのテキストを入れられないため、`options.encoding` が `ShiftJis` の場合は
+/// `invalid_options` のエラーとする (Shift-JISのDXFは `jww_to_dxf_bytes` で出力する)。
+#[cfg(feature = "dxf")]
+fn check_string_encoding(options: &jww_dxf::ConvertOptions) -> Result<(), ErrorInfo> {
+    if options.encoding == jww_dxf::DxfEncoding::Utf8 {
+        return Ok(());
+    }
+    Err(ErrorInfo::new(
+        "invalid_options",
+        format!(
+            "encoding {:?} cannot be returned as a string; use jww_to_dxf_bytes",
+            options.encoding
+        ),
+    ))
+}
+
+/// DXF文字列に変換する ([`check_string_encoding`] を満たさない場合はエラー)
+#[cfg(feature = "dxf")]
+fn dxf_string(
+    jww_doc: &jww_core::Document,
+    options: &jww_dxf::ConvertOptions,
+) -> Result<String, ErrorInfo> {
+    check_string_encoding(options)?;
+    let dxf_doc = jww_dxf::convert_document_with_options(jww_doc, options);
+    Ok(jww_dxf::to_string(&dxf_doc))
+}
+
+/// `jww_to_dxf_layers` で選択するレイヤ
+#[cfg(feature = "dxf")]
+#[derive(Debug, serde::Deserialize)]
+struct LayerSelection {
+    /// レイヤグループ番号 (0-15)
+    group: u16,
+    /// レイヤ番号 (0-15、省略時はグループ内の全レイヤ)
+    layer: Option<u16>,
+}
+
+/// 選択したレイヤのみをDXF文字列に変換する
+///
+/// Webアプリのレイヤ別エクスポートで、DXF全体を受け取ってからJS側で
+/// 絞り込まずに済むようにする。ブロック定義内のエンティティにも同じ条件を適用する。
+///
+/// # 引数
+/// * `data` - JWWファイルのバイナリデータ (Uint8Array)
+/// * `layers` - 出力するレイヤの配列 (`[{ group, layer }]`、`layer` を省略するとグループ全体)
+/// * `parse_options` - パースオプション (`JwwParseOptions`、省略可)
+///
+/// # 戻り値
+/// ParseResult - 成功時はdataフィールドにDXF文字列、失敗時はerrorフィールドにエラー情報
+#[cfg(feature = "dxf")]
+#[wasm_bindgen]
+pub fn jww_to_dxf_layers(
+    data: &[u8],
+    layers: JsValue,
+    #[wasm_bindgen(unchecked_param_type = "JwwParseOptions | null | undefined")] parse_options: JsValue,
+) -> ParseResult {
+    let selection: Vec<LayerSelection> = match options_from_js(layers) {
+        Ok(selection) => selection,
+        Err(e) => return ParseResult::failure(e),
+    };
+    if selection.is_empty() {
+        return ParseResult::failure(ErrorInfo::new("invalid_options", "no layers selected"));
+    }
+    let mut options = jww_dxf::ConvertOptions::default();
+    for item in &selection {
+        let out_of_range = item.group > 15 || item.layer.is_some_and(|layer| layer > 15);
+        if out_of_range {
+            return ParseResult::failure(ErrorInfo::new(
+                "invalid_options",
+                format!("layer out of range: {:?}", item),
+            ));
+        }
+        match item.layer {
+            Some(layer) => options.filter.layers.push((item.group, layer)),
+            None => options
+                .filter
+                .layers
+                .extend((0..16).map(|layer| (item.group, layer))),
+        }
+    }
+    match parse(data, parse_options) {
+        Ok(jww_doc) => {
+            let dxf_doc = jww_dxf::convert_document_with_options(&jww_doc, &options);
+            ParseResult::success(JsValue::from_str(&jww_dxf::to_string(&dxf_doc)))
+        }
+        Err(e) => ParseResult::failure(e),
+    }
+}
+
+/// `jww_to_dxf_bytes` のオプション (変換オプションに出力形式を加えたもの)
+#[cfg(feature = "dxf")]
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+struct DxfBytesOptions {
+    #[serde(flatten)]
+    convert: jww_dxf::ConvertOptions,
+    /// 出力形式 (`ShiftJis`・`Utf8`・`Binary`、既定値は `ShiftJis`)
+    format: jww_dxf::DxfFormat,
+}
+
+/// JWWファイルをパースし、DXFのバイト列を返す
+///
+/// 大きな図面でも巨大なJS文字列を作らずに、Shift-JIS (CP932) のテキストDXFまたは
+/// バイナリDXFをそのままファイルに保存できる。
+///
+/// # 引数
+/// * `data` - JWWファイルのバイナリデータ (Uint8Array)
+/// * `options` - 変換オプション (`ConvertOptions` の項目と出力形式 `format`、省略可)
+/// * `parse_options` - パースオプション (`JwwParseOptions`、省略可)
+///
+/// # 戻り値
+/// ParseResult - 成功時はdataフィールドにUint8Array、失敗時はerrorフィールドにエラー情報
+#[cfg(feature = "dxf")]
+#[wasm_bindgen]
+pub fn jww_to_dxf_bytes(
+    data: &[u8],
+    options: JsValue,
+    #[wasm_bindgen(unchecked_param_type = "JwwParseOptions | null | undefined")] parse_options: JsValue,
+) -> ParseResult {
+    let options: DxfBytesOptions = match options_from_js(options) {
+        Ok(options) => options,
+        Err(e) => return ParseResult::failure(e),
+    };
+    match parse(data, parse_options) {
+        Ok(jww_doc) => {
+            let dxf_doc = jww_dxf::convert_document_with_options(&jww_doc, &options.convert);
+            let bytes = jww_dxf::to_bytes(&dxf_doc, options.format);
+            ParseResult::success(js_sys::Uint8Array::from(bytes.as_slice()).into())
+        }
+        Err(e) => ParseResult::failure(e),
+    }
+}
+
+/// `jww_to_dxf_chunks` のオプション
+#[cfg(feature = "dxf")]
+#[derive(Debug, serde::Deserialize)]
+#[serde(default)]
+struct DxfChunkOptions {
+    #[serde(flatten)]
+    convert: jww_dxf::ConvertOptions,
+    /// 1回に渡す文字列のおおよそのバイト数 (UTF-8、既定値は1MiB)
+    chunk_size: usize,
+}
+
+#[cfg(feature = "dxf")]
+impl Default for DxfChunkOptions {
+    fn default() -> Self {
+        Self {
+            convert: jww_dxf::ConvertOptions::default(),
+            chunk_size: 1 << 20,
+        }
+    }
+}
+
+/// JWWファイルをパースし、DXF文字列を分割してコールバックに渡す
+///
+/// 数百MBのDXFを1つのJS文字列にするとタブが落ちることがあるため、DXF全体を文字列にせず、
+/// 書き出しながら行の区切りで `chunk_size` 程度に分割して順に `callback(chunk)` を呼ぶ。
+/// `WritableStream` に書き出す場合は `chunk => writer.write(chunk)` を渡す
+/// (戻り値のPromiseは待たない)。
+///
+/// # 引数
+/// * `data` - JWWファイルのバイナリデータ (Uint8Array)
+/// * `options` - 変換オプション (`ConvertOptions` の項目と `chunk_size`、省略可)
+/// * `callback` - 分割した文字列を受け取る関数
+/// * `parse_options` - パースオプション (`JwwParseOptions`、省略可)
+///
+/// # 戻り値
+/// ParseResult - 成功時はdataフィールドにコールバックを呼んだ回数、失敗時はerrorフィールドにエラー情報
+/// (コールバックが例外を投げた場合はエラーコード `callback_error` で中断する)
+#[cfg(feature = "dxf")]
+#[wasm_bindgen]
+pub fn jww_to_dxf_chunks(
+    data: &[u8],
+    options: JsValue,
+    callback: &js_sys::Function,
+    #[wasm_bindgen(unchecked_param_type = "JwwParseOptions | null | undefined")] parse_options: JsValue,
+) -> ParseResult {
+    let options: DxfChunkOptions = match options_from_js(options) {
+        Ok(options) => options,
+        Err(e) => return ParseResult::failure(e),
+    };
+    let jww_doc = match parse(data, parse_options) {
+        Ok(jww_doc) => jww_doc,
+        Err(e) => return ParseResult::failure(e),
+    };
+    let dxf_doc = jww_dxf::convert_document_with_options(&jww_doc, &options.convert);
+    drop(jww_doc);
+
+    let mut sink = ChunkSink::new(options.chunk_size, |chunk: &str| {
+        callback
+            .call1(&JsValue::UNDEFINED, &JsValue::from_str(chunk))
+            .map(|_| ())
+            .map_err(|e| e.as_string().unwrap_or_else(|| format!("{:?}", e)))
+    });
+    jww_dxf::write_to(&dxf_doc, &mut sink);
+    match sink.finish() {
+        Ok(count) => ParseResult::success(JsValue::from(count)),
+        Err(message) => ParseResult::failure(ErrorInfo::new(
+            "callback_error",
+            format!("callback failed: {}", message),
+        )),
+    }
+}
+
+/// 書き込まれた文字列を `size` バイト以下の行単位にまとめて `callback` に渡す出力先
+///
+/// `callback` が失敗した後の書き込みは捨てる (DXFの書き出しは中断できないため)。
+#[cfg(feature = "dxf")]
+struct ChunkSink<F> {
+    callback: F,
+    size: usize,
+    buffer: String,
+    count: u32,
+    error: Option<String>,
+}
+
+#[cfg(feature = "dxf")]
+impl<F: FnMut(&str) -> Result<(), String>> ChunkSink<F> {
+    fn new(size: usize, callback: F) -> Self {
+        let size = size.max(1);
+        Self {
+            callback,
+            size,
+            buffer: String::with_capacity(size),
+            count: 0,
+            error: None,
+        }
+    }
+
+    /// `buffer` の先頭から `end` バイトを渡す
+    fn emit(&mut self, end: usize) {
+        if let Err(e) = (self.callback)(&self.buffer[..end]) {
+            self.error = Some(e);
+            self.buffer = String::new();
+            return;
+        }
+        self.count += 1;
+        self.buffer.drain(..end);
+    }
+
+    /// 残りを渡し、`callback` を呼んだ回数を返す
+    fn finish(mut self) -> Result<u32, String> {
+        while self.error.is_none() && !self.buffer.is_empty() {
+            let end = chunk_end(&self.buffer, self.size);
+            self.emit(end);
         }
-        Err(e) => ParseResult {
-            ok: false,
-            data: JsValue::NULL,
-            error: format!("parse error: {}", e),
-        },
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.count),
+        }
+    }
+}
+
+#[cfg(feature = "dxf")]
+impl<F: FnMut(&str) -> Result<(), String>> std::fmt::Write for ChunkSink<F> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        if self.error.is_some() {
+            return Ok(());
+        }
+        self.buffer.push_str(s);
+        while self.error.is_none() && self.buffer.len() > self.size {
+            let end = chunk_end(&self.buffer, self.size);
+            self.emit(end);
+        }
+        Ok(())
+    }
+}
+
+/// 文字列の先頭から `size` バイト以下で、最後の改行の直後までの長さを返す
+///
+/// 先頭 `size` バイトに改行がない場合は文字の区切りで分割する
+/// (`size` が1文字より短い場合のみ `size` を超える)。
+#[cfg(feature = "dxf")]
+fn chunk_end(text: &str, size: usize) -> usize {
+    if text.len() <= size {
+        return text.len();
     }
+    match text.as_bytes()[..size].iter().rposition(|&b| b == b'\n') {
+        Some(newline) => newline + 1,
+        None => (1..=size)
+            .rev()
+            .find(|&i| text.is_char_boundary(i))
+            .unwrap_or_else(|| (size..text.len()).find(|&i| text.is_char_boundary(i)).unwrap_or(text.len())),
+    }
+}
+
+/// JWWファイルをパースし、SVG文字列を返す
+///
+/// # 引数
+/// * `data` - JWWファイルのバイナリデータ (Uint8Array)
+/// * `options` - SVG出力オプション (`SvgOptions` と同じ構造のオブジェクト、省略可)
+/// * `parse_options` - パースオプション (`JwwParseOptions`、省略可)
+///
+/// # 戻り値
+/// ParseResult - 成功時はdataフィールドにSVG文字列、失敗時はerrorフィールドにエラー情報
+#[cfg(feature = "svg")]
+#[wasm_bindgen]
+pub fn jww_to_svg(
+    data: &[u8],
+    options: JsValue,
+    #[wasm_bindgen(unchecked_param_type = "JwwParseOptions | null | undefined")] parse_options: JsValue,
+) -> ParseResult {
+    let options: jww_export::SvgOptions = match options_from_js(options) {
+        Ok(options) => options,
+        Err(e) => return ParseResult::failure(e),
+    };
+    match parse(data, parse_options) {
+        Ok(jww_doc) => {
+            let svg = jww_export::to_svg_with_options(&jww_doc, &options);
+            ParseResult::success(JsValue::from_str(&svg))
+        }
+        Err(e) => ParseResult::failure(e),
+    }
+}
+
+/// JWWファイルをパースし、PDFのバイナリを返す
+///
+/// ブラウザ内だけで「PDFとしてダウンロード」を実現するために使う。
+///
+/// # 引数
+/// * `data` - JWWファイルのバイナリデータ (Uint8Array)
+/// * `options` - PDF出力オプション (`PdfOptions` と同じ構造のオブジェクト、省略可)
+/// * `parse_options` - パースオプション (`JwwParseOptions`、省略可)
+///
+/// # 戻り値
+/// ParseResult - 成功時はdataフィールドにUint8Array、失敗時はerrorフィールドにエラー情報
+#[cfg(feature = "pdf")]
+#[wasm_bindgen]
+pub fn jww_to_pdf(
+    data: &[u8],
+    options: JsValue,
+    #[wasm_bindgen(unchecked_param_type = "JwwParseOptions | null | undefined")] parse_options: JsValue,
+) -> ParseResult {
+    let options: jww_export::PdfOptions = match options_from_js(options) {
+        Ok(options) => options,
+        Err(e) => return ParseResult::failure(e),
+    };
+    match parse(data, parse_options) {
+        Ok(jww_doc) => {
+            let pdf = jww_export::to_pdf(&jww_doc, &options);
+            ParseResult::success(js_sys::Uint8Array::from(pdf.as_slice()).into())
+        }
+        Err(e) => ParseResult::failure(e),
+    }
+}
+
+/// JSから渡されたオプションを読み込む (undefined/nullの場合は既定値)
+fn options_from_js<T: serde::de::DeserializeOwned + Default>(
+    options: JsValue,
+) -> Result<T, ErrorInfo> {
+    if options.is_undefined() || options.is_null() {
+        return Ok(T::default());
+    }
+    serde_wasm_bindgen::from_value(options)
+        .map_err(|e| ErrorInfo::new("invalid_options", format!("invalid options: {}", e)))
+}
+
+/// メモリ使用量 ([`jww_memory_usage`] の結果)
+#[derive(Debug, Serialize)]
+struct MemoryUsage {
+    /// WASMの線形メモリのバイト数 (確保済みのページ全体、解放しても縮小しない)
+    linear_memory_bytes: usize,
+    /// 解放されていない `JwwDocument` の数
+    retained_documents: usize,
+    /// 解放されていない `JwwDocument` のおおよその合計バイト数
+    retained_document_bytes: usize,
+}
+
+/// メモリ使用量を返す
+///
+/// 長時間動作するアプリで、`JwwDocument` を `free()` するかどうかの判断に使う。
+///
+/// # 戻り値
+/// `{ linear_memory_bytes, retained_documents, retained_document_bytes }`
+#[wasm_bindgen]
+pub fn jww_memory_usage() -> Result<JsValue, JsValue> {
+    #[cfg(target_arch = "wasm32")]
+    let linear_memory_bytes = core::arch::wasm32::memory_size(0) * 65536;
+    #[cfg(not(target_arch = "wasm32"))]
+    let linear_memory_bytes = 0;
+    let (retained_documents, retained_document_bytes) = document::retained();
+    let usage = MemoryUsage {
+        linear_memory_bytes,
+        retained_documents,
+        retained_document_bytes,
+    };
+    Ok(to_js(&usage)?)
 }
 
 /// WASMモジュールのバージョンを返す
@@ -128,15 +1151,124 @@ pub fn jww_get_version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
 }
 
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console, js_name = debug)]
+    fn console_debug(message: &str);
+
+    #[wasm_bindgen(js_namespace = console, js_name = error)]
+    fn console_error(message: &str);
+}
+
+/// ログをブラウザのコンソールに出力するロガー
+struct ConsoleLogger;
+
+impl log::Log for ConsoleLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            console_debug(&format!("[{}] {}", record.target(), record.args()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: ConsoleLogger = ConsoleLogger;
+
 /// デバッグモードを設定
+///
+/// 有効にすると、パーサー・変換処理のデバッグログを `console.debug` に出力し、
+/// パニック時のメッセージを `console.error` に出力するフックを登録する。
 #[wasm_bindgen]
-pub fn jww_set_debug(_enabled: bool) {
-    // TODO: デバッグモードの実装
+pub fn jww_set_debug(enabled: bool) {
+    if enabled {
+        // 2回目以降はロガー登録済みのためエラーになるが無視してよい
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(log::LevelFilter::Debug);
+        std::panic::set_hook(Box::new(|info| console_error(&info.to_string())));
+    } else {
+        log::set_max_level(log::LevelFilter::Off);
+        let _ = std::panic::take_hook();
+    }
 }
 
-/// コミットハッシュを返す
+/// ビルドしたコミットのハッシュを返す (gitの情報がない環境でビルドした場合は `unknown`)
 #[wasm_bindgen]
 pub fn jww_commit_hash() -> String {
-    // ビルド時に設定される
-    "unknown".to_string()
+    env!("JWW_COMMIT_HASH").to_string()
+}
+
+/// ビルド情報 ([`jww_build_info`] の結果)
+#[derive(Debug, Serialize)]
+struct BuildInfo {
+    /// jww-wasmのバージョン
+    version: &'static str,
+    commit_hash: &'static str,
+    /// ビルド日時 (ISO 8601形式のUTC)
+    build_timestamp: &'static str,
+    core_version: &'static str,
+    /// jww-dxfのバージョン (`dxf` featureが無効の場合はnull)
+    dxf_version: Option<&'static str>,
+    /// 有効なfeature
+    features: Vec<&'static str>,
+}
+
+/// ビルド情報を返す
+///
+/// 不具合報告の際に、利用しているモジュールのビルドを特定するために使う。
+///
+/// # 戻り値
+/// `{ version, commit_hash, build_timestamp, core_version, dxf_version, features }`
+#[wasm_bindgen]
+pub fn jww_build_info() -> Result<JsValue, JsValue> {
+    let mut features = Vec::new();
+    if cfg!(feature = "dxf") {
+        features.push("dxf");
+    }
+    if cfg!(feature = "svg") {
+        features.push("svg");
+    }
+    if cfg!(feature = "pdf") {
+        features.push("pdf");
+    }
+    if cfg!(feature = "json") {
+        features.push("json");
+    }
+    let info = BuildInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        commit_hash: env!("JWW_COMMIT_HASH"),
+        build_timestamp: env!("JWW_BUILD_TIMESTAMP"),
+        core_version: jww_core::VERSION,
+        #[cfg(feature = "dxf")]
+        dxf_version: Some(jww_dxf::VERSION),
+        #[cfg(not(feature = "dxf"))]
+        dxf_version: None,
+        features,
+    };
+    Ok(to_js(&info)?)
+}
+
+#[cfg(all(test, feature = "dxf"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dxf_string_encoding() {
+        let doc = jww_core::Document::default();
+        let dxf = dxf_string(&doc, &jww_dxf::ConvertOptions::default()).unwrap();
+        assert!(dxf.ends_with("EOF\n"));
+
+        // Shift-JISは文字列で返せないためエラーとする
+        let options = jww_dxf::ConvertOptions {
+            encoding: jww_dxf::DxfEncoding::ShiftJis,
+            ..Default::default()
+        };
+        let error = dxf_string(&doc, &options).unwrap_err();
+        assert_eq!(error.code, "invalid_options");
+        assert!(error.message.contains("jww_to_dxf_bytes"));
+    }
 }
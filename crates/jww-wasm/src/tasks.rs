@@ -0,0 +1,172 @@
+//! Web Worker向けの非同期API
+//!
+//! 一定件数ごとにイベントループへ制御を返しながら処理するため、長い変換中でも
+//! Workerが中断メッセージを受け取れる。
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+#[cfg(feature = "dxf")]
+use crate::check_string_encoding;
+#[cfg(any(feature = "json", feature = "dxf"))]
+use crate::options_from_js;
+use crate::{ErrorInfo, ParseResult};
+
+/// 制御を返すまでに読み取る・変換するエンティティ数
+const ENTITIES_PER_STEP: usize = 2000;
+
+/// 処理の中断に使うハンドル
+///
+/// `AbortSignal` と同じく `aborted` プロパティを持つ。非同期APIには
+/// このハンドルと `AbortSignal` のどちらも渡せる。
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct CancelHandle {
+    aborted: Rc<Cell<bool>>,
+}
+
+#[wasm_bindgen]
+impl CancelHandle {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> CancelHandle {
+        CancelHandle::default()
+    }
+
+    /// 処理の中断を要求する
+    pub fn cancel(&self) {
+        self.aborted.set(true);
+    }
+
+    /// 中断が要求されたかどうか
+    #[wasm_bindgen(getter)]
+    pub fn aborted(&self) -> bool {
+        self.aborted.get()
+    }
+}
+
+/// `signal.aborted` が真の場合は中断エラーを返す
+fn check_aborted(signal: &JsValue) -> Result<(), ErrorInfo> {
+    if signal.is_undefined() || signal.is_null() {
+        return Ok(());
+    }
+    let aborted = js_sys::Reflect::get(signal, &JsValue::from_str("aborted"))
+        .map(|value| value.is_truthy())
+        .unwrap_or(false);
+    if aborted {
+        Err(ErrorInfo::new("aborted", "operation was aborted"))
+    } else {
+        Ok(())
+    }
+}
+
+/// `setTimeout(0)` でイベントループに制御を返す
+///
+/// Promiseの解決 (マイクロタスク) だけではWorkerのメッセージ処理が進まないため、
+/// タイマーを使用する。`setTimeout` がない環境ではすぐに再開する。
+async fn yield_now() {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let set_timeout = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("setTimeout"))
+            .ok()
+            .and_then(|f| f.dyn_into::<js_sys::Function>().ok());
+        match set_timeout {
+            Some(set_timeout) => {
+                let _ = set_timeout.call2(&JsValue::UNDEFINED, &resolve, &JsValue::from(0));
+            }
+            None => {
+                let _ = resolve.call0(&JsValue::UNDEFINED);
+            }
+        }
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+/// エンティティを少しずつ読み取り、読み取りの合間に制御を返す
+async fn parse_cooperatively<'a>(
+    data: &'a [u8],
+    options: &jww_core::ParseOptions,
+    signal: &JsValue,
+) -> Result<jww_core::Document<'a>, ErrorInfo> {
+    let mut parser = jww_core::IncrementalParser::with_options(data, options)?;
+    loop {
+        check_aborted(signal)?;
+        if parser.step(ENTITIES_PER_STEP)? {
+            break;
+        }
+        yield_now().await;
+    }
+    Ok(parser.finish()?)
+}
+
+/// `jww_parse` の非同期版
+///
+/// # 引数
+/// * `data` - JWWファイルのバイナリデータ (Uint8Array)
+/// * `signal` - `aborted` プロパティを持つオブジェクト (`CancelHandle`・`AbortSignal`、省略可)
+/// * `parse_options` - パースオプション (`JwwParseOptions`、省略可)
+///
+/// # 戻り値
+/// ParseResultに解決されるPromise (中断時のエラーコードは `aborted`)
+#[cfg(feature = "json")]
+#[wasm_bindgen]
+pub async fn jww_parse_async(
+    data: Vec<u8>,
+    signal: JsValue,
+    #[wasm_bindgen(unchecked_param_type = "JwwParseOptions | null | undefined")] parse_options: JsValue,
+) -> ParseResult {
+    let result = async {
+        let parse_options: jww_core::ParseOptions = options_from_js(parse_options)?;
+        parse_cooperatively(&data, &parse_options, &signal).await
+    }
+    .await;
+    match result {
+        Ok(doc) => ParseResult::from_serialize(&doc),
+        Err(e) => ParseResult::failure(e),
+    }
+}
+
+/// `jww_to_dxf_string` の非同期版
+///
+/// `jww_to_dxf_string` と同じく、`options.encoding` に `ShiftJis` を指定した場合は
+/// `invalid_options` のエラーを返す。
+///
+/// # 引数
+/// * `data` - JWWファイルのバイナリデータ (Uint8Array)
+/// * `options` - 変換オプション (`ConvertOptions` と同じ構造のオブジェクト、省略可)
+/// * `signal` - `aborted` プロパティを持つオブジェクト (`CancelHandle`・`AbortSignal`、省略可)
+/// * `parse_options` - パースオプション (`JwwParseOptions`、省略可)
+///
+/// # 戻り値
+/// ParseResultに解決されるPromise (中断時のエラーコードは `aborted`)
+#[cfg(feature = "dxf")]
+#[wasm_bindgen]
+pub async fn jww_to_dxf_string_async(
+    data: Vec<u8>,
+    options: JsValue,
+    signal: JsValue,
+    #[wasm_bindgen(unchecked_param_type = "JwwParseOptions | null | undefined")] parse_options: JsValue,
+) -> ParseResult {
+    let result = async {
+        let options: jww_dxf::ConvertOptions = options_from_js(options)?;
+        check_string_encoding(&options)?;
+        let parse_options: jww_core::ParseOptions = options_from_js(parse_options)?;
+        let doc = parse_cooperatively(&data, &parse_options, &signal).await?;
+        let mut converter = jww_dxf::IncrementalConverter::new(&doc, &options);
+        loop {
+            yield_now().await;
+            check_aborted(&signal)?;
+            if converter.step(ENTITIES_PER_STEP) {
+                break;
+            }
+        }
+        let (dxf_doc, _) = converter.finish();
+        Ok::<_, ErrorInfo>(jww_dxf::to_string(&dxf_doc))
+    }
+    .await;
+    match result {
+        Ok(dxf_text) => ParseResult::success(JsValue::from_str(&dxf_text)),
+        Err(e) => ParseResult::failure(e),
+    }
+}